@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsStore;
+
+/// How often preferences are re-read; not every platform notifies on change
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Font scales outside this range are clamped; beyond it layouts break
+const FONT_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+/// Accessibility settings the frontend and terminal views adapt rendering to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AccessibilityPrefs {
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+    /// Text size relative to the default, from the OS's preferred text size
+    pub font_scale: f32,
+}
+
+impl Default for AccessibilityPrefs {
+    fn default() -> Self {
+        Self {
+            high_contrast: false,
+            reduced_motion: false,
+            font_scale: 1.0,
+        }
+    }
+}
+
+/// Preferences set in the app that win over the OS's; None follows the OS
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityOverrides {
+    pub high_contrast: Option<bool>,
+    pub reduced_motion: Option<bool>,
+    pub font_scale: Option<f32>,
+}
+
+impl AccessibilityOverrides {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.font_scale {
+            Some(scale) if !FONT_SCALE_RANGE.contains(&scale) => Err(format!(
+                "Font scale {} is out of range ({} to {})",
+                scale,
+                FONT_SCALE_RANGE.start(),
+                FONT_SCALE_RANGE.end()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&self, detected: AccessibilityPrefs) -> AccessibilityPrefs {
+        AccessibilityPrefs {
+            high_contrast: self.high_contrast.unwrap_or(detected.high_contrast),
+            reduced_motion: self.reduced_motion.unwrap_or(detected.reduced_motion),
+            font_scale: self.font_scale.unwrap_or(detected.font_scale),
+        }
+    }
+}
+
+/// Tracks the OS's preferences and emits `accessibility-changed` with the
+/// effective ones when they move, whether the OS or the settings moved them
+pub struct AccessibilityMonitor {
+    detected: Mutex<AccessibilityPrefs>,
+    current: Mutex<AccessibilityPrefs>,
+}
+
+impl AccessibilityMonitor {
+    /// Detect the current preferences
+    pub fn new() -> Self {
+        let prefs = detect();
+        log::info!("Accessibility preferences: {:?}", prefs);
+        Self {
+            detected: Mutex::new(prefs),
+            current: Mutex::new(prefs),
+        }
+    }
+
+    /// The preferences in effect
+    pub fn current(&self) -> AccessibilityPrefs {
+        *self.current.lock().unwrap()
+    }
+
+    /// Re-detect and emit `accessibility-changed` if anything changed
+    pub fn refresh(&self, app_handle: &AppHandle) {
+        let detected = detect();
+        *self.detected.lock().unwrap() = detected;
+        self.apply(app_handle, detected);
+    }
+
+    /// Settings changed: recombine them with the last detected preferences
+    pub fn reapply(&self, app_handle: &AppHandle) {
+        let detected = *self.detected.lock().unwrap();
+        self.apply(app_handle, detected);
+    }
+
+    fn apply(&self, app_handle: &AppHandle, detected: AccessibilityPrefs) {
+        let prefs = overrides(app_handle).apply(detected);
+        let changed = {
+            let mut current = self.current.lock().unwrap();
+            let changed = *current != prefs;
+            *current = prefs;
+            changed
+        };
+
+        if changed {
+            log::info!("Accessibility preferences changed: {:?}", prefs);
+            if let Err(e) = app_handle.emit("accessibility-changed", prefs) {
+                log::error!("Failed to emit accessibility-changed event: {}", e);
+            }
+        }
+    }
+
+    /// Poll for changes in the background
+    pub fn start(self: &Arc<Self>, app_handle: AppHandle) {
+        let monitor = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let monitor = monitor.clone();
+                let app_handle = app_handle.clone();
+                let _ = tauri::async_runtime::spawn_blocking(move || monitor.refresh(&app_handle)).await;
+            }
+        });
+    }
+}
+
+fn overrides(app_handle: &AppHandle) -> AccessibilityOverrides {
+    app_handle
+        .try_state::<Arc<SettingsStore>>()
+        .map(|settings| settings.get().accessibility)
+        .unwrap_or_default()
+}
+
+/// Clamp a detected scale, treating nonsense as the default
+fn font_scale(scale: f64) -> f32 {
+    let scale = scale as f32;
+    if scale.is_finite() && scale > 0.0 {
+        scale.clamp(*FONT_SCALE_RANGE.start(), *FONT_SCALE_RANGE.end())
+    } else {
+        1.0
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> AccessibilityPrefs {
+    let workspace = objc2_app_kit::NSWorkspace::sharedWorkspace();
+    AccessibilityPrefs {
+        high_contrast: workspace.accessibilityDisplayShouldIncreaseContrast(),
+        reduced_motion: workspace.accessibilityDisplayShouldReduceMotion(),
+        // macOS has no system-wide text size; the system font size (13pt by
+        // default) is the closest there is
+        font_scale: font_scale(objc2_app_kit::NSFont::systemFontSize() / 13.0),
+    }
+}
+
+#[cfg(windows)]
+fn detect() -> AccessibilityPrefs {
+    use windows::core::BOOL;
+    use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: both calls write into correctly sized, initialized structs
+    let high_contrast = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            contrast.cbSize,
+            Some(&mut contrast as *mut _ as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .is_ok()
+        && contrast.dwFlags.contains(HCF_HIGHCONTRASTON);
+
+    let mut animation = BOOL(1);
+    let reduced_motion = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            Some(&mut animation as *mut _ as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .is_ok()
+        && !animation.as_bool();
+
+    // Settings > Accessibility > Text size
+    let text_scale = windows::UI::ViewManagement::UISettings::new()
+        .and_then(|settings| settings.TextScaleFactor())
+        .unwrap_or(1.0);
+
+    AccessibilityPrefs {
+        high_contrast,
+        reduced_motion,
+        font_scale: font_scale(text_scale),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn detect() -> AccessibilityPrefs {
+    let gsettings = |schema: &str, key: &str| -> Option<String> {
+        let output = std::process::Command::new("gsettings").args(["get", schema, key]).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'').to_string())
+    };
+
+    let high_contrast = gsettings("org.gnome.desktop.a11y.interface", "high-contrast").as_deref() == Some("true")
+        || gsettings("org.gnome.desktop.interface", "gtk-theme").is_some_and(|theme| theme.contains("HighContrast"));
+    let reduced_motion = gsettings("org.gnome.desktop.interface", "enable-animations").as_deref() == Some("false");
+    let text_scale = gsettings("org.gnome.desktop.interface", "text-scaling-factor")
+        .and_then(|factor| factor.parse::<f64>().ok())
+        .unwrap_or(1.0);
+
+    AccessibilityPrefs {
+        high_contrast,
+        reduced_motion,
+        font_scale: font_scale(text_scale),
+    }
+}
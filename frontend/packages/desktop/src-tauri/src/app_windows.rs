@@ -0,0 +1,111 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::event_bus::EventBus;
+use crate::thread_compression;
+
+/// What a secondary window shows. The frontend reads it from the window's URL;
+/// the event bus uses it to keep other threads' events out of the window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowContext {
+    Thread { thread_id: String },
+    Blueprint { path: String },
+}
+
+impl WindowContext {
+    fn kind(&self) -> &'static str {
+        match self {
+            WindowContext::Thread { .. } => "thread",
+            WindowContext::Blueprint { .. } => "blueprint",
+        }
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            WindowContext::Thread { thread_id } => thread_id,
+            WindowContext::Blueprint { path } => path,
+        }
+    }
+
+    /// Window label, the same each time for the same context so reopening focuses
+    /// the existing window
+    fn label(&self) -> String {
+        let hash = format!("{:x}", Sha256::digest(self.key().as_bytes()));
+        format!("{}-{}", self.kind(), &hash[..16])
+    }
+
+    /// Frontend route with the context in its query string
+    fn url(&self) -> WebviewUrl {
+        let mut query = reqwest::Url::parse("chimera://window/").expect("static URL parses");
+        query.query_pairs_mut().append_pair("window", self.kind());
+        match self {
+            WindowContext::Thread { thread_id } => query.query_pairs_mut().append_pair("thread_id", thread_id),
+            WindowContext::Blueprint { path } => query.query_pairs_mut().append_pair("path", path),
+        };
+        WebviewUrl::App(format!("index.html?{}", query.query().unwrap_or_default()).into())
+    }
+
+    /// Whether an event belongs in this window: events about one thread or
+    /// blueprint only reach the window showing it; everything else reaches all
+    pub fn wants(&self, payload: &serde_json::Value) -> bool {
+        let thread_id = payload.get("thread_id").and_then(|t| t.as_str());
+        let blueprint_id = payload.get("blueprint_id").and_then(|b| b.as_str());
+        match self {
+            WindowContext::Thread { thread_id: shown } => thread_id.is_none_or(|id| id == shown) && blueprint_id.is_none(),
+            WindowContext::Blueprint { path } => {
+                let shown = std::path::Path::new(path).file_stem().and_then(|s| s.to_str());
+                thread_id.is_none() && blueprint_id.is_none_or(|id| Some(id) == shown)
+            }
+        }
+    }
+}
+
+/// Open `context` in its own window, or focus the window already showing it.
+/// Returns the window's label.
+fn open(app_handle: &AppHandle, context: WindowContext, title: &str) -> Result<String, String> {
+    let label = context.label();
+    if let Some(window) = app_handle.get_webview_window(&label) {
+        let _ = window.unminimize();
+        window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
+        return Ok(label);
+    }
+
+    // Routes are in place before the window can subscribe to the bus
+    if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+        bus.set_window_context(&label, context.clone());
+    }
+
+    let built = WebviewWindowBuilder::new(app_handle, &label, context.url())
+        .title(title)
+        .inner_size(1000.0, 800.0)
+        .min_inner_size(375.0, 667.0)
+        .build();
+    if let Err(e) = built {
+        if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+            bus.remove_window(&label);
+        }
+        return Err(format!("Failed to open window: {}", e));
+    }
+
+    log::info!("Opened {} window {} for {}", context.kind(), label, context.key());
+    Ok(label)
+}
+
+/// Open a thread in its own window
+pub fn open_thread(app_handle: &AppHandle, thread_id: String) -> Result<String, String> {
+    if !thread_compression::thread_exists(&thread_id)? {
+        return Err(format!("Thread {} not found", thread_id));
+    }
+    open(app_handle, WindowContext::Thread { thread_id }, "Chimera Thread")
+}
+
+/// Open a blueprint file in its own window
+pub fn open_blueprint(app_handle: &AppHandle, path: String) -> Result<String, String> {
+    if !std::path::Path::new(&path).is_file() {
+        return Err(format!("Blueprint file not found: {}", path));
+    }
+    open(app_handle, WindowContext::Blueprint { path }, "Chimera Blueprint")
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Notify};
+
+use crate::error::ChimeraError;
+use crate::filesystem::{self, FsError};
+
+/// How often buffered appends are written out
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Buffered bytes per thread that trigger an immediate write
+const FLUSH_BYTES: usize = 64 * 1024;
+
+/// Thread files not written for this long are closed
+const IDLE_CLOSE: Duration = Duration::from_secs(10);
+
+/// Serialized lines waiting to be written to one thread file, and the file
+/// they're written through while the thread is busy
+#[derive(Default)]
+struct Pending {
+    data: Vec<u8>,
+    events: usize,
+    file: filesystem::OpenThreadFile,
+    last_write: Option<Instant>,
+}
+
+/// What a buffer past `FLUSH_BYTES` does
+enum WhenFull {
+    /// Write it in the caller
+    Write,
+    /// Wake the flush loop to write it
+    Notify,
+}
+
+/// Coalesces the many small appends made while a response streams into a few
+/// larger writes. Each thread has its own lock, held across the write, so lines
+/// land on disk in the order they were appended. A thread's file stays open
+/// between writes until it goes idle.
+pub struct AppendBuffer {
+    threads: StdMutex<HashMap<String, Arc<Mutex<Pending>>>>,
+    /// Wakes the flush loop early when a queued buffer fills up
+    full: Arc<Notify>,
+}
+
+impl AppendBuffer {
+    pub fn new() -> Self {
+        Self {
+            threads: StdMutex::new(HashMap::new()),
+            full: Arc::new(Notify::new()),
+        }
+    }
+
+    fn pending(&self, thread_id: &str) -> Arc<Mutex<Pending>> {
+        self.threads
+            .lock()
+            .unwrap()
+            .entry(thread_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Buffer events for a thread, writing immediately once the buffer is large
+    pub async fn append(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), FsError> {
+        self.buffer(thread_id, events, WhenFull::Write).await
+    }
+
+    /// Buffer events for a thread without ever writing in the caller: a full
+    /// buffer is left to the flush loop, so streaming callers return at once.
+    /// A failed background write keeps the events buffered for `flush` to retry
+    /// and report.
+    pub async fn queue(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), FsError> {
+        self.buffer(thread_id, events, WhenFull::Notify).await
+    }
+
+    async fn buffer(&self, thread_id: &str, events: &[serde_json::Value], when_full: WhenFull) -> Result<(), FsError> {
+        filesystem::get_thread_path(thread_id)?;
+
+        let pending = self.pending(thread_id);
+        let mut pending = pending.lock().await;
+
+        for event in events {
+            filesystem::serialize_bounded_event_line(event, &mut pending.data).await?;
+        }
+        pending.events += events.len();
+
+        if pending.data.len() >= FLUSH_BYTES {
+            match when_full {
+                WhenFull::Write => Self::write(thread_id, &mut pending).await?,
+                WhenFull::Notify => self.full.notify_one(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write any buffered events for a thread
+    pub async fn flush(&self, thread_id: &str) -> Result<(), FsError> {
+        let pending = self.threads.lock().unwrap().get(thread_id).cloned();
+        match pending {
+            Some(pending) => Self::write(thread_id, &mut *pending.lock().await).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Write buffered events for a thread and stop tracking it
+    pub async fn close(&self, thread_id: &str) -> Result<(), FsError> {
+        self.flush(thread_id).await?;
+        self.threads.lock().unwrap().remove(thread_id);
+        Ok(())
+    }
+
+    /// Write buffered events for a thread, then run `operation` with further appends
+    /// to it held back, for rewrites that replace the whole file. A compressed
+    /// thread is decompressed first, so the rewrite finds its JSONL.
+    pub async fn exclusive<T, E: Into<ChimeraError>>(
+        &self,
+        thread_id: &str,
+        operation: impl std::future::Future<Output = Result<T, E>>,
+    ) -> Result<T, ChimeraError> {
+        let pending = self.pending(thread_id);
+        let mut pending = pending.lock().await;
+        Self::write(thread_id, &mut pending).await?;
+        // The rewrite replaces the file this one has open
+        pending.file.close();
+        crate::thread_compression::ensure_decompressed(thread_id).await?;
+        operation.await.map_err(Into::into)
+    }
+
+    /// Threads appended to since they were last closed
+    pub fn open_threads(&self) -> Vec<String> {
+        self.threads.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Write buffered events for every thread
+    pub async fn flush_all(&self) {
+        let threads: Vec<(String, Arc<Mutex<Pending>>)> = self
+            .threads
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, pending)| (id.clone(), pending.clone()))
+            .collect();
+
+        for (thread_id, pending) in threads {
+            if let Err(e) = Self::write(&thread_id, &mut *pending.lock().await).await {
+                log::error!("Failed to flush appends for thread {}: {}", thread_id, e);
+            }
+        }
+    }
+
+    /// Close the files of threads not written to for `IDLE_CLOSE`. Threads busy
+    /// being written are skipped rather than waited for.
+    fn close_idle(&self) {
+        let threads: Vec<Arc<Mutex<Pending>>> = self.threads.lock().unwrap().values().cloned().collect();
+        for pending in threads {
+            let Ok(mut pending) = pending.try_lock() else { continue };
+            if pending.file.is_open() && pending.last_write.is_some_and(|at| at.elapsed() >= IDLE_CLOSE) {
+                pending.file.close();
+            }
+        }
+    }
+
+    /// Flush on a short timer, or as soon as a queued buffer fills, for as long
+    /// as the buffer is alive
+    pub fn start(self: &Arc<Self>) {
+        let buffer = Arc::downgrade(self);
+        let full = self.full.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = full.notified() => {}
+                }
+                let Some(buffer) = buffer.upgrade() else { break };
+                buffer.flush_all().await;
+                buffer.close_idle();
+            }
+        });
+    }
+
+    async fn write(thread_id: &str, pending: &mut Pending) -> Result<(), FsError> {
+        if pending.data.is_empty() {
+            return Ok(());
+        }
+        let Pending { data, events, file, last_write } = pending;
+        filesystem::append_thread_lines_open(thread_id, file, data, *events).await?;
+        data.clear();
+        *events = 0;
+        *last_write = Some(Instant::now());
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::filesystem::{self, FsError};
+use crate::thread_compression;
+
+/// Event type recording a file attached to a thread
+pub const ATTACHMENT_EVENT: &str = "data-attachment";
+
+/// Largest file that can be attached
+const MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+
+/// How long a dropped file can be attached without asking for folder access
+const DROP_GRANT: Duration = Duration::from_secs(5 * 60);
+
+/// Files the user dropped onto a window, and when
+static DROPPED: Mutex<Option<HashMap<PathBuf, Instant>>> = Mutex::new(None);
+
+/// A file attached to a thread. `hash` is the reference the frontend and the
+/// backend pass to `read_attachment`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileAttachment {
+    pub thread_id: String,
+    pub hash: String,
+    pub name: String,
+    pub media_type: String,
+    pub bytes: u64,
+}
+
+/// An attachment's content
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentContent {
+    pub hash: String,
+    pub bytes: u64,
+    /// Base64 of the file
+    pub data: String,
+}
+
+/// Attachments live in `attachments/<thread_id>/<sha256>` in the data directory
+fn get_attachments_dir(thread_id: &str) -> Result<PathBuf, FsError> {
+    // Same id rules as the thread file
+    filesystem::get_thread_path(thread_id)?;
+    Ok(filesystem::get_data_dir()?.join("attachments").join(thread_id))
+}
+
+fn attachment_path(thread_id: &str, hash: &str) -> Result<PathBuf, String> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Invalid attachment hash: {}", hash));
+    }
+    Ok(get_attachments_dir(thread_id)?.join(hash.to_ascii_lowercase()))
+}
+
+/// Remember files dropped onto a window, so attaching them doesn't ask for access
+/// to their folder: the drop already shows the user meant to share them
+pub fn note_dropped(paths: &[PathBuf]) {
+    let mut dropped = DROPPED.lock().unwrap();
+    let dropped = dropped.get_or_insert_with(HashMap::new);
+    dropped.retain(|_, at| at.elapsed() < DROP_GRANT);
+    for path in paths {
+        dropped.insert(path.clone(), Instant::now());
+    }
+}
+
+/// Whether `path` was dropped onto a window recently
+pub fn was_dropped(path: &Path) -> bool {
+    DROPPED
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|dropped| dropped.get(path))
+        .is_some_and(|at| at.elapsed() < DROP_GRANT)
+}
+
+/// Media type from a file's extension
+fn media_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "csv" => "text/csv",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "txt" | "log" | "rs" | "py" | "ts" | "tsx" | "js" | "jsx" | "toml" | "yaml" | "yml" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Copy a file into the thread's attachments folder under its SHA-256, so the same
+/// file attached twice is stored once. Returns the reference to record (blocking).
+pub fn store(thread_id: &str, source: &Path) -> Result<FileAttachment, String> {
+    if !thread_compression::thread_exists(thread_id)? {
+        return Err(format!("Thread {} not found", thread_id));
+    }
+    let metadata = std::fs::metadata(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", source.display()));
+    }
+    if metadata.len() > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{} is too large to attach ({} bytes, limit {})",
+            source.display(),
+            metadata.len(),
+            MAX_ATTACHMENT_BYTES
+        ));
+    }
+
+    let dir = get_attachments_dir(thread_id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+
+    // Hash while copying, then move into place under the hash
+    let mut input = std::fs::File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let temp = dir.join(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let mut output = std::fs::File::create(&temp).map_err(|e| format!("Failed to create attachment: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut bytes = 0u64;
+    let copied = (|| -> std::io::Result<()> {
+        loop {
+            let n = input.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            output.write_all(&buffer[..n])?;
+            bytes += n as u64;
+        }
+        output.sync_all()
+    })();
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("Failed to copy {}: {}", source.display(), e));
+    }
+
+    let hash = format!("{:x}", hasher.finalize());
+    let dest = dir.join(&hash);
+    if dest.exists() {
+        let _ = std::fs::remove_file(&temp);
+    } else {
+        std::fs::rename(&temp, &dest).map_err(|e| format!("Failed to store attachment: {}", e))?;
+    }
+
+    log::info!("Attached {} to thread {} as {}", source.display(), thread_id, hash);
+    Ok(FileAttachment {
+        thread_id: thread_id.to_string(),
+        hash,
+        name: source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        media_type: media_type(source).to_string(),
+        bytes,
+    })
+}
+
+/// Store content that isn't a file yet, e.g. a pasted image, the way `store`
+/// does a file; `name` gives it a media type (blocking)
+pub fn store_bytes(thread_id: &str, name: &str, content: &[u8]) -> Result<FileAttachment, String> {
+    if !thread_compression::thread_exists(thread_id)? {
+        return Err(format!("Thread {} not found", thread_id));
+    }
+    if content.len() as u64 > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{} is too large to attach ({} bytes, limit {})",
+            name,
+            content.len(),
+            MAX_ATTACHMENT_BYTES
+        ));
+    }
+
+    let dir = get_attachments_dir(thread_id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+
+    let hash = format!("{:x}", Sha256::digest(content));
+    let dest = dir.join(&hash);
+    if !dest.exists() {
+        let temp = dir.join(format!(".{}.tmp", uuid::Uuid::new_v4()));
+        let written = std::fs::File::create(&temp).and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| std::fs::rename(&temp, &dest)) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(format!("Failed to store attachment: {}", e));
+        }
+    }
+
+    log::info!("Attached {} to thread {} as {}", name, thread_id, hash);
+    Ok(FileAttachment {
+        thread_id: thread_id.to_string(),
+        hash,
+        name: name.to_string(),
+        media_type: media_type(Path::new(name)).to_string(),
+        bytes: content.len() as u64,
+    })
+}
+
+/// Give thread `to` every attachment of thread `from`, hard-linked where the
+/// filesystem allows since stored attachments never change. Returns how many
+/// were copied (blocking).
+pub fn copy_all(from: &str, to: &str) -> Result<usize, FsError> {
+    let source = get_attachments_dir(from)?;
+    let entries = match std::fs::read_dir(&source) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(FsError::io(&format!("read attachments of thread {}", from), e)),
+    };
+    let dest = get_attachments_dir(to)?;
+    std::fs::create_dir_all(&dest).map_err(|e| FsError::io("create attachments directory", e))?;
+
+    let mut copied = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        // Half-copied files from an interrupted `store` start with a dot
+        if name.to_string_lossy().starts_with('.') || !entry.path().is_file() {
+            continue;
+        }
+        let target = dest.join(&name);
+        if std::fs::hard_link(entry.path(), &target).is_err() {
+            std::fs::copy(entry.path(), &target)
+                .map_err(|e| FsError::io(&format!("copy attachment {}", name.to_string_lossy()), e))?;
+        }
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// The event recording an attachment in the thread's JSONL
+pub fn event(attachment: &FileAttachment) -> serde_json::Value {
+    serde_json::json!({
+        "type": ATTACHMENT_EVENT,
+        "data": {
+            "hash": attachment.hash,
+            "name": attachment.name,
+            "mediaType": attachment.media_type,
+            "bytes": attachment.bytes,
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// An attachment's content, by the hash `store` returned (blocking)
+pub fn read(thread_id: &str, hash: &str) -> Result<AttachmentContent, String> {
+    let path = attachment_path(thread_id, hash)?;
+    let content = std::fs::read(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("Attachment {} not found in thread {}", hash, thread_id),
+        _ => format!("Failed to read attachment {}: {}", hash, e),
+    })?;
+    Ok(AttachmentContent {
+        hash: hash.to_ascii_lowercase(),
+        bytes: content.len() as u64,
+        data: BASE64.encode(&content),
+    })
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::filesystem;
+
+/// Days of entries kept when `CHIMERA_AUDIT_RETENTION_DAYS` isn't set
+const DEFAULT_RETENTION_DAYS: i64 = 90;
+
+/// Entries returned by `get_audit_log` when no limit is given
+const DEFAULT_QUERY_LIMIT: usize = 1000;
+
+/// Serializes writers so concurrent entries never interleave within a line
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One line of `audit.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    /// Area of the app, e.g. `fs`
+    pub category: String,
+    pub action: String,
+    /// Who asked: `ui`, `cli`, `hook`, `sync`, `backend`, or `app` for the app's
+    /// own housekeeping
+    pub actor: String,
+    pub target: String,
+    pub allowed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Files removed, moved or overwritten
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+impl AuditEntry {
+    pub fn new(category: &str, action: &str, actor: &str, target: impl Into<String>, allowed: bool) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            category: category.to_string(),
+            action: action.to_string(),
+            actor: actor.to_string(),
+            target: target.into(),
+            allowed,
+            detail: None,
+            paths: Vec::new(),
+        }
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn paths<P: AsRef<Path>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.paths
+            .extend(paths.into_iter().map(|path| path.as_ref().to_string_lossy().to_string()));
+        self
+    }
+}
+
+/// Which entries `get_audit_log` returns; unset fields match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditFilter {
+    pub category: Option<String>,
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    /// Entries whose target or affected paths contain this, e.g. a thread id
+    pub path: Option<String>,
+    /// RFC 3339 lower bound, inclusive
+    pub since: Option<String>,
+    /// RFC 3339 upper bound, exclusive
+    pub until: Option<String>,
+    /// Return at most this many of the newest matches
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.category.as_ref().is_none_or(|c| *c == entry.category)
+            && self.action.as_ref().is_none_or(|a| *a == entry.action)
+            && self.actor.as_ref().is_none_or(|a| *a == entry.actor)
+            && self.path.as_ref().is_none_or(|path| {
+                entry.target.contains(path.as_str()) || entry.paths.iter().any(|p| p.contains(path.as_str()))
+            })
+            && self.since.as_ref().is_none_or(|since| entry.timestamp >= *since)
+            && self.until.as_ref().is_none_or(|until| entry.timestamp < *until)
+    }
+}
+
+fn get_audit_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("audit.jsonl"))
+}
+
+/// Whether `path` lies outside the data directory, i.e. is worth auditing
+pub fn outside_data_dir(path: &Path) -> bool {
+    let Ok(data_dir) = filesystem::get_data_dir() else { return true };
+    let data_dir = data_dir.canonicalize().unwrap_or(data_dir);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    !path.starts_with(data_dir)
+}
+
+/// Append an entry to the audit log (blocking). Failures are logged, never returned:
+/// auditing must not break the operation being audited.
+pub fn record(entry: AuditEntry) {
+    if let Err(e) = write(&entry) {
+        log::error!("Failed to write audit entry {:?}: {}", entry, e);
+    }
+}
+
+fn write(entry: &AuditEntry) -> Result<(), String> {
+    let mut line = serde_json::to_vec(entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    line.push(b'\n');
+
+    let _guard = WRITE_LOCK.lock().unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_audit_path()?)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    file.write_all(&line).map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+/// Append an entry from async code without blocking the runtime
+pub async fn record_async(entry: AuditEntry) {
+    let _ = tokio::task::spawn_blocking(move || record(entry)).await;
+}
+
+fn read_entries() -> Result<Vec<AuditEntry>, String> {
+    let path = get_audit_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Entries matching `filter`, oldest first (blocking)
+pub fn query(filter: &AuditFilter) -> Result<Vec<AuditEntry>, String> {
+    let mut entries: Vec<AuditEntry> = read_entries()?.into_iter().filter(|e| filter.matches(e)).collect();
+    let limit = filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(entries)
+}
+
+/// Drop entries older than the retention period (`CHIMERA_AUDIT_RETENTION_DAYS`,
+/// 0 keeps everything). Returns how many were removed (blocking).
+pub fn prune() -> Result<usize, String> {
+    let days = std::env::var("CHIMERA_AUDIT_RETENTION_DAYS")
+        .ok()
+        .and_then(|d| d.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    if days <= 0 {
+        return Ok(0);
+    }
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+
+    let _guard = WRITE_LOCK.lock().unwrap();
+    let entries = read_entries()?;
+    let before = entries.len();
+    let kept: Vec<&AuditEntry> = entries.iter().filter(|e| e.timestamp >= cutoff).collect();
+    let removed = before - kept.len();
+    if removed == 0 {
+        return Ok(0);
+    }
+
+    let mut content = Vec::new();
+    for entry in kept {
+        serde_json::to_writer(&mut content, entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        content.push(b'\n');
+    }
+
+    // Write beside the log and rename over it so a crash can't truncate it
+    let path = get_audit_path()?;
+    let temp = path.with_extension("jsonl.tmp");
+    std::fs::write(&temp, content).map_err(|e| format!("Failed to write audit log: {}", e))?;
+    std::fs::rename(&temp, &path).map_err(|e| format!("Failed to replace audit log: {}", e))?;
+
+    log::info!("Pruned {} audit entries older than {} days", removed, days);
+    Ok(removed)
+}
@@ -0,0 +1,339 @@
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::append_buffer::AppendBuffer;
+use crate::blueprint_cache::BlueprintCache;
+use crate::event_bus::EventBus;
+use crate::export::ExporterRegistry;
+use crate::filesystem;
+use crate::permissions::Permissions;
+use crate::thread_compression;
+
+/// Export format when automation doesn't name one: raw events, blueprint and attachments
+const DEFAULT_EXPORT_FORMAT: &str = "bundle";
+
+/// Bring the main window to the front
+fn focus_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Publish an automation event to the frontend
+fn publish(app_handle: &AppHandle, topic: &str, payload: serde_json::Value) {
+    if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+        bus.publish(topic, payload);
+    }
+}
+
+/// Create a thread from a blueprint (the first one if unspecified) and ask the
+/// frontend to open it and send the prompt. Returns the new thread id.
+pub async fn new_thread(app_handle: &AppHandle, prompt: String, blueprint_id: Option<String>) -> Result<String, String> {
+    let blueprints = match app_handle.try_state::<Arc<BlueprintCache>>() {
+        Some(cache) => cache.list().await?,
+        None => filesystem::list_blueprints().await?,
+    };
+
+    let blueprint = match &blueprint_id {
+        Some(id) => blueprints
+            .iter()
+            .find(|b| &b.id == id)
+            .ok_or_else(|| format!("Blueprint not found: {}", id))?,
+        None => blueprints.first().ok_or("No blueprints available")?,
+    };
+
+    let blueprint_json = filesystem::read_blueprint(blueprint.file_path.clone()).await?;
+    let header = crate::provenance::header(app_handle, &blueprint_json).await;
+    let thread_id = filesystem::create_thread_with_header(blueprint_json, &header).await?;
+
+    log::info!("Automation created thread {} from blueprint {}", thread_id, blueprint.id);
+
+    publish(
+        app_handle,
+        "thread-changed",
+        serde_json::json!({ "thread_id": thread_id, "change": "created" }),
+    );
+    publish(
+        app_handle,
+        "automation-new-thread",
+        serde_json::json!({ "thread_id": thread_id, "prompt": prompt }),
+    );
+    focus_main_window(app_handle);
+
+    Ok(thread_id)
+}
+
+/// Ask the frontend to open an existing thread
+pub fn open_thread(app_handle: &AppHandle, thread_id: String) -> Result<(), String> {
+    if !thread_compression::thread_exists(&thread_id)? {
+        return Err(format!("Thread {} not found", thread_id));
+    }
+
+    publish(app_handle, "automation-open-thread", serde_json::json!({ "thread_id": thread_id }));
+    focus_main_window(app_handle);
+    Ok(())
+}
+
+/// Export a thread the way the `export_thread` command does, in `format` or
+/// `DEFAULT_EXPORT_FORMAT`. A directory destination gets a file named after the
+/// thread. Returns the written path.
+pub async fn export_thread(
+    app_handle: &AppHandle,
+    thread_id: String,
+    dest_path: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let starting = || "Chimera is still starting".to_string();
+    let appends = app_handle.try_state::<Arc<AppendBuffer>>().ok_or_else(starting)?;
+    let exporters = app_handle.try_state::<Arc<ExporterRegistry>>().ok_or_else(starting)?;
+    let permissions = app_handle.try_state::<Arc<Permissions>>().ok_or_else(starting)?;
+
+    let format = format.unwrap_or_else(|| DEFAULT_EXPORT_FORMAT.to_string());
+    let mut dest = std::path::PathBuf::from(&dest_path);
+    if dest.is_dir() {
+        let extension = exporters
+            .list()
+            .into_iter()
+            .find(|info| info.id == format)
+            .map(|info| info.extension)
+            .ok_or_else(|| format!("Unknown export format: {}", format))?;
+        dest = dest.join(format!("{}.{}", thread_id, extension));
+    }
+
+    let report = crate::export_thread_with(
+        app_handle,
+        &appends,
+        &exporters,
+        &permissions,
+        thread_id.clone(),
+        &format,
+        dest.to_string_lossy().to_string(),
+    )
+    .await?;
+
+    log::info!("Automation exported thread {} to {}", thread_id, report.dest_path);
+    Ok(report.dest_path)
+}
+
+/// Start the platform automation surface (Cocoa scripting on macOS, DBus on Linux)
+pub fn start(app_handle: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    applescript::register(app_handle.clone());
+
+    #[cfg(target_os = "linux")]
+    {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            match dbus::serve(app_handle.clone()).await {
+                Ok(connection) => {
+                    app_handle.manage(connection);
+                    log::info!("DBus automation service registered as {}", dbus::BUS_NAME);
+                }
+                Err(e) => log::warn!("DBus automation service unavailable: {}", e),
+            }
+        });
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let _ = app_handle;
+}
+
+/// AppleScript/JXA support via Cocoa scripting (see Chimera.sdef)
+#[cfg(target_os = "macos")]
+mod applescript {
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2::{define_class, ClassType, Message};
+    use objc2_foundation::{NSScriptCommand, NSString};
+    use std::future::Future;
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+
+    /// App handle shared with the script command classes
+    static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+    /// Generic AppleScript error number (errOSAGeneralError)
+    const SCRIPT_ERROR: isize = -2700;
+
+    /// Read a string argument from the command (direct parameter or named key)
+    fn string_arg(command: &NSScriptCommand, key: Option<&str>) -> Option<String> {
+        let value = match key {
+            None => command.directParameter(),
+            Some(key) => command
+                .evaluatedArguments()
+                .and_then(|args| args.objectForKey(&NSString::from_str(key))),
+        }?;
+        value.downcast::<NSString>().ok().map(|s| s.to_string())
+    }
+
+    /// Convert a command result into the value returned to AppleScript
+    fn finish(command: &NSScriptCommand, result: Result<Option<String>, String>) -> Option<Retained<AnyObject>> {
+        match result {
+            Ok(value) => value.map(|v| Retained::into_super(Retained::into_super(NSString::from_str(&v)))),
+            Err(e) => {
+                log::warn!("AppleScript command failed: {}", e);
+                command.setScriptErrorNumber(SCRIPT_ERROR);
+                command.setScriptErrorString(Some(&NSString::from_str(&e)));
+                None
+            }
+        }
+    }
+
+    fn app_handle() -> Result<&'static AppHandle, String> {
+        APP_HANDLE.get().ok_or_else(|| "Chimera is still starting".to_string())
+    }
+
+    /// A suspended command on its way back to the main thread
+    struct Suspended(Retained<NSScriptCommand>);
+
+    // SAFETY: the command is only touched again on the main thread, by `run_on_main_thread`
+    unsafe impl Send for Suspended {}
+
+    /// Suspend the command and run `work` on the async runtime, resuming it with
+    /// the result on the main thread, so the app keeps handling events meanwhile
+    fn suspend_until<W, F>(command: &NSScriptCommand, work: W) -> Option<Retained<AnyObject>>
+    where
+        W: FnOnce(&'static AppHandle) -> F,
+        F: Future<Output = Result<Option<String>, String>> + Send + 'static,
+    {
+        let app_handle = match app_handle() {
+            Ok(app_handle) => app_handle,
+            Err(e) => return finish(command, Err(e)),
+        };
+        let work = work(app_handle);
+        command.suspendExecution();
+        let suspended = Suspended(command.retain());
+        tauri::async_runtime::spawn(async move {
+            let result = work.await;
+            let resumed = app_handle.run_on_main_thread(move || {
+                let command = suspended.0;
+                let value = finish(&command, result);
+                // SAFETY: on the main thread, once, after `suspendExecution`
+                unsafe { command.resumeExecutionWithResult(value.as_deref()) };
+            });
+            if let Err(e) = resumed {
+                log::warn!("Failed to resume AppleScript command: {}", e);
+            }
+        });
+        // Ignored for a suspended command; the result goes to `resumeExecutionWithResult`
+        None
+    }
+
+    define_class!(
+        // SAFETY: NSScriptCommand may be subclassed; we don't implement Drop.
+        #[unsafe(super(NSScriptCommand))]
+        #[name = "ChimeraNewThreadCommand"]
+        struct NewThreadCommand;
+
+        impl NewThreadCommand {
+            #[unsafe(method_id(performDefaultImplementation))]
+            fn perform(&self) -> Option<Retained<AnyObject>> {
+                let Some(prompt) = string_arg(self, None) else {
+                    return finish(self, Err("Missing prompt".to_string()));
+                };
+                let blueprint = string_arg(self, Some("blueprint"));
+                suspend_until(self, move |app_handle| async move {
+                    super::new_thread(app_handle, prompt, blueprint).await.map(Some)
+                })
+            }
+        }
+    );
+
+    define_class!(
+        // SAFETY: NSScriptCommand may be subclassed; we don't implement Drop.
+        #[unsafe(super(NSScriptCommand))]
+        #[name = "ChimeraOpenThreadCommand"]
+        struct OpenThreadCommand;
+
+        impl OpenThreadCommand {
+            #[unsafe(method_id(performDefaultImplementation))]
+            fn perform(&self) -> Option<Retained<AnyObject>> {
+                let result = (|| {
+                    let thread_id = string_arg(self, None).ok_or("Missing thread id")?;
+                    super::open_thread(app_handle()?, thread_id).map(|_| None)
+                })();
+                finish(self, result)
+            }
+        }
+    );
+
+    define_class!(
+        // SAFETY: NSScriptCommand may be subclassed; we don't implement Drop.
+        #[unsafe(super(NSScriptCommand))]
+        #[name = "ChimeraExportThreadCommand"]
+        struct ExportThreadCommand;
+
+        impl ExportThreadCommand {
+            #[unsafe(method_id(performDefaultImplementation))]
+            fn perform(&self) -> Option<Retained<AnyObject>> {
+                let (Some(thread_id), Some(dest)) = (string_arg(self, None), string_arg(self, Some("destination"))) else {
+                    return finish(self, Err("Missing thread id or destination".to_string()));
+                };
+                let format = string_arg(self, Some("format"));
+                suspend_until(self, move |app_handle| async move {
+                    super::export_thread(app_handle, thread_id, dest, format).await.map(Some)
+                })
+            }
+        }
+    );
+
+    /// Register the script command classes with the Objective-C runtime so
+    /// Cocoa scripting can instantiate them by name
+    pub fn register(app_handle: AppHandle) {
+        let _ = APP_HANDLE.set(app_handle);
+        let _ = NewThreadCommand::class();
+        let _ = OpenThreadCommand::class();
+        let _ = ExportThreadCommand::class();
+        log::info!("Registered AppleScript commands");
+    }
+}
+
+/// DBus automation service on the session bus
+#[cfg(target_os = "linux")]
+mod dbus {
+    use tauri::AppHandle;
+
+    pub const BUS_NAME: &str = "com.ericksonc.ChimeraDesktop";
+    const OBJECT_PATH: &str = "/com/ericksonc/ChimeraDesktop";
+
+    struct AutomationService {
+        app_handle: AppHandle,
+    }
+
+    #[zbus::interface(name = "com.ericksonc.ChimeraDesktop.Automation")]
+    impl AutomationService {
+        /// Create a thread and send it a prompt; an empty blueprint id uses the first blueprint
+        async fn new_thread(&self, prompt: String, blueprint_id: String) -> zbus::fdo::Result<String> {
+            let blueprint_id = Some(blueprint_id).filter(|id| !id.is_empty());
+            super::new_thread(&self.app_handle, prompt, blueprint_id)
+                .await
+                .map_err(zbus::fdo::Error::Failed)
+        }
+
+        /// Open an existing thread in the main window
+        async fn open_thread(&self, thread_id: String) -> zbus::fdo::Result<()> {
+            super::open_thread(&self.app_handle, thread_id).map_err(zbus::fdo::Error::Failed)
+        }
+
+        /// Export a thread to a file or directory; an empty format exports a Chimera bundle
+        async fn export_thread(&self, thread_id: String, dest_path: String, format: String) -> zbus::fdo::Result<String> {
+            let format = Some(format).filter(|format| !format.is_empty());
+            super::export_thread(&self.app_handle, thread_id, dest_path, format)
+                .await
+                .map_err(zbus::fdo::Error::Failed)
+        }
+    }
+
+    /// Claim the bus name and serve the automation interface
+    pub async fn serve(app_handle: AppHandle) -> Result<zbus::Connection, String> {
+        zbus::connection::Builder::session()
+            .and_then(|b| b.name(BUS_NAME))
+            .and_then(|b| b.serve_at(OBJECT_PATH, AutomationService { app_handle }))
+            .map_err(|e| format!("Failed to configure DBus connection: {}", e))?
+            .build()
+            .await
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))
+    }
+}
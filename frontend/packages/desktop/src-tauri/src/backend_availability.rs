@@ -0,0 +1,143 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+
+use crate::event_bus::EventBus;
+use crate::python_backend::{BackendStatus, DEFAULT_WORKSPACE};
+
+/// Longest `wait_for_backend` waits
+pub const MAX_WAIT: Duration = Duration::from_secs(120);
+
+/// Whether the default backend can take requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AvailabilityState {
+    Available,
+    Starting,
+    Unavailable,
+}
+
+/// The default backend's availability, as sent in `backend-availability` events
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Availability {
+    pub state: AvailabilityState,
+    /// Why the backend is starting or unavailable
+    pub reason: Option<String>,
+    pub url: Option<String>,
+    pub updated_at: String,
+}
+
+impl Availability {
+    fn new(state: AvailabilityState, reason: Option<String>, url: Option<String>) -> Self {
+        Self {
+            state,
+            reason,
+            url,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Availability implied by a `backend-status` event
+    fn from_status(status: &BackendStatus) -> Self {
+        let (state, reason) = match status.state.as_str() {
+            "running" => (AvailabilityState::Available, None),
+            "restarting" => (AvailabilityState::Starting, Some("Backend is restarting".to_string())),
+            "unhealthy" => (
+                AvailabilityState::Unavailable,
+                Some(status.last_error.clone().unwrap_or_else(|| "Backend isn't responding".to_string())),
+            ),
+            "failed" => (
+                AvailabilityState::Unavailable,
+                Some(status.last_error.clone().unwrap_or_else(|| "Backend failed".to_string())),
+            ),
+            other => (AvailabilityState::Unavailable, Some(format!("Backend is {}", other))),
+        };
+        Self::new(state, reason, Some(status.url.clone()))
+    }
+}
+
+/// Tracks whether the default backend is up, so the frontend can show a
+/// "connecting" state and hold messages instead of finding out from failed fetches
+pub struct BackendAvailability {
+    current: watch::Sender<Availability>,
+    bus: Arc<EventBus>,
+}
+
+impl BackendAvailability {
+    /// Starts out `starting`: the backend is launched after the window opens
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        let initial = Availability::new(AvailabilityState::Starting, Some("Backend is starting".to_string()), None);
+        Self {
+            current: watch::Sender::new(initial),
+            bus,
+        }
+    }
+
+    pub fn get(&self) -> Availability {
+        self.current.borrow().clone()
+    }
+
+    /// Record a change and publish `backend-availability`. Repeats are dropped.
+    pub fn set(&self, state: AvailabilityState, reason: Option<String>, url: Option<String>) {
+        self.update(Availability::new(state, reason, url));
+    }
+
+    fn update(&self, availability: Availability) {
+        let changed = self.current.send_if_modified(|current| {
+            if (current.state, &current.reason, &current.url) == (availability.state, &availability.reason, &availability.url) {
+                return false;
+            }
+            *current = availability.clone();
+            true
+        });
+        if changed {
+            log::info!("Backend availability: {:?}", availability.state);
+            self.bus.publish("backend-availability", serde_json::json!(availability));
+        }
+    }
+
+    /// Follow the default backend's `backend-status` events
+    pub fn watch(self: &Arc<Self>) {
+        let mut receiver = self.bus.listen();
+        let availability = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let event = receiver.recv().await;
+                let Some(availability) = availability.upgrade() else { break };
+                match event {
+                    Ok(event) if event.topic == "backend-status" => {
+                        let Ok(status) = serde_json::from_value::<BackendStatus>(event.payload) else { continue };
+                        if status.workspace_id == DEFAULT_WORKSPACE {
+                            availability.update(Availability::from_status(&status));
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Wait up to `timeout` for the backend to be available
+    pub async fn wait(&self, timeout: Duration) -> Result<Availability, String> {
+        let mut receiver = self.current.subscribe();
+        let waited = tokio::time::timeout(
+            timeout.min(MAX_WAIT),
+            receiver.wait_for(|availability| availability.state == AvailabilityState::Available),
+        )
+        .await;
+        match waited {
+            Ok(Ok(availability)) => Ok(availability.clone()),
+            Ok(Err(_)) => Err("Backend availability is no longer tracked".to_string()),
+            Err(_) => {
+                let current = self.get();
+                Err(match current.reason {
+                    Some(reason) => format!("Backend not available after {:?}: {}", timeout, reason),
+                    None => format!("Backend not available after {:?}", timeout),
+                })
+            }
+        }
+    }
+}
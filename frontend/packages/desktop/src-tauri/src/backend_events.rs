@@ -0,0 +1,107 @@
+use futures_util::StreamExt;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+use crate::event_bus::EventBus;
+use crate::python_backend::PythonBackend;
+
+/// First wait before reconnecting; doubles on each failure up to `MAX_RECONNECT_DELAY`
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Endpoint of the backend's event socket (`CHIMERA_BACKEND_EVENTS_PATH`)
+fn events_path() -> String {
+    let path = std::env::var("CHIMERA_BACKEND_EVENTS_PATH").unwrap_or_else(|_| "/events".to_string());
+    format!("/{}", path.trim_start_matches('/'))
+}
+
+/// Publish `backend-events-status` when the socket connects or drops
+fn publish_status(bus: &EventBus, workspace_id: &str, connected: bool) {
+    bus.publish(
+        "backend-events-status",
+        serde_json::json!({ "workspace_id": workspace_id, "connected": connected }),
+    );
+}
+
+/// Keep a WebSocket open to the backend's event socket and re-publish each
+/// message as a `backend-event`: `{workspace_id, thread_id, event}`. The socket
+/// drops whenever the backend restarts, possibly on a new port, so it's
+/// reconnected with backoff until `token` is cancelled or the backend is gone.
+pub async fn bridge(backend: Weak<PythonBackend>, bus: Arc<EventBus>, token: CancellationToken) {
+    let mut delay = RECONNECT_DELAY;
+    loop {
+        // Only hold the backend while reading its address, so it can be dropped
+        let (workspace_id, url) = {
+            let Some(backend) = backend.upgrade() else { break };
+            if backend.is_stopping() {
+                break;
+            }
+            let url = backend.base_url().replacen("http://", "ws://", 1) + &events_path();
+            (backend.status().workspace_id, url)
+        };
+
+        let connected = tokio::select! {
+            _ = token.cancelled() => break,
+            connected = tokio_tungstenite::connect_async(url.as_str()) => connected,
+        };
+        match connected {
+            Ok((mut socket, _)) => {
+                log::info!("Connected to backend events at {}", url);
+                publish_status(&bus, &workspace_id, true);
+                delay = RECONNECT_DELAY;
+
+                loop {
+                    let message = tokio::select! {
+                        _ = token.cancelled() => None,
+                        message = socket.next() => message,
+                    };
+                    match message {
+                        Some(Ok(Message::Text(text))) => relay(&bus, &workspace_id, &text),
+                        Some(Ok(Message::Close(_))) | None => break,
+                        // Pings are answered by tungstenite while reading
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            log::warn!("Backend event socket failed: {}", e);
+                            break;
+                        }
+                    }
+                }
+
+                publish_status(&bus, &workspace_id, false);
+                if token.is_cancelled() {
+                    let _ = socket.close(None).await;
+                    break;
+                }
+                log::info!("Backend event socket closed, reconnecting");
+            }
+            Err(e) => log::debug!("Failed to connect to backend events at {}: {}", url, e),
+        }
+
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+fn relay(bus: &EventBus, workspace_id: &str, text: &str) {
+    let message: serde_json::Value = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            log::warn!("Ignoring malformed backend event: {}", e);
+            return;
+        }
+    };
+    bus.publish(
+        "backend-event",
+        serde_json::json!({
+            "workspace_id": workspace_id,
+            "thread_id": message.get("threadId"),
+            "event": message.get("event"),
+        }),
+    );
+}
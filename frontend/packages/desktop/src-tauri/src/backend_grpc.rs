@@ -0,0 +1,89 @@
+/// Default address of the backend's gRPC service
+const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:33004";
+
+/// The gRPC endpoint to stream from, when `CHIMERA_BACKEND_TRANSPORT=grpc` selects
+/// it (`CHIMERA_GRPC_ENDPOINT` overrides the address). HTTP is used otherwise.
+pub fn endpoint() -> Option<String> {
+    let transport = std::env::var("CHIMERA_BACKEND_TRANSPORT").ok()?;
+    if !transport.eq_ignore_ascii_case("grpc") {
+        return None;
+    }
+
+    if cfg!(not(feature = "grpc")) {
+        log::warn!("CHIMERA_BACKEND_TRANSPORT=grpc ignored: built without the grpc feature");
+        return None;
+    }
+
+    Some(std::env::var("CHIMERA_GRPC_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string()))
+}
+
+#[cfg(feature = "grpc")]
+pub use client::open_stream;
+
+/// Hand-written client for `proto/backend.proto`, so builds don't need protoc
+#[cfg(feature = "grpc")]
+mod client {
+    use std::time::Duration;
+    use tonic::codec::ProstCodec;
+    use tonic::codegen::http::uri::PathAndQuery;
+    use tonic::metadata::{MetadataKey, MetadataValue};
+    use tonic::transport::Endpoint;
+
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    const STREAM_METHOD: &str = "/chimera.backend.v1.Backend/Stream";
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StreamRequest {
+        #[prost(string, tag = "1")]
+        pub path: String,
+        #[prost(string, tag = "2")]
+        pub body_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StreamChunk {
+        #[prost(string, tag = "1")]
+        pub data: String,
+    }
+
+    /// Call `Backend/Stream` for `path` with the JSON `body`
+    pub async fn open_stream(
+        endpoint: &str,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<tonic::Streaming<StreamChunk>, String> {
+        let channel = Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| format!("Invalid gRPC endpoint {}: {}", endpoint, e))?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to connect to backend: {}", e))?;
+
+        let mut client = tonic::client::Grpc::new(channel);
+        client
+            .ready()
+            .await
+            .map_err(|e| format!("Failed to connect to backend: {}", e))?;
+
+        let mut request = tonic::Request::new(StreamRequest {
+            path: path.to_string(),
+            body_json: body.to_string(),
+        });
+        for (name, value) in crate::telemetry::trace_headers() {
+            if let (Ok(key), Ok(value)) = (MetadataKey::from_bytes(name.as_bytes()), MetadataValue::try_from(value)) {
+                request.metadata_mut().insert(key, value);
+            }
+        }
+
+        let response = client
+            .server_streaming(
+                request,
+                PathAndQuery::from_static(STREAM_METHOD),
+                ProstCodec::<StreamRequest, StreamChunk>::default(),
+            )
+            .await
+            .map_err(|status| format!("Backend returned {}: {}", status.code(), status.message()))?;
+
+        Ok(response.into_inner())
+    }
+}
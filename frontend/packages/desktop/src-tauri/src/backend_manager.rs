@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use tauri::Manager;
+use tokio::sync::Mutex;
+
+use crate::backend_env::BackendEnvVar;
+use crate::error::ChimeraError;
+use crate::event_bus::EventBus;
+use crate::python_backend::{BackendConfig, BackendStatus, PythonBackend, DEFAULT_WORKSPACE};
+use crate::settings::SettingsStore;
+
+/// The Python backends that are running, one per workspace. Each has its own
+/// process, port, supervisor, log and PID file. The default workspace's backend
+/// is also managed as `Arc<PythonBackend>`, which is what the proxy, run queue
+/// and the rest of the app talk to.
+pub struct BackendManager {
+    app_handle: tauri::AppHandle,
+    backends: StdMutex<HashMap<String, Arc<PythonBackend>>>,
+    /// Held while a backend starts, so one workspace doesn't get two
+    start_lock: Mutex<()>,
+    /// Extra environment given to every backend, e.g. the file tool API address
+    env: StdMutex<Vec<(String, String)>>,
+}
+
+impl BackendManager {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self {
+            app_handle,
+            backends: StdMutex::new(HashMap::new()),
+            start_lock: Mutex::new(()),
+            env: StdMutex::new(Vec::new()),
+        }
+    }
+
+    pub fn set_env(&self, env: Vec<(String, String)>) {
+        *self.env.lock().unwrap() = env;
+    }
+
+    /// The backend for a workspace, starting it if it isn't running
+    #[tracing::instrument(skip(self), fields(workspace = %config.workspace_id), err)]
+    pub async fn start(&self, config: BackendConfig) -> Result<Arc<PythonBackend>, ChimeraError> {
+        let _starting = self.start_lock.lock().await;
+        if let Some(backend) = self.get(&config.workspace_id) {
+            return Ok(backend);
+        }
+
+        let workspace_id = config.workspace_id.clone();
+        // Secrets are read fresh for each start so they're only ever in memory
+        let vars = self.env_vars().await?;
+        log::info!("Backend environment for workspace {}: {}", workspace_id, crate::backend_env::describe(&vars));
+        let backend = Arc::new(PythonBackend::start(&crate::backend_env::pairs(&vars), config).await?);
+        log::info!("Backend for workspace {} started at {}", workspace_id, backend.base_url());
+
+        // Restart it if it dies, and pass on the events it pushes
+        if let Some(bus) = self.app_handle.try_state::<Arc<EventBus>>() {
+            backend.supervise(bus.inner().clone());
+            backend.bridge_events(bus.inner().clone());
+        }
+        if workspace_id == DEFAULT_WORKSPACE {
+            self.app_handle.manage(backend.clone());
+        }
+        self.backends.lock().unwrap().insert(workspace_id, backend.clone());
+        Ok(backend)
+    }
+
+    /// The environment a backend starting now would be given, with its values
+    pub async fn env_vars(&self) -> Result<Vec<BackendEnvVar>, String> {
+        let app_env = self.env.lock().unwrap().clone();
+        let settings_env = self
+            .app_handle
+            .try_state::<Arc<SettingsStore>>()
+            .map(|settings| settings.get().backend_env)
+            .unwrap_or_default();
+        crate::filesystem::blocking(move || Ok(crate::backend_env::collect(&app_env, &settings_env))).await
+    }
+
+    pub fn get(&self, workspace_id: &str) -> Option<Arc<PythonBackend>> {
+        self.backends.lock().unwrap().get(workspace_id).cloned()
+    }
+
+    /// Shut down a workspace's backend. The default workspace's backend runs for
+    /// the life of the app; use `restart_backend` on it instead.
+    #[tracing::instrument(skip(self, bus), err)]
+    pub async fn stop(&self, workspace_id: &str, bus: Option<&EventBus>) -> Result<(), ChimeraError> {
+        if workspace_id == DEFAULT_WORKSPACE {
+            return Err(ChimeraError::InvalidInput("The default backend can't be stopped".to_string()));
+        }
+        let backend = self
+            .backends
+            .lock()
+            .unwrap()
+            .remove(workspace_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("No backend is running for workspace {}", workspace_id)))?;
+        backend.shutdown(bus).await;
+        Ok(())
+    }
+
+    /// Status of every running backend, the default workspace first
+    pub fn list(&self) -> Vec<BackendStatus> {
+        let mut statuses: Vec<BackendStatus> =
+            self.backends.lock().unwrap().values().map(|backend| backend.status()).collect();
+        statuses.sort_by(|a, b| {
+            (a.workspace_id != DEFAULT_WORKSPACE, &a.workspace_id).cmp(&(b.workspace_id != DEFAULT_WORKSPACE, &b.workspace_id))
+        });
+        statuses
+    }
+
+    /// Process ids of the running backends
+    pub async fn pids(&self) -> Vec<u32> {
+        let backends: Vec<_> = self.backends.lock().unwrap().values().cloned().collect();
+        let mut pids = Vec::new();
+        for backend in backends {
+            pids.extend(backend.pid().await);
+        }
+        pids
+    }
+
+    /// Workspace id and process id of each running backend
+    pub async fn workspace_pids(&self) -> Vec<(String, u32)> {
+        let backends: Vec<_> =
+            self.backends.lock().unwrap().iter().map(|(id, backend)| (id.clone(), backend.clone())).collect();
+        let mut pids = Vec::new();
+        for (workspace_id, backend) in backends {
+            if let Some(pid) = backend.pid().await {
+                pids.push((workspace_id, pid));
+            }
+        }
+        pids
+    }
+
+    /// Shut down every backend at once, on app exit
+    pub async fn shutdown_all(&self, bus: Option<&EventBus>) {
+        let backends: Vec<_> = self.backends.lock().unwrap().drain().map(|(_, backend)| backend).collect();
+        futures_util::future::join_all(backends.iter().map(|backend| backend.shutdown(bus))).await;
+    }
+}
@@ -0,0 +1,361 @@
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::Manager;
+
+use crate::run_recovery::{RunGuard, RunTracker};
+
+/// Requests kept in the debug log
+const LOG_CAPACITY: usize = 500;
+
+/// Largest request body forwarded
+const MAX_REQUEST_BYTES: usize = 32 * 1024 * 1024;
+
+/// Headers not passed through in either direction
+const HOP_HEADERS: [&str; 7] = [
+    "host",
+    "authorization",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "upgrade",
+    "content-length",
+];
+
+/// Path prefix of requests the proxy answers itself
+const RESUME_PREFIX: &str = "/_chimera/runs/";
+
+/// Where the webview should send backend requests, and the token they need
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyInfo {
+    pub url: String,
+    pub token: String,
+}
+
+/// One proxied request, for debugging
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyLogEntry {
+    pub id: u64,
+    /// `webview` for requests through the proxy port, `command` for `proxy_request`
+    pub source: String,
+    pub method: String,
+    pub path: String,
+    pub status: Option<u16>,
+    /// Until response headers arrived; streams run on after this
+    pub duration_ms: u64,
+    pub request_bytes: usize,
+    /// From Content-Length; unknown for streamed responses
+    pub response_bytes: Option<u64>,
+    pub error: Option<String>,
+    pub started_at: String,
+}
+
+/// Response to a `proxy_request` call
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Authenticated reverse proxy in front of the Python backend. The webview talks
+/// to a random loopback port with a per-launch token instead of reaching the
+/// backend directly; responses, including SSE streams, are passed through as
+/// they arrive.
+pub struct BackendProxy {
+    app_handle: tauri::AppHandle,
+    token: String,
+    port: u16,
+    client: reqwest::Client,
+    log: Mutex<VecDeque<ProxyLogEntry>>,
+    next_id: AtomicU64,
+}
+
+impl BackendProxy {
+    /// Bind the proxy port and start serving
+    pub fn start(app_handle: tauri::AppHandle) -> Result<Arc<Self>, String> {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))
+            .map_err(|e| format!("Failed to bind backend proxy: {}", e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure backend proxy: {}", e))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read backend proxy address: {}", e))?
+            .port();
+
+        let proxy = Arc::new(Self {
+            app_handle,
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            port,
+            client: reqwest::Client::new(),
+            log: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(1),
+        });
+
+        let router = Router::new().fallback(handle).with_state(proxy.clone());
+        tauri::async_runtime::spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log::error!("Failed to start backend proxy: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = axum::serve(listener, router).await {
+                log::error!("Backend proxy server error: {}", e);
+            }
+        });
+
+        log::info!("Proxying backend requests on http://127.0.0.1:{}", port);
+        Ok(proxy)
+    }
+
+    pub fn info(&self) -> ProxyInfo {
+        ProxyInfo {
+            url: format!("http://127.0.0.1:{}", self.port),
+            token: self.token.clone(),
+        }
+    }
+
+    fn authenticated(&self, headers: &HeaderMap, query: Option<&str>) -> bool {
+        let bearer = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        // EventSource can't set headers, so SSE clients may pass the token in the query
+        let query_token = query.and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("chimera_token=")));
+        bearer.or(query_token).is_some_and(|token| token == self.token)
+    }
+
+    fn record(&self, entry: ProxyLogEntry) {
+        let mut log = self.log.lock().unwrap();
+        log.push_back(entry);
+        if log.len() > LOG_CAPACITY {
+            log.pop_front();
+        }
+    }
+
+    /// Most recent requests first
+    pub fn log(&self, limit: Option<usize>) -> Vec<ProxyLogEntry> {
+        let log = self.log.lock().unwrap();
+        log.iter().rev().take(limit.unwrap_or(LOG_CAPACITY)).cloned().collect()
+    }
+
+    pub fn clear_log(&self) {
+        self.log.lock().unwrap().clear();
+    }
+
+    /// Forward a request to the backend, logging it. Returns the backend's response
+    /// with its body unread.
+    async fn forward(
+        &self,
+        source: &str,
+        method: Method,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, String> {
+        let started = Instant::now();
+        let mut entry = ProxyLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            source: source.to_string(),
+            method: method.to_string(),
+            path: path_and_query.to_string(),
+            status: None,
+            duration_ms: 0,
+            request_bytes: body.len(),
+            response_bytes: None,
+            error: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let result = async {
+            let base_url = crate::python_backend::wait_for_url(&self.app_handle).await?;
+            let mut request = self
+                .client
+                .request(method, format!("{}/{}", base_url, path_and_query.trim_start_matches('/')));
+            for (name, value) in headers {
+                if !HOP_HEADERS.contains(&name.as_str()) {
+                    request = request.header(name, value);
+                }
+            }
+            request
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| format!("Backend request failed: {}", e))
+        }
+        .await;
+
+        entry.duration_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(response) => {
+                entry.status = Some(response.status().as_u16());
+                entry.response_bytes = response.content_length();
+            }
+            Err(e) => entry.error = Some(e.clone()),
+        }
+        log::debug!("{} {} {} -> {:?} in {}ms", entry.source, entry.method, entry.path, entry.status, entry.duration_ms);
+        self.record(entry);
+
+        result
+    }
+
+    /// Send a request through the proxy from Rust, reading the whole response
+    pub async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+        headers: HashMap<String, String>,
+    ) -> Result<ProxyResponse, String> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| format!("Invalid header {}: {}", name, e))?;
+            let value = HeaderValue::from_str(&value).map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+            header_map.insert(name, value);
+        }
+        let body = match body {
+            Some(body) => {
+                header_map
+                    .entry("content-type")
+                    .or_insert(HeaderValue::from_static("application/json"));
+                serde_json::to_vec(&body).map_err(|e| format!("Failed to serialize request body: {}", e))?
+            }
+            None => Vec::new(),
+        };
+
+        let response = self.forward("command", method, path, &header_map, body).await?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read backend response: {}", e))?;
+
+        Ok(ProxyResponse { status, headers, body })
+    }
+}
+
+/// CORS headers, so the webview's origin may call the proxy port
+fn allow_cors(headers: &mut HeaderMap) {
+    headers.insert("access-control-allow-origin", HeaderValue::from_static("*"));
+    headers.insert("access-control-allow-headers", HeaderValue::from_static("*"));
+    headers.insert("access-control-allow-methods", HeaderValue::from_static("*"));
+    headers.insert("access-control-expose-headers", HeaderValue::from_static("x-chimera-run-id"));
+}
+
+async fn handle(State(proxy): State<Arc<BackendProxy>>, request: Request) -> Response {
+    // Preflights carry no credentials
+    if request.method() == Method::OPTIONS {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        allow_cors(response.headers_mut());
+        return response;
+    }
+
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    if !proxy.authenticated(request.headers(), request.uri().query()) {
+        let mut response = StatusCode::UNAUTHORIZED.into_response();
+        allow_cors(response.headers_mut());
+        return response;
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(body) => body.to_vec(),
+        Err(e) => return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response(),
+    };
+    let (mut method, mut path_and_query, mut headers, mut body) = (parts.method, path_and_query, parts.headers, body);
+
+    // `POST /_chimera/runs/{run_id}/resume` re-issues an interrupted run once the
+    // backend is healthy, streaming it back like the original
+    let tracker = proxy.app_handle.state::<Arc<RunTracker>>().inner().clone();
+    if let Some(run_id) = path_and_query
+        .strip_prefix(RESUME_PREFIX)
+        .and_then(|rest| rest.strip_suffix("/resume"))
+    {
+        if let Err(e) = crate::run_queue::wait_for_backend(&proxy.app_handle).await {
+            let mut response = (StatusCode::SERVICE_UNAVAILABLE, e).into_response();
+            allow_cors(response.headers_mut());
+            return response;
+        }
+        let original = match tracker.take_resumable(run_id) {
+            Ok(original) => original,
+            Err(e) => {
+                let mut response = (StatusCode::CONFLICT, e).into_response();
+                allow_cors(response.headers_mut());
+                return response;
+            }
+        };
+        log::info!("Re-issuing interrupted run {}", run_id);
+        (method, path_and_query, body) = (Method::POST, "/stream".to_string(), original);
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+    }
+
+    // Agent runs are tracked so a backend crash mid-stream can be recovered
+    let mut run = (method == Method::POST && path_and_query.split('?').next() == Some("/stream"))
+        .then(|| tracker.begin(&body))
+        .flatten()
+        .map(|run_id| RunGuard::new(tracker, run_id));
+
+    let upstream = match proxy
+        .forward("webview", method, &path_and_query, &headers, body)
+        .await
+    {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            let mut response = (StatusCode::BAD_GATEWAY, e).into_response();
+            allow_cors(response.headers_mut());
+            return response;
+        }
+    };
+
+    let mut response = Response::builder().status(upstream.status().as_u16());
+    if let Some(headers) = response.headers_mut() {
+        for (name, value) in upstream.headers() {
+            if !HOP_HEADERS.contains(&name.as_str()) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        allow_cors(headers);
+        if let Some(run) = &run {
+            if let Ok(run_id) = HeaderValue::from_str(run.run_id()) {
+                headers.insert("x-chimera-run-id", run_id);
+            }
+        }
+    }
+
+    // Stream the body through as it arrives, so SSE works
+    let stream = upstream.bytes_stream().map(move |chunk| {
+        if let Some(run) = &mut run {
+            match &chunk {
+                Ok(bytes) => run.observe(bytes),
+                Err(e) => run.interrupt(format!("Backend stream interrupted: {}", e)),
+            }
+        }
+        chunk.map_err(std::io::Error::other)
+    });
+    response
+        .body(Body::from_stream(stream))
+        .unwrap_or_else(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tokio::sync::{oneshot, Notify};
+
+use crate::event_bus::EventBus;
+use crate::python_backend::PythonBackend;
+
+/// Requests held while the backend is down; more are refused
+const MAX_PENDING: usize = 200;
+
+/// Requests still waiting after this long fail instead of replaying
+const MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// How often the queue checks whether the backend is back
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A buffered request
+struct QueuedRequest {
+    id: String,
+    method: reqwest::Method,
+    path: String,
+    body: Option<serde_json::Value>,
+    queued_at: Instant,
+    /// Gone if the caller stopped waiting; the request is still replayed
+    reply: Option<oneshot::Sender<Result<serde_json::Value, String>>>,
+}
+
+/// Queue state, as sent in `backend-queue-status` events
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    /// `online` or `buffering`
+    pub state: String,
+    pub pending: usize,
+    /// Age of the oldest buffered request
+    pub oldest_secs: Option<u64>,
+}
+
+/// Why a request couldn't be delivered
+enum SendError {
+    /// The backend isn't reachable; worth retrying once it's healthy
+    Unavailable(String),
+    /// The backend answered with an error, or the request itself is bad
+    Failed(String),
+}
+
+/// Non-streaming backend calls, buffered while the backend is restarting or down
+/// and replayed in order once it's healthy again
+pub struct BackendQueue {
+    pending: Mutex<VecDeque<QueuedRequest>>,
+    wake: Notify,
+    client: reqwest::Client,
+    bus: Arc<EventBus>,
+}
+
+impl BackendQueue {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            pending: Mutex::new(VecDeque::new()),
+            wake: Notify::new(),
+            client: reqwest::Client::new(),
+            bus,
+        }
+    }
+
+    pub fn status(&self) -> QueueStatus {
+        let pending = self.pending.lock().unwrap();
+        QueueStatus {
+            state: if pending.is_empty() { "online" } else { "buffering" }.to_string(),
+            pending: pending.len(),
+            oldest_secs: pending.front().map(|request| request.queued_at.elapsed().as_secs()),
+        }
+    }
+
+    fn publish_status(&self) {
+        self.bus.publish("backend-queue-status", self.status());
+    }
+
+    /// Base URL of the backend if it's up and healthy
+    fn healthy_url(app_handle: &tauri::AppHandle) -> Option<String> {
+        if let Some(url) = crate::mock_backend::url(app_handle) {
+            return Some(url);
+        }
+        let backend = app_handle.try_state::<Arc<PythonBackend>>()?;
+        (backend.status().state == "running").then(|| backend.base_url())
+    }
+
+    async fn send(
+        &self,
+        base_url: &str,
+        method: &reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value, SendError> {
+        let url = format!("{}/{}", base_url, path.trim_start_matches('/'));
+        let mut request = self.client.request(method.clone(), &url).timeout(REQUEST_TIMEOUT);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_connect() {
+                SendError::Unavailable(e.to_string())
+            } else {
+                SendError::Failed(format!("Backend request failed: {}", e))
+            }
+        })?;
+
+        let status = response.status();
+        // 502-504 come from the backend being mid-restart
+        if matches!(status.as_u16(), 502..=504) {
+            return Err(SendError::Unavailable(format!("Backend returned {}", status)));
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| SendError::Failed(format!("Failed to read backend response: {}", e)))?;
+        if !status.is_success() {
+            return Err(SendError::Failed(format!("Backend returned {}: {}", status, text)));
+        }
+        Ok(serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text)))
+    }
+
+    /// Send a request to the backend, buffering it until the backend is healthy if
+    /// it's down. Only the backend's own errors, or a request expiring in the
+    /// queue, are returned as errors.
+    pub async fn request(
+        &self,
+        app_handle: &tauri::AppHandle,
+        method: &str,
+        path: String,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+        // Go straight through unless something is already waiting, to keep order
+        let queue_empty = self.pending.lock().unwrap().is_empty();
+        if queue_empty {
+            if let Some(base_url) = Self::healthy_url(app_handle) {
+                match self.send(&base_url, &method, &path, body.as_ref()).await {
+                    Ok(value) => return Ok(value),
+                    Err(SendError::Failed(e)) => return Err(e),
+                    Err(SendError::Unavailable(e)) => log::info!("Backend unavailable ({}); buffering {}", e, path),
+                }
+            }
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.len() >= MAX_PENDING {
+                return Err(format!("Backend is unavailable and {} requests are already waiting", MAX_PENDING));
+            }
+            pending.push_back(QueuedRequest {
+                id: uuid::Uuid::new_v4().to_string(),
+                method,
+                path,
+                body,
+                queued_at: Instant::now(),
+                reply: Some(sender),
+            });
+        }
+        self.publish_status();
+        self.wake.notify_one();
+
+        receiver
+            .await
+            .map_err(|_| "Backend request was dropped from the queue".to_string())?
+    }
+
+    /// Replay buffered requests whenever the backend is healthy
+    pub fn start(self: &Arc<Self>, app_handle: tauri::AppHandle) {
+        let queue = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let Some(queue) = queue.upgrade() else { break };
+                tokio::select! {
+                    _ = queue.wake.notified() => {}
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                }
+                queue.expire();
+                if let Some(base_url) = Self::healthy_url(&app_handle) {
+                    queue.drain(&base_url).await;
+                }
+            }
+        });
+    }
+
+    /// Fail requests that have waited too long
+    fn expire(&self) {
+        let expired = {
+            let mut pending = self.pending.lock().unwrap();
+            let (expired, kept): (VecDeque<_>, VecDeque<_>) =
+                pending.drain(..).partition(|request| request.queued_at.elapsed() >= MAX_AGE);
+            *pending = kept;
+            expired
+        };
+        if expired.is_empty() {
+            return;
+        }
+
+        for mut request in expired {
+            log::warn!("Backend request {} to {} expired in the queue", request.id, request.path);
+            if let Some(reply) = request.reply.take() {
+                let _ = reply.send(Err(format!(
+                    "Backend was unavailable for over {} minutes",
+                    MAX_AGE.as_secs() / 60
+                )));
+            }
+        }
+        self.publish_status();
+    }
+
+    /// Send buffered requests in order, stopping if the backend drops out again
+    async fn drain(&self, base_url: &str) {
+        let mut replayed = 0;
+        loop {
+            let Some(mut request) = self.pending.lock().unwrap().pop_front() else { break };
+
+            let result = match self.send(base_url, &request.method, &request.path, request.body.as_ref()).await {
+                Ok(value) => Ok(value),
+                Err(SendError::Failed(e)) => Err(e),
+                Err(SendError::Unavailable(e)) => {
+                    log::info!("Backend unavailable again ({}); {} still buffered", e, self.status().pending + 1);
+                    self.pending.lock().unwrap().push_front(request);
+                    break;
+                }
+            };
+
+            replayed += 1;
+            if let Some(reply) = request.reply.take() {
+                let _ = reply.send(result);
+            }
+            self.publish_status();
+        }
+        if replayed > 0 {
+            log::info!("Replayed {} buffered backend request(s)", replayed);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::settings::SettingsStore;
+use crate::tasks::{TaskManager, TaskProgress};
+use crate::{filesystem, snapshots};
+
+/// `reason` of snapshots taken on the backup schedule; only these are pruned
+pub const SCHEDULED: &str = "scheduled";
+
+/// `reason` of the snapshot taken just before a backup is restored
+pub const PRE_RESTORE: &str = "pre-restore";
+
+/// How often the schedule is checked, so settings changes apply without a restart
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether a scheduled backup is due: none has been taken yet, or the newest is
+/// older than `interval_hours`
+fn due(interval_hours: u32) -> Result<bool, String> {
+    let Some(newest) = snapshots::list()?.into_iter().find(|s| s.reason == SCHEDULED) else {
+        return Ok(true);
+    };
+    let taken = chrono::DateTime::parse_from_rfc3339(&newest.created_at)
+        .map_err(|e| format!("Invalid backup time {}: {}", newest.created_at, e))?;
+    Ok(chrono::Utc::now().signed_duration_since(taken) >= chrono::Duration::hours(interval_hours.into()))
+}
+
+/// Take a scheduled backup if one is due, then prune old ones
+async fn run_once(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let Some(settings) = app_handle.try_state::<Arc<SettingsStore>>() else {
+        return Ok(());
+    };
+    let backup = settings.get().backup;
+    if backup.interval_hours == 0 || !filesystem::blocking(move || due(backup.interval_hours)).await? {
+        return Ok(());
+    }
+
+    if let Some(appends) = app_handle.try_state::<Arc<AppendBuffer>>() {
+        appends.flush_all().await;
+    }
+    let create = |progress: TaskProgress| async move {
+        Ok(filesystem::blocking(move || snapshots::create(SCHEDULED, Some(&progress))).await?)
+    };
+    let snapshot = match app_handle.try_state::<Arc<TaskManager>>() {
+        Some(tasks) => tasks.run("backup", "Scheduled backup", create).await.map_err(|e| e.to_string())?,
+        None => filesystem::blocking(|| snapshots::create(SCHEDULED, None)).await?,
+    };
+    let pruned = filesystem::blocking(move || snapshots::prune(SCHEDULED, backup.keep)).await?;
+
+    if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+        bus.publish("backup-created", serde_json::json!({ "backup": snapshot, "pruned": pruned }));
+    }
+    Ok(())
+}
+
+/// Back up threads and blueprints on the interval in settings for as long as the
+/// app runs. Backups are snapshots in `backups/<timestamp>/` in the data directory.
+pub fn start(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // The first check waits, leaving startup to finish
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + CHECK_INTERVAL, CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_once(&app_handle).await {
+                log::error!("Scheduled backup failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::export::ExporterRegistry;
+use crate::filesystem;
+
+/// Finished batches remembered for `get_batch_operation`
+const FINISHED_HISTORY: usize = 20;
+
+/// What to do to every thread in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BatchOp {
+    /// Move to the trash
+    Delete,
+    Archive,
+    Unarchive,
+    /// Add tags, keeping existing ones
+    Tag { tags: Vec<String> },
+    /// Remove tags
+    Untag { tags: Vec<String> },
+    /// Write each thread to `dest_dir` as `<thread_id>.<extension>`
+    Export { format: String, dest_dir: String },
+}
+
+impl BatchOp {
+    fn name(&self) -> &'static str {
+        match self {
+            BatchOp::Delete => "delete",
+            BatchOp::Archive => "archive",
+            BatchOp::Unarchive => "unarchive",
+            BatchOp::Tag { .. } => "tag",
+            BatchOp::Untag { .. } => "untag",
+            BatchOp::Export { .. } => "export",
+        }
+    }
+}
+
+/// Outcome for one thread
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    pub thread_id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    /// Where an export was written
+    pub output: Option<String>,
+}
+
+/// A batch and its progress, as sent in `batch-finished` events
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchStatus {
+    pub id: String,
+    pub op: String,
+    /// `running`, `finished` or `cancelled`
+    pub state: String,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Runs operations over many threads in the background, reporting progress
+/// on the event bus
+pub struct BatchManager {
+    batches: Mutex<HashMap<String, BatchStatus>>,
+    cancelled: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    bus: Arc<EventBus>,
+    appends: Arc<AppendBuffer>,
+    exporters: Arc<ExporterRegistry>,
+}
+
+impl BatchManager {
+    pub fn new(bus: Arc<EventBus>, appends: Arc<AppendBuffer>, exporters: Arc<ExporterRegistry>) -> Self {
+        Self {
+            batches: Mutex::new(HashMap::new()),
+            cancelled: Mutex::new(HashMap::new()),
+            bus,
+            appends,
+            exporters,
+        }
+    }
+
+    /// Start a batch, returning its id. Progress arrives as `batch-progress`
+    /// events and the final status as `batch-finished`.
+    pub fn start(self: &Arc<Self>, op: BatchOp, thread_ids: Vec<String>) -> Result<String, String> {
+        if thread_ids.is_empty() {
+            return Err("No threads given".to_string());
+        }
+        if let BatchOp::Export { format, dest_dir } = &op {
+            if !self.exporters.list().iter().any(|info| &info.id == format) {
+                return Err(format!("Unknown export format: {}", format));
+            }
+            std::fs::create_dir_all(dest_dir).map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let status = BatchStatus {
+            id: id.clone(),
+            op: op.name().to_string(),
+            state: "running".to_string(),
+            total: thread_ids.len(),
+            completed: 0,
+            failed: 0,
+            results: Vec::new(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+        };
+        log::info!("Starting batch {} ({} over {} threads)", id, status.op, status.total);
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.batches.lock().unwrap().insert(id.clone(), status);
+        self.cancelled.lock().unwrap().insert(id.clone(), cancel.clone());
+
+        let manager = self.clone();
+        let batch_id = id.clone();
+        tauri::async_runtime::spawn(async move {
+            manager.run(&batch_id, op, thread_ids, &cancel).await;
+        });
+
+        Ok(id)
+    }
+
+    async fn run(&self, batch_id: &str, op: BatchOp, thread_ids: Vec<String>, cancel: &AtomicBool) {
+        for thread_id in thread_ids {
+            if cancel.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let result = match self.apply(&op, &thread_id).await {
+                Ok(output) => BatchItemResult {
+                    thread_id,
+                    ok: true,
+                    error: None,
+                    output,
+                },
+                Err(e) => {
+                    log::warn!("Batch {} failed on thread {}: {}", batch_id, thread_id, e);
+                    BatchItemResult {
+                        thread_id,
+                        ok: false,
+                        error: Some(e),
+                        output: None,
+                    }
+                }
+            };
+
+            let (completed, total) = {
+                let mut batches = self.batches.lock().unwrap();
+                let Some(status) = batches.get_mut(batch_id) else { return };
+                status.completed += 1;
+                if !result.ok {
+                    status.failed += 1;
+                }
+                status.results.push(result.clone());
+                (status.completed, status.total)
+            };
+            self.bus.publish(
+                "batch-progress",
+                serde_json::json!({
+                    "batch_id": batch_id,
+                    "thread_id": result.thread_id,
+                    "ok": result.ok,
+                    "error": result.error,
+                    "completed": completed,
+                    "total": total,
+                }),
+            );
+        }
+
+        self.cancelled.lock().unwrap().remove(batch_id);
+        let status = {
+            let mut batches = self.batches.lock().unwrap();
+            let Some(status) = batches.get_mut(batch_id) else { return };
+            status.state = if cancel.load(Ordering::SeqCst) { "cancelled" } else { "finished" }.to_string();
+            status.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            let status = status.clone();
+
+            // Forget the oldest finished batches
+            let mut finished: Vec<(String, String)> = batches
+                .values()
+                .filter_map(|b| b.finished_at.clone().map(|at| (at, b.id.clone())))
+                .collect();
+            if finished.len() > FINISHED_HISTORY {
+                finished.sort();
+                for (_, id) in &finished[..finished.len() - FINISHED_HISTORY] {
+                    batches.remove(id);
+                }
+            }
+            status
+        };
+
+        log::info!(
+            "Batch {} {}: {} of {} threads, {} failed",
+            batch_id,
+            status.state,
+            status.completed,
+            status.total,
+            status.failed
+        );
+        self.bus.publish("batch-finished", &status);
+    }
+
+    /// Apply the operation to one thread
+    async fn apply(&self, op: &BatchOp, thread_id: &str) -> Result<Option<String>, String> {
+        let changed = |change: &str| {
+            self.bus
+                .publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": change }));
+        };
+
+        match op {
+            BatchOp::Delete => {
+                self.appends.close(thread_id).await?;
+                filesystem::delete_thread(thread_id.to_string(), "ui").await?;
+                changed("deleted");
+                Ok(None)
+            }
+            BatchOp::Archive | BatchOp::Unarchive => {
+                let archived = matches!(op, BatchOp::Archive);
+                self.appends.flush(thread_id).await?;
+                let events = filesystem::load_thread(thread_id.to_string()).await?;
+                if filesystem::thread_archived(&events) == archived {
+                    return Ok(None);
+                }
+                filesystem::set_thread_archived(thread_id.to_string(), archived).await?;
+                changed(if archived { "archived" } else { "unarchived" });
+                Ok(None)
+            }
+            BatchOp::Tag { tags } | BatchOp::Untag { tags } => {
+                self.appends.flush(thread_id).await?;
+                let events = filesystem::load_thread(thread_id.to_string()).await?;
+                let mut current = filesystem::thread_tags(&events);
+                if matches!(op, BatchOp::Tag { .. }) {
+                    current.extend(tags.iter().cloned());
+                } else {
+                    current.retain(|tag| !tags.contains(tag));
+                }
+                filesystem::set_thread_tags(thread_id.to_string(), current).await?;
+                changed("tagged");
+                Ok(None)
+            }
+            BatchOp::Export { format, dest_dir } => {
+                let extension = self
+                    .exporters
+                    .list()
+                    .into_iter()
+                    .find(|info| &info.id == format)
+                    .map(|info| info.extension)
+                    .unwrap_or_else(|| "txt".to_string());
+                let dest = std::path::Path::new(dest_dir).join(format!("{}.{}", thread_id, extension));
+                self.appends.flush(thread_id).await?;
+                let report = self
+                    .exporters
+                    .export_thread(thread_id.to_string(), format, dest.to_string_lossy().into_owned())
+                    .await?;
+                Ok(Some(report.dest_path))
+            }
+        }
+    }
+
+    pub fn get(&self, batch_id: &str) -> Result<BatchStatus, String> {
+        self.batches
+            .lock()
+            .unwrap()
+            .get(batch_id)
+            .cloned()
+            .ok_or_else(|| format!("Batch not found: {}", batch_id))
+    }
+
+    /// Stop a running batch after the thread in progress
+    pub fn cancel(&self, batch_id: &str) -> Result<(), String> {
+        self.cancelled
+            .lock()
+            .unwrap()
+            .get(batch_id)
+            .ok_or_else(|| format!("No running batch {}", batch_id))?
+            .store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::filesystem::{self, FsError};
+
+/// Key of a reference stub: `{"$blob": "<sha256>"}` stands in for the stored value
+pub const REF_KEY: &str = "$blob";
+
+/// Key of an attachment stub: `{"$attachment": "<sha256>", "bytes": n, "preview": "..."}`
+/// stands in for a payload too large to keep inline. Unlike `$blob` references these
+/// are not rehydrated on load; fetch them with `get_attachment`.
+pub const ATTACHMENT_KEY: &str = "$attachment";
+
+/// Default largest serialized event kept inline
+const DEFAULT_MAX_EVENT_BYTES: usize = 1024 * 1024;
+
+/// Characters of a spilled payload kept in its stub
+const PREVIEW_CHARS: usize = 1024;
+
+/// Fields that identify an event and always stay inline
+pub const INLINE_FIELDS: [&str; 3] = ["type", "id", "toolCallId"];
+
+/// Largest serialized event kept inline (`CHIMERA_MAX_EVENT_BYTES`, default 1 MB)
+pub fn max_event_bytes() -> usize {
+    static LIMIT: OnceLock<usize> = OnceLock::new();
+    *LIMIT.get_or_init(|| {
+        std::env::var("CHIMERA_MAX_EVENT_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(DEFAULT_MAX_EVENT_BYTES)
+    })
+}
+
+/// Get the content-addressed blob directory
+pub fn get_blobs_dir() -> Result<PathBuf, FsError> {
+    Ok(filesystem::get_data_dir()?.join("blobs"))
+}
+
+fn blob_path(hash: &str) -> Result<PathBuf, FsError> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(FsError::invalid(format!("Invalid blob hash: {}", hash)));
+    }
+    Ok(get_blobs_dir()?.join(format!("{}.json", hash)))
+}
+
+/// The hash a value is referencing, if it is a reference stub
+pub fn blob_ref(value: &serde_json::Value) -> Option<&str> {
+    let object = value.as_object()?;
+    if object.len() != 1 {
+        return None;
+    }
+    object.get(REF_KEY)?.as_str()
+}
+
+/// Build a reference stub for `hash`
+pub fn make_ref(hash: &str) -> serde_json::Value {
+    serde_json::json!({ REF_KEY: hash })
+}
+
+/// Store already-serialized JSON under its hash. Returns the hash and whether a new
+/// blob was written, as opposed to an identical one already existing (blocking).
+pub fn put_bytes(json: &[u8]) -> Result<(String, bool), FsError> {
+    let hash = format!("{:x}", Sha256::digest(json));
+    let path = blob_path(&hash)?;
+    if path.exists() {
+        return Ok((hash, false));
+    }
+
+    std::fs::create_dir_all(get_blobs_dir()?).map_err(|e| FsError::io("create blobs directory", e))?;
+
+    // Write beside the blob and rename, so a blob file is always complete
+    let temp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&temp, json).map_err(|e| FsError::io("write blob", e))?;
+    std::fs::rename(&temp, &path).map_err(|e| FsError::io("store blob", e))?;
+
+    Ok((hash, true))
+}
+
+/// Read a stored value (blocking)
+pub fn get(hash: &str) -> Result<serde_json::Value, FsError> {
+    let content = std::fs::read(blob_path(hash)?).map_err(|e| FsError::io(&format!("read blob {}", hash), e))?;
+    serde_json::from_slice(&content).map_err(|e| FsError::invalid(format!("Failed to parse blob {}: {}", hash, e)))
+}
+
+/// Whether any event has a top-level field stored as a blob
+fn has_refs(events: &[serde_json::Value]) -> bool {
+    events
+        .iter()
+        .filter_map(|event| event.as_object())
+        .any(|event| event.values().any(|value| blob_ref(value).is_some()))
+}
+
+/// Replace reference stubs in events' top-level fields with the stored values.
+/// Missing blobs are logged and their stubs left in place.
+pub async fn rehydrate(mut events: Vec<serde_json::Value>) -> Result<Vec<serde_json::Value>, FsError> {
+    if !has_refs(&events) {
+        return Ok(events);
+    }
+
+    filesystem::blocking_fs(move || {
+        let mut loaded: HashMap<String, serde_json::Value> = HashMap::new();
+        for event in events.iter_mut().filter_map(|event| event.as_object_mut()) {
+            for value in event.values_mut() {
+                let Some(hash) = blob_ref(value).map(str::to_string) else { continue };
+                if !loaded.contains_key(&hash) {
+                    match get(&hash) {
+                        Ok(blob) => {
+                            loaded.insert(hash.clone(), blob);
+                        }
+                        Err(e) => {
+                            log::warn!("{}", e);
+                            continue;
+                        }
+                    }
+                }
+                *value = loaded[&hash].clone();
+            }
+        }
+        Ok(events)
+    })
+    .await
+}
+
+/// Move an oversized event's largest top-level fields into attachment blobs until
+/// the event fits in `max_event_bytes` (blocking)
+pub fn spill(mut event: serde_json::Value) -> Result<serde_json::Value, FsError> {
+    let limit = max_event_bytes();
+    let Some(fields) = event.as_object_mut() else { return Ok(event) };
+
+    let mut sized: Vec<(String, Vec<u8>)> = fields
+        .iter()
+        .filter(|(key, _)| !INLINE_FIELDS.contains(&key.as_str()))
+        .map(|(key, value)| Ok((key.clone(), serde_json::to_vec(value)?)))
+        .collect::<Result<_, serde_json::Error>>()
+        .map_err(|e| FsError::invalid(format!("Failed to serialize event: {}", e)))?;
+    sized.sort_by_key(|(_, json)| std::cmp::Reverse(json.len()));
+
+    let mut total: usize = fields
+        .iter()
+        .filter(|(key, _)| INLINE_FIELDS.contains(&key.as_str()))
+        .map(|(key, value)| key.len() + value.to_string().len())
+        .chain(sized.iter().map(|(key, json)| key.len() + json.len()))
+        .sum();
+
+    for (key, json) in sized {
+        if total <= limit {
+            break;
+        }
+
+        let (hash, _) = put_bytes(&json)?;
+        let preview: String = match &fields[&key] {
+            serde_json::Value::String(text) => text.chars().take(PREVIEW_CHARS).collect(),
+            _ => String::from_utf8_lossy(&json[..json.len().min(PREVIEW_CHARS * 4)])
+                .chars()
+                .take(PREVIEW_CHARS)
+                .collect(),
+        };
+        let stub = serde_json::json!({
+            ATTACHMENT_KEY: hash,
+            "bytes": json.len(),
+            "preview": preview,
+        });
+
+        total = total - json.len() + serde_json::to_vec(&stub).map_or(0, |stub| stub.len());
+        log::warn!("Spilled {} byte field '{}' of an event into attachment {}", json.len(), key, hash);
+        fields.insert(key, stub);
+    }
+
+    Ok(event)
+}
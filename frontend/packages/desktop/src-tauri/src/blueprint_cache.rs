@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::filesystem::{self, BlueprintMetadata};
+
+/// Parsed metadata plus the file stamp it was parsed from
+struct CachedBlueprint {
+    modified: Option<SystemTime>,
+    size: u64,
+    metadata: Option<BlueprintMetadata>,
+}
+
+impl CachedBlueprint {
+    fn is_current(&self, modified: Option<SystemTime>, size: u64) -> bool {
+        self.modified.is_some() && self.modified == modified && self.size == size
+    }
+}
+
+/// Blueprint metadata cache keyed by path, invalidated by mtime and size
+pub struct BlueprintCache {
+    entries: Mutex<HashMap<PathBuf, CachedBlueprint>>,
+}
+
+impl BlueprintCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// List blueprints, re-parsing only files that changed since the last call
+    pub async fn list(&self) -> Result<Vec<BlueprintMetadata>, String> {
+        let files = filesystem::blocking(|| {
+            Ok(filesystem::list_blueprint_files()?
+                .into_iter()
+                .filter_map(|path| match std::fs::metadata(&path) {
+                    Ok(stat) => Some((path, stat.modified().ok(), stat.len())),
+                    Err(e) => {
+                        log::warn!("Failed to stat blueprint {}: {}", path.display(), e);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>())
+        })
+        .await?;
+
+        let stale: Vec<PathBuf> = {
+            let entries = self.entries.lock().unwrap();
+            files
+                .iter()
+                .filter(|(path, modified, size)| !entries.get(path).is_some_and(|c| c.is_current(*modified, *size)))
+                .map(|(path, _, _)| path.clone())
+                .collect()
+        };
+
+        let parsed_count = stale.len();
+        let mut parsed: HashMap<PathBuf, Option<BlueprintMetadata>> =
+            filesystem::read_blueprints(stale).await.into_iter().collect();
+
+        let mut entries = self.entries.lock().unwrap();
+        let mut refreshed = HashMap::with_capacity(files.len());
+        let mut blueprints = Vec::with_capacity(files.len());
+
+        for (path, modified, size) in files {
+            let entry = match parsed.remove(&path) {
+                Some(metadata) => CachedBlueprint { modified, size, metadata },
+                None => match entries.remove(&path) {
+                    Some(cached) => cached,
+                    // Dropped by a concurrent listing; picked up next time
+                    None => continue,
+                },
+            };
+
+            if let Some(metadata) = &entry.metadata {
+                blueprints.push(metadata.clone());
+            }
+            refreshed.insert(path, entry);
+        }
+
+        // Anything left over was deleted from disk
+        *entries = refreshed;
+
+        if parsed_count > 0 {
+            log::debug!("Parsed {} blueprint(s), {} cached", parsed_count, entries.len().saturating_sub(parsed_count));
+        }
+
+        Ok(blueprints)
+    }
+
+    /// Populate the cache so the first picker open is cheap
+    pub async fn prime(&self) {
+        match self.list().await {
+            Ok(blueprints) => log::info!("Cached metadata for {} blueprint(s)", blueprints.len()),
+            Err(e) => log::warn!("Failed to prime blueprint cache: {}", e),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::Serialize;
+
+/// One saved version of a blueprint
+#[derive(Debug, Clone, Serialize)]
+pub struct BlueprintVersion {
+    pub commit: String,
+    pub message: String,
+    pub committed_at: String,
+}
+
+/// Whether saves are being versioned
+pub fn enabled() -> bool {
+    cfg!(feature = "blueprint-history")
+}
+
+#[cfg(feature = "blueprint-history")]
+pub use repo::{history, record, restore};
+
+/// Saves are committed to a repository at `blueprints/.git`, one commit per change
+/// with the blueprint's file as it was left
+#[cfg(feature = "blueprint-history")]
+mod repo {
+    use git2::{IndexAddOption, Oid, Repository, Signature, Sort};
+    use std::path::Path;
+
+    use super::BlueprintVersion;
+    use crate::filesystem;
+
+    const AUTHOR: &str = "Chimera Desktop";
+    const EMAIL: &str = "chimera-desktop@localhost";
+
+    fn open() -> Result<Repository, String> {
+        let dir = filesystem::get_blueprints_dir()?;
+        Repository::open(&dir)
+            .or_else(|_| Repository::init(&dir))
+            .map_err(|e| format!("Failed to open blueprint history: {}", e))
+    }
+
+    fn file_name(blueprint_id: &str) -> Result<String, String> {
+        let path = filesystem::get_blueprint_path(blueprint_id)?;
+        Ok(path.file_name().unwrap_or_default().to_string_lossy().into_owned())
+    }
+
+    /// Commit the blueprint's file as it is now, or its removal. Nothing is
+    /// committed when it hasn't changed since the last commit (blocking).
+    pub fn record(blueprint_id: &str, message: &str) -> Result<(), String> {
+        let repo = open()?;
+        let name = file_name(blueprint_id)?;
+        let mut index = repo.index().map_err(|e| format!("Failed to read blueprint history index: {}", e))?;
+        let staged = if repo.workdir().is_some_and(|dir| dir.join(&name).exists()) {
+            index.add_all([&name], IndexAddOption::DEFAULT, None)
+        } else {
+            index.remove_path(Path::new(&name))
+        };
+        staged
+            .and_then(|_| index.write())
+            .map_err(|e| format!("Failed to stage blueprint {}: {}", blueprint_id, e))?;
+
+        let tree_id = index.write_tree().map_err(|e| format!("Failed to write blueprint history: {}", e))?;
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        if parent.as_ref().is_some_and(|parent| parent.tree_id() == tree_id) {
+            return Ok(());
+        }
+
+        let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to write blueprint history: {}", e))?;
+        let signature = Signature::now(AUTHOR, EMAIL).map_err(|e| format!("Failed to sign blueprint history: {}", e))?;
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+            .map_err(|e| format!("Failed to commit blueprint {}: {}", blueprint_id, e))?;
+        Ok(())
+    }
+
+    /// Commits that changed the blueprint's file, newest first (blocking)
+    pub fn history(blueprint_id: &str) -> Result<Vec<BlueprintVersion>, String> {
+        let repo = open()?;
+        let name = file_name(blueprint_id)?;
+        if repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut walk = repo.revwalk().map_err(|e| format!("Failed to read blueprint history: {}", e))?;
+        walk.set_sorting(Sort::TIME)
+            .and_then(|_| walk.push_head())
+            .map_err(|e| format!("Failed to read blueprint history: {}", e))?;
+
+        let blob_at = |commit: &git2::Commit| commit.tree().ok()?.get_name(&name).map(|entry| entry.id());
+        let mut versions = Vec::new();
+        for oid in walk.flatten() {
+            let Ok(commit) = repo.find_commit(oid) else { continue };
+            let blob = blob_at(&commit);
+            let parent_blob = commit.parent(0).ok().and_then(|parent| blob_at(&parent));
+            // Only commits that saved this file; its removal isn't a version to restore
+            if blob.is_none() || blob == parent_blob {
+                continue;
+            }
+            versions.push(BlueprintVersion {
+                commit: oid.to_string(),
+                message: commit.summary().unwrap_or_default().to_string(),
+                committed_at: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                    .unwrap_or_default()
+                    .to_rfc3339(),
+            });
+        }
+        Ok(versions)
+    }
+
+    /// Put the blueprint's file back as it was at `commit`, recording that as a
+    /// new version (blocking)
+    pub fn restore(blueprint_id: &str, commit: &str) -> Result<(), String> {
+        let repo = open()?;
+        let name = file_name(blueprint_id)?;
+        let oid = Oid::from_str(commit).map_err(|e| format!("Invalid commit {}: {}", commit, e))?;
+        let tree = repo
+            .find_commit(oid)
+            .and_then(|commit| commit.tree())
+            .map_err(|_| format!("No blueprint version {}", commit))?;
+        let entry = tree
+            .get_name(&name)
+            .ok_or_else(|| format!("Blueprint {} isn't in version {}", blueprint_id, commit))?;
+        let blob = repo
+            .find_blob(entry.id())
+            .map_err(|e| format!("Failed to read blueprint version {}: {}", commit, e))?;
+
+        // Write then rename, so a crash mid-restore leaves the current file intact
+        let path = filesystem::get_blueprint_path(blueprint_id)?;
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, blob.content()).map_err(|e| format!("Failed to write blueprint file: {}", e))?;
+        std::fs::rename(&temp, &path).map_err(|e| format!("Failed to replace blueprint file: {}", e))?;
+
+        let short = &commit[..commit.len().min(7)];
+        record(blueprint_id, &format!("Restore {} to {}", blueprint_id, short))
+    }
+}
+
+#[cfg(not(feature = "blueprint-history"))]
+pub fn record(_blueprint_id: &str, _message: &str) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(feature = "blueprint-history"))]
+pub fn history(_blueprint_id: &str) -> Result<Vec<BlueprintVersion>, String> {
+    Err("Blueprint history needs a build with the blueprint-history feature".to_string())
+}
+
+#[cfg(not(feature = "blueprint-history"))]
+pub fn restore(_blueprint_id: &str, _commit: &str) -> Result<(), String> {
+    Err("Blueprint history needs a build with the blueprint-history feature".to_string())
+}
+
+/// Record a change, logging rather than failing: the save itself already succeeded
+pub async fn record_async(blueprint_id: String, message: String) {
+    if !enabled() {
+        return;
+    }
+    let recorded = crate::filesystem::blocking(move || record(&blueprint_id, &message)).await;
+    if let Err(e) = recorded {
+        log::warn!("Failed to record blueprint history: {}", e);
+    }
+}
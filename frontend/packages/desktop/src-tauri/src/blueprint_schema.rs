@@ -0,0 +1,84 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct ComponentConfig {
+    #[serde(rename = "className")]
+    _class_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum AgentConfig {
+    #[serde(rename = "inline")]
+    Inline {
+        id: String,
+        #[serde(rename = "name")]
+        _name: String,
+        #[serde(rename = "description")]
+        _description: String,
+        #[serde(rename = "basePrompt")]
+        _base_prompt: String,
+        #[serde(rename = "widgets", default)]
+        _widgets: Vec<ComponentConfig>,
+    },
+    #[serde(rename = "reference")]
+    Reference {
+        #[serde(rename = "agentUuid")]
+        _agent_uuid: String,
+        #[serde(rename = "version")]
+        _version: String,
+        #[serde(rename = "widgets", default)]
+        _widgets: Vec<ComponentConfig>,
+    },
+}
+
+#[derive(Deserialize)]
+struct SpaceConfig {
+    agents: Vec<AgentConfig>,
+    #[serde(rename = "widgets", default)]
+    _widgets: Vec<ComponentConfig>,
+}
+
+#[derive(Deserialize)]
+struct BlueprintConfig {
+    space: SpaceConfig,
+}
+
+/// A blueprint file: the `thread-blueprint` header new threads start with,
+/// mirroring `chimera_core.threadprotocol.blueprint`. Extra fields are allowed.
+#[derive(Deserialize)]
+struct BlueprintFile {
+    blueprint: BlueprintConfig,
+}
+
+/// Check a blueprint file's JSON before it's written, so a bad edit can't leave
+/// a blueprint the backend refuses to load
+pub fn validate(blueprint: &serde_json::Value) -> Result<(), String> {
+    if let Some(event_type) = blueprint.get("type") {
+        if event_type != "thread-blueprint" {
+            return Err(format!("Invalid blueprint: type is {}, expected \"thread-blueprint\"", event_type));
+        }
+    }
+
+    let file = BlueprintFile::deserialize(blueprint).map_err(|e| format!("Invalid blueprint: {}", e))?;
+    if file.blueprint.space.agents.is_empty() {
+        return Err("Invalid blueprint: the space has no agents".to_string());
+    }
+
+    let mut ids: Vec<&str> = Vec::new();
+    for agent in &file.blueprint.space.agents {
+        let AgentConfig::Inline { id, .. } = agent else { continue };
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!(
+                "Invalid blueprint: agent id {:?} must contain only letters, digits, hyphens and underscores",
+                id
+            ));
+        }
+        if ids.contains(&id.as_str()) {
+            return Err(format!("Invalid blueprint: duplicate agent id {:?}", id));
+        }
+        ids.push(id);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// `{{name}}`, or `{{name|default}}` for a value used when none is given. Names
+/// start with a letter or underscore; spaces inside the braces are ignored.
+const PLACEHOLDER_REGEX: &str = r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*(?:\|([^}]*))?\}\}";
+
+/// Longest value a variable can be filled in with
+const MAX_VALUE_LEN: usize = 16 * 1024;
+
+fn placeholder_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(PLACEHOLDER_REGEX).expect("valid placeholder regex"))
+}
+
+/// A placeholder to fill in before a thread is created from a blueprint
+#[derive(Debug, Clone, Serialize)]
+pub struct BlueprintVariable {
+    pub name: String,
+    /// Value used when none is given, from the first placeholder that has one
+    pub default: Option<String>,
+    /// How many times it appears
+    pub occurrences: usize,
+}
+
+/// Every string in a blueprint, in document order. Only values are templated,
+/// so keys stay the ones the backend expects.
+fn strings(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => out.push(s.clone()),
+        serde_json::Value::Array(items) => items.iter().for_each(|item| strings(item, out)),
+        serde_json::Value::Object(fields) => fields.values().for_each(|field| strings(field, out)),
+        _ => {}
+    }
+}
+
+/// The placeholders in a blueprint, in the order they first appear
+pub fn extract(blueprint: &serde_json::Value) -> Vec<BlueprintVariable> {
+    let mut texts = Vec::new();
+    strings(blueprint, &mut texts);
+
+    let mut variables: Vec<BlueprintVariable> = Vec::new();
+    for captures in texts.iter().flat_map(|text| placeholder_regex().captures_iter(text)) {
+        let name = &captures[1];
+        let default = captures.get(2).map(|m| m.as_str().trim().to_string());
+        match variables.iter_mut().find(|v| v.name == name) {
+            Some(variable) => {
+                variable.occurrences += 1;
+                if variable.default.is_none() {
+                    variable.default = default;
+                }
+            }
+            None => variables.push(BlueprintVariable {
+                name: name.to_string(),
+                default,
+                occurrences: 1,
+            }),
+        }
+    }
+    variables
+}
+
+/// Check `values` against a blueprint's placeholders: every one without a
+/// default needs a value, and no value may name a placeholder that isn't there
+fn check(variables: &[BlueprintVariable], values: &HashMap<String, String>) -> Result<(), String> {
+    let mut unknown: Vec<&str> = values
+        .keys()
+        .filter(|name| !variables.iter().any(|v| &v.name == *name))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(format!("Unknown blueprint variables: {}", unknown.join(", ")));
+    }
+
+    let missing: Vec<&str> = variables
+        .iter()
+        .filter(|v| v.default.is_none() && values.get(&v.name).is_none_or(|value| value.trim().is_empty()))
+        .map(|v| v.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing values for blueprint variables: {}", missing.join(", ")));
+    }
+
+    for (name, value) in values {
+        if value.len() > MAX_VALUE_LEN {
+            return Err(format!("Value for {} is longer than {} bytes", name, MAX_VALUE_LEN));
+        }
+        if value.chars().any(|c| c.is_control() && c != '\n' && c != '\t') {
+            return Err(format!("Value for {} contains control characters", name));
+        }
+    }
+    Ok(())
+}
+
+fn fill(value: &mut serde_json::Value, values: &HashMap<String, String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let filled = placeholder_regex().replace_all(s, |captures: &Captures| {
+                let given = values.get(&captures[1]).filter(|value| !value.trim().is_empty());
+                match (given, captures.get(2)) {
+                    (Some(value), _) => value.clone(),
+                    (None, Some(default)) => default.as_str().trim().to_string(),
+                    // check() has ruled this out
+                    (None, None) => captures[0].to_string(),
+                }
+            });
+            if let std::borrow::Cow::Owned(filled) = filled {
+                *s = filled;
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| fill(item, values)),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| fill(field, values)),
+        _ => {}
+    }
+}
+
+/// Fill in a blueprint's placeholders, checking the values first and the
+/// resulting blueprint after. Values go into JSON strings, so they can't change
+/// the blueprint's structure.
+pub fn substitute(blueprint_json: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut blueprint: serde_json::Value =
+        serde_json::from_str(blueprint_json).map_err(|e| format!("Failed to parse blueprint JSON: {}", e))?;
+    check(&extract(&blueprint), values)?;
+    fill(&mut blueprint, values);
+    crate::blueprint_schema::validate(&blueprint)?;
+    serde_json::to_string(&blueprint).map_err(|e| format!("Failed to serialize blueprint: {}", e))
+}
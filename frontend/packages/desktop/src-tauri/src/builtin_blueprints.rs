@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::filesystem::{self, BlueprintMetadata};
+use crate::workspace_archive::MergeStrategy;
+
+/// A starter blueprint shipped with the app
+#[derive(Debug, Clone, Serialize)]
+pub struct BuiltinBlueprint {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// A blueprint with the same id is already in the blueprints directory
+    pub installed: bool,
+}
+
+/// Starter blueprints are bundled under `resources/blueprints/<id>.json`
+fn get_builtin_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .resolve("resources/blueprints", BaseDirectory::Resource)
+        .map_err(|e| format!("Failed to resolve bundled blueprints: {}", e))
+}
+
+fn get_builtin_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid builtin blueprint id: {}", id));
+    }
+    Ok(get_builtin_dir(app)?.join(format!("{}.json", id)))
+}
+
+/// The starter blueprints, by id (blocking)
+pub fn list(app: &AppHandle) -> Result<Vec<BuiltinBlueprint>, String> {
+    let dir = get_builtin_dir(app)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut builtins: Vec<BuiltinBlueprint> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            let metadata = filesystem::read_blueprint_metadata(&path)?;
+            let installed = filesystem::get_blueprint_path(&id).is_ok_and(|p| p.exists());
+            Some(BuiltinBlueprint {
+                id,
+                name: metadata.name,
+                description: metadata.description,
+                installed,
+            })
+        })
+        .collect();
+    builtins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(builtins)
+}
+
+/// Copy a starter blueprint into the blueprints directory under its own id.
+/// `strategy` decides what happens when that id is taken: `keep_both` (default)
+/// installs it as `<id>-2`, `skip` leaves the existing one, `overwrite` replaces it.
+pub async fn install(app: &AppHandle, id: &str, strategy: MergeStrategy) -> Result<BlueprintMetadata, String> {
+    let source = get_builtin_path(app, id)?;
+    let content = tokio::fs::read_to_string(&source)
+        .await
+        .map_err(|e| format!("Builtin blueprint {} not found: {}", id, e))?;
+
+    let dest = filesystem::get_blueprint_path(id)?;
+    let exists = tokio::fs::try_exists(&dest).await.unwrap_or(false);
+    let path = match (exists, strategy) {
+        (true, MergeStrategy::Skip) => dest,
+        (true, MergeStrategy::Overwrite) => {
+            // Write then rename, so a crash mid-install leaves the old file intact
+            let temp = dest.with_extension("json.tmp");
+            tokio::fs::write(&temp, &content)
+                .await
+                .map_err(|e| format!("Failed to write blueprint file: {}", e))?;
+            tokio::fs::rename(&temp, &dest)
+                .await
+                .map_err(|e| format!("Failed to replace blueprint file: {}", e))?;
+            crate::audit::record_async(
+                crate::audit::AuditEntry::new("blueprints", "overwrite", "ui", id, true)
+                    .detail("Replaced with the builtin blueprint")
+                    .paths([&dest]),
+            )
+            .await;
+            dest
+        }
+        _ => filesystem::create_blueprint_file(id, &content).await?,
+    };
+
+    log::info!("Installed builtin blueprint {} as {}", id, path.display());
+    Ok(filesystem::blocking_fs(move || filesystem::saved_blueprint_metadata(path)).await?)
+}
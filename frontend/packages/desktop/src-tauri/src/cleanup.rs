@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::filesystem;
+
+/// What `cleanup_app_data` should remove. Everything defaults to off; deleting user
+/// data additionally requires its confirmation flag.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    /// Regenerable data: the semantic search index and the webview cache
+    pub caches: bool,
+    pub logs: bool,
+    /// Threads, blueprints, plugins and all configuration
+    pub user_data: bool,
+    pub confirm_delete_user_data: bool,
+    /// Credentials stored in the OS keychain
+    pub keychain: bool,
+    pub confirm_delete_keychain: bool,
+    /// Autostart entries and URL protocol handlers
+    pub integrations: bool,
+}
+
+/// What was removed, what wasn't, and why
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    pub removed: Vec<String>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+impl CleanupReport {
+    /// Remove a file or directory tree, recording the outcome
+    fn remove(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+
+        let size = disk_usage(path);
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+
+        match result {
+            Ok(()) => {
+                log::info!("Cleanup removed {}", path.display());
+                self.removed.push(path.display().to_string());
+                self.bytes_freed += size;
+            }
+            Err(e) => self.errors.push(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Total size of a file or directory tree
+pub(crate) fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else { return 0 };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| disk_usage(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Autostart entries the app or its installers may have created
+#[cfg(windows)]
+fn autostart_entries(_identifier: &str) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Autostart entries the app or its installers may have created
+#[cfg(not(windows))]
+fn autostart_entries(identifier: &str) -> Vec<PathBuf> {
+    let mut entries = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    if let Some(home) = dirs::home_dir() {
+        entries.push(home.join("Library/LaunchAgents").join(format!("{}.plist", identifier)));
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    if let Some(config) = dirs::config_dir() {
+        entries.push(config.join("autostart").join("chimera-desktop.desktop"));
+        entries.push(config.join("autostart").join(format!("{}.desktop", identifier)));
+    }
+
+    entries
+}
+
+/// Remove app data according to `options` (blocking)
+pub fn run(app: &AppHandle, options: &CleanupOptions) -> Result<CleanupReport, String> {
+    if options.user_data && !options.confirm_delete_user_data {
+        return Err("Deleting user data requires confirm_delete_user_data".to_string());
+    }
+    if options.keychain && !options.confirm_delete_keychain {
+        return Err("Deleting keychain entries requires confirm_delete_keychain".to_string());
+    }
+
+    let mut report = CleanupReport::default();
+    let data_dir = filesystem::get_data_dir()?;
+
+    if options.caches {
+        report.remove(&data_dir.join("index"));
+        match app.path().app_cache_dir() {
+            Ok(dir) => report.remove(&dir),
+            Err(e) => report.errors.push(format!("Failed to resolve cache directory: {}", e)),
+        }
+    }
+
+    if options.logs {
+        match app.path().app_log_dir() {
+            Ok(dir) => report.remove(&dir),
+            Err(e) => report.errors.push(format!("Failed to resolve log directory: {}", e)),
+        }
+    }
+
+    if options.keychain {
+        report.skipped.push("keychain: no entries are stored in this version".to_string());
+    }
+
+    if options.integrations {
+        let identifier = app.config().identifier.clone();
+        for entry in autostart_entries(&identifier) {
+            report.remove(&entry);
+        }
+        #[cfg(windows)]
+        report.skipped.push("autostart: Windows registry entries are not managed by this version".to_string());
+        report.skipped.push("protocol handlers: none are registered in this version".to_string());
+    }
+
+    if options.user_data {
+        log::warn!("Deleting all user data in {}", data_dir.display());
+        report.remove(&data_dir);
+        match app.path().app_data_dir() {
+            Ok(dir) => report.remove(&dir),
+            Err(e) => report.errors.push(format!("Failed to resolve app data directory: {}", e)),
+        }
+        if let Some(local) = dirs::data_local_dir() {
+            report.remove(&local.join("chimera-desktop"));
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,120 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::{DynamicImage, ImageFormat};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::Cursor;
+use std::sync::Mutex;
+
+use crate::attachments::{self, FileAttachment};
+use crate::error::ChimeraError;
+
+/// Largest pasted image read, in pixels
+const MAX_IMAGE_PIXELS: usize = 64 * 1024 * 1024;
+
+/// The clipboard, kept open for the life of the app: on Linux what we copy is
+/// served by this handle, and is lost when it's dropped
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// What `read_clipboard` found
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ClipboardContent {
+    Empty,
+    Text {
+        text: String,
+    },
+    Image {
+        width: u32,
+        height: u32,
+        /// Base64 of the image as PNG
+        data: String,
+        /// Set when it was stored in a thread's attachments
+        attachment: Option<FileAttachment>,
+    },
+}
+
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+    let mut clipboard = CLIPBOARD.lock().unwrap();
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().map_err(|e| format!("Failed to open the clipboard: {}", e))?);
+    }
+    let clipboard = clipboard.as_mut().expect("opened above");
+    f(clipboard).map_err(|e| format!("Clipboard error: {}", e))
+}
+
+/// Put `content` on the clipboard: text for `text/plain`, HTML (with no plain
+/// text alternative) for `text/html`, or a base64 PNG for `image/png` (blocking)
+pub fn copy(content: &str, mime: &str) -> Result<(), ChimeraError> {
+    match mime {
+        "text/plain" => Ok(with_clipboard(|clipboard| clipboard.set_text(content))?),
+        "text/html" => Ok(with_clipboard(|clipboard| clipboard.set_html(content, None::<&str>))?),
+        "image/png" => {
+            let png = BASE64
+                .decode(content.trim())
+                .map_err(|e| ChimeraError::InvalidInput(format!("Image isn't valid base64: {}", e)))?;
+            let image = image::load_from_memory_with_format(&png, ImageFormat::Png)
+                .map_err(|e| ChimeraError::InvalidInput(format!("Failed to decode PNG: {}", e)))?
+                .to_rgba8();
+            let image = arboard::ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: Cow::Owned(image.into_raw()),
+            };
+            Ok(with_clipboard(|clipboard| clipboard.set_image(image))?)
+        }
+        _ => Err(ChimeraError::InvalidInput(format!(
+            "Can't copy {}; use text/plain, text/html or image/png",
+            mime
+        ))),
+    }
+}
+
+/// What's on the clipboard, preferring an image, since copying one from a browser
+/// also puts its markup there as text. An image is stored in `thread_id`'s
+/// attachments when one is given (blocking).
+pub fn read(thread_id: Option<&str>) -> Result<ClipboardContent, String> {
+    let image = with_clipboard(|clipboard| match clipboard.get_image() {
+        Ok(image) => Ok(Some(image)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    })?;
+
+    if let Some(image) = image {
+        if image.width * image.height > MAX_IMAGE_PIXELS {
+            return Err(format!("Clipboard image is too large ({}x{})", image.width, image.height));
+        }
+        let (width, height) = (image.width as u32, image.height as u32);
+        let pixels = image::RgbaImage::from_raw(width, height, image.bytes.into_owned())
+            .ok_or("Clipboard image has an unexpected size")?;
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(pixels)
+            .write_to(&mut png, ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode clipboard image: {}", e))?;
+        let png = png.into_inner();
+
+        let attachment = match thread_id {
+            Some(thread_id) => {
+                let name = format!("pasted-{}.png", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+                Some(attachments::store_bytes(thread_id, &name, &png)?)
+            }
+            None => None,
+        };
+        return Ok(ClipboardContent::Image {
+            width,
+            height,
+            data: BASE64.encode(&png),
+            attachment,
+        });
+    }
+
+    let text = with_clipboard(|clipboard| match clipboard.get_text() {
+        Ok(text) => Ok(Some(text)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    })?;
+    Ok(match text {
+        Some(text) => ClipboardContent::Text { text },
+        None => ClipboardContent::Empty,
+    })
+}
@@ -0,0 +1,236 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::blob_store;
+use crate::filesystem;
+
+/// Top-level event fields at least this large (serialized) move to the blob store
+const MIN_BLOB_BYTES: usize = 4 * 1024;
+
+/// Result of `compact_thread`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompactionReport {
+    pub events: usize,
+    /// Fields replaced by a blob reference
+    pub values_replaced: usize,
+    /// New blobs written; identical content already stored is shared
+    pub blobs_written: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Move large event payloads into the shared blob store, leaving `{"$blob": hash}`
+/// references that `load_thread` rehydrates. Identical payloads, within this thread
+/// or across threads, are stored once. The blueprint line and unparseable lines are
+/// kept as they are. Callers must keep appends to the thread out while this runs
+/// (blocking).
+pub fn compact_thread(thread_id: &str) -> Result<CompactionReport, String> {
+    let path = filesystem::get_thread_path(thread_id)?;
+    let content = std::fs::read(&path).map_err(|_| format!("Thread {} not found", thread_id))?;
+
+    let mut report = CompactionReport {
+        bytes_before: content.len() as u64,
+        ..Default::default()
+    };
+    let mut output = Vec::with_capacity(content.len());
+
+    for (index, line) in content.split(|&b| b == b'\n').enumerate() {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let mut event = match serde_json::from_slice::<serde_json::Value>(line) {
+            Ok(event) if index > 0 => event,
+            _ => {
+                output.extend_from_slice(line);
+                output.push(b'\n');
+                continue;
+            }
+        };
+        report.events += 1;
+
+        if let Some(fields) = event.as_object_mut() {
+            for (key, value) in fields.iter_mut() {
+                if blob_store::INLINE_FIELDS.contains(&key.as_str()) || blob_store::blob_ref(value).is_some() {
+                    continue;
+                }
+                let serialized = serde_json::to_vec(value).map_err(|e| format!("Failed to serialize event: {}", e))?;
+                if serialized.len() < MIN_BLOB_BYTES {
+                    continue;
+                }
+
+                let (hash, written) = blob_store::put_bytes(&serialized)?;
+                *value = blob_store::make_ref(&hash);
+                report.values_replaced += 1;
+                report.blobs_written += written as usize;
+            }
+        }
+
+        filesystem::serialize_event_line(&event, &mut output)?;
+    }
+
+    if report.values_replaced == 0 {
+        report.bytes_after = report.bytes_before;
+        return Ok(report);
+    }
+
+    filesystem::replace_thread_file(&path, &output)?;
+
+    report.bytes_after = output.len() as u64;
+    log::info!(
+        "Compacted thread {}: {} -> {} bytes, {} values replaced, {} new blobs",
+        thread_id,
+        report.bytes_before,
+        report.bytes_after,
+        report.values_replaced,
+        report.blobs_written
+    );
+
+    Ok(report)
+}
+
+/// Result of `compact_stream_events`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamCompactionReport {
+    /// Streamed text and reasoning parts merged into one event each
+    pub parts_merged: usize,
+    /// Start, delta and end events removed
+    pub events_removed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// The event a finished stream of `kind` is stored as, e.g. `text-complete`
+fn complete_type(kind: &str) -> String {
+    format!("{}-complete", kind)
+}
+
+/// `text` or `reasoning` and the part id, for a streaming event of either
+fn stream_part(event: &serde_json::Value) -> Option<(&'static str, &'static str, String)> {
+    let event_type = event.get("type")?.as_str()?;
+    let (kind, stage) = event_type.split_once('-')?;
+    let kind = ["text", "reasoning"].into_iter().find(|k| *k == kind)?;
+    let stage = ["start", "delta", "end"].into_iter().find(|s| *s == stage)?;
+    let id = event.get("id")?.as_str()?.to_string();
+    Some((kind, stage, id))
+}
+
+/// Merge streamed text and reasoning (`-start`, `-delta`s, `-end`) into a single
+/// `text-complete` or `reasoning-complete` event marked `"streamed": true`, in
+/// place of the `-end` event. Parts whose stream never ended are left as they are,
+/// as are tool input deltas unless the full input followed in `tool-input-available`.
+/// Callers must keep appends to the thread out while this runs (blocking).
+pub fn compact_stream_events(thread_id: &str) -> Result<StreamCompactionReport, String> {
+    let path = filesystem::get_thread_path(thread_id)?;
+    let content = std::fs::read(&path).map_err(|_| format!("Thread {} not found", thread_id))?;
+
+    let lines: Vec<(&[u8], Option<serde_json::Value>)> = content
+        .split(|&b| b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|(index, line)| {
+            // The blueprint line is never touched
+            let event = (index > 0).then(|| serde_json::from_slice(line).ok()).flatten();
+            (line, event)
+        })
+        .collect();
+
+    // Parts whose stream finished, and tool calls whose input arrived in full
+    let mut ended = HashSet::new();
+    let mut tool_inputs = HashSet::new();
+    for (_, event) in &lines {
+        let Some(event) = event else { continue };
+        if let Some((kind, "end", id)) = stream_part(event) {
+            ended.insert((kind, id));
+        } else if event.get("type").and_then(|t| t.as_str()) == Some("tool-input-available") {
+            if let Some(id) = event.get("toolCallId").and_then(|id| id.as_str()) {
+                tool_inputs.insert(id.to_string());
+            }
+        }
+    }
+
+    let mut report = StreamCompactionReport {
+        bytes_before: content.len() as u64,
+        ..Default::default()
+    };
+    let mut text: HashMap<(&str, String), String> = HashMap::new();
+    let mut streamed_tools = HashSet::new();
+    let mut output = Vec::with_capacity(content.len());
+
+    for (line, event) in lines {
+        let Some(mut event) = event else {
+            output.extend_from_slice(line);
+            output.push(b'\n');
+            continue;
+        };
+
+        if let Some((kind, stage, id)) = stream_part(&event) {
+            if ended.contains(&(kind, id.clone())) {
+                report.events_removed += 1;
+                match stage {
+                    "delta" => {
+                        let delta = event.get("delta").and_then(|d| d.as_str()).unwrap_or_default();
+                        text.entry((kind, id)).or_default().push_str(delta);
+                        continue;
+                    }
+                    "start" => {
+                        text.entry((kind, id)).or_default();
+                        continue;
+                    }
+                    _ => {
+                        let mut complete = serde_json::json!({
+                            "type": complete_type(kind),
+                            "id": id,
+                            "content": text.remove(&(kind, id.clone())).unwrap_or_default(),
+                            "streamed": true,
+                        });
+                        if let Some(timestamp) = event.get("timestamp") {
+                            complete["timestamp"] = timestamp.clone();
+                        }
+                        report.parts_merged += 1;
+                        filesystem::serialize_event_line(&complete, &mut output)?;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or_default().to_string();
+        let tool_call = event
+            .get("toolCallId")
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
+            .unwrap_or_default();
+        match event_type.as_str() {
+            "tool-input-start" | "tool-input-delta" if tool_inputs.contains(&tool_call) => {
+                report.events_removed += 1;
+                streamed_tools.insert(tool_call);
+                continue;
+            }
+            "tool-input-available" if streamed_tools.remove(&tool_call) => {
+                event["streamed"] = serde_json::Value::Bool(true);
+            }
+            _ => {}
+        }
+        filesystem::serialize_event_line(&event, &mut output)?;
+    }
+
+    if report.events_removed == 0 {
+        report.bytes_after = report.bytes_before;
+        return Ok(report);
+    }
+
+    filesystem::replace_thread_file(&path, &output)?;
+
+    report.bytes_after = output.len() as u64;
+    log::info!(
+        "Compacted streaming in thread {}: {} -> {} bytes, {} parts merged, {} events removed",
+        thread_id,
+        report.bytes_before,
+        report.bytes_after,
+        report.parts_merged,
+        report.events_removed
+    );
+
+    Ok(report)
+}
@@ -0,0 +1,522 @@
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio::time::Instant;
+
+use crate::event_bus::{BusEvent, EventBus};
+use crate::filesystem;
+use crate::share_session::local_ip;
+use crate::thread_compression;
+
+/// mDNS service type advertised while the companion server runs
+const SERVICE_TYPE: &str = "_chimera-companion._tcp.local.";
+
+/// How long a pairing key stays valid
+const PAIRING_KEY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How often event streams re-check that their device is still paired
+const REVOCATION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Bus topics forwarded to companion devices
+const COMPANION_TOPICS: [&str; 3] = ["thread-changed", "backend-stream", "companion-user-message"];
+
+/// A paired device as stored on disk (only a hash of its token is kept)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDevice {
+    id: String,
+    name: String,
+    token_hash: String,
+    paired_at: String,
+    last_seen: Option<String>,
+}
+
+/// A paired device as shown in the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanionDevice {
+    pub id: String,
+    pub name: String,
+    pub paired_at: String,
+    pub last_seen: Option<String>,
+}
+
+/// Details needed to pair a new device
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingInfo {
+    /// `chimera-companion://pair?...` URI encoded in the QR code
+    pub pairing_uri: String,
+    pub qr_svg: String,
+    pub host: String,
+    pub port: u16,
+    pub expires_at: String,
+}
+
+/// Companion server status
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanionStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub pairing_open: bool,
+    pub devices: usize,
+}
+
+/// A one-time pairing key awaiting a device
+struct PendingPairing {
+    key: String,
+    expires: Instant,
+}
+
+/// State shared between the manager and request handlers
+struct CompanionState {
+    devices: Mutex<Vec<StoredDevice>>,
+    pairing: Mutex<Option<PendingPairing>>,
+    bus: Arc<EventBus>,
+}
+
+/// A running companion server and its mDNS registration
+struct RunningServer {
+    port: u16,
+    shutdown: watch::Sender<bool>,
+    mdns: Option<(mdns_sd::ServiceDaemon, String)>,
+}
+
+/// Lets companion devices on the local network pair with the desktop app,
+/// follow thread events and submit user messages
+pub struct CompanionServer {
+    state: Arc<CompanionState>,
+    running: tokio::sync::Mutex<Option<RunningServer>>,
+}
+
+#[derive(Deserialize)]
+struct PairRequest {
+    key: String,
+    device_name: String,
+}
+
+#[derive(Serialize)]
+struct PairResponse {
+    device_id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct MessageRequest {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Get the paired devices file path
+fn get_devices_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("companion_devices.json"))
+}
+
+/// Hash a device token for storage
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Advertise the companion endpoint over mDNS
+fn register_mdns(ip: IpAddr, port: u16) -> Result<(mdns_sd::ServiceDaemon, String), String> {
+    let daemon = mdns_sd::ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {}", e))?;
+
+    let machine = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "chimera".to_string());
+    let instance = format!("Chimera on {}", machine);
+    let host_name = format!("{}.local.", machine.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "-"));
+
+    let service = mdns_sd::ServiceInfo::new(
+        SERVICE_TYPE,
+        &instance,
+        &host_name,
+        ip,
+        port,
+        &[("version", env!("CARGO_PKG_VERSION"))][..],
+    )
+    .map_err(|e| format!("Failed to describe mDNS service: {}", e))?;
+
+    let fullname = service.get_fullname().to_string();
+    daemon
+        .register(service)
+        .map_err(|e| format!("Failed to register mDNS service: {}", e))?;
+
+    Ok((daemon, fullname))
+}
+
+impl CompanionState {
+    /// Persist the paired device list
+    fn save(&self, devices: &[StoredDevice]) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(devices)
+            .map_err(|e| format!("Failed to serialize companion devices: {}", e))?;
+        std::fs::write(get_devices_path()?, content).map_err(|e| format!("Failed to write companion devices: {}", e))
+    }
+
+    /// Resolve a bearer token to a paired device id, recording when it was last seen
+    fn authenticate(&self, token: Option<&str>) -> Option<String> {
+        let hash = hash_token(token?);
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.iter_mut().find(|d| d.token_hash == hash)?;
+        device.last_seen = Some(chrono::Utc::now().to_rfc3339());
+        Some(device.id.clone())
+    }
+
+    fn is_paired(&self, device_id: &str) -> bool {
+        self.devices.lock().unwrap().iter().any(|d| d.id == device_id)
+    }
+}
+
+impl CompanionServer {
+    /// Create the companion manager, loading paired devices. The server starts on first pairing.
+    pub fn load(bus: Arc<EventBus>) -> Self {
+        let devices: Vec<StoredDevice> = get_devices_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(devices) => Some(devices),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable companion devices file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        log::info!("Loaded {} companion device(s)", devices.len());
+
+        Self {
+            state: Arc::new(CompanionState {
+                devices: Mutex::new(devices),
+                pairing: Mutex::new(None),
+                bus,
+            }),
+            running: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Start the server if needed and issue a fresh one-time pairing key
+    pub async fn start_pairing(&self, port: Option<u16>) -> Result<PairingInfo, String> {
+        let mut running = self.running.lock().await;
+        if running.is_none() {
+            *running = Some(self.serve(port).await?);
+        }
+        let port = running.as_ref().map(|r| r.port).unwrap_or_default();
+
+        let key = uuid::Uuid::new_v4().simple().to_string();
+        *self.state.pairing.lock().unwrap() = Some(PendingPairing {
+            key: key.clone(),
+            expires: Instant::now() + PAIRING_KEY_TTL,
+        });
+
+        let host = local_ip().to_string();
+        let pairing_uri = format!("chimera-companion://pair?host={}&port={}&key={}", host, port, key);
+        let qr_svg = qrcode::QrCode::new(pairing_uri.as_bytes())
+            .map_err(|e| format!("Failed to generate QR code: {}", e))?
+            .render::<qrcode::render::svg::Color>()
+            .min_dimensions(256, 256)
+            .build();
+
+        log::info!("Companion pairing opened on port {}", port);
+
+        Ok(PairingInfo {
+            pairing_uri,
+            qr_svg,
+            host,
+            port,
+            expires_at: (chrono::Utc::now() + chrono::Duration::from_std(PAIRING_KEY_TTL).unwrap_or_default())
+                .to_rfc3339(),
+        })
+    }
+
+    /// Bind the HTTP server and advertise it
+    async fn serve(&self, port: Option<u16>) -> Result<RunningServer, String> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port.unwrap_or(0))))
+            .await
+            .map_err(|e| format!("Failed to bind companion server: {}", e))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read companion server address: {}", e))?
+            .port();
+
+        let router = Router::new()
+            .route("/pair", post(pair))
+            .route("/threads", get(list_threads))
+            .route("/threads/:thread_id", get(load_thread))
+            .route("/threads/:thread_id/messages", post(submit_message))
+            .route("/events", get(events))
+            .with_state(self.state.clone());
+
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        tauri::async_runtime::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown_rx.wait_for(|stopped| *stopped).await;
+            };
+            if let Err(e) = axum::serve(listener, router).with_graceful_shutdown(shutdown).await {
+                log::error!("Companion server error: {}", e);
+            }
+        });
+
+        // Devices can still connect by address if mDNS is unavailable
+        let mdns = match register_mdns(local_ip(), port) {
+            Ok(registration) => Some(registration),
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        };
+
+        log::info!("Companion server listening on port {}", port);
+        Ok(RunningServer {
+            port,
+            shutdown: shutdown_tx,
+            mdns,
+        })
+    }
+
+    /// Stop the server, withdraw the mDNS advertisement and cancel pairing
+    pub async fn stop(&self) {
+        *self.state.pairing.lock().unwrap() = None;
+
+        if let Some(server) = self.running.lock().await.take() {
+            let _ = server.shutdown.send(true);
+            if let Some((daemon, fullname)) = server.mdns {
+                let _ = daemon.unregister(&fullname);
+                let _ = daemon.shutdown();
+            }
+            log::info!("Companion server stopped");
+        }
+    }
+
+    /// Current server status
+    pub async fn status(&self) -> CompanionStatus {
+        let port = self.running.lock().await.as_ref().map(|r| r.port);
+        let pairing_open = self
+            .state
+            .pairing
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|p| p.expires > Instant::now());
+
+        CompanionStatus {
+            running: port.is_some(),
+            port,
+            pairing_open,
+            devices: self.state.devices.lock().unwrap().len(),
+        }
+    }
+
+    /// List paired devices
+    pub fn devices(&self) -> Vec<CompanionDevice> {
+        self.state
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|d| CompanionDevice {
+                id: d.id.clone(),
+                name: d.name.clone(),
+                paired_at: d.paired_at.clone(),
+                last_seen: d.last_seen.clone(),
+            })
+            .collect()
+    }
+
+    /// Revoke a device; its open event streams close within a few seconds
+    pub fn revoke(&self, device_id: &str) -> Result<(), String> {
+        let mut devices = self.state.devices.lock().unwrap();
+        let before = devices.len();
+        devices.retain(|d| d.id != device_id);
+
+        if devices.len() == before {
+            return Err(format!("Companion device not found: {}", device_id));
+        }
+
+        self.state.save(&devices)?;
+        log::info!("Revoked companion device {}", device_id);
+        Ok(())
+    }
+}
+
+/// Extract a bearer token from the Authorization header or `?token=`
+/// (EventSource clients can't set headers)
+fn request_token<'a>(headers: &'a HeaderMap, query: &'a TokenQuery) -> Option<&'a str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(query.token.as_deref())
+}
+
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, "Device is not paired").into_response()
+}
+
+async fn pair(State(state): State<Arc<CompanionState>>, Json(request): Json<PairRequest>) -> Response {
+    {
+        let mut pairing = state.pairing.lock().unwrap();
+        let valid = pairing
+            .as_ref()
+            .is_some_and(|p| p.key == request.key && p.expires > Instant::now());
+        if !valid {
+            return (StatusCode::FORBIDDEN, "Invalid or expired pairing key").into_response();
+        }
+        // Keys are single use
+        *pairing = None;
+    }
+
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let device = StoredDevice {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.device_name.chars().take(64).collect(),
+        token_hash: hash_token(&token),
+        paired_at: chrono::Utc::now().to_rfc3339(),
+        last_seen: None,
+    };
+
+    let device_id = device.id.clone();
+    {
+        let mut devices = state.devices.lock().unwrap();
+        devices.push(device);
+        if let Err(e) = state.save(&devices) {
+            log::error!("{}", e);
+        }
+    }
+
+    log::info!("Paired companion device {} ({})", device_id, request.device_name);
+    state
+        .bus
+        .publish("companion-paired", serde_json::json!({ "device_id": device_id, "name": request.device_name }));
+
+    Json(PairResponse { device_id, token }).into_response()
+}
+
+async fn list_threads(
+    State(state): State<Arc<CompanionState>>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if state.authenticate(request_token(&headers, &query)).is_none() {
+        return unauthorized();
+    }
+
+    match filesystem::list_threads().await {
+        Ok(threads) => Json(threads).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn load_thread(
+    State(state): State<Arc<CompanionState>>,
+    Path(thread_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if state.authenticate(request_token(&headers, &query)).is_none() {
+        return unauthorized();
+    }
+
+    if let Err(e) = filesystem::get_thread_path(&thread_id) {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
+
+    match filesystem::load_thread(thread_id).await {
+        Ok(events) => Json(events).into_response(),
+        Err(e) if e.kind == filesystem::FsErrorKind::NotFound => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Hand a user message to the desktop UI, which runs the turn as if typed locally
+async fn submit_message(
+    State(state): State<Arc<CompanionState>>,
+    Path(thread_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+    Json(request): Json<MessageRequest>,
+) -> Response {
+    let Some(device_id) = state.authenticate(request_token(&headers, &query)) else {
+        return unauthorized();
+    };
+
+    match thread_compression::thread_exists(&thread_id) {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::NOT_FOUND, format!("Thread {} not found", thread_id)).into_response(),
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+
+    if request.content.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, "Message is empty").into_response();
+    }
+
+    state.bus.publish(
+        "companion-user-message",
+        serde_json::json!({
+            "thread_id": thread_id,
+            "content": request.content,
+            "device_id": device_id,
+        }),
+    );
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+async fn events(
+    State(state): State<Arc<CompanionState>>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    let Some(device_id) = state.authenticate(request_token(&headers, &query)) else {
+        return unauthorized();
+    };
+
+    let receiver = state.bus.listen();
+    let events = stream::unfold((state, device_id, receiver), |(state, device_id, mut receiver)| async move {
+        loop {
+            if !state.is_paired(&device_id) {
+                log::info!("Closing event stream for revoked device {}", device_id);
+                return None;
+            }
+
+            let event: BusEvent = match tokio::time::timeout(REVOCATION_CHECK_INTERVAL, receiver.recv()).await {
+                Err(_) => continue,
+                Ok(Ok(event)) => event,
+                Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                    log::warn!("Companion device {} missed {} events", device_id, skipped);
+                    continue;
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) => return None,
+            };
+
+            if !COMPANION_TOPICS.contains(&event.topic.as_str()) {
+                continue;
+            }
+
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            let sse = Event::default().event(event.topic).id(event.seq.to_string()).data(data);
+            return Some((Ok::<_, Infallible>(sse), (state, device_id, receiver)));
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Longest request line accepted
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// One command sent to a headless instance, as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlRequest {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// The reply to a `ControlRequest`, as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default)]
+    pub result: serde_json::Value,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl From<Result<serde_json::Value, String>> for ControlResponse {
+    fn from(result: Result<serde_json::Value, String>) -> Self {
+        match result {
+            Ok(result) => Self {
+                ok: true,
+                result,
+                error: None,
+            },
+            Err(e) => Self {
+                ok: false,
+                result: serde_json::Value::Null,
+                error: Some(e),
+            },
+        }
+    }
+}
+
+/// `headless.sock` in the data directory
+#[cfg(unix)]
+fn socket_path() -> Result<std::path::PathBuf, String> {
+    Ok(crate::filesystem::get_data_dir()?.join("headless.sock"))
+}
+
+/// A named pipe per data directory, so instances on different data don't collide
+#[cfg(windows)]
+fn pipe_name() -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let data_dir = crate::filesystem::get_data_dir()?;
+    let hash = format!("{:x}", Sha256::digest(data_dir.to_string_lossy().as_bytes()));
+    Ok(format!(r"\\.\pipe\chimera-desktop-{}", &hash[..16]))
+}
+
+/// Read one request, answer it with `handle`
+async fn serve_connection<S, F, Fut>(stream: S, handle: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(ControlRequest) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value, String>>,
+{
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    let response: ControlResponse = match (&mut stream).take(MAX_REQUEST_BYTES as u64).read_line(&mut line).await {
+        Ok(_) => match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => handle(request).await.into(),
+            Err(e) => Err(format!("Invalid request: {}", e)).into(),
+        },
+        Err(e) => Err(format!("Failed to read request: {}", e)).into(),
+    };
+
+    let mut reply = serde_json::to_vec(&response).unwrap_or_default();
+    reply.push(b'\n');
+    let stream = stream.get_mut();
+    if let Err(e) = stream.write_all(&reply).await.and(stream.flush().await) {
+        log::warn!("Failed to answer control request: {}", e);
+    }
+}
+
+/// Accept control requests until `shutdown` resolves. Fails if another instance
+/// is already listening.
+#[cfg(unix)]
+pub async fn serve<F, Fut>(handle: F, shutdown: impl Future<Output = ()>) -> Result<(), String>
+where
+    F: Fn(ControlRequest) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<serde_json::Value, String>> + Send,
+{
+    let path = socket_path()?;
+    if path.exists() {
+        if tokio::net::UnixStream::connect(&path).await.is_ok() {
+            return Err(format!("Another headless instance is listening on {}", path.display()));
+        }
+        // Left behind by an instance that didn't shut down cleanly
+        let _ = std::fs::remove_file(&path);
+    }
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Only this user may drive the instance
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to secure {}: {}", path.display(), e))?;
+    }
+    log::info!("Control socket listening on {}", path.display());
+
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let handle = handle.clone();
+                    tokio::spawn(async move { serve_connection(stream, handle).await });
+                }
+                Err(e) => log::warn!("Failed to accept control connection: {}", e),
+            },
+            _ = &mut shutdown => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+/// Accept control requests until `shutdown` resolves. Fails if another instance
+/// is already listening.
+#[cfg(windows)]
+pub async fn serve<F, Fut>(handle: F, shutdown: impl Future<Output = ()>) -> Result<(), String>
+where
+    F: Fn(ControlRequest) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<serde_json::Value, String>> + Send,
+{
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = pipe_name()?;
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)
+        .map_err(|e| format!("Failed to listen on {} (is another headless instance running?): {}", name, e))?;
+    log::info!("Control pipe listening on {}", name);
+
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            connected = server.connect() => {
+                if let Err(e) = connected {
+                    log::warn!("Failed to accept control connection: {}", e);
+                    continue;
+                }
+                let next = ServerOptions::new()
+                    .create(&name)
+                    .map_err(|e| format!("Failed to listen on {}: {}", name, e))?;
+                let stream = std::mem::replace(&mut server, next);
+                let handle = handle.clone();
+                tokio::spawn(async move { serve_connection(stream, handle).await });
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+    Ok(())
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(stream: S, request: &ControlRequest) -> Result<ControlResponse, String> {
+    let mut stream = BufReader::new(stream);
+    let mut line = serde_json::to_vec(request).map_err(|e| format!("Failed to serialize request: {}", e))?;
+    line.push(b'\n');
+    stream
+        .get_mut()
+        .write_all(&line)
+        .await
+        .map_err(|e| format!("Failed to send control request: {}", e))?;
+
+    let mut reply = String::new();
+    stream
+        .read_line(&mut reply)
+        .await
+        .map_err(|e| format!("Failed to read control response: {}", e))?;
+    serde_json::from_str(&reply).map_err(|e| format!("Invalid control response: {}", e))
+}
+
+/// Send a request to the running headless instance. None when there isn't one.
+#[cfg(unix)]
+pub async fn request(request: &ControlRequest) -> Option<Result<ControlResponse, String>> {
+    let stream = tokio::net::UnixStream::connect(socket_path().ok()?).await.ok()?;
+    Some(exchange(stream, request).await)
+}
+
+/// Send a request to the running headless instance. None when there isn't one.
+#[cfg(windows)]
+pub async fn request(request: &ControlRequest) -> Option<Result<ControlResponse, String>> {
+    let client = tokio::net::windows::named_pipe::ClientOptions::new()
+        .open(pipe_name().ok()?)
+        .ok()?;
+    Some(exchange(client, request).await)
+}
@@ -0,0 +1,249 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Url;
+
+use crate::event_bus::EventBus;
+use crate::filesystem::{self, BlueprintMetadata};
+
+/// Scheme registered for share links
+pub const SCHEME: &str = "chimera";
+
+/// Imports not confirmed within this long are dropped
+const PENDING_TTL: chrono::TimeDelta = chrono::TimeDelta::minutes(30);
+
+/// Largest blueprint fetched from a link's `url`
+const MAX_FETCH_BYTES: usize = 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A shared blueprint waiting for the user to confirm it, as sent in
+/// `blueprint-import-request` events
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingImport {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Names of the blueprint's agents
+    pub agents: Vec<String>,
+    /// Verified SHA-256 of the payload
+    pub sha256: String,
+    /// The URL the payload was fetched from; None when it came inside the link
+    pub source_url: Option<String>,
+    pub blueprint: serde_json::Value,
+    pub received_at: String,
+}
+
+/// Where a share link's payload comes from
+enum Payload {
+    /// Base64url blueprint JSON in the link itself
+    Inline(String),
+    /// An https URL serving the blueprint JSON
+    Remote(Url),
+}
+
+/// A parsed `chimera://blueprint?sha256=<hex>&data=<base64url>` or
+/// `chimera://blueprint?sha256=<hex>&url=<https url>` link
+struct ShareLink {
+    sha256: String,
+    payload: Payload,
+}
+
+fn parse(url: &Url) -> Result<ShareLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not a {} link: {}", SCHEME, url));
+    }
+    if url.host_str() != Some("blueprint") {
+        return Err(format!("Unsupported link: {}", url));
+    }
+
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let sha256 = query
+        .get("sha256")
+        .map(|hash| hash.to_ascii_lowercase())
+        .filter(|hash| hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or("Share link has no valid sha256")?;
+    let payload = match (query.get("data"), query.get("url")) {
+        (Some(data), None) => Payload::Inline(data.clone()),
+        (None, Some(source)) => {
+            let source = Url::parse(source).map_err(|e| format!("Invalid blueprint URL {}: {}", source, e))?;
+            if source.scheme() != "https" {
+                return Err(format!("Blueprint URLs must use https: {}", source));
+            }
+            Payload::Remote(source)
+        }
+        _ => return Err("Share link needs exactly one of data or url".to_string()),
+    };
+    Ok(ShareLink { sha256, payload })
+}
+
+/// Download a blueprint, refusing anything over `MAX_FETCH_BYTES`
+async fn fetch(url: &Url) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch blueprint from {}: {}", url, e))?;
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to fetch blueprint from {}: {}", url, e))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_FETCH_BYTES {
+            return Err(format!("Blueprint at {} is larger than {} bytes", url, MAX_FETCH_BYTES));
+        }
+    }
+    Ok(body)
+}
+
+/// Imports blueprints from share links once the user confirms them
+pub struct DeepLinks {
+    pending: Mutex<HashMap<String, PendingImport>>,
+    bus: Arc<EventBus>,
+}
+
+impl DeepLinks {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            bus,
+        }
+    }
+
+    /// Fetch, decode and verify a link's blueprint, then hold it for confirmation
+    /// and publish `blueprint-import-request`
+    #[tracing::instrument(skip(self), err)]
+    pub async fn open(&self, url: &Url) -> Result<PendingImport, String> {
+        let link = parse(url)?;
+        let (bytes, source_url) = match &link.payload {
+            Payload::Inline(data) => (
+                BASE64_URL
+                    .decode(data.trim_end_matches('='))
+                    .map_err(|e| format!("Failed to decode shared blueprint: {}", e))?,
+                None,
+            ),
+            Payload::Remote(source) => (fetch(source).await?, Some(source.to_string())),
+        };
+
+        let actual = format!("{:x}", Sha256::digest(&bytes));
+        if actual != link.sha256 {
+            return Err(format!(
+                "Shared blueprint failed verification: expected sha256 {}, got {}",
+                link.sha256, actual
+            ));
+        }
+
+        let blueprint: serde_json::Value =
+            serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse shared blueprint: {}", e))?;
+        crate::blueprint_schema::validate(&blueprint)?;
+
+        let agents: Vec<String> = blueprint
+            .pointer("/blueprint/space/agents")
+            .and_then(|agents| agents.as_array())
+            .map(|agents| {
+                agents
+                    .iter()
+                    .filter_map(|agent| agent.get("name").or_else(|| agent.get("agentUuid"))?.as_str())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let import = PendingImport {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: agents.first().cloned().unwrap_or_else(|| "Shared blueprint".to_string()),
+            description: blueprint
+                .pointer("/blueprint/space/agents/0/description")
+                .and_then(|d| d.as_str())
+                .map(str::to_string),
+            agents,
+            sha256: actual,
+            source_url,
+            blueprint,
+            received_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        {
+            let mut pending = self.pending.lock().unwrap();
+            pending.retain(|_, import| !Self::expired(import));
+            pending.insert(import.id.clone(), import.clone());
+        }
+        log::info!("Shared blueprint {} ({}) is waiting for confirmation", import.name, import.sha256);
+        self.bus.publish("blueprint-import-request", serde_json::json!(import));
+        Ok(import)
+    }
+
+    fn expired(import: &PendingImport) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&import.received_at)
+            .map(|at| chrono::Utc::now().signed_duration_since(at) > PENDING_TTL)
+            .unwrap_or(true)
+    }
+
+    /// Imports waiting for confirmation, e.g. from a link that launched the app
+    /// before the frontend was listening
+    pub fn list_pending(&self) -> Vec<PendingImport> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, import| !Self::expired(import));
+        let mut imports: Vec<PendingImport> = pending.values().cloned().collect();
+        imports.sort_by(|a, b| a.received_at.cmp(&b.received_at));
+        imports
+    }
+
+    /// Write a confirmed import into the blueprints directory, or drop a rejected one
+    pub async fn resolve(&self, id: &str, accept: bool) -> Result<Option<BlueprintMetadata>, String> {
+        let import = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(id)
+            .filter(|import| !Self::expired(import))
+            .ok_or_else(|| format!("No pending blueprint import {}", id))?;
+
+        let saved = if accept {
+            let json = serde_json::to_string(&import.blueprint)
+                .map_err(|e| format!("Failed to serialize shared blueprint: {}", e))?;
+            let saved = filesystem::save_blueprint(None, json).await?;
+            log::info!("Imported shared blueprint {} as {}", import.sha256, saved.id);
+            Some(saved)
+        } else {
+            log::info!("Rejected shared blueprint {}", import.sha256);
+            None
+        };
+        self.bus.publish(
+            "blueprint-import-resolved",
+            serde_json::json!({ "id": id, "accepted": accept, "blueprint": saved }),
+        );
+        Ok(saved)
+    }
+
+    /// Handle links the app was opened with. Bad links are logged and skipped.
+    pub fn handle_urls(self: &Arc<Self>, urls: Vec<Url>) {
+        if crate::viewer::is_active() {
+            log::warn!("Ignoring {} links in read-only viewer mode", urls.len());
+            return;
+        }
+        for url in urls {
+            let links = self.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = links.open(&url).await {
+                    log::warn!("Failed to open link: {}", e);
+                    links.bus.publish(
+                        "blueprint-import-failed",
+                        serde_json::json!({ "url": url.to_string(), "error": e }),
+                    );
+                }
+            });
+        }
+    }
+}
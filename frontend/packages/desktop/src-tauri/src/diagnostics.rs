@@ -0,0 +1,286 @@
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::filesystem;
+use crate::python_backend;
+use crate::terminal_backend::{self, TerminalSnapshot};
+
+/// Scrollback lines per terminal when the caller doesn't say
+pub const DEFAULT_SCROLLBACK_LINES: usize = 200;
+
+/// Only the end of each log file goes in a bundle, so bundles stay attachable
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Settings keys containing any of these have their values replaced
+pub(crate) const SECRET_KEY_PARTS: &[&str] = &["secret", "token", "password", "passphrase", "api_key", "apikey", "credential", "auth"];
+
+/// Where the panic hook writes crash reports
+fn get_crash_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("crash-reports"))
+}
+
+/// Where bundles go when the caller doesn't pick a path
+fn get_bundle_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("diagnostics"))
+}
+
+/// A crash report left by a previous run
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    pub file_name: String,
+    pub created_at: String,
+    /// The panic message
+    pub summary: String,
+}
+
+/// A written diagnostics bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub bytes: u64,
+    /// Paths of the files inside the zip
+    pub files: Vec<String>,
+    /// Crash reports included, which are then removed from the pending list
+    pub crash_reports: usize,
+}
+
+/// What goes into a bundle besides the files read from disk
+pub struct BundleInputs {
+    pub app_log_dir: Option<PathBuf>,
+    pub settings: Option<serde_json::Value>,
+    pub terminals: Vec<TerminalSnapshot>,
+    pub scrollback_lines: usize,
+    pub include_crash_reports: bool,
+}
+
+/// Write a crash report for any panic, then run the default hook. Reports are
+/// offered for inclusion in a diagnostics bundle on the next launch.
+pub fn install_panic_hook() {
+    let crash_dir = match get_crash_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("Crash reports disabled: {}", e);
+            return;
+        }
+    };
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let now = chrono::Utc::now();
+        let report = format!(
+            "{}\n\nlocation: {}\nthread: {}\nversion: {}\nos: {} {}\ntime: {}\n\n{}\n",
+            message,
+            location,
+            std::thread::current().name().unwrap_or("<unnamed>"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            now.to_rfc3339(),
+            std::backtrace::Backtrace::force_capture(),
+        );
+        let path = crash_dir.join(format!("crash-{}-{}.txt", now.format("%Y%m%dT%H%M%SZ"), std::process::id()));
+        // Nothing here may panic, so failures are only reported on stderr
+        if let Err(e) = std::fs::create_dir_all(&crash_dir).and_then(|_| std::fs::write(&path, report)) {
+            eprintln!("Failed to write crash report: {}", e);
+        }
+        default_hook(info);
+    }));
+}
+
+/// Crash reports not yet included in a bundle or dismissed, oldest first (blocking)
+pub fn pending_crash_reports() -> Result<Vec<CrashReport>, String> {
+    Ok(crash_report_paths()?
+        .into_iter()
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let created_at = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+                .unwrap_or_default();
+            Some(CrashReport {
+                file_name: path.file_name()?.to_string_lossy().into_owned(),
+                created_at,
+                summary: content.lines().next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Delete pending crash reports without sending them (blocking)
+pub fn dismiss_crash_reports() -> Result<usize, String> {
+    let paths = crash_report_paths()?;
+    for path in &paths {
+        std::fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    log::info!("Dismissed {} crash reports", paths.len());
+    Ok(paths.len())
+}
+
+fn crash_report_paths() -> Result<Vec<PathBuf>, String> {
+    let dir = get_crash_dir()?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "txt"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Replace the values of secret-looking keys, at any depth
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            for (key, value) in obj.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if !value.is_null() && SECRET_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *value = serde_json::Value::String("[redacted]".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// The last `MAX_LOG_BYTES` of a file
+fn read_tail(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len > MAX_LOG_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_LOG_BYTES))
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    }
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(content)
+}
+
+/// The last `lines` lines of a terminal's scrollback, as plain text
+fn scrollback_tail(scrollback: &str, lines: usize) -> String {
+    let text = terminal_backend::strip_ansi(scrollback);
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+fn system_info(terminals: usize) -> serde_json::Value {
+    serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "os_version": sysinfo::System::long_os_version(),
+        "kernel_version": sysinfo::System::kernel_version(),
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "viewer_mode": crate::viewer::is_active(),
+        "open_terminals": terminals,
+    })
+}
+
+/// Zip logs, redacted settings, system details, terminal scrollback and any
+/// pending crash reports for a bug report. Written to `dest_zip`, or a new file
+/// under the data directory (blocking).
+pub fn generate(dest_zip: Option<PathBuf>, inputs: BundleInputs) -> Result<DiagnosticsBundle, String> {
+    let dest_zip = match dest_zip {
+        Some(path) => path,
+        None => {
+            let dir = get_bundle_dir()?;
+            std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            dir.join(format!("chimera-diagnostics-{}.zip", chrono::Utc::now().format("%Y%m%dT%H%M%SZ")))
+        }
+    };
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let to_json = |value: &serde_json::Value| serde_json::to_vec_pretty(value).map_err(|e| e.to_string());
+
+    entries.push(("system.json".to_string(), to_json(&system_info(inputs.terminals.len()))?));
+
+    if let Some(mut settings) = inputs.settings {
+        redact(&mut settings);
+        entries.push(("settings.json".to_string(), to_json(&settings)?));
+    }
+
+    let mut logs: Vec<(String, PathBuf)> = Vec::new();
+    if let Some(dir) = &inputs.app_log_dir {
+        if let Ok(dir_entries) = std::fs::read_dir(dir) {
+            logs.extend(dir_entries.flatten().map(|entry| entry.path()).filter(|p| p.is_file()).filter_map(|p| {
+                Some((format!("logs/app/{}", p.file_name()?.to_string_lossy()), p))
+            }));
+        }
+    }
+    match python_backend::log_files() {
+        Ok(files) => logs.extend(
+            files
+                .into_iter()
+                .filter_map(|p| Some((format!("logs/backend/{}", p.file_name()?.to_string_lossy()), p))),
+        ),
+        Err(e) => log::warn!("Leaving backend logs out of diagnostics: {}", e),
+    }
+    for (name, path) in logs {
+        match read_tail(&path) {
+            Ok(content) => entries.push((name, content)),
+            Err(e) => log::warn!("Leaving log out of diagnostics: {}", e),
+        }
+    }
+
+    for terminal in &inputs.terminals {
+        entries.push((
+            format!("terminals/{}.txt", terminal.terminal_id),
+            scrollback_tail(&terminal.scrollback, inputs.scrollback_lines).into_bytes(),
+        ));
+    }
+
+    let crash_reports = if inputs.include_crash_reports { crash_report_paths()? } else { Vec::new() };
+    for path in &crash_reports {
+        let content = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        entries.push((format!("crash-reports/{}", name), content));
+    }
+
+    // Written beside the destination and renamed, so a failed bundle leaves nothing
+    let temp = dest_zip.with_extension("zip.tmp");
+    let file = std::fs::File::create(&temp).map_err(|e| format!("Failed to create diagnostics bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in &entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+        zip.write_all(content)
+            .map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+    std::fs::rename(&temp, &dest_zip).map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+
+    // They're in the bundle now, so stop offering them
+    for path in &crash_reports {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove crash report {}: {}", path.display(), e);
+        }
+    }
+
+    let bytes = std::fs::metadata(&dest_zip).map(|m| m.len()).unwrap_or(0);
+    log::info!("Wrote diagnostics bundle to {} ({} files)", dest_zip.display(), entries.len());
+    Ok(DiagnosticsBundle {
+        path: dest_zip.to_string_lossy().into_owned(),
+        bytes,
+        files: entries.into_iter().map(|(name, _)| name).collect(),
+        crash_reports: crash_reports.len(),
+    })
+}
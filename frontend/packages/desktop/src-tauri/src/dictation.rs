@@ -0,0 +1,507 @@
+use serde::Serialize;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use crate::event_bus::EventBus;
+
+#[cfg(target_os = "macos")]
+use macos as backend;
+#[cfg(not(any(target_os = "macos", windows)))]
+use whisper as backend;
+#[cfg(windows)]
+use windows_speech as backend;
+
+/// A recognition update sent to the frontend on the "dictation" topic.
+/// `partial` text replaces the previous partial; `final` text is committed.
+#[derive(Debug, Clone, Serialize)]
+pub struct DictationEvent {
+    pub session_id: String,
+    pub kind: &'static str,
+    pub text: Option<String>,
+}
+
+/// Emits events for one dictation session
+#[derive(Clone)]
+struct Session {
+    id: String,
+    bus: Arc<EventBus>,
+}
+
+impl Session {
+    fn emit(&self, kind: &'static str, text: Option<String>) {
+        self.bus.publish(
+            "dictation",
+            DictationEvent {
+                session_id: self.id.clone(),
+                kind,
+                text,
+            },
+        );
+    }
+}
+
+/// A running dictation session; the recognizer lives on its own thread
+struct ActiveDictation {
+    session_id: String,
+    stop: mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+/// Captures microphone audio and streams recognized text to the frontend
+pub struct DictationManager {
+    bus: Arc<EventBus>,
+    active: Mutex<Option<ActiveDictation>>,
+}
+
+impl DictationManager {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            bus,
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Start a dictation session, replacing any running one. Blocks until the
+    /// recognizer is listening (or fails), so call it off the main thread.
+    pub fn start(&self, language: Option<String>) -> Result<String, String> {
+        self.stop();
+
+        let session = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            bus: self.bus.clone(),
+        };
+        let session_id = session.id.clone();
+
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name("dictation".to_string())
+            .spawn(move || match backend::setup(session.clone(), language) {
+                Ok(handle) => {
+                    let _ = ready_tx.send(Ok(()));
+                    let _ = stop_rx.recv();
+                    handle.stop();
+                    session.emit("stopped", None);
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            })
+            .map_err(|e| format!("Failed to start dictation thread: {}", e))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| "Dictation thread exited unexpectedly".to_string())??;
+
+        log::info!("Dictation session {} started", session_id);
+
+        *self.active.lock().unwrap() = Some(ActiveDictation {
+            session_id: session_id.clone(),
+            stop: stop_tx,
+            thread,
+        });
+
+        Ok(session_id)
+    }
+
+    /// Stop the running session, if any, flushing any pending text
+    pub fn stop(&self) {
+        let active = self.active.lock().unwrap().take();
+        if let Some(active) = active {
+            let _ = active.stop.send(());
+            let _ = active.thread.join();
+            log::info!("Dictation session {} stopped", active.session_id);
+        }
+    }
+}
+
+/// macOS Speech framework recognizer fed from an AVAudioEngine input tap
+#[cfg(target_os = "macos")]
+mod macos {
+    use block2::RcBlock;
+    use objc2::rc::Retained;
+    use objc2::AnyThread;
+    use objc2_avf_audio::{AVAudioEngine, AVAudioPCMBuffer, AVAudioTime};
+    use objc2_foundation::{NSError, NSLocale, NSString};
+    use objc2_speech::{
+        SFSpeechAudioBufferRecognitionRequest, SFSpeechRecognitionResult, SFSpeechRecognitionTask, SFSpeechRecognizer,
+        SFSpeechRecognizerAuthorizationStatus,
+    };
+    use std::ptr::NonNull;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::Session;
+
+    pub struct Handle {
+        engine: Retained<AVAudioEngine>,
+        request: Retained<SFSpeechAudioBufferRecognitionRequest>,
+        task: Retained<SFSpeechRecognitionTask>,
+        _recognizer: Retained<SFSpeechRecognizer>,
+    }
+
+    /// Ensure the user has granted speech recognition, prompting if needed
+    fn authorize() -> Result<(), String> {
+        let mut status = unsafe { SFSpeechRecognizer::authorizationStatus() };
+
+        if status == SFSpeechRecognizerAuthorizationStatus::NotDetermined {
+            let (tx, rx) = mpsc::channel();
+            let handler = RcBlock::new(move |status: SFSpeechRecognizerAuthorizationStatus| {
+                let _ = tx.send(status);
+            });
+            unsafe { SFSpeechRecognizer::requestAuthorization(&handler) };
+            status = rx
+                .recv_timeout(Duration::from_secs(120))
+                .map_err(|_| "Timed out waiting for speech recognition permission".to_string())?;
+        }
+
+        if status != SFSpeechRecognizerAuthorizationStatus::Authorized {
+            return Err("Speech recognition permission was denied".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn setup(session: Session, language: Option<String>) -> Result<Handle, String> {
+        authorize()?;
+
+        let recognizer = match language {
+            Some(language) => {
+                let locale = NSLocale::initWithLocaleIdentifier(NSLocale::alloc(), &NSString::from_str(&language));
+                unsafe { SFSpeechRecognizer::initWithLocale(SFSpeechRecognizer::alloc(), &locale) }
+            }
+            None => unsafe { SFSpeechRecognizer::init(SFSpeechRecognizer::alloc()) },
+        }
+        .ok_or("Speech recognition is not supported for this language")?;
+
+        if !unsafe { recognizer.isAvailable() } {
+            return Err("Speech recognition is currently unavailable".to_string());
+        }
+
+        let request = unsafe { SFSpeechAudioBufferRecognitionRequest::new() };
+        unsafe { request.setShouldReportPartialResults(true) };
+
+        let engine = unsafe { AVAudioEngine::new() };
+        let input = unsafe { engine.inputNode() };
+        let format = unsafe { input.outputFormatForBus(0) };
+
+        let tap_request = request.clone();
+        let tap = RcBlock::new(move |buffer: NonNull<AVAudioPCMBuffer>, _when: NonNull<AVAudioTime>| unsafe {
+            tap_request.appendAudioPCMBuffer(buffer.as_ref());
+        });
+        unsafe { input.installTapOnBus_bufferSize_format_block(0, 1024, Some(&format), RcBlock::as_ptr(&tap)) };
+
+        let results = RcBlock::new(move |result: *mut SFSpeechRecognitionResult, error: *mut NSError| {
+            if let Some(result) = unsafe { result.as_ref() } {
+                let text = unsafe { result.bestTranscription().formattedString() }.to_string();
+                let kind = if unsafe { result.isFinal() } { "final" } else { "partial" };
+                session.emit(kind, Some(text));
+            } else if let Some(error) = unsafe { error.as_ref() } {
+                session.emit("error", Some(error.localizedDescription().to_string()));
+            }
+        });
+        let task = unsafe { recognizer.recognitionTaskWithRequest_resultHandler(&request, &results) };
+
+        unsafe { engine.prepare() };
+        if let Err(e) = unsafe { engine.startAndReturnError() } {
+            unsafe {
+                task.cancel();
+                input.removeTapOnBus(0);
+            }
+            return Err(format!("Failed to start audio capture: {}", e.localizedDescription()));
+        }
+
+        Ok(Handle {
+            engine,
+            request,
+            task,
+            _recognizer: recognizer,
+        })
+    }
+
+    impl Handle {
+        /// Stop capturing; the recognizer delivers its final result asynchronously
+        pub fn stop(self) {
+            unsafe {
+                self.engine.stop();
+                self.engine.inputNode().removeTapOnBus(0);
+                self.request.endAudio();
+                self.task.finish();
+            }
+        }
+    }
+}
+
+/// Windows.Media.SpeechRecognition continuous dictation
+#[cfg(windows)]
+mod windows_speech {
+    use windows::core::{Ref, HSTRING};
+    use windows::Foundation::TypedEventHandler;
+    use windows::Globalization::Language;
+    use windows::Media::SpeechRecognition::{
+        SpeechContinuousRecognitionResultGeneratedEventArgs, SpeechContinuousRecognitionSession,
+        SpeechRecognitionHypothesisGeneratedEventArgs, SpeechRecognitionResultStatus, SpeechRecognizer,
+    };
+
+    use super::Session;
+
+    pub struct Handle {
+        continuous: SpeechContinuousRecognitionSession,
+        _recognizer: SpeechRecognizer,
+    }
+
+    fn describe(e: windows::core::Error) -> String {
+        format!("Speech recognition failed: {}", e.message())
+    }
+
+    pub fn setup(session: Session, language: Option<String>) -> Result<Handle, String> {
+        let recognizer = match language {
+            Some(tag) => Language::CreateLanguage(&HSTRING::from(tag)).and_then(|l| SpeechRecognizer::Create(&l)),
+            None => SpeechRecognizer::new(),
+        }
+        .map_err(describe)?;
+
+        let compilation = recognizer
+            .CompileConstraintsAsync()
+            .and_then(|op| op.get())
+            .map_err(describe)?;
+        if compilation.Status().map_err(describe)? != SpeechRecognitionResultStatus::Success {
+            return Err("Speech recognition is unavailable (check the system speech language)".to_string());
+        }
+
+        let partial = session.clone();
+        recognizer
+            .HypothesisGenerated(&TypedEventHandler::new(
+                move |_, args: Ref<'_, SpeechRecognitionHypothesisGeneratedEventArgs>| {
+                    let text = args.ok()?.Hypothesis()?.Text()?.to_string();
+                    partial.emit("partial", Some(text));
+                    Ok(())
+                },
+            ))
+            .map_err(describe)?;
+
+        let continuous = recognizer.ContinuousRecognitionSession().map_err(describe)?;
+        continuous
+            .ResultGenerated(&TypedEventHandler::new(
+                move |_, args: Ref<'_, SpeechContinuousRecognitionResultGeneratedEventArgs>| {
+                    let text = args.ok()?.Result()?.Text()?.to_string();
+                    if !text.is_empty() {
+                        session.emit("final", Some(text));
+                    }
+                    Ok(())
+                },
+            ))
+            .map_err(describe)?;
+
+        continuous.StartAsync().and_then(|op| op.get()).map_err(describe)?;
+
+        Ok(Handle {
+            continuous,
+            _recognizer: recognizer,
+        })
+    }
+
+    impl Handle {
+        pub fn stop(self) {
+            if let Err(e) = self.continuous.StopAsync().and_then(|op| op.get()) {
+                log::warn!("Failed to stop speech recognition: {}", e.message());
+            }
+        }
+    }
+}
+
+/// Fallback: capture with cpal and transcribe fixed-length chunks with the
+/// whisper.cpp CLI (`CHIMERA_WHISPER_BIN`, `CHIMERA_WHISPER_MODEL`)
+#[cfg(not(any(target_os = "macos", windows)))]
+mod whisper {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::Session;
+
+    /// whisper.cpp expects 16 kHz mono audio
+    const SAMPLE_RATE: u32 = 16_000;
+
+    /// Length of audio transcribed per whisper invocation
+    const CHUNK_SECONDS: u32 = 5;
+
+    /// Chunks quieter than this RMS level are skipped
+    const SILENCE_RMS: f32 = 0.01;
+
+    pub struct Handle {
+        stream: cpal::Stream,
+        stopped: Arc<AtomicBool>,
+        worker: std::thread::JoinHandle<()>,
+    }
+
+    /// Downmixes interleaved input to mono and decimates it to 16 kHz
+    struct Resampler {
+        channels: usize,
+        step: f64,
+        phase: f64,
+    }
+
+    impl Resampler {
+        fn push(&mut self, samples: impl Iterator<Item = f32>, out: &mut Vec<f32>) {
+            let mut sum = 0.0;
+            let mut count = 0;
+            for sample in samples {
+                sum += sample;
+                count += 1;
+                if count == self.channels {
+                    self.phase += self.step;
+                    if self.phase >= 1.0 {
+                        self.phase -= 1.0;
+                        out.push(sum / self.channels as f32);
+                    }
+                    sum = 0.0;
+                    count = 0;
+                }
+            }
+        }
+    }
+
+    /// Write 16-bit PCM mono samples as a WAV file
+    fn write_wav(path: &Path, samples: &[f32]) -> std::io::Result<()> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::with_capacity(44 + data_len as usize);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+        bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+        }
+        std::fs::write(path, bytes)
+    }
+
+    /// Transcribe a chunk with the whisper.cpp CLI
+    fn transcribe(binary: &str, model: &str, language: &str, samples: &[f32]) -> Result<String, String> {
+        let wav: PathBuf = std::env::temp_dir().join(format!("chimera-dictation-{}.wav", uuid::Uuid::new_v4()));
+        write_wav(&wav, samples).map_err(|e| format!("Failed to write audio chunk: {}", e))?;
+
+        let output = std::process::Command::new(binary)
+            .args(["-m", model, "-l", language, "-nt", "-np", "-f"])
+            .arg(&wav)
+            .output();
+        let _ = std::fs::remove_file(&wav);
+
+        let output = output.map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "whisper.cpp failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    pub fn setup(session: Session, language: Option<String>) -> Result<Handle, String> {
+        let model = std::env::var("CHIMERA_WHISPER_MODEL")
+            .map_err(|_| "Dictation needs CHIMERA_WHISPER_MODEL set to a whisper.cpp model file".to_string())?;
+        let binary = std::env::var("CHIMERA_WHISPER_BIN").unwrap_or_else(|_| "whisper-cli".to_string());
+        // whisper.cpp takes bare language codes ("en"), not locales ("en-US")
+        let language = language
+            .and_then(|l| l.split(['-', '_']).next().map(|s| s.to_lowercase()))
+            .unwrap_or_else(|| "auto".to_string());
+
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("No microphone available")?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| format!("Failed to query microphone: {}", e))?;
+
+        let buffer = Arc::new(Mutex::new(Vec::<f32>::new()));
+        let mut resampler = Resampler {
+            channels: config.channels() as usize,
+            step: SAMPLE_RATE as f64 / config.sample_rate().0 as f64,
+            phase: 0.0,
+        };
+
+        let error_session = session.clone();
+        let on_error = move |e: cpal::StreamError| error_session.emit("error", Some(e.to_string()));
+
+        let sink = buffer.clone();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &config.into(),
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    resampler.push(data.iter().copied(), &mut sink.lock().unwrap());
+                },
+                on_error,
+                None,
+            ),
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &config.into(),
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    let samples = data.iter().map(|s| *s as f32 / i16::MAX as f32);
+                    resampler.push(samples, &mut sink.lock().unwrap());
+                },
+                on_error,
+                None,
+            ),
+            other => return Err(format!("Unsupported microphone sample format: {:?}", other)),
+        }
+        .map_err(|e| format!("Failed to open microphone: {}", e))?;
+
+        stream.play().map_err(|e| format!("Failed to start audio capture: {}", e))?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = stopped.clone();
+        let chunk_len = (SAMPLE_RATE * CHUNK_SECONDS) as usize;
+
+        let worker = std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_millis(250));
+            let stopping = worker_stopped.load(Ordering::SeqCst);
+
+            let chunk = {
+                let mut samples = buffer.lock().unwrap();
+                if samples.len() < chunk_len && !stopping {
+                    continue;
+                }
+                std::mem::take(&mut *samples)
+            };
+
+            let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len().max(1) as f32).sqrt();
+            if rms >= SILENCE_RMS {
+                match transcribe(&binary, &model, &language, &chunk) {
+                    Ok(text) if !text.is_empty() => session.emit("final", Some(text)),
+                    Ok(_) => {}
+                    Err(e) => session.emit("error", Some(e)),
+                }
+            }
+
+            if stopping {
+                break;
+            }
+        });
+
+        Ok(Handle {
+            stream,
+            stopped,
+            worker,
+        })
+    }
+
+    impl Handle {
+        /// Stop capturing and transcribe whatever audio is left
+        pub fn stop(self) {
+            drop(self.stream);
+            self.stopped.store(true, Ordering::SeqCst);
+            let _ = self.worker.join();
+        }
+    }
+}
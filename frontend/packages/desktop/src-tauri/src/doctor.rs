@@ -0,0 +1,431 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::Disks;
+
+use crate::filesystem;
+use crate::python_backend;
+use crate::settings;
+
+/// Longest a `--version` probe may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Free space below which the data directory fails the check
+const MIN_FREE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Free space below which the data directory gets a warning
+const LOW_FREE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Outcome of one check, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One setup check
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    /// Stable id, e.g. `backend` or `disk_space`
+    pub id: &'static str,
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What to do about a warning or failure
+    pub hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(id: &'static str, name: &'static str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            id,
+            name,
+            status,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Result of `run_doctor`
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// Worst status of any check
+    pub status: CheckStatus,
+    /// Whether the bundled backend was checked rather than the development setup
+    pub production: bool,
+    pub checks: Vec<DoctorCheck>,
+    pub checked_at: String,
+}
+
+/// Look `program` up as the OS would run it: a path as given, else each PATH
+/// directory, trying the PATHEXT extensions on Windows
+pub(crate) fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') || program.contains('\\') {
+        return Path::new(program).is_file().then(|| PathBuf::from(program));
+    }
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(str::to_string)
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// First line `program args` prints, e.g. its version; an error if it can't be
+/// run, fails or takes longer than `PROBE_TIMEOUT`
+async fn probe(program: &Path, args: &[&str]) -> Result<String, String> {
+    let mut command = tokio::process::Command::new(program);
+    command.args(args).stdin(std::process::Stdio::null()).kill_on_drop(true);
+    let output = tokio::time::timeout(PROBE_TIMEOUT, command.output())
+        .await
+        .map_err(|_| {
+            format!(
+                "{} didn't answer within {}s",
+                program.display(),
+                PROBE_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| format!("Failed to run {}: {}", program.display(), e))?;
+    let first_line = |bytes: &[u8]| {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+    };
+    if !output.status.success() {
+        let detail = first_line(&output.stderr).unwrap_or_else(|| output.status.to_string());
+        return Err(format!("{} failed: {}", program.display(), detail));
+    }
+    Ok(first_line(&output.stdout)
+        .or_else(|| first_line(&output.stderr))
+        .unwrap_or_default())
+}
+
+/// The backend program the development setup runs, and the Python it uses
+async fn check_development_backend(checks: &mut Vec<DoctorCheck>) {
+    let command = settings::backend_command();
+    let (program, root) = match python_backend::development_command(&command) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            checks.push(
+                DoctorCheck::new("backend", "Backend command", CheckStatus::Fail, e)
+                    .hint("Fix the backend command in Settings"),
+            );
+            return;
+        }
+    };
+
+    let Some(path) = find_program(&program) else {
+        let hint = if program == "uv" {
+            "Install uv from https://docs.astral.sh/uv/ and restart the app"
+        } else {
+            "Install it or point the backend command in Settings at it"
+        };
+        checks.push(
+            DoctorCheck::new(
+                "backend",
+                "Backend command",
+                CheckStatus::Fail,
+                format!("{} not found", program),
+            )
+            .hint(hint),
+        );
+        return;
+    };
+    match probe(&path, &["--version"]).await {
+        Ok(version) => checks.push(DoctorCheck::new(
+            "backend",
+            "Backend command",
+            CheckStatus::Pass,
+            format!("{} ({})", path.display(), version),
+        )),
+        Err(e) => {
+            checks.push(
+                DoctorCheck::new("backend", "Backend command", CheckStatus::Fail, e)
+                    .hint("Reinstall it or choose another backend command in Settings"),
+            );
+            return;
+        }
+    }
+
+    if !root.is_dir() {
+        checks.push(
+            DoctorCheck::new(
+                "backend_source",
+                "Backend source",
+                CheckStatus::Fail,
+                format!("{} doesn't exist", root.display()),
+            )
+            .hint("Set the backend directory in Settings to a checkout of the Python backend"),
+        );
+    } else if command.command.is_none() && !root.join("pyproject.toml").is_file() {
+        checks.push(
+            DoctorCheck::new(
+                "backend_source",
+                "Backend source",
+                CheckStatus::Warn,
+                format!("No pyproject.toml in {}", root.display()),
+            )
+            .hint("Set the backend directory in Settings to a checkout of the Python backend"),
+        );
+    } else {
+        checks.push(DoctorCheck::new(
+            "backend_source",
+            "Backend source",
+            CheckStatus::Pass,
+            root.display().to_string(),
+        ));
+    }
+
+    // uv finds (or downloads) Python itself; otherwise the configured interpreter must run
+    let python = match (&command.python, program == "uv") {
+        (Some(python), _) => find_program(python).ok_or_else(|| format!("{} not found", python)),
+        (None, true) => {
+            let found = probe(&path, &["python", "find"]).await.map(PathBuf::from);
+            if let Err(e) = &found {
+                checks.push(
+                    DoctorCheck::new(
+                        "python",
+                        "Python",
+                        CheckStatus::Warn,
+                        format!("uv has no Python installed yet: {}", e),
+                    )
+                    .hint("uv downloads one on first start, which needs network access; or run `uv python install`"),
+                );
+                return;
+            }
+            found
+        }
+        (None, false) => return,
+    };
+    let check = match python {
+        Ok(python) => match probe(&python, &["--version"]).await {
+            Ok(version) => DoctorCheck::new(
+                "python",
+                "Python",
+                CheckStatus::Pass,
+                format!("{} ({})", python.display(), version),
+            ),
+            Err(e) => DoctorCheck::new("python", "Python", CheckStatus::Fail, e)
+                .hint("Point the backend Python in Settings at a working interpreter"),
+        },
+        Err(e) => DoctorCheck::new("python", "Python", CheckStatus::Fail, e)
+            .hint("Point the backend Python in Settings at a working interpreter"),
+    };
+    checks.push(check);
+}
+
+fn check_bundled_backend() -> DoctorCheck {
+    match python_backend::bundled_backend_path() {
+        Ok(path) if path.is_file() => DoctorCheck::new(
+            "backend",
+            "Bundled backend",
+            CheckStatus::Pass,
+            path.display().to_string(),
+        ),
+        Ok(path) => DoctorCheck::new(
+            "backend",
+            "Bundled backend",
+            CheckStatus::Fail,
+            format!("{} is missing", path.display()),
+        )
+        .hint("Reinstall the app"),
+        Err(e) => DoctorCheck::new("backend", "Bundled backend", CheckStatus::Fail, e).hint("Reinstall the app"),
+    }
+}
+
+/// node, which the ink CLI terminal profile runs under in development; a warning
+/// at worst, since only that profile needs it
+async fn check_node(production: bool) -> DoctorCheck {
+    if production {
+        // Shipped next to the bundled backend
+        return match python_backend::bundled_backend_path().map(|path| path.with_file_name("ink-cli")) {
+            Ok(path) if path.is_file() => {
+                DoctorCheck::new("node", "Ink CLI", CheckStatus::Pass, path.display().to_string())
+            }
+            Ok(path) => DoctorCheck::new(
+                "node",
+                "Ink CLI",
+                CheckStatus::Warn,
+                format!("{} is missing", path.display()),
+            )
+            .hint("Reinstall the app to use the Ink CLI terminal profile"),
+            Err(e) => DoctorCheck::new("node", "Ink CLI", CheckStatus::Warn, e),
+        };
+    }
+    let Some(path) = find_program("node") else {
+        return DoctorCheck::new("node", "Node.js", CheckStatus::Warn, "node not found")
+            .hint("Install Node.js to use the Ink CLI terminal profile");
+    };
+    match probe(&path, &["--version"]).await {
+        Ok(version) => DoctorCheck::new(
+            "node",
+            "Node.js",
+            CheckStatus::Pass,
+            format!("{} ({})", path.display(), version),
+        ),
+        Err(e) => DoctorCheck::new("node", "Node.js", CheckStatus::Warn, e)
+            .hint("Reinstall Node.js to use the Ink CLI terminal profile"),
+    }
+}
+
+async fn check_data_dir() -> DoctorCheck {
+    let probe = filesystem::probe_data_dir().await;
+    let problems = || {
+        probe
+            .problems
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    let hint = || {
+        probe
+            .problems
+            .iter()
+            .find_map(|p| p.hint)
+            .unwrap_or("Choose another data directory in Settings")
+    };
+    if !probe.writable {
+        DoctorCheck::new("data_dir", "Data directory", CheckStatus::Fail, problems()).hint(hint())
+    } else if probe.degraded() {
+        DoctorCheck::new("data_dir", "Data directory", CheckStatus::Warn, problems()).hint(hint())
+    } else {
+        DoctorCheck::new(
+            "data_dir",
+            "Data directory",
+            CheckStatus::Pass,
+            format!("{} is writable", probe.data_dir),
+        )
+    }
+}
+
+/// Free space on the disk holding the data directory: the mount with the longest
+/// matching path (blocking)
+fn check_disk_space() -> DoctorCheck {
+    let dir = match filesystem::get_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => return DoctorCheck::new("disk_space", "Free disk space", CheckStatus::Warn, e),
+    };
+    // The directory may not exist yet; its nearest existing ancestor is on the same disk
+    let dir = dir.ancestors().find_map(|dir| dir.canonicalize().ok()).unwrap_or(dir);
+    let disks = Disks::new_with_refreshed_list();
+    let Some(disk) = disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+    else {
+        return DoctorCheck::new(
+            "disk_space",
+            "Free disk space",
+            CheckStatus::Warn,
+            format!("Couldn't find the disk holding {}", dir.display()),
+        );
+    };
+
+    let free = disk.available_space();
+    let message = format!(
+        "{:.1} GB free on {}",
+        free as f64 / (1024.0 * 1024.0 * 1024.0),
+        disk.mount_point().display()
+    );
+    let status = if free < MIN_FREE_BYTES {
+        CheckStatus::Fail
+    } else if free < LOW_FREE_BYTES {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    let check = DoctorCheck::new("disk_space", "Free disk space", status, message);
+    match status {
+        CheckStatus::Pass => check,
+        _ => check.hint("Free up space, or move the data directory in Settings"),
+    }
+}
+
+/// Whether the backend's fixed port, if it has one, can be bound. `backend_port`
+/// is the port the running default backend holds, which counts as available.
+fn check_port(backend_port: Option<u16>) -> DoctorCheck {
+    let port = python_backend::requested_port();
+    if port == 0 {
+        return DoctorCheck::new("port", "Backend port", CheckStatus::Pass, "Any free port");
+    }
+    if backend_port == Some(port) {
+        return DoctorCheck::new(
+            "port",
+            "Backend port",
+            CheckStatus::Pass,
+            format!("{} is used by the backend", port),
+        );
+    }
+    match std::net::TcpListener::bind(("127.0.0.1", port)) {
+        Ok(_) => DoctorCheck::new("port", "Backend port", CheckStatus::Pass, format!("{} is free", port)),
+        Err(e) => DoctorCheck::new(
+            "port",
+            "Backend port",
+            CheckStatus::Fail,
+            format!("Port {} is unavailable: {}", port, e),
+        )
+        .hint("Stop whatever is using it, or change the backend port in Settings"),
+    }
+}
+
+/// Check what the app needs to start: the backend (uv and Python in development,
+/// the bundled executable in production), node for the ink CLI, a writable data
+/// directory with free space, and the backend port. `backend_port` is the port
+/// the running default backend holds, if one is running.
+pub async fn run(backend_port: Option<u16>) -> DoctorReport {
+    let production = python_backend::is_production();
+    let mut checks = Vec::new();
+    if production {
+        checks.push(check_bundled_backend());
+    } else {
+        check_development_backend(&mut checks).await;
+    }
+    checks.push(check_node(production).await);
+    checks.push(check_data_dir().await);
+    let blocking_checks = filesystem::blocking(move || Ok(vec![check_disk_space(), check_port(backend_port)])).await;
+    match blocking_checks {
+        Ok(blocking_checks) => checks.extend(blocking_checks),
+        Err(e) => log::warn!("Doctor checks failed: {}", e),
+    }
+
+    let status = checks
+        .iter()
+        .map(|check| check.status)
+        .max()
+        .unwrap_or(CheckStatus::Pass);
+    log::info!(
+        "Doctor: {:?}, {} of {} checks passed",
+        status,
+        checks.iter().filter(|check| check.status == CheckStatus::Pass).count(),
+        checks.len()
+    );
+    DoctorReport {
+        status,
+        production,
+        checks,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::filesystem;
+
+/// Keystrokes are written at most this often
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Drafts live in `drafts/<thread_id>.txt` in the data directory
+fn get_drafts_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("drafts"))
+}
+
+fn draft_path(thread_id: &str) -> Result<PathBuf, String> {
+    // Validates the id the same way as thread files
+    filesystem::get_thread_path(thread_id)?;
+    Ok(get_drafts_dir()?.join(format!("{}.txt", thread_id)))
+}
+
+/// Replace or remove a draft file (blocking)
+fn write(path: &Path, content: Option<&str>) -> Result<(), String> {
+    let Some(content) = content else {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove draft: {}", e)),
+            _ => Ok(()),
+        };
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let temp = path.with_extension("txt.tmp");
+    std::fs::write(&temp, content).map_err(|e| format!("Failed to write draft: {}", e))?;
+    std::fs::rename(&temp, path).map_err(|e| format!("Failed to replace draft: {}", e))
+}
+
+/// Unsent messages per thread, kept on disk so a crash or accidental quit doesn't
+/// lose them. Saves are held for a moment and written in the background, so the
+/// frontend can save on every keystroke.
+pub struct DraftStore {
+    /// Drafts not yet written, by file path so a workspace switch in between
+    /// can't send them to the wrong data directory; None removes the file
+    pending: Mutex<HashMap<PathBuf, Option<String>>>,
+    changed: tokio::sync::Notify,
+}
+
+impl DraftStore {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            changed: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// Remember a thread's draft; an empty one is the same as clearing it
+    pub fn save(&self, thread_id: &str, content: String) -> Result<(), String> {
+        let path = draft_path(thread_id)?;
+        let content = (!content.trim().is_empty()).then_some(content);
+        self.pending.lock().unwrap().insert(path, content);
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    /// A thread's draft, including one not yet written
+    pub async fn get(&self, thread_id: &str) -> Result<Option<String>, String> {
+        let path = draft_path(thread_id)?;
+        if let Some(pending) = self.pending.lock().unwrap().get(&path) {
+            return Ok(pending.clone());
+        }
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read draft: {}", e)),
+        }
+    }
+
+    /// Remove a thread's draft now, e.g. once the message is sent
+    pub async fn clear(&self, thread_id: &str) -> Result<(), String> {
+        let path = draft_path(thread_id)?;
+        self.pending.lock().unwrap().remove(&path);
+        filesystem::blocking(move || write(&path, None)).await
+    }
+
+    /// Every draft in the current data directory, by thread id (blocking)
+    pub fn all(&self) -> HashMap<String, String> {
+        let Ok(dir) = get_drafts_dir() else {
+            return HashMap::new();
+        };
+        let mut drafts: HashMap<String, String> = std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("txt") {
+                    return None;
+                }
+                let thread_id = path.file_stem()?.to_str()?.to_string();
+                Some((thread_id, std::fs::read_to_string(&path).ok()?))
+            })
+            .collect();
+
+        for (path, content) in self.pending.lock().unwrap().iter() {
+            if path.parent() != Some(dir.as_path()) {
+                continue;
+            }
+            let Some(thread_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match content {
+                Some(content) => drafts.insert(thread_id.to_string(), content.clone()),
+                None => drafts.remove(thread_id),
+            };
+        }
+        drafts
+    }
+
+    /// Write every pending draft now (blocking)
+    pub fn flush(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for (path, content) in pending {
+            if let Err(e) = write(&path, content.as_deref()) {
+                log::warn!("{}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Write saves in the background, a moment after the last keystroke rather
+    /// than on every one
+    pub fn start(self: &Arc<Self>) {
+        let store = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                store.changed.notified().await;
+                tokio::time::sleep(SAVE_DELAY).await;
+                let writer = store.clone();
+                let _ = filesystem::blocking(move || {
+                    writer.flush();
+                    Ok(())
+                })
+                .await;
+            }
+        });
+    }
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::doctor::find_program;
+use crate::error::ChimeraError;
+
+/// Editor `open_in_editor` uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditorKind {
+    /// The first of VS Code, Cursor, Zed and Sublime Text that's installed, else
+    /// the file's default app
+    #[default]
+    Auto,
+    Vscode,
+    Cursor,
+    Zed,
+    Sublime,
+    /// The file's default app, without jumping to the line
+    System,
+    /// `command` from the preference
+    Custom,
+}
+
+/// The `editor` setting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorPreference {
+    pub kind: EditorKind,
+    /// For `custom`: the program and its arguments, in which `{path}`, `{line}`
+    /// and `{column}` are replaced (line and column with 1 when not known)
+    pub command: Option<Vec<String>>,
+}
+
+impl EditorPreference {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind != EditorKind::Custom {
+            return Ok(());
+        }
+        let Some(command) = self
+            .command
+            .as_ref()
+            .filter(|c| c.first().is_some_and(|p| !p.trim().is_empty()))
+        else {
+            return Err("editor.command is required for a custom editor".to_string());
+        };
+        if !command.iter().any(|arg| arg.contains("{path}")) {
+            return Err("editor.command must include {path}".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum GotoStyle {
+    /// `--goto path:line:column`
+    GotoFlag,
+    /// `path:line:column`
+    Suffix,
+}
+
+/// An editor that can open a file at a line
+struct Editor {
+    kind: EditorKind,
+    name: &'static str,
+    /// Its command line tool as found on PATH
+    cli: &'static str,
+    style: GotoStyle,
+}
+
+/// Editors in the order `auto` looks for them
+const EDITORS: [Editor; 4] = [
+    Editor {
+        kind: EditorKind::Vscode,
+        name: "VS Code",
+        cli: "code",
+        style: GotoStyle::GotoFlag,
+    },
+    Editor {
+        kind: EditorKind::Cursor,
+        name: "Cursor",
+        cli: "cursor",
+        style: GotoStyle::GotoFlag,
+    },
+    Editor {
+        kind: EditorKind::Zed,
+        name: "Zed",
+        cli: "zed",
+        style: GotoStyle::Suffix,
+    },
+    Editor {
+        kind: EditorKind::Sublime,
+        name: "Sublime Text",
+        cli: "subl",
+        style: GotoStyle::Suffix,
+    },
+];
+
+/// Where an editor's command line tool is installed when it isn't on PATH: apps
+/// started from the Finder don't get the shell's PATH, and not every Windows
+/// installer adds to it
+#[cfg(target_os = "macos")]
+fn installed_cli(kind: EditorKind) -> Vec<PathBuf> {
+    let bundled = match kind {
+        EditorKind::Vscode => "Visual Studio Code.app/Contents/Resources/app/bin/code",
+        EditorKind::Cursor => "Cursor.app/Contents/Resources/app/bin/cursor",
+        EditorKind::Zed => "Zed.app/Contents/MacOS/cli",
+        EditorKind::Sublime => "Sublime Text.app/Contents/SharedSupport/bin/subl",
+        _ => return Vec::new(),
+    };
+    let mut candidates = vec![Path::new("/Applications").join(bundled)];
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join("Applications").join(bundled));
+    }
+    candidates
+}
+
+#[cfg(windows)]
+fn installed_cli(kind: EditorKind) -> Vec<PathBuf> {
+    let local = dirs::data_local_dir().map(|dir| dir.join("Programs"));
+    let program_files = std::env::var_os("ProgramFiles").map(PathBuf::from);
+    let candidates = match kind {
+        EditorKind::Vscode => vec![
+            local.map(|dir| dir.join(r"Microsoft VS Code\bin\code.cmd")),
+            program_files.map(|dir| dir.join(r"Microsoft VS Code\bin\code.cmd")),
+        ],
+        EditorKind::Cursor => vec![local.map(|dir| dir.join(r"cursor\resources\app\bin\cursor.cmd"))],
+        EditorKind::Zed => vec![local.map(|dir| dir.join(r"Zed\bin\zed.exe"))],
+        EditorKind::Sublime => vec![program_files.map(|dir| dir.join(r"Sublime Text\subl.exe"))],
+        _ => Vec::new(),
+    };
+    candidates.into_iter().flatten().collect()
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn installed_cli(kind: EditorKind) -> Vec<PathBuf> {
+    // Snap installs put their launchers outside the usual PATH
+    let name = match kind {
+        EditorKind::Vscode => "code",
+        EditorKind::Sublime => "subl",
+        _ => return Vec::new(),
+    };
+    vec![Path::new("/snap/bin").join(name)]
+}
+
+fn find_editor(editor: &Editor) -> Option<PathBuf> {
+    find_program(editor.cli).or_else(|| installed_cli(editor.kind).into_iter().find(|path| path.is_file()))
+}
+
+/// `path:line:column`, leaving out what isn't known
+fn location(path: &Path, line: Option<u32>, column: Option<u32>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path.display(), line, column),
+        (Some(line), None) => format!("{}:{}", path.display(), line),
+        _ => path.display().to_string(),
+    }
+}
+
+/// Start `command` without a console window and reap it on a thread: editors'
+/// command line tools hand the file to the editor's window and exit
+fn launch(mut command: Command) -> std::io::Result<()> {
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// `path` if it is absolute and exists
+fn existing(path: &str) -> Result<&Path, ChimeraError> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return Err(ChimeraError::InvalidInput(format!(
+            "Path must be absolute: {}",
+            path.display()
+        )));
+    }
+    if !path.exists() {
+        return Err(ChimeraError::NotFound(format!("File not found: {}", path.display())));
+    }
+    Ok(path)
+}
+
+/// Open `path` at `line` and `column` in the editor `preference` names. With
+/// `auto` that's the first one installed that can jump to a line, else the
+/// file's default app without the position.
+pub fn open(
+    app: &tauri::AppHandle,
+    preference: &EditorPreference,
+    path: &str,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), ChimeraError> {
+    let path = existing(path)?;
+
+    match preference.kind {
+        EditorKind::System => return open_default(app, path),
+        EditorKind::Custom => return open_custom(preference, path, line, column),
+        _ => {}
+    }
+
+    let candidates = EDITORS
+        .iter()
+        .filter(|editor| preference.kind == EditorKind::Auto || editor.kind == preference.kind);
+    for editor in candidates {
+        let Some(program) = find_editor(editor) else { continue };
+        let target = location(path, line, column);
+        let mut command = Command::new(&program);
+        match editor.style {
+            GotoStyle::GotoFlag => command.arg("--goto").arg(target),
+            GotoStyle::Suffix => command.arg(target),
+        };
+        match launch(command) {
+            Ok(()) => {
+                log::info!("Opened {} in {}", path.display(), editor.name);
+                return Ok(());
+            }
+            Err(e) => log::warn!("Failed to start {}: {}", program.display(), e),
+        }
+    }
+
+    if let Some(editor) = EDITORS.iter().find(|editor| editor.kind == preference.kind) {
+        return Err(ChimeraError::NotFound(format!(
+            "{} was not found; install its '{}' command or choose another editor",
+            editor.name, editor.cli
+        )));
+    }
+    open_default(app, path)
+}
+
+fn open_custom(
+    preference: &EditorPreference,
+    path: &Path,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), ChimeraError> {
+    preference.validate().map_err(ChimeraError::InvalidInput)?;
+    let template = preference.command.as_deref().unwrap_or_default();
+    let fill = |arg: &String| {
+        arg.replace("{path}", &path.to_string_lossy())
+            .replace("{line}", &line.unwrap_or(1).to_string())
+            .replace("{column}", &column.unwrap_or(1).to_string())
+    };
+    let mut command = Command::new(fill(&template[0]));
+    command.args(template[1..].iter().map(fill));
+    launch(command).map_err(|e| ChimeraError::Internal(format!("Failed to start {}: {}", template[0], e)))?;
+    log::info!("Opened {} with {}", path.display(), template[0]);
+    Ok(())
+}
+
+fn open_default(app: &tauri::AppHandle, path: &Path) -> Result<(), ChimeraError> {
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| ChimeraError::Internal(format!("Failed to open {}: {}", path.display(), e)))?;
+    log::info!("Opened {} in its default app", path.display());
+    Ok(())
+}
+
+/// Open `path` in the app the system associates with it
+pub fn open_in_default_app(app: &tauri::AppHandle, path: &str) -> Result<(), ChimeraError> {
+    open_default(app, existing(path)?)
+}
+
+/// Show `path` selected in Finder, Explorer or the desktop's file manager
+pub fn reveal_in_file_manager(app: &tauri::AppHandle, path: &str) -> Result<(), ChimeraError> {
+    let path = existing(path)?;
+    app.opener()
+        .reveal_item_in_dir(path)
+        .map_err(|e| ChimeraError::Internal(format!("Failed to reveal {}: {}", path.display(), e)))?;
+    log::info!("Revealed {} in the file manager", path.display());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::filesystem::{FsError, FsErrorKind};
+
+/// The error every command returns, serialized as `{code, message, detail}`.
+/// `message` is for people; the UI matches on `code`, which is stable:
+///
+/// - `not_found`: a thread, blueprint, terminal or other item doesn't exist
+/// - `invalid_input`: bad arguments, or content that doesn't parse
+/// - `permission_denied`: the OS or a folder grant refused access
+/// - `read_only`: viewer mode, or a data directory on read-only media
+/// - `disk_full`: no space left for the data directory
+/// - `unreachable`: the data directory's drive isn't responding
+/// - `io`: any other filesystem failure
+/// - `backend_unavailable`: the Python backend isn't running or didn't answer
+/// - `terminal`: a terminal couldn't be started or written to
+/// - `cancelled`: the operation was cancelled, e.g. with `cancel_task`
+/// - `internal`: anything else
+///
+/// `detail` is an object or null. Filesystem errors carry `{kind, hint}`,
+/// with `hint` saying what the user can do about it.
+#[derive(Debug, Clone)]
+pub enum ChimeraError {
+    NotFound(String),
+    InvalidInput(String),
+    /// Needs the user's approval first, e.g. a folder grant or an allowlisted command
+    PermissionDenied(String),
+    /// Refused because the app is in viewer mode
+    ReadOnly(String),
+    Filesystem(FsError),
+    BackendUnavailable(String),
+    Terminal(String),
+    Cancelled(String),
+    Internal(String),
+}
+
+impl ChimeraError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::ReadOnly(_) => "read_only",
+            Self::Filesystem(e) => match e.kind {
+                FsErrorKind::NotFound => "not_found",
+                FsErrorKind::PermissionDenied => "permission_denied",
+                FsErrorKind::ReadOnly => "read_only",
+                FsErrorKind::DiskFull => "disk_full",
+                FsErrorKind::Unreachable => "unreachable",
+                FsErrorKind::Io => "io",
+                FsErrorKind::Invalid => "invalid_input",
+            },
+            Self::BackendUnavailable(_) => "backend_unavailable",
+            Self::Terminal(_) => "terminal",
+            Self::Cancelled(_) => "cancelled",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    fn detail(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::Filesystem(e) => Some(serde_json::json!({ "kind": e.kind, "hint": e.hint })),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ChimeraError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Filesystem(e) => e.fmt(f),
+            Self::NotFound(message)
+            | Self::InvalidInput(message)
+            | Self::PermissionDenied(message)
+            | Self::ReadOnly(message)
+            | Self::BackendUnavailable(message)
+            | Self::Terminal(message)
+            | Self::Cancelled(message)
+            | Self::Internal(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ChimeraError {}
+
+impl Serialize for ChimeraError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ChimeraError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("detail", &self.detail())?;
+        state.end()
+    }
+}
+
+impl From<FsError> for ChimeraError {
+    fn from(error: FsError) -> Self {
+        Self::Filesystem(error)
+    }
+}
+
+/// Errors from helpers that only have a message
+impl From<String> for ChimeraError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+impl From<&str> for ChimeraError {
+    fn from(message: &str) -> Self {
+        Self::Internal(message.to_string())
+    }
+}
+
+/// For the helpers that still deal in messages, e.g. startup code calling a command
+impl From<ChimeraError> for String {
+    fn from(error: ChimeraError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,453 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager};
+
+use crate::app_windows::WindowContext;
+
+/// Number of events retained per window for replay after a reconnect
+const HISTORY_CAPACITY: usize = 10_000;
+
+/// Events buffered per in-process listener before it starts lagging
+const LISTENER_CAPACITY: usize = 1024;
+
+/// A single event delivered over the bus
+#[derive(Debug, Clone, Serialize)]
+pub struct BusEvent {
+    pub seq: u64,
+    pub topic: String,
+    pub payload: serde_json::Value,
+    pub timestamp: String,
+}
+
+/// Ordered event stream for one window
+struct WindowStream {
+    next_seq: u64,
+    history: VecDeque<BusEvent>,
+    subscriber: Option<Channel<BusEvent>>,
+}
+
+impl WindowStream {
+    fn new() -> Self {
+        Self {
+            next_seq: 1,
+            history: VecDeque::new(),
+            subscriber: None,
+        }
+    }
+
+    /// Assign the next sequence number, record the event and deliver it live
+    fn push(&mut self, topic: &str, payload: &serde_json::Value) {
+        let event = BusEvent {
+            seq: self.next_seq,
+            topic: topic.to_string(),
+            payload: payload.clone(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        self.next_seq += 1;
+
+        if let Some(channel) = &self.subscriber {
+            if let Err(e) = channel.send(event.clone()) {
+                log::warn!("Event bus subscriber dropped: {}", e);
+                self.subscriber = None;
+            }
+        }
+
+        self.history.push_back(event);
+        while self.history.len() > HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+    }
+}
+
+/// Which windows get which events
+#[derive(Default)]
+struct Routes {
+    /// Secondary windows and what they show; the main window has none and gets everything
+    contexts: HashMap<String, WindowContext>,
+    /// Window each terminal was opened from, by terminal id
+    terminal_owners: HashMap<String, String>,
+}
+
+impl Routes {
+    /// Terminal events go only to the window that opened the terminal; other events
+    /// go by the window's context
+    fn wants(&self, label: &str, payload: &serde_json::Value) -> bool {
+        if let Some(owner) = payload
+            .get("terminal_id")
+            .and_then(|t| t.as_str())
+            .and_then(|terminal_id| self.terminal_owners.get(terminal_id))
+        {
+            return owner == label;
+        }
+        self.contexts.get(label).is_none_or(|context| context.wants(payload))
+    }
+}
+
+/// Multiplexes terminal output, filesystem changes, backend streams and task
+/// progress into a single ordered channel per window
+pub struct EventBus {
+    streams: Mutex<HashMap<String, WindowStream>>,
+    /// Fan-out for in-process listeners (e.g. companion devices), with their own sequence
+    listeners: tokio::sync::broadcast::Sender<BusEvent>,
+    listener_seq: AtomicU64,
+    /// Backend streams currently being forwarded
+    active_streams: Mutex<HashSet<String>>,
+    routes: Mutex<Routes>,
+    /// None when running without windows (test harness)
+    app_handle: Option<AppHandle>,
+}
+
+impl EventBus {
+    /// Create a new event bus
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self::with_app_handle(Some(app_handle))
+    }
+
+    /// Create a bus with no windows; events only reach `listen()` receivers
+    pub fn detached() -> Self {
+        Self::with_app_handle(None)
+    }
+
+    fn with_app_handle(app_handle: Option<AppHandle>) -> Self {
+        Self {
+            streams: Mutex::new(HashMap::new()),
+            listeners: tokio::sync::broadcast::channel(LISTENER_CAPACITY).0,
+            listener_seq: AtomicU64::new(1),
+            active_streams: Mutex::new(HashSet::new()),
+            routes: Mutex::new(Routes::default()),
+            app_handle,
+        }
+    }
+
+    /// Publish an event to every open window it's routed to
+    pub fn publish<T: Serialize>(&self, topic: &str, payload: T) {
+        let payload = match serde_json::to_value(payload) {
+            Ok(value) => value,
+            Err(e) => {
+                log::error!("Failed to serialize {} event: {}", topic, e);
+                return;
+            }
+        };
+
+        let labels: Vec<String> = self
+            .app_handle
+            .as_ref()
+            .map(|app| app.webview_windows().keys().cloned().collect())
+            .unwrap_or_default();
+
+        let routes = self.routes.lock().unwrap();
+        let mut streams = self.streams.lock().unwrap();
+        for label in labels.into_iter().filter(|label| routes.wants(label, &payload)) {
+            streams
+                .entry(label)
+                .or_insert_with(WindowStream::new)
+                .push(topic, &payload);
+        }
+        drop(streams);
+        drop(routes);
+
+        self.send_to_listeners(topic, payload);
+    }
+
+    /// Publish only to in-process listeners, for events whose window delivery goes
+    /// over a dedicated channel. Serializes nothing when no one is listening.
+    pub fn publish_local<T: Serialize>(&self, topic: &str, payload: T) {
+        if self.listeners.receiver_count() == 0 {
+            return;
+        }
+        match serde_json::to_value(payload) {
+            Ok(payload) => self.send_to_listeners(topic, payload),
+            Err(e) => log::error!("Failed to serialize {} event: {}", topic, e),
+        }
+    }
+
+    fn send_to_listeners(&self, topic: &str, payload: serde_json::Value) {
+        if self.listeners.receiver_count() > 0 {
+            let _ = self.listeners.send(BusEvent {
+                seq: self.listener_seq.fetch_add(1, Ordering::Relaxed),
+                topic: topic.to_string(),
+                payload,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+    }
+
+    /// Receive every event published from now on, independent of any window
+    pub fn listen(&self) -> tokio::sync::broadcast::Receiver<BusEvent> {
+        self.listeners.subscribe()
+    }
+
+    /// Attach a channel to a window's stream, replaying everything after `since_seq`.
+    /// Returns the sequence number of the last event replayed (0 if none).
+    pub fn subscribe(&self, window_label: &str, since_seq: Option<u64>, channel: Channel<BusEvent>) -> u64 {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams
+            .entry(window_label.to_string())
+            .or_insert_with(WindowStream::new);
+
+        let since = since_seq.unwrap_or(0);
+        let mut last_seq = since;
+
+        // Replay while holding the lock so no live event can slip in between
+        for event in stream.history.iter().filter(|e| e.seq > since) {
+            if let Err(e) = channel.send(event.clone()) {
+                log::warn!("Failed to replay event {} to {}: {}", event.seq, window_label, e);
+                break;
+            }
+            last_seq = event.seq;
+        }
+
+        if let Some(oldest) = stream.history.front() {
+            if since > 0 && oldest.seq > since + 1 {
+                log::warn!(
+                    "Window {} requested replay from {} but history starts at {}",
+                    window_label, since, oldest.seq
+                );
+            }
+        }
+
+        stream.subscriber = Some(channel);
+        log::info!("Window {} subscribed to event bus (replayed through seq {})", window_label, last_seq);
+
+        last_seq
+    }
+
+    /// Detach the live channel for a window (history is kept for replay)
+    pub fn unsubscribe(&self, window_label: &str) {
+        let mut streams = self.streams.lock().unwrap();
+        if let Some(stream) = streams.get_mut(window_label) {
+            stream.subscriber = None;
+        }
+    }
+
+    /// Sequence number of the last event in a window's stream (0 if none)
+    pub fn last_seq(&self, window_label: &str) -> u64 {
+        let streams = self.streams.lock().unwrap();
+        streams
+            .get(window_label)
+            .map(|stream| stream.next_seq - 1)
+            .unwrap_or(0)
+    }
+
+    /// Ids of backend streams still being forwarded
+    pub fn active_streams(&self) -> Vec<String> {
+        self.active_streams.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Route events for a secondary window by what it shows
+    pub fn set_window_context(&self, window_label: &str, context: WindowContext) {
+        self.routes.lock().unwrap().contexts.insert(window_label.to_string(), context);
+    }
+
+    /// Send a terminal's events only to the window that opened it
+    pub fn assign_terminal(&self, terminal_id: &str, window_label: &str) {
+        self.routes
+            .lock()
+            .unwrap()
+            .terminal_owners
+            .insert(terminal_id.to_string(), window_label.to_string());
+    }
+
+    /// Forget a terminal's window once the terminal is gone
+    pub fn release_terminal(&self, terminal_id: &str) {
+        self.routes.lock().unwrap().terminal_owners.remove(terminal_id);
+    }
+
+    /// Drop all state for a window that has been destroyed. Returns the terminals
+    /// it opened, which the caller should close.
+    pub fn remove_window(&self, window_label: &str) -> Vec<String> {
+        let mut streams = self.streams.lock().unwrap();
+        streams.remove(window_label);
+        drop(streams);
+
+        let mut routes = self.routes.lock().unwrap();
+        routes.contexts.remove(window_label);
+        let terminals: Vec<String> = routes
+            .terminal_owners
+            .iter()
+            .filter(|(_, owner)| owner.as_str() == window_label)
+            .map(|(terminal_id, _)| terminal_id.clone())
+            .collect();
+        for terminal_id in &terminals {
+            routes.terminal_owners.remove(terminal_id);
+        }
+        terminals
+    }
+}
+
+/// Backend stream chunk payload
+#[derive(Clone, Serialize)]
+pub struct BackendStreamEvent {
+    stream_id: String,
+    data: Option<String>,
+    done: bool,
+    error: Option<String>,
+}
+
+/// Delivers one backend stream's chunks to its requester's channel when it has
+/// one, falling back to `backend-stream` events on the bus
+struct StreamDelivery {
+    bus: Arc<EventBus>,
+    channel: Option<Channel<BackendStreamEvent>>,
+}
+
+impl StreamDelivery {
+    fn send(&mut self, event: BackendStreamEvent) {
+        if let Some(channel) = &self.channel {
+            match channel.send(event.clone()) {
+                Ok(()) => {
+                    // In-process listeners (companion devices) still see every stream
+                    self.bus.publish_local("backend-stream", event);
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Backend stream {} channel dropped, falling back to events: {}", event.stream_id, e);
+                    self.channel = None;
+                }
+            }
+        }
+        self.bus.publish("backend-stream", event);
+    }
+
+    fn end(&mut self, stream_id: &str, error: Option<String>) {
+        self.bus.active_streams.lock().unwrap().remove(stream_id);
+        self.send(BackendStreamEvent {
+            stream_id: stream_id.to_string(),
+            data: None,
+            done: true,
+            error,
+        });
+    }
+}
+
+/// POST a request to the backend and forward its SSE stream to `on_chunk`, or onto
+/// the bus when no channel is given. Goes over gRPC instead when that transport is
+/// selected (see `backend_grpc::endpoint`).
+#[tracing::instrument(skip(bus, body, on_chunk), fields(status, chunks))]
+pub async fn forward_backend_stream(
+    bus: Arc<EventBus>,
+    stream_id: String,
+    url: String,
+    body: serde_json::Value,
+    on_chunk: Option<Channel<BackendStreamEvent>>,
+) {
+    bus.active_streams.lock().unwrap().insert(stream_id.clone());
+    let mut delivery = StreamDelivery { bus, channel: on_chunk };
+
+    #[cfg(feature = "grpc")]
+    if let Some(endpoint) = crate::backend_grpc::endpoint() {
+        let path = reqwest::Url::parse(&url)
+            .map(|url| url.path().to_string())
+            .unwrap_or_else(|_| url.clone());
+        forward_grpc_stream(&mut delivery, &stream_id, &endpoint, &path, &body).await;
+        return;
+    }
+
+    let mut request = reqwest::Client::new().post(&url).json(&body);
+    for (name, value) in crate::telemetry::trace_headers() {
+        request = request.header(name, value);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            log::error!("Backend stream {} failed to connect: {}", stream_id, e);
+            delivery.end(&stream_id, Some(format!("Failed to connect to backend: {}", e)));
+            return;
+        }
+    };
+
+    tracing::Span::current().record("status", response.status().as_u16());
+
+    if !response.status().is_success() {
+        let status = response.status();
+        log::error!("Backend stream {} returned {}", stream_id, status);
+        delivery.end(&stream_id, Some(format!("Backend returned {}", status)));
+        return;
+    }
+
+    let mut bytes = response.bytes_stream();
+    let mut pending = String::new();
+    let mut chunks: u64 = 0;
+
+    while let Some(chunk) = bytes.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                log::error!("Backend stream {} interrupted: {}", stream_id, e);
+                delivery.end(&stream_id, Some(format!("Backend stream interrupted: {}", e)));
+                return;
+            }
+        };
+
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+
+        // SSE lines are newline-delimited; keep any partial line for the next chunk
+        while let Some(newline) = pending.find('\n') {
+            let line: String = pending.drain(..=newline).collect();
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            if let Some(data) = line.strip_prefix("data:") {
+                chunks += 1;
+                delivery.send(BackendStreamEvent {
+                    stream_id: stream_id.clone(),
+                    data: Some(data.trim_start().to_string()),
+                    done: false,
+                    error: None,
+                });
+            }
+        }
+    }
+
+    tracing::Span::current().record("chunks", chunks);
+    log::info!("Backend stream {} finished", stream_id);
+    delivery.end(&stream_id, None);
+}
+
+/// Forward the chunks of a gRPC `Backend/Stream` call
+#[cfg(feature = "grpc")]
+async fn forward_grpc_stream(
+    delivery: &mut StreamDelivery,
+    stream_id: &str,
+    endpoint: &str,
+    path: &str,
+    body: &serde_json::Value,
+) {
+    let mut stream = match crate::backend_grpc::open_stream(endpoint, path, body).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            log::error!("Backend stream {} failed over gRPC: {}", stream_id, e);
+            delivery.end(stream_id, Some(e));
+            return;
+        }
+    };
+
+    let mut chunks: u64 = 0;
+    loop {
+        match stream.message().await {
+            Ok(Some(chunk)) => {
+                chunks += 1;
+                delivery.send(BackendStreamEvent {
+                    stream_id: stream_id.to_string(),
+                    data: Some(chunk.data),
+                    done: false,
+                    error: None,
+                });
+            }
+            Ok(None) => break,
+            Err(status) => {
+                log::error!("Backend stream {} interrupted: {}", stream_id, status);
+                delivery.end(stream_id, Some(format!("Backend stream interrupted: {}", status.message())));
+                return;
+            }
+        }
+    }
+
+    tracing::Span::current().record("chunks", chunks);
+    log::info!("Backend stream {} finished over gRPC", stream_id);
+    delivery.end(stream_id, None);
+}
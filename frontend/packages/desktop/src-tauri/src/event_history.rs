@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::filesystem;
+
+/// One amendment, as recorded in a thread's history sidecar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Amendment {
+    pub timestamp: String,
+    /// Position of the event in `load_thread`'s result (0 is the blueprint line)
+    pub event_index: usize,
+    pub original: serde_json::Value,
+    pub replacement: serde_json::Value,
+}
+
+/// Get the history sidecar for a thread (`<data>/history/<thread_id>.jsonl`), kept
+/// outside the threads directory so it is never listed as a thread
+fn get_history_path(thread_id: &str) -> Result<PathBuf, String> {
+    filesystem::get_thread_path(thread_id)?;
+    Ok(filesystem::get_data_dir()?
+        .join("history")
+        .join(format!("{}.jsonl", thread_id)))
+}
+
+/// Replace one event in a thread, recording the original in the history sidecar
+/// first so the edit can be undone. Callers must keep appends to the thread out
+/// while this runs (blocking).
+pub fn amend_event(thread_id: &str, event_index: usize, new_event: serde_json::Value) -> Result<Amendment, String> {
+    if event_index == 0 {
+        return Err("The blueprint line can't be amended".to_string());
+    }
+    if !new_event.is_object() {
+        return Err("Replacement event must be a JSON object".to_string());
+    }
+
+    let path = filesystem::get_thread_path(thread_id)?;
+    let content = std::fs::read(&path).map_err(|_| format!("Thread {} not found", thread_id))?;
+
+    let range = filesystem::event_line_ranges(&content)
+        .get(event_index)
+        .cloned()
+        .ok_or_else(|| format!("Thread {} has no event {}", thread_id, event_index))?;
+    let original: serde_json::Value = serde_json::from_slice(&content[range.clone()])
+        .map_err(|e| format!("Failed to parse event {}: {}", event_index, e))?;
+
+    let amendment = Amendment {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        event_index,
+        original,
+        replacement: new_event,
+    };
+
+    // History first: if the rewrite fails the sidecar has an extra entry, never a
+    // missing one
+    let history_path = get_history_path(thread_id)?;
+    if let Some(dir) = history_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+    let mut entry = Vec::new();
+    filesystem::serialize_event_line(
+        &serde_json::to_value(&amendment).map_err(|e| format!("Failed to serialize amendment: {}", e))?,
+        &mut entry,
+    )?;
+    let mut history = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_path)
+        .map_err(|e| format!("Failed to open history sidecar: {}", e))?;
+    history
+        .write_all(&entry)
+        .and_then(|_| history.sync_all())
+        .map_err(|e| format!("Failed to write history sidecar: {}", e))?;
+
+    // Only the event's line changes; every other line is kept byte for byte
+    let mut output = Vec::with_capacity(content.len());
+    output.extend_from_slice(&content[..range.start]);
+    filesystem::serialize_event_line(&amendment.replacement, &mut output)?;
+    output.extend_from_slice(content.get(range.end + 1..).unwrap_or_default());
+    filesystem::replace_thread_file(&path, &output)?;
+
+    log::info!("Amended event {} of thread {}", event_index, thread_id);
+    Ok(amendment)
+}
+
+/// Amendments made to a thread, oldest first (blocking)
+pub fn get_history(thread_id: &str) -> Result<Vec<Amendment>, String> {
+    let path = get_history_path(thread_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read history sidecar: {}", e))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
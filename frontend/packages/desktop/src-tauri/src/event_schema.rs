@@ -0,0 +1,425 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Event type an invalid event is wrapped in under `wrap` validation
+pub const UNKNOWN_EVENT: &str = "data-unknown-event";
+
+/// What to do with events that fail validation (`CHIMERA_EVENT_VALIDATION`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Fail the whole append
+    Reject,
+    /// Log and write the event as is
+    Warn,
+    /// Write the event inside a `data-unknown-event` wrapper
+    Wrap,
+}
+
+impl ValidationMode {
+    /// Read from `CHIMERA_EVENT_VALIDATION` (`reject`, `warn` or `wrap`; default `warn`)
+    pub fn from_env() -> Self {
+        match std::env::var("CHIMERA_EVENT_VALIDATION").as_deref() {
+            Ok("reject") => Self::Reject,
+            Ok("wrap") => Self::Wrap,
+            Ok("warn") | Err(_) => Self::Warn,
+            Ok(other) => {
+                log::warn!("Unknown CHIMERA_EVENT_VALIDATION {:?}; using warn", other);
+                Self::Warn
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AgentData {
+    #[serde(rename = "agentId")]
+    _agent_id: String,
+}
+
+#[derive(Deserialize)]
+struct TitleData {
+    #[serde(rename = "title")]
+    _title: String,
+}
+
+#[derive(Deserialize)]
+struct BlueprintData {
+    #[serde(rename = "blueprint")]
+    _blueprint: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ArchivedData {
+    #[serde(rename = "archived")]
+    _archived: bool,
+}
+
+#[derive(Deserialize)]
+struct TagsData {
+    #[serde(rename = "tags")]
+    _tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct AttachmentData {
+    #[serde(rename = "hash")]
+    _hash: String,
+    #[serde(rename = "name")]
+    _name: String,
+}
+
+#[derive(Deserialize)]
+struct UsageData {
+    #[serde(rename = "inputTokens")]
+    _input_tokens: u64,
+    #[serde(rename = "outputTokens")]
+    _output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct MutationData {
+    #[serde(rename = "source")]
+    _source: String,
+    #[serde(rename = "payload")]
+    _payload: serde_json::Value,
+}
+
+/// ThreadProtocol v0.0.7 events as persisted, mirroring `thread-protocol.ts`, plus
+/// the desktop's own `data-*` events. Extra fields are allowed; the listed ones
+/// must be present with the right type. Deserializing is the whole check, so
+/// nothing reads the fields.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum KnownEvent {
+    #[serde(rename = "thread-start")]
+    ThreadStart {
+        #[serde(rename = "threadId")]
+        _thread_id: String,
+    },
+    #[serde(rename = "thread-end")]
+    ThreadEnd {
+        #[serde(rename = "threadId")]
+        _thread_id: String,
+    },
+    #[serde(rename = "thread-blueprint")]
+    ThreadBlueprint {
+        #[serde(rename = "blueprint")]
+        _blueprint: serde_json::Map<String, serde_json::Value>,
+    },
+    #[serde(rename = "data-agent-start")]
+    AgentStart {
+        #[serde(rename = "data")]
+        _data: AgentData,
+    },
+    #[serde(rename = "data-agent-finish")]
+    AgentFinish {
+        #[serde(rename = "data")]
+        _data: AgentData,
+    },
+    #[serde(rename = "start")]
+    MessageStart {},
+    #[serde(rename = "finish")]
+    MessageFinish {},
+    #[serde(rename = "user-message")]
+    UserMessage {
+        #[serde(rename = "content")]
+        _content: String,
+    },
+    #[serde(rename = "data-user-message")]
+    DataUserMessage {},
+    #[serde(rename = "data-user-turn-start")]
+    UserTurnStart {},
+    #[serde(rename = "data-user-turn-end")]
+    UserTurnEnd {},
+    #[serde(rename = "text-complete")]
+    TextComplete {
+        // Early v0.0.7 threads used "text"
+        #[serde(rename = "content", alias = "text")]
+        _content: String,
+    },
+    #[serde(rename = "reasoning-complete")]
+    ReasoningComplete {
+        #[serde(rename = "content", alias = "text")]
+        _content: String,
+    },
+    #[serde(rename = "tool-input-available")]
+    ToolInputAvailable {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+        #[serde(rename = "toolName")]
+        _tool_name: String,
+    },
+    #[serde(rename = "tool-output-available")]
+    ToolOutputAvailable {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+    },
+    #[serde(rename = "tool-approval-request")]
+    ToolApprovalRequest {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+    },
+    #[serde(rename = "data-tool-approval-response")]
+    ToolApprovalResponse {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+        #[serde(rename = "approved")]
+        _approved: bool,
+    },
+    #[serde(rename = "tool-output-denied")]
+    ToolOutputDenied {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+    },
+    #[serde(rename = "tool-input-error")]
+    ToolInputError {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+        #[serde(rename = "errorText")]
+        _error_text: String,
+    },
+    #[serde(rename = "tool-output-error")]
+    ToolOutputError {
+        #[serde(rename = "toolCallId")]
+        _tool_call_id: String,
+        #[serde(rename = "errorText")]
+        _error_text: String,
+    },
+    #[serde(rename = "start-step")]
+    StepStart {},
+    #[serde(rename = "finish-step")]
+    StepEnd {},
+    #[serde(rename = "error")]
+    Error {
+        #[serde(rename = "errorText")]
+        _error_text: String,
+    },
+    #[serde(rename = "data-sys-usage")]
+    Usage {
+        #[serde(rename = "data")]
+        _data: UsageData,
+    },
+    #[serde(rename = "data-app-chimera")]
+    Mutation {
+        #[serde(rename = "data")]
+        _data: MutationData,
+    },
+    #[serde(rename = "data-thread-title")]
+    ThreadTitle {
+        #[serde(rename = "data")]
+        _data: TitleData,
+    },
+    #[serde(rename = "data-blueprint-update")]
+    BlueprintUpdate {
+        #[serde(rename = "data")]
+        _data: BlueprintData,
+    },
+    #[serde(rename = "data-thread-provenance")]
+    Provenance {
+        #[serde(rename = "data")]
+        _data: crate::provenance::Provenance,
+    },
+    #[serde(rename = "data-thread-archived")]
+    Archived {
+        #[serde(rename = "data")]
+        _data: ArchivedData,
+    },
+    #[serde(rename = "data-thread-tags")]
+    Tags {
+        #[serde(rename = "data")]
+        _data: TagsData,
+    },
+    #[serde(rename = "data-attachment")]
+    Attachment {
+        #[serde(rename = "data")]
+        _data: AttachmentData,
+    },
+    #[serde(rename = "data-unknown-event")]
+    Unknown {},
+}
+
+/// Why an event doesn't match the schema, or None if it does
+pub fn check(event: &serde_json::Value) -> Option<String> {
+    let event_type = match event.get("type") {
+        Some(serde_json::Value::String(t)) => t,
+        Some(_) => return Some("\"type\" is not a string".to_string()),
+        None => return Some("missing \"type\"".to_string()),
+    };
+    match KnownEvent::deserialize(event) {
+        Ok(_) => None,
+        Err(e) if e.to_string().starts_with("unknown variant") => Some(format!("unknown event type {}", event_type)),
+        Err(e) => Some(format!("invalid {} event: {}", event_type, e)),
+    }
+}
+
+/// Validate events about to be appended to a thread, applying the configured mode.
+/// Returns the events to write.
+pub fn validate(thread_id: &str, events: Vec<serde_json::Value>) -> Result<Vec<serde_json::Value>, String> {
+    let mode = ValidationMode::from_env();
+    let mut problems: HashMap<usize, String> = HashMap::new();
+    for (index, event) in events.iter().enumerate() {
+        if let Some(problem) = check(event) {
+            problems.insert(index, problem);
+        }
+    }
+    if problems.is_empty() {
+        return Ok(events);
+    }
+
+    match mode {
+        ValidationMode::Reject => {
+            let mut problems: Vec<_> = problems.into_iter().collect();
+            problems.sort();
+            let listed: Vec<String> = problems
+                .into_iter()
+                .map(|(index, problem)| format!("event {}: {}", index, problem))
+                .collect();
+            Err(format!("Rejected events for thread {}: {}", thread_id, listed.join("; ")))
+        }
+        ValidationMode::Warn => {
+            for (index, problem) in &problems {
+                log::warn!("Appending malformed event {} to thread {}: {}", index, thread_id, problem);
+            }
+            Ok(events)
+        }
+        ValidationMode::Wrap => Ok(events
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| match problems.remove(&index) {
+                Some(problem) => {
+                    log::warn!("Wrapping malformed event {} for thread {}: {}", index, thread_id, problem);
+                    quarantine(event, problem)
+                }
+                None => event,
+            })
+            .collect()),
+    }
+}
+
+/// Schema version of threads created by this build, recorded as `schema_version`
+/// on the blueprint line. Threads without one are version 0.
+pub const CURRENT_THREAD_VERSION: u32 = 1;
+
+/// Header field holding a thread's schema version
+pub const VERSION_FIELD: &str = "schema_version";
+
+/// A thread's schema version, from its blueprint line
+pub fn thread_version(header: &serde_json::Value) -> u32 {
+    header.get(VERSION_FIELD).and_then(|v| v.as_u64()).unwrap_or(0) as u32
+}
+
+/// Result of `migrate_thread`
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Events moved into `data-unknown-event` wrappers
+    pub quarantined: usize,
+}
+
+/// Wrap an event, or a line that isn't JSON, so loads skip it but nothing is lost
+fn quarantine(event: serde_json::Value, reason: String) -> serde_json::Value {
+    let timestamp = event.get("timestamp").cloned();
+    let mut wrapped = serde_json::json!({
+        "type": UNKNOWN_EVENT,
+        "data": { "reason": reason, "event": event },
+    });
+    if let Some(timestamp) = timestamp {
+        wrapped["timestamp"] = timestamp;
+    }
+    wrapped
+}
+
+/// Version 0 to 1: quarantine lines that aren't valid events, which older builds
+/// appended unchecked
+fn migrate_v1(events: Vec<serde_json::Value>, report: &mut MigrationReport) -> Vec<serde_json::Value> {
+    events
+        .into_iter()
+        .map(|event| match check(&event) {
+            Some(problem) => {
+                report.quarantined += 1;
+                quarantine(event, problem)
+            }
+            None => event,
+        })
+        .collect()
+}
+
+/// Upgrade a thread's events to `to_version` (default `CURRENT_THREAD_VERSION`), one
+/// version at a time, and record the version on the blueprint line. Threads
+/// already there are left untouched. Callers must keep appends to the thread out
+/// while this runs (blocking).
+pub fn migrate_thread(thread_id: &str, to_version: Option<u32>) -> Result<MigrationReport, String> {
+    let to_version = to_version.unwrap_or(CURRENT_THREAD_VERSION);
+    if to_version > CURRENT_THREAD_VERSION {
+        return Err(format!(
+            "Thread schema version {} is newer than this build supports ({})",
+            to_version, CURRENT_THREAD_VERSION
+        ));
+    }
+
+    let _lock = crate::filesystem::lock_thread_blocking(thread_id)?;
+    let path = crate::filesystem::get_thread_path(thread_id)?;
+    let content = std::fs::read(&path).map_err(|_| format!("Thread {} not found", thread_id))?;
+    let mut lines = content
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace));
+
+    let mut header: serde_json::Value = lines
+        .next()
+        .and_then(|line| serde_json::from_slice(line).ok())
+        .filter(serde_json::Value::is_object)
+        .ok_or_else(|| format!("Thread {} has no readable blueprint line", thread_id))?;
+    let from_version = thread_version(&header);
+    let mut report = MigrationReport {
+        from_version,
+        to_version: from_version,
+        quarantined: 0,
+    };
+    if from_version > to_version {
+        return Err(format!(
+            "Thread {} is at schema version {}; downgrading to {} isn't supported",
+            thread_id, from_version, to_version
+        ));
+    }
+    if from_version == to_version {
+        return Ok(report);
+    }
+
+    let mut events: Vec<serde_json::Value> = lines
+        .map(|line| {
+            serde_json::from_slice(line).unwrap_or_else(|e| {
+                report.quarantined += 1;
+                quarantine(
+                    serde_json::Value::String(String::from_utf8_lossy(line).into_owned()),
+                    format!("not JSON: {}", e),
+                )
+            })
+        })
+        .collect();
+
+    while report.to_version < to_version {
+        report.to_version += 1;
+        events = match report.to_version {
+            1 => migrate_v1(events, &mut report),
+            _ => events,
+        };
+    }
+    header[VERSION_FIELD] = serde_json::json!(report.to_version);
+
+    let mut output = Vec::with_capacity(content.len());
+    crate::filesystem::serialize_event_line(&header, &mut output)?;
+    for event in &events {
+        crate::filesystem::serialize_event_line(event, &mut output)?;
+    }
+    crate::filesystem::replace_thread_file(&path, &output)?;
+
+    log::info!(
+        "Migrated thread {} from schema version {} to {} ({} events quarantined)",
+        thread_id,
+        report.from_version,
+        report.to_version,
+        report.quarantined
+    );
+    Ok(report)
+}
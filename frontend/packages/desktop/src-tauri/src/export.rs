@@ -0,0 +1,708 @@
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::blob_store;
+use crate::filesystem;
+use crate::plugins::{PluginExporterSpec, PluginHost};
+
+/// What an export wrote
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    pub dest_path: String,
+    pub format: String,
+    pub bytes: u64,
+    pub events: usize,
+}
+
+/// A format offered by `list_export_formats`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportFormatInfo {
+    /// Passed back as `format` to `export_thread`; plugin formats are `plugin.id`
+    pub id: String,
+    pub name: String,
+    /// Suggested file extension, without the dot
+    pub extension: String,
+    pub description: String,
+    /// `builtin`, or the name of the plugin providing it
+    pub source: String,
+}
+
+/// The thread being exported
+pub struct ExportSource<'a> {
+    pub thread_id: &'a str,
+    /// Every event, the blueprint first
+    pub events: &'a [serde_json::Value],
+}
+
+/// Renders a thread into one file format
+pub trait Exporter: Send + Sync {
+    fn info(&self) -> ExportFormatInfo;
+
+    /// Write the thread to `dest` (blocking)
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String>;
+}
+
+fn builtin(id: &str, name: &str, extension: &str, description: &str) -> ExportFormatInfo {
+    ExportFormatInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        extension: extension.to_string(),
+        description: description.to_string(),
+        source: "builtin".to_string(),
+    }
+}
+
+fn write_file(dest: &Path, content: impl AsRef<[u8]>) -> Result<(), String> {
+    std::fs::write(dest, content).map_err(|e| format!("Failed to write export file: {}", e))
+}
+
+/// One readable section of a thread
+enum Section<'a> {
+    Message { role: &'static str, text: &'a str },
+    Reasoning(&'a str),
+    ToolCall { name: &'a str, input: String },
+    ToolResult { name: &'a str, output: String },
+    Error(&'a str),
+}
+
+fn content(event: &serde_json::Value) -> Option<&str> {
+    event
+        .get("content")
+        .and_then(|c| c.as_str())
+        .or_else(|| event.get("data").and_then(|d| d.get("content")).and_then(|c| c.as_str()))
+}
+
+fn json_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => serde_json::to_string_pretty(other).unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+/// The sections worth reading, in order. The first event is the blueprint.
+fn sections(events: &[serde_json::Value]) -> Vec<Section<'_>> {
+    event_sections(events).into_iter().map(|(_, section)| section).collect()
+}
+
+/// Sections alongside the event each came from
+fn event_sections(events: &[serde_json::Value]) -> Vec<(&serde_json::Value, Section<'_>)> {
+    let mut sections = Vec::new();
+
+    for event in events.iter().skip(1) {
+        let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let tool = || event.get("toolName").and_then(|n| n.as_str()).unwrap_or("tool");
+
+        let section = match event_type {
+            "user-message" | "data-user-message" => content(event).map(|text| Section::Message { role: "User", text }),
+            "text-complete" => content(event).map(|text| Section::Message { role: "Assistant", text }),
+            "reasoning-complete" => content(event).map(Section::Reasoning),
+            "tool-input-available" => Some(Section::ToolCall {
+                name: tool(),
+                input: json_text(event.get("input")),
+            }),
+            "tool-output-available" => Some(Section::ToolResult {
+                name: tool(),
+                output: json_text(event.get("output")),
+            }),
+            "error" => Some(Section::Error(
+                event
+                    .get("errorText")
+                    .and_then(|e| e.as_str())
+                    .or(content(event))
+                    .unwrap_or("Unknown error"),
+            )),
+            _ => None,
+        };
+        sections.extend(section.map(|section| (event, section)));
+    }
+
+    sections
+}
+
+/// Latest explicit title, else the start of the first user message
+pub(crate) fn thread_title(events: &[serde_json::Value]) -> String {
+    let explicit = events.iter().rev().find_map(|event| {
+        (event.get("type").and_then(|t| t.as_str()) == Some("data-thread-title"))
+            .then(|| event.get("data")?.get("title")?.as_str())
+            .flatten()
+    });
+    let first_message = || {
+        events
+            .iter()
+            .find(|event| event.get("type").and_then(|t| t.as_str()) == Some("user-message"))
+            .and_then(content)
+            .map(|text| text.chars().take(50).collect::<String>())
+    };
+    explicit
+        .map(str::to_string)
+        .or_else(first_message)
+        .unwrap_or_else(|| "Untitled thread".to_string())
+}
+
+/// A fence longer than any backtick run in `text`
+fn fence(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn render_markdown(thread_id: &str, events: &[serde_json::Value]) -> String {
+    let mut doc = format!("# {}\n\n", thread_title(events));
+    doc.push_str(&format!("Thread `{}`, exported {}\n\n", thread_id, chrono::Utc::now().to_rfc3339()));
+
+    for section in sections(events) {
+        match section {
+            Section::Message { role, text } => doc.push_str(&format!("## {}\n\n{}\n\n", role, text.trim())),
+            Section::Reasoning(text) => {
+                let quoted: Vec<String> = text.trim().lines().map(|line| format!("> {}", line)).collect();
+                doc.push_str(&format!("> **Reasoning**\n>\n{}\n\n", quoted.join("\n")));
+            }
+            Section::ToolCall { name, input } => {
+                let fence = fence(&input);
+                doc.push_str(&format!("### Tool call: {}\n\n{}json\n{}\n{}\n\n", name, fence, input, fence));
+            }
+            Section::ToolResult { name, output } => {
+                let fence = fence(&output);
+                doc.push_str(&format!("### Tool result: {}\n\n{}\n{}\n{}\n\n", name, fence, output, fence));
+            }
+            Section::Error(text) => doc.push_str(&format!("> **Error:** {}\n\n", text.trim())),
+        }
+    }
+
+    doc
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Message text as HTML: fenced code blocks become `<pre>`, everything else
+/// paragraphs with line breaks kept
+fn render_text_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, html: &mut String| {
+        if !paragraph.is_empty() {
+            let lines: Vec<String> = paragraph.iter().map(|line| escape_html(line)).collect();
+            html.push_str(&format!("<p>{}</p>\n", lines.join("<br>\n")));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        match (&mut code, is_fence) {
+            (Some(block), false) => block.push(line),
+            (Some(block), true) => {
+                html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&block.join("\n"))));
+                code = None;
+            }
+            (None, true) => {
+                flush(&mut paragraph, &mut html);
+                code = Some(Vec::new());
+            }
+            (None, false) if line.trim().is_empty() => flush(&mut paragraph, &mut html),
+            (None, false) => paragraph.push(line),
+        }
+    }
+    if let Some(block) = code {
+        html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&block.join("\n"))));
+    }
+    flush(&mut paragraph, &mut html);
+
+    html
+}
+
+pub(crate) const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:48rem;margin:2rem auto;padding:0 1rem;line-height:1.5;color:#1f2328}\
+section{margin:1.5rem 0}h2{font-size:1rem;text-transform:uppercase;letter-spacing:.05em;color:#59636e}\
+pre{background:#f6f8fa;padding:.75rem;overflow-x:auto;border-radius:6px}details{border-left:3px solid #d1d9e0;padding-left:.75rem;margin:1rem 0}\
+.error{border-left:3px solid #d1242f;padding-left:.75rem;color:#d1242f}.meta{color:#59636e;font-size:.875rem}";
+
+pub(crate) fn render_html(thread_id: &str, events: &[serde_json::Value]) -> String {
+    let title = escape_html(&thread_title(events));
+    let mut body = String::new();
+
+    for section in sections(events) {
+        match section {
+            Section::Message { role, text } => body.push_str(&format!(
+                "<section class=\"{}\"><h2>{}</h2>\n{}</section>\n",
+                role.to_lowercase(),
+                role,
+                render_text_html(text.trim())
+            )),
+            Section::Reasoning(text) => body.push_str(&format!(
+                "<details><summary>Reasoning</summary>\n{}</details>\n",
+                render_text_html(text.trim())
+            )),
+            Section::ToolCall { name, input } => body.push_str(&format!(
+                "<details><summary>Tool call: {}</summary>\n<pre><code>{}</code></pre></details>\n",
+                escape_html(name),
+                escape_html(&input)
+            )),
+            Section::ToolResult { name, output } => body.push_str(&format!(
+                "<details><summary>Tool result: {}</summary>\n<pre><code>{}</code></pre></details>\n",
+                escape_html(name),
+                escape_html(&output)
+            )),
+            Section::Error(text) => body.push_str(&format!("<p class=\"error\">{}</p>\n", escape_html(text.trim()))),
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">Thread {thread_id}, exported {exported}</p>\n{body}</body>\n</html>\n",
+        title = title,
+        style = HTML_STYLE,
+        thread_id = escape_html(thread_id),
+        exported = chrono::Utc::now().to_rfc3339(),
+        body = body,
+    )
+}
+
+/// Hashes of attachment stubs in events' top-level fields
+fn attachment_hashes(events: &[serde_json::Value]) -> Vec<String> {
+    let mut hashes: Vec<String> = events
+        .iter()
+        .filter_map(|event| event.as_object())
+        .flat_map(|event| event.values())
+        .filter_map(|value| value.get(blob_store::ATTACHMENT_KEY)?.as_str().map(str::to_string))
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+/// Zip the thread's events, its blueprint and any attachments (blocking)
+fn write_bundle(thread_id: &str, events: &[serde_json::Value], dest: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(dest).map_err(|e| format!("Failed to create export file: {}", e))?;
+    write_bundle_to(thread_id, events, file)
+}
+
+/// Write a thread bundle to any seekable writer (blocking)
+pub(crate) fn write_bundle_to<W: Write + std::io::Seek>(
+    thread_id: &str,
+    events: &[serde_json::Value],
+    writer: W,
+) -> Result<(), String> {
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write export bundle: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write export bundle: {}", e);
+
+    let manifest = serde_json::json!({
+        "thread_id": thread_id,
+        "title": thread_title(events),
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "events": events.len(),
+    });
+    zip.start_file("manifest.json", options).map_err(zip_err)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)
+        .map_err(io_err)?;
+
+    if let Some(blueprint) = events.first() {
+        zip.start_file("blueprint.json", options).map_err(zip_err)?;
+        zip.write_all(&serde_json::to_vec_pretty(blueprint).map_err(|e| e.to_string())?)
+            .map_err(io_err)?;
+    }
+
+    zip.start_file("thread.jsonl", options).map_err(zip_err)?;
+    let mut lines = Vec::new();
+    for event in events {
+        filesystem::serialize_event_line(event, &mut lines)?;
+    }
+    zip.write_all(&lines).map_err(io_err)?;
+
+    for hash in attachment_hashes(events) {
+        match blob_store::get(&hash) {
+            Ok(value) => {
+                zip.start_file(format!("attachments/{}.json", hash), options).map_err(zip_err)?;
+                zip.write_all(&serde_json::to_vec(&value).map_err(|e| e.to_string())?)
+                    .map_err(io_err)?;
+            }
+            Err(e) => log::warn!("Leaving attachment out of export: {}", e),
+        }
+    }
+
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+/// Chat Completions style `messages`, for replaying a thread against another model
+fn render_openai(events: &[serde_json::Value]) -> serde_json::Value {
+    let mut messages = Vec::new();
+
+    for event in events.iter().skip(1) {
+        let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let call_id = event.get("toolCallId").and_then(|id| id.as_str()).unwrap_or("");
+
+        match event_type {
+            "user-message" | "data-user-message" => {
+                if let Some(text) = content(event) {
+                    messages.push(serde_json::json!({ "role": "user", "content": text }));
+                }
+            }
+            "text-complete" => {
+                if let Some(text) = content(event) {
+                    messages.push(serde_json::json!({ "role": "assistant", "content": text }));
+                }
+            }
+            "tool-input-available" => {
+                let arguments = match event.get("input") {
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                    None => "{}".to_string(),
+                };
+                messages.push(serde_json::json!({
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": call_id,
+                        "type": "function",
+                        "function": {
+                            "name": event.get("toolName").and_then(|n| n.as_str()).unwrap_or("tool"),
+                            "arguments": arguments,
+                        },
+                    }],
+                }));
+            }
+            "tool-output-available" => messages.push(serde_json::json!({
+                "role": "tool",
+                "tool_call_id": call_id,
+                "content": json_text(event.get("output")),
+            })),
+            _ => {}
+        }
+    }
+
+    serde_json::json!({ "messages": messages })
+}
+
+/// Terminal width used for asciinema playback
+const CAST_WIDTH: u16 = 100;
+const CAST_HEIGHT: u16 = 40;
+
+/// Delay between events that carry no timestamp
+const CAST_STEP_SECS: f64 = 0.5;
+
+/// An asciinema v2 recording that replays the conversation, paced by event timestamps
+fn render_asciinema(events: &[serde_json::Value]) -> String {
+    let timestamp = |event: &serde_json::Value| {
+        event
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    };
+    let start = events.iter().find_map(timestamp);
+
+    let header = serde_json::json!({
+        "version": 2,
+        "width": CAST_WIDTH,
+        "height": CAST_HEIGHT,
+        "timestamp": start.map(|t| t.timestamp()).unwrap_or_else(|| chrono::Utc::now().timestamp()),
+        "title": thread_title(events),
+    });
+    let mut cast = format!("{}\n", header);
+
+    let mut elapsed = 0.0_f64;
+    for (event, section) in event_sections(events) {
+        elapsed = match (start, timestamp(event)) {
+            (Some(start), Some(at)) => ((at - start).num_milliseconds() as f64 / 1000.0).max(elapsed),
+            _ => elapsed + CAST_STEP_SECS,
+        };
+
+        let text = match section {
+            Section::Message { role, text } => format!("\x1b[1m{}\x1b[0m\n{}\n\n", role, text.trim()),
+            Section::Reasoning(text) => format!("\x1b[2m{}\x1b[0m\n\n", text.trim()),
+            Section::ToolCall { name, input } => format!("\x1b[36m▶ {}\x1b[0m\n{}\n\n", name, input),
+            Section::ToolResult { name, output } => format!("\x1b[36m◀ {}\x1b[0m\n{}\n\n", name, output),
+            Section::Error(text) => format!("\x1b[31mError: {}\x1b[0m\n\n", text.trim()),
+        };
+        // Terminals need CRLF to return to the first column
+        let line = serde_json::json!([elapsed, "o", text.replace('\n', "\r\n")]);
+        cast.push_str(&format!("{}\n", line));
+    }
+
+    cast
+}
+
+struct MarkdownExporter;
+
+impl Exporter for MarkdownExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("markdown", "Markdown", "md", "Readable transcript with tool calls in code blocks")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        write_file(dest, render_markdown(source.thread_id, source.events))
+    }
+}
+
+struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("html", "HTML", "html", "Standalone web page")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        write_file(dest, render_html(source.thread_id, source.events))
+    }
+}
+
+struct BundleExporter;
+
+impl Exporter for BundleExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("bundle", "Chimera bundle", "zip", "Raw events, the originating blueprint and any attachments")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        write_bundle(source.thread_id, source.events, dest)
+    }
+}
+
+struct OpenAiExporter;
+
+impl Exporter for OpenAiExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("openai", "OpenAI messages", "json", "Chat Completions message list, including tool calls")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&render_openai(source.events))
+            .map_err(|e| format!("Failed to serialize export: {}", e))?;
+        write_file(dest, json)
+    }
+}
+
+struct ObsidianNoteExporter;
+
+impl Exporter for ObsidianNoteExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("obsidian", "Obsidian note", "md", "Markdown note with frontmatter and callouts")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        let timestamps: Vec<&str> = source
+            .events
+            .iter()
+            .filter_map(|event| event.get("timestamp").and_then(|t| t.as_str()))
+            .collect();
+        let now = chrono::Utc::now().to_rfc3339();
+        let thread = filesystem::ThreadMetadata {
+            thread_id: source.thread_id.to_string(),
+            title: Some(thread_title(source.events)),
+            created_at: timestamps.first().map(|t| t.to_string()).unwrap_or_else(|| now.clone()),
+            updated_at: timestamps.last().map(|t| t.to_string()).unwrap_or(now),
+            file_path: dest.to_string_lossy().into_owned(),
+            event_count: source.events.len(),
+            preview: None,
+            last_event_at: timestamps.last().map(|t| t.to_string()),
+            blueprint_id: None,
+            blueprint_hash: None,
+            archived: filesystem::thread_archived(source.events),
+            tags: filesystem::thread_tags(source.events),
+            parent_thread_id: None,
+            draft: None,
+            display: None,
+        };
+        write_file(dest, crate::obsidian::render_standalone_note(&thread, source.events))
+    }
+}
+
+struct AsciinemaExporter;
+
+impl Exporter for AsciinemaExporter {
+    fn info(&self) -> ExportFormatInfo {
+        builtin("asciinema", "asciinema", "cast", "Terminal playback of the conversation")
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        write_file(dest, render_asciinema(source.events))
+    }
+}
+
+/// A format provided by a plugin command. The command gets
+/// `{thread_id, title, events}` and returns the document as a string, or
+/// `{content}` with one.
+struct PluginExporter {
+    host: Arc<PluginHost>,
+    plugin: String,
+    spec: PluginExporterSpec,
+}
+
+impl Exporter for PluginExporter {
+    fn info(&self) -> ExportFormatInfo {
+        ExportFormatInfo {
+            id: format!("{}.{}", self.plugin, self.spec.id),
+            name: self.spec.name.clone(),
+            extension: self.spec.extension.clone(),
+            description: self.spec.description.clone().unwrap_or_default(),
+            source: self.plugin.clone(),
+        }
+    }
+
+    fn export(&self, source: &ExportSource, dest: &Path) -> Result<(), String> {
+        let input = serde_json::json!({
+            "thread_id": source.thread_id,
+            "title": thread_title(source.events),
+            "events": source.events,
+        });
+        let output = self
+            .host
+            .invoke(&format!("{}.{}", self.plugin, self.spec.command), &input)?;
+
+        let content = match &output {
+            serde_json::Value::String(s) => Some(s.as_str()),
+            other => other.get("content").and_then(|c| c.as_str()),
+        }
+        .ok_or_else(|| format!("Exporter {}.{} returned no content", self.plugin, self.spec.id))?;
+        write_file(dest, content)
+    }
+}
+
+/// Export formats by id: the built-in ones plus whatever loaded plugins declare
+pub struct ExporterRegistry {
+    exporters: RwLock<Vec<Arc<dyn Exporter>>>,
+    plugins: OnceLock<Arc<PluginHost>>,
+}
+
+impl ExporterRegistry {
+    /// A registry with the built-in formats
+    pub fn new() -> Self {
+        let registry = Self {
+            exporters: RwLock::new(Vec::new()),
+            plugins: OnceLock::new(),
+        };
+        let builtins: [Arc<dyn Exporter>; 6] = [
+            Arc::new(MarkdownExporter),
+            Arc::new(HtmlExporter),
+            Arc::new(BundleExporter),
+            Arc::new(OpenAiExporter),
+            Arc::new(ObsidianNoteExporter),
+            Arc::new(AsciinemaExporter),
+        ];
+        for exporter in builtins {
+            if let Err(e) = registry.register(exporter) {
+                log::error!("{}", e);
+            }
+        }
+        registry
+    }
+
+    /// Add a format. Ids must be unique.
+    pub fn register(&self, exporter: Arc<dyn Exporter>) -> Result<(), String> {
+        let id = exporter.info().id;
+        let mut exporters = self.exporters.write().unwrap();
+        if exporters.iter().any(|existing| existing.info().id == id) {
+            return Err(format!("Export format {} is already registered", id));
+        }
+        exporters.push(exporter);
+        Ok(())
+    }
+
+    /// Offer the export formats of loaded plugins. They are looked up on each call,
+    /// so reloading plugins updates the list.
+    pub fn attach_plugins(&self, host: Arc<PluginHost>) {
+        let _ = self.plugins.set(host);
+    }
+
+    fn plugin_exporters(&self) -> Vec<Arc<dyn Exporter>> {
+        let Some(host) = self.plugins.get() else {
+            return Vec::new();
+        };
+        host.list()
+            .into_iter()
+            .flat_map(|manifest| {
+                let plugin = manifest.name;
+                manifest.exporters.into_iter().map(move |spec| {
+                    Arc::new(PluginExporter {
+                        host: host.clone(),
+                        plugin: plugin.clone(),
+                        spec,
+                    }) as Arc<dyn Exporter>
+                })
+            })
+            .collect()
+    }
+
+    fn all(&self) -> Vec<Arc<dyn Exporter>> {
+        let mut exporters = self.exporters.read().unwrap().clone();
+        exporters.extend(self.plugin_exporters());
+        exporters
+    }
+
+    /// Every available format
+    pub fn list(&self) -> Vec<ExportFormatInfo> {
+        self.all().iter().map(|exporter| exporter.info()).collect()
+    }
+
+    fn get(&self, format: &str) -> Result<Arc<dyn Exporter>, String> {
+        self.all()
+            .into_iter()
+            .find(|exporter| exporter.info().id == format)
+            .ok_or_else(|| format!("Unknown export format: {}", format))
+    }
+
+    /// Render a thread to `dest_path` without involving the backend
+    pub async fn export_thread(&self, thread_id: String, format: &str, dest_path: String) -> Result<ExportReport, String> {
+        let exporter = self.get(format)?;
+        let events = filesystem::load_thread(thread_id.clone()).await?;
+        let dest = std::path::PathBuf::from(&dest_path);
+
+        if crate::audit::outside_data_dir(&dest) {
+            crate::audit::record_async(crate::audit::AuditEntry::new(
+                "fs",
+                "export_thread",
+                "ui",
+                dest_path.as_str(),
+                true,
+            ))
+            .await;
+        }
+
+        let event_count = events.len();
+        {
+            let dest = dest.clone();
+            let thread_id = thread_id.clone();
+            filesystem::blocking(move || {
+                exporter.export(
+                    &ExportSource {
+                        thread_id: &thread_id,
+                        events: &events,
+                    },
+                    &dest,
+                )
+            })
+            .await?;
+        }
+
+        let bytes = tokio::fs::metadata(&dest).await.map(|m| m.len()).unwrap_or(0);
+        log::info!("Exported thread {} as {} to {}", thread_id, format, dest.display());
+
+        Ok(ExportReport {
+            dest_path,
+            format: format.to_string(),
+            bytes,
+            events: event_count,
+        })
+    }
+}
@@ -1,9 +1,113 @@
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
+/// What kind of filesystem failure an `FsError` is, for telling the user what to do about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsErrorKind {
+    NotFound,
+    PermissionDenied,
+    /// The data directory is on read-only media or a read-only mount
+    ReadOnly,
+    DiskFull,
+    /// A network share that stopped answering or went away
+    Unreachable,
+    /// Any other I/O failure
+    Io,
+    /// Bad input or content, e.g. an invalid id or unparseable JSON
+    Invalid,
+}
+
+impl FsErrorKind {
+    fn of(error: &std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match error.kind() {
+            ErrorKind::NotFound => Self::NotFound,
+            ErrorKind::PermissionDenied => Self::PermissionDenied,
+            ErrorKind::ReadOnlyFilesystem => Self::ReadOnly,
+            ErrorKind::StorageFull | ErrorKind::QuotaExceeded => Self::DiskFull,
+            ErrorKind::TimedOut
+            | ErrorKind::NetworkDown
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NotConnected
+            | ErrorKind::ConnectionReset
+            | ErrorKind::StaleNetworkFileHandle => Self::Unreachable,
+            ErrorKind::InvalidData | ErrorKind::InvalidInput => Self::Invalid,
+            _ => Self::Io,
+        }
+    }
+
+    /// What the user can do about it, for kinds where there's something
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            Self::PermissionDenied => Some("Check the data directory's permissions, or choose another in settings"),
+            Self::ReadOnly => Some("The data directory is read-only; choose a writable one in settings"),
+            Self::DiskFull => Some("The disk is full; free up space or move the data directory"),
+            Self::Unreachable => Some("The data directory's drive isn't responding; reconnect it or choose a local one"),
+            Self::NotFound | Self::Io | Self::Invalid => None,
+        }
+    }
+}
+
+/// A failed filesystem operation. Converts to the `String` errors the rest of
+/// the app uses; the message reads as before, with a hint added.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsError {
+    pub kind: FsErrorKind,
+    pub message: String,
+    pub hint: Option<&'static str>,
+}
+
+impl FsError {
+    pub fn new(kind: FsErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            hint: kind.hint(),
+        }
+    }
+
+    /// An I/O error while trying to `action`, e.g. "write thread file"
+    pub fn io(action: &str, error: std::io::Error) -> Self {
+        Self::new(FsErrorKind::of(&error), format!("Failed to {}: {}", action, error))
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(FsErrorKind::NotFound, message)
+    }
+
+    /// Bad input or content rather than a failed operation
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(FsErrorKind::Invalid, message)
+    }
+}
+
+impl std::fmt::Display for FsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.hint {
+            Some(hint) => write!(f, "{}. {}", self.message, hint),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for FsError {}
+
+impl From<FsError> for String {
+    fn from(error: FsError) -> Self {
+        error.to_string()
+    }
+}
+
 /// Metadata for a blueprint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlueprintMetadata {
@@ -21,35 +125,452 @@ pub struct ThreadMetadata {
     pub created_at: String,
     pub updated_at: String,
     pub file_path: String,
+    #[serde(default)]
+    pub event_count: usize,
+    /// Start of the latest user or assistant message
+    #[serde(default)]
+    pub preview: Option<String>,
+    /// Timestamp of the thread's latest event
+    #[serde(default)]
+    pub last_event_at: Option<String>,
+    /// File id of the blueprint the thread was created from, if it still exists
+    #[serde(default)]
+    pub blueprint_id: Option<String>,
+    /// SHA-256 of the thread's blueprint definition
+    #[serde(default)]
+    pub blueprint_hash: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Thread this one was forked from
+    #[serde(default)]
+    pub parent_thread_id: Option<String>,
+    /// Unsent message saved with `save_draft`, filled in by `list_threads`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<String>,
+    /// Times and counts formatted for the user's locale, filled in by `list_threads`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<ThreadDisplay>,
 }
 
-/// Get the Chimera desktop data directory (~/chimera-desktop)
-fn get_data_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
-    Ok(home.join("chimera-desktop"))
+/// A thread's timestamps and event count as the user's locale writes them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadDisplay {
+    pub locale: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub last_event_at: Option<String>,
+    pub event_count: String,
+}
+
+impl ThreadMetadata {
+    /// Fill in `display` for a locale
+    pub fn format_for(&mut self, formatter: &crate::i18n::Formatter) {
+        self.display = Some(ThreadDisplay {
+            locale: formatter.locale().to_string(),
+            created_at: formatter.date_time(&self.created_at),
+            updated_at: formatter.date_time(&self.updated_at),
+            last_event_at: self.last_event_at.as_deref().and_then(|at| formatter.date_time(at)),
+            event_count: formatter.number(self.event_count as u64),
+        });
+    }
+}
+
+/// Get the Chimera desktop data directory: `CHIMERA_DATA_DIR` if set, otherwise
+/// the active workspace's (~/chimera-desktop for the default workspace)
+pub fn get_data_dir() -> Result<PathBuf, FsError> {
+    if let Some(dir) = crate::workspaces::pinned() {
+        return Ok(dir);
+    }
+    // Only fails when there's no home directory to put the default one in
+    crate::workspaces::active_dir().map_err(|e| FsError::new(FsErrorKind::NotFound, e))
+}
+
+/// How long the data directory probe waits before calling it unreachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the data directory's filesystem supports, from `probe_data_dir`
+#[derive(Debug, Clone, Serialize)]
+pub struct FsCapabilities {
+    pub data_dir: String,
+    pub writable: bool,
+    /// Whether writes can be synced to disk; without it a crash can lose recent events
+    pub fsync: bool,
+    /// Whether `a` and `A` are different files; None when it couldn't be checked
+    pub case_sensitive: Option<bool>,
+    /// What failed, each with a hint where there's something to do about it
+    pub problems: Vec<FsError>,
+}
+
+impl FsCapabilities {
+    pub fn degraded(&self) -> bool {
+        !self.problems.is_empty()
+    }
+}
+
+fn probe_blocking(dir: PathBuf) -> FsCapabilities {
+    let mut capabilities = FsCapabilities {
+        data_dir: dir.display().to_string(),
+        writable: false,
+        fsync: false,
+        case_sensitive: None,
+        problems: Vec::new(),
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        capabilities.problems.push(FsError::io("create the data directory", e));
+        return capabilities;
+    }
+
+    let probe = dir.join(format!(".chimera-probe-{}", uuid::Uuid::new_v4().simple()));
+    let written = fs::File::create(&probe).and_then(|mut file| {
+        use std::io::Write;
+        file.write_all(b"probe")?;
+        Ok(file)
+    });
+    let file = match written {
+        Ok(file) => file,
+        Err(e) => {
+            capabilities.problems.push(FsError::io("write to the data directory", e));
+            return capabilities;
+        }
+    };
+    capabilities.writable = true;
+
+    match file.sync_all() {
+        Ok(()) => capabilities.fsync = true,
+        Err(e) => capabilities.problems.push(FsError::io("sync a file in the data directory", e)),
+    }
+    drop(file);
+
+    // The probe name is lowercase hex, so its uppercase twin only exists if case is ignored
+    let name = probe.file_name().map(|n| n.to_string_lossy().to_uppercase()).unwrap_or_default();
+    capabilities.case_sensitive = dir.join(name).try_exists().ok().map(|exists| !exists);
+    if let Err(e) = fs::remove_file(&probe) {
+        capabilities.problems.push(FsError::io("delete a file in the data directory", e));
+    }
+    capabilities
+}
+
+/// Check that the data directory can be written and synced, and whether it
+/// ignores case. A directory on a network share that doesn't answer within
+/// `PROBE_TIMEOUT` is reported unreachable.
+pub async fn probe_data_dir() -> FsCapabilities {
+    let dir = match get_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            return FsCapabilities {
+                data_dir: String::new(),
+                writable: false,
+                fsync: false,
+                case_sensitive: None,
+                problems: vec![e],
+            }
+        }
+    };
+    let data_dir = dir.display().to_string();
+    match tokio::time::timeout(PROBE_TIMEOUT, tokio::task::spawn_blocking(move || probe_blocking(dir))).await {
+        Ok(Ok(capabilities)) => capabilities,
+        Ok(Err(e)) => FsCapabilities {
+            data_dir,
+            writable: false,
+            fsync: false,
+            case_sensitive: None,
+            problems: vec![FsError::new(FsErrorKind::Io, format!("Filesystem task failed: {}", e))],
+        },
+        Err(_) => FsCapabilities {
+            problems: vec![FsError::new(
+                FsErrorKind::Unreachable,
+                format!("The data directory {} didn't respond within {}s", data_dir, PROBE_TIMEOUT.as_secs()),
+            )],
+            data_dir,
+            writable: false,
+            fsync: false,
+            case_sensitive: None,
+        },
+    }
 }
 
 /// Get the blueprints directory
-fn get_blueprints_dir() -> Result<PathBuf, String> {
+pub fn get_blueprints_dir() -> Result<PathBuf, FsError> {
     Ok(get_data_dir()?.join("blueprints"))
 }
 
 /// Get the threads directory
-fn get_threads_dir() -> Result<PathBuf, String> {
+pub fn get_threads_dir() -> Result<PathBuf, FsError> {
     Ok(get_data_dir()?.join("threads"))
 }
 
+/// Get the directory deleted threads are moved to until purged
+fn get_trash_dir() -> Result<PathBuf, FsError> {
+    Ok(get_threads_dir()?.join(".trash"))
+}
+
+/// Stored metadata for a thread, kept beside it so a rename is read without
+/// scanning the thread's events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadMetaRecord {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Metadata records live in `thread-meta/<thread_id>.json` in the data directory
+fn get_thread_meta_path(thread_id: &str) -> Result<PathBuf, FsError> {
+    get_thread_path(thread_id)?;
+    Ok(get_data_dir()?.join("thread-meta").join(format!("{}.json", thread_id)))
+}
+
+/// A thread's metadata record, if it has one
+pub async fn read_thread_meta(thread_id: &str) -> Option<ThreadMetaRecord> {
+    let content = tokio::fs::read(get_thread_meta_path(thread_id).ok()?).await.ok()?;
+    serde_json::from_slice(&content)
+        .inspect_err(|e| log::warn!("Ignoring unreadable metadata for thread {}: {}", thread_id, e))
+        .ok()
+}
+
+/// Replace a thread's metadata record
+async fn write_thread_meta(thread_id: &str, record: &ThreadMetaRecord) -> Result<(), FsError> {
+    let path = get_thread_meta_path(thread_id)?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| FsError::io("create thread metadata directory", e))?;
+    }
+    let content = serde_json::to_vec_pretty(record).map_err(|e| FsError::invalid(format!("Failed to serialize thread metadata: {}", e)))?;
+    let temp = path.with_extension("json.tmp");
+    tokio::fs::write(&temp, content)
+        .await
+        .map_err(|e| FsError::io("write thread metadata", e))?;
+    tokio::fs::rename(&temp, &path)
+        .await
+        .map_err(|e| FsError::io("replace thread metadata", e))
+}
+
+/// Get the JSONL path for a thread, rejecting ids that could escape the threads directory
+pub fn get_thread_path(thread_id: &str) -> Result<PathBuf, FsError> {
+    if thread_id.is_empty() || thread_id.contains(['/', '\\']) || thread_id.contains("..") {
+        return Err(FsError::invalid(format!("Invalid thread id: {}", thread_id)));
+    }
+    Ok(get_threads_dir()?.join(format!("{}.jsonl", thread_id)))
+}
+
+/// Thread files at least this large are memory-mapped instead of read line by line
+const MMAP_THRESHOLD: u64 = 50 * 1024 * 1024;
+
+/// With the `parallel-parse` feature, threads at least this large take the mapped,
+/// parallel path too
+const PARALLEL_PARSE_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// Bytes parsed between progress reports for mapped loads
+const PROGRESS_INTERVAL: u64 = 8 * 1024 * 1024;
+
+/// Maximum files read or parsed concurrently during a scan
+pub const SCAN_CONCURRENCY: usize = 16;
+
+/// Run blocking std::fs work on the blocking pool so large scans don't stall the runtime
+pub async fn blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Filesystem task failed: {}", e))?
+}
+
+/// `blocking` for work that fails with an `FsError`, keeping its kind
+pub async fn blocking_fs<T, F>(f: F) -> Result<T, FsError>
+where
+    F: FnOnce() -> Result<T, FsError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| FsError::new(FsErrorKind::Io, format!("Filesystem task failed: {}", e)))?
+}
+
+/// How long exit waits for thread writes in flight before warning the frontend
+pub const PENDING_WRITES_WARN_AFTER: Duration = Duration::from_millis(500);
+
+/// Longest exit waits for thread writes in flight
+pub const PENDING_WRITES_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A thread write in progress
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingWrite {
+    pub thread_id: String,
+    pub started_at: String,
+}
+
+/// Thread writes in progress, so exit can wait for them rather than cut them off
+#[derive(Default)]
+struct WriteQueue {
+    pending: Mutex<HashMap<u64, PendingWrite>>,
+    next_id: AtomicU64,
+    idle: tokio::sync::Notify,
+}
+
+static WRITE_QUEUE: LazyLock<WriteQueue> = LazyLock::new(WriteQueue::default);
+
+/// Marks a thread write as in flight until dropped
+pub struct WriteGuard {
+    id: u64,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        let mut pending = WRITE_QUEUE.pending.lock().unwrap();
+        pending.remove(&self.id);
+        if pending.is_empty() {
+            WRITE_QUEUE.idle.notify_waiters();
+        }
+    }
+}
+
+/// Track a write to `thread_id` until the returned guard is dropped
+pub fn track_write(thread_id: &str) -> WriteGuard {
+    let id = WRITE_QUEUE.next_id.fetch_add(1, Ordering::Relaxed);
+    WRITE_QUEUE.pending.lock().unwrap().insert(
+        id,
+        PendingWrite {
+            thread_id: thread_id.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        },
+    );
+    WriteGuard { id }
+}
+
+/// Thread writes in flight, oldest first
+pub fn pending_writes() -> Vec<PendingWrite> {
+    let mut pending: Vec<PendingWrite> = WRITE_QUEUE.pending.lock().unwrap().values().cloned().collect();
+    pending.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    pending
+}
+
+/// Wait up to `timeout` for every thread write in flight to finish
+pub async fn flush_pending_writes(timeout: Duration) -> Result<(), FsError> {
+    let drained = tokio::time::timeout(timeout, async {
+        loop {
+            let idle = WRITE_QUEUE.idle.notified();
+            tokio::pin!(idle);
+            // Register before checking, so a write finishing in between still wakes us
+            idle.as_mut().enable();
+            if WRITE_QUEUE.pending.lock().unwrap().is_empty() {
+                return;
+            }
+            idle.await;
+        }
+    })
+    .await;
+
+    match drained {
+        Ok(()) => Ok(()),
+        Err(_) => {
+            let threads: Vec<String> = pending_writes().into_iter().map(|write| write.thread_id).collect();
+            Err(FsError::new(
+                FsErrorKind::Io,
+                format!("{} thread writes still pending: {}", threads.len(), threads.join(", ")),
+            ))
+        }
+    }
+}
+
+/// One async mutex per thread, so writers in this process queue up rather than
+/// contend for the file lock
+static THREAD_LOCKS: LazyLock<Mutex<HashMap<String, std::sync::Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Exclusive write access to a thread, shared with other processes (a second
+/// window's backend, the headless CLI) through an advisory lock on
+/// `locks/<thread_id>.lock` in the data directory. Released when dropped.
+pub struct ThreadLock {
+    _file: fs::File,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
+
+fn thread_mutex(thread_id: &str) -> std::sync::Arc<tokio::sync::Mutex<()>> {
+    let mut locks = THREAD_LOCKS.lock().unwrap();
+    // Drop entries nobody holds or waits on
+    locks.retain(|_, lock| std::sync::Arc::strong_count(lock) > 1);
+    locks.entry(thread_id.to_string()).or_default().clone()
+}
+
+/// Take the advisory lock on a thread's lock file (blocking)
+fn lock_thread_file(thread_id: &str) -> Result<fs::File, FsError> {
+    // Same id rules as the thread file
+    get_thread_path(thread_id)?;
+    let dir = get_data_dir()?.join("locks");
+    fs::create_dir_all(&dir).map_err(|e| FsError::io("create locks directory", e))?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(format!("{}.lock", thread_id)))
+        .map_err(|e| FsError::io(&format!("open lock file for thread {}", thread_id), e))?;
+    file.lock().map_err(|e| FsError::io(&format!("lock thread {}", thread_id), e))?;
+    Ok(file)
+}
+
+/// Wait for exclusive write access to a thread
+pub async fn lock_thread(thread_id: &str) -> Result<ThreadLock, FsError> {
+    let guard = thread_mutex(thread_id).lock_owned().await;
+    let id = thread_id.to_string();
+    let file = blocking_fs(move || lock_thread_file(&id)).await?;
+    Ok(ThreadLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// Wait for exclusive write access to a thread (blocking; not from async code)
+pub fn lock_thread_blocking(thread_id: &str) -> Result<ThreadLock, FsError> {
+    let guard = thread_mutex(thread_id).blocking_lock_owned();
+    Ok(ThreadLock {
+        _file: lock_thread_file(thread_id)?,
+        _guard: guard,
+    })
+}
+
+/// A thread's JSONL path, decompressing the thread first if it was compressed
+async fn existing_thread_path(thread_id: &str) -> Result<PathBuf, FsError> {
+    let file_path = get_thread_path(thread_id)?;
+    crate::thread_compression::ensure_decompressed(thread_id).await?;
+    if !path_exists(&file_path).await {
+        return Err(FsError::not_found(format!("Thread {} not found", thread_id)));
+    }
+    Ok(file_path)
+}
+
+/// A compressed thread's JSONL, decompressed in memory for reading. The file is
+/// left compressed, so this works on a read-only data directory.
+async fn read_compressed_thread(thread_id: &str) -> Result<Vec<u8>, FsError> {
+    let path = crate::thread_compression::compressed_path(thread_id)?;
+    if !path_exists(&path).await {
+        return Err(FsError::not_found(format!("Thread {} not found", thread_id)));
+    }
+    blocking_fs(move || crate::thread_compression::read_thread_file(&path)).await
+}
+
+/// Whether a path exists, without blocking the runtime
+async fn path_exists(path: &PathBuf) -> bool {
+    tokio::fs::try_exists(path).await.unwrap_or(false)
+}
+
 /// Initialize the filesystem structure
-pub async fn init_filesystem() -> Result<(), String> {
+pub async fn init_filesystem() -> Result<(), FsError> {
     let data_dir = get_data_dir()?;
     let blueprints_dir = get_blueprints_dir()?;
     let threads_dir = get_threads_dir()?;
 
     // Create directories if they don't exist
-    fs::create_dir_all(&blueprints_dir)
-        .map_err(|e| format!("Failed to create blueprints directory: {}", e))?;
-    fs::create_dir_all(&threads_dir)
-        .map_err(|e| format!("Failed to create threads directory: {}", e))?;
+    tokio::fs::create_dir_all(&blueprints_dir)
+        .await
+        .map_err(|e| FsError::io("create blueprints directory", e))?;
+    tokio::fs::create_dir_all(&threads_dir)
+        .await
+        .map_err(|e| FsError::io("create threads directory", e))?;
 
     log::info!("Initialized filesystem at {:?}", data_dir);
     log::info!("Blueprints: {:?}", blueprints_dir);
@@ -59,90 +580,132 @@ pub async fn init_filesystem() -> Result<(), String> {
 }
 
 /// List all available blueprints
-pub async fn list_blueprints() -> Result<Vec<BlueprintMetadata>, String> {
+pub async fn list_blueprints() -> Result<Vec<BlueprintMetadata>, FsError> {
+    let files = blocking_fs(list_blueprint_files).await?;
+
+    Ok(read_blueprints(files).await.into_iter().filter_map(|(_, blueprint)| blueprint).collect())
+}
+
+/// Parse blueprint files concurrently on the blocking pool, preserving input order
+pub async fn read_blueprints(paths: Vec<PathBuf>) -> Vec<(PathBuf, Option<BlueprintMetadata>)> {
+    stream::iter(paths)
+        .map(|path| async move {
+            let parsed = blocking_fs({
+                let path = path.clone();
+                move || Ok(read_blueprint_metadata(&path))
+            })
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                None
+            });
+            (path, parsed)
+        })
+        .buffered(SCAN_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Paths of all blueprint JSON files (blocking)
+pub fn list_blueprint_files() -> Result<Vec<PathBuf>, FsError> {
     let blueprints_dir = get_blueprints_dir()?;
 
     if !blueprints_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut blueprints = Vec::new();
+    let mut files = Vec::new();
 
     let entries = fs::read_dir(&blueprints_dir)
-        .map_err(|e| format!("Failed to read blueprints directory: {}", e))?;
+        .map_err(|e| FsError::io("read blueprints directory", e))?;
 
     for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let entry = entry.map_err(|e| FsError::io("read directory entry", e))?;
         let path = entry.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            // Read the blueprint file to extract metadata
-            match fs::read_to_string(&path) {
-                Ok(content) => {
-                    match serde_json::from_str::<serde_json::Value>(&content) {
-                        Ok(json) => {
-                            // Extract metadata from blueprint
-                            let blueprint = json.get("blueprint").and_then(|b| b.as_object());
-                            let space = blueprint.and_then(|b| b.get("space")).and_then(|s| s.as_object());
-                            let agents = space.and_then(|s| s.get("agents")).and_then(|a| a.as_array());
-
-                            // Get first agent's name and description
-                            let first_agent = agents.and_then(|a| a.first()).and_then(|a| a.as_object());
-                            let name = first_agent
-                                .and_then(|a| a.get("name"))
-                                .and_then(|n| n.as_str())
-                                .unwrap_or("Unknown Agent")
-                                .to_string();
-
-                            let description = first_agent
-                                .and_then(|a| a.get("description"))
-                                .and_then(|d| d.as_str())
-                                .map(|s| s.to_string());
-
-                            // Use filename (without extension) as blueprint id
-                            let id = path.file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-
-                            blueprints.push(BlueprintMetadata {
-                                id,
-                                name,
-                                description,
-                                file_path: path.to_string_lossy().to_string(),
-                            });
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to parse blueprint {}: {}", path.display(), e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::warn!("Failed to read blueprint {}: {}", path.display(), e);
-                }
-            }
+            files.push(path);
         }
     }
 
-    Ok(blueprints)
+    Ok(files)
+}
+
+/// Read a blueprint file and extract its metadata, logging (and skipping) bad files (blocking)
+pub fn read_blueprint_metadata(path: &std::path::Path) -> Option<BlueprintMetadata> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("Failed to read blueprint {}: {}", path.display(), e);
+            return None;
+        }
+    };
+
+    let json = match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(json) => json,
+        Err(e) => {
+            log::warn!("Failed to parse blueprint {}: {}", path.display(), e);
+            return None;
+        }
+    };
+
+    // Extract metadata from blueprint
+    let blueprint = json.get("blueprint").and_then(|b| b.as_object());
+    let space = blueprint.and_then(|b| b.get("space")).and_then(|s| s.as_object());
+    let agents = space.and_then(|s| s.get("agents")).and_then(|a| a.as_array());
+
+    // Get first agent's name and description
+    let first_agent = agents.and_then(|a| a.first()).and_then(|a| a.as_object());
+    let name = first_agent
+        .and_then(|a| a.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("Unknown Agent")
+        .to_string();
+
+    let description = first_agent
+        .and_then(|a| a.get("description"))
+        .and_then(|d| d.as_str())
+        .map(|s| s.to_string());
+
+    // Use filename (without extension) as blueprint id
+    let id = path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    Some(BlueprintMetadata {
+        id,
+        name,
+        description,
+        file_path: path.to_string_lossy().to_string(),
+    })
 }
 
 /// Create a new thread with the given blueprint
-pub async fn create_thread(blueprint_json: String) -> Result<String, String> {
+pub async fn create_thread(blueprint_json: String) -> Result<String, FsError> {
+    create_thread_with_header(blueprint_json, &[]).await
+}
+
+/// Create a thread whose blueprint line is followed by `header` events
+pub async fn create_thread_with_header(blueprint_json: String, header: &[serde_json::Value]) -> Result<String, FsError> {
     let threads_dir = get_threads_dir()?;
 
     // Parse blueprint JSON
     let mut blueprint: serde_json::Value = serde_json::from_str(&blueprint_json)
-        .map_err(|e| format!("Failed to parse blueprint JSON: {}", e))?;
+        .map_err(|e| FsError::invalid(format!("Failed to parse blueprint JSON: {}", e)))?;
 
     // Generate a new UUID for this thread
     let thread_id = uuid::Uuid::new_v4().to_string();
 
-    // Add thread_id to the blueprint
+    // Add thread_id and the schema version to the blueprint
     if let Some(obj) = blueprint.as_object_mut() {
         obj.insert("thread_id".to_string(), serde_json::Value::String(thread_id.clone()));
+        obj.insert(
+            crate::event_schema::VERSION_FIELD.to_string(),
+            serde_json::json!(crate::event_schema::CURRENT_THREAD_VERSION),
+        );
     } else {
-        return Err("Blueprint JSON is not an object".to_string());
+        return Err(FsError::invalid("Blueprint JSON is not an object"));
     }
 
     let file_path = threads_dir.join(format!("{}.jsonl", thread_id));
@@ -154,21 +717,30 @@ pub async fn create_thread(blueprint_json: String) -> Result<String, String> {
         .truncate(true)
         .open(&file_path)
         .await
-        .map_err(|e| format!("Failed to create thread file: {}", e))?;
+        .map_err(|e| FsError::io("create thread file", e))?;
 
     // Serialize as minified JSON (no pretty-printing) for JSONL format
     let minified_json = serde_json::to_string(&blueprint)
-        .map_err(|e| format!("Failed to serialize blueprint: {}", e))?;
+        .map_err(|e| FsError::invalid(format!("Failed to serialize blueprint: {}", e)))?;
 
     file.write_all(minified_json.as_bytes())
         .await
-        .map_err(|e| format!("Failed to write blueprint: {}", e))?;
+        .map_err(|e| FsError::io("write blueprint", e))?;
     file.write_all(b"\n")
         .await
-        .map_err(|e| format!("Failed to write newline: {}", e))?;
+        .map_err(|e| FsError::io("write newline", e))?;
+
+    let mut lines = Vec::new();
+    for event in header {
+        serialize_event_line(event, &mut lines)?;
+    }
+    file.write_all(&lines)
+        .await
+        .map_err(|e| FsError::io("write thread header", e))?;
+
     file.flush()
         .await
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
+        .map_err(|e| FsError::io("flush file", e))?;
 
     log::info!("Created thread {} at {:?}", thread_id, file_path);
 
@@ -176,24 +748,59 @@ pub async fn create_thread(blueprint_json: String) -> Result<String, String> {
 }
 
 /// Load a thread's events
-pub async fn load_thread(thread_id: String) -> Result<Vec<serde_json::Value>, String> {
-    let threads_dir = get_threads_dir()?;
-    let file_path = threads_dir.join(format!("{}.jsonl", thread_id));
+pub async fn load_thread(thread_id: String) -> Result<Vec<serde_json::Value>, FsError> {
+    load_thread_with_progress(thread_id, |_, _| {}).await
+}
 
-    if !file_path.exists() {
-        return Err(format!("Thread {} not found", thread_id));
+/// Load a thread's events, memory-mapping files over `MMAP_THRESHOLD` (or
+/// `PARALLEL_PARSE_THRESHOLD` with the `parallel-parse` feature) and reporting
+/// `(bytes_parsed, total_bytes)` to `on_progress` while they parse. Payloads moved
+/// to the blob store by compaction are rehydrated. A compressed thread is
+/// decompressed in memory and stays compressed on disk.
+pub async fn load_thread_with_progress<F>(thread_id: String, on_progress: F) -> Result<Vec<serde_json::Value>, FsError>
+where
+    F: Fn(u64, u64) + Send + 'static,
+{
+    let started = std::time::Instant::now();
+    let file_path = get_thread_path(&thread_id)?;
+
+    let size = match tokio::fs::metadata(&file_path).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => {
+            let content = read_compressed_thread(&thread_id).await?;
+            let size = content.len() as u64;
+            let events = blocking_fs(move || {
+                let mut events = Vec::new();
+                parse_lines(&content, &mut events);
+                on_progress(size, size);
+                Ok(events)
+            })
+            .await?;
+            log::info!("Loaded {} events from thread {} (compressed)", events.len(), thread_id);
+            let events = crate::blob_store::rehydrate(events).await?;
+            crate::metrics::record_filesystem("load_thread", started.elapsed());
+            return Ok(events);
+        }
+    };
+
+    if size >= MMAP_THRESHOLD || (cfg!(feature = "parallel-parse") && size >= PARALLEL_PARSE_THRESHOLD) {
+        let events = blocking_fs(move || load_mapped(&file_path, size, on_progress)).await?;
+        log::info!("Loaded {} events from thread {} (mapped, {} bytes)", events.len(), thread_id, size);
+        let events = crate::blob_store::rehydrate(events).await?;
+        crate::metrics::record_filesystem("load_thread", started.elapsed());
+        return Ok(events);
     }
 
     let file = tokio::fs::File::open(&file_path)
         .await
-        .map_err(|e| format!("Failed to open thread file: {}", e))?;
+        .map_err(|e| FsError::io("open thread file", e))?;
 
     let reader = BufReader::new(file);
     let mut lines = reader.lines();
     let mut events = Vec::new();
 
     while let Some(line) = lines.next_line().await
-        .map_err(|e| format!("Failed to read line: {}", e))? {
+        .map_err(|e| FsError::io("read line", e))? {
 
         if !line.trim().is_empty() {
             match serde_json::from_str::<serde_json::Value>(&line) {
@@ -208,119 +815,766 @@ pub async fn load_thread(thread_id: String) -> Result<Vec<serde_json::Value>, St
 
     log::info!("Loaded {} events from thread {}", events.len(), thread_id);
 
+    let events = crate::blob_store::rehydrate(events).await?;
+    crate::metrics::record_filesystem("load_thread", started.elapsed());
+    Ok(events)
+}
+
+/// Parse a large thread file straight from a read-only mapping, one line slice at a
+/// time, so no per-line `String` is allocated (blocking)
+fn load_mapped<F>(path: &PathBuf, size: u64, on_progress: F) -> Result<Vec<serde_json::Value>, FsError>
+where
+    F: Fn(u64, u64),
+{
+    let file = fs::File::open(path).map_err(|e| FsError::io("open thread file", e))?;
+
+    // SAFETY: thread files are only ever appended to, which doesn't invalidate the
+    // mapped prefix; nothing in the app truncates a thread file while it is loaded.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| FsError::io("map thread file", e))?;
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+
+    let mut events = Vec::new();
+    let mut offset = 0;
+
+    on_progress(0, size);
+
+    // Parse in chunks of whole lines so progress can be reported between them
+    while offset < map.len() {
+        let mut end = (offset + PROGRESS_INTERVAL as usize).min(map.len());
+        end = match map[end..].iter().position(|b| *b == b'\n') {
+            Some(newline) => end + newline + 1,
+            None => map.len(),
+        };
+
+        parse_lines(&map[offset..end], &mut events);
+        offset = end;
+        on_progress(offset as u64, size);
+    }
+
+    on_progress(size, size);
+
     Ok(events)
 }
 
-/// Append events to a thread's JSONL file
+/// Parse a block of JSONL, skipping blank and malformed lines
+#[cfg(not(feature = "parallel-parse"))]
+fn parse_lines(block: &[u8], events: &mut Vec<serde_json::Value>) {
+    for line in block.split(|b| *b == b'\n') {
+        if let Some(event) = parse_line(line) {
+            events.push(event);
+        }
+    }
+}
+
+/// Parse a block of JSONL across the rayon pool with simd-json, preserving line order
+#[cfg(feature = "parallel-parse")]
+fn parse_lines(block: &[u8], events: &mut Vec<serde_json::Value>) {
+    use rayon::prelude::*;
+
+    let parsed: Vec<Option<serde_json::Value>> = block
+        .par_split(|b| *b == b'\n')
+        .map(|line| {
+            if line.trim_ascii().is_empty() {
+                return None;
+            }
+            // simd-json parses in place, so it needs its own copy of the line
+            let mut scratch = line.to_vec();
+            match simd_json::serde::from_slice::<serde_json::Value>(&mut scratch) {
+                Ok(event) => Some(event),
+                Err(_) => parse_line(line),
+            }
+        })
+        .collect();
+
+    events.extend(parsed.into_iter().flatten());
+}
+
+/// Events sent per chunk by `stream_thread` unless the caller asks otherwise
+pub const DEFAULT_STREAM_CHUNK_EVENTS: usize = 500;
+
+/// A run of a thread's events, as sent by `stream_thread`
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadChunk {
+    pub thread_id: String,
+    /// Index of the chunk's first event, as `load_thread` would number it
+    pub offset: usize,
+    pub events: Vec<serde_json::Value>,
+    /// The last chunk; `events` may be empty
+    pub done: bool,
+}
+
+/// Read a thread and hand its events to `on_chunk` `chunk_size` at a time as
+/// they're parsed, so a long history can be shown before it's all read. Events
+/// match `load_thread`'s, blobs rehydrated. Returns the number of events.
+pub async fn stream_thread<F>(thread_id: String, chunk_size: usize, mut on_chunk: F) -> Result<usize, FsError>
+where
+    F: FnMut(ThreadChunk) -> Result<(), String>,
+{
+    let chunk_size = chunk_size.max(1);
+    let path = get_thread_path(&thread_id)?;
+    let reader: Box<dyn tokio::io::AsyncRead + Unpin + Send> = match tokio::fs::File::open(&path).await {
+        Ok(file) => Box::new(file),
+        Err(_) => Box::new(std::io::Cursor::new(read_compressed_thread(&thread_id).await?)),
+    };
+    let mut lines = BufReader::new(reader).lines();
+
+    let mut offset = 0;
+    let mut chunk = Vec::with_capacity(chunk_size);
+    loop {
+        let line = lines.next_line().await.map_err(|e| FsError::io("read line", e))?;
+        let done = line.is_none();
+        if let Some(event) = line.and_then(|line| parse_line(line.as_bytes())) {
+            chunk.push(event);
+        }
+        if done || chunk.len() >= chunk_size {
+            let events = crate::blob_store::rehydrate(std::mem::take(&mut chunk)).await?;
+            let sent = events.len();
+            on_chunk(ThreadChunk {
+                thread_id: thread_id.clone(),
+                offset,
+                events,
+                done,
+            })
+            .map_err(|e| FsError::new(FsErrorKind::Io, e))?;
+            offset += sent;
+        }
+        if done {
+            break;
+        }
+    }
+
+    log::info!("Streamed {} events from thread {}", offset, thread_id);
+    Ok(offset)
+}
+
+/// Parse one JSONL line, skipping blank and malformed ones
+pub fn parse_line(line: &[u8]) -> Option<serde_json::Value> {
+    if line.trim_ascii().is_empty() {
+        return None;
+    }
+    match serde_json::from_slice::<serde_json::Value>(line) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            log::warn!("Failed to parse event line: {}", e);
+            // Continue reading - don't fail on single bad line
+            None
+        }
+    }
+}
+
+/// Whether `parse_line` gives an event for a line, without building the event
+pub fn is_event_line(line: &[u8]) -> bool {
+    !line.trim_ascii().is_empty() && serde_json::from_slice::<serde::de::IgnoredAny>(line).is_ok()
+}
+
+/// Byte ranges of a thread's event lines, numbered as `load_thread` numbers its
+/// events: blank and malformed lines are skipped and get no index. Ranges don't
+/// include the newline.
+pub fn event_line_ranges(content: &[u8]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for line in content.split(|&b| b == b'\n') {
+        let end = start + line.len();
+        if is_event_line(line) {
+            ranges.push(start..end);
+        }
+        start = end + 1;
+    }
+    ranges
+}
+
+/// Append events to a thread's JSONL file, skipping any whose `event_id` was
+/// appended recently
 pub async fn append_thread_events(
     thread_id: String,
     events: Vec<serde_json::Value>,
-) -> Result<(), String> {
-    let threads_dir = get_threads_dir()?;
-    let file_path = threads_dir.join(format!("{}.jsonl", thread_id));
+) -> Result<AppendOutcome, FsError> {
+    let _write = track_write(&thread_id);
+    let (events, outcome) = dedupe_events(&thread_id, events).await?;
+    if events.is_empty() {
+        return Ok(outcome);
+    }
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&file_path)
-        .await
-        .map_err(|e| format!("Failed to open thread file for append: {}", e))?;
+    let ids: Vec<String> = events.iter().filter_map(event_id).map(str::to_string).collect();
+    let written = async {
+        let events = crate::event_schema::validate(&thread_id, events).map_err(FsError::invalid)?;
+        let mut data = Vec::new();
+        for event in &events {
+            serialize_bounded_event_line(event, &mut data).await?;
+        }
+        append_thread_lines(&thread_id, &data, events.len()).await
+    };
+    if let Err(e) = written.await {
+        forget_event_ids(&thread_id, &ids);
+        return Err(e);
+    }
+    Ok(outcome)
+}
 
-    let event_count = events.len();
+/// `event_id`s remembered per thread, so a retried append isn't written twice
+const RECENT_EVENT_IDS: usize = 256;
 
-    for event in &events {
-        let line = serde_json::to_string(event)
-            .map_err(|e| format!("Failed to serialize event: {}", e))?;
+/// Bytes read from the end of a thread file for the ids of its latest events, the
+/// first time it's appended to
+const RECENT_EVENT_IDS_SEED_BYTES: u64 = 64 * 1024;
 
-        file.write_all(line.as_bytes())
-            .await
-            .map_err(|e| format!("Failed to write event: {}", e))?;
-        file.write_all(b"\n")
-            .await
-            .map_err(|e| format!("Failed to write newline: {}", e))?;
+/// The most recently appended `event_id`s of one thread
+#[derive(Default)]
+struct RecentEventIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl RecentEventIds {
+    fn insert(&mut self, id: &str) {
+        if !self.ids.insert(id.to_string()) {
+            return;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > RECENT_EVENT_IDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        if self.ids.remove(id) {
+            self.order.retain(|recent| recent != id);
+        }
+    }
+}
+
+static RECENT_EVENTS: LazyLock<Mutex<HashMap<String, RecentEventIds>>> = LazyLock::new(Default::default);
+
+/// What an append wrote
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AppendOutcome {
+    /// Indexes, in the events given, of those written
+    pub written: Vec<usize>,
+    /// `event_id`s of events skipped because they were already appended
+    pub duplicates: Vec<String>,
+}
+
+/// An event's idempotency key, set by the frontend so retries can be recognised
+pub fn event_id(event: &serde_json::Value) -> Option<&str> {
+    event.get("event_id").and_then(|id| id.as_str()).filter(|id| !id.is_empty())
+}
+
+/// `event_id`s in the last `RECENT_EVENT_IDS_SEED_BYTES` of a thread file, oldest
+/// first, so retries across a restart are caught too (blocking)
+fn seed_event_ids(thread_id: &str) -> Vec<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let Some(mut file) = get_thread_path(thread_id).ok().and_then(|path| fs::File::open(path).ok()) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(RECENT_EVENT_IDS_SEED_BYTES);
+    let mut tail = Vec::new();
+    if file.seek(SeekFrom::Start(start)).and_then(|_| file.read_to_end(&mut tail)).is_err() {
+        return Vec::new();
+    }
+    // The first line is likely cut off unless the whole file was read
+    let lines = tail.split(|&b| b == b'\n').skip(usize::from(start > 0));
+    lines
+        .filter(|line| memchr::memmem::find(line, b"\"event_id\"").is_some())
+        .filter_map(parse_line)
+        .filter_map(|event| event_id(&event).map(str::to_string))
+        .collect()
+}
+
+/// Split off events whose `event_id` was appended recently, or appears earlier in
+/// the same batch, and remember the ids of the rest. Events without an id are
+/// always kept.
+pub async fn dedupe_events(
+    thread_id: &str,
+    events: Vec<serde_json::Value>,
+) -> Result<(Vec<serde_json::Value>, AppendOutcome), FsError> {
+    let mut outcome = AppendOutcome::default();
+    if !events.iter().any(|event| event_id(event).is_some()) {
+        outcome.written = (0..events.len()).collect();
+        return Ok((events, outcome));
+    }
+
+    if !RECENT_EVENTS.lock().unwrap().contains_key(thread_id) {
+        let id = thread_id.to_string();
+        let seed = blocking_fs(move || Ok(seed_event_ids(&id))).await?;
+        let mut recent = RECENT_EVENTS.lock().unwrap();
+        let ids = recent.entry(thread_id.to_string()).or_default();
+        for id in seed {
+            ids.insert(&id);
+        }
+    }
+
+    let mut recent = RECENT_EVENTS.lock().unwrap();
+    let ids = recent.entry(thread_id.to_string()).or_default();
+    let mut kept = Vec::with_capacity(events.len());
+    for (index, event) in events.into_iter().enumerate() {
+        if let Some(id) = event_id(&event) {
+            if ids.ids.contains(id) {
+                outcome.duplicates.push(id.to_string());
+                continue;
+            }
+            ids.insert(id);
+        }
+        outcome.written.push(index);
+        kept.push(event);
+    }
+    if !outcome.duplicates.is_empty() {
+        log::info!(
+            "Skipped {} duplicate events appended to thread {}",
+            outcome.duplicates.len(),
+            thread_id
+        );
+    }
+    Ok((kept, outcome))
+}
+
+/// Forget the `event_id`s of events that failed to be written, so a retry goes through
+pub fn forget_event_ids(thread_id: &str, event_ids: &[String]) {
+    if let Some(ids) = RECENT_EVENTS.lock().unwrap().get_mut(thread_id) {
+        for id in event_ids {
+            ids.remove(id);
+        }
+    }
+}
+
+/// Forget a thread's recent `event_id`s, e.g. when it's deleted
+pub fn clear_event_ids(thread_id: &str) {
+    RECENT_EVENTS.lock().unwrap().remove(thread_id);
+}
+
+/// Serialize an event as one JSONL line onto `out`, spilling oversized payloads into
+/// attachment blobs so no line exceeds `blob_store::max_event_bytes`
+pub async fn serialize_bounded_event_line(event: &serde_json::Value, out: &mut Vec<u8>) -> Result<(), FsError> {
+    let start = out.len();
+    serialize_event_line(event, out)?;
+    if out.len() - start <= crate::blob_store::max_event_bytes() {
+        return Ok(());
     }
 
+    out.truncate(start);
+    let event = event.clone();
+    let bounded = blocking_fs(move || crate::blob_store::spill(event)).await?;
+    serialize_event_line(&bounded, out)
+}
+
+/// Serialize an event as one JSONL line onto `out`
+pub fn serialize_event_line(event: &serde_json::Value, out: &mut Vec<u8>) -> Result<(), FsError> {
+    serde_json::to_writer(&mut *out, event)
+        .map_err(|e| FsError::invalid(format!("Failed to serialize event: {}", e)))?;
+    out.push(b'\n');
+    Ok(())
+}
+
+/// Replace a thread file's contents atomically: write a sibling file, sync it and
+/// rename it over the original, so a crash leaves one complete version (blocking)
+pub fn replace_thread_file(path: &std::path::Path, data: &[u8]) -> Result<(), FsError> {
+    use std::io::Write;
+
+    let temp = path.with_extension("jsonl.rewrite");
+    let mut file = fs::File::create(&temp).map_err(|e| FsError::io("create replacement thread file", e))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| FsError::io("write replacement thread file", e))?;
+    fs::rename(&temp, path).map_err(|e| FsError::io("replace thread file", e))
+}
+
+/// A thread file kept open between appends, for writers that append often
+#[derive(Default)]
+pub struct OpenThreadFile {
+    file: Option<tokio::fs::File>,
+}
+
+impl OpenThreadFile {
+    pub fn is_open(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn close(&mut self) {
+        self.file = None;
+    }
+}
+
+/// Whether an open handle is still the file at `path`, rather than one a rewrite
+/// has since renamed over it
+async fn is_same_file(file: &tokio::fs::File, path: &std::path::Path) -> bool {
+    let (Ok(open), Ok(on_disk)) = (file.metadata().await, tokio::fs::metadata(path).await) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        open.dev() == on_disk.dev() && open.ino() == on_disk.ino()
+    }
+    #[cfg(not(unix))]
+    {
+        open.len() == on_disk.len() && open.modified().ok() == on_disk.modified().ok()
+    }
+}
+
+/// Append already-serialized JSONL lines to a thread's file in one write
+pub async fn append_thread_lines(thread_id: &str, data: &[u8], event_count: usize) -> Result<(), FsError> {
+    append_thread_lines_open(thread_id, &mut OpenThreadFile::default(), data, event_count).await
+}
+
+/// `append_thread_lines` through `open`, which is opened on first use and kept
+/// open for the next call unless the write fails
+pub async fn append_thread_lines_open(
+    thread_id: &str,
+    open: &mut OpenThreadFile,
+    data: &[u8],
+    event_count: usize,
+) -> Result<(), FsError> {
+    let _write = track_write(thread_id);
+    let started = std::time::Instant::now();
+    let file_path = get_thread_path(thread_id)?;
+    crate::thread_compression::ensure_decompressed(thread_id).await?;
+    let _lock = lock_thread(thread_id).await?;
+
+    let mut file = match open.file.take() {
+        Some(file) if is_same_file(&file, &file_path).await => file,
+        _ => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .await
+            .map_err(|e| FsError::io("open thread file for append", e))?,
+    };
+
+    file.write_all(data)
+        .await
+        .map_err(|e| FsError::io("write events", e))?;
     file.flush()
         .await
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
+        .map_err(|e| FsError::io("flush file", e))?;
+    open.file = Some(file);
 
+    crate::metrics::record_append(event_count, data.len());
+    crate::metrics::record_filesystem("append", started.elapsed());
     log::info!("Appended {} events to thread {}", event_count, thread_id);
 
     Ok(())
 }
 
 /// List all threads with metadata
-pub async fn list_threads() -> Result<Vec<ThreadMetadata>, String> {
+pub async fn list_threads() -> Result<Vec<ThreadMetadata>, FsError> {
+    let started = std::time::Instant::now();
+    let files = blocking_fs(|| {
+        list_listed_thread_files()?
+            .into_iter()
+            .map(|path| {
+                // Get file metadata for timestamps
+                let metadata = fs::metadata(&path)
+                    .map_err(|e| FsError::io("get file metadata", e))?;
+                Ok((path, metadata))
+            })
+            .collect::<Result<Vec<_>, FsError>>()
+    })
+    .await?;
+    crate::thread_summary::retain(&files.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>());
+
+    let mut threads: Vec<ThreadMetadata> = stream::iter(files)
+        .map(|(path, metadata)| async move { read_thread_metadata(&path, &metadata).await })
+        .buffer_unordered(SCAN_CONCURRENCY)
+        .collect()
+        .await;
+
+    let ids = blocking_fs(|| Ok(blueprint_ids_by_hash())).await?;
+    resolve_blueprint_ids(&mut threads, &ids);
+
+    // Sort by updated_at (most recent first)
+    threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    crate::metrics::record_filesystem("list_threads", started.elapsed());
+    Ok(threads)
+}
+
+/// Paths of every thread to list: the JSONL files and compressed threads (blocking)
+pub fn list_listed_thread_files() -> Result<Vec<PathBuf>, FsError> {
+    let mut files = list_thread_files()?;
+    files.extend(crate::thread_compression::list_compressed()?);
+    Ok(files)
+}
+
+/// Paths of all thread JSONL files (blocking)
+pub fn list_thread_files() -> Result<Vec<PathBuf>, FsError> {
     let threads_dir = get_threads_dir()?;
 
     if !threads_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut threads = Vec::new();
+    let mut files = Vec::new();
 
     let entries = fs::read_dir(&threads_dir)
-        .map_err(|e| format!("Failed to read threads directory: {}", e))?;
+        .map_err(|e| FsError::io("read threads directory", e))?;
 
     for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let entry = entry.map_err(|e| FsError::io("read directory entry", e))?;
         let path = entry.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-            let thread_id = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            // Get file metadata for timestamps
-            let metadata = fs::metadata(&path)
-                .map_err(|e| format!("Failed to get file metadata: {}", e))?;
-
-            let created_at = metadata.created()
-                .ok()
-                .and_then(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339().parse().ok())
-                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-
-            let updated_at = metadata.modified()
-                .ok()
-                .and_then(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339().parse().ok())
-                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-
-            // Extract title from first user message (if available)
-            let title = extract_thread_title(&path).await;
-
-            threads.push(ThreadMetadata {
-                thread_id,
-                title,
-                created_at,
-                updated_at,
-                file_path: path.to_string_lossy().to_string(),
-            });
+            files.push(path);
         }
     }
 
-    // Sort by updated_at (most recent first)
-    threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(files)
+}
 
-    Ok(threads)
+/// Build a thread's listing metadata from its file and stat
+pub async fn read_thread_metadata(path: &std::path::Path, metadata: &fs::Metadata) -> ThreadMetadata {
+    let thread_id = crate::thread_compression::thread_file_id(path)
+        .unwrap_or("unknown")
+        .to_string();
+
+    let created_at = metadata.created()
+        .ok()
+        .and_then(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339().parse().ok())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let updated_at = metadata.modified()
+        .ok()
+        .and_then(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339().parse().ok())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let summary = summarize_thread(path).await.unwrap_or_default();
+    let stored_title = read_thread_meta(&thread_id).await.and_then(|record| record.title);
+
+    ThreadMetadata {
+        title: stored_title.or(summary.explicit_title).or(summary.first_message_title),
+        thread_id,
+        created_at,
+        updated_at,
+        file_path: path.to_string_lossy().to_string(),
+        event_count: summary.event_count,
+        preview: summary.preview,
+        last_event_at: summary.last_event_at,
+        blueprint_id: None,
+        blueprint_hash: summary.blueprint_hash,
+        archived: summary.archived,
+        tags: summary.tags,
+        parent_thread_id: summary.parent_thread_id,
+        draft: None,
+        display: None,
+    }
+}
+
+/// Hash identifying a blueprint definition (the `blueprint` object of a blueprint
+/// file or thread header)
+pub fn blueprint_hash(definition: &serde_json::Value) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(definition.to_string().as_bytes()))
+}
+
+/// Blueprint file ids by the hash of their definition (blocking)
+pub fn blueprint_ids_by_hash() -> std::collections::HashMap<String, String> {
+    list_blueprint_files()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|path| {
+            let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            let id = path.file_stem()?.to_str()?.to_string();
+            Some((blueprint_hash(json.get("blueprint")?), id))
+        })
+        .collect()
+}
+
+/// Fill in `blueprint_id` from the blueprint files' hashes
+pub fn resolve_blueprint_ids(threads: &mut [ThreadMetadata], ids: &std::collections::HashMap<String, String>) {
+    for thread in threads {
+        thread.blueprint_id = thread.blueprint_hash.as_ref().and_then(|hash| ids.get(hash)).cloned();
+    }
 }
 
 /// Read a blueprint file and return its JSON content
-pub async fn read_blueprint(file_path: String) -> Result<String, String> {
-    let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read blueprint file: {}", e))?;
+pub async fn read_blueprint(file_path: String) -> Result<String, FsError> {
+    if crate::audit::outside_data_dir(std::path::Path::new(&file_path)) {
+        crate::audit::record_async(crate::audit::AuditEntry::new("fs", "read_blueprint", "ui", file_path.as_str(), true))
+            .await;
+    }
+
+    let content = tokio::fs::read_to_string(&file_path)
+        .await
+        .map_err(|e| FsError::io("read blueprint file", e))?;
     Ok(content)
 }
 
-/// Update the title of a thread by appending a data-thread-title event
-pub async fn update_thread_title(thread_id: String, title: String) -> Result<(), String> {
-    let threads_dir = get_threads_dir()?;
-    let file_path = threads_dir.join(format!("{}.jsonl", thread_id));
+/// Get the path for a blueprint id, rejecting ids that could escape the blueprints directory
+pub(crate) fn get_blueprint_path(blueprint_id: &str) -> Result<PathBuf, FsError> {
+    if blueprint_id.is_empty() || blueprint_id.contains(['/', '\\']) || blueprint_id.contains("..") {
+        return Err(FsError::invalid(format!("Invalid blueprint id: {}", blueprint_id)));
+    }
+    Ok(get_blueprints_dir()?.join(format!("{}.json", blueprint_id)))
+}
+
+/// Lowercase, hyphenated file id for a blueprint name
+fn blueprint_slug(name: &str) -> String {
+    let slug = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "blueprint".to_string()
+    } else {
+        slug
+    }
+}
 
-    if !file_path.exists() {
-        return Err(format!("Thread {} not found", thread_id));
+/// Create a new blueprint file named after `name`, adding `-2`, `-3`, ... on
+/// collision. The file is created exclusively, so concurrent saves can't clobber
+/// each other. Returns the new path.
+pub(crate) async fn create_blueprint_file(name: &str, content: &str) -> Result<PathBuf, FsError> {
+    let blueprints_dir = get_blueprints_dir()?;
+    tokio::fs::create_dir_all(&blueprints_dir)
+        .await
+        .map_err(|e| FsError::io("create blueprints directory", e))?;
+
+    let slug = blueprint_slug(name);
+    for n in 1.. {
+        let id = if n == 1 { slug.clone() } else { format!("{}-{}", slug, n) };
+        let path = blueprints_dir.join(format!("{}.json", id));
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(FsError::io("create blueprint file", e)),
+        };
+        file.write_all(content.as_bytes())
+            .await
+            .map_err(|e| FsError::io("write blueprint file", e))?;
+        file.flush().await.map_err(|e| FsError::io("flush blueprint file", e))?;
+        return Ok(path);
+    }
+    unreachable!()
+}
+
+/// Parse and validate blueprint JSON, returning it pretty-printed
+fn prepare_blueprint(blueprint_json: &str) -> Result<(serde_json::Value, String), FsError> {
+    let blueprint: serde_json::Value = serde_json::from_str(blueprint_json)
+        .map_err(|e| FsError::invalid(format!("Failed to parse blueprint JSON: {}", e)))?;
+    crate::blueprint_schema::validate(&blueprint).map_err(FsError::invalid)?;
+    let content = serde_json::to_string_pretty(&blueprint)
+        .map_err(|e| FsError::invalid(format!("Failed to serialize blueprint: {}", e)))?;
+    Ok((blueprint, content))
+}
+
+/// Name of a blueprint's first agent, used for new file ids
+fn first_agent_name(blueprint: &serde_json::Value) -> &str {
+    blueprint
+        .pointer("/blueprint/space/agents/0/name")
+        .and_then(|n| n.as_str())
+        .unwrap_or("blueprint")
+}
+
+pub(crate) fn saved_blueprint_metadata(path: PathBuf) -> Result<BlueprintMetadata, FsError> {
+    read_blueprint_metadata(&path).ok_or_else(|| FsError::new(FsErrorKind::Io, format!("Failed to read saved blueprint {}", path.display())))
+}
+
+/// Validate and write a blueprint. With an id the existing file is replaced;
+/// without one a new file is created, named after the first agent.
+pub async fn save_blueprint(blueprint_id: Option<String>, blueprint_json: String) -> Result<BlueprintMetadata, FsError> {
+    let (blueprint, content) = prepare_blueprint(&blueprint_json)?;
+
+    let path = match blueprint_id {
+        Some(id) => {
+            let path = get_blueprint_path(&id)?;
+            if !path_exists(&path).await {
+                return Err(FsError::not_found(format!("Blueprint {} not found", id)));
+            }
+            // Write then rename, so a crash mid-save leaves the old file intact
+            let temp = path.with_extension("json.tmp");
+            tokio::fs::write(&temp, content)
+                .await
+                .map_err(|e| FsError::io("write blueprint file", e))?;
+            tokio::fs::rename(&temp, &path)
+                .await
+                .map_err(|e| FsError::io("replace blueprint file", e))?;
+            log::info!("Saved blueprint {}", id);
+            path
+        }
+        None => {
+            let path = create_blueprint_file(first_agent_name(&blueprint), &content).await?;
+            log::info!("Created blueprint {}", path.display());
+            path
+        }
+    };
+
+    let saved = blocking_fs(move || saved_blueprint_metadata(path)).await?;
+    crate::blueprint_history::record_async(saved.id.clone(), format!("Save {}", saved.id)).await;
+    Ok(saved)
+}
+
+/// Copy a blueprint to a new file (`<id>-copy`, `<id>-copy-2`, ...)
+pub async fn duplicate_blueprint(blueprint_id: String) -> Result<BlueprintMetadata, FsError> {
+    let source = get_blueprint_path(&blueprint_id)?;
+    let content = tokio::fs::read_to_string(&source)
+        .await
+        .map_err(|e| FsError::io(&format!("read blueprint {}", blueprint_id), e))?;
+    let (_, content) = prepare_blueprint(&content)?;
+
+    let path = create_blueprint_file(&format!("{}-copy", blueprint_id), &content).await?;
+    log::info!("Duplicated blueprint {} to {}", blueprint_id, path.display());
+
+    let saved = blocking_fs(move || saved_blueprint_metadata(path)).await?;
+    crate::blueprint_history::record_async(saved.id.clone(), format!("Duplicate {} as {}", blueprint_id, saved.id)).await;
+    Ok(saved)
+}
+
+/// Give a blueprint a new file id derived from `new_name`. Threads keep working:
+/// they carry their own copy of the blueprint.
+pub async fn rename_blueprint(blueprint_id: String, new_name: String) -> Result<BlueprintMetadata, FsError> {
+    let source = get_blueprint_path(&blueprint_id)?;
+    if !path_exists(&source).await {
+        return Err(FsError::not_found(format!("Blueprint {} not found", blueprint_id)));
+    }
+    if blueprint_slug(&new_name) == blueprint_id {
+        return blocking_fs(move || saved_blueprint_metadata(source)).await;
+    }
+
+    let content = tokio::fs::read_to_string(&source)
+        .await
+        .map_err(|e| FsError::io(&format!("read blueprint {}", blueprint_id), e))?;
+    // Claim the new name before removing the old file, so a failure loses nothing
+    let path = create_blueprint_file(&new_name, &content).await?;
+    if let Err(e) = tokio::fs::remove_file(&source).await {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(FsError::io("remove old blueprint file", e));
     }
+    log::info!("Renamed blueprint {} to {}", blueprint_id, path.display());
+
+    let saved = blocking_fs(move || saved_blueprint_metadata(path)).await?;
+    let message = format!("Rename {} to {}", blueprint_id, saved.id);
+    crate::blueprint_history::record_async(blueprint_id, message.clone()).await;
+    crate::blueprint_history::record_async(saved.id.clone(), message).await;
+    Ok(saved)
+}
+
+/// Delete a blueprint file
+pub async fn delete_blueprint(blueprint_id: String) -> Result<(), FsError> {
+    let path = get_blueprint_path(&blueprint_id)?;
+    if !path_exists(&path).await {
+        return Err(FsError::not_found(format!("Blueprint {} not found", blueprint_id)));
+    }
+    tokio::fs::remove_file(&path)
+        .await
+        .map_err(|e| FsError::io(&format!("delete blueprint {}", blueprint_id), e))?;
+    log::info!("Deleted blueprint {}", blueprint_id);
+    crate::blueprint_history::record_async(blueprint_id.clone(), format!("Delete {}", blueprint_id)).await;
+    Ok(())
+}
+
+/// Rename a thread. The title is stored in the thread's metadata record, which
+/// listings read first, and appended as a data-thread-title event so copies of the
+/// JSONL (exports, backups, share bundles) carry it too.
+pub async fn update_thread_title(thread_id: String, title: String) -> Result<(), FsError> {
+    let file_path = existing_thread_path(&thread_id).await?;
+    let _lock = lock_thread(&thread_id).await?;
 
     // Create the title event
     let title_event = serde_json::json!({
@@ -336,64 +1590,421 @@ pub async fn update_thread_title(thread_id: String, title: String) -> Result<(),
         .append(true)
         .open(&file_path)
         .await
-        .map_err(|e| format!("Failed to open thread file for title update: {}", e))?;
+        .map_err(|e| FsError::io("open thread file for title update", e))?;
 
     let line = serde_json::to_string(&title_event)
-        .map_err(|e| format!("Failed to serialize title event: {}", e))?;
+        .map_err(|e| FsError::invalid(format!("Failed to serialize title event: {}", e)))?;
 
     file.write_all(line.as_bytes())
         .await
-        .map_err(|e| format!("Failed to write title event: {}", e))?;
+        .map_err(|e| FsError::io("write title event", e))?;
     file.write_all(b"\n")
         .await
-        .map_err(|e| format!("Failed to write newline: {}", e))?;
+        .map_err(|e| FsError::io("write newline", e))?;
     file.flush()
         .await
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
+        .map_err(|e| FsError::io("flush file", e))?;
+
+    let mut record = read_thread_meta(&thread_id).await.unwrap_or_default();
+    record.title = Some(title.clone());
+    record.updated_at = title_event.get("timestamp").and_then(|t| t.as_str()).map(str::to_string);
+    write_thread_meta(&thread_id, &record).await?;
 
     log::info!("Updated title for thread {} to: {}", thread_id, title);
 
     Ok(())
 }
 
-/// Extract title from thread - checks for data-thread-title event first, falls back to first user message
-async fn extract_thread_title(path: &PathBuf) -> Option<String> {
-    let file = tokio::fs::File::open(path).await.ok()?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
+/// Start a new thread from the blueprint line and events `1..=up_to_event_index`
+/// of another, as indexed by `load_thread`. The copy gets a fresh id, and its
+/// header records where it came from under `forked_from`. Callers should flush
+/// buffered appends to the source first (blocking).
+pub fn fork_thread(thread_id: &str, up_to_event_index: usize) -> Result<String, FsError> {
+    let source = get_thread_path(thread_id)?;
+    crate::thread_compression::decompress(thread_id)?;
+    // Hold the source still while it's read, so the copy doesn't end in a half-written line
+    let content = {
+        let _lock = lock_thread_blocking(thread_id)?;
+        fs::read(&source).map_err(|_| FsError::not_found(format!("Thread {} not found", thread_id)))?
+    };
+
+    let lines = event_line_ranges(&content);
+    if up_to_event_index >= lines.len() {
+        return Err(FsError::invalid(format!("Thread {} has no event {}", thread_id, up_to_event_index)));
+    }
+
+    let mut header: serde_json::Value = serde_json::from_slice(&content[lines[0].clone()])
+        .map_err(|e| FsError::invalid(format!("Failed to parse thread header: {}", e)))?;
+    let Some(obj) = header.as_object_mut() else {
+        return Err(FsError::invalid("Thread header is not an object"));
+    };
+    let fork_id = uuid::Uuid::new_v4().to_string();
+    obj.insert("thread_id".to_string(), serde_json::Value::String(fork_id.clone()));
+    obj.insert(
+        "forked_from".to_string(),
+        serde_json::json!({
+            "thread_id": thread_id,
+            "event_index": up_to_event_index,
+            "forked_at": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+
+    // Events are copied byte for byte; blob references stay valid since the
+    // blob store is shared
+    let mut output = Vec::with_capacity(content.len());
+    serialize_event_line(&header, &mut output)?;
+    for line in &lines[1..=up_to_event_index] {
+        output.extend_from_slice(&content[line.clone()]);
+        output.push(b'\n');
+    }
+    replace_thread_file(&get_thread_path(&fork_id)?, &output)?;
 
-    let mut explicit_title: Option<String> = None;
-    let mut user_message_title: Option<String> = None;
+    log::info!("Forked thread {} at event {} as {}", thread_id, up_to_event_index, fork_id);
+    Ok(fork_id)
+}
 
-    while let Some(line) = lines.next_line().await.ok()? {
-        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
-            let event_type = event.get("type").and_then(|t| t.as_str());
+/// Copy a whole thread under a new id, with its attachments and stored title.
+/// The header's `thread_id` becomes the new id, with `duplicated_from` recording
+/// the original. Returns the new id.
+pub async fn duplicate_thread(thread_id: String) -> Result<String, FsError> {
+    existing_thread_path(&thread_id).await?;
+    let copy_id = uuid::Uuid::new_v4().to_string();
+
+    let (id, copy) = (thread_id.clone(), copy_id.clone());
+    blocking_fs(move || {
+        // Hold the source still while it's read, so the copy doesn't end in a half-written line
+        let content = {
+            let _lock = lock_thread_blocking(&id)?;
+            fs::read(get_thread_path(&id)?).map_err(|_| FsError::not_found(format!("Thread {} not found", id)))?
+        };
+        let (first, rest) = match content.iter().position(|&b| b == b'\n') {
+            Some(end) => (&content[..end], &content[end + 1..]),
+            None => (&content[..], &[][..]),
+        };
+
+        let mut header: serde_json::Value =
+            serde_json::from_slice(first).map_err(|e| FsError::invalid(format!("Failed to parse thread header: {}", e)))?;
+        let Some(obj) = header.as_object_mut() else {
+            return Err(FsError::invalid("Thread header is not an object"));
+        };
+        obj.insert("thread_id".to_string(), serde_json::Value::String(copy.clone()));
+        obj.insert(
+            "duplicated_from".to_string(),
+            serde_json::json!({
+                "thread_id": id,
+                "duplicated_at": chrono::Utc::now().to_rfc3339(),
+            }),
+        );
+
+        // Attachments first, so the copy never refers to one it doesn't have
+        let attachments = crate::attachments::copy_all(&id, &copy)?;
+        let mut output = Vec::with_capacity(content.len());
+        serialize_event_line(&header, &mut output)?;
+        output.extend_from_slice(rest);
+        replace_thread_file(&get_thread_path(&copy)?, &output)?;
+        log::info!("Duplicated thread {} as {} with {} attachments", id, copy, attachments);
+        Ok(())
+    })
+    .await?;
+
+    if let Some(record) = read_thread_meta(&thread_id).await {
+        write_thread_meta(&copy_id, &record).await?;
+    }
+    Ok(copy_id)
+}
 
-            // Check for explicit title event (takes precedence)
-            if event_type == Some("data-thread-title") {
-                if let Some(title) = event.get("data")
-                    .and_then(|d| d.get("title"))
-                    .and_then(|t| t.as_str()) {
-                    explicit_title = Some(title.to_string());
-                    // Keep reading to find the latest title event
-                }
-            }
+/// Event type recording a blueprint change partway through a thread
+pub const BLUEPRINT_UPDATE_EVENT: &str = "data-blueprint-update";
 
-            // Also capture first user message as fallback (only if we don't have one yet)
-            if user_message_title.is_none() && event_type == Some("user-message") {
-                if let Some(content) = event.get("content").and_then(|c| c.as_str()) {
-                    // Truncate to first 50 chars for title
-                    let title = if content.len() > 50 {
-                        format!("{}...", &content[..50])
-                    } else {
-                        content.to_string()
-                    };
-                    user_message_title = Some(title);
-                }
+/// Change a thread's blueprint by appending a data-blueprint-update event. The
+/// header line is left as is, so the history shows when the configuration changed.
+pub async fn update_thread_blueprint(thread_id: String, blueprint_json: String) -> Result<(), FsError> {
+    existing_thread_path(&thread_id).await?;
+
+    let mut blueprint: serde_json::Value = serde_json::from_str(&blueprint_json)
+        .map_err(|e| FsError::invalid(format!("Failed to parse blueprint JSON: {}", e)))?;
+    let Some(obj) = blueprint.as_object_mut() else {
+        return Err(FsError::invalid("Blueprint JSON is not an object"));
+    };
+    obj.insert("thread_id".to_string(), serde_json::Value::String(thread_id.clone()));
+
+    let update_event = serde_json::json!({
+        "type": BLUEPRINT_UPDATE_EVENT,
+        "data": {
+            "blueprint": blueprint
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+
+    append_thread_events(thread_id.clone(), vec![update_event]).await?;
+
+    log::info!("Updated blueprint for thread {}", thread_id);
+
+    Ok(())
+}
+
+/// Event type recording a thread being archived or unarchived
+pub const ARCHIVED_EVENT: &str = "data-thread-archived";
+
+/// Event type recording a thread's tags, replacing any earlier ones
+pub const TAGS_EVENT: &str = "data-thread-tags";
+
+/// The `data` of the last event of `event_type`
+fn latest_data<'a>(events: &'a [serde_json::Value], event_type: &str) -> Option<&'a serde_json::Value> {
+    events
+        .iter()
+        .rev()
+        .find(|event| event.get("type").and_then(|t| t.as_str()) == Some(event_type))
+        .and_then(|event| event.get("data"))
+}
+
+/// Whether the thread's latest archive event archived it
+pub fn thread_archived(events: &[serde_json::Value]) -> bool {
+    latest_data(events, ARCHIVED_EVENT)
+        .and_then(|data| data.get("archived"))
+        .and_then(|archived| archived.as_bool())
+        .unwrap_or(false)
+}
+
+/// The thread's current tags
+pub fn thread_tags(events: &[serde_json::Value]) -> Vec<String> {
+    latest_data(events, TAGS_EVENT)
+        .and_then(|data| data.get("tags"))
+        .and_then(|tags| serde_json::from_value(tags.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Archive or unarchive a thread by appending a data-thread-archived event
+pub async fn set_thread_archived(thread_id: String, archived: bool) -> Result<(), FsError> {
+    existing_thread_path(&thread_id).await?;
+
+    let event = serde_json::json!({
+        "type": ARCHIVED_EVENT,
+        "data": {
+            "archived": archived
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    append_thread_events(thread_id.clone(), vec![event]).await?;
+
+    log::info!("{} thread {}", if archived { "Archived" } else { "Unarchived" }, thread_id);
+    Ok(())
+}
+
+/// Replace a thread's tags by appending a data-thread-tags event. Tags are trimmed,
+/// and empty and duplicate ones dropped.
+pub async fn set_thread_tags(thread_id: String, tags: Vec<String>) -> Result<Vec<String>, FsError> {
+    existing_thread_path(&thread_id).await?;
+
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !cleaned.contains(&tag) {
+            cleaned.push(tag);
+        }
+    }
+
+    let event = serde_json::json!({
+        "type": TAGS_EVENT,
+        "data": {
+            "tags": cleaned
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    append_thread_events(thread_id.clone(), vec![event]).await?;
+
+    log::info!("Tagged thread {} with {:?}", thread_id, cleaned);
+    Ok(cleaned)
+}
+
+/// The thread protocol to send the backend for the next turn: the latest
+/// blueprint-update replaces the header, and the update events themselves are dropped
+pub fn effective_thread_protocol(mut events: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+    let is_update = |event: &serde_json::Value| event.get("type").and_then(|t| t.as_str()) == Some(BLUEPRINT_UPDATE_EVENT);
+
+    let latest = events
+        .iter()
+        .rev()
+        .filter(|event| is_update(event))
+        .find_map(|event| event.get("data").and_then(|d| d.get("blueprint")).cloned());
+    let Some(latest) = latest else { return events };
+
+    events.retain(|event| !is_update(event));
+    if let Some(header) = events.first_mut() {
+        *header = latest;
+    }
+    events
+}
+
+/// Days a deleted thread stays in the trash by default (`CHIMERA_TRASH_RETENTION_DAYS`)
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Tombstone written beside a deleted thread in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedThread {
+    pub thread_id: String,
+    pub title: Option<String>,
+    pub deleted_at: String,
+    pub original_path: String,
+}
+
+fn trash_paths(thread_id: &str) -> Result<(PathBuf, PathBuf), FsError> {
+    // Validates the id the same way as live threads
+    get_thread_path(thread_id)?;
+    let trash_dir = get_trash_dir()?;
+    Ok((
+        trash_dir.join(format!("{}.jsonl", thread_id)),
+        trash_dir.join(format!("{}.tombstone.json", thread_id)),
+    ))
+}
+
+/// Move a thread into the trash, recording a tombstone so it can be restored.
+/// `actor` is who asked, for the audit log.
+pub async fn delete_thread(thread_id: String, actor: &str) -> Result<TrashedThread, FsError> {
+    let file_path = existing_thread_path(&thread_id).await?;
+
+    let (trashed_path, tombstone_path) = trash_paths(&thread_id)?;
+    tokio::fs::create_dir_all(get_trash_dir()?)
+        .await
+        .map_err(|e| FsError::io("create trash directory", e))?;
+
+    let tombstone = TrashedThread {
+        thread_id: thread_id.clone(),
+        title: match read_thread_meta(&thread_id).await.and_then(|record| record.title) {
+            Some(title) => Some(title),
+            None => summarize_thread(&file_path)
+                .await
+                .and_then(|summary| summary.explicit_title.or(summary.first_message_title)),
+        },
+        deleted_at: chrono::Utc::now().to_rfc3339(),
+        original_path: file_path.to_string_lossy().to_string(),
+    };
+    let content = serde_json::to_string_pretty(&tombstone)
+        .map_err(|e| FsError::invalid(format!("Failed to serialize tombstone: {}", e)))?;
+    tokio::fs::write(&tombstone_path, content)
+        .await
+        .map_err(|e| FsError::io("write tombstone", e))?;
+
+    if let Err(e) = tokio::fs::rename(&file_path, &trashed_path).await {
+        let _ = tokio::fs::remove_file(&tombstone_path).await;
+        return Err(FsError::io("move thread to trash", e));
+    }
+
+    clear_event_ids(&thread_id);
+    log::info!("Moved thread {} to trash", thread_id);
+    crate::audit::record_async(
+        crate::audit::AuditEntry::new("threads", "delete", actor, thread_id.as_str(), true)
+            .paths([&file_path, &trashed_path]),
+    )
+    .await;
+
+    Ok(tombstone)
+}
+
+/// Move a thread out of the trash back into the threads directory
+pub async fn restore_thread(thread_id: String) -> Result<(), FsError> {
+    let file_path = get_thread_path(&thread_id)?;
+    let (trashed_path, tombstone_path) = trash_paths(&thread_id)?;
+
+    if !path_exists(&trashed_path).await {
+        return Err(FsError::not_found(format!("Thread {} is not in the trash", thread_id)));
+    }
+    if path_exists(&file_path).await {
+        return Err(FsError::invalid(format!("Thread {} already exists", thread_id)));
+    }
+
+    tokio::fs::rename(&trashed_path, &file_path)
+        .await
+        .map_err(|e| FsError::io("restore thread", e))?;
+    if let Err(e) = tokio::fs::remove_file(&tombstone_path).await {
+        log::warn!("Failed to remove tombstone for {}: {}", thread_id, e);
+    }
+
+    log::info!("Restored thread {} from trash", thread_id);
+
+    Ok(())
+}
+
+/// List threads in the trash, most recently deleted first
+pub async fn list_trash() -> Result<Vec<TrashedThread>, FsError> {
+    let trash_dir = get_trash_dir()?;
+    if !path_exists(&trash_dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = tokio::fs::read_dir(&trash_dir)
+        .await
+        .map_err(|e| FsError::io("read trash directory", e))?;
+
+    let mut trashed = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| FsError::io("read directory entry", e))?
+    {
+        let path = entry.path();
+        if !path.to_string_lossy().ends_with(".tombstone.json") {
+            continue;
+        }
+        let tombstone = tokio::fs::read_to_string(&path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<TrashedThread>(&content).ok());
+        match tombstone {
+            Some(tombstone) => trashed.push(tombstone),
+            None => log::warn!("Skipping unreadable tombstone {}", path.display()),
+        }
+    }
+
+    trashed.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(trashed)
+}
+
+/// Permanently remove trashed threads deleted more than `older_than_days` ago
+/// (default `CHIMERA_TRASH_RETENTION_DAYS`, or 30; 0 empties the trash).
+/// Returns the ids removed. `actor` is who asked, for the audit log.
+pub async fn purge_trash(older_than_days: Option<i64>, actor: &str) -> Result<Vec<String>, FsError> {
+    let days = older_than_days
+        .or_else(|| std::env::var("CHIMERA_TRASH_RETENTION_DAYS").ok().and_then(|v| v.parse().ok()))
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+    let cutoff = (chrono::Utc::now() - chrono::TimeDelta::days(days.max(0))).to_rfc3339();
+
+    let mut purged = Vec::new();
+    for tombstone in list_trash().await? {
+        if tombstone.deleted_at > cutoff {
+            continue;
+        }
+        let (trashed_path, tombstone_path) = trash_paths(&tombstone.thread_id)?;
+        if let Err(e) = tokio::fs::remove_file(&trashed_path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::error!("Failed to purge thread {}: {}", tombstone.thread_id, e);
+                continue;
             }
         }
+        let _ = tokio::fs::remove_file(&tombstone_path).await;
+        let mut removed = vec![trashed_path, tombstone_path];
+        if let Ok(meta_path) = get_thread_meta_path(&tombstone.thread_id) {
+            let _ = tokio::fs::remove_file(&meta_path).await;
+            removed.push(meta_path);
+        }
+        crate::audit::record_async(
+            crate::audit::AuditEntry::new("threads", "purge", actor, tombstone.thread_id.as_str(), true)
+                .detail(format!("Trashed {}", tombstone.deleted_at))
+                .paths(removed),
+        )
+        .await;
+        purged.push(tombstone.thread_id);
     }
 
-    // Prefer explicit title, fall back to user message
-    explicit_title.or(user_message_title)
+    if !purged.is_empty() {
+        log::info!("Purged {} thread(s) from trash", purged.len());
+    }
+
+    Ok(purged)
+}
+
+/// A thread file's listing summary, cached by modification time
+async fn summarize_thread(path: &std::path::Path) -> Option<crate::thread_summary::ThreadSummary> {
+    let path = path.to_path_buf();
+    blocking_fs(move || Ok(crate::thread_summary::summarize(&path))).await.ok().flatten()
 }
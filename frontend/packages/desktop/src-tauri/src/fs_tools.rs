@@ -0,0 +1,191 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::audit::{self, AuditEntry};
+use crate::filesystem;
+use crate::permissions::{Access, Permissions};
+
+/// Largest file `read_file` will return
+const MAX_READ_BYTES: u64 = 10 * 1024 * 1024;
+
+/// One entry of a `list_dir` result
+#[derive(Debug, Clone, Serialize)]
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Check `path` against the granted roots, auditing the attempt either way
+fn authorize(permissions: &Permissions, action: &str, actor: &str, path: &str, access: Access) -> Result<std::path::PathBuf, String> {
+    match permissions.check(Path::new(path), access) {
+        Ok(resolved) => {
+            audit::record(AuditEntry::new("fs", action, actor, resolved.display().to_string(), true));
+            Ok(resolved)
+        }
+        Err(e) => {
+            log::warn!("Denied {} of {} by {}: {}", action, path, actor, e);
+            audit::record(AuditEntry::new("fs", action, actor, path, false).detail(e.clone()));
+            Err(e)
+        }
+    }
+}
+
+/// Read a UTF-8 file inside a granted root
+pub async fn read_file(permissions: Arc<Permissions>, path: String, actor: &str) -> Result<String, String> {
+    let actor = actor.to_string();
+    filesystem::blocking(move || {
+        let resolved = authorize(&permissions, "read_file", &actor, &path, Access::Read)?;
+        let metadata = std::fs::metadata(&resolved).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if metadata.len() > MAX_READ_BYTES {
+            return Err(format!("{} is larger than {} bytes", path, MAX_READ_BYTES));
+        }
+        std::fs::read_to_string(&resolved).map_err(|e| format!("Failed to read {}: {}", path, e))
+    })
+    .await
+}
+
+/// Write a file inside a root granting write access, creating parent directories
+pub async fn write_file(permissions: Arc<Permissions>, path: String, contents: String, actor: &str) -> Result<(), String> {
+    let actor = actor.to_string();
+    filesystem::blocking(move || {
+        let resolved = authorize(&permissions, "write_file", &actor, &path, Access::Write)?;
+        if let Some(parent) = resolved.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&resolved, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+    })
+    .await
+}
+
+/// List a directory inside a granted root
+pub async fn list_dir(permissions: Arc<Permissions>, path: String, actor: &str) -> Result<Vec<DirEntry>, String> {
+    let actor = actor.to_string();
+    filesystem::blocking(move || {
+        let resolved = authorize(&permissions, "list_dir", &actor, &path, Access::Read)?;
+        let mut entries: Vec<DirEntry> = std::fs::read_dir(&resolved)
+            .map_err(|e| format!("Failed to list {}: {}", path, e))?
+            .flatten()
+            .map(|entry| {
+                let metadata = entry.metadata().ok();
+                DirEntry {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    is_dir: metadata.as_ref().is_some_and(|m| m.is_dir()),
+                    size: metadata.map(|m| m.len()).unwrap_or(0),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    })
+    .await
+}
+
+/// Address and credentials of the local tool API, handed to the backend
+#[derive(Debug, Clone)]
+pub struct ToolApi {
+    pub url: String,
+    token: String,
+}
+
+impl ToolApi {
+    /// Environment for the backend process (`CHIMERA_TOOLS_URL`, `CHIMERA_TOOLS_TOKEN`)
+    pub fn backend_env(&self) -> Vec<(String, String)> {
+        vec![
+            ("CHIMERA_TOOLS_URL".to_string(), self.url.clone()),
+            ("CHIMERA_TOOLS_TOKEN".to_string(), self.token.clone()),
+        ]
+    }
+}
+
+struct ApiState {
+    permissions: Arc<Permissions>,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct PathRequest {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct WriteRequest {
+    path: String,
+    contents: String,
+}
+
+fn authenticated(state: &ApiState, headers: &HeaderMap) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| token == state.token)
+}
+
+fn respond<T: Serialize>(result: Result<T, String>) -> Response {
+    match result {
+        Ok(value) => Json(value).into_response(),
+        Err(e) if e.contains("has not been granted") => (StatusCode::FORBIDDEN, e).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+async fn api_read_file(State(state): State<Arc<ApiState>>, headers: HeaderMap, Json(request): Json<PathRequest>) -> Response {
+    if !authenticated(&state, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    respond(read_file(state.permissions.clone(), request.path, "backend").await)
+}
+
+async fn api_write_file(State(state): State<Arc<ApiState>>, headers: HeaderMap, Json(request): Json<WriteRequest>) -> Response {
+    if !authenticated(&state, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    respond(write_file(state.permissions.clone(), request.path, request.contents, "backend").await)
+}
+
+async fn api_list_dir(State(state): State<Arc<ApiState>>, headers: HeaderMap, Json(request): Json<PathRequest>) -> Response {
+    if !authenticated(&state, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    respond(list_dir(state.permissions.clone(), request.path, "backend").await)
+}
+
+/// Serve the file tools on an ephemeral localhost port for the backend's agents
+pub async fn serve(permissions: Arc<Permissions>) -> Result<ToolApi, String> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let state = Arc::new(ApiState {
+        permissions,
+        token: token.clone(),
+    });
+
+    let router = Router::new()
+        .route("/fs/read_file", post(api_read_file))
+        .route("/fs/write_file", post(api_write_file))
+        .route("/fs/list_dir", post(api_list_dir))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(|e| format!("Failed to bind tool API: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read tool API address: {}", e))?
+        .port();
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            log::error!("Tool API server error: {}", e);
+        }
+    });
+
+    let url = format!("http://127.0.0.1:{}", port);
+    log::info!("Serving file tools on {}", url);
+    Ok(ToolApi { url, token })
+}
@@ -0,0 +1,215 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+
+/// Quiet period before a burst of changes is reported; editors often write a file
+/// several times per save
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Longest a steady stream of changes is held back
+const MAX_DELAY: Duration = Duration::from_secs(3);
+
+/// Changes within this long of the app itself publishing one for the same
+/// thread or blueprint are taken to be the app's own writes
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// What a watched file is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Thread,
+    Blueprint,
+}
+
+impl Kind {
+    fn topic(self) -> &'static str {
+        match self {
+            Kind::Thread => "thread-changed",
+            Kind::Blueprint => "blueprint-changed",
+        }
+    }
+
+    fn id_field(self) -> &'static str {
+        match self {
+            Kind::Thread => "thread_id",
+            Kind::Blueprint => "blueprint_id",
+        }
+    }
+}
+
+/// A thread or blueprint file and how its first change in a burst looked
+struct Pending {
+    path: PathBuf,
+    created: bool,
+}
+
+/// Watches the threads and blueprints directories and publishes `thread-changed`
+/// and `blueprint-changed` (with `source: "external"`) when files change outside
+/// the app, e.g. a blueprint edited in a text editor
+pub struct FileWatcher {
+    bus: Arc<EventBus>,
+    own_writes: Arc<Mutex<HashMap<(Kind, String), Instant>>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+/// Thread or blueprint id for a path in a watched directory, skipping temp files,
+/// the thread index and anything in subdirectories such as the trash
+fn classify(path: &Path, threads_dir: &Path, blueprints_dir: &Path) -> Option<(Kind, String)> {
+    let parent = path.parent()?;
+    let stem = path.file_stem()?.to_str()?;
+    let extension = path.extension()?.to_str()?;
+    if stem.starts_with('.') {
+        return None;
+    }
+    match extension {
+        "jsonl" if parent == threads_dir => Some((Kind::Thread, stem.to_string())),
+        "json" if parent == blueprints_dir => Some((Kind::Blueprint, stem.to_string())),
+        _ => None,
+    }
+}
+
+impl FileWatcher {
+    pub fn start(bus: Arc<EventBus>) -> Result<Self, String> {
+        let watcher = Self {
+            bus,
+            own_writes: Arc::new(Mutex::new(HashMap::new())),
+            watcher: Mutex::new(None),
+        };
+        Self::track_own_writes(&watcher.bus, watcher.own_writes.clone());
+        watcher.watch_data_dir()?;
+
+        log::info!("Watching threads and blueprints for external changes");
+        Ok(watcher)
+    }
+
+    /// Watch the current data directory, replacing any earlier watch, e.g. after
+    /// switching workspaces
+    pub fn watch_data_dir(&self) -> Result<(), String> {
+        let threads_dir = filesystem::get_threads_dir()?;
+        let blueprints_dir = filesystem::get_blueprints_dir()?;
+        for dir in [&threads_dir, &blueprints_dir] {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                let _ = sender.send(event);
+            }
+            Err(e) => log::warn!("File watcher error: {}", e),
+        })
+        .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+
+        for dir in [&threads_dir, &blueprints_dir] {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+        }
+
+        // Dropping the old watcher closes its channel, which ends its debounce task
+        *self.watcher.lock().unwrap() = Some(watcher);
+        tauri::async_runtime::spawn(Self::debounce(
+            receiver,
+            self.bus.clone(),
+            self.own_writes.clone(),
+            threads_dir,
+            blueprints_dir,
+        ));
+        Ok(())
+    }
+
+    /// Note when the app publishes its own thread and blueprint changes
+    fn track_own_writes(bus: &EventBus, own_writes: Arc<Mutex<HashMap<(Kind, String), Instant>>>) {
+        let mut receiver = bus.listen();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                // Our own reports don't count as the app writing
+                if event.payload.get("source").and_then(|s| s.as_str()) == Some("external") {
+                    continue;
+                }
+                let Some(kind) = [Kind::Thread, Kind::Blueprint].into_iter().find(|kind| kind.topic() == event.topic)
+                else {
+                    continue;
+                };
+                if let Some(id) = event.payload.get(kind.id_field()).and_then(|id| id.as_str()) {
+                    let mut own_writes = own_writes.lock().unwrap();
+                    own_writes.retain(|_, at: &mut Instant| at.elapsed() < OWN_WRITE_WINDOW);
+                    own_writes.insert((kind, id.to_string()), Instant::now());
+                }
+            }
+        });
+    }
+
+    /// Collect file events until things go quiet, then publish one change per file
+    async fn debounce(
+        mut receiver: mpsc::UnboundedReceiver<notify::Event>,
+        bus: Arc<EventBus>,
+        own_writes: Arc<Mutex<HashMap<(Kind, String), Instant>>>,
+        threads_dir: PathBuf,
+        blueprints_dir: PathBuf,
+    ) {
+        let mut pending: HashMap<(Kind, String), Pending> = HashMap::new();
+        let mut first_at: Option<Instant> = None;
+
+        loop {
+            let timeout = match first_at {
+                Some(first) => DEBOUNCE.min(MAX_DELAY.saturating_sub(first.elapsed())),
+                None => Duration::MAX,
+            };
+            let event = tokio::select! {
+                event = receiver.recv() => match event {
+                    Some(event) => Some(event),
+                    None => break,
+                },
+                _ = tokio::time::sleep(timeout) => None,
+            };
+
+            if let Some(event) = event {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    let Some(key) = classify(&path, &threads_dir, &blueprints_dir) else { continue };
+                    pending.entry(key).or_insert(Pending {
+                        path,
+                        created: matches!(event.kind, EventKind::Create(_)),
+                    });
+                    first_at.get_or_insert_with(Instant::now);
+                }
+                continue;
+            }
+
+            first_at = None;
+            let own_writes: HashMap<(Kind, String), Instant> = own_writes.lock().unwrap().clone();
+            for ((kind, id), change) in pending.drain() {
+                if own_writes
+                    .get(&(kind, id.clone()))
+                    .is_some_and(|at| at.elapsed() < OWN_WRITE_WINDOW)
+                {
+                    continue;
+                }
+                let change = match (change.path.exists(), change.created) {
+                    (false, _) => "deleted",
+                    (true, true) => "created",
+                    (true, false) => "modified",
+                };
+                log::debug!("External change to {:?} {}: {}", kind, id, change);
+                bus.publish(
+                    kind.topic(),
+                    serde_json::json!({ kind.id_field(): id, "change": change, "source": "external" }),
+                );
+            }
+        }
+    }
+}
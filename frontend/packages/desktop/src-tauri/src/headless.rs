@@ -0,0 +1,403 @@
+use futures_util::StreamExt;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use crate::control_socket::{self, ControlRequest};
+use crate::export::ExporterRegistry;
+use crate::filesystem;
+use crate::python_backend::{BackendConfig, PythonBackend};
+
+/// Exit codes for headless runs
+const EXIT_OK: i32 = 0;
+const EXIT_USAGE: i32 = 2;
+const EXIT_BACKEND_FAILED: i32 = 3;
+const EXIT_RUN_FAILED: i32 = 4;
+
+const USAGE: &str = "Usage:
+  chimera-desktop --headless --blueprint <path> [--prompt <text> | --prompt-file <path>]
+      Run the blueprint once without opening a window and stream backend events to
+      stdout as JSONL. The prompt is read from stdin when neither --prompt nor
+      --prompt-file is given.
+
+  chimera-desktop --headless
+      Start the Python backend without a window and answer the commands below on a
+      local control socket until interrupted.
+
+  chimera-desktop list-threads
+  chimera-desktop export-thread <id> [--format <format>] [--out <path>]
+  chimera-desktop backend-status
+      Print the result as JSON. Commands go to the running headless instance when
+      there is one, and otherwise read the data directory directly.";
+
+/// Commands run from the command line or over the control socket
+const SUBCOMMANDS: &[&str] = &["list-threads", "export-thread", "backend-status"];
+
+/// Parsed headless arguments
+struct HeadlessArgs {
+    blueprint_path: String,
+    prompt: Option<String>,
+    prompt_file: Option<String>,
+}
+
+/// Returns true when the process was launched with `--headless` or a subcommand
+pub fn is_requested(args: &[String]) -> bool {
+    args.iter().any(|a| a == "--headless") || subcommand(args).is_some()
+}
+
+fn subcommand(args: &[String]) -> Option<&str> {
+    args.get(1).map(String::as_str).filter(|arg| SUBCOMMANDS.contains(arg))
+}
+
+/// Parse headless arguments (everything after the binary name)
+fn parse_args(args: &[String]) -> Result<HeadlessArgs, String> {
+    let mut blueprint_path = None;
+    let mut prompt = None;
+    let mut prompt_file = None;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--headless" => {}
+            "--blueprint" => blueprint_path = iter.next().cloned(),
+            "--prompt" => prompt = iter.next().cloned(),
+            "--prompt-file" => prompt_file = iter.next().cloned(),
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("Unknown argument: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    let blueprint_path = blueprint_path.ok_or_else(|| format!("Missing --blueprint\n\n{}", USAGE))?;
+
+    Ok(HeadlessArgs {
+        blueprint_path,
+        prompt,
+        prompt_file,
+    })
+}
+
+/// Resolve the prompt from the command line, a file, or stdin
+fn read_prompt(args: &HeadlessArgs) -> Result<String, String> {
+    let prompt = if let Some(prompt) = &args.prompt {
+        prompt.clone()
+    } else if let Some(path) = &args.prompt_file {
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read prompt file: {}", e))?
+    } else {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|e| format!("Failed to read prompt from stdin: {}", e))?;
+        buffer
+    };
+
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+
+    Ok(prompt)
+}
+
+/// Write a single JSONL line to stdout
+fn emit_line(value: &serde_json::Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", value);
+    let _ = stdout.flush();
+}
+
+/// Run a subcommand, serve the control socket, or run a blueprint once, and
+/// return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    if let Some(command) = subcommand(args) {
+        return run_command(command, &args[2..]);
+    }
+    if !args.iter().any(|a| a == "--blueprint" || a == "--help" || a == "-h") {
+        return serve(args);
+    }
+    run_once(args)
+}
+
+/// Run a blueprint headlessly and return the process exit code
+fn run_once(args: &[String]) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+
+    let blueprint_json = match std::fs::read_to_string(&args.blueprint_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Failed to read blueprint {}: {}", args.blueprint_path, e);
+            return EXIT_USAGE;
+        }
+    };
+
+    let prompt = match read_prompt(&args) {
+        Ok(prompt) => prompt,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+
+    let code = tauri::async_runtime::block_on(async move {
+        crate::telemetry::init();
+
+        let backend = match PythonBackend::start(&[], BackendConfig::default_workspace()).await {
+            Ok(backend) => backend,
+            Err(e) => {
+                eprintln!("Failed to start Python backend: {}", e);
+                return EXIT_BACKEND_FAILED;
+            }
+        };
+
+        let code = match run_blueprint(&backend.base_url(), &blueprint_json, prompt).await {
+            Ok(()) => EXIT_OK,
+            Err(e) => {
+                eprintln!("{}", e);
+                EXIT_RUN_FAILED
+            }
+        };
+
+        backend.shutdown(None).await;
+        code
+    });
+
+    crate::telemetry::shutdown();
+    code
+}
+
+/// Send the blueprint and prompt to the backend and stream events to stdout
+#[tracing::instrument(skip_all, fields(thread_id))]
+async fn run_blueprint(base_url: &str, blueprint_json: &str, prompt: String) -> Result<(), String> {
+    let mut blueprint: serde_json::Value = serde_json::from_str(blueprint_json)
+        .map_err(|e| format!("Failed to parse blueprint JSON: {}", e))?;
+
+    let thread_id = uuid::Uuid::new_v4().to_string();
+    blueprint
+        .as_object_mut()
+        .ok_or("Blueprint JSON is not an object")?
+        .insert("thread_id".to_string(), serde_json::Value::String(thread_id.clone()));
+    tracing::Span::current().record("thread_id", thread_id.as_str());
+
+    let body = serde_json::json!({
+        "thread_protocol": [blueprint],
+        "user_input": {
+            "kind": "message",
+            "content": prompt,
+        },
+    });
+
+    let mut request = reqwest::Client::new().post(format!("{}/stream", base_url)).json(&body);
+    for (name, value) in crate::telemetry::trace_headers() {
+        request = request.header(name, value);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to backend: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let detail = response.text().await.unwrap_or_default();
+        return Err(format!("Backend returned {}: {}", status, detail));
+    }
+
+    let mut bytes = response.bytes_stream();
+    let mut pending = String::new();
+    let mut saw_error = false;
+
+    while let Some(chunk) = bytes.next().await {
+        let chunk = chunk.map_err(|e| format!("Backend stream interrupted: {}", e))?;
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(newline) = pending.find('\n') {
+            let line: String = pending.drain(..=newline).collect();
+            let Some(data) = line.trim_end().strip_prefix("data:") else {
+                continue;
+            };
+
+            let data = data.trim_start();
+            if data == "[DONE]" {
+                continue;
+            }
+
+            let event = serde_json::from_str::<serde_json::Value>(data)
+                .unwrap_or_else(|_| serde_json::Value::String(data.to_string()));
+
+            if event.get("type").and_then(|t| t.as_str()) == Some("error") {
+                saw_error = true;
+            }
+
+            emit_line(&event);
+        }
+    }
+
+    if saw_error {
+        return Err("Run finished with an error event".to_string());
+    }
+
+    Ok(())
+}
+
+/// `--out` made absolute, since a headless instance may run from another directory
+fn absolute_args(args: &[String]) -> Result<Vec<String>, String> {
+    let mut args = args.to_vec();
+    if let Some(index) = args.iter().position(|a| a == "--out") {
+        let out = args.get(index + 1).ok_or_else(|| format!("Missing value for --out\n\n{}", USAGE))?;
+        let absolute = std::path::absolute(out).map_err(|e| format!("Invalid --out path {}: {}", out, e))?;
+        args[index + 1] = absolute.to_string_lossy().into_owned();
+    }
+    Ok(args)
+}
+
+/// Run a subcommand, through the headless instance if one is running
+fn run_command(command: &str, args: &[String]) -> i32 {
+    let args = match absolute_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+    let request = ControlRequest {
+        command: command.to_string(),
+        args,
+    };
+
+    let result = tauri::async_runtime::block_on(async move {
+        match control_socket::request(&request).await {
+            Some(Ok(response)) if response.ok => Ok(response.result),
+            Some(Ok(response)) => Err(response.error.unwrap_or_else(|| "Command failed".to_string())),
+            Some(Err(e)) => Err(e),
+            None => execute(&request, None).await,
+        }
+    });
+
+    match result {
+        Ok(result) => {
+            emit_line(&result);
+            EXIT_OK
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            EXIT_RUN_FAILED
+        }
+    }
+}
+
+fn to_json(value: impl serde::Serialize) -> Result<serde_json::Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Carry out one command. `backend` is the headless instance's backend, if this
+/// is it.
+async fn execute(request: &ControlRequest, backend: Option<&PythonBackend>) -> Result<serde_json::Value, String> {
+    match request.command.as_str() {
+        "list-threads" => to_json(filesystem::list_threads().await?),
+        "export-thread" => {
+            let mut thread_id = None;
+            let mut format = "markdown".to_string();
+            let mut out = None;
+            let mut iter = request.args.iter();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--format" => format = iter.next().cloned().ok_or("Missing value for --format")?,
+                    "--out" => out = iter.next().cloned(),
+                    other if thread_id.is_none() && !other.starts_with("--") => thread_id = Some(other.to_string()),
+                    other => return Err(format!("Unknown argument: {}\n\n{}", other, USAGE)),
+                }
+            }
+            let thread_id = thread_id.ok_or_else(|| format!("Missing thread id\n\n{}", USAGE))?;
+
+            let exporters = ExporterRegistry::new();
+            let out = match out {
+                Some(out) => out,
+                None => {
+                    let extension = exporters
+                        .list()
+                        .into_iter()
+                        .find(|info| info.id == format)
+                        .map(|info| info.extension)
+                        .ok_or_else(|| format!("Unknown export format: {}", format))?;
+                    let name = format!("{}.{}", thread_id, extension);
+                    std::path::absolute(&name)
+                        .map_err(|e| format!("Invalid output path {}: {}", name, e))?
+                        .to_string_lossy()
+                        .into_owned()
+                }
+            };
+            to_json(exporters.export_thread(thread_id, &format, out).await?)
+        }
+        "backend-status" => {
+            let backend = backend.ok_or("No headless instance is running")?;
+            let mut status = to_json(backend.status())?;
+            status["pid"] = serde_json::json!(backend.pid().await);
+            Ok(status)
+        }
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+/// Start the backend and answer control requests until Ctrl-C or a `shutdown`
+/// request
+fn serve(args: &[String]) -> i32 {
+    if let Some(other) = args.iter().skip(1).find(|a| *a != "--headless") {
+        eprintln!("Unknown argument: {}\n\n{}", other, USAGE);
+        return EXIT_USAGE;
+    }
+
+    let code = tauri::async_runtime::block_on(async move {
+        crate::telemetry::init();
+        if let Err(e) = filesystem::init_filesystem().await {
+            eprintln!("{}", e);
+            return EXIT_BACKEND_FAILED;
+        }
+
+        let backend = match PythonBackend::start(&[], BackendConfig::default_workspace()).await {
+            Ok(backend) => Arc::new(backend),
+            Err(e) => {
+                eprintln!("Failed to start Python backend: {}", e);
+                return EXIT_BACKEND_FAILED;
+            }
+        };
+        emit_line(&serde_json::json!({ "ready": true, "url": backend.base_url() }));
+
+        let stop = Arc::new(tokio::sync::Notify::new());
+        let handler = {
+            let (backend, stop) = (backend.clone(), stop.clone());
+            move |request: ControlRequest| {
+                let (backend, stop) = (backend.clone(), stop.clone());
+                async move {
+                    if request.command == "shutdown" {
+                        stop.notify_one();
+                        return Ok(serde_json::json!({ "stopping": true }));
+                    }
+                    execute(&request, Some(&backend)).await
+                }
+            }
+        };
+        let shutdown = async move {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = stop.notified() => {}
+            }
+        };
+
+        let code = match control_socket::serve(handler, shutdown).await {
+            Ok(()) => EXIT_OK,
+            Err(e) => {
+                eprintln!("{}", e);
+                EXIT_RUN_FAILED
+            }
+        };
+        backend.shutdown(None).await;
+        code
+    });
+
+    crate::telemetry::shutdown();
+    code
+}
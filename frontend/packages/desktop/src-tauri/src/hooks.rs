@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::settings::SettingsStore;
+use crate::webhooks::{self, WebhookManager};
+
+/// Runs kept in memory for `get_hook_runs`
+const RUN_LOG_CAPACITY: usize = 200;
+
+/// Output kept from each of a command's stdout and stderr, or a URL's response
+const MAX_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// `hooks.jsonl` is rotated to `hooks.jsonl.1` past this size
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Longest a hook may be given to run
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+/// What a hook does when its event happens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Run through the shell (`sh -c`, or `cmd /C` on Windows) with
+    /// `CHIMERA_HOOK_EVENT`, `CHIMERA_HOOK_ID` and `CHIMERA_HOOK_PAYLOAD`, the path
+    /// of a JSON file holding the event, in its environment
+    Command { command: String, cwd: Option<String> },
+    /// POST the event as JSON
+    Webhook { url: String },
+}
+
+/// A command or URL, configured in settings, run when app events happen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    pub id: String,
+    /// `thread.created`, `agent.finished`, `agent.error` or `backend.crashed`;
+    /// empty means all of them
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(flatten)]
+    pub action: HookAction,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl Hook {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Hook has no id".to_string());
+        }
+        if let Some(unknown) = self
+            .events
+            .iter()
+            .find(|e| !webhooks::KNOWN_EVENTS.contains(&e.as_str()))
+        {
+            return Err(format!("Hook {}: unknown event {}", self.id, unknown));
+        }
+        if !(1..=MAX_TIMEOUT_SECS).contains(&self.timeout_secs) {
+            return Err(format!(
+                "Hook {}: timeout must be 1 to {} seconds",
+                self.id, MAX_TIMEOUT_SECS
+            ));
+        }
+        match &self.action {
+            HookAction::Command { command, cwd } => {
+                if command.trim().is_empty() {
+                    return Err(format!("Hook {} has no command", self.id));
+                }
+                if cwd.as_ref().is_some_and(|cwd| !std::path::Path::new(cwd).is_absolute()) {
+                    return Err(format!("Hook {}: working directory must be absolute", self.id));
+                }
+            }
+            HookAction::Webhook { url } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(format!("Hook {}: URL must be http(s): {}", self.id, url));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn wants(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+}
+
+/// Check a hook list from settings: each hook and unique ids
+pub fn validate(hooks: &[Hook]) -> Result<(), String> {
+    for (i, hook) in hooks.iter().enumerate() {
+        hook.validate()?;
+        if hooks[..i].iter().any(|other| other.id == hook.id) {
+            return Err(format!("Duplicate hook id: {}", hook.id));
+        }
+    }
+    Ok(())
+}
+
+/// One run of a hook, as kept in the hooks log
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRun {
+    pub hook_id: String,
+    pub event: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub success: bool,
+    /// A command's exit code; none if it was killed or didn't start
+    pub exit_code: Option<i32>,
+    /// A webhook's HTTP status
+    pub status: Option<u16>,
+    /// A command's stdout and stderr, or a webhook's response body, truncated
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// Runs the hooks in settings when app events happen, logging each run to
+/// `logs/hooks.jsonl` in the data directory
+pub struct HookRunner {
+    app_handle: AppHandle,
+    runs: Arc<Mutex<VecDeque<HookRun>>>,
+    client: reqwest::Client,
+}
+
+impl HookRunner {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            runs: Arc::new(Mutex::new(VecDeque::new())),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Run every enabled hook for `event` in the background
+    pub fn dispatch(&self, event: &str, data: serde_json::Value) {
+        let Some(settings) = self.app_handle.try_state::<Arc<SettingsStore>>() else {
+            return;
+        };
+        let hooks: Vec<Hook> = settings
+            .get()
+            .hooks
+            .into_iter()
+            .filter(|hook| hook.wants(event))
+            .collect();
+        if hooks.is_empty() {
+            return;
+        }
+
+        let payload = serde_json::json!({
+            "event": event,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "data": data,
+        });
+        for hook in hooks {
+            let (client, runs, event, payload) = (
+                self.client.clone(),
+                self.runs.clone(),
+                event.to_string(),
+                payload.clone(),
+            );
+            tauri::async_runtime::spawn(async move {
+                let run = run_hook(&client, &hook, &event, &payload).await;
+                if run.success {
+                    log::info!("Hook {} ran for {}", run.hook_id, run.event);
+                } else {
+                    log::warn!(
+                        "Hook {} failed for {}: {}",
+                        run.hook_id,
+                        run.event,
+                        run.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                record(&runs, run).await;
+            });
+        }
+    }
+
+    /// Most recent runs, newest first
+    pub fn runs(&self, limit: usize) -> Vec<HookRun> {
+        self.runs.lock().unwrap().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Fire `backend.crashed`, to hooks and webhooks, whenever the backend is found
+    /// dead or unresponsive, or given up on
+    pub fn watch(self: &Arc<Self>, bus: &EventBus) {
+        let mut receiver = bus.listen();
+        let runner = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.topic == "backend-status" => {
+                        let state = event.payload.get("state").and_then(|s| s.as_str());
+                        if !matches!(state, Some("unhealthy" | "failed")) {
+                            continue;
+                        }
+                        runner.dispatch(webhooks::EVENT_BACKEND_CRASHED, event.payload.clone());
+                        if let Some(webhooks) = runner.app_handle.try_state::<Arc<WebhookManager>>() {
+                            webhooks.dispatch(webhooks::EVENT_BACKEND_CRASHED, event.payload);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => log::debug!("Hook runner skipped {} events", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+async fn run_hook(client: &reqwest::Client, hook: &Hook, event: &str, payload: &serde_json::Value) -> HookRun {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let started = Instant::now();
+    let timeout = Duration::from_secs(hook.timeout_secs);
+    let mut run = HookRun {
+        hook_id: hook.id.clone(),
+        event: event.to_string(),
+        started_at,
+        duration_ms: 0,
+        success: false,
+        exit_code: None,
+        status: None,
+        output: String::new(),
+        error: None,
+    };
+
+    match &hook.action {
+        HookAction::Command { command, cwd } => run_command(&mut run, command, cwd.as_deref(), payload, timeout).await,
+        HookAction::Webhook { url } => {
+            let response = client
+                .post(url)
+                .header("X-Chimera-Event", event)
+                .json(payload)
+                .timeout(timeout)
+                .send()
+                .await;
+            match response {
+                Ok(response) => {
+                    let status = response.status();
+                    run.status = Some(status.as_u16());
+                    run.success = status.is_success();
+                    if !run.success {
+                        run.error = Some(format!("HTTP {}", status));
+                    }
+                    run.output = truncate(&response.bytes().await.unwrap_or_default());
+                }
+                Err(e) => run.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    run.duration_ms = started.elapsed().as_millis() as u64;
+    run
+}
+
+async fn run_command(
+    run: &mut HookRun,
+    command: &str,
+    cwd: Option<&str>,
+    payload: &serde_json::Value,
+    timeout: Duration,
+) {
+    let payload_path = std::env::temp_dir().join(format!("chimera-hook-{}.json", uuid::Uuid::new_v4()));
+    if let Err(e) = tokio::fs::write(&payload_path, payload.to_string()).await {
+        run.error = Some(format!("Failed to write hook payload: {}", e));
+        return;
+    }
+
+    #[cfg(windows)]
+    let mut process = {
+        let mut process = tokio::process::Command::new("cmd");
+        process.arg("/C").arg(command);
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        process.creation_flags(CREATE_NO_WINDOW);
+        process
+    };
+    #[cfg(not(windows))]
+    let mut process = {
+        let mut process = tokio::process::Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+    process
+        .env("CHIMERA_HOOK_EVENT", &run.event)
+        .env("CHIMERA_HOOK_ID", &run.hook_id)
+        .env("CHIMERA_HOOK_PAYLOAD", &payload_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // A command still running at the timeout is killed when its future is dropped
+        .kill_on_drop(true);
+    if let Some(cwd) = cwd {
+        process.current_dir(cwd);
+    }
+
+    match process.spawn() {
+        Ok(child) => match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => {
+                run.exit_code = output.status.code();
+                run.success = output.status.success();
+                if !run.success {
+                    run.error = Some(format!("Exited with {}", output.status));
+                }
+                run.output = truncate(&output.stdout);
+                let stderr = truncate(&output.stderr);
+                if !stderr.is_empty() {
+                    if !run.output.is_empty() {
+                        run.output.push('\n');
+                    }
+                    run.output.push_str(&stderr);
+                }
+            }
+            Ok(Err(e)) => run.error = Some(format!("Failed to wait for command: {}", e)),
+            Err(_) => run.error = Some(format!("Timed out after {}s", timeout.as_secs())),
+        },
+        Err(e) => run.error = Some(format!("Failed to start command: {}", e)),
+    }
+
+    let _ = tokio::fs::remove_file(&payload_path).await;
+}
+
+/// Text of `bytes`, cut to `MAX_OUTPUT_BYTES` at a character boundary
+fn truncate(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_OUTPUT_BYTES)]);
+    let mut text = text.trim_end_matches('\u{fffd}').trim_end().to_string();
+    if bytes.len() > MAX_OUTPUT_BYTES {
+        text.push('…');
+    }
+    text
+}
+
+fn log_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("logs").join("hooks.jsonl"))
+}
+
+/// Keep a run in memory and append it to the hooks log
+async fn record(runs: &Mutex<VecDeque<HookRun>>, run: HookRun) {
+    let line = serde_json::to_string(&run);
+    {
+        let mut runs = runs.lock().unwrap();
+        runs.push_back(run);
+        while runs.len() > RUN_LOG_CAPACITY {
+            runs.pop_front();
+        }
+    }
+
+    let appended = async {
+        let mut line = line.map_err(|e| format!("Failed to serialize hook run: {}", e))?;
+        line.push('\n');
+        let path = log_path()?;
+        filesystem::blocking(move || {
+            use std::io::Write;
+
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create logs directory: {}", e))?;
+            }
+            if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+                let _ = std::fs::rename(&path, path.with_extension("jsonl.1"));
+            }
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(line.as_bytes()))
+                .map_err(|e| format!("Failed to write hooks log: {}", e))
+        })
+        .await
+    };
+    if let Err(e) = appended.await {
+        log::warn!("{}", e);
+    }
+}
@@ -0,0 +1,330 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsStore;
+
+/// Language whose bundle has every key; the others fall back to it
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// Locale used when the OS doesn't say
+const FALLBACK_LOCALE: &str = "en-US";
+
+/// BCP 47 tag from a POSIX or Windows locale name: `de_DE.UTF-8@euro` becomes
+/// `de-DE`. None for `C`, `POSIX` and empty names, which say nothing.
+pub fn normalize(name: &str) -> Option<String> {
+    let name = name.split(['.', '@']).next()?.trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    let mut parts = name.split(['_', '-']);
+    let language = parts.next()?.to_ascii_lowercase();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut tag = language;
+    for part in parts.filter(|part| !part.is_empty()) {
+        if !part.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        tag.push('-');
+        match part.len() {
+            // Regions are upper case, scripts title case
+            2 | 3 => tag.push_str(&part.to_ascii_uppercase()),
+            4 => {
+                tag.push_str(&part[..1].to_ascii_uppercase());
+                tag.push_str(&part[1..].to_ascii_lowercase());
+            }
+            _ => tag.push_str(part),
+        }
+    }
+    Some(tag)
+}
+
+/// Check a locale override from settings
+pub fn validate(locale: &str) -> Result<(), String> {
+    normalize(locale)
+        .filter(|tag| tag.len() == locale.len())
+        .map(|_| ())
+        .ok_or_else(|| format!("Invalid locale: {} (expected a tag like en-US)", locale))
+}
+
+/// The OS's locale as a BCP 47 tag
+#[cfg(target_os = "macos")]
+pub fn system_locale() -> String {
+    let identifier = objc2_foundation::NSLocale::currentLocale().localeIdentifier().to_string();
+    normalize(&identifier).unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
+
+/// The OS's locale as a BCP 47 tag
+#[cfg(windows)]
+pub fn system_locale() -> String {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    let mut buffer = [0u16; 85];
+    // SAFETY: the buffer is LOCALE_NAME_MAX_LENGTH long, as the call expects
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    let name = (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]));
+    name.as_deref().and_then(normalize).unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
+
+/// The OS's locale as a BCP 47 tag, from the variables that set date formats
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn system_locale() -> String {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|value| normalize(&value))
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
+
+/// The locale to format for: the settings override, else the OS's
+pub fn effective_locale(app_handle: &AppHandle) -> String {
+    app_handle
+        .try_state::<Arc<SettingsStore>>()
+        .and_then(|settings| settings.get().locale)
+        .unwrap_or_else(system_locale)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// How a locale writes dates, times and numbers. A small table of the
+/// conventions that differ most, not full CLDR data.
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    locale: String,
+    order: DateOrder,
+    date_separator: char,
+    hour12: bool,
+    grouping_separator: &'static str,
+    decimal_separator: char,
+}
+
+impl Formatter {
+    pub fn new(locale: &str) -> Self {
+        let locale = normalize(locale).unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+        let mut parts = locale.split('-');
+        let language = parts.next().unwrap_or(FALLBACK_LANGUAGE).to_string();
+        let region = parts.find(|part| part.len() == 2 || part.len() == 3).unwrap_or("").to_string();
+        let (language, region) = (language.as_str(), region.as_str());
+
+        let order = match (language, region) {
+            ("en", "" | "US" | "PH") => DateOrder::MonthDayYear,
+            ("ja" | "zh" | "ko" | "hu" | "lt" | "sv" | "mn", _) | (_, "CN" | "JP" | "KR" | "TW") => {
+                DateOrder::YearMonthDay
+            }
+            _ => DateOrder::DayMonthYear,
+        };
+        let date_separator = match language {
+            "de" | "ru" | "pl" | "cs" | "sk" | "fi" | "nb" | "nn" | "no" | "da" | "tr" | "uk" | "ro" | "hu" => '.',
+            "nl" | "sv" | "lt" => '-',
+            _ => '/',
+        };
+        let hour12 = match (language, region) {
+            ("en", "GB" | "IE") => false,
+            ("en" | "hi" | "ar" | "ko", _) => true,
+            _ => false,
+        };
+        let (grouping_separator, decimal_separator) = match (language, region) {
+            (_, "CH" | "LI") => ("\u{2019}", '.'),
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "vi", _) => (".", ','),
+            ("fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "nn" | "no" | "fi" | "uk" | "hu" | "lt", _) => {
+                ("\u{202f}", ',')
+            }
+            _ => (",", '.'),
+        };
+
+        Self {
+            locale,
+            order,
+            date_separator,
+            hour12,
+            grouping_separator,
+            decimal_separator,
+        }
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// An RFC 3339 timestamp as local date and time, e.g. `15.10.2026 14:05` or
+    /// `10/15/2026 2:05 PM`. None when it doesn't parse.
+    pub fn date_time(&self, rfc3339: &str) -> Option<String> {
+        use chrono::{Datelike, Timelike};
+
+        let local = chrono::DateTime::parse_from_rfc3339(rfc3339).ok()?.with_timezone(&chrono::Local);
+        let (year, month, day) = (local.year(), local.month(), local.day());
+        let sep = self.date_separator;
+        let date = match self.order {
+            DateOrder::DayMonthYear => format!("{:02}{sep}{:02}{sep}{}", day, month, year),
+            DateOrder::MonthDayYear => format!("{}{sep}{}{sep}{}", month, day, year),
+            DateOrder::YearMonthDay => format!("{}{sep}{:02}{sep}{:02}", year, month, day),
+        };
+        let time = if self.hour12 {
+            let (pm, hour) = local.hour12();
+            format!("{}:{:02} {}", hour, local.minute(), if pm { "PM" } else { "AM" })
+        } else {
+            format!("{:02}:{:02}", local.hour(), local.minute())
+        };
+        Some(format!("{} {}", date, time))
+    }
+
+    /// A count with the locale's digit grouping, e.g. `12,345` or `12.345`
+    pub fn number(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3 * self.grouping_separator.len());
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                out.push_str(self.grouping_separator);
+            }
+            out.push(digit);
+        }
+        out
+    }
+}
+
+/// The system locale, the one in effect and how it formats, for `get_system_locale`
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    /// The OS's locale
+    pub system: String,
+    /// The settings override if there is one, else `system`
+    pub locale: String,
+    /// Language of the translation bundle used for `locale`
+    pub language: String,
+    pub hour12: bool,
+    pub decimal_separator: char,
+    pub grouping_separator: String,
+    /// Languages with a translation bundle
+    pub available_languages: Vec<String>,
+}
+
+/// Messages for one language, with the fallback language's filling any gaps
+#[derive(Debug, Clone, Serialize)]
+pub struct Translations {
+    /// Bundle the messages came from, e.g. `de` when `de-AT` was asked for
+    pub language: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+/// Translation bundles shipped under `resources/i18n/<language>.json`: flat
+/// objects of message keys to text, with `{name}` placeholders
+pub struct I18n {
+    dir: Option<PathBuf>,
+    bundles: Mutex<HashMap<String, Arc<BTreeMap<String, String>>>>,
+}
+
+impl I18n {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let dir = app_handle
+            .path()
+            .resolve("resources/i18n", BaseDirectory::Resource)
+            .inspect_err(|e| log::warn!("Failed to resolve translation bundles: {}", e))
+            .ok();
+        Self {
+            dir,
+            bundles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Languages with a bundle, sorted
+    pub fn languages(&self) -> Vec<String> {
+        let Some(dir) = &self.dir else { return Vec::new() };
+        let mut languages: Vec<String> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                path.extension().filter(|e| *e == "json")?;
+                Some(path.file_stem()?.to_str()?.to_string())
+            })
+            .collect();
+        languages.sort();
+        languages
+    }
+
+    /// A bundle, read once and kept. Missing or unreadable bundles are empty.
+    fn bundle(&self, language: &str) -> Arc<BTreeMap<String, String>> {
+        if let Some(bundle) = self.bundles.lock().unwrap().get(language) {
+            return bundle.clone();
+        }
+        let bundle = self
+            .dir
+            .as_ref()
+            .filter(|_| language.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+            .map(|dir| dir.join(format!("{}.json", language)))
+            .filter(|path| path.exists())
+            .and_then(|path| {
+                let content = std::fs::read_to_string(&path)
+                    .inspect_err(|e| log::warn!("Failed to read {}: {}", path.display(), e))
+                    .ok()?;
+                serde_json::from_str::<BTreeMap<String, String>>(&content)
+                    .inspect_err(|e| log::warn!("Failed to parse {}: {}", path.display(), e))
+                    .ok()
+            })
+            .unwrap_or_default();
+        let bundle = Arc::new(bundle);
+        self.bundles.lock().unwrap().insert(language.to_string(), bundle.clone());
+        bundle
+    }
+
+    /// The bundle for a locale: `pt-BR` uses `pt-BR` if there is one, else `pt`,
+    /// else the fallback language
+    pub fn resolve(&self, locale: &str) -> String {
+        let available = self.languages();
+        let tag = normalize(locale).unwrap_or_else(|| FALLBACK_LANGUAGE.to_string());
+        let language = tag.split('-').next().unwrap_or(FALLBACK_LANGUAGE).to_string();
+        [tag, language]
+            .into_iter()
+            .find(|candidate| available.contains(candidate))
+            .unwrap_or_else(|| FALLBACK_LANGUAGE.to_string())
+    }
+
+    /// Every message for a locale
+    pub fn translations(&self, locale: &str) -> Translations {
+        let language = self.resolve(locale);
+        let mut messages = (*self.bundle(FALLBACK_LANGUAGE)).clone();
+        if language != FALLBACK_LANGUAGE {
+            messages.extend(self.bundle(&language).iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Translations { language, messages }
+    }
+
+    /// One message with its `{name}` placeholders filled in; the key itself when
+    /// no bundle has it
+    pub fn message(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+        let language = self.resolve(locale);
+        let text = self
+            .bundle(&language)
+            .get(key)
+            .or_else(|| self.bundle(FALLBACK_LANGUAGE).get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string());
+        args.iter()
+            .fold(text, |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+    }
+
+    pub fn locale_info(&self, app_handle: &AppHandle) -> LocaleInfo {
+        let locale = effective_locale(app_handle);
+        let formatter = Formatter::new(&locale);
+        LocaleInfo {
+            system: system_locale(),
+            language: self.resolve(&locale),
+            hour12: formatter.hour12,
+            decimal_separator: formatter.decimal_separator,
+            grouping_separator: formatter.grouping_separator.to_string(),
+            available_languages: self.languages(),
+            locale: formatter.locale,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::Serialize;
+use std::io::Cursor;
+
+use crate::blob_store;
+
+/// Default longest side, in pixels; larger images are scaled down
+const DEFAULT_MAX_DIMENSION: u32 = 1568;
+
+/// Default largest encoded image. Base64 grows it by a third, so this stays under
+/// the 5 MB per-image limit common to vision APIs.
+const DEFAULT_MAX_BYTES: usize = 3_750_000;
+
+/// Largest file read for ingestion
+const MAX_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// JPEG quality for re-encoded images, and the lowest it's lowered to for size
+const JPEG_QUALITY: u8 = 85;
+const MIN_JPEG_QUALITY: u8 = 55;
+
+/// Images are never scaled below this to meet the byte limit
+const MIN_DIMENSION: u32 = 64;
+
+/// Size limits applied to ingested images
+#[derive(Debug, Clone, Copy)]
+pub struct ImageLimits {
+    pub max_dimension: u32,
+    pub max_bytes: usize,
+}
+
+impl ImageLimits {
+    /// `CHIMERA_IMAGE_MAX_DIMENSION` and `CHIMERA_IMAGE_MAX_BYTES`, overridden per call
+    pub fn resolve(max_dimension: Option<u32>, max_bytes: Option<usize>) -> Self {
+        fn env<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        Self {
+            max_dimension: max_dimension
+                .or_else(|| env("CHIMERA_IMAGE_MAX_DIMENSION"))
+                .unwrap_or(DEFAULT_MAX_DIMENSION)
+                .max(MIN_DIMENSION),
+            max_bytes: max_bytes
+                .or_else(|| env("CHIMERA_IMAGE_MAX_BYTES"))
+                .unwrap_or(DEFAULT_MAX_BYTES),
+        }
+    }
+}
+
+/// An image stored as an attachment, ready to send to a vision model
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestedImage {
+    /// Attachment stub to put in an event; fetch it again with `get_attachment`
+    pub attachment: serde_json::Value,
+    pub hash: String,
+    pub media_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: usize,
+    /// Base64 of the encoded image
+    pub data: String,
+    /// Whether the image was scaled or re-encoded to fit the limits
+    pub resized: bool,
+}
+
+fn media_type(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "image/png",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::WebP => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn encode(image: &DynamicImage, quality: u8) -> Result<(Vec<u8>, ImageFormat), String> {
+    let mut encoded = Cursor::new(Vec::new());
+    // Transparency needs PNG; everything else is smaller as JPEG
+    if image.color().has_alpha() {
+        image
+            .write_to(&mut encoded, ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        Ok((encoded.into_inner(), ImageFormat::Png))
+    } else {
+        image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, quality))
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        Ok((encoded.into_inner(), ImageFormat::Jpeg))
+    }
+}
+
+/// Scale `image` to fit `limits` and encode it, lowering JPEG quality and then
+/// the size until it's small enough
+fn fit(image: &DynamicImage, limits: ImageLimits) -> Result<(Vec<u8>, ImageFormat, u32, u32), String> {
+    let mut image = if image.width().max(image.height()) > limits.max_dimension {
+        image.resize(limits.max_dimension, limits.max_dimension, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+    let mut quality = JPEG_QUALITY;
+
+    loop {
+        let (encoded, format) = encode(&image, quality)?;
+        if encoded.len() <= limits.max_bytes {
+            return Ok((encoded, format, image.width(), image.height()));
+        }
+
+        if format == ImageFormat::Jpeg && quality > MIN_JPEG_QUALITY {
+            quality = quality.saturating_sub(10).max(MIN_JPEG_QUALITY);
+            continue;
+        }
+        let longest = image.width().max(image.height()) * 3 / 4;
+        if longest < MIN_DIMENSION {
+            return Err(format!("Image can't be made smaller than {} bytes", limits.max_bytes));
+        }
+        image = image.resize(longest, longest, FilterType::Triangle);
+    }
+}
+
+/// Store the encoded image as an attachment blob and describe it
+fn store(encoded: Vec<u8>, format: ImageFormat, width: u32, height: u32, resized: bool) -> Result<IngestedImage, String> {
+    let media_type = media_type(format).to_string();
+    let data = BASE64.encode(&encoded);
+    let blob = serde_json::json!({
+        "type": "image",
+        "mediaType": media_type,
+        "width": width,
+        "height": height,
+        "data": data,
+    });
+    let json = serde_json::to_vec(&blob).map_err(|e| format!("Failed to serialize image: {}", e))?;
+    let (hash, _) = blob_store::put_bytes(&json)?;
+
+    let attachment = serde_json::json!({
+        blob_store::ATTACHMENT_KEY: hash,
+        "bytes": json.len(),
+        "preview": format!("[{} {}x{}]", media_type, width, height),
+        "mediaType": media_type,
+        "width": width,
+        "height": height,
+    });
+    log::info!("Ingested {}x{} {} image as attachment {} ({} bytes)", width, height, media_type, hash, encoded.len());
+
+    Ok(IngestedImage {
+        attachment,
+        hash,
+        media_type,
+        width,
+        height,
+        bytes: encoded.len(),
+        data,
+        resized,
+    })
+}
+
+/// Ingest an image file, keeping the original bytes when it already fits (blocking)
+pub fn ingest_file(path: &str, limits: ImageLimits) -> Result<IngestedImage, String> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .len();
+    if size > MAX_SOURCE_BYTES {
+        return Err(format!("Image is too large to ingest ({} bytes)", size));
+    }
+    let original = std::fs::read(path).map_err(|e| format!("Failed to read image: {}", e))?;
+
+    let reader = ImageReader::new(Cursor::new(&original))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    let format = reader.format().ok_or("Unrecognized image format")?;
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    // Models don't read EXIF, so a rotated photo has to be re-encoded upright
+    let passthrough = matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP)
+        && orientation == Orientation::NoTransforms
+        && image.width().max(image.height()) <= limits.max_dimension
+        && original.len() <= limits.max_bytes;
+    if passthrough {
+        let (width, height) = (image.width(), image.height());
+        return store(original, format, width, height, false);
+    }
+
+    image.apply_orientation(orientation);
+    let (encoded, format, width, height) = fit(&image, limits)?;
+    store(encoded, format, width, height, true)
+}
+
+/// Ingest the image on the clipboard (blocking)
+pub fn ingest_clipboard(limits: ImageLimits) -> Result<IngestedImage, String> {
+    let pasted = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to open the clipboard: {}", e))?
+        .get_image()
+        .map_err(|e| match e {
+            arboard::Error::ContentNotAvailable => "The clipboard has no image".to_string(),
+            e => format!("Failed to read the clipboard: {}", e),
+        })?;
+
+    let pixels = image::RgbaImage::from_raw(pasted.width as u32, pasted.height as u32, pasted.bytes.into_owned())
+        .ok_or("Clipboard image has an unexpected size")?;
+    let mut image = DynamicImage::ImageRgba8(pixels);
+    // Screenshots usually come through with an opaque alpha channel; drop it so
+    // they can be sent as JPEG
+    if image.as_rgba8().is_some_and(|pixels| pixels.pixels().all(|p| p[3] == u8::MAX)) {
+        image = DynamicImage::ImageRgb8(image.to_rgb8());
+    }
+
+    let (encoded, format, width, height) = fit(&image, limits)?;
+    store(encoded, format, width, height, true)
+}
@@ -0,0 +1,89 @@
+/// Ties a child process and everything it starts to the app's lifetime. On
+/// Windows the process is put in a kill-on-close Job Object, so when the app
+/// exits or crashes and the job handle closes, the whole process tree dies with
+/// it; dropping the `JobObject` does the same. Elsewhere this does nothing: Linux
+/// children get PDEATHSIG and the backend watches its stdin pipe.
+pub struct JobObject {
+    #[cfg(windows)]
+    handle: windows::Win32::Foundation::HANDLE,
+}
+
+// The handle is only used through thread-safe kernel calls
+#[cfg(windows)]
+unsafe impl Send for JobObject {}
+#[cfg(windows)]
+unsafe impl Sync for JobObject {}
+
+impl JobObject {
+    /// Put process `pid` in a new kill-on-close job. Processes it has already
+    /// started stay outside the job, so call this straight after spawning.
+    #[cfg(windows)]
+    pub fn for_process(pid: u32) -> Result<Self, String> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+        use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+        unsafe {
+            let handle = CreateJobObjectW(None, PCWSTR::null()).map_err(|e| format!("Failed to create job object: {}", e))?;
+            // Closes the handle if anything below fails
+            let job = Self { handle };
+
+            let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job.handle,
+                JobObjectExtendedLimitInformation,
+                &limits as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+            .map_err(|e| format!("Failed to configure job object: {}", e))?;
+
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)
+                .map_err(|e| format!("Failed to open process {}: {}", pid, e))?;
+            let assigned = AssignProcessToJobObject(job.handle, process);
+            let _ = CloseHandle(process);
+            assigned.map_err(|e| format!("Failed to add process {} to job object: {}", pid, e))?;
+
+            Ok(job)
+        }
+    }
+
+    #[cfg(not(windows))]
+    pub fn for_process(_pid: u32) -> Result<Self, String> {
+        Ok(Self {})
+    }
+
+    /// Kill every process in the job
+    #[cfg(windows)]
+    pub fn terminate(&self) {
+        unsafe {
+            if let Err(e) = windows::Win32::System::JobObjects::TerminateJobObject(self.handle, 1) {
+                log::warn!("Failed to terminate job object: {}", e);
+            }
+        }
+    }
+}
+
+/// A job for `pid`, logging instead of failing: the process still runs without one
+pub fn attach(pid: Option<u32>, name: &str) -> Option<JobObject> {
+    match JobObject::for_process(pid?) {
+        Ok(job) => Some(job),
+        Err(e) => {
+            log::warn!("{} won't be stopped with the app if it crashes: {}", name, e);
+            None
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = windows::Win32::Foundation::CloseHandle(self.handle);
+        }
+    }
+}
@@ -1,11 +1,148 @@
 mod python_backend;
+mod backend_manager;
+mod backend_env;
+mod backend_availability;
+mod backend_events;
+mod stale_backend;
+mod process_stats;
+mod logging;
+mod accessibility;
+mod backend_grpc;
+mod backend_proxy;
+mod backend_queue;
+mod error;
 mod filesystem;
+mod append_buffer;
+mod blueprint_cache;
+mod blueprint_schema;
+mod blueprint_history;
+mod blueprint_variables;
+mod image_ingest;
+mod clipboard;
+mod settings;
+mod i18n;
+mod shortcuts;
+mod notifications;
+mod secrets;
+mod updater;
+mod diagnostics;
+mod storage_stats;
+mod storage_integrity;
+mod builtin_blueprints;
+mod attachments;
+mod deeplink;
+mod share_bundle;
+mod spellcheck;
+mod supervisor;
+mod sync;
+mod tasks;
+mod thread_index;
+mod viewer;
+mod thread_tail;
+mod thread_summary;
+mod thread_stats;
+mod window_state;
+mod drafts;
+mod hooks;
+mod blob_store;
+mod compaction;
+mod redaction;
+mod event_history;
+mod quarantine;
+mod scratch;
+mod snapshots;
+mod storage;
+mod backups;
+mod workspace_archive;
+mod workspaces;
+mod timeline;
+mod export;
+mod batch;
+mod provenance;
+mod event_schema;
+mod thread_compression;
+mod usage;
+mod run_queue;
+mod scheduler;
+mod run_recovery;
 mod terminal_backend;
+mod terminal_keys;
+mod terminal_links;
+mod terminal_env;
+mod terminal_guard;
+mod terminal_commands;
+mod terminal_profiles;
+mod event_bus;
+mod app_windows;
+mod headless;
+mod control_socket;
+mod test_harness;
+mod mock_backend;
+mod telemetry;
+mod metrics;
+mod perf_check;
+mod doctor;
+mod editor;
+mod cleanup;
+mod audit;
+mod permissions;
+mod fs_tools;
+mod fs_watcher;
+mod job_object;
+mod resync;
+mod webhooks;
+mod plugins;
+mod semantic_search;
+mod share_session;
+mod automation;
+mod obsidian;
+mod dictation;
+mod companion;
+#[cfg(target_os = "macos")]
+mod macos_share;
 
 use std::sync::Arc;
+use tauri::ipc::Channel;
 use tauri::{Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
 use python_backend::PythonBackend;
-use terminal_backend::TerminalBackend;
+use backend_availability::BackendAvailability;
+use backend_manager::BackendManager;
+use process_stats::ProcessMonitor;
+use terminal_backend::{SpawnOptions, TerminalBackend};
+use terminal_guard::{GuardConfig, PendingWrite, TerminalGuard};
+use terminal_commands::{CommandAllowlist, CommandSpec, TerminalCommands};
+use event_bus::{BusEvent, EventBus};
+use webhooks::{DeliveryRecord, WebhookInfo, WebhookManager};
+use hooks::HookRunner;
+use sync::SyncEngine;
+use window_state::WindowStateStore;
+use drafts::DraftStore;
+use plugins::{PluginHost, PluginManifest};
+use semantic_search::{IndexStats, SearchResult, SemanticIndex};
+use share_session::{ShareSessionInfo, ShareSessionManager};
+use obsidian::{ObsidianConfig, ObsidianExporter, ObsidianSyncStats};
+use dictation::DictationManager;
+use spellcheck::{Misspelling, Spellchecker};
+use accessibility::{AccessibilityMonitor, AccessibilityPrefs};
+use companion::{CompanionDevice, CompanionServer, CompanionStatus, PairingInfo};
+use append_buffer::AppendBuffer;
+use backend_proxy::BackendProxy;
+use mock_backend::MockBackend;
+use backend_queue::BackendQueue;
+use blueprint_cache::BlueprintCache;
+use settings::SettingsStore;
+use i18n::I18n;
+use supervisor::TaskGroup;
+use tasks::TaskManager;
+use thread_index::ThreadIndex;
+use thread_tail::{ThreadTailEvent, ThreadTails};
+use usage::UsageLedger;
+use run_queue::{QueuedRun, RunQueue};
+use export::ExporterRegistry;
+use batch::{BatchManager, BatchOp, BatchStatus};
+use permissions::{Permissions, RootGrant};
+use error::ChimeraError;
 use filesystem::{BlueprintMetadata, ThreadMetadata};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -15,123 +152,3291 @@ fn greet(name: &str) -> String {
 }
 
 // Filesystem commands
+/// Check the data directory again: whether it can be written and synced, and
+/// whether it ignores case. Problems come with hints for the user.
 #[tauri::command]
-async fn init_filesystem() -> Result<(), String> {
-    filesystem::init_filesystem().await
+async fn get_filesystem_capabilities() -> filesystem::FsCapabilities {
+    filesystem::probe_data_dir().await
 }
 
 #[tauri::command]
-async fn list_blueprints() -> Result<Vec<BlueprintMetadata>, String> {
-    filesystem::list_blueprints().await
+#[tracing::instrument(err)]
+async fn init_filesystem() -> Result<(), ChimeraError> {
+    Ok(filesystem::init_filesystem().await?)
 }
 
 #[tauri::command]
-async fn create_thread(blueprint_json: String) -> Result<String, String> {
-    filesystem::create_thread(blueprint_json).await
+#[tracing::instrument(skip_all, err)]
+async fn list_blueprints(
+    cache: tauri::State<'_, Arc<BlueprintCache>>,
+) -> Result<Vec<BlueprintMetadata>, ChimeraError> {
+    Ok(cache.list().await?)
 }
 
 #[tauri::command]
-async fn load_thread(thread_id: String) -> Result<Vec<serde_json::Value>, String> {
-    filesystem::load_thread(thread_id).await
+#[tracing::instrument(skip_all, fields(thread_id), err)]
+async fn create_thread(
+    blueprint_json: String,
+    app: tauri::AppHandle,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+    hooks: tauri::State<'_, Arc<HookRunner>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("create threads")?;
+    let thread_id = start_thread(&app, blueprint_json, &bus, &webhooks, &hooks).await?;
+    tracing::Span::current().record("thread_id", thread_id.as_str());
+    Ok(thread_id)
+}
+
+/// Write a new thread from its blueprint and announce it
+async fn start_thread(
+    app: &tauri::AppHandle,
+    blueprint_json: String,
+    bus: &EventBus,
+    webhooks: &WebhookManager,
+    hooks: &HookRunner,
+) -> Result<String, ChimeraError> {
+    let header = provenance::header(app, &blueprint_json).await;
+    let thread_id = filesystem::create_thread_with_header(blueprint_json, &header).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "created" }));
+    webhooks.dispatch(webhooks::EVENT_THREAD_CREATED, serde_json::json!({ "thread_id": thread_id }));
+    hooks.dispatch(webhooks::EVENT_THREAD_CREATED, serde_json::json!({ "thread_id": thread_id }));
+    Ok(thread_id)
+}
+
+/// The `{{name}}` placeholders in a blueprint file, to ask for before creating a
+/// thread from it
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_blueprint_variables(path: String) -> Result<Vec<blueprint_variables::BlueprintVariable>, ChimeraError> {
+    let content = filesystem::read_blueprint(path).await?;
+    let blueprint: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| ChimeraError::InvalidInput(format!("Failed to parse blueprint JSON: {}", e)))?;
+    Ok(blueprint_variables::extract(&blueprint))
+}
+
+/// Create a thread from a blueprint file with its placeholders filled in from `vars`
+#[tauri::command]
+#[tracing::instrument(skip(vars, app, bus, webhooks, hooks), fields(thread_id), err)]
+async fn create_thread_with_variables(
+    blueprint_path: String,
+    vars: std::collections::HashMap<String, String>,
+    app: tauri::AppHandle,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+    hooks: tauri::State<'_, Arc<HookRunner>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("create threads")?;
+    let content = filesystem::read_blueprint(blueprint_path).await?;
+    let blueprint_json = blueprint_variables::substitute(&content, &vars).map_err(ChimeraError::InvalidInput)?;
+    let thread_id = start_thread(&app, blueprint_json, &bus, &webhooks, &hooks).await?;
+    tracing::Span::current().record("thread_id", thread_id.as_str());
+    Ok(thread_id)
+}
+
+/// Branch a thread: a new thread with the same blueprint and the events up to
+/// and including `up_to_event_index` (0 is the blueprint line). Returns its id.
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn fork_thread(
+    thread_id: String,
+    up_to_event_index: usize,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("fork threads")?;
+    appends.flush(&thread_id).await?;
+    let id = thread_id.clone();
+    let fork_id = filesystem::blocking_fs(move || filesystem::fork_thread(&id, up_to_event_index)).await?;
+    bus.publish(
+        "thread-changed",
+        serde_json::json!({ "thread_id": fork_id, "change": "created", "parent_thread_id": thread_id }),
+    );
+    Ok(fork_id)
+}
+
+/// Copy a whole thread, with its attachments, under a new id. Returns the new id.
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn duplicate_thread(
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("duplicate threads")?;
+    appends.flush(&thread_id).await?;
+    let copy_id = filesystem::duplicate_thread(thread_id.clone()).await?;
+    bus.publish(
+        "thread-changed",
+        serde_json::json!({ "thread_id": copy_id, "change": "created", "parent_thread_id": thread_id }),
+    );
+    Ok(copy_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(bus, appends), err)]
+async fn load_thread(
+    thread_id: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<Vec<serde_json::Value>, ChimeraError> {
+    appends.flush(&thread_id).await?;
+
+    // Only very large (memory-mapped) threads report progress
+    let bus = bus.inner().clone();
+    let id = thread_id.clone();
+    Ok(filesystem::load_thread_with_progress(thread_id, move |bytes_parsed, total_bytes| {
+        bus.publish(
+            "thread-load-progress",
+            serde_json::json!({ "thread_id": id, "bytes_parsed": bytes_parsed, "total_bytes": total_bytes }),
+        );
+    })
+    .await?)
+}
+
+/// Send a thread's events to `on_chunk` in chunks of `chunk_size` (default 500) as
+/// they're read, instead of returning the whole history at once. Returns the
+/// number of events.
+#[tauri::command]
+#[tracing::instrument(skip(on_chunk, appends), err)]
+async fn stream_thread(
+    thread_id: String,
+    chunk_size: Option<usize>,
+    on_chunk: Channel<filesystem::ThreadChunk>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<usize, ChimeraError> {
+    appends.flush(&thread_id).await?;
+    let chunk_size = chunk_size.unwrap_or(filesystem::DEFAULT_STREAM_CHUNK_EVENTS);
+    Ok(filesystem::stream_thread(thread_id, chunk_size, |chunk| {
+        on_chunk
+            .send(chunk)
+            .map_err(|e| format!("Failed to send thread chunk: {}", e))
+    })
+    .await?)
+}
+
+/// Append events to a thread. Events whose `event_id` was already appended, e.g.
+/// by a retried call, are skipped; the outcome says which were written.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(thread_id = %thread_id, events = events.len()), err)]
+async fn append_thread_events(
+    thread_id: String,
+    events: Vec<serde_json::Value>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+    hooks: tauri::State<'_, Arc<HookRunner>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    usage: tauri::State<'_, Arc<UsageLedger>>,
+) -> Result<filesystem::AppendOutcome, ChimeraError> {
+    let sinks = AppendSinks { bus: &bus, webhooks: &webhooks, hooks: &hooks, appends: &appends, usage: &usage };
+    append_events(thread_id, events, false, sinks).await
+}
+
+/// `append_thread_events` for streaming output: the events are buffered and
+/// written with others for the thread a moment later, so the call never waits on
+/// the disk. `flush_thread` writes them now and reports a failed write.
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(thread_id = %thread_id, events = events.len()), err)]
+async fn queue_thread_events(
+    thread_id: String,
+    events: Vec<serde_json::Value>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+    hooks: tauri::State<'_, Arc<HookRunner>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    usage: tauri::State<'_, Arc<UsageLedger>>,
+) -> Result<filesystem::AppendOutcome, ChimeraError> {
+    let sinks = AppendSinks { bus: &bus, webhooks: &webhooks, hooks: &hooks, appends: &appends, usage: &usage };
+    append_events(thread_id, events, true, sinks).await
+}
+
+/// Write a thread's queued events now, keeping its file open for more
+#[tauri::command]
+#[tracing::instrument(skip(appends), err)]
+async fn flush_thread(thread_id: String, appends: tauri::State<'_, Arc<AppendBuffer>>) -> Result<(), ChimeraError> {
+    Ok(appends.flush(&thread_id).await?)
+}
+
+/// Everything told about appended events
+struct AppendSinks<'a> {
+    bus: &'a Arc<EventBus>,
+    webhooks: &'a WebhookManager,
+    hooks: &'a HookRunner,
+    appends: &'a Arc<AppendBuffer>,
+    usage: &'a UsageLedger,
+}
+
+/// Dedupe, validate and buffer events for a thread, then announce them. With
+/// `queued` a full buffer is left to the flush loop instead of written here.
+async fn append_events(
+    thread_id: String,
+    events: Vec<serde_json::Value>,
+    queued: bool,
+    sinks: AppendSinks<'_>,
+) -> Result<filesystem::AppendOutcome, ChimeraError> {
+    let AppendSinks { bus, webhooks, hooks, appends, usage } = sinks;
+    viewer::ensure_writable("append events")?;
+    let _write = filesystem::track_write(&thread_id);
+    // A retried call repeats events already written; those with a known event_id are dropped
+    let (events, outcome) = filesystem::dedupe_events(&thread_id, events).await?;
+    if events.is_empty() {
+        return Ok(outcome);
+    }
+    let ids: Vec<String> = events.iter().filter_map(filesystem::event_id).map(str::to_string).collect();
+    let events = match event_schema::validate(&thread_id, events) {
+        Ok(validated) => validated,
+        Err(e) => {
+            filesystem::forget_event_ids(&thread_id, &ids);
+            return Err(e.into());
+        }
+    };
+
+    // Note agent completion/error events before the events are consumed
+    let outcomes: Vec<(&str, serde_json::Value)> = events
+        .iter()
+        .filter_map(|event| match event.get("type").and_then(|t| t.as_str()) {
+            Some("finish") => Some((webhooks::EVENT_AGENT_FINISHED, event.clone())),
+            Some("error") => Some((webhooks::EVENT_AGENT_ERROR, event.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let buffered = if queued {
+        appends.queue(&thread_id, &events).await
+    } else {
+        appends.append(&thread_id, &events).await
+    };
+    if let Err(e) = buffered {
+        filesystem::forget_event_ids(&thread_id, &ids);
+        return Err(e.into());
+    }
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "appended" }));
+    usage.record(&thread_id, &events);
+
+    // Once a turn finishes its streaming deltas are no longer needed
+    if outcomes.iter().any(|(name, _)| *name == webhooks::EVENT_AGENT_FINISHED) {
+        let (appends, bus, thread_id) = (appends.clone(), bus.clone(), thread_id.clone());
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = compact_streaming(&thread_id, &appends, &bus).await {
+                log::warn!("Failed to compact streaming events in thread {}: {}", thread_id, e);
+            }
+        });
+    }
+
+    for (name, event) in outcomes {
+        let payload = serde_json::json!({ "thread_id": thread_id, "event": event });
+        hooks.dispatch(name, payload.clone());
+        webhooks.dispatch(name, payload);
+    }
+
+    Ok(outcome)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+async fn list_threads(
+    filter: Option<thread_index::ThreadFilter>,
+    app: tauri::AppHandle,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+) -> Result<Vec<ThreadMetadata>, ChimeraError> {
+    appends.flush_all().await;
+    let mut threads = index.list().await?;
+    let drafts = drafts.inner().clone();
+    let mut drafts = filesystem::blocking(move || Ok(drafts.all())).await?;
+    let formatter = i18n::Formatter::new(&i18n::effective_locale(&app));
+    for thread in &mut threads {
+        thread.draft = drafts.remove(&thread.thread_id);
+        thread.format_for(&formatter);
+    }
+    Ok(match filter {
+        Some(filter) => filter.apply(threads),
+        None => threads,
+    })
+}
+
+/// Events whose text contains every word of `query`, best matches first with
+/// SQLite storage
+#[tauri::command]
+#[tracing::instrument(skip(storage, appends), err)]
+async fn search_threads(
+    query: String,
+    limit: Option<usize>,
+    storage: tauri::State<'_, Arc<dyn storage::Storage>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<Vec<storage::SearchHit>, ChimeraError> {
+    appends.flush_all().await;
+    let storage = storage.inner().clone();
+    let limit = limit.unwrap_or(50).min(500);
+    Ok(filesystem::blocking(move || storage.search(&query, limit)).await?)
+}
+
+/// Copy every thread into the SQLite database and switch to it on the next launch
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, settings), err)]
+async fn migrate_storage_to_sqlite(
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<storage::MigrationReport, ChimeraError> {
+    viewer::ensure_writable("migrate storage")?;
+    appends.flush_all().await;
+    let report = filesystem::blocking(storage::migrate_to_sqlite).await?;
+    settings.update(&app, serde_json::json!({ "storage": storage::StorageKind::Sqlite }))?;
+    Ok(report)
+}
+
+/// Read `limit` of a thread's events starting at event `offset`, so long threads
+/// can be loaded a page at a time
+#[tauri::command]
+#[tracing::instrument(skip(index, appends), err)]
+async fn load_thread_page(
+    thread_id: String,
+    offset: usize,
+    limit: usize,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<thread_index::ThreadPage, ChimeraError> {
+    appends.flush(&thread_id).await?;
+    Ok(index.load_page(&thread_id, offset, limit).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(index, appends), err)]
+async fn get_thread_event_count(
+    thread_id: String,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<usize, ChimeraError> {
+    appends.flush(&thread_id).await?;
+    Ok(index.event_count(&thread_id).await?)
+}
+
+/// Discard `threads/index.json` and re-read every thread file, e.g. after editing
+/// threads outside the app. Runs as a cancellable task. Returns the number of threads.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+async fn rebuild_thread_index(
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    tasks: tauri::State<'_, Arc<TaskManager>>,
+) -> Result<usize, ChimeraError> {
+    appends.flush_all().await;
+    let index = index.inner().clone();
+    tasks
+        .run("index_rebuild", "Rebuilding the thread index", |progress| async move {
+            Ok(index.rebuild(&progress).await?)
+        })
+        .await
+}
+
+/// Message, tool call and token totals for a thread, overall and per agent, for a
+/// summary header that doesn't need the events
+#[tauri::command]
+#[tracing::instrument(skip(appends), err)]
+async fn get_thread_stats(
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<thread_stats::ThreadStats, ChimeraError> {
+    appends.flush(&thread_id).await?;
+    Ok(thread_stats::collect(thread_id).await?)
+}
+
+/// Chronological feed of what happened across all threads in `range`
+#[tauri::command]
+#[tracing::instrument(skip(index, appends), err)]
+async fn get_activity_timeline(
+    range: Option<timeline::TimelineRange>,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<Vec<timeline::TimelineEntry>, ChimeraError> {
+    appends.flush_all().await;
+    Ok(timeline::collect(&index, range.unwrap_or_default()).await?)
+}
+
+/// Token and cost totals for `range`, grouped by day, month, thread or overall
+#[tauri::command]
+#[tracing::instrument(skip(usage))]
+fn get_usage_report(
+    range: Option<usage::UsageRange>,
+    group_by: Option<usage::UsageGrouping>,
+    usage: tauri::State<'_, Arc<UsageLedger>>,
+) -> Vec<usage::UsageRow> {
+    usage.report(&range.unwrap_or_default(), group_by.unwrap_or_default())
+}
+
+/// Write any buffered events for a thread and close its file, e.g. when its
+/// view closes. `flush_thread` writes without closing.
+#[tauri::command]
+#[tracing::instrument(skip(appends), err)]
+async fn close_thread_writer(thread_id: String, appends: tauri::State<'_, Arc<AppendBuffer>>) -> Result<(), ChimeraError> {
+    Ok(appends.close(&thread_id).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, fields(thread_id = %thread_id), err)]
+async fn update_thread_title(
+    thread_id: String,
+    title: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("rename threads")?;
+    appends.flush(&thread_id).await?;
+    filesystem::update_thread_title(thread_id.clone(), title).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "title" }));
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(bus, appends), err)]
+async fn archive_thread(
+    thread_id: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("archive threads")?;
+    appends.flush(&thread_id).await?;
+    filesystem::set_thread_archived(thread_id.clone(), true).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "archived" }));
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(bus, appends), err)]
+async fn unarchive_thread(
+    thread_id: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("unarchive threads")?;
+    appends.flush(&thread_id).await?;
+    filesystem::set_thread_archived(thread_id.clone(), false).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "unarchived" }));
+    Ok(())
+}
+
+/// Replace a thread's tags, returning them as saved
+#[tauri::command]
+#[tracing::instrument(skip(bus, appends), err)]
+async fn set_thread_tags(
+    thread_id: String,
+    tags: Vec<String>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<Vec<String>, ChimeraError> {
+    viewer::ensure_writable("tag threads")?;
+    appends.flush(&thread_id).await?;
+    let tags = filesystem::set_thread_tags(thread_id.clone(), tags).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "tagged" }));
+    Ok(tags)
+}
+
+/// Move a thread to the trash
+#[tauri::command]
+#[tracing::instrument(skip(bus, appends), err)]
+async fn delete_thread(
+    thread_id: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+) -> Result<filesystem::TrashedThread, ChimeraError> {
+    viewer::ensure_writable("delete threads")?;
+    appends.close(&thread_id).await?;
+    let tombstone = filesystem::delete_thread(thread_id.clone(), "ui").await?;
+    if let Err(e) = drafts.clear(&thread_id).await {
+        log::warn!("Failed to remove the draft of deleted thread {}: {}", thread_id, e);
+    }
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "deleted" }));
+    Ok(tombstone)
+}
+
+/// Delete, archive, tag or export many threads in the background. Returns the
+/// batch id; progress arrives as `batch-progress` and `batch-finished` events.
+#[tauri::command]
+#[tracing::instrument(skip(thread_ids, app, batches, permissions), fields(threads = thread_ids.len()), err)]
+async fn batch_thread_operation(
+    op: BatchOp,
+    thread_ids: Vec<String>,
+    app: tauri::AppHandle,
+    batches: tauri::State<'_, Arc<BatchManager>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<String, ChimeraError> {
+    match &op {
+        BatchOp::Export { dest_dir, .. } => {
+            authorize_dir(&app, &permissions, dest_dir.into(), permissions::Operation::Export).await?;
+        }
+        _ => viewer::ensure_writable("change threads")?,
+    }
+    Ok(batches.start(op, thread_ids)?)
+}
+
+/// Check the user has allowed `operation` in `dir`, asking them if it's outside
+/// the data directory and not yet approved
+async fn authorize_dir(
+    app: &tauri::AppHandle,
+    permissions: &Arc<Permissions>,
+    dir: std::path::PathBuf,
+    operation: permissions::Operation,
+) -> Result<(), String> {
+    let (app, permissions) = (app.clone(), permissions.clone());
+    filesystem::blocking(move || permissions.authorize(&app, &dir, operation).map(|_| ())).await
+}
+
+/// The directory a file will be read from or written to
+fn parent_dir(path: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(path);
+    path.parent().unwrap_or(path).to_path_buf()
+}
+
+// Settings commands
+#[tauri::command]
+fn get_settings(settings: tauri::State<'_, Arc<SettingsStore>>) -> settings::AppSettings {
+    settings.get()
+}
+
+/// Zoom the calling window's webview, remembered with its size and position
+#[tauri::command]
+#[tracing::instrument(skip(window, window_state), err)]
+fn set_window_zoom(
+    zoom: f64,
+    window: tauri::WebviewWindow,
+    window_state: tauri::State<'_, Arc<WindowStateStore>>,
+) -> Result<(), ChimeraError> {
+    window_state.set_zoom(&window, zoom).map_err(ChimeraError::InvalidInput)
+}
+
+/// Set the theme to `light`, `dark` or `system` (follow the OS), for the native
+/// window chrome and, through `theme-changed`, the webview
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+fn set_theme(
+    mode: String,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<settings::ThemeState, ChimeraError> {
+    if !["system", "light", "dark"].contains(&mode.as_str()) {
+        return Err(ChimeraError::InvalidInput(format!("Unknown theme: {}", mode)));
+    }
+    settings.update(&app, serde_json::json!({ "theme": mode }))?;
+    Ok(settings.theme(&app))
+}
+
+/// The theme setting and whether it comes to light or dark
+#[tauri::command]
+fn get_theme(app: tauri::AppHandle, settings: tauri::State<'_, Arc<SettingsStore>>) -> settings::ThemeState {
+    settings.theme(&app)
+}
+
+/// The OS locale, the one in effect after the `locale` setting, and how it
+/// writes numbers and times
+#[tauri::command]
+fn get_system_locale(app: tauri::AppHandle, i18n: tauri::State<'_, Arc<I18n>>) -> i18n::LocaleInfo {
+    i18n.locale_info(&app)
+}
+
+/// UI messages for `lang`, or for the locale in effect when none is given.
+/// Keys missing from its bundle come from the English one.
+#[tauri::command]
+fn get_translations(
+    lang: Option<String>,
+    app: tauri::AppHandle,
+    i18n: tauri::State<'_, Arc<I18n>>,
+) -> i18n::Translations {
+    i18n.translations(&lang.unwrap_or_else(|| i18n::effective_locale(&app)))
+}
+
+/// Merge `patch` into the settings. Changes to `data_dir`, `backend_port` and
+/// `storage` apply on the next launch.
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+fn update_settings(
+    patch: serde_json::Value,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<settings::AppSettings, ChimeraError> {
+    Ok(settings.update(&app, patch)?)
+}
+
+/// Bind a global shortcut, or unbind it with no accelerator. The shortcut is
+/// registered before it's saved, so one another app holds is reported and not kept.
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+fn set_global_shortcut(
+    action: shortcuts::ShortcutAction,
+    accelerator: Option<String>,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<settings::AppSettings, ChimeraError> {
+    let current = settings.get().shortcuts;
+    let mut updated = current.clone();
+    match action {
+        shortcuts::ShortcutAction::Summon => updated.summon = accelerator,
+        shortcuts::ShortcutAction::NewMessage => updated.new_message = accelerator,
+    }
+    if let Err(e) = shortcuts::apply(&app, &updated) {
+        let _ = shortcuts::apply(&app, &current);
+        return Err(ChimeraError::InvalidInput(e));
+    }
+    let patch = serde_json::to_value(&updated).map_err(|e| format!("Failed to serialize shortcuts: {}", e))?;
+    Ok(settings.update(&app, serde_json::json!({ "shortcuts": patch }))?)
+}
+
+/// Show a desktop notification. With `thread_id`, focusing the app soon after
+/// sends the main window a `focus-thread` event for that thread.
+#[tauri::command]
+#[tracing::instrument(skip(notifier), err)]
+fn notify_user(
+    title: String,
+    body: String,
+    thread_id: Option<String>,
+    notifier: tauri::State<'_, Arc<notifications::Notifier>>,
+) -> Result<(), ChimeraError> {
+    Ok(notifier.notify(&title, &body, thread_id)?)
+}
+
+// Secrets commands
+
+/// Store a secret in the OS keychain. With `inject_into_backend`, backends started
+/// from now on get it as an environment variable of the same name.
+#[tauri::command]
+#[tracing::instrument(skip(value), err)]
+async fn set_secret(
+    name: String,
+    value: String,
+    inject_into_backend: Option<bool>,
+) -> Result<secrets::SecretInfo, ChimeraError> {
+    viewer::ensure_writable("store secrets")?;
+    let info = filesystem::blocking({
+        let name = name.clone();
+        move || secrets::set(&name, &value, inject_into_backend.unwrap_or(false))
+    })
+    .await?;
+    audit::record_async(audit::AuditEntry::new("secrets", "set", "ui", name, true)).await;
+    Ok(info)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_secret(name: String) -> Result<Option<String>, ChimeraError> {
+    let value = filesystem::blocking({
+        let name = name.clone();
+        move || secrets::get(&name)
+    })
+    .await?;
+    audit::record_async(audit::AuditEntry::new("secrets", "get", "ui", name, true)).await;
+    Ok(value)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn delete_secret(name: String) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("delete secrets")?;
+    filesystem::blocking({
+        let name = name.clone();
+        move || secrets::delete(&name)
+    })
+    .await?;
+    audit::record_async(audit::AuditEntry::new("secrets", "delete", "ui", name, true)).await;
+    Ok(())
+}
+
+/// Names of stored secrets and whether each goes to the backend; never their values
+#[tauri::command]
+async fn list_secret_names() -> Result<Vec<secrets::SecretInfo>, ChimeraError> {
+    Ok(filesystem::blocking(secrets::list).await?)
+}
+
+// Updater commands
+
+/// Look for an update on the channel in settings. Also publishes `update-available`
+/// the first time a version is found.
+#[tauri::command]
+#[tracing::instrument(skip(updater), err)]
+async fn check_for_updates(
+    updater: tauri::State<'_, Arc<updater::Updater>>,
+) -> Result<Option<updater::UpdateInfo>, ChimeraError> {
+    Ok(updater.check().await?)
+}
+
+/// Download the update found by the last check, publishing `update-download-progress`
+/// and then `update-downloaded`
+#[tauri::command]
+#[tracing::instrument(skip(updater), err)]
+async fn download_update(updater: tauri::State<'_, Arc<updater::Updater>>) -> Result<updater::UpdateInfo, ChimeraError> {
+    viewer::ensure_writable("download updates")?;
+    Ok(updater.download().await?)
+}
+
+/// Stop terminals and Python backends, install the downloaded update and relaunch
+#[tauri::command]
+#[tracing::instrument(skip(updater), err)]
+async fn install_update_and_restart(updater: tauri::State<'_, Arc<updater::Updater>>) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("install updates")?;
+    audit::record_async(audit::AuditEntry::new("updates", "install", "ui", "", true)).await;
+    Ok(updater.install_and_restart().await?)
+}
+
+// Diagnostics commands
+
+/// Zip app and backend logs, redacted settings, system details, the end of each
+/// terminal's scrollback and pending crash reports for a bug report. Written to
+/// `dest_zip`, or under the data directory when unset.
+#[tauri::command]
+#[tracing::instrument(skip(app, settings, terminals, permissions), err)]
+async fn generate_diagnostics_bundle(
+    dest_zip: Option<String>,
+    scrollback_lines: Option<usize>,
+    include_crash_reports: Option<bool>,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    terminals: tauri::State<'_, Arc<TerminalBackend>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<diagnostics::DiagnosticsBundle, ChimeraError> {
+    if let Some(dest_zip) = &dest_zip {
+        authorize_dir(&app, &permissions, parent_dir(dest_zip), permissions::Operation::Export).await?;
+    }
+    let inputs = diagnostics::BundleInputs {
+        app_log_dir: app.path().app_log_dir().ok(),
+        settings: serde_json::to_value(settings.get()).ok(),
+        terminals: terminals.snapshot().await,
+        scrollback_lines: scrollback_lines.unwrap_or(diagnostics::DEFAULT_SCROLLBACK_LINES),
+        include_crash_reports: include_crash_reports.unwrap_or(true),
+    };
+    Ok(filesystem::blocking(move || diagnostics::generate(dest_zip.map(std::path::PathBuf::from), inputs)).await?)
+}
+
+/// Crash reports from earlier runs, for offering to include them in a bundle
+#[tauri::command]
+async fn list_crash_reports() -> Result<Vec<diagnostics::CrashReport>, ChimeraError> {
+    Ok(filesystem::blocking(diagnostics::pending_crash_reports).await?)
+}
+
+/// Delete pending crash reports without sending them
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn dismiss_crash_reports() -> Result<usize, ChimeraError> {
+    Ok(filesystem::blocking(diagnostics::dismiss_crash_reports).await?)
+}
+
+/// How much disk the data directory uses: threads, blueprints, backups, indexes.
+/// Served from a cache kept current in the background unless `refresh` is set.
+#[tauri::command]
+#[tracing::instrument(skip(cache), err)]
+async fn get_storage_stats(
+    refresh: Option<bool>,
+    cache: tauri::State<'_, Arc<storage_stats::StorageStatsCache>>,
+) -> Result<storage_stats::StorageStats, ChimeraError> {
+    Ok(cache.get(refresh.unwrap_or(false)).await?)
+}
+
+/// Cross-reference thread events with stored attachments, blobs and thread
+/// metadata, reporting orphans, missing files and checksum mismatches
+#[tauri::command]
+#[tracing::instrument(skip(appends, drafts), err)]
+async fn verify_storage_integrity(
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+) -> Result<storage_integrity::IntegrityReport, ChimeraError> {
+    appends.flush_all().await;
+    let drafts = drafts.inner().clone();
+    Ok(filesystem::blocking(move || {
+        drafts.flush();
+        storage_integrity::verify()
+    })
+    .await?)
+}
+
+/// Remove what `verify_storage_integrity` reports as orphaned, or with `dry_run`
+/// list what would be removed
+#[tauri::command]
+#[tracing::instrument(skip(appends, drafts, index), err)]
+async fn gc_storage(
+    dry_run: bool,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+) -> Result<storage_integrity::GcReport, ChimeraError> {
+    if !dry_run {
+        viewer::ensure_writable("clean up storage")?;
+    }
+    appends.flush_all().await;
+    let drafts = drafts.inner().clone();
+    let report = filesystem::blocking(move || {
+        drafts.flush();
+        storage_integrity::collect(dry_run)
+    })
+    .await?;
+    if !dry_run && !report.removed.is_empty() {
+        audit::record_async(
+            audit::AuditEntry::new("storage", "gc", "ui", "", true)
+                .detail(format!("{} bytes freed", report.freed_bytes))
+                .paths(&report.removed),
+        )
+        .await;
+    }
+    // Listing again drops index entries for files that are gone and saves it
+    if !dry_run && report.stale_index_entries > 0 {
+        index.invalidate_all();
+        index.list().await?;
+    }
+    Ok(report)
+}
+
+/// The workspace being viewed when running read-only, or None
+#[tauri::command]
+fn get_viewer_mode() -> Option<viewer::ViewerMode> {
+    viewer::mode().cloned()
+}
+
+#[tauri::command]
+fn get_batch_operation(batch_id: String, batches: tauri::State<'_, Arc<BatchManager>>) -> Result<BatchStatus, ChimeraError> {
+    Ok(batches.get(&batch_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(batches), err)]
+fn cancel_batch_operation(batch_id: String, batches: tauri::State<'_, Arc<BatchManager>>) -> Result<(), ChimeraError> {
+    Ok(batches.cancel(&batch_id)?)
+}
+
+/// Bring a thread back from the trash
+#[tauri::command]
+#[tracing::instrument(skip(bus), err)]
+async fn restore_thread(thread_id: String, bus: tauri::State<'_, Arc<EventBus>>) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("restore threads")?;
+    filesystem::restore_thread(thread_id.clone()).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "restored" }));
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn list_trash() -> Result<Vec<filesystem::TrashedThread>, ChimeraError> {
+    Ok(filesystem::list_trash().await?)
+}
+
+/// Permanently remove threads trashed more than `older_than_days` ago
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn purge_trash(older_than_days: Option<i64>) -> Result<Vec<String>, ChimeraError> {
+    viewer::ensure_writable("purge the trash")?;
+    Ok(filesystem::purge_trash(older_than_days, "ui").await?)
+}
+
+// Snapshot commands
+/// Copy every thread and blueprint into a new workspace snapshot, as a cancellable task
+#[tauri::command]
+#[tracing::instrument(skip(appends, tasks), err)]
+async fn create_snapshot(
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    tasks: tauri::State<'_, Arc<TaskManager>>,
+) -> Result<snapshots::Snapshot, ChimeraError> {
+    viewer::ensure_writable("take snapshots")?;
+    appends.flush_all().await;
+    tasks
+        .run("backup", "Taking a snapshot", |progress| async move {
+            Ok(filesystem::blocking(move || snapshots::create("manual", Some(&progress))).await?)
+        })
+        .await
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn list_snapshots() -> Result<Vec<snapshots::Snapshot>, ChimeraError> {
+    Ok(filesystem::blocking(snapshots::list).await?)
+}
+
+/// Threads and blueprints added, removed or modified since a snapshot
+#[tauri::command]
+#[tracing::instrument(skip(appends), err)]
+async fn diff_snapshot(
+    snapshot_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<snapshots::SnapshotDiff, ChimeraError> {
+    appends.flush_all().await;
+    Ok(filesystem::blocking(move || snapshots::diff(&snapshot_id)).await?)
+}
+
+/// Put one thread back as it was in a snapshot
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn restore_thread_from_snapshot(
+    snapshot_id: String,
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("restore threads")?;
+    let id = thread_id.clone();
+    appends
+        .exclusive(&thread_id, filesystem::blocking(move || snapshots::restore_thread(&snapshot_id, &id)))
+        .await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "restored" }));
+    Ok(())
+}
+
+/// Backups, newest first. Backups are snapshots: scheduled ones, manual ones and
+/// those taken before a restore.
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn list_backups() -> Result<Vec<snapshots::Snapshot>, ChimeraError> {
+    Ok(filesystem::blocking(snapshots::list).await?)
+}
+
+/// Put every thread and blueprint back as it was in a backup. The current state
+/// is backed up first, so the restore can itself be undone.
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn restore_backup(
+    backup_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<snapshots::RestoreReport, ChimeraError> {
+    viewer::ensure_writable("restore backups")?;
+    appends.flush_all().await;
+    let before = filesystem::blocking(|| snapshots::create(backups::PRE_RESTORE, None)).await?;
+    log::info!("Backed up the workspace as {} before restoring {}", before.id, backup_id);
+
+    let id = backup_id.clone();
+    let restored = filesystem::blocking(move || snapshots::restore(&id)).await?;
+    let mut paths = Vec::new();
+    for thread_id in &restored.threads {
+        paths.extend(filesystem::get_thread_path(thread_id));
+    }
+    for blueprint_id in &restored.blueprints {
+        paths.extend(filesystem::get_blueprint_path(blueprint_id));
+    }
+    audit::record_async(
+        audit::AuditEntry::new("backups", "restore", "ui", backup_id, true)
+            .detail(format!("Workspace backed up first as {}", before.id))
+            .paths(paths),
+    )
+    .await;
+    for thread_id in &restored.threads {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "restored" }));
+    }
+    Ok(restored)
+}
+
+/// The environment snapshot recorded when a thread was created, if it has one
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_thread_provenance(thread_id: String) -> Result<Option<provenance::Provenance>, ChimeraError> {
+    let events = filesystem::load_thread(thread_id).await?;
+    Ok(provenance::find(&events))
+}
+
+/// Write a thread out in one of the formats from `list_export_formats`
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, exporters, permissions), err)]
+async fn export_thread(
+    thread_id: String,
+    format: String,
+    dest_path: String,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    exporters: tauri::State<'_, Arc<ExporterRegistry>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<export::ExportReport, ChimeraError> {
+    export_thread_with(&app, &appends, &exporters, &permissions, thread_id, &format, dest_path).await
+}
+
+/// `export_thread` for callers outside a command, e.g. automation: asks before
+/// writing outside the data directory and flushes buffered appends first
+async fn export_thread_with(
+    app: &tauri::AppHandle,
+    appends: &AppendBuffer,
+    exporters: &ExporterRegistry,
+    permissions: &Arc<Permissions>,
+    thread_id: String,
+    format: &str,
+    dest_path: String,
+) -> Result<export::ExportReport, ChimeraError> {
+    authorize_dir(app, permissions, parent_dir(&dest_path), permissions::Operation::Export).await?;
+    appends.flush(&thread_id).await?;
+    Ok(exporters.export_thread(thread_id, format, dest_path).await?)
+}
+
+/// Export a thread as a passphrase-encrypted bundle that can be sent anywhere
+/// and opened with `open_share_bundle`
+#[tauri::command]
+#[tracing::instrument(skip(passphrase, app, appends, permissions), err)]
+async fn create_share_bundle(
+    thread_id: String,
+    passphrase: String,
+    dest_path: String,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<share_bundle::ShareBundleInfo, ChimeraError> {
+    authorize_dir(&app, &permissions, parent_dir(&dest_path), permissions::Operation::Export).await?;
+    appends.flush(&thread_id).await?;
+    let events = filesystem::load_thread(thread_id.clone()).await?;
+    Ok(filesystem::blocking(move || share_bundle::create(&thread_id, &events, &passphrase, &dest_path)).await?)
+}
+
+/// Decrypt a share bundle and add its thread to this workspace
+#[tauri::command]
+#[tracing::instrument(skip(passphrase, app, bus, permissions), err)]
+async fn open_share_bundle(
+    path: String,
+    passphrase: String,
+    app: tauri::AppHandle,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<share_bundle::OpenedShareBundle, ChimeraError> {
+    viewer::ensure_writable("import threads")?;
+    authorize_dir(&app, &permissions, parent_dir(&path), permissions::Operation::Import).await?;
+    let opened = filesystem::blocking(move || share_bundle::open(&path, &passphrase)).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": opened.thread_id, "change": "created" }));
+    Ok(opened)
+}
+
+/// Package every thread, blueprint, attachment and the settings into a zip that
+/// `import_workspace` can load on another machine. Runs as a cancellable task.
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, settings, permissions, tasks), err)]
+async fn export_workspace(
+    dest_zip: String,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+    tasks: tauri::State<'_, Arc<TaskManager>>,
+) -> Result<workspace_archive::WorkspaceExport, ChimeraError> {
+    authorize_dir(&app, &permissions, parent_dir(&dest_zip), permissions::Operation::Export).await?;
+    appends.flush_all().await;
+    let settings = serde_json::to_value(settings.get()).ok();
+    let label = format!("Exporting the workspace to {}", dest_zip);
+    tasks
+        .run("workspace_export", label, |progress| async move {
+            Ok(filesystem::blocking(move || {
+                workspace_archive::export(std::path::Path::new(&dest_zip), settings, &progress)
+            })
+            .await?)
+        })
+        .await
+}
+
+/// Add a workspace archive to this one. `merge_strategy` decides what happens to
+/// ids that already exist: `keep_both` (default), `skip` or `overwrite`.
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, bus, settings, permissions), err)]
+async fn import_workspace(
+    src_zip: String,
+    merge_strategy: Option<workspace_archive::MergeStrategy>,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<workspace_archive::WorkspaceImport, ChimeraError> {
+    viewer::ensure_writable("import a workspace")?;
+    authorize_dir(&app, &permissions, parent_dir(&src_zip), permissions::Operation::Import).await?;
+    appends.flush_all().await;
+    let strategy = merge_strategy.unwrap_or_default();
+    let archive = src_zip.clone();
+    let imported =
+        filesystem::blocking(move || workspace_archive::import(std::path::Path::new(&archive), strategy)).await?;
+    let mut paths = Vec::new();
+    for thread_id in &imported.threads {
+        paths.extend(filesystem::get_thread_path(thread_id));
+    }
+    for blueprint_id in &imported.blueprints {
+        paths.extend(filesystem::get_blueprint_path(blueprint_id));
+    }
+    audit::record_async(
+        audit::AuditEntry::new("workspace", "import", "ui", src_zip, true)
+            .detail(format!(
+                "{:?}: {} threads, {} blueprints, {} skipped",
+                strategy,
+                imported.threads.len(),
+                imported.blueprints.len(),
+                imported.skipped.len()
+            ))
+            .paths(paths),
+    )
+    .await;
+
+    for thread_id in &imported.threads {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "created" }));
+    }
+    if let Some(patch) = imported.settings.clone() {
+        if let Err(e) = settings.update(&app, patch) {
+            log::warn!("Imported workspace settings weren't applied: {}", e);
+        }
+    }
+    Ok(imported)
+}
+
+/// Workspaces, each a separate data root, and which one is active
+#[tauri::command]
+#[tracing::instrument(err)]
+fn list_workspaces() -> Result<workspaces::WorkspaceList, ChimeraError> {
+    workspaces::list()
+}
+
+/// Add a workspace, in `data_dir` if given or a new directory otherwise. It
+/// isn't switched to.
+#[tauri::command]
+#[tracing::instrument(err)]
+fn create_workspace(name: String, data_dir: Option<String>) -> Result<workspaces::Workspace, ChimeraError> {
+    viewer::ensure_writable("create workspaces")?;
+    workspaces::create(&name, data_dir.as_deref())
+}
+
+/// Make another workspace the active one. Buffered writes go to the old one
+/// first; threads, blueprints and the file watcher then follow the new one.
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, bus, index, cache, settings), err)]
+async fn switch_workspace(
+    id: String,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    index: tauri::State<'_, Arc<ThreadIndex>>,
+    cache: tauri::State<'_, Arc<BlueprintCache>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<workspaces::WorkspaceChanged, ChimeraError> {
+    viewer::ensure_writable("switch workspaces")?;
+    let previous = filesystem::get_data_dir()?;
+    appends.flush_all().await;
+    filesystem::flush_pending_writes(filesystem::PENDING_WRITES_TIMEOUT).await?;
+
+    let workspace = workspaces::switch(&id)?;
+    filesystem::init_filesystem().await?;
+    index.reload().await;
+    if let Some(watcher) = app.try_state::<fs_watcher::FileWatcher>() {
+        if let Err(e) = watcher.watch_data_dir() {
+            log::warn!("Failed to watch workspace {}: {}", workspace.id, e);
+        }
+    }
+    cache.prime().await;
+
+    let changed = workspaces::WorkspaceChanged {
+        workspace,
+        previous_data_dir: previous.to_string_lossy().to_string(),
+        restart_required: settings.get().storage == storage::StorageKind::Sqlite,
+    };
+    bus.publish("workspace-changed", &changed);
+    bus.publish("thread-changed", serde_json::json!({ "change": "workspace" }));
+    bus.publish("blueprint-changed", serde_json::json!({ "change": "workspace" }));
+    Ok(changed)
+}
+
+/// Built-in and plugin-provided export formats
+#[tauri::command]
+fn list_export_formats(exporters: tauri::State<'_, Arc<ExporterRegistry>>) -> Vec<export::ExportFormatInfo> {
+    exporters.list()
+}
+
+/// Create (or reuse) a thread's temporary workspace, where agents may always write
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn create_scratch_dir(thread_id: String) -> Result<scratch::ScratchDir, ChimeraError> {
+    viewer::ensure_writable("create scratch directories")?;
+    Ok(filesystem::blocking(move || scratch::create(&thread_id)).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn list_scratch_dirs() -> Result<Vec<scratch::ScratchDir>, ChimeraError> {
+    Ok(filesystem::blocking(scratch::list).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn remove_scratch_dir(thread_id: String) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("remove scratch directories")?;
+    Ok(filesystem::blocking(move || scratch::remove(&thread_id)).await?)
+}
+
+/// Remember the unsent message in a thread's input, written shortly after the
+/// last call so it can be made on every keystroke
+#[tauri::command]
+#[tracing::instrument(skip(content, drafts), err)]
+fn save_draft(
+    thread_id: String,
+    content: String,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("save drafts")?;
+    Ok(drafts.save(&thread_id, content)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(drafts), err)]
+async fn get_draft(
+    thread_id: String,
+    drafts: tauri::State<'_, Arc<DraftStore>>,
+) -> Result<Option<String>, ChimeraError> {
+    Ok(drafts.get(&thread_id).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(drafts), err)]
+async fn clear_draft(thread_id: String, drafts: tauri::State<'_, Arc<DraftStore>>) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("clear drafts")?;
+    Ok(drafts.clear(&thread_id).await?)
+}
+
+/// Switch a thread to a new blueprint from its next turn on
+#[tauri::command]
+#[tracing::instrument(skip(blueprint_json, bus, appends), fields(thread_id = %thread_id), err)]
+async fn update_thread_blueprint(
+    thread_id: String,
+    blueprint_json: String,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("change thread blueprints")?;
+    appends.flush(&thread_id).await?;
+    filesystem::update_thread_blueprint(thread_id.clone(), blueprint_json).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "blueprint" }));
+    Ok(())
+}
+
+/// A thread's events with blueprint updates applied, ready to send to the backend
+#[tauri::command]
+#[tracing::instrument(skip(appends), err)]
+async fn get_thread_protocol(
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+) -> Result<Vec<serde_json::Value>, ChimeraError> {
+    appends.flush(&thread_id).await?;
+    Ok(filesystem::effective_thread_protocol(filesystem::load_thread(thread_id).await?))
+}
+
+/// Reduced motion, high contrast and font scale: the OS's, with the
+/// `accessibility` settings applied over them
+#[tauri::command]
+fn get_accessibility_prefs(accessibility: tauri::State<'_, Arc<AccessibilityMonitor>>) -> AccessibilityPrefs {
+    accessibility.current()
+}
+
+/// Health and restart count of the Python backend
+#[tauri::command]
+fn get_backend_status(app: tauri::AppHandle) -> Option<python_backend::BackendStatus> {
+    app.try_state::<Arc<PythonBackend>>().map(|backend| backend.status())
+}
+
+/// Whether the default backend is available, starting or unavailable
+#[tauri::command]
+fn get_backend_availability(availability: tauri::State<'_, Arc<BackendAvailability>>) -> backend_availability::Availability {
+    availability.get()
+}
+
+/// Wait up to `timeout_ms` (default 30s, at most 2 minutes) for the default backend
+/// to be available. Errors with the reason it isn't when time runs out.
+#[tauri::command]
+#[tracing::instrument(skip(availability), err)]
+async fn wait_for_backend(
+    timeout_ms: Option<u64>,
+    availability: tauri::State<'_, Arc<BackendAvailability>>,
+) -> Result<backend_availability::Availability, ChimeraError> {
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    Ok(availability.wait(timeout).await?)
+}
+
+/// Status of every running backend, the default workspace's first
+#[tauri::command]
+fn list_backends(backends: tauri::State<'_, Arc<BackendManager>>) -> Vec<python_backend::BackendStatus> {
+    backends.list()
+}
+
+/// Start a backend for a workspace, running in `cwd` if given, and return its URL.
+/// Returns the existing backend's URL if the workspace already has one.
+#[tauri::command]
+#[tracing::instrument(skip(backends), err)]
+async fn start_backend(
+    workspace_id: String,
+    cwd: Option<String>,
+    backends: tauri::State<'_, Arc<BackendManager>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("start a backend")?;
+    let config = python_backend::BackendConfig::new(workspace_id, cwd.map(std::path::PathBuf::from))?;
+    Ok(backends.start(config).await?.base_url())
+}
+
+/// Shut down a workspace's backend
+#[tauri::command]
+#[tracing::instrument(skip(backends, bus), err)]
+async fn stop_backend(
+    workspace_id: String,
+    backends: tauri::State<'_, Arc<BackendManager>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<(), ChimeraError> {
+    backends.stop(&workspace_id, Some(&bus)).await
+}
+
+/// The last `lines` lines of a workspace backend's output (default 200, default
+/// workspace). New lines arrive as `backend-log` events.
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn tail_backend_log(
+    lines: Option<usize>,
+    workspace_id: Option<String>,
+) -> Result<Vec<python_backend::BackendLogLine>, ChimeraError> {
+    let workspace_id = workspace_id.unwrap_or_else(|| python_backend::DEFAULT_WORKSPACE.to_string());
+    tauri::async_runtime::spawn_blocking(move || python_backend::tail_log(&workspace_id, lines.unwrap_or(200)))
+        .await
+        .map_err(|e| format!("Backend log task failed: {}", e))?
+}
+
+/// Delete a workspace backend's log and its rotated copies, returning how many
+/// files were removed
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn clear_backend_logs(workspace_id: Option<String>) -> Result<usize, ChimeraError> {
+    let workspace_id = workspace_id.unwrap_or_else(|| python_backend::DEFAULT_WORKSPACE.to_string());
+    tauri::async_runtime::spawn_blocking(move || python_backend::clear_logs(&workspace_id))
+        .await
+        .map_err(|e| format!("Backend log task failed: {}", e))?
+}
+
+/// Stop backend processes left running by an earlier session, sparing the live ones
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+async fn force_cleanup_backend(app: tauri::AppHandle) -> Result<stale_backend::StaleCleanup, ChimeraError> {
+    let live = match app.try_state::<Arc<BackendManager>>() {
+        Some(backends) => backends.pids().await,
+        None => Vec::new(),
+    };
+    Ok(filesystem::blocking(move || Ok(python_backend::cleanup_stale_backend(&live))).await?)
+}
+
+/// The environment the next backend to start will be given, with secret values
+/// and proxy passwords redacted
+#[tauri::command]
+#[tracing::instrument(skip(backends), err)]
+async fn get_backend_env_preview(
+    backends: tauri::State<'_, Arc<BackendManager>>,
+) -> Result<Vec<backend_env::BackendEnvVar>, ChimeraError> {
+    Ok(backend_env::redacted(&backends.env_vars().await?))
+}
+
+/// Start a backend with development-mode overrides that aren't saved yet, wait
+/// for it to come up and stop it, reporting the command line and any error
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn test_backend_command(
+    command: python_backend::BackendCommand,
+) -> Result<python_backend::BackendCommandTest, ChimeraError> {
+    viewer::ensure_writable("start a backend")?;
+    Ok(python_backend::test_command(command).await)
+}
+
+/// Restart the Python backend now
+#[tauri::command]
+#[tracing::instrument(skip(app, bus), err)]
+async fn restart_backend(app: tauri::AppHandle, bus: tauri::State<'_, Arc<EventBus>>) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("start the backend")?;
+    let backend = app
+        .try_state::<Arc<PythonBackend>>()
+        .ok_or_else(|| ChimeraError::BackendUnavailable("Python backend is not running".to_string()))?
+        .inner()
+        .clone();
+    backend.restart_now(&bus).await
+}
+
+/// Make a non-streaming call to the backend. While the backend is down or
+/// restarting the call is buffered and replayed once it's healthy, with
+/// `backend-queue-status` events reporting the queue.
+#[tauri::command]
+#[tracing::instrument(skip(app, body, queue), err)]
+async fn backend_request(
+    app: tauri::AppHandle,
+    method: Option<String>,
+    path: String,
+    body: Option<serde_json::Value>,
+    queue: tauri::State<'_, Arc<BackendQueue>>,
+) -> Result<serde_json::Value, ChimeraError> {
+    let method = method.unwrap_or_else(|| if body.is_some() { "POST" } else { "GET" }.to_string());
+    Ok(queue.request(&app, &method, path, body).await?)
+}
+
+#[tauri::command]
+fn get_backend_queue(queue: tauri::State<'_, Arc<BackendQueue>>) -> backend_queue::QueueStatus {
+    queue.status()
+}
+
+/// Address and token of the authenticated backend proxy the webview should use
+#[tauri::command]
+fn get_backend_proxy(proxy: tauri::State<'_, Arc<BackendProxy>>) -> backend_proxy::ProxyInfo {
+    proxy.info()
+}
+
+/// Send a request to the backend through the proxy, logging it
+#[tauri::command]
+#[tracing::instrument(skip(body, headers, proxy), err)]
+async fn proxy_request(
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    headers: Option<std::collections::HashMap<String, String>>,
+    proxy: tauri::State<'_, Arc<BackendProxy>>,
+) -> Result<backend_proxy::ProxyResponse, ChimeraError> {
+    Ok(proxy.request(&method, &path, body, headers.unwrap_or_default()).await?)
+}
+
+/// Recent proxied requests, newest first
+#[tauri::command]
+fn get_proxy_log(limit: Option<usize>, proxy: tauri::State<'_, Arc<BackendProxy>>) -> Vec<backend_proxy::ProxyLogEntry> {
+    proxy.log(limit)
+}
+
+#[tauri::command]
+fn clear_proxy_log(proxy: tauri::State<'_, Arc<BackendProxy>>) {
+    proxy.clear_log();
+}
+
+/// Runs cut off by the backend crashing, most recent first. Resumable ones can be
+/// re-issued with `POST /_chimera/runs/{run_id}/resume` on the proxy.
+#[tauri::command]
+fn get_interrupted_runs(tracker: tauri::State<'_, Arc<run_recovery::RunTracker>>) -> Vec<run_recovery::InterruptedRun> {
+    tracker.interrupted()
+}
+
+#[tauri::command]
+fn dismiss_interrupted_run(run_id: String, tracker: tauri::State<'_, Arc<run_recovery::RunTracker>>) {
+    tracker.dismiss(&run_id);
+}
+
+/// Whether the app talks to the Python backend or the mock one replaying fixtures
+#[tauri::command]
+fn get_backend_mode(mock: tauri::State<'_, Arc<MockBackend>>) -> mock_backend::BackendModeState {
+    mock.state()
+}
+
+/// Switch to the mock backend, replaying fixtures from `fixtures_dir` or the data
+/// directory's `mock-backend/`, or back to Python, starting it if it never was
+#[tauri::command]
+#[tracing::instrument(skip(mock, backends, availability), err)]
+async fn set_backend_mode(
+    mode: mock_backend::BackendMode,
+    fixtures_dir: Option<String>,
+    mock: tauri::State<'_, Arc<MockBackend>>,
+    backends: tauri::State<'_, Arc<BackendManager>>,
+    availability: tauri::State<'_, Arc<BackendAvailability>>,
+) -> Result<mock_backend::BackendModeState, ChimeraError> {
+    match mode {
+        mock_backend::BackendMode::Mock => {
+            let url = mock.start(fixtures_dir.map(std::path::PathBuf::from)).await?;
+            availability.set(backend_availability::AvailabilityState::Available, None, Some(url));
+        }
+        mock_backend::BackendMode::Python => {
+            mock.stop();
+            availability.set(
+                backend_availability::AvailabilityState::Starting,
+                Some("Backend is starting".to_string()),
+                None,
+            );
+            match backends.start(python_backend::BackendConfig::default_workspace()).await {
+                Ok(backend) => {
+                    availability.set(backend_availability::AvailabilityState::Available, None, Some(backend.base_url()))
+                }
+                Err(e) => {
+                    availability.set(backend_availability::AvailabilityState::Unavailable, Some(e.to_string()), None);
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(mock.state())
+}
+
+/// URL of a workspace's backend. Without a workspace this is the default
+/// backend, waiting for it to start if needed.
+#[tauri::command]
+async fn get_backend_url(
+    app: tauri::AppHandle,
+    workspace_id: Option<String>,
+    backends: tauri::State<'_, Arc<BackendManager>>,
+) -> Result<String, ChimeraError> {
+    match workspace_id.filter(|id| id != python_backend::DEFAULT_WORKSPACE) {
+        Some(workspace_id) => Ok(backends
+            .get(&workspace_id)
+            .map(|backend| backend.base_url())
+            .ok_or_else(|| format!("No backend is running for workspace {}", workspace_id))?),
+        None => Ok(python_backend::wait_for_url(&app).await?),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn read_blueprint(file_path: String) -> Result<String, ChimeraError> {
+    Ok(filesystem::read_blueprint(file_path).await?)
+}
+
+/// Validate and write a blueprint: replaces `blueprint_id` if given, otherwise
+/// creates a new file
+#[tauri::command]
+#[tracing::instrument(skip(blueprint_json), err)]
+async fn save_blueprint(blueprint_id: Option<String>, blueprint_json: String) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("save blueprints")?;
+    let overwrites = blueprint_id.clone();
+    let saved = filesystem::save_blueprint(blueprint_id, blueprint_json).await?;
+    if let Some(id) = overwrites {
+        audit::record_async(audit::AuditEntry::new("blueprints", "overwrite", "ui", id, true).paths([&saved.file_path]))
+            .await;
+    }
+    Ok(saved)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn duplicate_blueprint(blueprint_id: String) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("duplicate blueprints")?;
+    Ok(filesystem::duplicate_blueprint(blueprint_id).await?)
+}
+
+/// Saved versions of a blueprint, newest first
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_blueprint_history(blueprint_id: String) -> Result<Vec<blueprint_history::BlueprintVersion>, ChimeraError> {
+    Ok(filesystem::blocking(move || blueprint_history::history(&blueprint_id)).await?)
+}
+
+/// Put a blueprint back as it was at `commit`, keeping the current version in history
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn restore_blueprint_version(blueprint_id: String, commit: String) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("restore blueprints")?;
+    Ok(filesystem::blocking(move || {
+        blueprint_history::restore(&blueprint_id, &commit)?;
+        let saved = filesystem::saved_blueprint_metadata(filesystem::get_blueprint_path(&blueprint_id)?)?;
+        audit::record(
+            audit::AuditEntry::new("blueprints", "overwrite", "ui", blueprint_id, true)
+                .detail(format!("Restored version {}", commit))
+                .paths([&saved.file_path]),
+        );
+        Ok(saved)
+    })
+    .await?)
+}
+
+/// Blueprints from `chimera://` share links waiting for the user to confirm them
+#[tauri::command]
+fn list_pending_blueprint_imports(links: tauri::State<'_, Arc<deeplink::DeepLinks>>) -> Vec<deeplink::PendingImport> {
+    links.list_pending()
+}
+
+/// Write a shared blueprint the user confirmed into the blueprints directory
+#[tauri::command]
+#[tracing::instrument(skip(links), err)]
+async fn confirm_blueprint_import(
+    id: String,
+    links: tauri::State<'_, Arc<deeplink::DeepLinks>>,
+) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("import blueprints")?;
+    let saved = links.resolve(&id, true).await?;
+    Ok(saved.ok_or_else(|| format!("Blueprint import {} was not saved", id))?)
+}
+
+/// Drop a shared blueprint without importing it
+#[tauri::command]
+#[tracing::instrument(skip(links), err)]
+async fn reject_blueprint_import(id: String, links: tauri::State<'_, Arc<deeplink::DeepLinks>>) -> Result<(), ChimeraError> {
+    Ok(links.resolve(&id, false).await.map(|_| ())?)
+}
+
+/// Starter blueprints bundled with the app, and whether each is installed
+#[tauri::command]
+async fn list_builtin_blueprints(app: tauri::AppHandle) -> Result<Vec<builtin_blueprints::BuiltinBlueprint>, ChimeraError> {
+    Ok(filesystem::blocking(move || builtin_blueprints::list(&app)).await?)
+}
+
+/// Copy a starter blueprint into the blueprints directory. `on_conflict` is
+/// `keep_both` (default), `skip` or `overwrite`.
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+async fn install_builtin_blueprint(
+    id: String,
+    on_conflict: Option<workspace_archive::MergeStrategy>,
+    app: tauri::AppHandle,
+) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("install blueprints")?;
+    Ok(builtin_blueprints::install(&app, &id, on_conflict.unwrap_or_default()).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn rename_blueprint(blueprint_id: String, new_name: String) -> Result<BlueprintMetadata, ChimeraError> {
+    viewer::ensure_writable("rename blueprints")?;
+    Ok(filesystem::rename_blueprint(blueprint_id, new_name).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn delete_blueprint(blueprint_id: String) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("delete blueprints")?;
+    let path = filesystem::get_blueprint_path(&blueprint_id)?;
+    filesystem::delete_blueprint(blueprint_id.clone()).await?;
+    audit::record_async(audit::AuditEntry::new("blueprints", "delete", "ui", blueprint_id, true).paths([path])).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn compact_thread(
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<compaction::CompactionReport, ChimeraError> {
+    viewer::ensure_writable("compact threads")?;
+    let id = thread_id.clone();
+    let report = appends
+        .exclusive(&thread_id, filesystem::blocking(move || compaction::compact_thread(&id)))
+        .await?;
+    if report.values_replaced > 0 {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "compacted" }));
+    }
+    Ok(report)
+}
+
+/// Replace API keys, email addresses or custom regex matches in a thread with
+/// placeholders, keeping the original as a `.bak` file. `patterns` are `api_keys`,
+/// `emails` or regular expressions.
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn redact_thread(
+    thread_id: String,
+    patterns: Vec<String>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<redaction::RedactionReport, ChimeraError> {
+    viewer::ensure_writable("redact threads")?;
+    let id = thread_id.clone();
+    let report = appends
+        .exclusive(&thread_id, filesystem::blocking(move || redaction::redact_thread(&id, &patterns)))
+        .await?;
+    let mut paths: Vec<String> = filesystem::get_thread_path(&thread_id)
+        .map(|path| path.to_string_lossy().to_string())
+        .into_iter()
+        .collect();
+    paths.extend(report.backup_path.clone());
+    audit::record_async(
+        audit::AuditEntry::new("threads", "redact", "ui", thread_id.as_str(), true)
+            .detail(format!("{} of {} events modified", report.events_modified, report.events))
+            .paths(paths),
+    )
+    .await;
+    if report.events_modified > 0 {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "redacted" }));
+    }
+    Ok(report)
+}
+
+/// Compress threads not modified in `older_than_days` days (default: the
+/// `compress_threads_after_days` setting) into `.jsonl.zst` files. They're
+/// decompressed again when opened or appended to.
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+async fn compact_threads(
+    older_than_days: Option<u32>,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<thread_compression::CompressionReport, ChimeraError> {
+    viewer::ensure_writable("compress threads")?;
+    let days = older_than_days.unwrap_or_else(|| settings.get().compress_threads_after_days);
+    Ok(thread_compression::run(&app, days).await?)
+}
+
+/// Upgrade a thread's JSONL to a newer event schema version (default: current)
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn migrate_thread(
+    thread_id: String,
+    to_version: Option<u32>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<event_schema::MigrationReport, ChimeraError> {
+    viewer::ensure_writable("migrate threads")?;
+    let id = thread_id.clone();
+    let report = appends
+        .exclusive(&thread_id, filesystem::blocking(move || event_schema::migrate_thread(&id, to_version)))
+        .await?;
+    if report.to_version != report.from_version {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "migrated" }));
+    }
+    Ok(report)
+}
+
+/// Merge a thread's streamed text and reasoning deltas into their final events
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn compact_stream_events(
+    thread_id: String,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<compaction::StreamCompactionReport, ChimeraError> {
+    viewer::ensure_writable("compact threads")?;
+    Ok(compact_streaming(&thread_id, &appends, &bus).await?)
+}
+
+async fn compact_streaming(
+    thread_id: &str,
+    appends: &AppendBuffer,
+    bus: &EventBus,
+) -> Result<compaction::StreamCompactionReport, ChimeraError> {
+    let id = thread_id.to_string();
+    let report = appends
+        .exclusive(thread_id, filesystem::blocking(move || compaction::compact_stream_events(&id)))
+        .await?;
+    if report.events_removed > 0 {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "compacted" }));
+    }
+    Ok(report)
+}
+
+/// Replace one event in a thread, keeping the original in its history sidecar
+#[tauri::command]
+#[tracing::instrument(skip(new_event, appends, bus), err)]
+async fn amend_event(
+    thread_id: String,
+    event_index: usize,
+    new_event: serde_json::Value,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<event_history::Amendment, ChimeraError> {
+    viewer::ensure_writable("amend events")?;
+    let id = thread_id.clone();
+    let amendment = appends
+        .exclusive(&thread_id, filesystem::blocking(move || event_history::amend_event(&id, event_index, new_event)))
+        .await?;
+    bus.publish(
+        "thread-changed",
+        serde_json::json!({ "thread_id": thread_id, "change": "amended", "event_index": event_index }),
+    );
+    Ok(amendment)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_event_history(thread_id: String) -> Result<Vec<event_history::Amendment>, ChimeraError> {
+    Ok(filesystem::blocking(move || event_history::get_history(&thread_id)).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn list_quarantined() -> Result<Vec<quarantine::QuarantinedThread>, ChimeraError> {
+    Ok(filesystem::blocking(quarantine::list).await?)
+}
+
+/// Move a quarantined thread back into the threads directory
+#[tauri::command]
+#[tracing::instrument(skip(bus), err)]
+async fn recover_quarantined(id: String, bus: tauri::State<'_, Arc<EventBus>>) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("recover threads")?;
+    let thread_id = id.clone();
+    filesystem::blocking(move || quarantine::recover(&thread_id)).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": id, "change": "recovered" }));
+    Ok(())
+}
+
+/// Fetch a payload that was spilled out of an oversized event
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn get_attachment(hash: String) -> Result<serde_json::Value, ChimeraError> {
+    Ok(filesystem::blocking_fs(move || blob_store::get(&hash)).await?)
+}
+
+/// Copy a file into the thread's attachments folder and record a `data-attachment`
+/// event. Files just dropped onto a window don't need folder access granted first.
+#[tauri::command]
+#[tracing::instrument(skip(app, appends, bus, permissions), err)]
+async fn attach_file_to_thread(
+    thread_id: String,
+    path: String,
+    app: tauri::AppHandle,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<attachments::FileAttachment, ChimeraError> {
+    viewer::ensure_writable("attach files")?;
+    let source = std::path::PathBuf::from(&path);
+    if !attachments::was_dropped(&source) {
+        authorize_dir(&app, &permissions, parent_dir(&path), permissions::Operation::Import).await?;
+    }
+    let _write = filesystem::track_write(&thread_id);
+    let attachment = {
+        let thread_id = thread_id.clone();
+        filesystem::blocking(move || attachments::store(&thread_id, &source)).await?
+    };
+    appends.append(&thread_id, &[attachments::event(&attachment)]).await?;
+    bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "appended" }));
+    Ok(attachment)
+}
+
+/// Content of a file attached with `attach_file_to_thread`, as base64
+#[tauri::command]
+#[tracing::instrument(err)]
+async fn read_attachment(thread_id: String, hash: String) -> Result<attachments::AttachmentContent, ChimeraError> {
+    Ok(filesystem::blocking(move || attachments::read(&thread_id, &hash)).await?)
+}
+
+/// Read an image from `path`, or the clipboard if none is given, scale and re-encode
+/// it to the size limits, and store it as an attachment
+#[tauri::command]
+#[tracing::instrument(skip(app, permissions), err)]
+async fn ingest_image(
+    path: Option<String>,
+    max_dimension: Option<u32>,
+    max_bytes: Option<usize>,
+    app: tauri::AppHandle,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<image_ingest::IngestedImage, ChimeraError> {
+    viewer::ensure_writable("add images")?;
+    if let Some(path) = &path {
+        authorize_dir(&app, &permissions, parent_dir(path), permissions::Operation::Import).await?;
+    }
+    let limits = image_ingest::ImageLimits::resolve(max_dimension, max_bytes);
+    Ok(filesystem::blocking(move || match path {
+        Some(path) => image_ingest::ingest_file(&path, limits),
+        None => image_ingest::ingest_clipboard(limits),
+    })
+    .await?)
+}
+
+/// Put text, HTML or a base64 PNG on the system clipboard. `mime` defaults to
+/// `text/plain`.
+#[tauri::command]
+#[tracing::instrument(skip(content), err)]
+async fn copy_to_clipboard(content: String, mime: Option<String>) -> Result<(), ChimeraError> {
+    let mime = mime.unwrap_or_else(|| "text/plain".to_string());
+    tauri::async_runtime::spawn_blocking(move || clipboard::copy(&content, &mime))
+        .await
+        .map_err(|e| format!("Clipboard task failed: {}", e))?
+}
+
+/// Text or an image from the system clipboard. With `thread_id`, an image is
+/// also attached to that thread, as `attach_file_to_thread` would.
+#[tauri::command]
+#[tracing::instrument(skip(appends, bus), err)]
+async fn read_clipboard(
+    thread_id: Option<String>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<clipboard::ClipboardContent, ChimeraError> {
+    let Some(thread_id) = thread_id else {
+        return Ok(filesystem::blocking(|| clipboard::read(None)).await?);
+    };
+
+    viewer::ensure_writable("attach pasted images")?;
+    let _write = filesystem::track_write(&thread_id);
+    let content = {
+        let thread_id = thread_id.clone();
+        filesystem::blocking(move || clipboard::read(Some(&thread_id))).await?
+    };
+    if let clipboard::ClipboardContent::Image {
+        attachment: Some(attachment),
+        ..
+    } = &content
+    {
+        appends.append(&thread_id, &[attachments::event(attachment)]).await?;
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "appended" }));
+    }
+    Ok(content)
+}
+
+/// Stream events appended to a thread file, by any writer, to `on_event`
+#[tauri::command]
+#[tracing::instrument(skip(on_event, tails), err)]
+async fn subscribe_thread(
+    thread_id: String,
+    on_event: Channel<ThreadTailEvent>,
+    tails: tauri::State<'_, Arc<ThreadTails>>,
+) -> Result<u64, ChimeraError> {
+    Ok(tails.subscribe(thread_id, on_event).await?)
+}
+
+#[tauri::command]
+fn unsubscribe_thread(subscription_id: u64, tails: tauri::State<'_, Arc<ThreadTails>>) {
+    tails.unsubscribe(subscription_id);
+}
+
+// Run queue commands
+/// Queue `prompt` to run in a thread at `run_at` (RFC 3339), or as soon as possible
+#[tauri::command]
+#[tracing::instrument(skip(prompt, queue), err)]
+fn enqueue_run(
+    thread_id: String,
+    prompt: String,
+    run_at: Option<String>,
+    queue: tauri::State<'_, Arc<RunQueue>>,
+) -> Result<QueuedRun, ChimeraError> {
+    viewer::ensure_writable("schedule runs")?;
+    Ok(queue.enqueue(thread_id, prompt, run_at)?)
+}
+
+/// List queued, running and recently finished runs
+#[tauri::command]
+fn list_runs(queue: tauri::State<'_, Arc<RunQueue>>) -> Vec<QueuedRun> {
+    queue.list()
+}
+
+/// Cancel a queued run that hasn't started
+#[tauri::command]
+#[tracing::instrument(skip(queue), err)]
+fn cancel_run(run_id: String, queue: tauri::State<'_, Arc<RunQueue>>) -> Result<(), ChimeraError> {
+    Ok(queue.cancel(&run_id)?)
+}
+
+/// Scheduled jobs from settings, with when each runs next and how its last run went
+#[tauri::command]
+fn list_scheduled_jobs(scheduler: tauri::State<'_, Arc<scheduler::Scheduler>>) -> Vec<scheduler::ScheduledJobStatus> {
+    scheduler.list()
+}
+
+/// Run a scheduled job now, in a new thread
+#[tauri::command]
+#[tracing::instrument(skip(scheduler), err)]
+async fn run_scheduled_job(
+    job_id: String,
+    scheduler: tauri::State<'_, Arc<scheduler::Scheduler>>,
+) -> Result<scheduler::JobRun, ChimeraError> {
+    viewer::ensure_writable("run scheduled jobs")?;
+    Ok(scheduler.run_now(&job_id).await?)
+}
+
+// Metrics commands
+/// Collected metrics in the Prometheus text format, or as JSON with `format: "json"`
+#[tauri::command]
+fn get_metrics(format: Option<String>) -> Result<String, ChimeraError> {
+    if !metrics::enabled() {
+        return Err("Metrics are disabled (set CHIMERA_METRICS_PORT or CHIMERA_METRICS_JSONL to enable)".into());
+    }
+    match format.as_deref() {
+        None | Some("prometheus") => Ok(metrics::render()),
+        Some("json") => Ok(metrics::snapshot().to_string()),
+        Some(other) => Err(ChimeraError::InvalidInput(format!("Unknown metrics format: {}", other))),
+    }
+}
+
+// Window commands
+
+/// Open a thread in its own window, or focus the one already showing it.
+/// Returns the window label. Async because building a window from a sync command
+/// deadlocks on Windows.
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+async fn open_thread_window(thread_id: String, app: tauri::AppHandle) -> Result<String, ChimeraError> {
+    Ok(app_windows::open_thread(&app, thread_id)?)
+}
+
+/// Open a blueprint file in its own window, or focus the one already showing it.
+/// Returns the window label.
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+async fn open_blueprint_window(path: String, app: tauri::AppHandle) -> Result<String, ChimeraError> {
+    Ok(app_windows::open_blueprint(&app, path)?)
+}
+
+// Event bus commands
+#[tauri::command]
+fn subscribe_events(
+    window: tauri::Window,
+    since_seq: Option<u64>,
+    on_event: Channel<BusEvent>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> u64 {
+    bus.subscribe(window.label(), since_seq, on_event)
+}
+
+#[tauri::command]
+fn unsubscribe_events(window: tauri::Window, bus: tauri::State<'_, Arc<EventBus>>) {
+    bus.unsubscribe(window.label());
+}
+
+/// Full current state (terminals with scrollback, backend, streams, open threads)
+/// for rebuilding the page after a webview reload or crash
+#[tauri::command]
+async fn resync_state(window: tauri::Window, app: tauri::AppHandle) -> Result<resync::ResyncState, ChimeraError> {
+    Ok(resync::collect(&app, window.label()).await)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app, body, bus), err)]
+async fn stream_backend_request(
+    app: tauri::AppHandle,
+    path: String,
+    body: serde_json::Value,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<String, ChimeraError> {
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let url = format!("{}/{}", python_backend::wait_for_url(&app).await?, path.trim_start_matches('/'));
+
+    tauri::async_runtime::spawn(event_bus::forward_backend_stream(
+        bus.inner().clone(),
+        stream_id.clone(),
+        url,
+        body,
+        None,
+    ));
+
+    Ok(stream_id)
+}
+
+/// Like `stream_backend_request`, but chunks go only to `on_chunk` rather than to
+/// every window's event stream
+#[tauri::command]
+#[tracing::instrument(skip(app, body, on_chunk, bus), err)]
+async fn open_backend_stream(
+    app: tauri::AppHandle,
+    path: String,
+    body: serde_json::Value,
+    on_chunk: Channel<event_bus::BackendStreamEvent>,
+    bus: tauri::State<'_, Arc<EventBus>>,
+) -> Result<String, ChimeraError> {
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let url = format!("{}/{}", python_backend::wait_for_url(&app).await?, path.trim_start_matches('/'));
+
+    tauri::async_runtime::spawn(event_bus::forward_backend_stream(
+        bus.inner().clone(),
+        stream_id.clone(),
+        url,
+        body,
+        Some(on_chunk),
+    ));
+
+    Ok(stream_id)
+}
+
+// Webhook commands
+#[tauri::command]
+fn list_webhooks(webhooks: tauri::State<'_, Arc<WebhookManager>>) -> Vec<WebhookInfo> {
+    webhooks.list()
+}
+
+#[tauri::command]
+fn add_webhook(
+    url: String,
+    events: Vec<String>,
+    secret: Option<String>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+) -> Result<WebhookInfo, ChimeraError> {
+    Ok(webhooks.add(url, events, secret)?)
+}
+
+#[tauri::command]
+fn remove_webhook(webhook_id: String, webhooks: tauri::State<'_, Arc<WebhookManager>>) -> Result<(), ChimeraError> {
+    Ok(webhooks.remove(&webhook_id)?)
+}
+
+#[tauri::command]
+fn get_webhook_deliveries(
+    limit: Option<usize>,
+    webhooks: tauri::State<'_, Arc<WebhookManager>>,
+) -> Vec<DeliveryRecord> {
+    webhooks.deliveries(limit.unwrap_or(100))
+}
+
+/// Recent runs of the hooks in settings, newest first, with their output
+#[tauri::command]
+fn get_hook_runs(limit: Option<usize>, hooks: tauri::State<'_, Arc<HookRunner>>) -> Vec<hooks::HookRun> {
+    hooks.runs(limit.unwrap_or(100))
+}
+
+/// Push and pull the data directory to the sync remote in settings now
+#[tauri::command]
+#[tracing::instrument(skip(sync), err)]
+async fn sync_now(sync: tauri::State<'_, Arc<SyncEngine>>) -> Result<sync::SyncReport, ChimeraError> {
+    viewer::ensure_writable("sync")?;
+    Ok(sync.sync_now().await?)
+}
+
+#[tauri::command]
+fn get_sync_status(sync: tauri::State<'_, Arc<SyncEngine>>) -> sync::SyncStatus {
+    sync.status()
+}
+
+// Semantic search commands
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+async fn refresh_semantic_index(
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    index: tauri::State<'_, Arc<SemanticIndex>>,
+) -> Result<IndexStats, ChimeraError> {
+    let endpoint = semantic_search::embeddings_url(&settings.get()).map_err(ChimeraError::InvalidInput)?;
+    Ok(index.refresh(&endpoint).await?)
+}
+
+/// Search what's indexed so far; the index is kept up to date in the background
+#[tauri::command]
+#[tracing::instrument(skip(query, settings, index), err)]
+async fn semantic_search(
+    query: String,
+    top_k: Option<usize>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    index: tauri::State<'_, Arc<SemanticIndex>>,
+) -> Result<Vec<SearchResult>, ChimeraError> {
+    let endpoint = semantic_search::embeddings_url(&settings.get()).map_err(ChimeraError::InvalidInput)?;
+    Ok(index.search(&endpoint, &query, top_k.unwrap_or(10)).await?)
+}
+
+// Share session commands
+/// Serve `thread_ids` read-only on the local network for `duration_minutes`.
+/// The returned link's token works once.
+#[tauri::command]
+#[tracing::instrument(skip(appends, shares), err)]
+async fn start_share_session(
+    thread_ids: Vec<String>,
+    duration_minutes: Option<u64>,
+    port: Option<u16>,
+    appends: tauri::State<'_, Arc<AppendBuffer>>,
+    shares: tauri::State<'_, Arc<ShareSessionManager>>,
+) -> Result<ShareSessionInfo, ChimeraError> {
+    for thread_id in &thread_ids {
+        appends.flush(thread_id).await?;
+    }
+    Ok(shares.start(thread_ids, duration_minutes, port).await?)
+}
+
+#[tauri::command]
+async fn stop_share_session(shares: tauri::State<'_, Arc<ShareSessionManager>>) -> Result<(), ChimeraError> {
+    shares.stop().await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_share_session(
+    shares: tauri::State<'_, Arc<ShareSessionManager>>,
+) -> Result<Option<ShareSessionInfo>, ChimeraError> {
+    Ok(shares.current().await)
+}
+
+// Obsidian export commands
+#[tauri::command]
+async fn get_obsidian_config(obsidian: tauri::State<'_, Arc<ObsidianExporter>>) -> Result<ObsidianConfig, ChimeraError> {
+    Ok(obsidian.config().await)
+}
+
+#[tauri::command]
+async fn set_obsidian_vault(
+    vault_path: Option<String>,
+    folder: Option<String>,
+    obsidian: tauri::State<'_, Arc<ObsidianExporter>>,
+) -> Result<ObsidianConfig, ChimeraError> {
+    Ok(obsidian.set_vault(vault_path, folder).await?)
+}
+
+#[tauri::command]
+async fn sync_obsidian_vault(
+    full: Option<bool>,
+    obsidian: tauri::State<'_, Arc<ObsidianExporter>>,
+) -> Result<ObsidianSyncStats, ChimeraError> {
+    Ok(obsidian.sync(full.unwrap_or(false)).await?)
+}
+
+// System share sheet (macOS)
+#[tauri::command]
+fn share_with_system(
+    window: tauri::WebviewWindow,
+    path: Option<String>,
+    text: Option<String>,
+) -> Result<(), ChimeraError> {
+    #[cfg(target_os = "macos")]
+    let result = macos_share::show_share_sheet(&window, path, text);
+
+    #[cfg(not(target_os = "macos"))]
+    let result = {
+        let _ = (window, path, text);
+        Err("The system share sheet is only available on macOS".to_string())
+    };
+
+    Ok(result?)
+}
+
+// Plugin commands
+#[tauri::command]
+fn list_plugins(plugins: tauri::State<'_, Arc<PluginHost>>) -> Vec<PluginManifest> {
+    plugins.list()
+}
+
+#[tauri::command]
+async fn reload_plugins(plugins: tauri::State<'_, Arc<PluginHost>>) -> Result<Vec<PluginManifest>, ChimeraError> {
+    let plugins = plugins.inner().clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || plugins.reload())
+        .await
+        .map_err(|e| format!("Plugin reload task failed: {}", e))??)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(input, plugins), err)]
+async fn invoke_plugin_command(
+    command: String,
+    input: serde_json::Value,
+    plugins: tauri::State<'_, Arc<PluginHost>>,
+) -> Result<serde_json::Value, ChimeraError> {
+    let plugins = plugins.inner().clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || plugins.invoke(&command, &input))
+        .await
+        .map_err(|e| format!("Plugin task failed: {}", e))??)
+}
+
+// Dictation commands
+#[tauri::command]
+async fn start_dictation(
+    language: Option<String>,
+    dictation: tauri::State<'_, Arc<DictationManager>>,
+) -> Result<String, ChimeraError> {
+    let dictation = dictation.inner().clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || dictation.start(language))
+        .await
+        .map_err(|e| format!("Dictation task failed: {}", e))??)
+}
+
+#[tauri::command]
+async fn stop_dictation(dictation: tauri::State<'_, Arc<DictationManager>>) -> Result<(), ChimeraError> {
+    let dictation = dictation.inner().clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || dictation.stop())
+        .await
+        .map_err(|e| format!("Dictation task failed: {}", e))?)
+}
+
+// Spellcheck commands
+#[tauri::command]
+async fn check_spelling(
+    text: String,
+    language: Option<String>,
+    spellchecker: tauri::State<'_, Arc<Spellchecker>>,
+) -> Result<Vec<Misspelling>, ChimeraError> {
+    let spellchecker = spellchecker.inner().clone();
+    Ok(filesystem::blocking(move || spellchecker.check(&text, language.as_deref())).await?)
+}
+
+#[tauri::command]
+async fn get_spelling_suggestions(
+    word: String,
+    language: Option<String>,
+    spellchecker: tauri::State<'_, Arc<Spellchecker>>,
+) -> Result<Vec<String>, ChimeraError> {
+    let spellchecker = spellchecker.inner().clone();
+    Ok(filesystem::blocking(move || spellchecker.suggest(&word, language.as_deref())).await?)
+}
+
+#[tauri::command]
+async fn get_spellcheck_languages(spellchecker: tauri::State<'_, Arc<Spellchecker>>) -> Result<Vec<String>, ChimeraError> {
+    let spellchecker = spellchecker.inner().clone();
+    Ok(filesystem::blocking(move || spellchecker.languages()).await?)
+}
+
+#[tauri::command]
+fn get_user_dictionary(spellchecker: tauri::State<'_, Arc<Spellchecker>>) -> Vec<String> {
+    spellchecker.user_words()
+}
+
+#[tauri::command]
+fn add_user_dictionary_word(
+    word: String,
+    spellchecker: tauri::State<'_, Arc<Spellchecker>>,
+) -> Result<Vec<String>, ChimeraError> {
+    viewer::ensure_writable("change the user dictionary")?;
+    Ok(spellchecker.add_word(&word)?)
+}
+
+#[tauri::command]
+fn remove_user_dictionary_word(
+    word: String,
+    spellchecker: tauri::State<'_, Arc<Spellchecker>>,
+) -> Result<Vec<String>, ChimeraError> {
+    viewer::ensure_writable("change the user dictionary")?;
+    Ok(spellchecker.remove_word(&word)?)
+}
+
+// Companion device commands
+#[tauri::command]
+async fn start_companion_pairing(
+    port: Option<u16>,
+    companion: tauri::State<'_, Arc<CompanionServer>>,
+) -> Result<PairingInfo, ChimeraError> {
+    Ok(companion.start_pairing(port).await?)
+}
+
+#[tauri::command]
+async fn stop_companion_server(companion: tauri::State<'_, Arc<CompanionServer>>) -> Result<(), ChimeraError> {
+    companion.stop().await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_companion_status(
+    companion: tauri::State<'_, Arc<CompanionServer>>,
+) -> Result<CompanionStatus, ChimeraError> {
+    Ok(companion.status().await)
+}
+
+#[tauri::command]
+fn list_companion_devices(companion: tauri::State<'_, Arc<CompanionServer>>) -> Vec<CompanionDevice> {
+    companion.devices()
+}
+
+#[tauri::command]
+fn revoke_companion_device(
+    device_id: String,
+    companion: tauri::State<'_, Arc<CompanionServer>>,
+) -> Result<(), ChimeraError> {
+    Ok(companion.revoke(&device_id)?)
+}
+
+// Agent filesystem tool commands
+#[tauri::command]
+fn list_fs_grants(permissions: tauri::State<'_, Arc<Permissions>>) -> Vec<RootGrant> {
+    permissions.list()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(permissions), err)]
+fn grant_fs_root(
+    path: String,
+    read: bool,
+    write: bool,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<RootGrant, ChimeraError> {
+    viewer::ensure_writable("grant filesystem access")?;
+    Ok(permissions.grant(std::path::Path::new(&path), read, write)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(permissions), err)]
+fn revoke_fs_root(path: String, permissions: tauri::State<'_, Arc<Permissions>>) -> Result<(), ChimeraError> {
+    Ok(permissions.revoke(std::path::Path::new(&path))?)
+}
+
+/// Withdraw a folder approval given for exports, imports or terminals
+#[tauri::command]
+#[tracing::instrument(skip(permissions), err)]
+fn revoke_fs_operation(
+    path: String,
+    operation: permissions::Operation,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<(), ChimeraError> {
+    Ok(permissions.revoke_operation(std::path::Path::new(&path), operation)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(permissions), err)]
+async fn read_file(path: String, permissions: tauri::State<'_, Arc<Permissions>>) -> Result<String, ChimeraError> {
+    Ok(fs_tools::read_file(permissions.inner().clone(), path, "ui").await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(contents, permissions), err)]
+async fn write_file(
+    path: String,
+    contents: String,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("write files")?;
+    Ok(fs_tools::write_file(permissions.inner().clone(), path, contents, "ui").await?)
 }
 
 #[tauri::command]
-async fn append_thread_events(thread_id: String, events: Vec<serde_json::Value>) -> Result<(), String> {
-    filesystem::append_thread_events(thread_id, events).await
+#[tracing::instrument(skip(permissions), err)]
+async fn list_dir(
+    path: String,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<Vec<fs_tools::DirEntry>, ChimeraError> {
+    Ok(fs_tools::list_dir(permissions.inner().clone(), path, "ui").await?)
 }
 
+// Audit log commands
 #[tauri::command]
-async fn list_threads() -> Result<Vec<ThreadMetadata>, String> {
-    filesystem::list_threads().await
+#[tracing::instrument(err)]
+async fn get_audit_log(filter: Option<audit::AuditFilter>) -> Result<Vec<audit::AuditEntry>, ChimeraError> {
+    Ok(filesystem::blocking(move || audit::query(&filter.unwrap_or_default())).await?)
 }
 
+// Terminal commands
+/// Open a terminal of type `shell` (the user's shell), `bash`, `ink-cli` or `command`,
+/// in terminal group `group_id` if given. A `command` not on the allowlist is held
+/// and an approval request published.
 #[tauri::command]
-async fn update_thread_title(thread_id: String, title: String) -> Result<(), String> {
-    filesystem::update_thread_title(thread_id, title).await
+#[tracing::instrument(skip(env, app, state, commands, permissions), err)]
+#[allow(clippy::too_many_arguments)]
+async fn spawn_terminal(
+    terminal_type: String,
+    cwd: Option<String>,
+    command: Option<String>,
+    args: Option<Vec<String>>,
+    env: Option<std::collections::HashMap<String, String>>,
+    init_command: Option<String>,
+    group_id: Option<String>,
+    webview_window: tauri::WebviewWindow,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+    commands: tauri::State<'_, Arc<TerminalCommands>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("spawn terminals")?;
+    if let Some(cwd) = &cwd {
+        authorize_dir(&app, &permissions, cwd.into(), permissions::Operation::Terminal).await?;
+    }
+    let options = SpawnOptions {
+        env: env.unwrap_or_default(),
+        init_command,
+        window: Some(webview_window.label().to_string()),
+        group_id,
+    };
+    let spec = command.map(|command| CommandSpec {
+        command,
+        args: args.unwrap_or_default(),
+        env: options.env.clone(),
+    });
+    if terminal_type == "command" {
+        if let Some(pending) = spec
+            .as_ref()
+            .and_then(|spec| commands.inspect(spec, cwd.as_deref(), options.init_command.as_deref()))
+        {
+            return Err(ChimeraError::PermissionDenied(format!(
+                "{} is not on the terminal command allowlist; approval requested ({})",
+                pending.spec.command, pending.id
+            )));
+        }
+    }
+    state.spawn_terminal(terminal_type, cwd, spec, options).await
 }
 
+/// CPU and memory of each backend and terminal, with the processes they started
 #[tauri::command]
-fn get_backend_url() -> String {
-    "http://localhost:33003".to_string()
+async fn get_process_stats(
+    app: tauri::AppHandle,
+    monitor: tauri::State<'_, Arc<ProcessMonitor>>,
+) -> Result<Vec<process_stats::ProcessStats>, ChimeraError> {
+    Ok(monitor.get(&app).await)
 }
 
+/// Terminal profiles from settings, in the order the user listed them
 #[tauri::command]
-async fn read_blueprint(file_path: String) -> Result<String, String> {
-    filesystem::read_blueprint(file_path).await
+fn list_terminal_profiles(settings: tauri::State<'_, Arc<SettingsStore>>) -> Vec<terminal_profiles::TerminalProfile> {
+    settings.get().terminal_profiles
 }
 
-// Terminal commands
+/// Open a terminal as the profile `profile_id` describes it, in `cwd`, else the
+/// directory of group `group_id`, else the profile's own working directory
 #[tauri::command]
-async fn spawn_terminal(
-    terminal_type: String,
+#[tracing::instrument(skip(webview_window, app, state, settings, permissions), err)]
+#[allow(clippy::too_many_arguments)]
+async fn spawn_terminal_from_profile(
+    profile_id: String,
     cwd: Option<String>,
+    group_id: Option<String>,
+    webview_window: tauri::WebviewWindow,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<String, ChimeraError> {
+    viewer::ensure_writable("spawn terminals")?;
+    let profile = settings
+        .get()
+        .terminal_profiles
+        .into_iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("Terminal profile not found: {}", profile_id))?;
+    if let Some(cwd) = cwd.as_ref().or(profile.cwd.as_ref()) {
+        authorize_dir(&app, &permissions, cwd.into(), permissions::Operation::Terminal).await?;
+    }
+    let options = SpawnOptions {
+        env: Default::default(),
+        init_command: None,
+        window: Some(webview_window.label().to_string()),
+        group_id,
+    };
+    state.spawn_from_profile(&profile, cwd, options).await
+}
+
+/// Start a held `command` terminal after the user approves it, returning its id
+#[tauri::command]
+#[tracing::instrument(skip(token, state, commands), err)]
+async fn approve_terminal_command(
+    id: String,
+    token: String,
+    remember: Option<bool>,
+    webview_window: tauri::WebviewWindow,
     state: tauri::State<'_, Arc<TerminalBackend>>,
-) -> Result<String, String> {
-    state.spawn_terminal(terminal_type, cwd).await
+    commands: tauri::State<'_, Arc<TerminalCommands>>,
+) -> Result<String, ChimeraError> {
+    let spawn = commands.approve(&id, &token, remember.unwrap_or(false))?;
+    let options = SpawnOptions {
+        env: spawn.spec.env.clone(),
+        init_command: spawn.init_command,
+        window: Some(webview_window.label().to_string()),
+        group_id: None,
+    };
+    state.spawn_terminal("command".to_string(), spawn.cwd, Some(spawn.spec), options).await
 }
 
+#[tauri::command]
+#[tracing::instrument(skip(commands), err)]
+fn deny_terminal_command(id: String, commands: tauri::State<'_, Arc<TerminalCommands>>) -> Result<(), ChimeraError> {
+    Ok(commands.deny(&id)?)
+}
+
+#[tauri::command]
+fn get_terminal_command_allowlist(commands: tauri::State<'_, Arc<TerminalCommands>>) -> CommandAllowlist {
+    commands.allowlist()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(commands), err)]
+fn set_terminal_command_allowlist(
+    allowlist: CommandAllowlist,
+    commands: tauri::State<'_, Arc<TerminalCommands>>,
+) -> Result<CommandAllowlist, ChimeraError> {
+    Ok(commands.set_allowlist(allowlist)?)
+}
+
+/// Write input to a terminal. Input from `source` "agent" that looks destructive is
+/// held while the guard is on; the pending write's id is returned instead.
 #[tauri::command]
 async fn write_to_terminal(
     terminal_id: String,
     data: String,
+    source: Option<String>,
     state: tauri::State<'_, Arc<TerminalBackend>>,
-) -> Result<(), String> {
-    state.write_to_terminal(&terminal_id, &data).await
+    guard: tauri::State<'_, Arc<TerminalGuard>>,
+) -> Result<Option<String>, ChimeraError> {
+    if let Some(pending_id) = guard.inspect(&terminal_id, &data, source.as_deref().unwrap_or("user")) {
+        return Ok(Some(pending_id));
+    }
+    state.write_to_terminal(&terminal_id, &data).await?;
+    Ok(None)
+}
+
+/// Send a held terminal write after the user approves it
+#[tauri::command]
+#[tracing::instrument(skip(token, state, guard), err)]
+async fn confirm_terminal_write(
+    id: String,
+    token: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+    guard: tauri::State<'_, Arc<TerminalGuard>>,
+) -> Result<(), ChimeraError> {
+    let write = guard.confirm(&id, &token)?;
+    state.write_to_terminal(&write.terminal_id, &write.data).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(guard), err)]
+fn deny_terminal_write(id: String, guard: tauri::State<'_, Arc<TerminalGuard>>) -> Result<(), ChimeraError> {
+    Ok(guard.deny(&id)?)
+}
+
+#[tauri::command]
+fn list_pending_terminal_writes(guard: tauri::State<'_, Arc<TerminalGuard>>) -> Vec<PendingWrite> {
+    guard.pending()
+}
+
+#[tauri::command]
+fn get_terminal_guard(guard: tauri::State<'_, Arc<TerminalGuard>>) -> GuardConfig {
+    guard.config()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(guard), err)]
+fn set_terminal_guard(config: GuardConfig, guard: tauri::State<'_, Arc<TerminalGuard>>) -> Result<GuardConfig, ChimeraError> {
+    Ok(guard.set_config(config)?)
+}
+
+/// Send a named key (`Up`, `F5`, `Ctrl+C`, `Alt+Enter`, ...) to a terminal
+#[tauri::command]
+async fn send_key(
+    terminal_id: String,
+    key: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<(), ChimeraError> {
+    state.send_key(&terminal_id, &key).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip(state), err)]
 async fn resize_terminal(
     terminal_id: String,
     cols: u16,
     rows: u16,
     state: tauri::State<'_, Arc<TerminalBackend>>,
-) -> Result<(), String> {
+) -> Result<(), ChimeraError> {
     state.resize_terminal(&terminal_id, cols, rows).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip(state), err)]
 async fn close_terminal(
     terminal_id: String,
     state: tauri::State<'_, Arc<TerminalBackend>>,
-) -> Result<(), String> {
+) -> Result<(), ChimeraError> {
     state.close_terminal(&terminal_id).await
 }
 
+/// Add a named group of terminals, e.g. one per project. Terminals spawned into it
+/// start in `cwd` unless given their own.
+#[tauri::command]
+#[tracing::instrument(skip(app, state, permissions), err)]
+async fn create_terminal_group(
+    name: String,
+    cwd: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<terminal_backend::TerminalGroupInfo, ChimeraError> {
+    viewer::ensure_writable("create terminal groups")?;
+    if let Some(cwd) = &cwd {
+        authorize_dir(&app, &permissions, cwd.into(), permissions::Operation::Terminal).await?;
+    }
+    state.create_group(&name, cwd).await
+}
+
+#[tauri::command]
+async fn list_terminal_groups(
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<Vec<terminal_backend::TerminalGroupInfo>, ChimeraError> {
+    Ok(state.list_groups().await)
+}
+
+/// Close every terminal in a group and remove the group, e.g. when its project
+/// tab closes. Returns the ids of the terminals closed.
+#[tauri::command]
+#[tracing::instrument(skip(state), err)]
+async fn close_terminal_group(
+    group_id: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<Vec<String>, ChimeraError> {
+    state.close_group(&group_id).await
+}
+
+/// Open a file at `line` and `column` in a code editor, e.g. a path from a link in
+/// terminal output
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+fn open_path_in_editor(
+    path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<(), ChimeraError> {
+    editor::open(&app, &settings.get().editor, &path, line, column)
+}
+
+/// Open a file at `line` and `column` in the editor chosen in settings, e.g. a
+/// thread attachment or a blueprint
+#[tauri::command]
+#[tracing::instrument(skip(app, settings), err)]
+fn open_in_editor(
+    path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<(), ChimeraError> {
+    editor::open(&app, &settings.get().editor, &path, line, column)
+}
+
+/// Show a file or directory selected in Finder, Explorer or the file manager
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+fn reveal_in_file_manager(path: String, app: tauri::AppHandle) -> Result<(), ChimeraError> {
+    editor::reveal_in_file_manager(&app, &path)
+}
+
+/// Open a file in the app the system associates with it. This can run programs,
+/// so the user approves opening files from its directory once.
+#[tauri::command]
+#[tracing::instrument(skip(app, permissions), err)]
+async fn open_in_default_app(
+    path: String,
+    app: tauri::AppHandle,
+    permissions: tauri::State<'_, Arc<Permissions>>,
+) -> Result<(), ChimeraError> {
+    authorize_dir(&app, &permissions, parent_dir(&path), permissions::Operation::Open).await?;
+    editor::open_in_default_app(&app, &path)
+}
+
+/// Send SIGINT, SIGTERM or SIGKILL to what's running in a terminal
+#[tauri::command]
+#[tracing::instrument(skip(state), err)]
+async fn signal_terminal(
+    terminal_id: String,
+    signal: terminal_backend::TerminalSignal,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<(), ChimeraError> {
+    viewer::ensure_writable("signal terminals")?;
+    state.signal(&terminal_id, signal).await
+}
+
+/// Receive a terminal's output as raw bytes on `on_output` instead of `terminal_output` events
+#[tauri::command]
+async fn attach_terminal_output(
+    terminal_id: String,
+    on_output: Channel,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<u64, ChimeraError> {
+    state.attach_output(&terminal_id, on_output).await
+}
+
+#[tauri::command]
+async fn detach_terminal_output(
+    terminal_id: String,
+    subscription_id: u64,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<(), ChimeraError> {
+    state.detach_output(&terminal_id, subscription_id).await
+}
+
+/// Pid, status, exit code, cwd and uptime of a terminal
+#[tauri::command]
+async fn get_terminal_info(
+    terminal_id: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<terminal_backend::TerminalInfo, ChimeraError> {
+    state.info(&terminal_id).await
+}
+
+/// When each open terminal last had input or output, and whether it's idle
+/// under the `terminal_idle` setting
+#[tauri::command]
+async fn get_terminal_activity(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<Vec<terminal_backend::TerminalActivity>, ChimeraError> {
+    let idle_after = terminal_backend::idle_policy(&app).map(|policy| policy.threshold());
+    Ok(state.activity(idle_after).await)
+}
+
+/// Recent output of a terminal, to rehydrate its view after a reload or remount, or
+/// to resume it after a `terminal_output_dropped` event
+#[tauri::command]
+async fn get_terminal_scrollback(
+    terminal_id: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<String, ChimeraError> {
+    state.scrollback(&terminal_id).await
+}
+
+/// Save a terminal's scrollback to `dest` as plain text or raw ANSI
+#[tauri::command]
+#[tracing::instrument(skip(state), err)]
+async fn export_terminal_buffer(
+    terminal_id: String,
+    dest: String,
+    format: Option<terminal_backend::ScrollbackFormat>,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<u64, ChimeraError> {
+    state
+        .export_buffer(&terminal_id, std::path::Path::new(&dest), format.unwrap_or_default())
+        .await
+}
+
+/// Record a terminal's output from now on to an asciicast v2 file at `dest`
+#[tauri::command]
+#[tracing::instrument(skip(state), err)]
+async fn start_terminal_recording(
+    terminal_id: String,
+    path: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<(), ChimeraError> {
+    state.start_recording(&terminal_id, std::path::Path::new(&path)).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(state), err)]
+async fn stop_terminal_recording(
+    terminal_id: String,
+    state: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<terminal_backend::RecordingSummary, ChimeraError> {
+    state.stop_recording(&terminal_id).await
+}
+
+// Diagnostics commands
+#[tauri::command]
+#[tracing::instrument(skip(terminals), err)]
+async fn run_performance_check(
+    large_thread_mb: Option<u64>,
+    terminals: tauri::State<'_, Arc<TerminalBackend>>,
+) -> Result<perf_check::PerformanceReport, ChimeraError> {
+    Ok(perf_check::run(&terminals, large_thread_mb).await?)
+}
+
+/// Check for setup problems that would stop the backend from starting, for the
+/// first-run guide
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn run_doctor(app: tauri::AppHandle) -> doctor::DoctorReport {
+    let backend_port = app.try_state::<Arc<PythonBackend>>().map(|backend| backend.port());
+    doctor::run(backend_port).await
+}
+
+/// Running and recently finished long operations, newest first
+#[tauri::command]
+fn list_tasks(tasks: tauri::State<'_, Arc<TaskManager>>) -> Vec<tasks::TaskInfo> {
+    tasks.list()
+}
+
+/// Stop a running export, backup or index rebuild. The operation's own command
+/// then fails with the `cancelled` code.
+#[tauri::command]
+#[tracing::instrument(skip(tasks), err)]
+fn cancel_task(task_id: String, tasks: tauri::State<'_, Arc<TaskManager>>) -> Result<tasks::TaskInfo, ChimeraError> {
+    tasks.cancel(&task_id)
+}
+
+/// Remove caches, logs, integrations and (with confirmation flags) user data
+#[tauri::command]
+#[tracing::instrument(skip(app), err)]
+async fn cleanup_app_data(
+    options: cleanup::CleanupOptions,
+    app: tauri::AppHandle,
+) -> Result<cleanup::CleanupReport, ChimeraError> {
+    viewer::ensure_writable("clean up app data")?;
+    let user_data = options.user_data;
+    let handle = app.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || cleanup::run(&handle, &options))
+        .await
+        .map_err(|e| format!("Cleanup task failed: {}", e))??;
+
+    // Leave an empty, working data directory behind
+    if user_data {
+        filesystem::init_filesystem().await?;
+    }
+
+    Ok(report)
+}
+
+/// Flush buffered appends and stop terminals and Python backends. Run on every
+/// exit path, and before an update is installed.
+pub(crate) async fn shutdown_services(handle: &tauri::AppHandle) {
+    // Let appends already under way reach the buffer or the disk, warning the
+    // frontend if that's taking a while
+    if let Err(pending) = filesystem::flush_pending_writes(filesystem::PENDING_WRITES_WARN_AFTER).await {
+        log::warn!("Waiting for thread writes before exit: {}", pending);
+        if let Some(bus) = handle.try_state::<Arc<EventBus>>() {
+            bus.publish(
+                "pending-writes-slow",
+                serde_json::json!({ "pending": filesystem::pending_writes() }),
+            );
+        }
+        let remaining = filesystem::PENDING_WRITES_TIMEOUT.saturating_sub(filesystem::PENDING_WRITES_WARN_AFTER);
+        if let Err(e) = filesystem::flush_pending_writes(remaining).await {
+            log::error!("Exiting with {}", e);
+        }
+    }
+
+    // Write out buffered thread appends
+    if let Some(appends) = handle.try_state::<Arc<AppendBuffer>>() {
+        appends.flush_all().await;
+    }
+
+    // Write drafts still waiting out their delay
+    if let Some(drafts) = handle.try_state::<Arc<DraftStore>>() {
+        let drafts = drafts.inner().clone();
+        let _ = filesystem::blocking(move || {
+            drafts.flush();
+            Ok(())
+        })
+        .await;
+    }
+
+    // Let startup work finish, so a backend still starting gets stopped too
+    if let Some(startup_tasks) = handle.try_state::<Arc<TaskGroup>>() {
+        startup_tasks.shutdown(supervisor::SHUTDOWN_TIMEOUT).await;
+    }
+
+    // Shutdown terminal backend
+    if let Some(terminal_backend) = handle.try_state::<Arc<TerminalBackend>>() {
+        log::info!("Shutting down terminal backend...");
+        terminal_backend.shutdown_all().await;
+    }
+
+    // Shutdown Python backends
+    if let Some(backends) = handle.try_state::<Arc<BackendManager>>() {
+        log::info!("Shutting down Python backends...");
+        let bus = handle.try_state::<Arc<EventBus>>();
+        backends.shutdown_all(bus.as_deref().map(|bus| bus.as_ref())).await;
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let args: Vec<String> = std::env::args().collect();
+
+    // The test harness uses a mock backend, so it must not touch a real one
+    if test_harness::is_requested(&args) {
+        std::process::exit(test_harness::run(&args));
+    }
+
+    // Active workspace, data directory and backend port from saved settings
+    settings::apply_startup();
+
+    // Crash reports go in the data directory, so this comes after its setting
+    diagnostics::install_panic_hook();
+
+    // Read-only viewer mode, possibly on an exported workspace instead of ours
+    if let Err(e) = viewer::init(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
     // Clean up any stale Python backend from a previous crash
-    python_backend::cleanup_stale_backend();
+    if !viewer::is_active() {
+        python_backend::cleanup_stale_backend(&[]);
+    }
+
+    // Headless mode runs a single blueprint without creating any windows
+    if headless::is_requested(&args) {
+        std::process::exit(headless::run(&args));
+    }
+
+    // Update signatures are checked against the key this build was made with
+    let mut updater_plugin = tauri_plugin_updater::Builder::new();
+    if let Some(pubkey) = updater::pubkey() {
+        updater_plugin = updater_plugin.pubkey(pubkey);
+    }
 
     let app = tauri::Builder::default()
+        // First, so a second launch (e.g. from a share link) hands off to this one
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            if let Err(e) = shortcuts::summon(app) {
+                log::error!("Failed to show the running instance: {}", e);
+            }
+        }))
         .plugin(tauri_plugin_log::Builder::default().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().with_handler(shortcuts::handle).build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(updater_plugin.build())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
-            // Initialize filesystem
-            tauri::async_runtime::spawn(async move {
+            // Prometheus-style metrics on localhost when CHIMERA_METRICS_PORT is set, and
+            // snapshots in a JSONL when CHIMERA_METRICS_JSONL is
+            if let Some(port) = metrics::init_from_env() {
+                tauri::async_runtime::spawn(metrics::serve(port));
+            }
+            if let Some(path) = metrics::jsonl_path() {
+                tauri::async_runtime::spawn(metrics::write_jsonl(path));
+            }
+
+            // Export tracing spans when an OTLP collector is configured
+            tauri::async_runtime::block_on(async { telemetry::init() });
+
+            // Initialize event bus
+            let event_bus = Arc::new(EventBus::new(app.handle().clone()));
+            app.manage(event_bus.clone());
+            app.manage(Arc::new(TaskManager::new(event_bus.clone())));
+
+            // User preferences; log level and theme apply right away
+            let settings = Arc::new(SettingsStore::load(event_bus.clone()));
+            settings.apply(app.handle());
+            let storage_kind = settings.get().storage;
+            app.manage(settings);
+
+            // Put the main window back where it was on these monitors
+            let window_state = Arc::new(WindowStateStore::load());
+            window_state.restore(app.handle());
+            window_state.start();
+            app.manage(window_state);
+            if let Some(endpoint) = backend_grpc::endpoint() {
+                log::info!("Streaming backend requests over gRPC at {}", endpoint);
+            }
+
+            // Initialize filesystem, set aside threads left broken by a crash, then
+            // prime the blueprint metadata cache
+            let blueprint_cache = Arc::new(BlueprintCache::new());
+            app.manage(blueprint_cache.clone());
+            // Startup work runs in its own group so exit waits for it
+            let startup_tasks = Arc::new(TaskGroup::new("startup"));
+            app.manage(startup_tasks.clone());
+            let startup_bus = event_bus.clone();
+            startup_tasks.spawn(async move {
+                // Nothing on disk is touched in viewer mode
+                if let Some(mode) = viewer::mode() {
+                    log::info!("Viewer mode: showing {} read-only", mode.data_dir);
+                    blueprint_cache.prime().await;
+                    return;
+                }
+                // A read-only or unreachable data directory fails everything after
+                // this, so the UI is told why up front
+                let capabilities = filesystem::probe_data_dir().await;
+                if capabilities.degraded() {
+                    for problem in &capabilities.problems {
+                        log::warn!("Data directory {}: {}", capabilities.data_dir, problem);
+                    }
+                    startup_bus.publish("filesystem-degraded", &capabilities);
+                } else if capabilities.case_sensitive == Some(false) {
+                    log::info!("Data directory {} ignores case", capabilities.data_dir);
+                }
                 if let Err(e) = init_filesystem().await {
                     log::error!("Failed to initialize filesystem: {}", e);
                 }
+                if let Err(e) = filesystem::blocking(audit::prune).await {
+                    log::error!("Failed to prune audit log: {}", e);
+                }
+                if let Err(e) = filesystem::purge_trash(None, "app").await {
+                    log::error!("Failed to purge trash: {}", e);
+                }
+                if let Err(e) = filesystem::blocking(scratch::cleanup).await {
+                    log::error!("Failed to clean up scratch directories: {}", e);
+                }
+                match filesystem::blocking(quarantine::scan).await {
+                    Ok(moved) if !moved.is_empty() => {
+                        startup_bus.publish("threads-quarantined", serde_json::json!({ "threads": moved }));
+                        startup_bus.publish("thread-changed", serde_json::json!({ "change": "quarantined" }));
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to check for broken threads: {}", e),
+                }
+                blueprint_cache.prime().await;
+            });
+
+            // Coalesce streaming appends into periodic writes
+            let append_buffer = Arc::new(AppendBuffer::new());
+            append_buffer.start();
+            app.manage(append_buffer.clone());
+
+            // Unsent messages, written a moment after each change
+            let drafts = Arc::new(DraftStore::new());
+            drafts.start();
+            app.manage(drafts);
+
+            // Thread storage serving search, kept following the JSONL files. Viewer
+            // mode leaves the workspace untouched, so it reads the files.
+            let storage_kind = if viewer::is_active() { storage::StorageKind::Files } else { storage_kind };
+            let thread_storage = storage::open(storage_kind).unwrap_or_else(|e| {
+                log::error!("Failed to open {:?} storage, using files: {}", storage_kind, e);
+                Arc::new(storage::FileStorage)
             });
+            storage::follow(thread_storage.clone(), &event_bus, append_buffer.clone());
+            app.manage(thread_storage);
+
+            // Periodic backups of threads and blueprints, and compression of idle threads
+            if !viewer::is_active() {
+                backups::start(app.handle().clone());
+                thread_compression::start(app.handle().clone());
+            }
+
+            // Incremental thread listing, kept current from thread-changed events
+            let thread_index = Arc::new(ThreadIndex::load());
+            thread_index.watch(&event_bus);
+            app.manage(thread_index);
+
+            // chimera:// share links, from this launch and any forwarded by later ones
+            let deep_links = Arc::new(deeplink::DeepLinks::new(event_bus.clone()));
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                log::warn!("Failed to register {}:// links: {}", deeplink::SCHEME, e);
+            }
+            let links = deep_links.clone();
+            app.deep_link().on_open_url(move |event| links.handle_urls(event.urls()));
+            match app.deep_link().get_current() {
+                Ok(Some(urls)) => deep_links.handle_urls(urls),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to read launch links: {}", e),
+            }
+            app.manage(deep_links);
+
+            // Disk usage for the settings screen, recomputed when threads change
+            let storage_stats = Arc::new(storage_stats::StorageStatsCache::default());
+            storage_stats.watch(&event_bus);
+            app.manage(storage_stats);
+
+            // Translation bundles for notifications and the frontend
+            app.manage(Arc::new(I18n::new(app.handle())));
+
+            // Desktop notifications when agents finish, fail or need approval
+            let notifier = Arc::new(notifications::Notifier::new(app.handle().clone()));
+            notifier.watch(&event_bus);
+            app.manage(notifier);
+
+            // Periodic update checks on the channel in settings
+            let updater = Arc::new(updater::Updater::new(app.handle().clone()));
+            if !viewer::is_active() {
+                updater.start();
+            }
+            app.manage(updater);
+
+            // Pick up threads and blueprints edited outside the app
+            match fs_watcher::FileWatcher::start(event_bus.clone()) {
+                Ok(watcher) => {
+                    app.manage(watcher);
+                }
+                Err(e) => log::error!("Failed to watch data directory: {}", e),
+            }
+
+            // Token and cost accounting for appended usage events
+            app.manage(Arc::new(UsageLedger::load(event_bus.clone())));
+
+            // Scheduled agent runs, including any missed while the app was closed
+            let run_queue = Arc::new(RunQueue::load());
+            if !viewer::is_active() {
+                run_queue.start(app.handle().clone());
+            }
+            app.manage(run_queue);
+
+            // Blueprints run on a schedule from settings, through the run queue
+            let scheduler = Arc::new(scheduler::Scheduler::new(app.handle().clone()));
+            if !viewer::is_active() {
+                scheduler.start(&event_bus);
+            }
+            app.manage(scheduler);
+
+            // Agent runs in flight, recovered when the backend dies mid-stream
+            app.manage(Arc::new(run_recovery::RunTracker::new(app.handle().clone())));
+
+            // Authenticated proxy the webview reaches the backend through
+            match BackendProxy::start(app.handle().clone()) {
+                Ok(proxy) => {
+                    app.manage(proxy);
+                }
+                Err(e) => log::error!("Failed to start backend proxy: {}", e),
+            }
+
+            // Backend calls buffered across restarts and outages
+            let backend_queue = Arc::new(BackendQueue::new(event_bus.clone()));
+            backend_queue.start(app.handle().clone());
+            app.manage(backend_queue);
+
+            // Live tails of thread files for the frontend
+            app.manage(Arc::new(ThreadTails::new(event_bus.clone())));
+
+            // Load webhook configuration
+            app.manage(Arc::new(WebhookManager::load()));
+
+            // Commands and URLs from settings, run on app events
+            let hook_runner = Arc::new(HookRunner::new(app.handle().clone()));
+            hook_runner.watch(&event_bus);
+            app.manage(hook_runner);
+
+            // Cross-device sync through the S3 bucket or WebDAV folder in settings
+            let sync_engine = Arc::new(SyncEngine::new(app.handle().clone(), event_bus.clone()));
+            if !viewer::is_active() {
+                sync_engine.start();
+            }
+            app.manage(sync_engine);
+
+            // OS high-contrast, reduced-motion and text size preferences, with the settings applied
+            let accessibility = Arc::new(AccessibilityMonitor::new());
+            accessibility.reapply(app.handle());
+            accessibility.start(app.handle().clone());
+            app.manage(accessibility);
+
+            // Semantic search index (loaded lazily, kept up to date in the background)
+            let semantic_index = Arc::new(SemanticIndex::new());
+            semantic_index.watch(&event_bus, app.state::<Arc<SettingsStore>>().inner().clone());
+            app.manage(semantic_index);
+
+            // Let selected text in other apps start a new thread via macOS Services
+            #[cfg(target_os = "macos")]
+            macos_share::register_services(app.handle().clone());
+
+            // AppleScript dictionary on macOS, DBus service on Linux
+            if !viewer::is_active() {
+                automation::start(app.handle());
+            }
+
+            // Obsidian vault export settings
+            app.manage(Arc::new(ObsidianExporter::load()));
+
+            // LAN share sessions (idle until started)
+            app.manage(Arc::new(ShareSessionManager::new()));
+
+            // Load WASM plugins
+            let exporters = Arc::new(ExporterRegistry::new());
+            match PluginHost::new() {
+                Ok(host) => {
+                    let host = Arc::new(host);
+                    exporters.attach_plugins(host.clone());
+                    app.manage(host);
+                }
+                Err(e) => log::error!("Failed to initialize plugin host: {}", e),
+            }
+
+            // Thread export formats, and batch operations over many threads
+            app.manage(Arc::new(BatchManager::new(event_bus.clone(), append_buffer, exporters.clone())));
+            app.manage(exporters);
+
+            // Speech-to-text for composing messages
+            app.manage(Arc::new(DictationManager::new(event_bus.clone())));
+
+            // Native spellcheck for the prompt composer
+            app.manage(Arc::new(Spellchecker::load()));
+
+            // Companion devices (server starts when pairing is opened)
+            app.manage(Arc::new(CompanionServer::load(event_bus.clone())));
 
             // Initialize terminal backend
-            let terminal_backend = Arc::new(TerminalBackend::new(app.handle().clone()));
+            terminal_env::prime();
+            app.manage(Arc::new(TerminalGuard::load(event_bus.clone())));
+            app.manage(Arc::new(TerminalCommands::load(event_bus.clone())));
+            let terminal_backend = Arc::new(TerminalBackend::new(event_bus));
+            terminal_backend::start_idle_checks(app.handle().clone(), terminal_backend.clone());
             app.manage(terminal_backend);
             log::info!("Terminal backend initialized");
 
-            // Start Python backend on app startup
+            // Filesystem roots the user has opened to agents
+            let permissions = Arc::new(Permissions::load());
+            app.manage(permissions.clone());
+
+            // CPU and memory of the backends and terminals, with warnings past the limits in settings
+            let process_monitor = Arc::new(ProcessMonitor::new(app.state::<Arc<EventBus>>().inner().clone()));
+            process_stats::start(app.handle().clone(), process_monitor.clone());
+            app.manage(process_monitor);
+
+            // Whether the default backend is up, for the frontend's connecting state
+            let availability = Arc::new(BackendAvailability::new(app.state::<Arc<EventBus>>().inner().clone()));
+            availability.watch();
+            app.manage(availability.clone());
+
+            // Start Python backend on app startup, pointed at the local file tool API.
+            // Viewer mode runs without one.
+            if viewer::is_active() {
+                availability.set(
+                    backend_availability::AvailabilityState::Unavailable,
+                    Some("No backend in read-only viewer mode".to_string()),
+                    None,
+                );
+                return Ok(());
+            }
+            let backends = Arc::new(BackendManager::new(app.handle().clone()));
+            app.manage(backends.clone());
+            // Fixture replay in place of Python, for frontend work and integration tests
+            let mock = Arc::new(MockBackend::new(app.state::<Arc<EventBus>>().inner().clone()));
+            app.manage(mock.clone());
             let app_handle_backend = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                match PythonBackend::start().await {
-                    Ok(backend) => {
-                        let backend_url = backend.base_url();
-                        log::info!("Python backend started successfully at {}", backend_url);
+            startup_tasks.spawn(async move {
+                let tool_env = match fs_tools::serve(permissions).await {
+                    Ok(api) => api.backend_env(),
+                    Err(e) => {
+                        log::error!("Failed to start file tool API: {}", e);
+                        Vec::new()
+                    }
+                };
 
-                        // Store backend in managed state
-                        app_handle_backend.manage(Arc::new(backend));
+                if let Some(bus) = app_handle_backend.try_state::<Arc<EventBus>>() {
+                    python_backend::forward_log(bus.inner().clone());
+                }
+                backends.set_env(tool_env);
+                if let Some(fixtures) = mock_backend::requested() {
+                    match mock.start(fixtures).await {
+                        Ok(url) => availability.set(backend_availability::AvailabilityState::Available, None, Some(url)),
+                        Err(e) => {
+                            log::error!("Failed to start mock backend: {}", e);
+                            availability.set(backend_availability::AvailabilityState::Unavailable, Some(e), None);
+                        }
+                    }
+                    return;
+                }
+                match backends.start(python_backend::BackendConfig::default_workspace()).await {
+                    Ok(backend) => {
+                        log::info!("Python backend started successfully at {}", backend.base_url());
+                        availability.set(backend_availability::AvailabilityState::Available, None, Some(backend.base_url()));
                     }
                     Err(e) => {
                         log::error!("Failed to start Python backend: {}", e);
                         // Note: We don't exit the app - it can run without backend
+                        availability.set(backend_availability::AvailabilityState::Unavailable, Some(e.to_string()), None);
                     }
                 }
             });
@@ -139,36 +3444,308 @@ pub fn run() {
             Ok(())
         })
         .on_window_event(|window, event| {
-            // Listen for OS theme changes
+            // Release event bus state for closed windows, and close the terminals
+            // they opened
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Some(bus) = window.try_state::<Arc<EventBus>>() {
+                    let terminals = bus.remove_window(window.label());
+                    if let (false, Some(backend)) = (terminals.is_empty(), window.try_state::<Arc<TerminalBackend>>()) {
+                        let backend = backend.inner().clone();
+                        tauri::async_runtime::spawn(async move {
+                            for terminal_id in terminals {
+                                let _ = backend.close_terminal(&terminal_id).await;
+                            }
+                        });
+                    }
+                }
+            }
+
+            // Remember where tracked windows are, per monitor layout
+            if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
+                if let Some(window_state) = window.try_state::<Arc<WindowStateStore>>() {
+                    window_state.record(window);
+                }
+            }
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if let Some(window_state) = window.try_state::<Arc<WindowStateStore>>() {
+                    window_state.record(window);
+                    if let Err(e) = window_state.save() {
+                        log::warn!("{}", e);
+                    }
+                }
+            }
+
+            // Files dropped onto a window: the frontend decides which thread gets
+            // them and calls attach_file_to_thread
+            if let tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, position }) = event {
+                attachments::note_dropped(paths);
+                let payload = serde_json::json!({ "paths": paths, "position": { "x": position.x, "y": position.y } });
+                if let Err(e) = window.emit("files-dropped", payload) {
+                    log::error!("Failed to emit files-dropped event: {}", e);
+                }
+            }
+
             if let tauri::WindowEvent::ThemeChanged(theme) = event {
                 let theme_str = match theme {
                     tauri::Theme::Dark => "dark",
                     tauri::Theme::Light => "light",
                     _ => "unknown",
                 };
-                log::info!("OS theme changed to: {}", theme_str);
+                log::info!("Window theme changed to: {}", theme_str);
 
-                // Emit event to frontend
-                if let Err(e) = window.emit("theme-changed", theme_str) {
+                // A theme chosen in settings wins over the OS one
+                let resolved = match window.try_state::<Arc<SettingsStore>>() {
+                    Some(settings) => settings.theme(window.app_handle()).resolved,
+                    None => theme_str,
+                };
+                if let Err(e) = window.emit("theme-changed", resolved) {
                     log::error!("Failed to emit theme-changed event: {}", e);
                 }
             }
+
+            if let tauri::WindowEvent::Focused(true) = event {
+                if let Some(notifier) = window.try_state::<Arc<notifications::Notifier>>() {
+                    notifier.on_focus();
+                }
+            }
+
+            // Contrast/motion settings often change together with the theme, or while
+            // the app is in the background
+            if matches!(event, tauri::WindowEvent::ThemeChanged(_) | tauri::WindowEvent::Focused(true)) {
+                if let Some(accessibility) = window.try_state::<Arc<AccessibilityMonitor>>() {
+                    let accessibility = accessibility.inner().clone();
+                    let app_handle = window.app_handle().clone();
+                    tauri::async_runtime::spawn_blocking(move || accessibility.refresh(&app_handle));
+                }
+            }
         })
         .invoke_handler(tauri::generate_handler![
             greet,
             init_filesystem,
+            get_filesystem_capabilities,
             list_blueprints,
             create_thread,
+            get_blueprint_variables,
+            create_thread_with_variables,
+            fork_thread,
+            duplicate_thread,
             load_thread,
+            stream_thread,
             append_thread_events,
+            queue_thread_events,
+            flush_thread,
             list_threads,
+            load_thread_page,
+            search_threads,
+            migrate_storage_to_sqlite,
+            get_thread_event_count,
+            rebuild_thread_index,
+            close_thread_writer,
             update_thread_title,
+            archive_thread,
+            unarchive_thread,
+            set_thread_tags,
+            update_thread_blueprint,
+            get_thread_provenance,
+            export_thread,
+            list_export_formats,
+            export_workspace,
+            import_workspace,
+            list_workspaces,
+            create_workspace,
+            switch_workspace,
+            create_share_bundle,
+            open_share_bundle,
+            delete_thread,
+            restore_thread,
+            batch_thread_operation,
+            get_batch_operation,
+            cancel_batch_operation,
+            get_viewer_mode,
+            get_settings,
+            update_settings,
+            set_theme,
+            get_theme,
+            get_system_locale,
+            get_translations,
+            set_window_zoom,
+            set_global_shortcut,
+            notify_user,
+            set_secret,
+            get_secret,
+            delete_secret,
+            list_secret_names,
+            check_for_updates,
+            download_update,
+            install_update_and_restart,
+            generate_diagnostics_bundle,
+            list_crash_reports,
+            dismiss_crash_reports,
+            get_storage_stats,
+            verify_storage_integrity,
+            gc_storage,
+            list_trash,
+            create_snapshot,
+            list_snapshots,
+            list_backups,
+            restore_backup,
+            diff_snapshot,
+            restore_thread_from_snapshot,
+            purge_trash,
+            create_scratch_dir,
+            list_scratch_dirs,
+            remove_scratch_dir,
+            save_draft,
+            get_draft,
+            clear_draft,
+            get_thread_protocol,
+            compact_thread,
+            redact_thread,
+            compact_threads,
+            migrate_thread,
+            compact_stream_events,
+            amend_event,
+            get_event_history,
+            list_quarantined,
+            recover_quarantined,
+            get_attachment,
+            attach_file_to_thread,
+            read_attachment,
+            ingest_image,
+            copy_to_clipboard,
+            read_clipboard,
+            get_activity_timeline,
+            get_thread_stats,
+            get_usage_report,
+            enqueue_run,
+            list_runs,
+            cancel_run,
+            list_scheduled_jobs,
+            run_scheduled_job,
+            subscribe_thread,
+            unsubscribe_thread,
             get_backend_url,
+            get_backend_mode,
+            set_backend_mode,
+            start_backend,
+            stop_backend,
+            list_backends,
+            backend_request,
+            get_backend_queue,
+            get_backend_proxy,
+            proxy_request,
+            get_proxy_log,
+            clear_proxy_log,
+            get_interrupted_runs,
+            dismiss_interrupted_run,
+            get_backend_status,
+            get_backend_availability,
+            wait_for_backend,
+            tail_backend_log,
+            clear_backend_logs,
+            force_cleanup_backend,
+            restart_backend,
+            test_backend_command,
+            get_backend_env_preview,
+            get_accessibility_prefs,
             read_blueprint,
+            save_blueprint,
+            duplicate_blueprint,
+            get_blueprint_history,
+            restore_blueprint_version,
+            list_builtin_blueprints,
+            install_builtin_blueprint,
+            list_pending_blueprint_imports,
+            confirm_blueprint_import,
+            reject_blueprint_import,
+            rename_blueprint,
+            delete_blueprint,
+            get_metrics,
+            subscribe_events,
+            open_thread_window,
+            open_blueprint_window,
+            unsubscribe_events,
+            resync_state,
+            stream_backend_request,
+            open_backend_stream,
+            list_webhooks,
+            add_webhook,
+            remove_webhook,
+            get_webhook_deliveries,
+            get_hook_runs,
+            sync_now,
+            get_sync_status,
+            refresh_semantic_index,
+            semantic_search,
+            start_share_session,
+            stop_share_session,
+            get_share_session,
+            share_with_system,
+            get_obsidian_config,
+            set_obsidian_vault,
+            sync_obsidian_vault,
+            list_plugins,
+            reload_plugins,
+            invoke_plugin_command,
+            start_dictation,
+            stop_dictation,
+            check_spelling,
+            get_spelling_suggestions,
+            get_spellcheck_languages,
+            get_user_dictionary,
+            add_user_dictionary_word,
+            remove_user_dictionary_word,
+            start_companion_pairing,
+            stop_companion_server,
+            get_companion_status,
+            list_companion_devices,
+            revoke_companion_device,
             spawn_terminal,
+            get_process_stats,
+            list_terminal_profiles,
+            spawn_terminal_from_profile,
+            approve_terminal_command,
+            deny_terminal_command,
+            get_terminal_command_allowlist,
+            set_terminal_command_allowlist,
             write_to_terminal,
+            send_key,
+            confirm_terminal_write,
+            deny_terminal_write,
+            list_pending_terminal_writes,
+            get_terminal_guard,
+            set_terminal_guard,
             resize_terminal,
-            close_terminal
+            close_terminal,
+            create_terminal_group,
+            list_terminal_groups,
+            close_terminal_group,
+            open_path_in_editor,
+            open_in_editor,
+            reveal_in_file_manager,
+            open_in_default_app,
+            signal_terminal,
+            attach_terminal_output,
+            detach_terminal_output,
+            export_terminal_buffer,
+            start_terminal_recording,
+            stop_terminal_recording,
+            get_terminal_scrollback,
+            get_terminal_info,
+            get_terminal_activity,
+            run_performance_check,
+            run_doctor,
+            list_tasks,
+            cancel_task,
+            cleanup_app_data,
+            list_fs_grants,
+            grant_fs_root,
+            revoke_fs_root,
+            revoke_fs_operation,
+            read_file,
+            write_file,
+            list_dir,
+            get_audit_log
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
@@ -185,17 +3762,7 @@ pub fn run() {
                 // Perform synchronous shutdown using block_on
                 let handle = app_handle.clone();
                 tauri::async_runtime::block_on(async move {
-                    // Shutdown terminal backend
-                    if let Some(terminal_backend) = handle.try_state::<Arc<TerminalBackend>>() {
-                        log::info!("Shutting down terminal backend...");
-                        terminal_backend.shutdown_all().await;
-                    }
-
-                    // Shutdown Python backend
-                    if let Some(python_backend) = handle.try_state::<Arc<PythonBackend>>() {
-                        log::info!("Shutting down Python backend...");
-                        python_backend.shutdown().await;
-                    }
+                    shutdown_services(&handle).await;
 
                     log::info!("Cleanup complete, exiting...");
                 });
@@ -210,20 +3777,12 @@ pub fn run() {
                 // Perform synchronous shutdown using block_on
                 let handle = app_handle.clone();
                 tauri::async_runtime::block_on(async move {
-                    // Shutdown terminal backend
-                    if let Some(terminal_backend) = handle.try_state::<Arc<TerminalBackend>>() {
-                        log::info!("Shutting down terminal backend...");
-                        terminal_backend.shutdown_all().await;
-                    }
-
-                    // Shutdown Python backend
-                    if let Some(python_backend) = handle.try_state::<Arc<PythonBackend>>() {
-                        log::info!("Shutting down Python backend...");
-                        python_backend.shutdown().await;
-                    }
+                    shutdown_services(&handle).await;
 
                     log::info!("Final cleanup complete");
                 });
+
+                telemetry::shutdown();
             }
             _ => {}
         }
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// Size a log file may reach before it's rotated
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Log files kept, counting the current one
+const MAX_LOG_FILES: usize = 5;
+
+/// Age at which a log file is rotated, and rotated files are deleted
+const MAX_LOG_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// An append-only log file that rotates to `<name>.1`, `<name>.2`, ... when it
+/// gets too large or too old, keeping at most `MAX_LOG_FILES` files
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    opened_at: SystemTime,
+}
+
+/// `<name>.<index>` for a rotated log file
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// The rotated files that exist for `path`, newest first
+pub fn rotated_paths(path: &Path) -> Vec<PathBuf> {
+    (1..MAX_LOG_FILES)
+        .map(|index| rotated_path(path, index))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+fn is_expired(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > MAX_LOG_AGE)
+}
+
+/// Delete a log and its rotated files, returning how many were removed (blocking)
+pub fn clear(path: &Path) -> Result<usize, String> {
+    let mut removed = 0;
+    for rotated in rotated_paths(path) {
+        std::fs::remove_file(&rotated).map_err(|e| format!("Failed to remove {}: {}", rotated.display(), e))?;
+        removed += 1;
+    }
+    // The backend may be writing to it, so empty it rather than delete it
+    if path.exists() {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| format!("Failed to clear {}: {}", path.display(), e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+impl RotatingLog {
+    /// Open `path` for appending, rotating it first if it's already too large or old
+    pub async fn open(path: PathBuf) -> Result<Self, String> {
+        let existing = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+        if existing >= MAX_LOG_BYTES || (existing > 0 && is_expired(&path)) {
+            Self::shift(&path).await?;
+        }
+        Self::prune(&path).await;
+
+        let (file, size) = Self::open_file(&path).await?;
+        Ok(Self {
+            path,
+            file,
+            size,
+            opened_at: SystemTime::now(),
+        })
+    }
+
+    async fn open_file(path: &Path) -> Result<(File, u64), String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| format!("Failed to create log file: {}", e))?;
+        let size = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+        Ok((file, size))
+    }
+
+    /// Move `<name>.N` to `<name>.N+1`, dropping the oldest, and `<name>` to `<name>.1`
+    async fn shift(path: &Path) -> Result<(), String> {
+        let _ = tokio::fs::remove_file(rotated_path(path, MAX_LOG_FILES - 1)).await;
+        for index in (1..MAX_LOG_FILES - 1).rev() {
+            let from = rotated_path(path, index);
+            if tokio::fs::try_exists(&from).await.unwrap_or(false) {
+                tokio::fs::rename(&from, rotated_path(path, index + 1))
+                    .await
+                    .map_err(|e| format!("Failed to rotate {}: {}", from.display(), e))?;
+            }
+        }
+        tokio::fs::rename(path, rotated_path(path, 1))
+            .await
+            .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))
+    }
+
+    /// Delete rotated files past the age limit
+    async fn prune(path: &Path) {
+        for rotated in rotated_paths(path) {
+            if is_expired(&rotated) {
+                if let Err(e) = tokio::fs::remove_file(&rotated).await {
+                    log::warn!("Failed to remove old log {}: {}", rotated.display(), e);
+                }
+            }
+        }
+    }
+
+    /// Start a new file, keeping the current one as `<name>.1`
+    async fn rotate(&mut self) -> Result<(), String> {
+        let _ = self.file.flush().await;
+        Self::shift(&self.path).await?;
+        Self::prune(&self.path).await;
+        (self.file, self.size) = Self::open_file(&self.path).await?;
+        self.opened_at = SystemTime::now();
+        log::info!("Rotated log {:?}", self.path);
+        Ok(())
+    }
+
+    /// Append a line, rotating first if it would push the file past its limits
+    pub async fn write_line(&mut self, line: &str) -> Result<(), String> {
+        let len = line.len() as u64 + 1;
+        let expired = self.opened_at.elapsed().is_ok_and(|age| age > MAX_LOG_AGE);
+        if self.size > 0 && (self.size + len > MAX_LOG_BYTES || expired) {
+            self.rotate().await?;
+        }
+
+        self.file
+            .write_all(format!("{}\n", line).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write log: {}", e))?;
+        self.size += len;
+        Ok(())
+    }
+}
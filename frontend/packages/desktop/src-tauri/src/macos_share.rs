@@ -0,0 +1,125 @@
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{define_class, msg_send, AnyThread, DefinedClass, MainThreadMarker, MainThreadOnly};
+use objc2_app_kit::{
+    NSApplication, NSPasteboard, NSPasteboardTypeString, NSSharingServicePicker, NSUpdateDynamicServices, NSView,
+};
+use objc2_foundation::{NSArray, NSObject, NSRectEdge, NSString, NSURL};
+use std::cell::RefCell;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::event_bus::EventBus;
+
+thread_local! {
+    /// Keeps the registered Services provider alive for the app's lifetime
+    static SERVICES_PROVIDER: RefCell<Option<Retained<ServicesProvider>>> = const { RefCell::new(None) };
+    /// Keeps the most recent share picker alive while it is on screen
+    static SHARE_PICKER: RefCell<Option<Retained<NSSharingServicePicker>>> = const { RefCell::new(None) };
+}
+
+/// Instance state for the Services provider
+struct ProviderIvars {
+    app_handle: AppHandle,
+}
+
+define_class!(
+    // SAFETY: NSObject has no subclassing requirements and we don't implement Drop.
+    #[unsafe(super(NSObject))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "ChimeraServicesProvider"]
+    #[ivars = ProviderIvars]
+    struct ServicesProvider;
+
+    impl ServicesProvider {
+        /// Handler for the "New Chimera Thread with Selection" service (see Info.plist)
+        #[unsafe(method(newThreadWithText:userData:error:))]
+        fn new_thread_with_text(
+            &self,
+            pboard: &NSPasteboard,
+            _user_data: Option<&NSString>,
+            _error: *mut *mut NSString,
+        ) {
+            let Some(text) = pboard.stringForType(unsafe { NSPasteboardTypeString }) else {
+                log::warn!("Services request had no text on the pasteboard");
+                return;
+            };
+            let text = text.to_string();
+
+            let app_handle = &self.ivars().app_handle;
+            log::info!("Received {} chars from macOS Services", text.len());
+
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+
+            if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+                bus.publish("service-new-thread", serde_json::json!({ "text": text }));
+            }
+        }
+    }
+);
+
+impl ServicesProvider {
+    fn new(mtm: MainThreadMarker, app_handle: AppHandle) -> Retained<Self> {
+        let this = mtm.alloc::<Self>().set_ivars(ProviderIvars { app_handle });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+/// Register the app as a macOS Services provider. Must run on the main thread.
+pub fn register_services(app_handle: AppHandle) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        log::error!("macOS Services must be registered on the main thread");
+        return;
+    };
+
+    let provider = ServicesProvider::new(mtm, app_handle);
+    let provider_object: &AnyObject = &provider;
+
+    let app = NSApplication::sharedApplication(mtm);
+    unsafe { app.setServicesProvider(Some(provider_object)) };
+    NSUpdateDynamicServices();
+
+    SERVICES_PROVIDER.with(|slot| *slot.borrow_mut() = Some(provider));
+    log::info!("Registered macOS Services provider");
+}
+
+/// Show the native share sheet for a file and/or text, anchored to the window
+pub fn show_share_sheet(window: &tauri::WebviewWindow, path: Option<String>, text: Option<String>) -> Result<(), String> {
+    if path.is_none() && text.is_none() {
+        return Err("Nothing to share".to_string());
+    }
+
+    if let Some(path) = &path {
+        if !std::path::Path::new(path).exists() {
+            return Err(format!("File not found: {}", path));
+        }
+    }
+
+    // Raw pointers aren't Send; the view is only dereferenced on the main thread
+    let ns_view = window.ns_view().map_err(|e| format!("Failed to get window view: {}", e))? as usize;
+
+    window
+        .run_on_main_thread(move || {
+            let view: &NSView = unsafe { &*(ns_view as *const NSView) };
+
+            let mut items: Vec<Retained<AnyObject>> = Vec::new();
+            if let Some(path) = &path {
+                let url = NSURL::fileURLWithPath(&NSString::from_str(path));
+                items.push(Retained::into_super(Retained::into_super(url)));
+            }
+            if let Some(text) = &text {
+                items.push(Retained::into_super(Retained::into_super(NSString::from_str(text))));
+            }
+
+            let array = NSArray::from_retained_slice(&items);
+            let picker = unsafe { NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &array) };
+            picker.showRelativeToRect_ofView_preferredEdge(view.bounds(), view, NSRectEdge::MinY);
+
+            SHARE_PICKER.with(|slot| *slot.borrow_mut() = Some(picker));
+        })
+        .map_err(|e| format!("Failed to show share sheet: {}", e))
+}
@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Histogram bucket bounds (seconds) for command latencies
+const LATENCY_BUCKETS: [f64; 11] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+
+/// Tracing target of spans created by `#[tauri::command]` handlers in lib.rs
+const COMMAND_TARGET: &str = "chimera_desktop_lib";
+
+/// Cumulative-bucket latency histogram
+struct Histogram {
+    counts: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            counts: [0; LATENCY_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, seconds: f64) {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(self.counts.iter_mut()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let buckets: serde_json::Map<String, serde_json::Value> = LATENCY_BUCKETS
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(bound, count)| (bound.to_string(), serde_json::json!(count)))
+            .collect();
+        serde_json::json!({
+            "count": self.count,
+            "sum_seconds": self.sum,
+            "mean_seconds": if self.count > 0 { self.sum / self.count as f64 } else { 0.0 },
+            "buckets": buckets,
+        })
+    }
+
+    fn write(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(self.counts.iter()) {
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", name, labels, sep, bound, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, sep, self.count);
+        let braces = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_sum{} {}", name, braces, self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, braces, self.count);
+    }
+}
+
+/// How often a line is added to the metrics JSONL
+const JSONL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Process-wide counters and histograms
+struct Metrics {
+    enabled: AtomicBool,
+    command_latency: Mutex<BTreeMap<String, Histogram>>,
+    health_check_latency: Mutex<Histogram>,
+    filesystem_latency: Mutex<BTreeMap<String, Histogram>>,
+    events_appended: AtomicU64,
+    append_bytes: AtomicU64,
+    terminal_output_bytes: AtomicU64,
+    terminal_spawn_failures: AtomicU64,
+    backend_starts: AtomicU64,
+    health_check_failures: AtomicU64,
+    task_panics: AtomicU64,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| Metrics {
+    enabled: AtomicBool::new(false),
+    command_latency: Mutex::new(BTreeMap::new()),
+    health_check_latency: Mutex::new(Histogram::new()),
+    filesystem_latency: Mutex::new(BTreeMap::new()),
+    events_appended: AtomicU64::new(0),
+    append_bytes: AtomicU64::new(0),
+    terminal_output_bytes: AtomicU64::new(0),
+    terminal_spawn_failures: AtomicU64::new(0),
+    backend_starts: AtomicU64::new(0),
+    health_check_failures: AtomicU64::new(0),
+    task_panics: AtomicU64::new(0),
+});
+
+/// Whether metrics collection is switched on
+pub fn enabled() -> bool {
+    METRICS.enabled.load(Ordering::Relaxed)
+}
+
+/// Record the latency of a command
+pub fn record_command(command: &str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    METRICS
+        .command_latency
+        .lock()
+        .unwrap()
+        .entry(command.to_string())
+        .or_insert_with(Histogram::new)
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a backend health check and how long it took to answer
+pub fn record_health_check(elapsed: Duration, healthy: bool) {
+    if !enabled() {
+        return;
+    }
+    METRICS.health_check_latency.lock().unwrap().observe(elapsed.as_secs_f64());
+    if !healthy {
+        METRICS.health_check_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Record how long a filesystem operation (`load_thread`, `append`, ...) took
+pub fn record_filesystem(operation: &str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    METRICS
+        .filesystem_latency
+        .lock()
+        .unwrap()
+        .entry(operation.to_string())
+        .or_insert_with(Histogram::new)
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a terminal that failed to start
+pub fn record_terminal_spawn_failure() {
+    if enabled() {
+        METRICS.terminal_spawn_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Record events written to a thread file
+pub fn record_append(events: usize, bytes: usize) {
+    if enabled() {
+        METRICS.events_appended.fetch_add(events as u64, Ordering::Relaxed);
+        METRICS.append_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// Record bytes read from a terminal
+pub fn record_terminal_output(bytes: usize) {
+    if enabled() {
+        METRICS.terminal_output_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// Record a backend process start
+pub fn record_backend_start() {
+    if enabled() {
+        METRICS.backend_starts.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Record a supervised background task panicking
+pub fn record_task_panic() {
+    if enabled() {
+        METRICS.task_panics.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn write_counter(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+
+    let name = "chimera_command_duration_seconds";
+    let _ = writeln!(out, "# HELP {} Latency of Tauri command handlers", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (command, histogram) in METRICS.command_latency.lock().unwrap().iter() {
+        histogram.write(&mut out, name, &format!("command=\"{}\"", command));
+    }
+
+    let name = "chimera_backend_health_check_seconds";
+    let _ = writeln!(out, "# HELP {} Time for the backend to answer health checks", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    METRICS.health_check_latency.lock().unwrap().write(&mut out, name, "");
+
+    let name = "chimera_filesystem_operation_seconds";
+    let _ = writeln!(out, "# HELP {} Latency of thread file operations", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (operation, histogram) in METRICS.filesystem_latency.lock().unwrap().iter() {
+        histogram.write(&mut out, name, &format!("operation=\"{}\"", operation));
+    }
+
+    write_counter(
+        &mut out,
+        "chimera_thread_events_appended_total",
+        "Events appended to thread files",
+        METRICS.events_appended.load(Ordering::Relaxed),
+    );
+    write_counter(
+        &mut out,
+        "chimera_thread_append_bytes_total",
+        "Bytes appended to thread files",
+        METRICS.append_bytes.load(Ordering::Relaxed),
+    );
+    write_counter(
+        &mut out,
+        "chimera_terminal_output_bytes_total",
+        "Bytes read from terminal PTYs",
+        METRICS.terminal_output_bytes.load(Ordering::Relaxed),
+    );
+
+    let starts = METRICS.backend_starts.load(Ordering::Relaxed);
+    write_counter(&mut out, "chimera_backend_starts_total", "Python backend process starts", starts);
+    write_counter(
+        &mut out,
+        "chimera_backend_restarts_total",
+        "Python backend starts after the first",
+        starts.saturating_sub(1),
+    );
+    write_counter(
+        &mut out,
+        "chimera_backend_health_check_failures_total",
+        "Backend health checks that failed or timed out",
+        METRICS.health_check_failures.load(Ordering::Relaxed),
+    );
+    write_counter(
+        &mut out,
+        "chimera_terminal_spawn_failures_total",
+        "Terminals that failed to start",
+        METRICS.terminal_spawn_failures.load(Ordering::Relaxed),
+    );
+    write_counter(
+        &mut out,
+        "chimera_task_panics_total",
+        "Supervised background tasks that panicked",
+        METRICS.task_panics.load(Ordering::Relaxed),
+    );
+
+    out
+}
+
+/// All metrics as JSON, for `get_metrics` and the metrics JSONL
+pub fn snapshot() -> serde_json::Value {
+    let histograms = |map: &BTreeMap<String, Histogram>| -> serde_json::Map<String, serde_json::Value> {
+        map.iter().map(|(key, histogram)| (key.clone(), histogram.to_json())).collect()
+    };
+    let starts = METRICS.backend_starts.load(Ordering::Relaxed);
+    serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "command_latency": histograms(&METRICS.command_latency.lock().unwrap()),
+        "filesystem_latency": histograms(&METRICS.filesystem_latency.lock().unwrap()),
+        "backend_health_check_latency": METRICS.health_check_latency.lock().unwrap().to_json(),
+        "backend_health_check_failures": METRICS.health_check_failures.load(Ordering::Relaxed),
+        "backend_starts": starts,
+        "backend_restarts": starts.saturating_sub(1),
+        "events_appended": METRICS.events_appended.load(Ordering::Relaxed),
+        "append_bytes": METRICS.append_bytes.load(Ordering::Relaxed),
+        "terminal_output_bytes": METRICS.terminal_output_bytes.load(Ordering::Relaxed),
+        "terminal_spawn_failures": METRICS.terminal_spawn_failures.load(Ordering::Relaxed),
+        "task_panics": METRICS.task_panics.load(Ordering::Relaxed),
+    })
+}
+
+/// Tracing layer that times command spans into the latency histogram
+pub struct CommandTimingLayer;
+
+impl<S> Layer<S> for CommandTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().target() != COMMAND_TARGET {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Instant::now());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(started) = span.extensions().get::<Instant>() {
+                record_command(span.name(), started.elapsed());
+            }
+        }
+    }
+}
+
+/// Enable collection when `CHIMERA_METRICS_PORT` or `CHIMERA_METRICS_JSONL` is
+/// set, returning the port to serve on. Call before `telemetry::init` so the
+/// timing layer is installed.
+pub fn init_from_env() -> Option<u16> {
+    if jsonl_path().is_some() {
+        METRICS.enabled.store(true, Ordering::Relaxed);
+    }
+    let port = std::env::var("CHIMERA_METRICS_PORT").ok()?.parse::<u16>().ok()?;
+    METRICS.enabled.store(true, Ordering::Relaxed);
+    Some(port)
+}
+
+/// Where to append metrics snapshots: `CHIMERA_METRICS_JSONL`, or `metrics.jsonl` in
+/// the logs directory when it's `1`
+pub fn jsonl_path() -> Option<PathBuf> {
+    match std::env::var("CHIMERA_METRICS_JSONL").ok()?.as_str() {
+        "" | "0" | "false" => None,
+        "1" | "true" => Some(crate::filesystem::get_data_dir().ok()?.join("logs").join("metrics.jsonl")),
+        path => Some(PathBuf::from(path)),
+    }
+}
+
+/// Append a snapshot to `path` every minute, for looking back at a slow session
+pub async fn write_jsonl(path: PathBuf) {
+    use tokio::io::AsyncWriteExt;
+
+    if let Some(dir) = path.parent() {
+        let _ = tokio::fs::create_dir_all(dir).await;
+    }
+    log::info!("Writing metrics to {}", path.display());
+    let mut interval = tokio::time::interval(JSONL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let mut line = snapshot().to_string();
+        line.push('\n');
+        let written = match tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await {
+            Ok(mut file) => file.write_all(line.as_bytes()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            log::warn!("Failed to write metrics to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Serve `/metrics` on localhost
+pub async fn serve(port: u16) {
+    let router = axum::Router::new().route(
+        "/metrics",
+        axum::routing::get(|| async { ([("content-type", "text/plain; version=0.0.4")], render()) }),
+    );
+
+    match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => {
+            log::info!("Serving metrics on http://127.0.0.1:{}/metrics", port);
+            if let Err(e) = axum::serve(listener, router).await {
+                log::error!("Metrics server error: {}", e);
+            }
+        }
+        Err(e) => log::error!("Failed to bind metrics port {}: {}", port, e),
+    }
+}
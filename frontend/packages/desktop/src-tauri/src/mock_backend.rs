@@ -0,0 +1,330 @@
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::{Method, Uri};
+use axum::response::Response;
+use axum::Router;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+
+/// Which backend the app talks to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendMode {
+    /// The Python backend process
+    #[default]
+    Python,
+    /// An embedded server replaying fixtures, for working without a Python toolchain
+    Mock,
+}
+
+/// Mock mode from `CHIMERA_MOCK_BACKEND`: `1` or `true` replays the fixtures in
+/// the data directory, a directory path replays the ones there
+pub fn requested() -> Option<Option<PathBuf>> {
+    let value = std::env::var("CHIMERA_MOCK_BACKEND").ok()?;
+    match value.trim() {
+        "" | "0" | "false" => None,
+        "1" | "true" => Some(None),
+        path => Some(Some(PathBuf::from(path))),
+    }
+}
+
+/// Fixtures live in `mock-backend/` in the data directory
+pub fn default_fixtures_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("mock-backend"))
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+/// A canned response. Fixture files are JSON objects of this shape, or arrays of
+/// them; the first fixture matching a request wins, files taken in name order.
+#[derive(Debug, Clone, Deserialize)]
+struct Fixture {
+    #[serde(default = "default_method")]
+    method: String,
+    path: String,
+    /// Only answer requests whose body contains this text
+    #[serde(default)]
+    matches: Option<String>,
+    #[serde(default = "default_status")]
+    status: u16,
+    /// JSON response body, when there are no `events`
+    #[serde(default)]
+    body: serde_json::Value,
+    /// Sent as an SSE stream ending with `[DONE]`, like `/stream`
+    #[serde(default)]
+    events: Option<Vec<serde_json::Value>>,
+    /// Pause before the response, and between streamed events
+    #[serde(default)]
+    delay_ms: u64,
+}
+
+impl Fixture {
+    fn answers(&self, method: &Method, path: &str, body: &str) -> bool {
+        self.method.eq_ignore_ascii_case(method.as_str())
+            && self.path == path
+            && self.matches.as_deref().is_none_or(|text| body.contains(text))
+    }
+}
+
+/// Every fixture in `dir`, in file name order. Files that don't parse are
+/// skipped with a warning, so one bad edit doesn't take the rest down (blocking).
+fn load_fixtures(dir: &Path) -> Vec<Fixture> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+
+    let mut fixtures = Vec::new();
+    for path in files {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()))
+            .and_then(|value| match value {
+                serde_json::Value::Array(_) => serde_json::from_value::<Vec<Fixture>>(value).map_err(|e| e.to_string()),
+                value => serde_json::from_value::<Fixture>(value).map(|f| vec![f]).map_err(|e| e.to_string()),
+            });
+        match parsed {
+            Ok(loaded) => fixtures.extend(loaded),
+            Err(e) => log::warn!("Skipping mock backend fixture {}: {}", path.display(), e),
+        }
+    }
+    fixtures
+}
+
+/// Responses for requests no fixture answers: health, an echoing `/stream` and `/halt`
+fn builtin_fixture(method: &Method, path: &str, body: &serde_json::Value) -> Option<Fixture> {
+    let fixture = |body: serde_json::Value, events: Option<Vec<serde_json::Value>>| Fixture {
+        method: method.to_string(),
+        path: path.to_string(),
+        matches: None,
+        status: 200,
+        body,
+        events,
+        delay_ms: 0,
+    };
+    match (method.as_str(), path) {
+        ("GET", "/") => Some(fixture(serde_json::json!({ "status": "ok", "mock": true }), None)),
+        ("POST", "/stream") => {
+            let content = body
+                .get("user_input")
+                .and_then(|u| u.get("content"))
+                .and_then(|c| c.as_str())
+                .unwrap_or("");
+            Some(fixture(
+                serde_json::Value::Null,
+                Some(vec![
+                    serde_json::json!({ "type": "start" }),
+                    serde_json::json!({ "type": "text-complete", "id": "mock-text", "content": format!("Echo: {}", content) }),
+                    serde_json::json!({ "type": "finish" }),
+                ]),
+            ))
+        }
+        ("POST", "/halt") => Some(fixture(serde_json::json!({ "status": "halted" }), None)),
+        _ => None,
+    }
+}
+
+fn render(fixture: Fixture) -> Response {
+    let delay = Duration::from_millis(fixture.delay_ms);
+    let builder = Response::builder().status(fixture.status);
+    match fixture.events {
+        Some(events) => {
+            let lines = events
+                .into_iter()
+                .map(|event| format!("data: {}\n\n", event))
+                .chain(std::iter::once("data: [DONE]\n\n".to_string()));
+            let stream = futures_util::stream::iter(lines).then(move |line| async move {
+                tokio::time::sleep(delay).await;
+                Ok::<_, std::convert::Infallible>(line)
+            });
+            builder
+                .header("content-type", "text/event-stream")
+                .header("cache-control", "no-cache")
+                .body(Body::from_stream(stream))
+                .unwrap_or_default()
+        }
+        None => builder
+            .header("content-type", "application/json")
+            .body(Body::from(fixture.body.to_string()))
+            .unwrap_or_default(),
+    }
+}
+
+struct ServerState {
+    fixtures: PathBuf,
+    bus: Option<Arc<EventBus>>,
+}
+
+/// Answer any request from the fixtures, read afresh each time so they can be
+/// edited while the app runs
+async fn respond(State(state): State<Arc<ServerState>>, method: Method, uri: Uri, body: Bytes) -> Response {
+    let path = uri.path().to_string();
+    let text = String::from_utf8_lossy(&body).to_string();
+    let json: serde_json::Value =
+        serde_json::from_str(&text).unwrap_or_else(|_| serde_json::Value::String(text.clone()));
+    if let Some(bus) = &state.bus {
+        bus.publish(
+            "mock-backend-request",
+            serde_json::json!({ "method": method.as_str(), "path": path, "body": json }),
+        );
+    }
+
+    let dir = state.fixtures.clone();
+    let fixtures = filesystem::blocking(move || Ok(load_fixtures(&dir))).await.unwrap_or_default();
+    let fixture = fixtures
+        .into_iter()
+        .find(|fixture| fixture.answers(&method, &path, &text))
+        .or_else(|| builtin_fixture(&method, &path, &json));
+    match fixture {
+        Some(fixture) => {
+            if fixture.events.is_none() && fixture.delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(fixture.delay_ms)).await;
+            }
+            render(fixture)
+        }
+        None => Response::builder()
+            .status(404)
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "error": format!("No mock fixture for {} {}", method, path) }).to_string(),
+            ))
+            .unwrap_or_default(),
+    }
+}
+
+/// A running mock backend
+pub struct MockServer {
+    pub url: String,
+    pub fixtures: PathBuf,
+    shutdown: watch::Sender<bool>,
+}
+
+impl MockServer {
+    pub fn stop(&self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+/// Start a mock backend on an ephemeral localhost port, replaying the fixtures
+/// in `fixtures`. Requests are published as `mock-backend-request` when a bus is given.
+pub async fn serve(fixtures: PathBuf, bus: Option<Arc<EventBus>>) -> Result<MockServer, String> {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("Failed to bind mock backend: {}", e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read mock backend address: {}", e))?;
+
+    let router = Router::new().fallback(respond).with_state(Arc::new(ServerState {
+        fixtures: fixtures.clone(),
+        bus,
+    }));
+
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        let shutdown = async move {
+            let _ = shutdown_rx.wait_for(|stopped| *stopped).await;
+        };
+        if let Err(e) = axum::serve(listener, router).with_graceful_shutdown(shutdown).await {
+            log::error!("Mock backend error: {}", e);
+        }
+    });
+
+    Ok(MockServer {
+        url: format!("http://{}", addr),
+        fixtures,
+        shutdown: shutdown_tx,
+    })
+}
+
+/// The backend mode, and the mock backend's address while it's in use
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendModeState {
+    pub mode: BackendMode,
+    pub url: Option<String>,
+    pub fixtures_dir: Option<String>,
+}
+
+/// Switches the app between the Python backend and the mock one. While the mock
+/// runs, everything that looks up the backend's URL gets the mock's instead.
+pub struct MockBackend {
+    server: Mutex<Option<MockServer>>,
+    bus: Arc<EventBus>,
+}
+
+impl MockBackend {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            server: Mutex::new(None),
+            bus,
+        }
+    }
+
+    pub fn state(&self) -> BackendModeState {
+        let server = self.server.lock().unwrap();
+        BackendModeState {
+            mode: if server.is_some() { BackendMode::Mock } else { BackendMode::Python },
+            url: server.as_ref().map(|server| server.url.clone()),
+            fixtures_dir: server.as_ref().map(|server| server.fixtures.to_string_lossy().to_string()),
+        }
+    }
+
+    pub fn url(&self) -> Option<String> {
+        self.server.lock().unwrap().as_ref().map(|server| server.url.clone())
+    }
+
+    /// Start replaying fixtures from `fixtures`, or the data directory's. A
+    /// running mock is restarted when the directory changes.
+    pub async fn start(&self, fixtures: Option<PathBuf>) -> Result<String, String> {
+        let fixtures = match fixtures {
+            Some(dir) => dir,
+            None => default_fixtures_dir()?,
+        };
+        if let Some(server) = self.server.lock().unwrap().as_ref().filter(|server| server.fixtures == fixtures) {
+            return Ok(server.url.clone());
+        }
+
+        let server = serve(fixtures, Some(self.bus.clone())).await?;
+        let url = server.url.clone();
+        log::info!("Mock backend replaying {} at {}", server.fixtures.display(), url);
+        if let Some(previous) = self.server.lock().unwrap().replace(server) {
+            previous.stop();
+        }
+        self.bus
+            .publish("backend-lifecycle", serde_json::json!({ "state": "started", "url": url, "mock": true }));
+        Ok(url)
+    }
+
+    pub fn stop(&self) {
+        if let Some(server) = self.server.lock().unwrap().take() {
+            server.stop();
+            log::info!("Mock backend at {} stopped", server.url);
+            self.bus
+                .publish("backend-lifecycle", serde_json::json!({ "state": "stopped", "url": server.url, "mock": true }));
+        }
+    }
+}
+
+/// The mock backend's URL when the app is in mock mode
+pub fn url(app_handle: &AppHandle) -> Option<String> {
+    app_handle.try_state::<Arc<MockBackend>>()?.url()
+}
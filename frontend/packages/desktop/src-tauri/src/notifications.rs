@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::event_bus::EventBus;
+use crate::i18n::{self, I18n};
+use crate::settings::{NotificationSettings, SettingsStore};
+use crate::thread_index::ThreadIndex;
+
+/// A rule doesn't fire again for the same thread within this long
+const REPEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Focusing the app this soon after a notification counts as clicking it
+const CLICK_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Backend activity that can raise a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Trigger {
+    AgentFinished,
+    Error,
+    ApprovalNeeded,
+}
+
+impl Trigger {
+    /// The trigger a backend stream event fires, if any
+    fn of(event: &serde_json::Value) -> Option<Self> {
+        match event.get("type")?.as_str()? {
+            "finish" => Some(Trigger::AgentFinished),
+            "error" => Some(Trigger::Error),
+            "tool-approval-request" => Some(Trigger::ApprovalNeeded),
+            _ => None,
+        }
+    }
+
+    fn enabled(self, settings: &NotificationSettings) -> bool {
+        match self {
+            Trigger::AgentFinished => settings.agent_finished,
+            Trigger::Error => settings.errors,
+            Trigger::ApprovalNeeded => settings.approvals,
+        }
+    }
+
+    /// Translation key prefix for the rule's messages
+    fn key(self) -> &'static str {
+        match self {
+            Trigger::AgentFinished => "notification.agent_finished",
+            Trigger::Error => "notification.error",
+            Trigger::ApprovalNeeded => "notification.approval_needed",
+        }
+    }
+
+    fn title(self, i18n: &I18n, locale: &str) -> String {
+        i18n.message(locale, &format!("{}.title", self.key()), &[])
+    }
+
+    fn body(self, i18n: &I18n, locale: &str, thread_title: &str, event: &serde_json::Value) -> String {
+        let error = (self == Trigger::Error)
+            .then(|| event.get("errorText").and_then(|t| t.as_str()))
+            .flatten();
+        match error {
+            Some(error) => i18n.message(
+                locale,
+                &format!("{}.body_with_detail", self.key()),
+                &[("thread", thread_title), ("error", error)],
+            ),
+            None => i18n.message(locale, &format!("{}.body", self.key()), &[("thread", thread_title)]),
+        }
+    }
+}
+
+/// Shows desktop notifications, both on request and from rules over backend events
+pub struct Notifier {
+    app_handle: AppHandle,
+    /// When each rule last fired for each thread
+    last_fired: Mutex<HashMap<(String, Trigger), Instant>>,
+    /// Thread of the latest notification shown while the app was in the background
+    pending_click: Mutex<Option<(String, Instant)>>,
+}
+
+impl Notifier {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            last_fired: Mutex::new(HashMap::new()),
+            pending_click: Mutex::new(None),
+        }
+    }
+
+    fn app_focused(&self) -> bool {
+        self.app_handle
+            .webview_windows()
+            .values()
+            .any(|window| window.is_focused().unwrap_or(false))
+    }
+
+    /// Show a notification. Desktop notifications can't report clicks, so when the
+    /// app is in the background, focusing it soon after opens `thread_id`.
+    pub fn notify(&self, title: &str, body: &str, thread_id: Option<String>) -> Result<(), String> {
+        self.app_handle
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| format!("Failed to show notification: {}", e))?;
+
+        if let Some(thread_id) = thread_id.filter(|_| !self.app_focused()) {
+            *self.pending_click.lock().unwrap() = Some((thread_id, Instant::now()));
+        }
+        Ok(())
+    }
+
+    /// A window gained focus: if a notification brought the user back, send the main
+    /// window a `focus-thread` event for its thread
+    pub fn on_focus(&self) {
+        let Some((thread_id, shown_at)) = self.pending_click.lock().unwrap().take() else {
+            return;
+        };
+        if shown_at.elapsed() > CLICK_WINDOW {
+            return;
+        }
+        if let Some(window) = self.app_handle.get_webview_window("main") {
+            if let Err(e) = window.emit("focus-thread", serde_json::json!({ "thread_id": thread_id })) {
+                log::error!("Failed to emit focus-thread event: {}", e);
+            }
+        }
+    }
+
+    /// Apply the rules to one `backend-event` payload
+    async fn handle(&self, payload: &serde_json::Value) {
+        let Some(event) = payload.get("event") else { return };
+        let Some(trigger) = Trigger::of(event) else { return };
+        let Some(settings) = self.app_handle.try_state::<Arc<SettingsStore>>() else { return };
+        let settings = settings.get().notifications;
+        if !settings.enabled || !trigger.enabled(&settings) || (settings.only_when_unfocused && self.app_focused()) {
+            return;
+        }
+
+        let thread_id = payload.get("thread_id").and_then(|t| t.as_str()).map(str::to_string);
+        let key = (thread_id.clone().unwrap_or_default(), trigger);
+        {
+            let mut last_fired = self.last_fired.lock().unwrap();
+            if last_fired.get(&key).is_some_and(|at| at.elapsed() < REPEAT_INTERVAL) {
+                return;
+            }
+            last_fired.retain(|_, at| at.elapsed() < REPEAT_INTERVAL);
+            last_fired.insert(key, Instant::now());
+        }
+
+        let Some(i18n) = self.app_handle.try_state::<Arc<I18n>>() else { return };
+        let locale = i18n::effective_locale(&self.app_handle);
+        let mut thread_title = i18n.message(&locale, "notification.untitled_thread", &[]);
+        if let (Some(thread_id), Some(index)) = (&thread_id, self.app_handle.try_state::<Arc<ThreadIndex>>()) {
+            if let Some(thread) = index.list().await.ok().and_then(|threads| {
+                threads.into_iter().find(|thread| &thread.thread_id == thread_id)
+            }) {
+                thread_title = thread.title.unwrap_or(thread_title);
+            }
+        }
+
+        let title = trigger.title(&i18n, &locale);
+        let body = trigger.body(&i18n, &locale, &thread_title, event);
+        if let Err(e) = self.notify(&title, &body, thread_id) {
+            log::warn!("{}", e);
+        }
+    }
+
+    /// Apply the notification rules to backend events for as long as the app runs
+    pub fn watch(self: &Arc<Self>, bus: &EventBus) {
+        let mut receiver = bus.listen();
+        let notifier = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.topic == "backend-event" => notifier.handle(&event.payload).await,
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => log::debug!("Notification rules skipped {} events", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+use crate::filesystem;
+
+/// Default folder inside the vault that Chimera notes are written to
+const DEFAULT_FOLDER: &str = "Chimera";
+
+/// Persisted Obsidian export settings and sync state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ObsidianState {
+    vault_path: Option<String>,
+    folder: Option<String>,
+    /// Thread id -> file mtime (seconds) at last export
+    #[serde(default)]
+    synced: HashMap<String, u64>,
+}
+
+/// Obsidian export settings exposed to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct ObsidianConfig {
+    pub vault_path: Option<String>,
+    pub folder: String,
+    pub synced_threads: usize,
+}
+
+/// Summary of a vault sync
+#[derive(Debug, Clone, Serialize)]
+pub struct ObsidianSyncStats {
+    pub exported: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub blueprints: usize,
+}
+
+/// Exports threads as interlinked Markdown notes into an Obsidian vault
+pub struct ObsidianExporter {
+    state: Mutex<ObsidianState>,
+}
+
+/// Get the Obsidian state file path
+fn get_state_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("obsidian.json"))
+}
+
+/// Quote a value for YAML frontmatter (JSON strings are valid YAML scalars)
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+/// Make a string safe for use as an Obsidian tag
+fn tag_slug(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+/// Prefix every line with `> ` so text nests inside a callout
+fn quote_lines(text: &str) -> String {
+    text.lines().map(|line| format!("> {}", line)).collect::<Vec<_>>().join("\n")
+}
+
+/// Render a JSON value as a fenced block inside a callout
+fn quoted_json(value: &serde_json::Value) -> String {
+    let body = match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    quote_lines(&format!("```\n{}\n```", body))
+}
+
+/// File mtime in seconds, or 0 if unavailable
+async fn modified_secs(path: &Path) -> u64 {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A blueprint as it appears in the vault
+struct VaultBlueprint {
+    id: String,
+    name: String,
+    description: Option<String>,
+    /// The inner `blueprint` object, used to match threads to their blueprint
+    definition: serde_json::Value,
+}
+
+/// Load blueprints along with their definitions for matching
+async fn load_blueprints() -> Result<Vec<VaultBlueprint>, String> {
+    let mut blueprints = Vec::new();
+
+    for metadata in filesystem::list_blueprints().await? {
+        let content = filesystem::read_blueprint(metadata.file_path.clone()).await?;
+        let definition = serde_json::from_str::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|json| json.get("blueprint").cloned())
+            .unwrap_or(serde_json::Value::Null);
+
+        blueprints.push(VaultBlueprint {
+            id: metadata.id,
+            name: metadata.name,
+            description: metadata.description,
+            definition,
+        });
+    }
+
+    Ok(blueprints)
+}
+
+/// Render a blueprint note
+fn render_blueprint_note(blueprint: &VaultBlueprint) -> String {
+    let mut note = String::new();
+    note.push_str("---\n");
+    note.push_str(&format!("chimera_blueprint_id: {}\n", yaml_string(&blueprint.id)));
+    note.push_str(&format!("aliases: [{}]\n", yaml_string(&blueprint.name)));
+    note.push_str(&format!("tags: [chimera/blueprint, blueprint/{}]\n", tag_slug(&blueprint.id)));
+    note.push_str("---\n\n");
+    note.push_str(&format!("# {}\n\n", blueprint.name));
+
+    if let Some(description) = &blueprint.description {
+        note.push_str(description);
+        note.push_str("\n\n");
+    }
+
+    note.push_str("Threads using this blueprint appear in the backlinks pane.\n\n");
+    note.push_str("```json\n");
+    note.push_str(&serde_json::to_string_pretty(&blueprint.definition).unwrap_or_default());
+    note.push_str("\n```\n");
+    note
+}
+
+/// Render a thread note outside any vault, without a blueprint link
+pub(crate) fn render_standalone_note(thread: &filesystem::ThreadMetadata, events: &[serde_json::Value]) -> String {
+    render_thread_note(thread, events, None, "")
+}
+
+/// Render a thread note from its events
+fn render_thread_note(
+    thread: &filesystem::ThreadMetadata,
+    events: &[serde_json::Value],
+    blueprint: Option<&VaultBlueprint>,
+    folder: &str,
+) -> String {
+    let title = thread.title.clone().unwrap_or_else(|| "Untitled thread".to_string());
+
+    let mut tags = vec!["chimera/thread".to_string()];
+    if let Some(blueprint) = blueprint {
+        tags.push(format!("blueprint/{}", tag_slug(&blueprint.id)));
+    }
+
+    let mut note = String::new();
+    note.push_str("---\n");
+    note.push_str(&format!("chimera_thread_id: {}\n", yaml_string(&thread.thread_id)));
+    note.push_str(&format!("title: {}\n", yaml_string(&title)));
+    note.push_str(&format!("aliases: [{}]\n", yaml_string(&title)));
+    note.push_str(&format!("created: {}\n", yaml_string(&thread.created_at)));
+    note.push_str(&format!("updated: {}\n", yaml_string(&thread.updated_at)));
+    if let Some(blueprint) = blueprint {
+        note.push_str(&format!(
+            "blueprint: {}\n",
+            yaml_string(&format!("[[{}/Blueprints/{}|{}]]", folder, blueprint.id, blueprint.name))
+        ));
+    }
+    note.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    note.push_str("---\n\n");
+    note.push_str(&format!("# {}\n\n", title));
+
+    if let Some(blueprint) = blueprint {
+        note.push_str(&format!("Blueprint: [[{}/Blueprints/{}|{}]]\n\n", folder, blueprint.id, blueprint.name));
+    }
+
+    // The first line is the blueprint itself
+    for event in events.iter().skip(1) {
+        let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let content = event
+            .get("content")
+            .and_then(|c| c.as_str())
+            .or_else(|| event.get("data").and_then(|d| d.get("content")).and_then(|c| c.as_str()));
+
+        match event_type {
+            "user-message" | "data-user-message" => {
+                if let Some(text) = content {
+                    note.push_str(&format!("## User\n\n{}\n\n", text.trim()));
+                }
+            }
+            "text-complete" => {
+                if let Some(text) = content {
+                    note.push_str(&format!("## Assistant\n\n{}\n\n", text.trim()));
+                }
+            }
+            "reasoning-complete" => {
+                if let Some(text) = content {
+                    note.push_str(&format!("> [!note]- Reasoning\n{}\n\n", quote_lines(text.trim())));
+                }
+            }
+            "tool-input-available" => {
+                let name = event.get("toolName").and_then(|n| n.as_str()).unwrap_or("tool");
+                let input = event.get("input").cloned().unwrap_or(serde_json::Value::Null);
+                note.push_str(&format!("> [!example]- Tool call: {}\n{}\n\n", name, quoted_json(&input)));
+            }
+            "tool-output-available" => {
+                let name = event.get("toolName").and_then(|n| n.as_str()).unwrap_or("tool");
+                let output = event.get("output").cloned().unwrap_or(serde_json::Value::Null);
+                note.push_str(&format!("> [!example]- Tool result: {}\n{}\n\n", name, quoted_json(&output)));
+            }
+            "error" => {
+                let text = event
+                    .get("errorText")
+                    .and_then(|e| e.as_str())
+                    .or(content)
+                    .unwrap_or("Unknown error");
+                note.push_str(&format!("> [!error] Error\n{}\n\n", quote_lines(text)));
+            }
+            _ => {}
+        }
+    }
+
+    note
+}
+
+impl ObsidianExporter {
+    /// Load settings and sync state from the data directory
+    pub fn load() -> Self {
+        let state = get_state_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable Obsidian settings: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    async fn save(state: &ObsidianState) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize Obsidian settings: {}", e))?;
+        tokio::fs::write(get_state_path()?, content)
+            .await
+            .map_err(|e| format!("Failed to save Obsidian settings: {}", e))
+    }
+
+    /// Current vault settings
+    pub async fn config(&self) -> ObsidianConfig {
+        let state = self.state.lock().await;
+        ObsidianConfig {
+            vault_path: state.vault_path.clone(),
+            folder: state.folder.clone().unwrap_or_else(|| DEFAULT_FOLDER.to_string()),
+            synced_threads: state.synced.len(),
+        }
+    }
+
+    /// Set (or clear) the vault path; changing vaults forgets previous sync state
+    pub async fn set_vault(&self, vault_path: Option<String>, folder: Option<String>) -> Result<ObsidianConfig, String> {
+        if let Some(path) = &vault_path {
+            if !Path::new(path).is_dir() {
+                return Err(format!("Vault directory not found: {}", path));
+            }
+        }
+
+        let folder = folder
+            .map(|f| f.trim_matches(|c| c == '/' || c == '\\').to_string())
+            .filter(|f| !f.is_empty());
+        if folder.as_deref().is_some_and(|f| f.split(['/', '\\']).any(|part| part == "..")) {
+            return Err("Vault folder must stay inside the vault".to_string());
+        }
+
+        {
+            let mut state = self.state.lock().await;
+            if state.vault_path != vault_path || state.folder != folder {
+                state.synced.clear();
+            }
+            state.vault_path = vault_path;
+            state.folder = folder;
+            Self::save(&state).await?;
+        }
+
+        Ok(self.config().await)
+    }
+
+    /// Write notes for new or changed threads and remove notes for deleted ones.
+    /// With `full`, every thread is rewritten.
+    pub async fn sync(&self, full: bool) -> Result<ObsidianSyncStats, String> {
+        let mut state = self.state.lock().await;
+
+        let vault = state.vault_path.clone().ok_or("No Obsidian vault configured")?;
+        let folder = state.folder.clone().unwrap_or_else(|| DEFAULT_FOLDER.to_string());
+        let root = Path::new(&vault).join(&folder);
+        let threads_dir = root.join("Threads");
+        let blueprints_dir = root.join("Blueprints");
+
+        for dir in [&threads_dir, &blueprints_dir] {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| format!("Failed to create vault folder: {}", e))?;
+        }
+
+        if full {
+            state.synced.clear();
+        }
+
+        // Blueprint notes are small, so they are always rewritten
+        let blueprints = load_blueprints().await?;
+        for blueprint in &blueprints {
+            tokio::fs::write(blueprints_dir.join(format!("{}.md", blueprint.id)), render_blueprint_note(blueprint))
+                .await
+                .map_err(|e| format!("Failed to write blueprint note: {}", e))?;
+        }
+
+        let threads = filesystem::list_threads().await?;
+        let mut exported = 0;
+        let mut unchanged = 0;
+
+        for thread in &threads {
+            let modified = modified_secs(Path::new(&thread.file_path)).await;
+            if state.synced.get(&thread.thread_id) == Some(&modified) {
+                unchanged += 1;
+                continue;
+            }
+
+            let events = filesystem::load_thread(thread.thread_id.clone()).await?;
+            let definition = events.first().and_then(|first| first.get("blueprint"));
+            let blueprint = definition.and_then(|d| blueprints.iter().find(|b| &b.definition == d));
+
+            let note = render_thread_note(thread, &events, blueprint, &folder);
+            tokio::fs::write(threads_dir.join(format!("{}.md", thread.thread_id)), note)
+                .await
+                .map_err(|e| format!("Failed to write thread note: {}", e))?;
+
+            state.synced.insert(thread.thread_id.clone(), modified);
+            exported += 1;
+        }
+
+        // Remove notes for threads that no longer exist
+        let stale: Vec<String> = state
+            .synced
+            .keys()
+            .filter(|id| !threads.iter().any(|t| &t.thread_id == *id))
+            .cloned()
+            .collect();
+        for thread_id in &stale {
+            let path = threads_dir.join(format!("{}.md", thread_id));
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to remove stale note {}: {}", path.display(), e);
+                }
+            }
+            state.synced.remove(thread_id);
+        }
+
+        Self::save(&state).await?;
+
+        let stats = ObsidianSyncStats {
+            exported,
+            unchanged,
+            removed: stale.len(),
+            blueprints: blueprints.len(),
+        };
+        log::info!(
+            "Obsidian sync to {}: {} exported, {} unchanged, {} removed",
+            root.display(),
+            stats.exported,
+            stats.unchanged,
+            stats.removed
+        );
+
+        Ok(stats)
+    }
+}
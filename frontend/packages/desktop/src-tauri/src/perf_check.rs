@@ -0,0 +1,187 @@
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::filesystem;
+use crate::terminal_backend::TerminalBackend;
+use crate::thread_compression;
+
+/// Single-event appends timed for the latency figures
+const APPEND_SAMPLES: usize = 200;
+
+/// Default size of the synthetic thread used for the load benchmark
+const DEFAULT_LARGE_THREAD_MB: u64 = 16;
+
+/// Keystroke echoes timed through a terminal
+const ECHO_SAMPLES: usize = 50;
+
+/// Prefix of the synthetic thread ids, removed again when the check finishes
+const THREAD_PREFIX: &str = "perf-check-";
+
+/// Percentile summary of a set of latencies
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_millis(mut millis: Vec<f64>) -> Self {
+        millis.sort_by(|a, b| a.total_cmp(b));
+        let at = |p: f64| {
+            millis
+                .get(((millis.len() as f64 - 1.0) * p).round() as usize)
+                .copied()
+                .unwrap_or(0.0)
+        };
+        Self {
+            samples: millis.len(),
+            p50_ms: at(0.5),
+            p95_ms: at(0.95),
+            max_ms: millis.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Throughput of loading one large thread
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadStats {
+    pub bytes: u64,
+    pub events: usize,
+    pub seconds: f64,
+    pub megabytes_per_second: f64,
+}
+
+/// Time to list every thread from disk, bypassing the listing snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct ListingStats {
+    pub threads: usize,
+    pub milliseconds: f64,
+}
+
+/// Result of `run_performance_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceReport {
+    pub os: String,
+    pub arch: String,
+    pub cpus: usize,
+    pub app_version: String,
+    pub append: LatencyStats,
+    pub load: LoadStats,
+    pub listing: ListingStats,
+    pub terminal_echo: Option<LatencyStats>,
+    /// Why the terminal measurement was skipped, if it was
+    pub terminal_error: Option<String>,
+}
+
+/// Measure append latency, large-thread load throughput, listing time and terminal
+/// echo round trip on this machine. Synthetic threads are written to the real data
+/// directory so the numbers reflect the user's disk, and removed afterwards.
+pub async fn run(terminals: &TerminalBackend, large_thread_mb: Option<u64>) -> Result<PerformanceReport, String> {
+    let append_id = format!("{}{}", THREAD_PREFIX, uuid::Uuid::new_v4());
+    let load_id = format!("{}{}", THREAD_PREFIX, uuid::Uuid::new_v4());
+
+    let result = measure(terminals, &append_id, &load_id, large_thread_mb.unwrap_or(DEFAULT_LARGE_THREAD_MB)).await;
+
+    for thread_id in [&append_id, &load_id] {
+        let paths = [filesystem::get_thread_path(thread_id), thread_compression::compressed_path(thread_id)];
+        for path in paths.into_iter().flatten() {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+
+    result
+}
+
+async fn measure(
+    terminals: &TerminalBackend,
+    append_id: &str,
+    load_id: &str,
+    large_thread_mb: u64,
+) -> Result<PerformanceReport, String> {
+    // Append latency: one event per call, as during token streaming
+    let mut append_millis = Vec::with_capacity(APPEND_SAMPLES);
+    for i in 0..APPEND_SAMPLES {
+        let event = serde_json::json!({ "type": "text-delta", "delta": format!("token {} ", i) });
+        let started = Instant::now();
+        filesystem::append_thread_events(append_id.to_string(), vec![event]).await?;
+        append_millis.push(started.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    // Load throughput for a synthetic large thread
+    let target_bytes = large_thread_mb * 1024 * 1024;
+    let path = filesystem::get_thread_path(load_id)?;
+    let bytes = filesystem::blocking(move || write_synthetic_thread(&path, target_bytes)).await?;
+
+    let started = Instant::now();
+    let events = filesystem::load_thread(load_id.to_string()).await?.len();
+    let seconds = started.elapsed().as_secs_f64();
+
+    // Cold listing of every thread
+    let started = Instant::now();
+    let threads = filesystem::list_threads().await?.len();
+    let listing_millis = started.elapsed().as_secs_f64() * 1000.0;
+
+    let (terminal_echo, terminal_error) = match terminals.measure_echo_round_trip(ECHO_SAMPLES).await {
+        Ok(millis) => (Some(LatencyStats::from_millis(millis)), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    Ok(PerformanceReport {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        append: LatencyStats::from_millis(append_millis),
+        load: LoadStats {
+            bytes,
+            events,
+            seconds,
+            megabytes_per_second: bytes as f64 / (1024.0 * 1024.0) / seconds.max(f64::EPSILON),
+        },
+        listing: ListingStats {
+            threads,
+            milliseconds: listing_millis,
+        },
+        terminal_echo,
+        terminal_error,
+    })
+}
+
+/// Write a thread of mixed event types until it reaches `target_bytes` (blocking)
+fn write_synthetic_thread(path: &std::path::Path, target_bytes: u64) -> Result<u64, String> {
+    use std::io::Write;
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create synthetic thread: {}", e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+    let mut written = 0u64;
+    let mut i = 0u64;
+
+    let mut write_line = |value: serde_json::Value, written: &mut u64| -> Result<(), String> {
+        let line = serde_json::to_string(&value).map_err(|e| format!("Failed to serialize event: {}", e))?;
+        writer
+            .write_all(line.as_bytes())
+            .and_then(|_| writer.write_all(b"\n"))
+            .map_err(|e| format!("Failed to write synthetic thread: {}", e))?;
+        *written += line.len() as u64 + 1;
+        Ok(())
+    };
+
+    write_line(serde_json::json!({ "thread_id": "perf-check", "blueprint": {} }), &mut written)?;
+
+    while written < target_bytes {
+        let event = match i % 3 {
+            0 => serde_json::json!({ "type": "user-message", "content": text }),
+            1 => serde_json::json!({ "type": "tool-output-available", "toolName": "read_file", "output": { "content": text } }),
+            _ => serde_json::json!({ "type": "text-complete", "content": text }),
+        };
+        write_line(event, &mut written)?;
+        i += 1;
+    }
+
+    writer.flush().map_err(|e| format!("Failed to write synthetic thread: {}", e))?;
+
+    Ok(written)
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::filesystem;
+
+/// Kind of access being asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// App operations that need the user's approval outside the data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Export,
+    Import,
+    Terminal,
+    Open,
+}
+
+impl Operation {
+    fn describe(self) -> &'static str {
+        match self {
+            Operation::Export => "save exports to",
+            Operation::Import => "import files from",
+            Operation::Terminal => "open terminals in",
+            Operation::Open => "open files from",
+        }
+    }
+}
+
+/// A directory the user has opened to agents, with what they may do inside it,
+/// and the app operations they've approved there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootGrant {
+    pub path: PathBuf,
+    pub read: bool,
+    pub write: bool,
+    #[serde(default)]
+    pub operations: Vec<Operation>,
+    pub granted_at: String,
+}
+
+impl RootGrant {
+    fn allows(&self, access: Access) -> bool {
+        match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+        }
+    }
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("permissions.json"))
+}
+
+/// User-approved filesystem roots, persisted in `permissions.json`
+pub struct Permissions {
+    grants: Mutex<Vec<RootGrant>>,
+    /// Held while asking the user, so concurrent requests get one prompt
+    prompt: Mutex<()>,
+}
+
+impl Permissions {
+    /// Load saved grants
+    pub fn load() -> Self {
+        let grants = get_config_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(Vec::new());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read permissions: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse permissions: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                Vec::new()
+            });
+
+        log::info!("Loaded {} filesystem grant(s)", grants.len());
+
+        Self {
+            grants: Mutex::new(grants),
+            prompt: Mutex::new(()),
+        }
+    }
+
+    fn save(&self, grants: &[RootGrant]) -> Result<(), String> {
+        let path = get_config_path()?;
+        let content = serde_json::to_string_pretty(grants)
+            .map_err(|e| format!("Failed to serialize permissions: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write permissions: {}", e))
+    }
+
+    pub fn list(&self) -> Vec<RootGrant> {
+        self.grants.lock().unwrap().clone()
+    }
+
+    /// Grant agents access to a directory, replacing any existing grant for it.
+    /// Approved app operations there are kept.
+    pub fn grant(&self, path: &Path, read: bool, write: bool) -> Result<RootGrant, String> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+        if !path.is_dir() {
+            return Err(format!("Not a directory: {}", path.display()));
+        }
+
+        let mut grants = self.grants.lock().unwrap();
+        let grant = RootGrant {
+            operations: grants
+                .iter()
+                .find(|g| g.path == path)
+                .map(|g| g.operations.clone())
+                .unwrap_or_default(),
+            path,
+            read,
+            write,
+            granted_at: chrono::Utc::now().to_rfc3339(),
+        };
+        grants.retain(|g| g.path != grant.path);
+        grants.push(grant.clone());
+        self.save(&grants)?;
+
+        log::info!("Granted {:?} (read: {}, write: {})", grant.path, read, write);
+        Ok(grant)
+    }
+
+    /// Remove the grant for a directory
+    pub fn revoke(&self, path: &Path) -> Result<(), String> {
+        let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut grants = self.grants.lock().unwrap();
+        let before = grants.len();
+        grants.retain(|g| g.path != resolved);
+        if grants.len() == before {
+            return Err(format!("No grant for {}", path.display()));
+        }
+        self.save(&grants)?;
+
+        log::info!("Revoked {:?}", resolved);
+        Ok(())
+    }
+
+    /// Withdraw approval for one app operation in a directory, dropping the grant
+    /// once nothing is left on it
+    pub fn revoke_operation(&self, path: &Path, operation: Operation) -> Result<(), String> {
+        let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut grants = self.grants.lock().unwrap();
+        let grant = grants
+            .iter_mut()
+            .find(|g| g.path == resolved && g.operations.contains(&operation))
+            .ok_or_else(|| format!("No {:?} grant for {}", operation, path.display()))?;
+        grant.operations.retain(|op| *op != operation);
+        grants.retain(|g| g.read || g.write || !g.operations.is_empty());
+        self.save(&grants)?;
+
+        log::info!("Revoked {:?} in {:?}", operation, resolved);
+        Ok(())
+    }
+
+    /// Check the user has approved `operation` in `dir`, asking with a native
+    /// dialog the first time it's outside the data directory. Approvals are saved
+    /// and cover subdirectories. Returns the resolved directory (blocking).
+    pub fn authorize(&self, app_handle: &tauri::AppHandle, dir: &Path, operation: Operation) -> Result<PathBuf, String> {
+        use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+        let resolved = resolve(dir)?;
+        let data_dir = filesystem::get_data_dir()?;
+        let data_dir = data_dir.canonicalize().unwrap_or(data_dir);
+        if resolved.starts_with(&data_dir) || crate::scratch::contains(&resolved) {
+            return Ok(resolved);
+        }
+
+        let approved = |grants: &[RootGrant]| {
+            grants
+                .iter()
+                .any(|g| resolved.starts_with(&g.path) && g.operations.contains(&operation))
+        };
+        let _prompt = self.prompt.lock().unwrap();
+        if approved(&self.grants.lock().unwrap()) {
+            return Ok(resolved);
+        }
+
+        let allowed = app_handle
+            .dialog()
+            .message(format!(
+                "Allow Chimera to {} {}?\n\nThis folder is outside Chimera's data directory. You can revoke access in Settings.",
+                operation.describe(),
+                resolved.display()
+            ))
+            .title("Allow folder access")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom("Allow".to_string(), "Don't Allow".to_string()))
+            .blocking_show();
+
+        let action = format!("{:?}", operation).to_lowercase();
+        crate::audit::record(crate::audit::AuditEntry::new(
+            "permission",
+            &action,
+            "user",
+            resolved.display().to_string(),
+            allowed,
+        ));
+        if !allowed {
+            return Err(format!("Access to {} was not allowed", resolved.display()));
+        }
+
+        let mut grants = self.grants.lock().unwrap();
+        match grants.iter_mut().find(|g| g.path == resolved) {
+            Some(grant) => grant.operations.push(operation),
+            None => grants.push(RootGrant {
+                path: resolved.clone(),
+                read: false,
+                write: false,
+                operations: vec![operation],
+                granted_at: chrono::Utc::now().to_rfc3339(),
+            }),
+        }
+        self.save(&grants)?;
+
+        log::info!("Approved {:?} in {:?}", operation, resolved);
+        Ok(resolved)
+    }
+
+    /// Resolve `path` and check it lies inside a root granting `access`, or inside a
+    /// thread scratch directory. Returns the resolved path. Symlinks are followed
+    /// before checking, so a link inside a root can't reach outside it.
+    pub fn check(&self, path: &Path, access: Access) -> Result<PathBuf, String> {
+        let resolved = resolve(path)?;
+        if crate::scratch::contains(&resolved) {
+            return Ok(resolved);
+        }
+        let grants = self.grants.lock().unwrap();
+        if grants.iter().any(|g| resolved.starts_with(&g.path) && g.allows(access)) {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "{} access to {} has not been granted",
+                match access {
+                    Access::Read => "Read",
+                    Access::Write => "Write",
+                },
+                resolved.display()
+            ))
+        }
+    }
+}
+
+/// Canonicalize a path that may not exist yet (the target of a write): resolve the
+/// deepest existing ancestor and append the remaining plain components
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path.display()));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("Path must not contain '..': {}", path.display()));
+    }
+
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(base) => {
+                return Ok(rest.iter().rev().fold(base, |base, part| base.join(part)));
+            }
+            Err(_) => {
+                let name = existing
+                    .file_name()
+                    .ok_or_else(|| format!("Failed to resolve {}", path.display()))?;
+                rest.push(name.to_os_string());
+                existing = existing
+                    .parent()
+                    .ok_or_else(|| format!("Failed to resolve {}", path.display()))?;
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use wasmtime::{Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::filesystem;
+use crate::thread_compression;
+
+/// Capability allowing a plugin to write to the app log
+pub const CAP_LOG: &str = "log";
+/// Capability allowing a plugin to read thread JSONL files
+pub const CAP_THREADS_READ: &str = "threads:read";
+
+const KNOWN_CAPABILITIES: [&str; 2] = [CAP_LOG, CAP_THREADS_READ];
+
+/// Fuel granted per invocation (roughly bounds CPU time)
+const FUEL_PER_CALL: u64 = 5_000_000_000;
+
+/// Linear memory limit per plugin instance
+const MEMORY_LIMIT_BYTES: usize = 256 * 1024 * 1024;
+
+/// Longest string a plugin may pass to a host function
+const MAX_GUEST_STRING_BYTES: usize = 64 * 1024;
+
+/// A command exported by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    /// Name of the exported wasm function (defaults to `name`)
+    pub export: Option<String>,
+    pub description: Option<String>,
+}
+
+/// An export format backed by a plugin command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginExporterSpec {
+    /// Format id, offered as `plugin.id`
+    pub id: String,
+    pub name: String,
+    /// File extension without the dot
+    pub extension: String,
+    pub description: Option<String>,
+    /// Command that renders the thread
+    pub command: String,
+}
+
+/// Contents of a plugin's `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// Path to the wasm module, relative to the plugin directory
+    pub module: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    #[serde(default)]
+    pub exporters: Vec<PluginExporterSpec>,
+}
+
+/// A loaded plugin
+struct LoadedPlugin {
+    manifest: PluginManifest,
+    module: Module,
+}
+
+/// Per-invocation store data
+struct PluginState {
+    plugin_name: String,
+    limits: StoreLimits,
+}
+
+/// Hosts sandboxed WASM plugins that register namespaced commands.
+///
+/// Plugins must export `memory`, `alloc(len: i32) -> i32`, and one
+/// `fn(ptr: i32, len: i32) -> i64` per command. Input and output are UTF-8
+/// JSON; the result is packed as `(ptr << 32) | len`.
+pub struct PluginHost {
+    engine: Engine,
+    plugins: RwLock<HashMap<String, Arc<LoadedPlugin>>>,
+}
+
+/// Get the plugins directory
+fn get_plugins_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("plugins"))
+}
+
+impl PluginHost {
+    /// Create a plugin host and load every plugin in the plugins directory
+    pub fn new() -> Result<Self, String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| format!("Failed to create WASM engine: {}", e))?;
+
+        let host = Self {
+            engine,
+            plugins: RwLock::new(HashMap::new()),
+        };
+        host.reload()?;
+        Ok(host)
+    }
+
+    /// Rescan the plugins directory, replacing all loaded plugins
+    pub fn reload(&self) -> Result<Vec<PluginManifest>, String> {
+        let plugins_dir = get_plugins_dir()?;
+        std::fs::create_dir_all(&plugins_dir)
+            .map_err(|e| format!("Failed to create plugins directory: {}", e))?;
+
+        let mut loaded = HashMap::new();
+
+        let entries = std::fs::read_dir(&plugins_dir)
+            .map_err(|e| format!("Failed to read plugins directory: {}", e))?;
+
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            if !dir.is_dir() {
+                continue;
+            }
+
+            match self.load_plugin(&dir) {
+                Ok(plugin) => {
+                    log::info!(
+                        "Loaded plugin {} v{} ({} commands)",
+                        plugin.manifest.name,
+                        plugin.manifest.version,
+                        plugin.manifest.commands.len()
+                    );
+                    loaded.insert(plugin.manifest.name.clone(), Arc::new(plugin));
+                }
+                Err(e) => log::warn!("Skipping plugin {}: {}", dir.display(), e),
+            }
+        }
+
+        let manifests = loaded.values().map(|p| p.manifest.clone()).collect();
+        *self.plugins.write().unwrap() = loaded;
+        Ok(manifests)
+    }
+
+    /// Load and validate a single plugin directory
+    fn load_plugin(&self, dir: &std::path::Path) -> Result<LoadedPlugin, String> {
+        let manifest_path = dir.join("plugin.json");
+        let content = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read plugin.json: {}", e))?;
+        let manifest: PluginManifest = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid plugin.json: {}", e))?;
+
+        if manifest.name.is_empty() || manifest.name.contains('.') {
+            return Err(format!("Invalid plugin name: {:?}", manifest.name));
+        }
+
+        if let Some(exporter) = manifest
+            .exporters
+            .iter()
+            .find(|e| !manifest.commands.iter().any(|c| c.name == e.command))
+        {
+            return Err(format!("Exporter {} uses undeclared command {}", exporter.id, exporter.command));
+        }
+
+        if let Some(unknown) = manifest
+            .capabilities
+            .iter()
+            .find(|c| !KNOWN_CAPABILITIES.contains(&c.as_str()))
+        {
+            return Err(format!("Unknown capability: {}", unknown));
+        }
+
+        // Keep the module path inside the plugin directory
+        let module_path = dir.join(&manifest.module);
+        let canonical_dir = dir.canonicalize().map_err(|e| format!("Failed to resolve plugin dir: {}", e))?;
+        let canonical_module = module_path
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve module {}: {}", manifest.module, e))?;
+        if !canonical_module.starts_with(&canonical_dir) {
+            return Err("Plugin module must live inside the plugin directory".to_string());
+        }
+
+        let module = Module::from_file(&self.engine, &canonical_module)
+            .map_err(|e| format!("Failed to compile module: {}", e))?;
+
+        Ok(LoadedPlugin { manifest, module })
+    }
+
+    /// List loaded plugin manifests
+    pub fn list(&self) -> Vec<PluginManifest> {
+        self.plugins.read().unwrap().values().map(|p| p.manifest.clone()).collect()
+    }
+
+    /// Invoke a namespaced plugin command (`plugin.command`) with JSON input
+    pub fn invoke(&self, command: &str, input: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let (plugin_name, command_name) = command
+            .split_once('.')
+            .ok_or_else(|| format!("Plugin command must be namespaced: {}", command))?;
+
+        let plugin = self
+            .plugins
+            .read()
+            .unwrap()
+            .get(plugin_name)
+            .cloned()
+            .ok_or_else(|| format!("Plugin not found: {}", plugin_name))?;
+
+        let spec = plugin
+            .manifest
+            .commands
+            .iter()
+            .find(|c| c.name == command_name)
+            .ok_or_else(|| format!("Plugin {} has no command {}", plugin_name, command_name))?;
+        let export_name = spec.export.clone().unwrap_or_else(|| spec.name.clone());
+
+        let state = PluginState {
+            plugin_name: plugin_name.to_string(),
+            limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT_BYTES).build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| format!("Failed to set fuel: {}", e))?;
+
+        let linker = self.build_linker(&plugin.manifest.capabilities)?;
+        let instance = linker
+            .instantiate(&mut store, &plugin.module)
+            .map_err(|e| format!("Failed to instantiate plugin {}: {}", plugin_name, e))?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Plugin does not export memory")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| format!("Plugin does not export alloc: {}", e))?;
+        let func = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, &export_name)
+            .map_err(|e| format!("Plugin does not export {}: {}", export_name, e))?;
+
+        let input_bytes = serde_json::to_vec(input).map_err(|e| format!("Failed to serialize input: {}", e))?;
+        let input_len = i32::try_from(input_bytes.len()).map_err(|_| "Plugin input too large".to_string())?;
+        let input_ptr = alloc
+            .call(&mut store, input_len)
+            .map_err(|e| format!("Plugin alloc failed: {}", e))?;
+        memory
+            .write(&mut store, input_ptr as usize, &input_bytes)
+            .map_err(|e| format!("Failed to write plugin input: {}", e))?;
+
+        let packed = func
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(|e| format!("Plugin command {} failed: {}", command, e))?;
+
+        let output = read_packed(&memory, &store, packed)?;
+        serde_json::from_slice(&output).map_err(|e| format!("Plugin returned invalid JSON: {}", e))
+    }
+
+    /// Build a linker exposing only the host functions the plugin declared
+    fn build_linker(&self, capabilities: &[String]) -> Result<Linker<PluginState>, String> {
+        let mut linker = Linker::new(&self.engine);
+
+        if capabilities.iter().any(|c| c == CAP_LOG) {
+            linker
+                .func_wrap("chimera", "log", |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| {
+                    if let Some(message) = read_guest_string(&mut caller, ptr, len) {
+                        log::info!("[plugin {}] {}", caller.data().plugin_name, message);
+                    }
+                })
+                .map_err(|e| format!("Failed to link log: {}", e))?;
+        }
+
+        if capabilities.iter().any(|c| c == CAP_THREADS_READ) {
+            linker
+                .func_wrap(
+                    "chimera",
+                    "read_thread",
+                    |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| -> i64 {
+                        let Some(thread_id) = read_guest_string(&mut caller, ptr, len) else {
+                            return 0;
+                        };
+                        let content = thread_compression::read_thread(&thread_id).unwrap_or_default();
+                        write_guest_bytes(&mut caller, &content).unwrap_or(0)
+                    },
+                )
+                .map_err(|e| format!("Failed to link read_thread: {}", e))?;
+        }
+
+        Ok(linker)
+    }
+}
+
+/// Read a UTF-8 string from guest memory. The range is checked against the
+/// guest's memory and `MAX_GUEST_STRING_BYTES` before anything is allocated.
+fn read_guest_string(caller: &mut Caller<'_, PluginState>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let ptr = usize::try_from(ptr).ok()?;
+    let len = usize::try_from(len).ok().filter(|&len| len <= MAX_GUEST_STRING_BYTES)?;
+    if ptr.checked_add(len)? > memory.data_size(&*caller) {
+        return None;
+    }
+
+    let mut buffer = vec![0u8; len];
+    memory.read(&*caller, ptr as usize, &mut buffer).ok()?;
+    String::from_utf8(buffer).ok()
+}
+
+/// Copy bytes into guest memory via its `alloc` export, returning a packed pointer
+fn write_guest_bytes(caller: &mut Caller<'_, PluginState>, bytes: &[u8]) -> Option<i64> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let alloc = caller
+        .get_export("alloc")?
+        .into_func()?
+        .typed::<i32, i32>(&*caller)
+        .ok()?;
+
+    let len = i32::try_from(bytes.len()).ok()?;
+    let ptr = alloc.call(&mut *caller, len).ok()?;
+    memory.write(&mut *caller, ptr as usize, bytes).ok()?;
+
+    Some(((ptr as i64) << 32) | (len as i64 & 0xffff_ffff))
+}
+
+/// Read a `(ptr << 32) | len` result out of guest memory
+fn read_packed(memory: &Memory, store: &Store<PluginState>, packed: i64) -> Result<Vec<u8>, String> {
+    let ptr = ((packed >> 32) & 0xffff_ffff) as usize;
+    let len = (packed & 0xffff_ffff) as usize;
+    if !ptr.checked_add(len).is_some_and(|end| end <= memory.data_size(store)) {
+        return Err("Plugin output is outside its memory".to_string());
+    }
+
+    let mut buffer = vec![0u8; len];
+    memory
+        .read(store, ptr, &mut buffer)
+        .map_err(|e| format!("Failed to read plugin output: {}", e))?;
+    Ok(buffer)
+}
@@ -0,0 +1,174 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::Manager;
+
+use crate::backend_manager::BackendManager;
+use crate::event_bus::EventBus;
+use crate::settings::{ProcessWarnings, SettingsStore};
+use crate::terminal_backend::TerminalBackend;
+
+/// How often processes are sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Consecutive samples over the CPU threshold before a warning, so a short
+/// burst of work doesn't raise one
+const CPU_SAMPLES_BEFORE_WARNING: u32 = 3;
+
+/// Resource use of a backend or terminal, including the processes it started
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessStats {
+    /// `backend` or `terminal`
+    pub kind: String,
+    /// Workspace id for backends, terminal id for terminals
+    pub id: String,
+    pub pid: u32,
+    /// Percent of one core, so it can pass 100 on multi-core machines
+    pub cpu_percent: f32,
+    /// Resident memory in bytes
+    pub memory_bytes: u64,
+    /// Processes counted, the root and its descendants
+    pub processes: usize,
+}
+
+#[derive(Default)]
+struct Warned {
+    memory: bool,
+    cpu_samples: u32,
+    cpu: bool,
+}
+
+/// Samples CPU and memory of the backends and terminal children, and publishes
+/// `process-warning` when one crosses the thresholds in settings
+pub struct ProcessMonitor {
+    system: Mutex<System>,
+    latest: Mutex<Vec<ProcessStats>>,
+    warned: Mutex<HashMap<(String, String), Warned>>,
+    bus: Arc<EventBus>,
+}
+
+impl ProcessMonitor {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            latest: Mutex::new(Vec::new()),
+            warned: Mutex::new(HashMap::new()),
+            bus,
+        }
+    }
+
+    /// The most recent sample, taking one if there's none yet. CPU is 0 until
+    /// there have been two samples to measure between.
+    pub async fn get(&self, app_handle: &tauri::AppHandle) -> Vec<ProcessStats> {
+        let latest = self.latest.lock().unwrap().clone();
+        if !latest.is_empty() {
+            return latest;
+        }
+        self.sample(app_handle).await
+    }
+
+    async fn sample(&self, app_handle: &tauri::AppHandle) -> Vec<ProcessStats> {
+        let mut roots: Vec<(&str, String, u32)> = Vec::new();
+        if let Some(backends) = app_handle.try_state::<Arc<BackendManager>>() {
+            roots.extend(backends.workspace_pids().await.into_iter().map(|(id, pid)| ("backend", id, pid)));
+        }
+        if let Some(terminals) = app_handle.try_state::<Arc<TerminalBackend>>() {
+            roots.extend(terminals.pids().await.into_iter().map(|(id, pid)| ("terminal", id, pid)));
+        }
+
+        let stats: Vec<ProcessStats> = {
+            let mut system = self.system.lock().unwrap();
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+            roots
+                .into_iter()
+                .filter(|(_, _, pid)| system.process(Pid::from_u32(*pid)).is_some())
+                .map(|(kind, id, pid)| {
+                    let tree = crate::stale_backend::with_descendants(&system, &[pid]);
+                    let (cpu_percent, memory_bytes) = tree
+                        .iter()
+                        .filter_map(|pid| system.process(Pid::from_u32(*pid)))
+                        .fold((0.0, 0), |(cpu, memory), process| {
+                            (cpu + process.cpu_usage(), memory + process.memory())
+                        });
+                    ProcessStats {
+                        kind: kind.to_string(),
+                        id,
+                        pid,
+                        cpu_percent,
+                        memory_bytes,
+                        processes: tree.len(),
+                    }
+                })
+                .collect()
+        };
+
+        *self.latest.lock().unwrap() = stats.clone();
+        stats
+    }
+
+    /// Publish a warning the first time a process crosses a threshold; it warns
+    /// again only after dropping back under
+    fn check(&self, stats: &[ProcessStats], limits: &ProcessWarnings) {
+        let mut warned = self.warned.lock().unwrap();
+        warned.retain(|(kind, id), _| stats.iter().any(|s| &s.kind == kind && &s.id == id));
+
+        for stat in stats {
+            let state = warned.entry((stat.kind.clone(), stat.id.clone())).or_default();
+
+            let over_memory = limits.memory_mb > 0 && stat.memory_bytes > limits.memory_mb * 1024 * 1024;
+            if over_memory && !state.memory {
+                log::warn!(
+                    "{} {} is using {} MB of memory",
+                    stat.kind,
+                    stat.id,
+                    stat.memory_bytes / 1024 / 1024
+                );
+                self.publish("memory", stat, limits);
+            }
+            state.memory = over_memory;
+
+            let over_cpu = limits.cpu_percent > 0.0 && stat.cpu_percent > limits.cpu_percent;
+            state.cpu_samples = if over_cpu { state.cpu_samples + 1 } else { 0 };
+            if state.cpu_samples >= CPU_SAMPLES_BEFORE_WARNING && !state.cpu {
+                log::warn!("{} {} is using {:.0}% CPU", stat.kind, stat.id, stat.cpu_percent);
+                self.publish("cpu", stat, limits);
+            }
+            state.cpu = state.cpu_samples >= CPU_SAMPLES_BEFORE_WARNING;
+        }
+    }
+
+    fn publish(&self, resource: &str, stat: &ProcessStats, limits: &ProcessWarnings) {
+        self.bus.publish(
+            "process-warning",
+            serde_json::json!({
+                "resource": resource,
+                "process": stat,
+                "memory_limit_bytes": limits.memory_mb * 1024 * 1024,
+                "cpu_limit_percent": limits.cpu_percent,
+            }),
+        );
+    }
+}
+
+/// Sample processes every few seconds for as long as the app runs
+pub fn start(app_handle: tauri::AppHandle, monitor: Arc<ProcessMonitor>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let stats = monitor.sample(&app_handle).await;
+            let limits = app_handle
+                .try_state::<Arc<SettingsStore>>()
+                .map(|settings| settings.get().process_warnings)
+                .unwrap_or_default();
+            monitor.check(&stats, &limits);
+        }
+    });
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::python_backend::PythonBackend;
+
+/// Event type of the snapshot written after a thread's blueprint line
+pub const PROVENANCE_EVENT: &str = "data-thread-provenance";
+
+/// Keys whose string values name a model
+const MODEL_KEYS: [&str; 3] = ["model", "model_id", "modelId"];
+
+/// Where a thread came from: what produced it, on what, with which configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub app_version: String,
+    /// Reported by the backend's `/health`; absent if it wasn't reachable
+    pub backend_version: Option<String>,
+    pub os: String,
+    pub arch: String,
+    /// SHA-256 of the blueprint as the thread was created with it
+    pub blueprint_hash: String,
+    pub models: Vec<String>,
+    pub captured_at: String,
+}
+
+fn collect_models(value: &serde_json::Value, models: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(model) if MODEL_KEYS.contains(&key.as_str()) => {
+                        if !models.contains(model) {
+                            models.push(model.clone());
+                        }
+                    }
+                    other => collect_models(other, models),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_models(item, models)),
+        _ => {}
+    }
+}
+
+async fn backend_version(app_handle: &tauri::AppHandle) -> Option<String> {
+    let backend = app_handle.try_state::<Arc<PythonBackend>>()?;
+    let response = reqwest::Client::new()
+        .get(format!("{}/health", backend.base_url()))
+        .timeout(Duration::from_secs(2))
+        .send()
+        .await
+        .ok()?;
+    let health: serde_json::Value = response.json().await.ok()?;
+    health.get("version")?.as_str().map(str::to_string)
+}
+
+/// Snapshot the environment a thread is being created in
+async fn capture(app_handle: &tauri::AppHandle, blueprint: &serde_json::Value) -> Provenance {
+    let mut models = Vec::new();
+    collect_models(blueprint, &mut models);
+
+    Provenance {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        backend_version: backend_version(app_handle).await,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        blueprint_hash: format!("{:x}", Sha256::digest(blueprint.to_string().as_bytes())),
+        models,
+        captured_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Header events for a new thread: the provenance snapshot, unless the blueprint
+/// doesn't parse (creating the thread reports that)
+pub async fn header(app_handle: &tauri::AppHandle, blueprint_json: &str) -> Vec<serde_json::Value> {
+    let Ok(blueprint) = serde_json::from_str::<serde_json::Value>(blueprint_json) else {
+        return Vec::new();
+    };
+    let provenance = capture(app_handle, &blueprint).await;
+    vec![serde_json::json!({
+        "type": PROVENANCE_EVENT,
+        "data": provenance,
+        "timestamp": provenance.captured_at,
+    })]
+}
+
+/// The snapshot recorded when a thread was created. Threads from before snapshots
+/// were recorded have none.
+pub fn find(events: &[serde_json::Value]) -> Option<Provenance> {
+    events
+        .iter()
+        .find(|event| event.get("type").and_then(|t| t.as_str()) == Some(PROVENANCE_EVENT))
+        .and_then(|event| serde_json::from_value(event.get("data")?.clone()).ok())
+}
@@ -1,13 +1,178 @@
+use std::io::{Read, Seek, SeekFrom};
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, Mutex as StdMutex};
 use std::time::Duration;
 use std::path::PathBuf;
-use tokio::fs::OpenOptions;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, Command};
-use tokio::sync::{mpsc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::time::Instant;
 
+use crate::error::ChimeraError;
+use crate::event_bus::EventBus;
+use crate::filesystem::FsError;
+use crate::job_object::{self, JobObject};
+use crate::stale_backend;
+use crate::logging::{self, RotatingLog};
+use crate::supervisor::{TaskGroup, SHUTDOWN_TIMEOUT};
+
+/// How long callers wait for the backend to finish starting before giving up on its URL
+const URL_WAIT: Duration = Duration::from_secs(30);
+
+/// How often the supervisor polls the backend's health endpoint
+const HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Failed health checks in a row before a live process is restarted
+const UNHEALTHY_THRESHOLD: u32 = 3;
+
+/// Restarts attempted before giving up, unless `CHIMERA_BACKEND_MAX_RESTARTS` says otherwise
+const DEFAULT_MAX_RESTARTS: u32 = 5;
+
+/// Longest wait between restart attempts
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How long active runs get to finish on shutdown, unless
+/// `CHIMERA_BACKEND_SHUTDOWN_TIMEOUT` (seconds) says otherwise
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the backend has to answer the shutdown request itself
+const SHUTDOWN_REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Time allowed after the runs drain for the backend's own cleanup
+const SHUTDOWN_EXIT_GRACE: Duration = Duration::from_secs(5);
+
+/// Log lines buffered for a slow `backend-log` listener before it starts skipping
+const LOG_CHANNEL_CAPACITY: usize = 1024;
+
+/// Most lines `tail_backend_log` returns
+const MAX_TAIL_LINES: usize = 5000;
+
+/// Live backend output, fed by the stdout and stderr readers
+static LOG_LINES: LazyLock<broadcast::Sender<BackendLogLine>> =
+    LazyLock::new(|| broadcast::channel(LOG_CHANNEL_CAPACITY).0);
+
+/// Workspace served by the backend started at launch
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// How the backend is started in development mode, in place of
+/// `uv run --project <repo> uvicorn chimera_api.main:app` from the repo the
+/// app was built in. `--host` and `--port` are always added.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BackendCommand {
+    /// Program to run, e.g. `uv` or a virtualenv's `uvicorn`
+    pub command: Option<String>,
+    /// Arguments before `--host` and `--port`; the default depends on the program
+    pub args: Option<Vec<String>>,
+    /// Checkout of the Python backend, used as the uv project and working directory
+    pub cwd: Option<String>,
+    /// Python interpreter; without `command` it runs `python -m uvicorn`, and
+    /// with uv it's passed as `--python`
+    pub python: Option<String>,
+}
+
+impl BackendCommand {
+    pub fn validate(&self) -> Result<(), ChimeraError> {
+        for (name, value) in [("command", &self.command), ("python", &self.python)] {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(ChimeraError::InvalidInput(format!("Backend {} is empty", name)));
+            }
+        }
+        if let Some(cwd) = &self.cwd {
+            if !std::path::Path::new(cwd).is_absolute() {
+                return Err(ChimeraError::InvalidInput(format!(
+                    "Backend directory must be an absolute path: {}",
+                    cwd
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Program, arguments and directory to run, given the repo root the app
+    /// guessed. The directory is where the backend source is, before any
+    /// workspace directory replaces it.
+    fn resolve(&self, default_root: &std::path::Path, port: u16) -> (String, Vec<String>, PathBuf) {
+        let root = self.cwd.as_ref().map(PathBuf::from).unwrap_or_else(|| default_root.to_path_buf());
+        let app_args = || vec!["uvicorn".to_string(), "chimera_api.main:app".to_string()];
+
+        let (program, mut args) = match (&self.command, &self.python) {
+            (Some(command), _) => (command.clone(), self.args.clone().unwrap_or_default()),
+            (None, Some(python)) => {
+                let mut args = vec!["-m".to_string()];
+                args.extend(app_args());
+                (python.clone(), self.args.clone().unwrap_or(args))
+            }
+            (None, None) => {
+                let args = self.args.clone().unwrap_or_else(|| {
+                    let mut args = vec!["run".to_string(), "--project".to_string(), root.display().to_string()];
+                    args.extend(app_args());
+                    args
+                });
+                ("uv".to_string(), args)
+            }
+        };
+        // uv takes the interpreter as an option of `run`
+        if let (Some(python), true) = (&self.python, program == "uv" && args.first().is_some_and(|a| a == "run")) {
+            args.splice(1..1, ["--python".to_string(), python.clone()]);
+        }
+        args.extend(["--host".to_string(), "127.0.0.1".to_string(), "--port".to_string(), port.to_string()]);
+        (program, args, root)
+    }
+}
+
+/// Which workspace a backend serves and where it runs
+#[derive(Debug, Clone)]
+pub struct BackendConfig {
+    pub workspace_id: String,
+    /// Working directory for the backend, e.g. the project a window has open
+    pub cwd: Option<PathBuf>,
+    /// Development-mode command, from settings
+    pub command: BackendCommand,
+}
+
+impl BackendConfig {
+    pub fn default_workspace() -> Self {
+        Self {
+            workspace_id: DEFAULT_WORKSPACE.to_string(),
+            cwd: None,
+            command: crate::settings::backend_command(),
+        }
+    }
+
+    pub fn new(workspace_id: String, cwd: Option<PathBuf>) -> Result<Self, ChimeraError> {
+        validate_workspace_id(&workspace_id)?;
+        if let Some(cwd) = &cwd {
+            if !cwd.is_dir() {
+                return Err(ChimeraError::InvalidInput(format!("Not a directory: {}", cwd.display())));
+            }
+        }
+        Ok(Self {
+            workspace_id,
+            cwd,
+            command: crate::settings::backend_command(),
+        })
+    }
+
+    fn is_default(&self) -> bool {
+        self.workspace_id == DEFAULT_WORKSPACE
+    }
+}
+
+/// Workspace ids name files, so they're kept to letters, digits, `-` and `_`
+pub fn validate_workspace_id(workspace_id: &str) -> Result<(), ChimeraError> {
+    let valid = !workspace_id.is_empty()
+        && workspace_id.len() <= 64
+        && workspace_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ChimeraError::InvalidInput(format!("Invalid workspace id: {}", workspace_id)))
+    }
+}
+
 /// Deployment mode for the backend
 #[derive(Debug, Clone, Copy)]
 enum DeploymentMode {
@@ -19,99 +184,287 @@ enum DeploymentMode {
 
 /// Manages the Python backend subprocess lifecycle
 pub struct PythonBackend {
+    config: BackendConfig,
     child: Arc<Mutex<Option<Child>>>,
-    port: u16,
+    /// Port the current process bound, which may change on restart
+    port: AtomicU16,
+    /// Port asked for: `CHIMERA_BACKEND_PORT` for the default workspace, or 0 for
+    /// any free port
+    requested_port: u16,
     mode: DeploymentMode,
-    pid_file: PathBuf,
     /// Stdin pipe - kept open so Python can detect when we die
     #[allow(dead_code)]
-    stdin: Arc<Mutex<Option<tokio::process::ChildStdin>>>,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+    /// Takes the process's descendants (uv's Python, workers) down with it
+    job: StdMutex<Option<JobObject>>,
+    /// Extra environment, reapplied on restart
+    env: Vec<(String, String)>,
+    status: StdMutex<BackendStatus>,
+    /// Restarts since the backend was last healthy
+    restart_attempts: AtomicU32,
+    /// Held while a restart is in progress so manual and automatic restarts don't overlap
+    restart_lock: Mutex<()>,
+    /// Set on shutdown so the supervisor stops restarting
+    stopping: AtomicBool,
+    /// Output readers and the health supervisor, stopped on shutdown
+    tasks: TaskGroup,
 }
 
-/// Get the path for the PID file
-fn get_pid_file_path() -> PathBuf {
-    dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("chimera-desktop")
-        .join("python-backend.pid")
+/// Backend health as reported by `backend-status` events
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BackendStatus {
+    pub workspace_id: String,
+    /// `running`, `unhealthy`, `restarting`, `failed`, `stopping` or `stopped`
+    pub state: String,
+    pub url: String,
+    /// Restarts performed since launch
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    pub updated_at: String,
 }
 
-/// Kill any stale Python backend process from a previous run
-pub fn cleanup_stale_backend() {
-    let pid_file = get_pid_file_path();
+/// A line of backend output
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BackendLogLine {
+    pub workspace_id: String,
+    /// `stdout` or `stderr`
+    pub stream: String,
+    pub text: String,
+    /// When the line was read; not known for lines loaded from the log file
+    pub timestamp: Option<String>,
+}
 
-    if let Ok(contents) = std::fs::read_to_string(&pid_file) {
-        if let Ok(pid) = contents.trim().parse::<i32>() {
-            log::info!("Found stale PID file with PID {}, checking if process exists...", pid);
+/// Backend output is appended to `python-backend.log` in the desktop package,
+/// or `python-backend-<workspace>.log` for other workspaces, and rotated to
+/// `python-backend.log.1` and so on
+fn log_path(workspace_id: &str) -> Result<PathBuf, ChimeraError> {
+    validate_workspace_id(workspace_id)?;
+    let name = if workspace_id == DEFAULT_WORKSPACE {
+        "python-backend.log".to_string()
+    } else {
+        format!("python-backend-{}.log", workspace_id)
+    };
+    Ok(std::env::current_dir()
+        .map_err(|e| FsError::io("get current directory", e))?
+        .parent()
+        .ok_or("Failed to get package directory")?
+        .join(name))
+}
 
-            #[cfg(unix)]
-            {
-                use nix::sys::signal::{kill, Signal};
-                use nix::unistd::Pid;
+/// Every backend log file, current and rotated, for all workspaces
+pub fn log_files() -> Result<Vec<PathBuf>, ChimeraError> {
+    let dir = log_path(DEFAULT_WORKSPACE)?
+        .parent()
+        .ok_or("Failed to get package directory")?
+        .to_path_buf();
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(FsError::io(&format!("read {}", dir.display()), e).into()),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("python-backend") && name.contains(".log"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
 
-                let pid = Pid::from_raw(pid);
+/// Send a line read from the backend to live listeners and log it
+fn emit_log_line(workspace_id: &str, stream: &str, text: &str) {
+    log::info!("[Python {} {}] {}", workspace_id, stream, text);
+    // No receivers just means nobody is watching
+    let _ = LOG_LINES.send(BackendLogLine {
+        workspace_id: workspace_id.to_string(),
+        stream: stream.to_string(),
+        text: text.to_string(),
+        timestamp: Some(chrono::Utc::now().to_rfc3339()),
+    });
+}
 
-                // Check if process exists (signal 0 doesn't send anything, just checks)
-                if kill(pid, None).is_ok() {
-                    log::warn!("Stale Python backend process {} found, killing it...", pid);
+/// The last `lines` lines of backend output, reaching into rotated log files when
+/// the current one is shorter (blocking)
+pub fn tail_log(workspace_id: &str, lines: usize) -> Result<Vec<BackendLogLine>, ChimeraError> {
+    let lines = lines.min(MAX_TAIL_LINES);
+    let path = log_path(workspace_id)?;
+    let mut tail = tail_file(&path, workspace_id, lines)?;
+    for rotated in logging::rotated_paths(&path) {
+        if tail.len() >= lines {
+            break;
+        }
+        let mut older = tail_file(&rotated, workspace_id, lines - tail.len())?;
+        older.append(&mut tail);
+        tail = older;
+    }
+    Ok(tail)
+}
 
-                    // Try SIGTERM first
-                    let _ = kill(pid, Signal::SIGTERM);
-                    std::thread::sleep(Duration::from_millis(500));
+/// Delete a workspace backend's log files
+pub fn clear_logs(workspace_id: &str) -> Result<usize, ChimeraError> {
+    let removed = logging::clear(&log_path(workspace_id)?)?;
+    log::info!("Cleared {} backend log files for workspace {}", removed, workspace_id);
+    Ok(removed)
+}
 
-                    // If still alive, SIGKILL
-                    if kill(pid, None).is_ok() {
-                        log::warn!("Process {} didn't respond to SIGTERM, sending SIGKILL", pid);
-                        let _ = kill(pid, Signal::SIGKILL);
-                        std::thread::sleep(Duration::from_millis(100));
-                    }
+/// The last `lines` lines of one log file (blocking)
+fn tail_file(path: &std::path::Path, workspace_id: &str, lines: usize) -> Result<Vec<BackendLogLine>, FsError> {
+    const CHUNK: u64 = 64 * 1024;
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(FsError::io("open backend log", e)),
+    };
+    let len = file
+        .metadata()
+        .map_err(|e| FsError::io("read backend log", e))?
+        .len();
+
+    // Read backwards a chunk at a time until there are enough lines
+    let mut start = len;
+    let mut tail = Vec::new();
+    while start > 0 && tail.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read_from = start.saturating_sub(CHUNK);
+        let mut chunk = vec![0; (start - read_from) as usize];
+        file.seek(SeekFrom::Start(read_from))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .map_err(|e| FsError::io("read backend log", e))?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        start = read_from;
+    }
 
-                    log::info!("Stale process cleanup complete");
-                } else {
-                    log::info!("PID {} is not running, cleaning up stale PID file", pid);
-                }
+    let text = String::from_utf8_lossy(&tail);
+    let mut parsed: Vec<BackendLogLine> = text
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (stream, text) = match line.split_once("] ") {
+                Some((stream, text)) if stream == "[stdout" || stream == "[stderr" => (&stream[1..], text),
+                _ => ("stdout", line),
+            };
+            BackendLogLine {
+                workspace_id: workspace_id.to_string(),
+                stream: stream.to_string(),
+                text: text.to_string(),
+                timestamp: None,
             }
+        })
+        .collect();
+    // The first line may have been cut by the chunk boundary
+    if start > 0 && !parsed.is_empty() {
+        parsed.remove(0);
+    }
+    let skip = parsed.len().saturating_sub(lines);
+    Ok(parsed.split_off(skip))
+}
 
-            #[cfg(windows)]
-            {
-                // On Windows, just try to kill by PID
-                let _ = std::process::Command::new("taskkill")
-                    .args(["/F", "/PID", &pid.to_string()])
-                    .output();
+/// Publish backend output as `backend-log` events for the in-app console
+pub fn forward_log(bus: Arc<EventBus>) {
+    let mut receiver = LOG_LINES.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(line) => bus.publish("backend-log", &line),
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!("Backend console skipped {} log lines", skipped);
+                }
+                Err(RecvError::Closed) => break,
             }
         }
+    });
+}
+
+/// The port to ask the backend for (`CHIMERA_BACKEND_PORT`); 0 lets the OS pick
+pub(crate) fn requested_port() -> u16 {
+    std::env::var("CHIMERA_BACKEND_PORT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// The port from uvicorn's "Uvicorn running on http://0.0.0.0:54321 (Press CTRL+C to quit)"
+fn parse_bound_port(line: &str) -> Option<u16> {
+    let address = line.split("Uvicorn running on").nth(1)?.split_whitespace().next()?;
+    address.trim_end_matches('/').rsplit(':').next()?.parse().ok()
+}
 
-        // Remove the stale PID file
-        let _ = std::fs::remove_file(&pid_file);
+/// The port a backend log line says it is ready on, if it says so. When a fixed port
+/// was requested the startup-complete line is enough; with an ephemeral port only the
+/// line naming the bound address will do.
+fn ready_port(line: &str, requested: u16) -> Option<u16> {
+    if line.contains("Uvicorn running on") {
+        return parse_bound_port(line).or((requested != 0).then_some(requested));
     }
+    if requested != 0 && line.contains("Application startup complete") {
+        return Some(requested);
+    }
+    None
 }
 
-/// Write the PID to the PID file
-fn write_pid_file(pid_file: &PathBuf, pid: u32) -> Result<(), String> {
-    // Ensure parent directory exists
-    if let Some(parent) = pid_file.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create PID file directory: {}", e))?;
+/// The running backend's URL, or the mock backend's in mock mode, waiting for
+/// it to finish starting if needed
+pub async fn wait_for_url(app_handle: &tauri::AppHandle) -> Result<String, ChimeraError> {
+    use tauri::Manager;
+
+    let deadline = Instant::now() + URL_WAIT;
+    loop {
+        if let Some(url) = crate::mock_backend::url(app_handle) {
+            return Ok(url);
+        }
+        if let Some(backend) = app_handle.try_state::<Arc<PythonBackend>>() {
+            return Ok(backend.base_url());
+        }
+        if Instant::now() >= deadline {
+            return Err(ChimeraError::BackendUnavailable("Python backend is not running".to_string()));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
     }
+}
 
-    std::fs::write(pid_file, pid.to_string())
-        .map_err(|e| format!("Failed to write PID file: {}", e))?;
+fn shutdown_timeout() -> Duration {
+    std::env::var("CHIMERA_BACKEND_SHUTDOWN_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+}
 
-    log::info!("Wrote PID {} to {:?}", pid, pid_file);
-    Ok(())
+/// Endpoint asked to stop the backend gracefully (`CHIMERA_BACKEND_SHUTDOWN_PATH`)
+fn shutdown_path() -> String {
+    let path = std::env::var("CHIMERA_BACKEND_SHUTDOWN_PATH").unwrap_or_else(|_| "/shutdown".to_string());
+    format!("/{}", path.trim_start_matches('/'))
 }
 
-/// Remove the PID file
-fn remove_pid_file(pid_file: &PathBuf) {
-    if pid_file.exists() {
-        if let Err(e) = std::fs::remove_file(pid_file) {
-            log::warn!("Failed to remove PID file: {}", e);
-        } else {
-            log::info!("Removed PID file {:?}", pid_file);
-        }
+/// Publish a `backend-shutdown` progress event: `requesting`, `finishing` (active
+/// runs are completing), `terminating` (falling back to signals) or `stopped`
+fn publish_shutdown(bus: Option<&EventBus>, workspace_id: &str, stage: &str, active_runs: u64) {
+    log::info!("Backend {} shutdown: {} ({} active runs)", workspace_id, stage, active_runs);
+    if let Some(bus) = bus {
+        bus.publish(
+            "backend-shutdown",
+            serde_json::json!({ "workspace_id": workspace_id, "stage": stage, "active_runs": active_runs }),
+        );
     }
 }
 
+fn max_restarts() -> u32 {
+    std::env::var("CHIMERA_BACKEND_MAX_RESTARTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_RESTARTS)
+}
+
+/// Stop backends left running by an earlier session (blocking)
+pub fn cleanup_stale_backend(keep: &[u32]) -> crate::stale_backend::StaleCleanup {
+    crate::stale_backend::cleanup(keep, requested_port())
+}
+
 impl Drop for PythonBackend {
     fn drop(&mut self) {
         // Best-effort synchronous cleanup on drop
@@ -133,7 +486,13 @@ impl Drop for PythonBackend {
 
             #[cfg(windows)]
             {
-                let _ = child.start_kill();
+                match self.job.lock().unwrap().take() {
+                    Some(job) => job.terminate(),
+                    None => {
+                        let mut child = child;
+                        let _ = child.start_kill();
+                    }
+                }
             }
 
             // Give it a brief moment to die, but don't block for long
@@ -141,35 +500,22 @@ impl Drop for PythonBackend {
         }
 
         // Clean up PID file
-        remove_pid_file(&self.pid_file);
+        stale_backend::remove_pid_file(&self.config.workspace_id);
     }
 }
 
 impl PythonBackend {
-    /// Start the Python backend subprocess
-    pub async fn start() -> Result<Self, String> {
-        log::info!("Starting Chimera backend...");
-
-        // Get the package root (for log files: go up from src-tauri -> desktop)
-        let package_root = std::env::current_dir()
-            .map_err(|e| format!("Failed to get current directory: {}", e))?
-            .parent()  // -> packages/desktop
-            .ok_or("Failed to get package directory")?
-            .to_path_buf();
-
-        // Get the workspace root (for finding chimera backend)
-        let project_root = package_root
-            .parent()  // -> packages
-            .ok_or("Failed to get packages directory")?
-            .parent()  // -> workspace root
-            .ok_or("Failed to get workspace root")?
-            .to_path_buf();
-
-        // Port for Chimera backend
-        let port = 33003;
+    /// Start the Python backend subprocess for a workspace with extra environment variables
+    #[tracing::instrument(skip(env), err)]
+    pub async fn start(env: &[(String, String)], config: BackendConfig) -> Result<Self, ChimeraError> {
+        log::info!("Starting Chimera backend for workspace {}...", config.workspace_id);
+
+        // Port for Chimera backend; the one actually bound is read from its startup log.
+        // Only the default workspace can have a fixed port.
+        let requested_port = if config.is_default() { requested_port() } else { 0 };
 
         // Detect deployment mode
-        let mode = if std::env::var("CHIMERA_DESKTOP_PRODUCTION").is_ok() {
+        let mode = if is_production() {
             log::info!("Production mode: looking for bundled executable");
             DeploymentMode::Production
         } else {
@@ -177,244 +523,640 @@ impl PythonBackend {
             DeploymentMode::Development
         };
 
-        // Build command based on deployment mode
-        let mut command = match mode {
-            DeploymentMode::Development => {
-                // Development: use uv run from monorepo root
-                // project_root = frontend (from src-tauri -> desktop -> packages -> frontend)
-                // monorepo root = frontend/.. (one level up)
-                let monorepo_root = project_root
-                    .parent()  // -> monorepo root
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_else(|| project_root.clone());
-
-                log::info!("Using monorepo root: {:?}", monorepo_root);
-
-                // Use uv run to start the backend
-                let mut cmd = Command::new("uv");
-                cmd.arg("run");
-                cmd.arg("uvicorn");
-                cmd.arg("chimera_api.main:app");
-                cmd.arg("--host");
-                cmd.arg("0.0.0.0");
-                cmd.arg("--port");
-                cmd.arg(port.to_string());
-                cmd.current_dir(&monorepo_root);
-                cmd
-            }
-            DeploymentMode::Production => {
-                // Production: ./chimera-backend --port 33003
-                let bundled_exe = project_root.join("resources").join("chimera-backend");
-                if !bundled_exe.exists() {
-                    return Err(format!("Bundled backend not found: {:?}", bundled_exe));
-                }
-                log::info!("Using bundled backend: {:?}", bundled_exe);
+        let tasks = TaskGroup::new("backend");
+        let (child, stdin, port, job) = spawn_process(requested_port, mode, env, &config, &tasks).await?;
+
+        crate::audit::record_async(
+            crate::audit::AuditEntry::new("backend", "start", "app", format!("http://localhost:{}", port), true)
+                .detail(format!("{:?}, workspace {}", mode, config.workspace_id)),
+        )
+        .await;
+
+        Ok(Self {
+            status: StdMutex::new(BackendStatus {
+                workspace_id: config.workspace_id.clone(),
+                state: "running".to_string(),
+                url: format!("http://localhost:{}", port),
+                restarts: 0,
+                last_error: None,
+                updated_at: chrono::Utc::now().to_rfc3339(),
+            }),
+            config,
+            child: Arc::new(Mutex::new(Some(child))),
+            port: AtomicU16::new(port),
+            requested_port,
+            mode,
+            stdin: Arc::new(Mutex::new(Some(stdin))),
+            job: StdMutex::new(job),
+            env: env.to_vec(),
+            restart_attempts: AtomicU32::new(0),
+            restart_lock: Mutex::new(()),
+            stopping: AtomicBool::new(false),
+            tasks,
+        })
+    }
+
+    /// Get the base URL for the Python backend
+    pub fn base_url(&self) -> String {
+        format!("http://localhost:{}", self.port())
+    }
+
+    /// Process id of the running backend
+    pub async fn pid(&self) -> Option<u32> {
+        self.child.lock().await.as_ref().and_then(|child| child.id())
+    }
+
+    /// Get the port
+    pub fn port(&self) -> u16 {
+        self.port.load(Ordering::SeqCst)
+    }
 
-                let mut cmd = Command::new(bundled_exe);
-                cmd.arg("--port");
-                cmd.arg(port.to_string());
-                cmd
+    /// Current health and restart count
+    pub fn status(&self) -> BackendStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn set_status(&self, bus: Option<&EventBus>, state: &str, last_error: Option<String>) {
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            if status.state == state && status.last_error == last_error {
+                return;
             }
+            status.state = state.to_string();
+            status.last_error = last_error;
+            status.updated_at = chrono::Utc::now().to_rfc3339();
+            status.clone()
         };
+        log::info!("Backend status: {}", status.state);
+        if let Some(bus) = bus {
+            bus.publish("backend-status", &status);
+        }
+    }
+
+    /// Stop the current process and start a fresh one
+    #[tracing::instrument(skip(self, bus), err)]
+    pub async fn restart(&self, bus: Option<&EventBus>) -> Result<(), ChimeraError> {
+        let _restarting = self.restart_lock.lock().await;
+        self.set_status(bus, "restarting", None);
 
-        // Set supervised mode env var - Python will monitor stdin and exit when we die
-        command.env("CHIMERA_SUPERVISED", "1");
-
-        // Pipe stdin so Python can detect when we die (stdin closes)
-        command.stdin(Stdio::piped());
-        command.stdout(Stdio::piped());
-        command.stderr(Stdio::piped());
-
-        // Configure process to be killed when parent dies (Linux-specific)
-        // On macOS, we use the CHIMERA_SUPERVISED env var + stdin pipe instead
-        #[cfg(target_os = "linux")]
-        unsafe {
-            command.pre_exec(|| {
-                // Use prctl to set parent death signal on Linux
-                // PR_SET_PDEATHSIG = 1, SIGKILL = 9
-                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
-                Ok(())
-            });
+        if let Some(mut child) = self.child.lock().await.take() {
+            #[cfg(unix)]
+            graceful_terminate_unix(&mut child).await;
+            #[cfg(windows)]
+            force_terminate_windows(&mut child).await;
+        }
+        self.stdin.lock().await.take();
+        // Anything the old process left running goes with its job
+        self.job.lock().unwrap().take();
+
+        let (child, stdin, port, job) = match spawn_process(self.requested_port, self.mode, &self.env, &self.config, &self.tasks).await {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                self.set_status(bus, "failed", Some(e.to_string()));
+                return Err(e);
+            }
+        };
+        *self.child.lock().await = Some(child);
+        *self.stdin.lock().await = Some(stdin);
+        *self.job.lock().unwrap() = job;
+        self.port.store(port, Ordering::SeqCst);
+        {
+            let mut status = self.status.lock().unwrap();
+            status.restarts += 1;
+            status.url = self.base_url();
         }
 
-        let mut child = command
-            .spawn()
-            .map_err(|e| format!("Failed to spawn Python backend: {}", e))?;
+        crate::audit::record_async(crate::audit::AuditEntry::new("backend", "restart", "app", self.base_url(), true))
+            .await;
+        self.set_status(bus, "running", None);
+        Ok(())
+    }
 
-        // Write PID file for cleanup on next startup if we crash
-        let pid_file = get_pid_file_path();
-        if let Some(pid) = child.id() {
-            write_pid_file(&pid_file, pid)?;
+    /// Why the backend needs a restart, if it does: the process exited, or it has
+    /// failed enough health checks in a row
+    async fn check_health(&self, client: &reqwest::Client, failures: &mut u32) -> Option<String> {
+        if let Some(child) = self.child.lock().await.as_mut() {
+            if let Ok(Some(status)) = child.try_wait() {
+                return Some(format!("Backend exited with {}", status));
+            }
         }
 
-        // Take stdin - we keep this open so Python can detect when we die
-        let stdin = child.stdin.take().expect("stdin was piped");
-        let stdout = child.stdout.take().expect("stdout was piped");
-        let stderr = child.stderr.take().expect("stderr was piped");
-
-        // Create log file for Python output
-        let log_path = package_root.join("python-backend.log");
-        let log_file = Arc::new(Mutex::new(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&log_path)
-                .await
-                .map_err(|e| format!("Failed to create log file: {}", e))?
-        ));
-        log::info!("Python logs will be written to: {:?}", log_path);
+        let started = std::time::Instant::now();
+        let healthy = client
+            .get(format!("{}/health", self.base_url()))
+            .timeout(Duration::from_secs(3))
+            .send()
+            .await
+            .is_ok_and(|r| r.status().is_success());
+        crate::metrics::record_health_check(started.elapsed(), healthy);
+        if healthy {
+            *failures = 0;
+            return None;
+        }
 
-        // Create channels for communication
-        let (ready_tx, mut ready_rx) = mpsc::channel::<bool>(1);
-        let ready_tx_clone = ready_tx.clone();
+        *failures += 1;
+        (*failures >= UNHEALTHY_THRESHOLD).then(|| format!("Backend failed {} health checks", failures))
+    }
 
-        // Monitor stdout for readiness signal
-        let log_file_stdout = log_file.clone();
-        let _stdout_task = tokio::spawn(async move {
-            let mut reader = BufReader::new(stdout);
-            let mut line = String::new();
+    /// Poll the health endpoint, publish `backend-status` changes and restart the
+    /// process with exponential backoff when it dies or stops responding
+    pub fn supervise(self: &Arc<Self>, bus: Arc<EventBus>) {
+        let backend = Arc::downgrade(self);
+        let token = self.tasks.token();
+        self.tasks.spawn(async move {
+            let client = reqwest::Client::new();
+            let mut failures = 0;
 
             loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if !trimmed.is_empty() {
-                            // Write to log file
-                            let mut file = log_file_stdout.lock().await;
-                            let _ = file.write_all(format!("[stdout] {}\n", trimmed).as_bytes()).await;
-
-                            log::info!("[Python stdout] {}", trimmed);
-
-                            // Look for Uvicorn's ready message
-                            if trimmed.contains("Uvicorn running on") || trimmed.contains("Application startup complete") {
-                                log::info!("Python backend is ready!");
-                                let _ = ready_tx.send(true).await;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Error reading stdout: {}", e);
-                        break;
-                    }
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = tokio::time::sleep(HEALTH_INTERVAL) => {}
+                }
+                let Some(backend) = backend.upgrade() else { break };
+                if backend.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                if backend.status.lock().unwrap().state == "failed" {
+                    continue;
                 }
-            }
-        });
 
-        // Monitor stderr for errors
-        let log_file_stderr = log_file.clone();
-        let _stderr_task = tokio::spawn(async move {
-            let mut reader = BufReader::new(stderr);
-            let mut line = String::new();
+                let Some(problem) = backend.check_health(&client, &mut failures).await else {
+                    backend.restart_attempts.store(0, Ordering::SeqCst);
+                    backend.set_status(Some(&bus), "running", None);
+                    continue;
+                };
+                if backend.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
 
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if !trimmed.is_empty() {
-                            // Write to log file
-                            let mut file = log_file_stderr.lock().await;
-                            let _ = file.write_all(format!("[stderr] {}\n", trimmed).as_bytes()).await;
-
-                            log::info!("[Python stderr] {}", trimmed);
-
-                            // Uvicorn also logs to stderr
-                            if trimmed.contains("Uvicorn running on") || trimmed.contains("Application startup complete") {
-                                log::info!("Python backend is ready (from stderr)!");
-                                let _ = ready_tx_clone.send(true).await;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Error reading stderr: {}", e);
-                        break;
-                    }
+                let attempt = backend.restart_attempts.fetch_add(1, Ordering::SeqCst);
+                if attempt >= max_restarts() {
+                    log::error!("{}; giving up after {} restarts", problem, attempt);
+                    backend.set_status(Some(&bus), "failed", Some(problem));
+                    continue;
+                }
+
+                let backoff = Duration::from_secs(1u64 << attempt.min(6)).min(MAX_RESTART_BACKOFF);
+                log::warn!("{}; restarting in {:?} (attempt {})", problem, backoff, attempt + 1);
+                backend.set_status(Some(&bus), "unhealthy", Some(problem));
+                tokio::time::sleep(backoff).await;
+
+                failures = 0;
+                if let Err(e) = backend.restart(Some(&bus)).await {
+                    log::error!("Backend restart failed: {}", e);
                 }
             }
         });
+    }
+
+    /// Relay the backend's event socket to the app as `backend-event`s, across restarts
+    pub fn bridge_events(self: &Arc<Self>, bus: Arc<EventBus>) {
+        self.tasks
+            .spawn(crate::backend_events::bridge(Arc::downgrade(self), bus, self.tasks.token()));
+    }
+
+    /// Whether the backend is being shut down for good
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
 
-        // Check if process exited early
-        if let Ok(Some(status)) = child.try_wait() {
-            if !status.success() {
-                return Err(format!("Python backend exited early with code {:?}", status));
+    /// Restart on request, resetting the retry budget
+    pub async fn restart_now(&self, bus: &EventBus) -> Result<(), ChimeraError> {
+        self.restart_attempts.store(0, Ordering::SeqCst);
+        self.restart(Some(bus)).await
+    }
+
+    /// Ask the backend over HTTP to stop once its active runs finish, and wait for
+    /// it to exit. Returns false if it couldn't be asked or didn't exit in time.
+    async fn request_shutdown(&self, child: &mut Child, bus: Option<&EventBus>) -> bool {
+        let timeout = shutdown_timeout();
+        publish_shutdown(bus, &self.config.workspace_id, "requesting", 0);
+
+        let response = reqwest::Client::new()
+            .post(format!("{}{}", self.base_url(), shutdown_path()))
+            .json(&serde_json::json!({ "timeout": timeout.as_secs_f64() }))
+            .timeout(SHUTDOWN_REQUEST_TIMEOUT)
+            .send()
+            .await;
+        let active_runs = match response {
+            Ok(response) if response.status().is_success() => response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| body.get("active_runs")?.as_u64())
+                .unwrap_or(0),
+            Ok(response) => {
+                log::warn!("Backend refused the shutdown request: {}", response.status());
+                return false;
+            }
+            Err(e) => {
+                log::warn!("Failed to request backend shutdown: {}", e);
+                return false;
+            }
+        };
+        publish_shutdown(bus, &self.config.workspace_id, "finishing", active_runs);
+
+        match tokio::time::timeout(timeout + SHUTDOWN_EXIT_GRACE, child.wait()).await {
+            Ok(Ok(status)) => {
+                log::info!("Process exited after shutdown request: {}", status);
+                true
+            }
+            Ok(Err(e)) => {
+                log::error!("Error waiting after shutdown request: {}", e);
+                false
+            }
+            Err(_) => {
+                log::warn!("Backend didn't exit within {:?} of the shutdown request", timeout + SHUTDOWN_EXIT_GRACE);
+                false
             }
         }
+    }
 
-        // Wait for backend to be ready with timeout
-        let timeout_duration = Duration::from_secs(30); // 30 second timeout
-        let start_time = Instant::now();
+    /// Gracefully shutdown the Python backend: ask it over HTTP to finish active
+    /// runs and exit, and signal it only if that fails
+    #[tracing::instrument(skip(self, bus))]
+    pub async fn shutdown(&self, bus: Option<&EventBus>) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.set_status(bus, "stopping", None);
+        let mut child_guard = self.child.lock().await;
 
-        log::info!("Waiting for Python backend to be ready...");
-        loop {
-            tokio::select! {
-                // Backend is ready
-                Some(true) = ready_rx.recv() => {
-                    log::info!("Python backend ready to accept requests!");
-                    break;
+        if let Some(mut child) = child_guard.take() {
+            log::info!("Shutting down Python backend...");
+
+            let exited = matches!(child.try_wait(), Ok(Some(_))) || self.request_shutdown(&mut child, bus).await;
+            if !exited {
+                publish_shutdown(bus, &self.config.workspace_id, "terminating", 0);
+
+                #[cfg(unix)]
+                {
+                    graceful_terminate_unix(&mut child).await;
                 }
-                // Check for process exit
-                _ = tokio::time::sleep(Duration::from_millis(100)) => {
-                    if let Ok(Some(status)) = child.try_wait() {
-                        return Err(format!("Python backend exited with code {:?}", status));
-                    }
 
-                    // Timeout check
-                    if start_time.elapsed() > timeout_duration {
-                        let _ = child.kill().await;
-                        return Err(format!("Python backend failed to start within {}s", timeout_duration.as_secs()));
-                    }
+                #[cfg(windows)]
+                {
+                    force_terminate_windows(&mut child).await;
                 }
             }
+
+            self.job.lock().unwrap().take();
+            publish_shutdown(bus, &self.config.workspace_id, "stopped", 0);
+            log::info!("Python backend shutdown complete");
         }
+        self.set_status(bus, "stopped", None);
+        drop(child_guard);
+        self.stdin.lock().await.take();
 
-        Ok(Self {
-            child: Arc::new(Mutex::new(Some(child))),
-            port,
-            mode,
-            pid_file,
-            stdin: Arc::new(Mutex::new(Some(stdin))),
-        })
+        // Stop the output readers and the supervisor
+        self.tasks.shutdown(SHUTDOWN_TIMEOUT).await;
+
+        // Clean up PID file
+        stale_backend::remove_pid_file(&self.config.workspace_id);
     }
+}
 
-    /// Get the base URL for the Python backend
-    pub fn base_url(&self) -> String {
-        format!("http://localhost:{}", self.port)
+/// The workspace root: `frontend`, from src-tauri -> desktop -> packages -> frontend
+fn project_root() -> Result<PathBuf, ChimeraError> {
+    let package_root = std::env::current_dir()
+        .map_err(|e| FsError::io("get current directory", e))?
+        .parent()  // -> packages/desktop
+        .ok_or("Failed to get package directory")?
+        .to_path_buf();
+    Ok(package_root
+        .parent()  // -> packages
+        .ok_or("Failed to get packages directory")?
+        .parent()  // -> workspace root
+        .ok_or("Failed to get workspace root")?
+        .to_path_buf())
+}
+
+/// The monorepo root, one level above `frontend`, where the Python backend is
+fn monorepo_root(project_root: &std::path::Path) -> PathBuf {
+    project_root.parent().unwrap_or(project_root).to_path_buf()
+}
+
+/// Whether the app runs the bundled backend executable rather than the Python
+/// module from the repo
+pub(crate) fn is_production() -> bool {
+    std::env::var("CHIMERA_DESKTOP_PRODUCTION").is_ok()
+}
+
+/// The PyInstaller executable production mode runs
+fn bundled_backend(project_root: &std::path::Path) -> PathBuf {
+    project_root.join("resources").join("chimera-backend")
+}
+
+/// Where the app expects the bundled backend executable
+pub(crate) fn bundled_backend_path() -> Result<PathBuf, ChimeraError> {
+    Ok(bundled_backend(&project_root()?))
+}
+
+/// Program development mode runs for the default backend and the directory it
+/// runs in, with `command`'s overrides applied
+pub(crate) fn development_command(command: &BackendCommand) -> Result<(String, PathBuf), ChimeraError> {
+    command.validate()?;
+    let (program, _, root) = command.resolve(&monorepo_root(&project_root()?), 0);
+    Ok((program, root))
+}
+
+/// Result of `test_command`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BackendCommandTest {
+    pub ok: bool,
+    /// The command line that was run, with port 0 for any free port
+    pub command: String,
+    pub cwd: String,
+    pub error: Option<String>,
+    /// The backend's last lines of output
+    pub output: Vec<String>,
+}
+
+/// Workspace the test backend runs as, keeping its PID file and log apart
+const TEST_WORKSPACE: &str = "backend-command-test";
+
+/// Start a backend with `command` on a free port, wait for it to report ready and
+/// shut it down again, so overrides can be checked before they're saved
+#[tracing::instrument]
+pub async fn test_command(command: BackendCommand) -> BackendCommandTest {
+    let resolved = if is_production() {
+        Err(ChimeraError::InvalidInput(
+            "Backend command overrides only apply in development mode".to_string(),
+        ))
+    } else {
+        command.validate()
+    };
+    let resolved = resolved.and_then(|_| Ok(command.resolve(&monorepo_root(&project_root()?), 0)));
+    let (program, args, root) = match resolved {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            return BackendCommandTest {
+                ok: false,
+                command: String::new(),
+                cwd: String::new(),
+                error: Some(e.to_string()),
+                output: Vec::new(),
+            }
+        }
+    };
+
+    let config = BackendConfig {
+        workspace_id: TEST_WORKSPACE.to_string(),
+        cwd: None,
+        command,
+    };
+    let error = match PythonBackend::start(&[], config).await {
+        Ok(backend) => {
+            backend.shutdown(None).await;
+            None
+        }
+        Err(e) => Some(e.to_string()),
+    };
+    let output = crate::filesystem::blocking(|| {
+        let lines = tail_log(TEST_WORKSPACE, 40)?;
+        clear_logs(TEST_WORKSPACE)?;
+        Ok(lines.into_iter().map(|line| line.text).collect())
+    })
+    .await
+    .unwrap_or_default();
+
+    BackendCommandTest {
+        ok: error.is_none(),
+        command: std::iter::once(program).chain(args).collect::<Vec<_>>().join(" "),
+        cwd: root.display().to_string(),
+        error,
+        output,
     }
+}
 
-    /// Get the port
-    #[allow(dead_code)]
-    pub fn port(&self) -> u16 {
-        self.port
+/// Spawn the backend process on `port` (0 for any free port) and wait for it to
+/// report ready. Returns the process, its stdin, the port it bound and the job
+/// holding its process tree.
+async fn spawn_process(
+    port: u16,
+    mode: DeploymentMode,
+    env: &[(String, String)],
+    config: &BackendConfig,
+    tasks: &TaskGroup,
+) -> Result<(Child, ChildStdin, u16, Option<JobObject>), ChimeraError> {
+    crate::metrics::record_backend_start();
+
+    let project_root = project_root()?;
+
+    // Build command based on deployment mode
+    let mut command = match mode {
+        DeploymentMode::Development => {
+            // Development: uv run from the monorepo root, unless settings say otherwise
+            let monorepo_root = monorepo_root(&project_root);
+            let (program, args, root) = config.command.resolve(&monorepo_root, port);
+            log::info!("Running backend: {} {} in {:?}", program, args.join(" "), root);
+
+            // In the workspace's directory if it has one
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd.current_dir(config.cwd.as_ref().unwrap_or(&root));
+            cmd
+        }
+        DeploymentMode::Production => {
+            // Production: ./chimera-backend --host 127.0.0.1 --port <port>
+            let bundled_exe = bundled_backend(&project_root);
+            if !bundled_exe.exists() {
+                return Err(ChimeraError::BackendUnavailable(format!(
+                    "Bundled backend not found: {:?}",
+                    bundled_exe
+                )));
+            }
+            log::info!("Using bundled backend: {:?}", bundled_exe);
+
+            let mut cmd = Command::new(bundled_exe);
+            cmd.arg("--host");
+            cmd.arg("127.0.0.1");
+            cmd.arg("--port");
+            cmd.arg(port.to_string());
+            if let Some(cwd) = &config.cwd {
+                cmd.current_dir(cwd);
+            }
+            cmd
+        }
+    };
+
+    // Set supervised mode env var - Python will monitor stdin and exit when we die
+    command.env("CHIMERA_SUPERVISED", "1");
+    command.env("CHIMERA_WORKSPACE_ID", &config.workspace_id);
+    if let Some(cwd) = &config.cwd {
+        command.env("CHIMERA_WORKSPACE_DIR", cwd);
+    }
+    command.envs(env.iter().map(|(k, v)| (k, v)));
+
+    // Pipe stdin so Python can detect when we die (stdin closes)
+    command.stdin(Stdio::piped());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+
+    // Configure process to be killed when parent dies (Linux-specific)
+    // On macOS, we use the CHIMERA_SUPERVISED env var + stdin pipe instead
+    #[cfg(target_os = "linux")]
+    unsafe {
+        command.pre_exec(|| {
+            // Use prctl to set parent death signal on Linux
+            // PR_SET_PDEATHSIG = 1, SIGKILL = 9
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+            Ok(())
+        });
     }
 
-    /// Gracefully shutdown the Python backend
-    pub async fn shutdown(&self) {
-        let mut child_guard = self.child.lock().await;
+    let mut child = command
+        .spawn()
+        .map_err(|e| ChimeraError::BackendUnavailable(format!("Failed to spawn Python backend: {}", e)))?;
+    // Before it starts anything, so its whole tree is in the job
+    let job = job_object::attach(child.id(), "Python backend");
 
-        if let Some(mut child) = child_guard.take() {
-            log::info!("Shutting down Python backend...");
+    // Write PID file for cleanup on next startup if we crash
+    if let Some(pid) = child.id() {
+        stale_backend::write_pid_file(&config.workspace_id, pid, port)?;
+    }
 
-            #[cfg(unix)]
-            {
-                graceful_terminate_unix(&mut child).await;
+    // Take stdin - we keep this open so Python can detect when we die
+    let stdin = child.stdin.take().expect("stdin was piped");
+    let stdout = child.stdout.take().expect("stdout was piped");
+    let stderr = child.stderr.take().expect("stderr was piped");
+
+    // Create log file for Python output
+    let log_path = log_path(&config.workspace_id)?;
+    let log_file = Arc::new(Mutex::new(RotatingLog::open(log_path.clone()).await?));
+    log::info!("Python logs will be written to: {:?}", log_path);
+
+    // Create channels for communication
+    let (ready_tx, mut ready_rx) = mpsc::channel::<u16>(1);
+    let ready_tx_clone = ready_tx.clone();
+
+    // Monitor stdout for readiness signal
+    let log_file_stdout = log_file.clone();
+    let workspace_id = config.workspace_id.clone();
+    let token = tasks.token();
+    tasks.spawn(async move {
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            let read = tokio::select! {
+                _ = token.cancelled() => break,
+                read = reader.read_line(&mut line) => read,
+            };
+            match read {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() {
+                        // Write to log file
+                        let mut file = log_file_stdout.lock().await;
+                        let _ = file.write_line(&format!("[stdout] {}", trimmed)).await;
+
+                        emit_log_line(&workspace_id, "stdout", trimmed);
+
+                        // Look for Uvicorn's ready message
+                        if let Some(bound) = ready_port(trimmed, port) {
+                            log::info!("Python backend is ready!");
+                            let _ = ready_tx.send(bound).await;
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::error!("Error reading stdout: {}", e);
+                    break;
+                }
             }
+        }
+    });
 
-            #[cfg(windows)]
-            {
-                force_terminate_windows(&mut child).await;
+    // Monitor stderr for errors
+    let log_file_stderr = log_file.clone();
+    let workspace_id = config.workspace_id.clone();
+    let token = tasks.token();
+    tasks.spawn(async move {
+        let mut reader = BufReader::new(stderr);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            let read = tokio::select! {
+                _ = token.cancelled() => break,
+                read = reader.read_line(&mut line) => read,
+            };
+            match read {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() {
+                        // Write to log file
+                        let mut file = log_file_stderr.lock().await;
+                        let _ = file.write_line(&format!("[stderr] {}", trimmed)).await;
+
+                        emit_log_line(&workspace_id, "stderr", trimmed);
+
+                        // Uvicorn also logs to stderr
+                        if let Some(bound) = ready_port(trimmed, port) {
+                            log::info!("Python backend is ready (from stderr)!");
+                            let _ = ready_tx_clone.send(bound).await;
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::error!("Error reading stderr: {}", e);
+                    break;
+                }
             }
+        }
+    });
+
+    // Check if process exited early
+    if let Ok(Some(status)) = child.try_wait() {
+        if !status.success() {
+            return Err(ChimeraError::BackendUnavailable(format!(
+                "Python backend exited early with code {:?}",
+                status
+            )));
+        }
+    }
 
-            log::info!("Python backend shutdown complete");
+    // Wait for backend to be ready with timeout
+    let timeout_duration = Duration::from_secs(30); // 30 second timeout
+    let start_time = Instant::now();
+
+    log::info!("Waiting for Python backend to be ready...");
+    let bound_port = loop {
+        tokio::select! {
+            // Backend is ready
+            Some(bound) = ready_rx.recv() => {
+                log::info!("Python backend ready to accept requests on port {}!", bound);
+                break bound;
+            }
+            // Check for process exit
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                if let Ok(Some(status)) = child.try_wait() {
+                    return Err(ChimeraError::BackendUnavailable(format!("Python backend exited with code {:?}", status)));
+                }
+
+                // Timeout check
+                if start_time.elapsed() > timeout_duration {
+                    let _ = child.kill().await;
+                    return Err(ChimeraError::BackendUnavailable(format!(
+                        "Python backend failed to start within {}s",
+                        timeout_duration.as_secs()
+                    )));
+                }
+            }
         }
+    };
 
-        // Clean up PID file
-        remove_pid_file(&self.pid_file);
+    // Now with the port, so a backend left behind can be found by it too
+    if let Some(pid) = child.id() {
+        stale_backend::write_pid_file(&config.workspace_id, pid, bound_port)?;
     }
+
+    Ok((child, stdin, bound_port, job))
 }
 
+
 /// Gracefully terminate a process on Unix (SIGTERM → SIGKILL)
 #[cfg(unix)]
 async fn graceful_terminate_unix(child: &mut Child) {
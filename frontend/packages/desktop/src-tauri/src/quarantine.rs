@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::filesystem;
+
+/// Header-only threads younger than this are left alone: they may simply not have
+/// had a first message yet
+const HEADER_ONLY_MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A thread file set aside because it holds no events
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedThread {
+    pub thread_id: String,
+    /// `empty` or `header-only`
+    pub reason: String,
+    pub size: u64,
+    pub modified_at: String,
+}
+
+/// Get the quarantine directory
+fn get_quarantine_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("quarantine"))
+}
+
+/// Threads the user recovered, which later scans must leave in place
+fn get_recovered_path() -> Result<PathBuf, String> {
+    Ok(get_quarantine_dir()?.join("recovered.json"))
+}
+
+fn load_recovered() -> Vec<String> {
+    get_recovered_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Why a thread file counts as broken, if it does
+fn broken_reason(path: &Path, metadata: &std::fs::Metadata) -> Option<&'static str> {
+    if metadata.len() == 0 {
+        return Some("empty");
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut lines = BufReader::new(file)
+        .split(b'\n')
+        .map_while(Result::ok)
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace));
+    match (lines.next(), lines.next()) {
+        (None, _) => Some("empty"),
+        (Some(_), None) => Some("header-only"),
+        _ => None,
+    }
+}
+
+fn describe(path: &Path) -> Option<QuarantinedThread> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(QuarantinedThread {
+        thread_id: path.file_stem()?.to_string_lossy().into_owned(),
+        reason: broken_reason(path, &metadata).unwrap_or("unknown").to_string(),
+        size: metadata.len(),
+        modified_at: metadata
+            .modified()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+            .unwrap_or_default(),
+    })
+}
+
+/// Move empty and stale header-only thread files into the quarantine directory.
+/// Returns what was moved (blocking).
+pub fn scan() -> Result<Vec<QuarantinedThread>, String> {
+    let mut moved = Vec::new();
+    let recovered = load_recovered();
+
+    for path in filesystem::list_thread_files()? {
+        let is_recovered = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|id| recovered.iter().any(|r| r == id));
+        if is_recovered {
+            continue;
+        }
+
+        let Ok(metadata) = std::fs::metadata(&path) else { continue };
+        let Some(reason) = broken_reason(&path, &metadata) else { continue };
+
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .unwrap_or_default();
+        if reason == "header-only" && age < HEADER_ONLY_MIN_AGE {
+            continue;
+        }
+
+        let quarantine_dir = get_quarantine_dir()?;
+        std::fs::create_dir_all(&quarantine_dir)
+            .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+        let Some(name) = path.file_name() else { continue };
+        let target = quarantine_dir.join(name);
+
+        match std::fs::rename(&path, &target) {
+            Ok(()) => {
+                log::warn!("Quarantined {} thread {}", reason, path.display());
+                if let Some(thread) = describe(&target) {
+                    moved.push(thread);
+                }
+            }
+            Err(e) => log::error!("Failed to quarantine {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(moved)
+}
+
+/// Threads currently in quarantine (blocking)
+pub fn list() -> Result<Vec<QuarantinedThread>, String> {
+    let quarantine_dir = get_quarantine_dir()?;
+    if !quarantine_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&quarantine_dir)
+        .map_err(|e| format!("Failed to read quarantine directory: {}", e))?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+        .filter_map(|path| describe(&path))
+        .collect())
+}
+
+/// Move a quarantined thread back into the threads directory (blocking)
+pub fn recover(thread_id: &str) -> Result<(), String> {
+    let target = filesystem::get_thread_path(thread_id)?;
+    let source = get_quarantine_dir()?.join(format!("{}.jsonl", thread_id));
+
+    if !source.exists() {
+        return Err(format!("Thread {} is not in quarantine", thread_id));
+    }
+    if crate::thread_compression::thread_exists(thread_id)? {
+        return Err(format!("Thread {} already exists", thread_id));
+    }
+
+    std::fs::rename(&source, &target).map_err(|e| format!("Failed to recover thread: {}", e))?;
+
+    let mut recovered = load_recovered();
+    recovered.push(thread_id.to_string());
+    let content = serde_json::to_string_pretty(&recovered)
+        .map_err(|e| format!("Failed to serialize recovered threads: {}", e))?;
+    std::fs::write(get_recovered_path()?, content)
+        .map_err(|e| format!("Failed to write recovered threads: {}", e))?;
+
+    log::info!("Recovered quarantined thread {}", thread_id);
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::blob_store;
+use crate::filesystem;
+
+/// Built-in pattern for API keys and tokens in the common provider formats
+pub const API_KEYS: &str = "api_keys";
+
+/// Built-in pattern for email addresses
+pub const EMAILS: &str = "emails";
+
+const API_KEY_REGEX: &str = concat!(
+    r"\b(?:sk-[A-Za-z0-9_-]{20,}",
+    r"|AKIA[0-9A-Z]{16}",
+    r"|gh[opsu]_[A-Za-z0-9]{36,}",
+    r"|github_pat_[A-Za-z0-9_]{22,}",
+    r"|xox[abprs]-[A-Za-z0-9-]{10,}",
+    r"|AIza[0-9A-Za-z_-]{35}",
+    r")|\bBearer\s+[A-Za-z0-9._~+/-]{20,}=*",
+);
+
+const EMAIL_REGEX: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+
+/// Result of `redact_thread`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RedactionReport {
+    pub events: usize,
+    pub events_modified: usize,
+    /// Matches replaced, by pattern
+    pub replacements: BTreeMap<String, usize>,
+    /// Copy of the thread from before its first redaction; later redactions keep it
+    pub backup_path: Option<String>,
+}
+
+/// A compiled pattern and what its matches become
+struct Redactor {
+    pattern: String,
+    regex: Regex,
+    placeholder: &'static str,
+}
+
+/// Compile `patterns`: `api_keys` and `emails` are built in, anything else is a regex
+fn compile(patterns: &[String]) -> Result<Vec<Redactor>, String> {
+    if patterns.is_empty() {
+        return Err("No redaction patterns given".to_string());
+    }
+    patterns
+        .iter()
+        .map(|pattern| {
+            let (source, placeholder) = match pattern.as_str() {
+                API_KEYS => (API_KEY_REGEX, "[REDACTED:api_key]"),
+                EMAILS => (EMAIL_REGEX, "[REDACTED:email]"),
+                custom => (custom, "[REDACTED]"),
+            };
+            let regex = Regex::new(source).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+            Ok(Redactor {
+                pattern: pattern.clone(),
+                regex,
+                placeholder,
+            })
+        })
+        .collect()
+}
+
+/// Redact every string in `value`, at any depth. Returns whether anything changed.
+fn redact_value(value: &mut serde_json::Value, redactors: &[Redactor], counts: &mut BTreeMap<String, usize>) -> bool {
+    match value {
+        serde_json::Value::String(text) => {
+            let mut changed = false;
+            for redactor in redactors {
+                let matches = redactor.regex.find_iter(text).count();
+                if matches == 0 {
+                    continue;
+                }
+                *text = redactor.regex.replace_all(text, redactor.placeholder).into_owned();
+                *counts.entry(redactor.pattern.clone()).or_default() += matches;
+                changed = true;
+            }
+            changed
+        }
+        serde_json::Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, item| redact_value(item, redactors, counts) | changed),
+        serde_json::Value::Object(fields) => fields
+            .values_mut()
+            .fold(false, |changed, field| redact_value(field, redactors, counts) | changed),
+        _ => false,
+    }
+}
+
+/// Redact an event, including fields moved to the blob store; a changed blob is
+/// stored again and the event pointed at the redacted copy
+fn redact_event(event: &mut serde_json::Value, redactors: &[Redactor], counts: &mut BTreeMap<String, usize>) -> Result<bool, String> {
+    let Some(fields) = event.as_object_mut() else {
+        return Ok(redact_value(event, redactors, counts));
+    };
+    let mut changed = false;
+    for value in fields.values_mut() {
+        let Some(hash) = blob_store::blob_ref(value).map(str::to_string) else {
+            changed |= redact_value(value, redactors, counts);
+            continue;
+        };
+        let mut content = blob_store::get(&hash)?;
+        if redact_value(&mut content, redactors, counts) {
+            let serialized = serde_json::to_vec(&content).map_err(|e| format!("Failed to serialize event: {}", e))?;
+            let (hash, _) = blob_store::put_bytes(&serialized)?;
+            *value = blob_store::make_ref(&hash);
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+fn backup_path(thread_id: &str) -> Result<PathBuf, String> {
+    Ok(filesystem::get_threads_dir()?.join(format!("{}.jsonl.bak", thread_id)))
+}
+
+/// Replace matches of `patterns` in every event of a thread, the blueprint line
+/// included, with placeholders such as `[REDACTED:email]`. The original is kept as
+/// `<thread_id>.jsonl.bak` in the threads directory. Unparseable lines are kept as
+/// they are. Callers must keep appends to the thread out while this runs (blocking).
+pub fn redact_thread(thread_id: &str, patterns: &[String]) -> Result<RedactionReport, String> {
+    let redactors = compile(patterns)?;
+    let path = filesystem::get_thread_path(thread_id)?;
+    let content = std::fs::read(&path).map_err(|_| format!("Thread {} not found", thread_id))?;
+
+    let mut report = RedactionReport::default();
+    let mut output = Vec::with_capacity(content.len());
+    for line in content.split(|&b| b == b'\n') {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let Ok(mut event) = serde_json::from_slice::<serde_json::Value>(line) else {
+            output.extend_from_slice(line);
+            output.push(b'\n');
+            continue;
+        };
+        report.events += 1;
+        if redact_event(&mut event, &redactors, &mut report.replacements)? {
+            report.events_modified += 1;
+        }
+        filesystem::serialize_event_line(&event, &mut output)?;
+    }
+
+    if report.events_modified == 0 {
+        return Ok(report);
+    }
+
+    let backup = backup_path(thread_id)?;
+    if !backup.exists() {
+        std::fs::copy(&path, &backup).map_err(|e| format!("Failed to back up thread {}: {}", thread_id, e))?;
+    }
+    report.backup_path = Some(backup.to_string_lossy().to_string());
+    filesystem::replace_thread_file(&path, &output)?;
+
+    log::info!(
+        "Redacted thread {}: {} of {} events modified, {} matches replaced",
+        thread_id,
+        report.events_modified,
+        report.events,
+        report.replacements.values().sum::<usize>()
+    );
+    Ok(report)
+}
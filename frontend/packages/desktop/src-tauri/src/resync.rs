@@ -0,0 +1,83 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::python_backend::PythonBackend;
+use crate::terminal_backend::{TerminalBackend, TerminalSnapshot};
+
+/// How long the backend health probe may take before it counts as unhealthy
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Python backend state as seen from the shell
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStatus {
+    pub running: bool,
+    pub url: Option<String>,
+    pub healthy: bool,
+}
+
+/// Everything a freshly loaded page needs to rebuild its view of the Rust side
+#[derive(Debug, Clone, Serialize)]
+pub struct ResyncState {
+    pub terminals: Vec<TerminalSnapshot>,
+    pub backend: BackendStatus,
+    /// Backend streams still in flight
+    pub active_streams: Vec<String>,
+    /// Threads with live appends that haven't been closed
+    pub open_threads: Vec<String>,
+    /// Last event bus sequence for this window; subscribe with this as `since_seq`
+    /// to receive only what happens after this snapshot
+    pub event_seq: u64,
+}
+
+/// Collect the current state for `window_label`
+pub async fn collect(app: &AppHandle, window_label: &str) -> ResyncState {
+    let bus = app.state::<Arc<EventBus>>();
+
+    // Read the sequence first so nothing published while collecting is skipped
+    let event_seq = bus.last_seq(window_label);
+
+    let terminals = match app.try_state::<Arc<TerminalBackend>>() {
+        Some(terminals) => terminals.snapshot().await,
+        None => Vec::new(),
+    };
+
+    let backend = match app.try_state::<Arc<PythonBackend>>() {
+        Some(backend) => {
+            let url = backend.base_url();
+            let healthy = reqwest::Client::new()
+                .get(&url)
+                .timeout(HEALTH_TIMEOUT)
+                .send()
+                .await
+                .map(|r| r.status().is_success())
+                .unwrap_or(false);
+            BackendStatus {
+                running: true,
+                url: Some(url),
+                healthy,
+            }
+        }
+        None => BackendStatus {
+            running: false,
+            url: None,
+            healthy: false,
+        },
+    };
+
+    let open_threads = app
+        .try_state::<Arc<AppendBuffer>>()
+        .map(|appends| appends.open_threads())
+        .unwrap_or_default();
+
+    ResyncState {
+        terminals,
+        backend,
+        active_streams: bus.active_streams(),
+        open_threads,
+        event_seq,
+    }
+}
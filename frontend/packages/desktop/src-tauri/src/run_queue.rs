@@ -0,0 +1,479 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::python_backend::PythonBackend;
+use crate::run_recovery::{InterruptedRun, RunTracker};
+use crate::thread_compression;
+use crate::usage::UsageLedger;
+
+/// How long a due run waits for the backend to come up before failing
+const BACKEND_WAIT: Duration = Duration::from_secs(60);
+
+/// Runs starting later than this after their time count as caught up
+const CATCH_UP_GRACE: chrono::TimeDelta = chrono::TimeDelta::seconds(60);
+
+/// Finished runs are dropped from the queue file after this long
+const FINISHED_RETENTION: chrono::TimeDelta = chrono::TimeDelta::days(30);
+
+/// Lifecycle of a queued run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A prompt scheduled to be sent to a thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRun {
+    pub id: String,
+    pub thread_id: String,
+    pub prompt: String,
+    /// RFC 3339
+    pub run_at: String,
+    pub status: RunStatus,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub error: Option<String>,
+    /// Ran late because the app was closed (or busy) when it was due
+    #[serde(default)]
+    pub caught_up: bool,
+}
+
+impl QueuedRun {
+    fn due_at(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(&self.run_at)
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now())
+    }
+}
+
+fn get_queue_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("run_queue.json"))
+}
+
+/// Durable queue of scheduled agent runs, executed one at a time
+pub struct RunQueue {
+    runs: Mutex<Vec<QueuedRun>>,
+    wake: Notify,
+}
+
+impl RunQueue {
+    /// Load the saved queue. Runs interrupted by the app closing go back to pending
+    /// so they are retried along with any that came due while it was closed.
+    pub fn load() -> Self {
+        let mut runs: Vec<QueuedRun> = get_queue_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(Vec::new());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read run queue: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse run queue: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                Vec::new()
+            });
+
+        let cutoff = (chrono::Utc::now() - FINISHED_RETENTION).to_rfc3339();
+        runs.retain(|run| {
+            matches!(run.status, RunStatus::Pending | RunStatus::Running)
+                || run.finished_at.as_ref().is_none_or(|finished| *finished >= cutoff)
+        });
+        for run in runs.iter_mut().filter(|run| run.status == RunStatus::Running) {
+            log::warn!("Queued run {} was interrupted; retrying", run.id);
+            run.status = RunStatus::Pending;
+            run.started_at = None;
+        }
+
+        let pending = runs.iter().filter(|run| run.status == RunStatus::Pending).count();
+        log::info!("Loaded run queue with {} pending run(s)", pending);
+
+        Self {
+            runs: Mutex::new(runs),
+            wake: Notify::new(),
+        }
+    }
+
+    fn save(&self, runs: &[QueuedRun]) -> Result<(), String> {
+        let path = get_queue_path()?;
+        let content = serde_json::to_string_pretty(runs).map_err(|e| format!("Failed to serialize run queue: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write run queue: {}", e))
+    }
+
+    /// Schedule `prompt` for `thread_id` at `run_at` (RFC 3339), or as soon as possible
+    pub fn enqueue(&self, thread_id: String, prompt: String, run_at: Option<String>) -> Result<QueuedRun, String> {
+        if !thread_compression::thread_exists(&thread_id)? {
+            return Err(format!("Thread {} not found", thread_id));
+        }
+        if prompt.trim().is_empty() {
+            return Err("Prompt is empty".to_string());
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let run_at = match run_at {
+            Some(run_at) => chrono::DateTime::parse_from_rfc3339(&run_at)
+                .map_err(|e| format!("Invalid run_at {}: {}", run_at, e))?
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339(),
+            None => now.clone(),
+        };
+
+        let run = QueuedRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            thread_id,
+            prompt,
+            run_at,
+            status: RunStatus::Pending,
+            created_at: now,
+            started_at: None,
+            finished_at: None,
+            error: None,
+            caught_up: false,
+        };
+
+        let mut runs = self.runs.lock().unwrap();
+        runs.push(run.clone());
+        self.save(&runs)?;
+        drop(runs);
+
+        log::info!("Queued run {} for thread {} at {}", run.id, run.thread_id, run.run_at);
+        self.wake.notify_one();
+        Ok(run)
+    }
+
+    /// All runs, soonest first
+    pub fn list(&self) -> Vec<QueuedRun> {
+        let mut runs = self.runs.lock().unwrap().clone();
+        runs.sort_by_key(|run| run.due_at());
+        runs
+    }
+
+    /// Cancel a run that hasn't started
+    pub fn cancel(&self, run_id: &str) -> Result<(), String> {
+        let mut runs = self.runs.lock().unwrap();
+        let run = runs
+            .iter_mut()
+            .find(|run| run.id == run_id)
+            .ok_or_else(|| format!("Run {} not found", run_id))?;
+        if run.status != RunStatus::Pending {
+            return Err(format!("Run {} is {:?} and can't be cancelled", run_id, run.status));
+        }
+        run.status = RunStatus::Cancelled;
+        run.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        self.save(&runs)?;
+        drop(runs);
+
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// When the next pending run is due
+    fn next_due(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.runs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|run| run.status == RunStatus::Pending)
+            .map(QueuedRun::due_at)
+            .min()
+    }
+
+    /// Mark the earliest due run as running and return it
+    fn take_due(&self) -> Option<QueuedRun> {
+        let now = chrono::Utc::now();
+        let mut runs = self.runs.lock().unwrap();
+        let run = runs
+            .iter_mut()
+            .filter(|run| run.status == RunStatus::Pending && run.due_at() <= now)
+            .min_by_key(|run| run.due_at())?;
+
+        run.status = RunStatus::Running;
+        run.started_at = Some(now.to_rfc3339());
+        run.caught_up = now - run.due_at() > CATCH_UP_GRACE;
+        let run = run.clone();
+
+        if let Err(e) = self.save(&runs) {
+            log::error!("{}", e);
+        }
+        Some(run)
+    }
+
+    fn finish(&self, run_id: &str, result: &Result<(), String>) {
+        let mut runs = self.runs.lock().unwrap();
+        if let Some(run) = runs.iter_mut().find(|run| run.id == run_id) {
+            run.status = if result.is_ok() { RunStatus::Completed } else { RunStatus::Failed };
+            run.error = result.as_ref().err().cloned();
+            run.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        if let Err(e) = self.save(&runs) {
+            log::error!("{}", e);
+        }
+    }
+
+    /// Execute runs as they come due, for as long as the app runs
+    pub fn start(self: &Arc<Self>, app_handle: AppHandle) {
+        let queue = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let Some(due) = queue.next_due() else {
+                    queue.wake.notified().await;
+                    continue;
+                };
+
+                if let Ok(wait) = (due - chrono::Utc::now()).to_std() {
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = queue.wake.notified() => continue,
+                    }
+                }
+
+                let Some(run) = queue.take_due() else { continue };
+                log::info!("Starting queued run {} for thread {}", run.id, run.thread_id);
+                publish(&app_handle, "queued-run-started", &run);
+
+                let result = execute(&app_handle, &run).await;
+                if let Err(e) = &result {
+                    log::error!("Queued run {} failed: {}", run.id, e);
+                }
+                queue.finish(&run.id, &result);
+
+                if let Some(run) = queue.list().into_iter().find(|r| r.id == run.id) {
+                    publish(&app_handle, "queued-run-finished", &run);
+                }
+            }
+        });
+    }
+}
+
+fn publish(app_handle: &AppHandle, topic: &str, run: &QueuedRun) {
+    if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+        bus.publish(topic, run);
+    }
+}
+
+/// Wait for the backend to be running and healthy, returning its URL
+pub async fn wait_for_backend(app_handle: &AppHandle) -> Result<String, String> {
+    let client = reqwest::Client::new();
+    let deadline = tokio::time::Instant::now() + BACKEND_WAIT;
+
+    loop {
+        if let Some(url) = crate::mock_backend::url(app_handle) {
+            return Ok(url);
+        }
+        if let Some(backend) = app_handle.try_state::<Arc<PythonBackend>>() {
+            let url = backend.base_url();
+            let healthy = client
+                .get(&url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+                .is_ok_and(|r| r.status().is_success());
+            if healthy {
+                return Ok(url);
+            }
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!("Backend not available after {}s", BACKEND_WAIT.as_secs()));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Folds a backend event stream into the events the thread file keeps: streamed
+/// text and reasoning become their `-complete` events, transport-only events
+/// (message boundaries, deltas) are dropped, and the rest is kept as is
+#[derive(Default)]
+struct EventRecorder {
+    text: HashMap<String, String>,
+    reasoning: HashMap<String, String>,
+    events: Vec<serde_json::Value>,
+    saw_error: bool,
+}
+
+impl EventRecorder {
+    /// Whether any tool produced output, so re-running would repeat its effects
+    fn tools_ran(&self) -> bool {
+        self.events.iter().any(|event| {
+            event
+                .get("type")
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| t.starts_with("tool-output"))
+        })
+    }
+
+    fn push(&mut self, mut event: serde_json::Value) {
+        let Some(event_type) = event.get("type").and_then(|t| t.as_str()).map(str::to_string) else { return };
+        let id = event.get("id").and_then(|i| i.as_str()).unwrap_or_default().to_string();
+        let delta = || event.get("delta").and_then(|d| d.as_str()).unwrap_or_default().to_string();
+
+        let complete = match event_type.as_str() {
+            "text-start" => {
+                self.text.insert(id, String::new());
+                return;
+            }
+            "text-delta" => {
+                self.text.entry(id).or_default().push_str(&delta());
+                return;
+            }
+            "text-end" => serde_json::json!({ "type": "text-complete", "id": id, "content": self.text.remove(&id).unwrap_or_default() }),
+            "reasoning-start" => {
+                self.reasoning.insert(id, String::new());
+                return;
+            }
+            "reasoning-delta" => {
+                self.reasoning.entry(id).or_default().push_str(&delta());
+                return;
+            }
+            "reasoning-end" => serde_json::json!({ "type": "reasoning-complete", "id": id, "content": self.reasoning.remove(&id).unwrap_or_default() }),
+            "start" | "finish" | "abort" | "message-metadata" | "tool-input-start" | "tool-input-delta"
+            | "tool-output-start" | "tool-output-delta" => return,
+            "error" => {
+                self.saw_error = true;
+                event.take()
+            }
+            _ => event.take(),
+        };
+
+        let mut complete = complete;
+        if let Some(object) = complete.as_object_mut() {
+            object
+                .entry("timestamp")
+                .or_insert_with(|| serde_json::Value::String(chrono::Utc::now().to_rfc3339()));
+        }
+        self.events.push(complete);
+    }
+}
+
+/// Times a queued run cut off by a backend crash is re-issued
+const MAX_REISSUES: u32 = 2;
+
+/// POST a run to the backend and record what it streams back. The second value is
+/// why the stream broke off, if it did.
+async fn stream_run(backend_url: &str, body: &serde_json::Value) -> Result<(EventRecorder, Option<String>), String> {
+    let mut request = reqwest::Client::new().post(format!("{}/stream", backend_url)).json(body);
+    for (name, value) in crate::telemetry::trace_headers() {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to backend: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Backend returned {}", response.status()));
+    }
+
+    let mut recorder = EventRecorder::default();
+    let mut bytes = response.bytes_stream();
+    let mut pending = String::new();
+    let mut interrupted = None;
+
+    while let Some(chunk) = bytes.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                interrupted = Some(format!("Backend stream interrupted: {}", e));
+                break;
+            }
+        };
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(newline) = pending.find('\n') {
+            let line: String = pending.drain(..=newline).collect();
+            let Some(data) = line.trim_end().strip_prefix("data:") else { continue };
+            let data = data.trim_start();
+            if data == "[DONE]" {
+                continue;
+            }
+            if let Ok(event) = serde_json::from_str::<serde_json::Value>(data) {
+                recorder.push(event);
+            }
+        }
+    }
+
+    Ok((recorder, interrupted))
+}
+
+/// Send a queued prompt to the backend with the thread's history and append what
+/// comes back to the thread. A run cut off by the backend crashing is recorded as
+/// interrupted and re-issued once the backend is back, unless a tool already ran.
+async fn execute(app_handle: &AppHandle, run: &QueuedRun) -> Result<(), String> {
+    let appends = app_handle.state::<Arc<AppendBuffer>>();
+
+    appends.flush(&run.thread_id).await?;
+    let history = filesystem::effective_thread_protocol(filesystem::load_thread(run.thread_id.clone()).await?);
+
+    let body = serde_json::json!({
+        "thread_protocol": history,
+        "user_input": {
+            "kind": "message",
+            "content": run.prompt,
+        },
+    });
+
+    let mut reissues = 0;
+    let (recorder, interrupted) = loop {
+        let backend_url = wait_for_backend(app_handle).await?;
+        let (recorder, interrupted) = stream_run(&backend_url, &body).await?;
+        let Some(reason) = interrupted else { break (recorder, None) };
+
+        let resumable = !recorder.tools_ran();
+        if let Some(tracker) = app_handle.try_state::<Arc<RunTracker>>() {
+            tracker.record(InterruptedRun {
+                run_id: run.id.clone(),
+                thread_id: run.thread_id.clone(),
+                reason: reason.clone(),
+                interrupted_at: chrono::Utc::now().to_rfc3339(),
+                resumable,
+            });
+        }
+        if !resumable || reissues >= MAX_REISSUES {
+            break (recorder, Some(reason));
+        }
+        reissues += 1;
+        log::warn!("Queued run {} was interrupted ({}); re-issuing", run.id, reason);
+    };
+
+    // Keep whatever arrived, with the prompt first if the backend didn't echo it
+    let mut events = recorder.events;
+    if !events.iter().any(|e| e.get("type").and_then(|t| t.as_str()) == Some("user-message")) {
+        events.insert(
+            0,
+            serde_json::json!({
+                "type": "user-message",
+                "content": run.prompt,
+                "timestamp": run.started_at.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            }),
+        );
+    }
+
+    appends.append(&run.thread_id, &events).await?;
+    if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+        bus.publish("thread-changed", serde_json::json!({ "thread_id": run.thread_id, "change": "appended" }));
+    }
+    if let Some(usage) = app_handle.try_state::<Arc<UsageLedger>>() {
+        usage.record(&run.thread_id, &events);
+    }
+
+    if let Some(e) = interrupted {
+        return Err(e);
+    }
+    if recorder.saw_error {
+        return Err("Run finished with an error event".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+
+/// Event type recording a run cut off by the backend going away
+pub const RUN_INTERRUPTED_EVENT: &str = "data-run-interrupted";
+
+/// Interrupted runs kept for resuming; the oldest are forgotten first
+const MAX_INTERRUPTED: usize = 50;
+
+/// Stream events showing a tool has run. Re-issuing a run after one of these
+/// would repeat the tool's side effects, so the run is no longer resumable.
+const TOOL_MARKERS: [&str; 2] = ["\"tool-output-available\"", "\"tool-output-error\""];
+
+/// A run the backend stopped answering mid-stream
+#[derive(Debug, Clone, Serialize)]
+pub struct InterruptedRun {
+    pub run_id: String,
+    pub thread_id: String,
+    pub reason: String,
+    pub interrupted_at: String,
+    /// Whether re-issuing the run is safe, i.e. no tool ran before the interruption
+    pub resumable: bool,
+}
+
+/// A `/stream` request in flight
+struct ActiveRun {
+    thread_id: String,
+    /// The original request, replayed to re-issue the run
+    body: Vec<u8>,
+    tools_ran: bool,
+}
+
+/// Agent runs streaming from the backend. When a stream breaks because the backend
+/// crashed or was restarted, the thread gets a `data-run-interrupted` event and
+/// the frontend a `run-interrupted` notification, and the run's request is kept
+/// so it can be re-issued once the backend is back. The backend is stateless, so
+/// re-issuing replays the turn from the thread as it was when the run started.
+pub struct RunTracker {
+    app_handle: tauri::AppHandle,
+    active: Mutex<HashMap<String, ActiveRun>>,
+    interrupted: Mutex<Vec<(InterruptedRun, Vec<u8>)>>,
+}
+
+/// Thread a `/stream` request body is for, from its thread protocol header
+pub fn stream_thread_id(body: &[u8]) -> Option<String> {
+    let request: serde_json::Value = serde_json::from_slice(body).ok()?;
+    request
+        .pointer("/thread_protocol/0/thread_id")
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+}
+
+impl RunTracker {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self {
+            app_handle,
+            active: Mutex::new(HashMap::new()),
+            interrupted: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Start tracking a `/stream` request. Returns its run id, or `None` if the
+    /// body doesn't name a thread.
+    pub fn begin(&self, body: &[u8]) -> Option<String> {
+        let thread_id = stream_thread_id(body)?;
+        let run_id = uuid::Uuid::new_v4().to_string();
+        self.active.lock().unwrap().insert(
+            run_id.clone(),
+            ActiveRun {
+                thread_id,
+                body: body.to_vec(),
+                tools_ran: false,
+            },
+        );
+        Some(run_id)
+    }
+
+    /// Look at a chunk of the run's stream, noting whether tools have run
+    pub fn observe(&self, run_id: &str, chunk: &[u8]) {
+        let mut active = self.active.lock().unwrap();
+        let Some(run) = active.get_mut(run_id) else { return };
+        if !run.tools_ran {
+            let text = String::from_utf8_lossy(chunk);
+            run.tools_ran = TOOL_MARKERS.iter().any(|marker| text.contains(marker));
+        }
+    }
+
+    /// The run's stream ended, normally or because the client went away
+    pub fn finish(&self, run_id: &str) {
+        self.active.lock().unwrap().remove(run_id);
+    }
+
+    /// The run's stream broke. Records the interruption in the thread and tells
+    /// the frontend, keeping the request so the run can be re-issued.
+    pub fn interrupt(&self, run_id: &str, reason: String) {
+        let Some(run) = self.active.lock().unwrap().remove(run_id) else { return };
+        let interrupted = InterruptedRun {
+            run_id: run_id.to_string(),
+            thread_id: run.thread_id,
+            reason,
+            interrupted_at: chrono::Utc::now().to_rfc3339(),
+            resumable: !run.tools_ran,
+        };
+        log::warn!(
+            "Run {} for thread {} was interrupted: {}",
+            interrupted.run_id,
+            interrupted.thread_id,
+            interrupted.reason
+        );
+
+        {
+            let mut runs = self.interrupted.lock().unwrap();
+            runs.push((interrupted.clone(), run.body));
+            if runs.len() > MAX_INTERRUPTED {
+                runs.remove(0);
+            }
+        }
+        self.record(interrupted);
+    }
+
+    /// Append the interruption to the thread and publish `run-interrupted`
+    pub fn record(&self, interrupted: InterruptedRun) {
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let event = serde_json::json!({
+                "type": RUN_INTERRUPTED_EVENT,
+                "data": {
+                    "run_id": interrupted.run_id,
+                    "reason": interrupted.reason,
+                    "resumable": interrupted.resumable,
+                },
+                "timestamp": interrupted.interrupted_at,
+            });
+            let appends = app_handle.state::<Arc<AppendBuffer>>();
+            if let Err(e) = appends.append(&interrupted.thread_id, &[event]).await {
+                log::error!("Failed to record interrupted run {}: {}", interrupted.run_id, e);
+            }
+
+            if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+                bus.publish(
+                    "thread-changed",
+                    serde_json::json!({ "thread_id": interrupted.thread_id, "change": "appended" }),
+                );
+                bus.publish("run-interrupted", &interrupted);
+            }
+        });
+    }
+
+    /// Interrupted runs, most recent first
+    pub fn interrupted(&self) -> Vec<InterruptedRun> {
+        self.interrupted.lock().unwrap().iter().rev().map(|(run, _)| run.clone()).collect()
+    }
+
+    /// Remove an interrupted run, returning its original request for re-issuing
+    pub fn take_resumable(&self, run_id: &str) -> Result<Vec<u8>, String> {
+        let mut runs = self.interrupted.lock().unwrap();
+        let index = runs
+            .iter()
+            .position(|(run, _)| run.run_id == run_id)
+            .ok_or_else(|| format!("No interrupted run {}", run_id))?;
+        if !runs[index].0.resumable {
+            return Err(format!("Run {} already ran tools and can't be re-issued", run_id));
+        }
+        Ok(runs.remove(index).1)
+    }
+
+    /// Forget an interrupted run without re-issuing it
+    pub fn dismiss(&self, run_id: &str) {
+        self.interrupted.lock().unwrap().retain(|(run, _)| run.run_id != run_id);
+    }
+}
+
+/// Ends a tracked run when its stream is dropped, unless it was interrupted
+pub struct RunGuard {
+    tracker: Arc<RunTracker>,
+    run_id: String,
+    interrupted: bool,
+}
+
+impl RunGuard {
+    pub fn new(tracker: Arc<RunTracker>, run_id: String) -> Self {
+        Self {
+            tracker,
+            run_id,
+            interrupted: false,
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn observe(&self, chunk: &[u8]) {
+        self.tracker.observe(&self.run_id, chunk);
+    }
+
+    pub fn interrupt(&mut self, reason: String) {
+        if !self.interrupted {
+            self.interrupted = true;
+            self.tracker.interrupt(&self.run_id, reason);
+        }
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if !self.interrupted {
+            self.tracker.finish(&self.run_id);
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::{Datelike, Duration as TimeDelta, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::hooks::HookRunner;
+use crate::notifications::Notifier;
+use crate::run_queue::{QueuedRun, RunQueue, RunStatus};
+use crate::settings::SettingsStore;
+use crate::webhooks::{self, WebhookManager};
+
+/// How far ahead `next_after` looks before deciding a schedule never fires, e.g. `0 0 30 2 *`
+const SEARCH_YEARS: i32 = 5;
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A blueprint run on a schedule, each time in a new thread
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    pub blueprint_id: String,
+    /// First message of each run
+    pub prompt: String,
+    /// `minute hour day-of-month month day-of-week` in local time, or `@daily` and the like
+    pub cron: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl ScheduledJob {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err(format!("Scheduled job {} has no id", self.name));
+        }
+        filesystem::get_blueprint_path(&self.blueprint_id)?;
+        if self.prompt.trim().is_empty() {
+            return Err(format!("Scheduled job {} has no prompt", self.name));
+        }
+        Schedule::parse(&self.cron).map_err(|e| format!("Scheduled job {}: {}", self.name, e))?;
+        Ok(())
+    }
+}
+
+/// Check a job list from settings: each job and unique ids
+pub fn validate(jobs: &[ScheduledJob]) -> Result<(), String> {
+    for (i, job) in jobs.iter().enumerate() {
+        job.validate()?;
+        if jobs[..i].iter().any(|other| other.id == job.id) {
+            return Err(format!("Duplicate scheduled job id: {}", job.id));
+        }
+    }
+    Ok(())
+}
+
+/// A parsed cron expression, one bit per allowed value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// Both day fields were restricted, so either matching is enough, as in cron
+    either_day: bool,
+}
+
+/// Bits for one field: lists, ranges, steps and `*`, with names where given
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_ascii_lowercase();
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(min + index as u32);
+        }
+        text.parse::<u32>().map_err(|_| format!("Invalid value {} in {}", text, field))
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("Invalid step in {}", field))?;
+                if step == 0 {
+                    return Err(format!("Step can't be 0 in {}", field));
+                }
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                None => {
+                    let start = value(range)?;
+                    (start, if step.is_some() { max } else { start })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("{} is out of range {}-{}", part, min, max));
+        }
+        for n in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("Expected 5 fields in cron expression: {}", expression));
+        };
+
+        // Sunday is 0 or 7
+        let weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])? as u32,
+            days: parse_field(day, 1, 31, &[])? as u32,
+            months: parse_field(month, 1, 12, &MONTH_NAMES)? as u16,
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            either_day: day != "*" && weekday != "*",
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// Whether the schedule fires in the minute starting at `time`
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        self.months & (1 << time.month()) != 0
+            && self.matches_day(time.date())
+            && self.hours & (1 << time.hour()) != 0
+            && self.minutes & (1 << time.minute()) != 0
+    }
+
+    /// The first time after `after` the schedule fires, skipping times a DST change
+    /// leaves out
+    pub fn next_after(&self, after: chrono::DateTime<Local>) -> Option<chrono::DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let mut time = start;
+        while time.year() <= start.year() + SEARCH_YEARS {
+            if self.months & (1 << time.month()) == 0 {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + TimeDelta::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += TimeDelta::minutes(1);
+            } else {
+                match Local.from_local_datetime(&time).earliest() {
+                    Some(local) => return Some(local),
+                    None => time += TimeDelta::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The latest run of a job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRun {
+    pub thread_id: String,
+    pub run_id: String,
+    pub started_at: String,
+    pub status: RunStatus,
+    pub finished_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A job as `list_scheduled_jobs` reports it
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledJobStatus {
+    #[serde(flatten)]
+    pub job: ScheduledJob,
+    pub next_run_at: Option<String>,
+    /// Since the app started
+    pub last_run: Option<JobRun>,
+}
+
+/// Runs the scheduled jobs from settings while the app is open, tray included.
+/// Each run creates a thread from the job's blueprint and hands the prompt to the
+/// run queue, which streams it through the backend. Runs missed while the app was
+/// closed or the machine asleep are skipped.
+pub struct Scheduler {
+    app_handle: AppHandle,
+    last_runs: Mutex<HashMap<String, JobRun>>,
+    /// Job of each queued run still going, by run id
+    running: Mutex<HashMap<String, String>>,
+}
+
+impl Scheduler {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            last_runs: Mutex::new(HashMap::new()),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn jobs(&self) -> Vec<ScheduledJob> {
+        self.app_handle
+            .try_state::<Arc<SettingsStore>>()
+            .map(|settings| settings.get().scheduled_jobs)
+            .unwrap_or_default()
+    }
+
+    pub fn list(&self) -> Vec<ScheduledJobStatus> {
+        let last_runs = self.last_runs.lock().unwrap();
+        self.jobs()
+            .into_iter()
+            .map(|job| ScheduledJobStatus {
+                next_run_at: Schedule::parse(&job.cron)
+                    .ok()
+                    .filter(|_| job.enabled)
+                    .and_then(|schedule| schedule.next_after(Local::now()))
+                    .map(|time| time.to_rfc3339()),
+                last_run: last_runs.get(&job.id).cloned(),
+                job,
+            })
+            .collect()
+    }
+
+    /// Start a run of job `job_id` now, whether or not it's enabled
+    pub async fn run_now(&self, job_id: &str) -> Result<JobRun, String> {
+        let job = self
+            .jobs()
+            .into_iter()
+            .find(|job| job.id == job_id)
+            .ok_or_else(|| format!("Scheduled job {} not found", job_id))?;
+        self.run(&job).await
+    }
+
+    /// Create the job's thread and queue its prompt
+    async fn run(&self, job: &ScheduledJob) -> Result<JobRun, String> {
+        let path = filesystem::get_blueprint_path(&job.blueprint_id)?;
+        let blueprint_json = filesystem::read_blueprint(path.to_string_lossy().to_string()).await?;
+        let header = crate::provenance::header(&self.app_handle, &blueprint_json).await;
+        let thread_id = filesystem::create_thread_with_header(blueprint_json, &header).await?;
+
+        if let Some(bus) = self.app_handle.try_state::<Arc<EventBus>>() {
+            bus.publish("thread-changed", serde_json::json!({ "thread_id": thread_id, "change": "created" }));
+        }
+        if let Some(webhooks) = self.app_handle.try_state::<Arc<WebhookManager>>() {
+            webhooks.dispatch(webhooks::EVENT_THREAD_CREATED, serde_json::json!({ "thread_id": thread_id }));
+        }
+        if let Some(hooks) = self.app_handle.try_state::<Arc<HookRunner>>() {
+            hooks.dispatch(webhooks::EVENT_THREAD_CREATED, serde_json::json!({ "thread_id": thread_id }));
+        }
+
+        let queue = self
+            .app_handle
+            .try_state::<Arc<RunQueue>>()
+            .ok_or("The run queue isn't available")?;
+        let queued = queue.enqueue(thread_id.clone(), job.prompt.clone(), None)?;
+
+        let run = JobRun {
+            thread_id,
+            run_id: queued.id.clone(),
+            started_at: queued.created_at,
+            status: RunStatus::Pending,
+            finished_at: None,
+            error: None,
+        };
+        self.running.lock().unwrap().insert(queued.id, job.id.clone());
+        self.last_runs.lock().unwrap().insert(job.id.clone(), run.clone());
+        log::info!("Scheduled job {} started in thread {}", job.name, run.thread_id);
+        if let Some(bus) = self.app_handle.try_state::<Arc<EventBus>>() {
+            bus.publish(
+                "scheduled-job-started",
+                serde_json::json!({ "job_id": job.id, "name": job.name, "thread_id": run.thread_id }),
+            );
+        }
+        Ok(run)
+    }
+
+    /// A queued run finished: if it was one of ours, record it and tell the user
+    fn finished(&self, run: &QueuedRun) {
+        let Some(job_id) = self.running.lock().unwrap().remove(&run.id) else { return };
+        if let Some(last) = self.last_runs.lock().unwrap().get_mut(&job_id).filter(|last| last.run_id == run.id) {
+            last.status = run.status;
+            last.finished_at = run.finished_at.clone();
+            last.error = run.error.clone();
+        }
+
+        let name = self
+            .jobs()
+            .into_iter()
+            .find(|job| job.id == job_id)
+            .map(|job| job.name)
+            .unwrap_or_else(|| job_id.clone());
+        if let Some(bus) = self.app_handle.try_state::<Arc<EventBus>>() {
+            bus.publish(
+                "scheduled-job-finished",
+                serde_json::json!({
+                    "job_id": job_id,
+                    "name": name,
+                    "thread_id": run.thread_id,
+                    "status": run.status,
+                    "error": run.error,
+                }),
+            );
+        }
+
+        let enabled = self
+            .app_handle
+            .try_state::<Arc<SettingsStore>>()
+            .is_some_and(|settings| settings.get().notifications.enabled);
+        if let (true, Some(notifier)) = (enabled, self.app_handle.try_state::<Arc<Notifier>>()) {
+            let (title, body) = match &run.error {
+                None => ("Scheduled run finished", format!("{} is ready", name)),
+                Some(e) => ("Scheduled run failed", format!("{}: {}", name, e)),
+            };
+            if let Err(e) = notifier.notify(title, &body, Some(run.thread_id.clone())) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+
+    /// Check the jobs at the start of every minute, and follow their runs
+    pub fn start(self: &Arc<Self>, bus: &EventBus) {
+        let scheduler = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut last_minute = None;
+            loop {
+                let now = Local::now();
+                let to_next_minute = 60 - u64::from(now.second()).min(59);
+                tokio::time::sleep(Duration::from_secs(to_next_minute)).await;
+
+                let Some(minute) = Local::now().naive_local().with_second(0).and_then(|t| t.with_nanosecond(0))
+                else {
+                    continue;
+                };
+                if last_minute == Some(minute) {
+                    continue;
+                }
+                last_minute = Some(minute);
+
+                for job in scheduler.jobs().into_iter().filter(|job| job.enabled) {
+                    match Schedule::parse(&job.cron) {
+                        Ok(schedule) if schedule.matches(&minute) => {}
+                        Ok(_) => continue,
+                        Err(e) => {
+                            log::warn!("Scheduled job {}: {}", job.name, e);
+                            continue;
+                        }
+                    }
+                    let scheduler = scheduler.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = scheduler.run(&job).await {
+                            log::error!("Scheduled job {} failed to start: {}", job.name, e);
+                        }
+                    });
+                }
+            }
+        });
+
+        let mut receiver = bus.listen();
+        let scheduler = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.topic == "queued-run-finished" => {
+                        let Some(scheduler) = scheduler.upgrade() else { break };
+                        if let Ok(run) = serde_json::from_value::<QueuedRun>(event.payload) {
+                            scheduler.finished(&run);
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::filesystem;
+use crate::thread_compression;
+
+/// Days an untouched scratch directory is kept by default (`CHIMERA_SCRATCH_RETENTION_DAYS`)
+const DEFAULT_RETENTION_DAYS: u64 = 7;
+
+/// A thread's temporary workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct ScratchDir {
+    pub thread_id: String,
+    pub path: String,
+    /// Total size of the files inside
+    pub size: u64,
+    /// Most recent modification of anything inside
+    pub last_used_at: String,
+}
+
+/// Get the directory holding every thread's scratch workspace
+pub fn get_scratch_root() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("scratch"))
+}
+
+fn scratch_path(thread_id: &str) -> Result<PathBuf, String> {
+    // Validates the id the same way as thread files
+    filesystem::get_thread_path(thread_id)?;
+    Ok(get_scratch_root()?.join(thread_id))
+}
+
+/// Whether a resolved path lies inside the scratch root, where agents may always
+/// read and write
+pub fn contains(resolved: &Path) -> bool {
+    get_scratch_root()
+        .ok()
+        .and_then(|root| root.canonicalize().ok())
+        .is_some_and(|root| resolved.starts_with(root))
+}
+
+/// Total size and newest modification time under `path`
+fn usage(path: &Path) -> (u64, SystemTime) {
+    let mut size = 0;
+    let mut newest = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            // symlink_metadata so links out of the workspace aren't followed
+            let Ok(metadata) = entry.path().symlink_metadata() else { continue };
+            if let Ok(modified) = metadata.modified() {
+                newest = newest.max(modified);
+            }
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+
+    (size, newest)
+}
+
+fn describe(thread_id: String, path: PathBuf) -> ScratchDir {
+    let (size, newest) = usage(&path);
+    ScratchDir {
+        thread_id,
+        path: path.to_string_lossy().into_owned(),
+        size,
+        last_used_at: chrono::DateTime::<chrono::Utc>::from(newest).to_rfc3339(),
+    }
+}
+
+/// Create (or reuse) the scratch directory for a thread (blocking)
+pub fn create(thread_id: &str) -> Result<ScratchDir, String> {
+    if !thread_compression::thread_exists(thread_id)? {
+        return Err(format!("Thread {} not found", thread_id));
+    }
+
+    let path = scratch_path(thread_id)?;
+    std::fs::create_dir_all(&path).map_err(|e| format!("Failed to create scratch directory: {}", e))?;
+
+    // Touching the directory counts as use, so an active thread's workspace survives cleanup
+    std::fs::File::open(&path)
+        .and_then(|dir| dir.set_modified(SystemTime::now()))
+        .unwrap_or_else(|e| log::debug!("Failed to touch {}: {}", path.display(), e));
+
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve scratch directory: {}", e))?;
+    Ok(describe(thread_id.to_string(), path))
+}
+
+/// Every scratch directory (blocking)
+pub fn list() -> Result<Vec<ScratchDir>, String> {
+    let root = get_scratch_root()?;
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&root).map_err(|e| format!("Failed to read scratch directory: {}", e))?;
+    Ok(entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| describe(entry.file_name().to_string_lossy().into_owned(), entry.path()))
+        .collect())
+}
+
+/// Delete a thread's scratch directory and everything in it (blocking)
+pub fn remove(thread_id: &str) -> Result<(), String> {
+    let path = scratch_path(thread_id)?;
+    if !path.exists() {
+        return Err(format!("Thread {} has no scratch directory", thread_id));
+    }
+    std::fs::remove_dir_all(&path).map_err(|e| format!("Failed to remove scratch directory: {}", e))?;
+    log::info!("Removed scratch directory for thread {}", thread_id);
+    Ok(())
+}
+
+/// Delete scratch directories nothing has touched for the retention period
+/// (`CHIMERA_SCRATCH_RETENTION_DAYS`, default 7; 0 keeps them). Returns the
+/// thread ids cleaned up (blocking).
+pub fn cleanup() -> Result<Vec<String>, String> {
+    let days = std::env::var("CHIMERA_SCRATCH_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    if days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+
+    let root = get_scratch_root()?;
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut removed = Vec::new();
+    let entries = std::fs::read_dir(&root).map_err(|e| format!("Failed to read scratch directory: {}", e))?;
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let (_, newest) = usage(&entry.path());
+        if newest >= cutoff {
+            continue;
+        }
+        match std::fs::remove_dir_all(entry.path()) {
+            Ok(()) => removed.push(entry.file_name().to_string_lossy().into_owned()),
+            Err(e) => log::error!("Failed to remove {}: {}", entry.path().display(), e),
+        }
+    }
+
+    if !removed.is_empty() {
+        log::info!("Cleaned up {} stale scratch directories", removed.len());
+    }
+    Ok(removed)
+}
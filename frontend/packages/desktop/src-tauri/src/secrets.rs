@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::filesystem;
+
+/// Keychain service every secret is stored under
+const SERVICE: &str = "chimera-desktop";
+
+/// A stored secret, without its value. Values live only in the OS keychain
+/// (Keychain on macOS, Credential Manager on Windows, Secret Service on Linux).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretInfo {
+    pub name: String,
+    /// Given to the Python backend as an environment variable of the same name
+    pub inject_into_backend: bool,
+    pub updated_at: String,
+}
+
+/// Serializes changes to the index
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Names of stored secrets, kept alongside since keychains can't be listed by service
+fn get_index_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("secrets.json"))
+}
+
+fn read_index() -> Result<Vec<SecretInfo>, String> {
+    let path = get_index_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read secrets index: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse secrets index: {}", e))
+}
+
+fn write_index(index: &[SecretInfo]) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(index).map_err(|e| format!("Failed to serialize secrets index: {}", e))?;
+    std::fs::write(get_index_path()?, content).map_err(|e| format!("Failed to write secrets index: {}", e))
+}
+
+/// Names double as environment variable names, so they're limited to what every
+/// platform accepts. `CHIMERA_` names are reserved for the app's own variables.
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 128
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Invalid secret name {:?}: use letters, digits and underscores", name));
+    }
+    if name.to_ascii_uppercase().starts_with("CHIMERA_") {
+        return Err(format!("Secret names starting with CHIMERA_ are reserved: {}", name));
+    }
+    Ok(())
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("Failed to open keychain entry {}: {}", name, e))
+}
+
+/// Store a secret in the keychain (blocking)
+pub fn set(name: &str, value: &str, inject_into_backend: bool) -> Result<SecretInfo, String> {
+    validate_name(name)?;
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store secret {}: {}", name, e))?;
+
+    let info = SecretInfo {
+        name: name.to_string(),
+        inject_into_backend,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = read_index()?;
+    index.retain(|existing| existing.name != name);
+    index.push(info.clone());
+    index.sort_by(|a, b| a.name.cmp(&b.name));
+    write_index(&index)?;
+
+    log::info!("Stored secret {}", name);
+    Ok(info)
+}
+
+/// A secret's value, or None if there isn't one (blocking)
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    validate_name(name)?;
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret {}: {}", name, e)),
+    }
+}
+
+/// Remove a secret from the keychain (blocking)
+pub fn delete(name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(format!("Failed to delete secret {}: {}", name, e)),
+    }
+
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = read_index()?;
+    index.retain(|existing| existing.name != name);
+    write_index(&index)?;
+
+    log::info!("Deleted secret {}", name);
+    Ok(())
+}
+
+/// Every stored secret, by name (blocking)
+pub fn list() -> Result<Vec<SecretInfo>, String> {
+    read_index()
+}
+
+/// Secrets marked for the backend, as environment variables. Read when a backend
+/// starts, so they reach the process without being written anywhere (blocking).
+pub fn backend_env() -> Vec<(String, String)> {
+    let index = match read_index() {
+        Ok(index) => index,
+        Err(e) => {
+            log::warn!("No secrets for the backend: {}", e);
+            return Vec::new();
+        }
+    };
+
+    index
+        .into_iter()
+        .filter(|info| info.inject_into_backend)
+        .filter_map(|info| match get(&info.name) {
+            Ok(Some(value)) => Some((info.name, value)),
+            Ok(None) => {
+                log::warn!("Secret {} is listed but missing from the keychain", info.name);
+                None
+            }
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        })
+        .collect()
+}
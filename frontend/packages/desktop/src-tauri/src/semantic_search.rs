@@ -0,0 +1,433 @@
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::settings::{AppSettings, SettingsStore};
+
+/// Maximum characters of an event's text sent for embedding
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// Number of texts embedded per request
+const EMBED_BATCH_SIZE: usize = 64;
+
+/// How often changed threads are re-embedded in the background
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Topics after which the index may be out of date: a thread changed, or the
+/// embeddings endpoint was configured
+const INVALIDATING_TOPICS: [&str; 2] = ["thread-changed", "settings-changed"];
+
+/// Event types whose text is indexed
+const INDEXED_EVENT_TYPES: [&str; 4] = ["user-message", "data-user-message", "text-complete", "reasoning-complete"];
+
+/// An indexed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    event_index: usize,
+    event_type: String,
+    text: String,
+    vector: Vec<f32>,
+}
+
+/// Indexed events for one thread, keyed by the file mtime they were computed from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ThreadIndex {
+    modified: u64,
+    entries: Vec<IndexEntry>,
+}
+
+/// Persisted embeddings index
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    model: Option<String>,
+    threads: HashMap<String, ThreadIndex>,
+}
+
+/// A semantic search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub thread_id: String,
+    pub event_index: usize,
+    pub event_type: String,
+    pub snippet: String,
+    pub score: f32,
+}
+
+/// Summary of an index refresh
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStats {
+    pub threads_indexed: usize,
+    pub threads_updated: usize,
+    /// Threads that couldn't be read or embedded; they're tried again next refresh
+    pub threads_failed: usize,
+    pub entries: usize,
+}
+
+/// Embedding index over thread messages, stored in the data directory
+pub struct SemanticIndex {
+    index: Mutex<Option<IndexFile>>,
+    /// Held for a whole refresh, so background and requested ones don't overlap
+    refreshing: Mutex<()>,
+    /// Whether threads may have changed since the last refresh
+    dirty: AtomicBool,
+    client: reqwest::Client,
+}
+
+/// Get the index file path
+fn get_index_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("index").join("embeddings.json"))
+}
+
+/// The embeddings endpoint: `CHIMERA_EMBEDDINGS_URL`, or the `embeddings_url` setting
+pub fn embeddings_url(settings: &AppSettings) -> Result<String, String> {
+    std::env::var("CHIMERA_EMBEDDINGS_URL")
+        .ok()
+        .or_else(|| settings.embeddings_url.clone())
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| {
+            "Embeddings endpoint not configured; set embeddings_url in settings or CHIMERA_EMBEDDINGS_URL".to_string()
+        })
+}
+
+/// Extract the searchable text from an event, if it is an indexed type
+fn event_text(event: &serde_json::Value) -> Option<(String, String)> {
+    let event_type = event.get("type").and_then(|t| t.as_str())?;
+    if !INDEXED_EVENT_TYPES.contains(&event_type) {
+        return None;
+    }
+
+    let text = event
+        .get("content")
+        .and_then(|c| c.as_str())
+        .or_else(|| event.get("data").and_then(|d| d.get("content")).and_then(|c| c.as_str()))?
+        .trim();
+
+    if text.is_empty() {
+        return None;
+    }
+
+    Some((event_type.to_string(), text.chars().take(MAX_TEXT_CHARS).collect()))
+}
+
+/// Cosine similarity between two vectors
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+impl SemanticIndex {
+    /// Create an index; the file is loaded lazily on first use
+    pub fn new() -> Self {
+        Self {
+            index: Mutex::new(None),
+            refreshing: Mutex::new(()),
+            // Threads may have changed while the app was closed
+            dirty: AtomicBool::new(true),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Request embeddings for a batch of texts (OpenAI-compatible API)
+    async fn embed(&self, endpoint: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let mut body = serde_json::json!({ "input": texts });
+        if let Ok(model) = std::env::var("CHIMERA_EMBEDDINGS_MODEL") {
+            body["model"] = serde_json::Value::String(model);
+        }
+
+        let mut request = self.client.post(endpoint).json(&body);
+        if let Ok(key) = std::env::var("CHIMERA_EMBEDDINGS_API_KEY") {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach embeddings endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Embeddings endpoint returned {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid embeddings response: {}", e))?;
+
+        let data = json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or("Embeddings response missing data")?;
+
+        let vectors: Vec<Vec<f32>> = data
+            .iter()
+            .map(|item| {
+                item.get("embedding")
+                    .and_then(|e| e.as_array())
+                    .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        if vectors.len() != texts.len() {
+            return Err(format!("Expected {} embeddings, got {}", texts.len(), vectors.len()));
+        }
+
+        Ok(vectors)
+    }
+
+    /// Load the index file into memory if it isn't already
+    async fn ensure_loaded(&self, slot: &mut Option<IndexFile>) -> Result<(), String> {
+        if slot.is_some() {
+            return Ok(());
+        }
+
+        let path = get_index_path()?;
+        let index = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Discarding unreadable embeddings index: {}", e);
+                IndexFile::default()
+            }),
+            Err(_) => IndexFile::default(),
+        };
+
+        *slot = Some(index);
+        Ok(())
+    }
+
+    /// Re-embed threads whose files changed since they were last indexed. The
+    /// index is only locked to read what's there and to store each thread's
+    /// entries, so searches go on while texts are embedded.
+    pub async fn refresh(&self, endpoint: &str) -> Result<IndexStats, String> {
+        let _refreshing = self.refreshing.lock().await;
+
+        let indexed: HashMap<String, u64> = {
+            let mut guard = self.index.lock().await;
+            self.ensure_loaded(&mut guard).await?;
+            let index = guard.as_mut().expect("index loaded");
+
+            let model = std::env::var("CHIMERA_EMBEDDINGS_MODEL").ok();
+            if index.model != model {
+                log::info!("Embedding model changed, rebuilding semantic index");
+                index.threads.clear();
+                index.model = model;
+            }
+            index.threads.iter().map(|(id, thread)| (id.clone(), thread.modified)).collect()
+        };
+
+        let threads = filesystem::list_threads().await?;
+        let seen: Vec<String> = threads.iter().map(|t| t.thread_id.clone()).collect();
+        let mut updated = 0;
+        let mut failed = 0;
+
+        // Stat every thread concurrently and keep the ones whose mtime moved
+        let stamped: Vec<(String, u64)> = stream::iter(threads)
+            .map(|thread| async move {
+                let modified = tokio::fs::metadata(&thread.file_path)
+                    .await
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                (thread.thread_id, modified)
+            })
+            .buffer_unordered(filesystem::SCAN_CONCURRENCY)
+            .collect()
+            .await;
+
+        let stale: Vec<(String, u64)> = stamped
+            .into_iter()
+            .filter(|(thread_id, modified)| indexed.get(thread_id) != Some(modified))
+            .collect();
+
+        // Load and extract text from stale threads concurrently; embedding stays sequential
+        let mut loaded = stream::iter(stale)
+            .map(|(thread_id, modified)| async move {
+                let events = filesystem::load_thread(thread_id.clone())
+                    .await
+                    .map_err(|e| format!("Failed to load thread {}: {}", thread_id, e))?;
+                let pending: Vec<(usize, String, String)> = events
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, event)| event_text(event).map(|(event_type, text)| (i, event_type, text)))
+                    .collect();
+                Ok::<_, String>((thread_id, modified, pending))
+            })
+            .buffer_unordered(filesystem::SCAN_CONCURRENCY);
+
+        // A thread that fails is left out, keeping what else was indexed
+        'threads: while let Some(result) = loaded.next().await {
+            let (thread_id, modified, pending) = match result {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    log::warn!("Skipped a thread in the semantic index. {}", e);
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let mut entries = Vec::with_capacity(pending.len());
+            for batch in pending.chunks(EMBED_BATCH_SIZE) {
+                let texts: Vec<String> = batch.iter().map(|(_, _, text)| text.clone()).collect();
+                let vectors = match self.embed(endpoint, &texts).await {
+                    Ok(vectors) => vectors,
+                    Err(e) => {
+                        log::warn!("Failed to embed thread {} for the semantic index: {}", thread_id, e);
+                        failed += 1;
+                        continue 'threads;
+                    }
+                };
+
+                for ((event_index, event_type, text), vector) in batch.iter().cloned().zip(vectors) {
+                    entries.push(IndexEntry {
+                        event_index,
+                        event_type,
+                        text,
+                        vector,
+                    });
+                }
+            }
+
+            if let Some(index) = self.index.lock().await.as_mut() {
+                index.threads.insert(thread_id, ThreadIndex { modified, entries });
+            }
+            updated += 1;
+        }
+
+        let mut guard = self.index.lock().await;
+        let index = guard.as_mut().expect("index loaded");
+        // Forget threads that no longer exist
+        index.threads.retain(|id, _| seen.contains(id));
+
+        let path = get_index_path()?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create index directory: {}", e))?;
+        }
+        let content = serde_json::to_string(&*index).map_err(|e| format!("Failed to serialize index: {}", e))?;
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| format!("Failed to write embeddings index: {}", e))?;
+
+        let stats = IndexStats {
+            threads_indexed: index.threads.len(),
+            threads_updated: updated,
+            threads_failed: failed,
+            entries: index.threads.values().map(|t| t.entries.len()).sum(),
+        };
+        log::info!(
+            "Semantic index refreshed: {} threads ({} updated, {} failed), {} entries",
+            stats.threads_indexed,
+            stats.threads_updated,
+            stats.threads_failed,
+            stats.entries
+        );
+
+        Ok(stats)
+    }
+
+    /// Return the `top_k` events most similar to the query, from what's indexed so far
+    pub async fn search(&self, endpoint: &str, query: &str, top_k: usize) -> Result<Vec<SearchResult>, String> {
+        let query_vector = self
+            .embed(endpoint, &[query.to_string()])
+            .await?
+            .pop()
+            .ok_or("No embedding returned for query")?;
+
+        let mut guard = self.index.lock().await;
+        self.ensure_loaded(&mut guard).await?;
+        let index = guard.as_ref().expect("index loaded");
+
+        let mut results: Vec<SearchResult> = index
+            .threads
+            .iter()
+            .flat_map(|(thread_id, thread)| {
+                thread.entries.iter().map(|entry| SearchResult {
+                    thread_id: thread_id.clone(),
+                    event_index: entry.event_index,
+                    event_type: entry.event_type.clone(),
+                    snippet: entry.text.chars().take(200).collect(),
+                    score: cosine(&query_vector, &entry.vector),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(top_k);
+
+        Ok(results)
+    }
+
+    /// Keep the index current in the background: threads changed on the bus are
+    /// re-embedded every `REFRESH_INTERVAL`, so a search never waits on indexing.
+    /// Nothing is indexed until an embeddings endpoint is configured.
+    pub fn watch(self: &Arc<Self>, bus: &EventBus, settings: Arc<SettingsStore>) {
+        let mut receiver = bus.listen();
+        let index = Arc::downgrade(self);
+
+        let refresher = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(REFRESH_INTERVAL).await;
+                let Some(index) = refresher.upgrade() else { break };
+                if !index.dirty.swap(false, Ordering::SeqCst) {
+                    continue;
+                }
+                let result = match embeddings_url(&settings.get()) {
+                    Ok(endpoint) => match index.refresh(&endpoint).await {
+                        Ok(stats) if stats.threads_failed > 0 => {
+                            Err(format!("{} thread(s) failed to index", stats.threads_failed))
+                        }
+                        Ok(_) => Ok(()),
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    // Tried again next time round
+                    index.dirty.store(true, Ordering::SeqCst);
+                    log::debug!("Semantic index not refreshed: {}", e);
+                }
+            }
+        });
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let event = receiver.recv().await;
+                let Some(index) = index.upgrade() else { break };
+                match event {
+                    Ok(event) if INVALIDATING_TOPICS.contains(&event.topic.as_str()) => {
+                        index.dirty.store(true, Ordering::SeqCst)
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => index.dirty.store(true, Ordering::SeqCst),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
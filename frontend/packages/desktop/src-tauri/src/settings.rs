@@ -0,0 +1,498 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::accessibility::AccessibilityOverrides;
+use crate::editor::EditorPreference;
+use crate::event_bus::EventBus;
+use crate::hooks::Hook;
+use crate::python_backend::BackendCommand;
+use crate::scheduler::ScheduledJob;
+use crate::storage::StorageKind;
+use crate::sync::SyncSettings;
+use crate::terminal_profiles::TerminalProfile;
+use crate::updater::UpdateChannel;
+use crate::workspaces;
+
+/// Schema version written to `settings.json`
+const CURRENT_VERSION: u32 = 1;
+
+/// Font used by terminal views
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalFont {
+    pub family: String,
+    pub size: f32,
+}
+
+impl Default for TerminalFont {
+    fn default() -> Self {
+        Self {
+            family: "monospace".to_string(),
+            size: 13.0,
+        }
+    }
+}
+
+/// Automatic backups of threads and blueprints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Hours between backups; 0 turns them off
+    pub interval_hours: u32,
+    /// Automatic backups kept; older ones are deleted. Manual snapshots aren't counted.
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            interval_hours: 6,
+            keep: 28,
+        }
+    }
+}
+
+/// System-wide keyboard shortcuts, as accelerators like `CommandOrControl+Shift+Space`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlobalShortcuts {
+    /// Brings the main window to the front; None turns it off
+    pub summon: Option<String>,
+    /// Brings the main window to the front with the new-message palette open
+    pub new_message: Option<String>,
+}
+
+impl Default for GlobalShortcuts {
+    fn default() -> Self {
+        Self {
+            summon: Some("CommandOrControl+Shift+Space".to_string()),
+            new_message: None,
+        }
+    }
+}
+
+/// Desktop notifications for agent activity in the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Stay quiet while one of the app's windows has focus
+    pub only_when_unfocused: bool,
+    /// When an agent turn finishes
+    pub agent_finished: bool,
+    /// When a run ends in an error
+    pub errors: bool,
+    /// When a tool call waits for approval
+    pub approvals: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_unfocused: true,
+            agent_finished: true,
+            errors: true,
+            approvals: true,
+        }
+    }
+}
+
+/// When `process-warning` events are published for the backends and terminal processes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessWarnings {
+    /// Resident memory, with child processes; 0 turns it off
+    pub memory_mb: u64,
+    /// Sustained CPU, in percent of one core; 0 turns it off
+    pub cpu_percent: f32,
+}
+
+impl Default for ProcessWarnings {
+    fn default() -> Self {
+        Self {
+            memory_mb: 4096,
+            cpu_percent: 0.0,
+        }
+    }
+}
+
+/// When open terminals count as idle, and what happens to them then
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalIdlePolicy {
+    /// Minutes without input or output before `terminal_idle` is published; 0 turns it off
+    pub idle_minutes: u32,
+    /// Close terminals once they're idle
+    pub auto_close: bool,
+}
+
+impl TerminalIdlePolicy {
+    pub fn threshold(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(u64::from(self.idle_minutes) * 60)
+    }
+}
+
+/// User preferences, persisted in `~/chimera-desktop/settings.json`. Missing
+/// fields take their defaults, so older files keep loading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+    /// Where the default workspace's threads and blueprints live; `CHIMERA_DATA_DIR`
+    /// wins if set. Applies on restart.
+    pub data_dir: Option<String>,
+    /// Port for the Python backend; `CHIMERA_BACKEND_PORT` wins if set. Applies on restart.
+    pub backend_port: Option<u16>,
+    /// `system`, `light` or `dark`
+    pub theme: String,
+    /// Locale for dates, numbers and translated text, like `de-DE`; None follows the OS
+    pub locale: Option<String>,
+    /// Reduced motion, high contrast and font scale, where they should differ from the OS's
+    pub accessibility: AccessibilityOverrides,
+    pub terminal_font: TerminalFont,
+    /// Named terminal setups offered when opening a terminal
+    pub terminal_profiles: Vec<TerminalProfile>,
+    pub terminal_idle: TerminalIdlePolicy,
+    /// `error`, `warn`, `info`, `debug` or `trace`
+    pub log_level: String,
+    pub backup: BackupSettings,
+    /// Storage serving thread search; the JSONL files are always kept. Applies on restart.
+    pub storage: StorageKind,
+    pub shortcuts: GlobalShortcuts,
+    pub notifications: NotificationSettings,
+    /// Release channel to take updates from
+    pub update_channel: UpdateChannel,
+    /// Threads untouched this many days are stored zstd-compressed; 0 turns it off
+    pub compress_threads_after_days: u32,
+    pub process_warnings: ProcessWarnings,
+    /// How the backend is started in development mode. Applies on restart.
+    pub backend_command: BackendCommand,
+    /// Extra environment for the backend, e.g. `HTTPS_PROXY` or `SSL_CERT_FILE`.
+    /// Secrets marked for the backend override these. Applies on restart.
+    pub backend_env: BTreeMap<String, String>,
+    /// Blueprints run on a schedule, each run in a new thread
+    pub scheduled_jobs: Vec<ScheduledJob>,
+    /// Editor attachments, blueprints and paths from terminal output open in
+    pub editor: EditorPreference,
+    /// Commands and URLs run when threads are created, agents finish or fail, or
+    /// the backend crashes
+    pub hooks: Vec<Hook>,
+    /// Pushing and pulling threads and blueprints to an S3 bucket or WebDAV folder
+    pub sync: SyncSettings,
+    /// OpenAI-compatible embeddings endpoint for semantic search, like
+    /// `https://api.openai.com/v1/embeddings`; `CHIMERA_EMBEDDINGS_URL` wins if set.
+    /// Threads aren't indexed without one.
+    pub embeddings_url: Option<String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            data_dir: None,
+            backend_port: None,
+            theme: "system".to_string(),
+            locale: None,
+            accessibility: AccessibilityOverrides::default(),
+            terminal_font: TerminalFont::default(),
+            terminal_profiles: TerminalProfile::defaults(),
+            terminal_idle: TerminalIdlePolicy::default(),
+            log_level: "info".to_string(),
+            backup: BackupSettings::default(),
+            storage: StorageKind::default(),
+            shortcuts: GlobalShortcuts::default(),
+            notifications: NotificationSettings::default(),
+            update_channel: UpdateChannel::default(),
+            compress_threads_after_days: 60,
+            process_warnings: ProcessWarnings::default(),
+            backend_command: BackendCommand::default(),
+            backend_env: BTreeMap::new(),
+            scheduled_jobs: Vec::new(),
+            editor: EditorPreference::default(),
+            hooks: Vec::new(),
+            sync: SyncSettings::default(),
+            embeddings_url: None,
+        }
+    }
+}
+
+impl AppSettings {
+    fn validate(&self) -> Result<(), String> {
+        if !["system", "light", "dark"].contains(&self.theme.as_str()) {
+            return Err(format!("Unknown theme: {}", self.theme));
+        }
+        if self.log_level.parse::<log::LevelFilter>().is_err() {
+            return Err(format!("Unknown log level: {}", self.log_level));
+        }
+        if let Some(locale) = &self.locale {
+            crate::i18n::validate(locale)?;
+        }
+        self.accessibility.validate()?;
+        if !(6.0..=72.0).contains(&self.terminal_font.size) {
+            return Err(format!("Terminal font size {} is out of range", self.terminal_font.size));
+        }
+        crate::terminal_profiles::validate(&self.terminal_profiles)?;
+        if self.terminal_idle.idle_minutes > 7 * 24 * 60 {
+            return Err(format!("Terminal idle time {} minutes is too long", self.terminal_idle.idle_minutes));
+        }
+        if self.backup.interval_hours > 24 * 30 {
+            return Err(format!("Backup interval {} hours is too long", self.backup.interval_hours));
+        }
+        if self.compress_threads_after_days > 3650 {
+            return Err(format!(
+                "Compression threshold {} days is too long",
+                self.compress_threads_after_days
+            ));
+        }
+        if !self.process_warnings.cpu_percent.is_finite() || self.process_warnings.cpu_percent < 0.0 {
+            return Err(format!("CPU warning threshold {} is invalid", self.process_warnings.cpu_percent));
+        }
+        self.backend_command.validate()?;
+        crate::backend_env::validate(&self.backend_env)?;
+        crate::scheduler::validate(&self.scheduled_jobs)?;
+        self.editor.validate()?;
+        crate::hooks::validate(&self.hooks)?;
+        self.sync.validate()?;
+        if let Some(url) = &self.embeddings_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(format!("Embeddings endpoint must be an http(s) URL: {}", url));
+            }
+        }
+        if self.backup.keep == 0 {
+            return Err("At least one backup must be kept".to_string());
+        }
+        let shortcuts: Vec<_> = [&self.shortcuts.summon, &self.shortcuts.new_message]
+            .into_iter()
+            .flatten()
+            .map(|accelerator| crate::shortcuts::parse(accelerator))
+            .collect::<Result<_, _>>()?;
+        if shortcuts.len() == 2 && shortcuts[0] == shortcuts[1] {
+            return Err("The summon and new message shortcuts must differ".to_string());
+        }
+        if let Some(dir) = &self.data_dir {
+            if !std::path::Path::new(dir).is_absolute() {
+                return Err(format!("Data directory must be an absolute path: {}", dir));
+            }
+        }
+        Ok(())
+    }
+
+    fn theme(&self) -> Option<tauri::Theme> {
+        match self.theme.as_str() {
+            "light" => Some(tauri::Theme::Light),
+            "dark" => Some(tauri::Theme::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// The `theme` setting and the theme it comes to
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeState {
+    /// `system`, `light` or `dark`
+    pub mode: String,
+    /// `light` or `dark`
+    pub resolved: &'static str,
+}
+
+/// The OS theme, as the main window sees it while no theme is forced
+pub fn system_theme(app_handle: &tauri::AppHandle) -> &'static str {
+    use tauri::Manager;
+    match app_handle.get_webview_window("main").and_then(|window| window.theme().ok()) {
+        Some(tauri::Theme::Dark) => "dark",
+        _ => "light",
+    }
+}
+
+/// Settings file path. It stays in the default location even when `data_dir`
+/// points elsewhere, since it's what says where the data is.
+fn get_settings_path() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home.join("chimera-desktop").join("settings.json"))
+}
+
+/// Bring settings written by an older version up to date, one version at a time
+fn migrate(mut value: serde_json::Value) -> serde_json::Value {
+    let Some(obj) = value.as_object_mut() else {
+        return value;
+    };
+    let mut version = obj.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+    while version < CURRENT_VERSION {
+        // Add a step here when a field is renamed or restructured. Files from
+        // before versioning (0) already have the version 1 layout.
+        version += 1;
+        log::info!("Migrated settings to version {}", version);
+    }
+
+    obj.insert("version".to_string(), serde_json::json!(CURRENT_VERSION));
+    value
+}
+
+/// Read the settings file, migrating and falling back to defaults
+fn read_settings() -> AppSettings {
+    get_settings_path()
+        .and_then(|path| {
+            if !path.exists() {
+                return Ok(AppSettings::default());
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+            let value: serde_json::Value =
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))?;
+            let settings: AppSettings =
+                serde_json::from_value(migrate(value)).map_err(|e| format!("Failed to parse settings: {}", e))?;
+            settings.validate()?;
+            Ok(settings)
+        })
+        .unwrap_or_else(|e| {
+            log::warn!("{}; using default settings", e);
+            AppSettings::default()
+        })
+}
+
+fn write_settings(settings: &AppSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let content =
+        serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Recursively merge `patch` into `target`; nulls clear optional fields
+fn merge(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) if value.is_object() => merge(existing, value),
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Apply settings read before the app is built: the data directory and backend
+/// port, through the environment variables that configure them
+pub fn apply_startup() {
+    let settings = read_settings();
+    workspaces::init(settings.data_dir.as_deref());
+    if let Some(port) = settings.backend_port {
+        if std::env::var_os("CHIMERA_BACKEND_PORT").is_none() {
+            std::env::set_var("CHIMERA_BACKEND_PORT", port.to_string());
+        }
+    }
+}
+
+/// The development-mode backend command from the settings file, for backends
+/// started outside the app's settings store, e.g. headless
+pub fn backend_command() -> BackendCommand {
+    read_settings().backend_command
+}
+
+/// App settings, with `settings-changed` events when they're updated
+pub struct SettingsStore {
+    settings: Mutex<AppSettings>,
+    bus: Arc<EventBus>,
+}
+
+impl SettingsStore {
+    pub fn load(bus: Arc<EventBus>) -> Self {
+        // Migrated settings are written back on the next update
+        Self {
+            settings: Mutex::new(read_settings()),
+            bus,
+        }
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// The theme setting, with `system` resolved to the OS theme
+    pub fn theme(&self, app_handle: &tauri::AppHandle) -> ThemeState {
+        let mode = self.get().theme;
+        let resolved = match mode.as_str() {
+            "light" => "light",
+            "dark" => "dark",
+            _ => system_theme(app_handle),
+        };
+        ThemeState { mode, resolved }
+    }
+
+    /// Apply the settings that take effect immediately
+    pub fn apply(&self, app_handle: &tauri::AppHandle) {
+        let settings = self.get();
+        if let Ok(level) = settings.log_level.parse::<log::LevelFilter>() {
+            log::set_max_level(level);
+        }
+        app_handle.set_theme(settings.theme());
+        if let Err(e) = crate::shortcuts::apply(app_handle, &settings.shortcuts) {
+            log::error!("{}", e);
+        }
+    }
+
+    /// Merge `patch` into the settings, validate and save them, and publish
+    /// `settings-changed` with the names of the fields that changed
+    pub fn update(&self, app_handle: &tauri::AppHandle, patch: serde_json::Value) -> Result<AppSettings, String> {
+        let (previous, updated) = {
+            let mut settings = self.settings.lock().unwrap();
+            let mut value =
+                serde_json::to_value(&*settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+            merge(&mut value, patch);
+            let mut updated: AppSettings =
+                serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+            updated.version = CURRENT_VERSION;
+            updated.validate()?;
+            if updated == *settings {
+                return Ok(updated);
+            }
+            write_settings(&updated)?;
+            (std::mem::replace(&mut *settings, updated.clone()), updated)
+        };
+
+        let before = serde_json::to_value(&previous).unwrap_or_default();
+        let after = serde_json::to_value(&updated).unwrap_or_default();
+        let changed: Vec<&String> = after
+            .as_object()
+            .map(|fields| fields.iter().filter(|(key, value)| before.get(key.as_str()) != Some(value)).map(|(key, _)| key).collect())
+            .unwrap_or_default();
+        let restart_required = changed
+            .iter()
+            .any(|key| ["data_dir", "backend_port", "storage", "backend_command", "backend_env"].contains(&key.as_str()));
+        log::info!("Settings changed: {:?}", changed);
+
+        self.apply(app_handle);
+        if changed.iter().any(|key| *key == "theme") {
+            // Native chrome has switched; the webview follows the same event the
+            // OS theme changing sends
+            use tauri::Emitter;
+            if let Err(e) = app_handle.emit("theme-changed", self.theme(app_handle).resolved) {
+                log::error!("Failed to emit theme-changed event: {}", e);
+            }
+        }
+        if changed.iter().any(|key| *key == "accessibility") {
+            use tauri::Manager;
+            if let Some(accessibility) = app_handle.try_state::<Arc<crate::accessibility::AccessibilityMonitor>>() {
+                accessibility.reapply(app_handle);
+            }
+        }
+        self.bus.publish(
+            "settings-changed",
+            serde_json::json!({
+                "settings": updated,
+                "changed": changed,
+                "restart_required": restart_required,
+            }),
+        );
+        Ok(updated)
+    }
+}
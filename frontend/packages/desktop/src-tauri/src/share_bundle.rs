@@ -0,0 +1,244 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+use crate::{blob_store, export, filesystem};
+
+/// `format` of a share bundle file
+const FORMAT: &str = "chimera-share";
+const VERSION: u32 = 1;
+
+/// Argon2id cost: 64 MiB, 3 passes
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+
+/// Shortest passphrase accepted when creating a bundle
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// Key derivation parameters, stored so they can be raised without breaking old bundles
+#[derive(Debug, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+/// A passphrase-encrypted thread bundle. The payload is the `bundle` export zip;
+/// the header fields are authenticated along with it.
+#[derive(Debug, Serialize, Deserialize)]
+struct ShareBundle {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Result of `create_share_bundle`
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareBundleInfo {
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Result of `open_share_bundle`
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedShareBundle {
+    pub thread_id: String,
+    pub title: Option<String>,
+    pub events: usize,
+    /// Whether the thread got a new id because the original already exists here
+    pub renamed: bool,
+}
+
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<Key, String> {
+    if kdf.algorithm != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", kdf.algorithm));
+    }
+    let salt = BASE64
+        .decode(&kdf.salt)
+        .map_err(|e| format!("Invalid share bundle salt: {}", e))?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Header fields bound to the ciphertext, so none can be swapped undetected
+fn associated_data(bundle: &ShareBundle) -> Vec<u8> {
+    format!(
+        "{}:{}:{}:{}:{}:{}:{}:{}",
+        bundle.format,
+        bundle.version,
+        bundle.kdf.algorithm,
+        bundle.kdf.salt,
+        bundle.kdf.memory_kib,
+        bundle.kdf.iterations,
+        bundle.kdf.parallelism,
+        bundle.cipher
+    )
+    .into_bytes()
+}
+
+/// Export a thread and encrypt it with `passphrase` into `dest_path` (blocking)
+pub fn create(thread_id: &str, events: &[serde_json::Value], passphrase: &str, dest_path: &str) -> Result<ShareBundleInfo, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_CHARS));
+    }
+
+    let mut zip = std::io::Cursor::new(Vec::new());
+    export::write_bundle_to(thread_id, events, &mut zip)?;
+
+    let mut salt = [0u8; 16];
+    chacha20poly1305::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut bundle = ShareBundle {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        kdf: KdfParams {
+            algorithm: "argon2id".to_string(),
+            salt: BASE64.encode(salt),
+            memory_kib: KDF_MEMORY_KIB,
+            iterations: KDF_ITERATIONS,
+            parallelism: KDF_PARALLELISM,
+        },
+        cipher: "xchacha20poly1305".to_string(),
+        nonce: BASE64.encode(nonce),
+        ciphertext: String::new(),
+    };
+
+    let key = derive_key(passphrase, &bundle.kdf)?;
+    let aad = associated_data(&bundle);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, Payload { msg: zip.get_ref(), aad: &aad })
+        .map_err(|_| "Failed to encrypt share bundle".to_string())?;
+    bundle.ciphertext = BASE64.encode(ciphertext);
+
+    let content = serde_json::to_vec(&bundle).map_err(|e| format!("Failed to serialize share bundle: {}", e))?;
+    std::fs::write(dest_path, &content).map_err(|e| format!("Failed to write share bundle: {}", e))?;
+
+    log::info!("Created share bundle for thread {} at {}", thread_id, dest_path);
+    Ok(ShareBundleInfo {
+        path: dest_path.to_string(),
+        bytes: content.len() as u64,
+    })
+}
+
+/// Decrypt a bundle, returning the export zip (blocking)
+fn decrypt(path: &str, passphrase: &str) -> Result<Vec<u8>, String> {
+    let content = std::fs::read(path).map_err(|e| format!("Failed to read share bundle: {}", e))?;
+    let bundle: ShareBundle =
+        serde_json::from_slice(&content).map_err(|_| "Not a Chimera share bundle".to_string())?;
+    if bundle.format != FORMAT {
+        return Err("Not a Chimera share bundle".to_string());
+    }
+    if bundle.version > VERSION {
+        return Err(format!("Share bundle version {} needs a newer Chimera", bundle.version));
+    }
+    if bundle.cipher != "xchacha20poly1305" {
+        return Err(format!("Unsupported share bundle cipher: {}", bundle.cipher));
+    }
+
+    let nonce: [u8; 24] = BASE64
+        .decode(&bundle.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or("Invalid share bundle nonce")?;
+    let ciphertext = BASE64
+        .decode(&bundle.ciphertext)
+        .map_err(|e| format!("Invalid share bundle payload: {}", e))?;
+
+    let key = derive_key(passphrase, &bundle.kdf)?;
+    let aad = associated_data(&bundle);
+    XChaCha20Poly1305::new(&key)
+        .decrypt(&XNonce::from(nonce), Payload { msg: &ciphertext, aad: &aad })
+        .map_err(|_| "Wrong passphrase, or the share bundle is damaged".to_string())
+}
+
+/// Decrypt a bundle and add its thread and attachments to this data directory.
+/// A thread whose id is already taken is imported under a new id (blocking).
+pub fn open(path: &str, passphrase: &str) -> Result<OpenedShareBundle, String> {
+    let zip = decrypt(path, passphrase)?;
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(zip)).map_err(|e| format!("Failed to read share bundle: {}", e))?;
+
+    let mut thread = Vec::new();
+    archive
+        .by_name("thread.jsonl")
+        .map_err(|e| format!("Share bundle has no thread: {}", e))?
+        .read_to_end(&mut thread)
+        .map_err(|e| format!("Failed to read shared thread: {}", e))?;
+    let mut events: Vec<serde_json::Value> = thread
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_slice)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to parse shared thread: {}", e))?;
+
+    // Attachments go into the blob store before the thread that refers to them
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| format!("Failed to read share bundle: {}", e))?;
+        let Some(hash) = file
+            .name()
+            .strip_prefix("attachments/")
+            .and_then(|name| name.strip_suffix(".json"))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let mut blob = Vec::new();
+        file.read_to_end(&mut blob)
+            .map_err(|e| format!("Failed to read shared attachment: {}", e))?;
+        let (stored, _) = blob_store::put_bytes(&blob)?;
+        if stored != hash {
+            log::warn!("Shared attachment {} was stored as {}", hash, stored);
+        }
+    }
+
+    let original_id = events
+        .first()
+        .and_then(|header| header.get("thread_id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let thread_id = match crate::thread_compression::thread_exists(&original_id) {
+        Ok(false) => original_id.clone(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    };
+    if let Some(header) = events.first_mut().and_then(|header| header.as_object_mut()) {
+        header.insert("thread_id".to_string(), serde_json::Value::String(thread_id.clone()));
+    }
+
+    let mut lines = Vec::new();
+    for event in &events {
+        filesystem::serialize_event_line(event, &mut lines)?;
+    }
+    filesystem::replace_thread_file(&filesystem::get_thread_path(&thread_id)?, &lines)?;
+
+    let title = events
+        .iter()
+        .rev()
+        .find(|event| event.get("type").and_then(|t| t.as_str()) == Some("data-thread-title"))
+        .and_then(|event| event.pointer("/data/title")?.as_str().map(str::to_string));
+    log::info!("Imported shared thread {} ({} events)", thread_id, events.len());
+
+    Ok(OpenedShareBundle {
+        renamed: thread_id != original_id,
+        thread_id,
+        title,
+        events: events.len(),
+    })
+}
@@ -0,0 +1,510 @@
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{watch, Mutex};
+use tokio::time::Instant;
+
+use crate::export;
+use crate::filesystem::{self, FsErrorKind};
+use crate::thread_compression;
+
+/// Default lifetime of a share session
+const DEFAULT_DURATION_MINUTES: u64 = 30;
+
+/// Upper bound on how long a share session may run
+const MAX_DURATION_MINUTES: u64 = 24 * 60;
+
+/// How often a shared thread file is checked for new events
+const TAIL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Cookie holding a viewer's session once the link's token is spent
+const SESSION_COOKIE: &str = "chimera_share";
+
+const VIEWER_HTML: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Chimera shared thread</title>
+<style>
+  body { font-family: -apple-system, system-ui, sans-serif; max-width: 860px; margin: 2rem auto; padding: 0 1rem; color: #222; }
+  .event { border-left: 3px solid #ccc; padding: .25rem .75rem; margin: .75rem 0; white-space: pre-wrap; }
+  .type { font-size: .75rem; color: #888; text-transform: uppercase; letter-spacing: .05em; }
+  .user-message, .data-user-message { border-color: #4a7dff; }
+  .text-complete { border-color: #2bb673; }
+  .error { border-color: #e5484d; }
+</style>
+</head>
+<body>
+<p><a href="/">All shared threads</a></p>
+<h1>Shared thread</h1>
+<p id="status">Connecting…</p>
+<div id="events"></div>
+<script>
+  const container = document.getElementById("events");
+  const status = document.getElementById("status");
+  const source = new EventSource(location.pathname.replace(/\/$/, "") + "/events");
+  source.onopen = () => { status.textContent = "Live (read-only)"; };
+  source.onerror = () => { status.textContent = "Disconnected"; };
+  source.onmessage = (msg) => {
+    let event;
+    try { event = JSON.parse(msg.data); } catch { return; }
+    const type = event.type || (event.blueprint ? "blueprint" : "event");
+    const text = event.content ?? event.data?.content ?? event.data?.title;
+    if (typeof text !== "string") return;
+    const el = document.createElement("div");
+    el.className = "event " + type;
+    const label = document.createElement("div");
+    label.className = "type";
+    label.textContent = type;
+    const body = document.createElement("div");
+    body.textContent = text;
+    el.append(label, body);
+    container.append(el);
+  };
+</script>
+</body>
+</html>
+"#;
+
+/// Public details of an active share session
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareSessionInfo {
+    pub thread_ids: Vec<String>,
+    /// Link to give out; its token works once
+    pub url: String,
+    pub started_at: String,
+    pub expires_at: String,
+}
+
+/// A running share server
+struct ActiveShare {
+    info: ShareSessionInfo,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Shared state for request handlers
+#[derive(Clone)]
+struct ServerState {
+    thread_ids: Arc<Vec<String>>,
+    /// The link's token, until its first use exchanges it for a session
+    token: Arc<StdMutex<Option<String>>>,
+    sessions: Arc<StdMutex<HashSet<String>>>,
+    expires: Instant,
+    stopped: watch::Receiver<bool>,
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Where a live view is in one thread file
+struct Tail {
+    thread_id: String,
+    path: PathBuf,
+    offset: u64,
+    /// Text after the last newline, waiting for the rest of its line
+    partial: String,
+    queue: VecDeque<String>,
+}
+
+/// Serves chosen threads read-only to other machines on the local network for a
+/// limited time: an index, and a live view and JSON of each. Every start makes a
+/// new one-time token: the first visit with it gets a session cookie and spends
+/// the token, so a link can't be reused once opened. Sessions end with the share.
+pub struct ShareSessionManager {
+    active: Mutex<Option<ActiveShare>>,
+}
+
+/// Best-effort LAN address of this machine (no packets are sent)
+pub(crate) fn local_ip() -> IpAddr {
+    std::net::UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:80")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+impl ShareSessionManager {
+    /// Create a manager with no active session
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Start sharing `thread_ids`, replacing any existing session
+    pub async fn start(
+        &self,
+        thread_ids: Vec<String>,
+        duration_minutes: Option<u64>,
+        port: Option<u16>,
+    ) -> Result<ShareSessionInfo, String> {
+        if thread_ids.is_empty() {
+            return Err("No threads to share".to_string());
+        }
+        for thread_id in &thread_ids {
+            if !thread_compression::thread_exists(thread_id)? {
+                return Err(format!("Thread {} not found", thread_id));
+            }
+        }
+
+        self.stop().await;
+
+        let minutes = duration_minutes
+            .unwrap_or(DEFAULT_DURATION_MINUTES)
+            .clamp(1, MAX_DURATION_MINUTES);
+        let token = uuid::Uuid::new_v4().simple().to_string();
+
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port.unwrap_or(0))))
+            .await
+            .map_err(|e| format!("Failed to bind share server: {}", e))?;
+        let bound_port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read share server address: {}", e))?
+            .port();
+
+        let lifetime = Duration::from_secs(minutes * 60);
+        let expires = Instant::now() + lifetime;
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let router = Router::new()
+            .route("/", get(index))
+            .route("/threads.json", get(index_json))
+            .route("/threads/:thread_id", get(viewer))
+            .route("/threads/:thread_id/json", get(thread_json))
+            .route("/threads/:thread_id/events", get(events))
+            .with_state(ServerState {
+                thread_ids: Arc::new(thread_ids.clone()),
+                token: Arc::new(StdMutex::new(Some(token.clone()))),
+                sessions: Arc::new(StdMutex::new(HashSet::new())),
+                expires,
+                stopped: shutdown_rx.clone(),
+            });
+
+        let mut stop_rx = shutdown_rx;
+        let shared = thread_ids.len();
+
+        tauri::async_runtime::spawn(async move {
+            let shutdown = async move {
+                tokio::select! {
+                    _ = stop_rx.wait_for(|stopped| *stopped) => {}
+                    _ = tokio::time::sleep_until(expires) => {
+                        log::info!("Share session for {} thread(s) expired", shared);
+                    }
+                }
+            };
+
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, service).with_graceful_shutdown(shutdown).await {
+                log::error!("Share server error: {}", e);
+            }
+        });
+
+        let now = chrono::Utc::now();
+        let info = ShareSessionInfo {
+            thread_ids,
+            url: format!("http://{}:{}/?token={}", local_ip(), bound_port, token),
+            started_at: now.to_rfc3339(),
+            expires_at: (now + chrono::Duration::minutes(minutes as i64)).to_rfc3339(),
+        };
+
+        log::info!(
+            "Sharing {} thread(s) on port {} for {} minutes",
+            info.thread_ids.len(),
+            bound_port,
+            minutes
+        );
+
+        *self.active.lock().await = Some(ActiveShare {
+            info: info.clone(),
+            shutdown: shutdown_tx,
+        });
+
+        Ok(info)
+    }
+
+    /// Stop the active session, if any
+    pub async fn stop(&self) {
+        if let Some(active) = self.active.lock().await.take() {
+            let _ = active.shutdown.send(true);
+            log::info!("Stopped share session for {} thread(s)", active.info.thread_ids.len());
+        }
+    }
+
+    /// Details of the active session, if it hasn't expired
+    pub async fn current(&self) -> Option<ShareSessionInfo> {
+        let mut active = self.active.lock().await;
+
+        let expired = active
+            .as_ref()
+            .and_then(|a| chrono::DateTime::parse_from_rfc3339(&a.info.expires_at).ok())
+            .map(|expires| expires < chrono::Utc::now())
+            .unwrap_or(false);
+        if expired {
+            *active = None;
+        }
+
+        active.as_ref().map(|a| a.info.clone())
+    }
+}
+
+/// Loopback, private and link-local addresses; anything else isn't on the local network
+fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_local(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// The session id from the request's cookies
+fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+}
+
+/// Refusal for requests from outside the local network or without a session.
+/// The first request bringing the token spends it: it's answered with a session
+/// cookie and a redirect to the same page without the token.
+fn refusal(state: &ServerState, peer: SocketAddr, headers: &HeaderMap, uri: &Uri, query: &TokenQuery) -> Option<Response> {
+    if !is_local(peer.ip()) {
+        return Some((StatusCode::FORBIDDEN, "Shared threads are only served on the local network").into_response());
+    }
+    if session_cookie(headers).is_some_and(|session| state.sessions.lock().unwrap().contains(session)) {
+        return None;
+    }
+
+    let mut token = state.token.lock().unwrap();
+    if query.token.is_none() || query.token != *token {
+        return Some((StatusCode::UNAUTHORIZED, "Invalid or already used share link").into_response());
+    }
+    *token = None;
+    let session = uuid::Uuid::new_v4().simple().to_string();
+    state.sessions.lock().unwrap().insert(session.clone());
+    log::info!("Share link opened by {}", peer.ip());
+    Some(private((
+        StatusCode::SEE_OTHER,
+        [
+            (header::LOCATION, uri.path().to_string()),
+            (
+                header::SET_COOKIE,
+                format!("{}={}; HttpOnly; SameSite=Lax; Path=/", SESSION_COOKIE, session),
+            ),
+        ],
+    )))
+}
+
+/// Refusal for threads that aren't part of the share
+fn not_shared(state: &ServerState, thread_id: &str) -> Option<Response> {
+    (!state.thread_ids.iter().any(|id| id == thread_id))
+        .then(|| (StatusCode::NOT_FOUND, "Thread is not shared").into_response())
+}
+
+/// Shared pages mustn't be cached or leak the link they came from as a referrer
+fn private(response: impl IntoResponse) -> Response {
+    (
+        [(header::CACHE_CONTROL, "no-store"), (header::REFERRER_POLICY, "no-referrer")],
+        response,
+    )
+        .into_response()
+}
+
+/// Shared thread ids with their titles, from the thread files' metadata
+async fn titles(state: &ServerState) -> Vec<(String, String)> {
+    let mut titles = Vec::new();
+    for thread_id in state.thread_ids.iter() {
+        let paths = [
+            filesystem::get_thread_path(thread_id),
+            thread_compression::compressed_path(thread_id),
+        ];
+        let mut title = None;
+        for path in paths.into_iter().flatten() {
+            if let Ok(stat) = tokio::fs::metadata(&path).await {
+                title = filesystem::read_thread_metadata(&path, &stat).await.title;
+                break;
+            }
+        }
+        titles.push((thread_id.clone(), title.unwrap_or_else(|| thread_id.clone())));
+    }
+    titles
+}
+
+async fn index(
+    State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    uri: Uri,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(refused) = refusal(&state, peer, &headers, &uri, &query) {
+        return refused;
+    }
+    let links: String = titles(&state)
+        .await
+        .into_iter()
+        .map(|(thread_id, title)| {
+            format!(
+                "<li><a href=\"/threads/{}\">{}</a></li>\n",
+                export::escape_html(&thread_id),
+                export::escape_html(&title)
+            )
+        })
+        .collect();
+    private(Html(format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Shared threads</title>\n<style>{}</style>\n</head>\n<body>\n<h1>Shared threads</h1>\n<p class=\"meta\">Read-only</p>\n<ul>\n{}</ul>\n</body>\n</html>\n",
+        export::HTML_STYLE,
+        links
+    )))
+}
+
+async fn index_json(
+    State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    uri: Uri,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(refused) = refusal(&state, peer, &headers, &uri, &query) {
+        return refused;
+    }
+    let threads: Vec<serde_json::Value> = titles(&state)
+        .await
+        .into_iter()
+        .map(|(thread_id, title)| serde_json::json!({ "thread_id": thread_id, "title": title }))
+        .collect();
+    private(Json(threads))
+}
+
+async fn viewer(
+    State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(thread_id): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(refused) = refusal(&state, peer, &headers, &uri, &query).or_else(|| not_shared(&state, &thread_id)) {
+        return refused;
+    }
+    private(Html(VIEWER_HTML))
+}
+
+async fn thread_json(
+    State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(thread_id): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(refused) = refusal(&state, peer, &headers, &uri, &query).or_else(|| not_shared(&state, &thread_id)) {
+        return refused;
+    }
+    match filesystem::load_thread(thread_id).await {
+        Ok(events) => private(Json(events)),
+        Err(e) if e.kind == FsErrorKind::NotFound => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read thread: {}", e)).into_response(),
+    }
+}
+
+async fn events(
+    State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(thread_id): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(refused) = refusal(&state, peer, &headers, &uri, &query).or_else(|| not_shared(&state, &thread_id)) {
+        return refused;
+    }
+    let path = match filesystem::get_thread_path(&thread_id) {
+        Ok(path) => path,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let tail = Tail {
+        thread_id,
+        path,
+        offset: 0,
+        partial: String::new(),
+        queue: VecDeque::new(),
+    };
+    private(Sse::new(tail_thread(state, tail)).keep_alive(KeepAlive::default()))
+}
+
+/// Stream every line of the thread file, then follow it for new lines until the session ends
+fn tail_thread(state: ServerState, tail: Tail) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((state, tail), |(state, mut tail)| async move {
+        loop {
+            if *state.stopped.borrow() || Instant::now() >= state.expires {
+                return None;
+            }
+
+            if let Some(line) = tail.queue.pop_front() {
+                return Some((Ok(Event::default().data(line)), (state, tail)));
+            }
+
+            match read_from(&tail.thread_id, &tail.path, tail.offset).await {
+                Ok(bytes) if !bytes.is_empty() => {
+                    tail.offset += bytes.len() as u64;
+                    tail.partial.push_str(&String::from_utf8_lossy(&bytes));
+
+                    while let Some(newline) = tail.partial.find('\n') {
+                        let line: String = tail.partial.drain(..=newline).collect();
+                        let line = line.trim();
+                        if !line.is_empty() {
+                            tail.queue.push_back(line.to_string());
+                        }
+                    }
+                }
+                Ok(_) => tokio::time::sleep(TAIL_INTERVAL).await,
+                Err(e) => {
+                    log::warn!("Share session stopped tailing {}: {}", tail.path.display(), e);
+                    return None;
+                }
+            }
+        }
+    })
+}
+
+/// Read everything in a thread file after `offset`. A compressed thread is read
+/// in memory once; its JSONL comes back with the same lines when it's appended to.
+async fn read_from(thread_id: &str, path: &PathBuf, offset: u64) -> Result<Vec<u8>, String> {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && offset == 0 => {
+            let id = thread_id.to_string();
+            return Ok(filesystem::blocking_fs(move || thread_compression::read_thread(&id)).await?);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    file.seek(std::io::SeekFrom::Start(offset)).await.map_err(|e| e.to_string())?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).await.map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
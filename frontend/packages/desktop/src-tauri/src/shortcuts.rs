@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::settings::GlobalShortcuts;
+
+/// What a global shortcut does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// Bring the main window to the front, recreating it if it was closed
+    Summon,
+    /// Summon, then open the quick new-message palette
+    NewMessage,
+}
+
+/// Shortcuts currently registered with the OS
+static REGISTERED: Mutex<Vec<(Shortcut, ShortcutAction)>> = Mutex::new(Vec::new());
+
+/// Parse an accelerator such as `CommandOrControl+Shift+Space`
+pub fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator).map_err(|e| format!("Invalid shortcut {}: {}", accelerator, e))
+}
+
+fn bindings(shortcuts: &GlobalShortcuts) -> Result<Vec<(Shortcut, ShortcutAction)>, String> {
+    let mut bindings = Vec::new();
+    for (accelerator, action) in [
+        (&shortcuts.summon, ShortcutAction::Summon),
+        (&shortcuts.new_message, ShortcutAction::NewMessage),
+    ] {
+        if let Some(accelerator) = accelerator {
+            bindings.push((parse(accelerator)?, action));
+        }
+    }
+    Ok(bindings)
+}
+
+/// Register `shortcuts` with the OS in place of the ones registered now. Nothing is
+/// unregistered if one can't be parsed; if the OS refuses one (another app holds
+/// it) the rest stay registered and the error is returned.
+pub fn apply(app_handle: &AppHandle, shortcuts: &GlobalShortcuts) -> Result<(), String> {
+    let bindings = bindings(shortcuts)?;
+    let mut registered = REGISTERED.lock().unwrap();
+    if *registered == bindings {
+        return Ok(());
+    }
+
+    let manager = app_handle.global_shortcut();
+    for (shortcut, _) in registered.drain(..) {
+        if let Err(e) = manager.unregister(shortcut) {
+            log::warn!("Failed to unregister global shortcut {}: {}", shortcut, e);
+        }
+    }
+
+    let mut failed = Vec::new();
+    for (shortcut, action) in bindings {
+        match manager.register(shortcut) {
+            Ok(()) => {
+                log::info!("Registered global shortcut {} for {:?}", shortcut, action);
+                registered.push((shortcut, action));
+            }
+            Err(e) => failed.push(format!("{}: {}", shortcut, e)),
+        }
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to register global shortcut {}", failed.join(", ")))
+    }
+}
+
+/// Bring the main window to the front, creating it from the app config if it was closed
+pub(crate) fn summon(app_handle: &AppHandle) -> Result<tauri::WebviewWindow, String> {
+    let window = match app_handle.get_webview_window("main") {
+        Some(window) => window,
+        None => {
+            let config = app_handle
+                .config()
+                .app
+                .windows
+                .iter()
+                .find(|window| window.label == "main")
+                .ok_or("No main window in the app config")?;
+            tauri::WebviewWindowBuilder::from_config(app_handle, config)
+                .and_then(|builder| builder.build())
+                .map_err(|e| format!("Failed to create main window: {}", e))?
+        }
+    };
+    let _ = window.unminimize();
+    window.show().map_err(|e| format!("Failed to show main window: {}", e))?;
+    window.set_focus().map_err(|e| format!("Failed to focus main window: {}", e))?;
+    Ok(window)
+}
+
+/// Handler for the global shortcut plugin
+pub fn handle(app_handle: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = REGISTERED
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(registered, _)| registered == shortcut)
+        .map(|(_, action)| *action);
+    let Some(action) = action else { return };
+
+    match summon(app_handle) {
+        Ok(window) => {
+            if action == ShortcutAction::NewMessage {
+                if let Err(e) = window.emit("open-new-message-palette", ()) {
+                    log::error!("Failed to emit open-new-message-palette event: {}", e);
+                }
+            }
+        }
+        Err(e) => log::error!("Global shortcut {:?} failed: {}", action, e),
+    }
+}
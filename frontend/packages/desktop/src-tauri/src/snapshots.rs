@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::filesystem;
+use crate::tasks::TaskProgress;
+
+/// Manifest written last into each snapshot; a snapshot without one is incomplete
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A thread or blueprint file as it was when the snapshot was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileRecord {
+    bytes: u64,
+    sha256: String,
+    /// Events in a thread, not counting the blueprint line; 0 for blueprints
+    events: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    created_at: String,
+    reason: String,
+    threads: BTreeMap<String, FileRecord>,
+    blueprints: BTreeMap<String, FileRecord>,
+}
+
+/// A point-in-time copy of the workspace's threads and blueprints
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub created_at: String,
+    /// Why it was taken, e.g. `manual`
+    pub reason: String,
+    pub threads: usize,
+    pub blueprints: usize,
+    pub bytes: u64,
+}
+
+/// How a thread or blueprint differs between a snapshot and now
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemChange {
+    pub id: String,
+    /// `added` (since the snapshot), `removed` or `modified`
+    pub change: String,
+    pub events_then: Option<usize>,
+    pub events_now: Option<usize>,
+}
+
+/// Result of `restore`
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub snapshot_id: String,
+    pub threads: Vec<String>,
+    pub blueprints: Vec<String>,
+}
+
+/// Result of `diff`: only changed items are listed
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub snapshot_id: String,
+    pub threads: Vec<ItemChange>,
+    pub blueprints: Vec<ItemChange>,
+}
+
+/// Snapshots live in `backups/<id>/` in the data directory
+fn snapshots_dir() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("backups"))
+}
+
+fn snapshot_dir(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid snapshot id: {}", id));
+    }
+    let dir = snapshots_dir()?.join(id);
+    if !dir.join(MANIFEST_FILE).exists() {
+        return Err(format!("Snapshot {} not found", id));
+    }
+    Ok(dir)
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let content = std::fs::read(dir.join(MANIFEST_FILE)).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+    serde_json::from_slice(&content).map_err(|e| format!("Failed to parse snapshot manifest: {}", e))
+}
+
+fn count_events(content: &[u8]) -> usize {
+    content
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .count()
+        .saturating_sub(1)
+}
+
+fn record(content: &[u8], is_thread: bool) -> FileRecord {
+    FileRecord {
+        bytes: content.len() as u64,
+        sha256: format!("{:x}", Sha256::digest(content)),
+        events: if is_thread { count_events(content) } else { 0 },
+    }
+}
+
+/// Files in `dir` with `extension`, by id (the file stem), skipping hidden and temp files
+fn list_files(dir: &Path, extension: &str) -> Result<BTreeMap<String, PathBuf>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            (!id.starts_with('.')).then_some((id, path))
+        })
+        .collect())
+}
+
+/// Thread files by id, with compressed threads where there's no JSONL
+fn list_thread_files() -> Result<BTreeMap<String, PathBuf>, String> {
+    let mut threads = list_files(&filesystem::get_threads_dir()?, "jsonl")?;
+    for path in crate::thread_compression::list_compressed()? {
+        if let Some(id) = crate::thread_compression::compressed_thread_id(&path) {
+            threads.insert(id.to_string(), path.clone());
+        }
+    }
+    Ok(threads)
+}
+
+/// The newest complete snapshot's directory and manifest
+fn latest() -> Option<(PathBuf, Manifest)> {
+    let id = list().ok()?.into_iter().next()?.id;
+    let dir = snapshots_dir().ok()?.join(id);
+    let manifest = read_manifest(&dir).ok()?;
+    Some((dir, manifest))
+}
+
+/// Copy every thread and blueprint into a new snapshot. Files unchanged since
+/// the previous snapshot are hard-linked to its copy where the filesystem
+/// allows, since snapshot files are never modified. Callers should flush
+/// buffered appends first. With `progress`, a cancelled task stops the copy
+/// and removes the partial snapshot (blocking).
+pub fn create(reason: &str, progress: Option<&TaskProgress>) -> Result<Snapshot, String> {
+    let created = chrono::Utc::now();
+    let id = created.format("%Y%m%dT%H%M%S%3fZ").to_string();
+    let dir = snapshots_dir()?.join(&id);
+    let previous = latest();
+
+    let mut manifest = Manifest {
+        created_at: created.to_rfc3339(),
+        reason: reason.to_string(),
+        threads: BTreeMap::new(),
+        blueprints: BTreeMap::new(),
+    };
+    // Compressed threads are kept as plain JSONL, so restores don't depend on it
+    let threads = list_thread_files()?;
+    let blueprints = list_files(&filesystem::get_blueprints_dir()?, "json")?;
+    let total = threads.len() + blueprints.len();
+    let mut done = 0;
+
+    for (name, files) in [("threads", threads), ("blueprints", blueprints)] {
+        let dest = dir.join(name);
+        std::fs::create_dir_all(&dest).map_err(|e| format!("Failed to create snapshot: {}", e))?;
+
+        for (item, path) in files {
+            if let Some(progress) = progress {
+                if let Err(e) = progress.check() {
+                    let _ = std::fs::remove_dir_all(&dir);
+                    return Err(e);
+                }
+                progress.report(done, total, format!("{}/{}", name, item));
+            }
+            done += 1;
+            // Copied rather than hard-linked: threads are appended to in place
+            let content = match std::fs::read(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+            };
+            let (content, file_name) = if crate::thread_compression::is_compressed(&path) {
+                let content = zstd::decode_all(content.as_slice())
+                    .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+                (content, std::ffi::OsString::from(format!("{}.jsonl", item)))
+            } else {
+                (content, path.file_name().unwrap_or_default().to_os_string())
+            };
+            let file_name = file_name.as_os_str();
+            let file_record = record(&content, name == "threads");
+            let target = dest.join(file_name);
+            let unchanged = previous.as_ref().and_then(|(previous_dir, previous)| {
+                let records = if name == "threads" { &previous.threads } else { &previous.blueprints };
+                let old = records.get(&item)?;
+                (old.sha256 == file_record.sha256).then(|| previous_dir.join(name).join(file_name))
+            });
+            let linked = unchanged.is_some_and(|source| std::fs::hard_link(source, &target).is_ok());
+            if !linked {
+                std::fs::write(&target, &content).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+            }
+            let records = if name == "threads" { &mut manifest.threads } else { &mut manifest.blueprints };
+            records.insert(item, file_record);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    std::fs::write(dir.join(MANIFEST_FILE), json).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+
+    let snapshot = summarize(id, &manifest);
+    log::info!(
+        "Created snapshot {} ({} threads, {} blueprints, {} bytes)",
+        snapshot.id,
+        snapshot.threads,
+        snapshot.blueprints,
+        snapshot.bytes
+    );
+    Ok(snapshot)
+}
+
+fn summarize(id: String, manifest: &Manifest) -> Snapshot {
+    Snapshot {
+        id,
+        created_at: manifest.created_at.clone(),
+        reason: manifest.reason.clone(),
+        threads: manifest.threads.len(),
+        blueprints: manifest.blueprints.len(),
+        bytes: manifest.threads.values().chain(manifest.blueprints.values()).map(|r| r.bytes).sum(),
+    }
+}
+
+/// Complete snapshots, newest first (blocking)
+pub fn list() -> Result<Vec<Snapshot>, String> {
+    let entries = match std::fs::read_dir(snapshots_dir()?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read snapshots: {}", e)),
+    };
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.to_string();
+            let manifest = read_manifest(&entry.path()).ok()?;
+            Some(summarize(id, &manifest))
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+/// Compare one kind of item between a snapshot and the live directory
+fn diff_files(
+    then: &BTreeMap<String, FileRecord>,
+    now: BTreeMap<String, PathBuf>,
+    is_thread: bool,
+) -> Result<Vec<ItemChange>, String> {
+    let mut changes = Vec::new();
+
+    for (id, path) in &now {
+        let content = crate::thread_compression::read_thread_file(path)?;
+        let events_now = is_thread.then(|| count_events(&content));
+        let change = match then.get(id) {
+            None => "added",
+            Some(old) if old.bytes == content.len() as u64 && old.sha256 == record(&content, false).sha256 => continue,
+            Some(_) => "modified",
+        };
+        changes.push(ItemChange {
+            id: id.clone(),
+            change: change.to_string(),
+            events_then: then.get(id).filter(|_| is_thread).map(|old| old.events),
+            events_now,
+        });
+    }
+    for (id, old) in then {
+        if !now.contains_key(id) {
+            changes.push(ItemChange {
+                id: id.clone(),
+                change: "removed".to_string(),
+                events_then: is_thread.then_some(old.events),
+                events_now: None,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(changes)
+}
+
+/// Threads and blueprints added, removed or modified since the snapshot (blocking)
+pub fn diff(snapshot_id: &str) -> Result<SnapshotDiff, String> {
+    let manifest = read_manifest(&snapshot_dir(snapshot_id)?)?;
+    Ok(SnapshotDiff {
+        snapshot_id: snapshot_id.to_string(),
+        threads: diff_files(&manifest.threads, list_thread_files()?, true)?,
+        blueprints: diff_files(&manifest.blueprints, list_files(&filesystem::get_blueprints_dir()?, "json")?, false)?,
+    })
+}
+
+/// Put one thread back as it was in the snapshot, replacing the current file if
+/// there is one. Callers must keep appends to the thread out while this runs
+/// (blocking).
+pub fn restore_thread(snapshot_id: &str, thread_id: &str) -> Result<(), String> {
+    let dir = snapshot_dir(snapshot_id)?;
+    let dest = filesystem::get_thread_path(thread_id)?;
+    let source = dir.join("threads").join(dest.file_name().unwrap_or_default());
+    let content = std::fs::read(&source).map_err(|_| format!("Thread {} is not in snapshot {}", thread_id, snapshot_id))?;
+
+    filesystem::replace_thread_file(&dest, &content)?;
+    log::info!("Restored thread {} from snapshot {}", thread_id, snapshot_id);
+    Ok(())
+}
+
+/// Put every thread and blueprint in the snapshot back as it was. Ones created
+/// since are left alone. Callers should snapshot the current state first and
+/// keep appends out while this runs (blocking).
+pub fn restore(snapshot_id: &str) -> Result<RestoreReport, String> {
+    let dir = snapshot_dir(snapshot_id)?;
+    let manifest = read_manifest(&dir)?;
+
+    let mut threads = Vec::new();
+    for thread_id in manifest.threads.keys() {
+        let dest = filesystem::get_thread_path(thread_id)?;
+        let source = dir.join("threads").join(dest.file_name().unwrap_or_default());
+        let content = std::fs::read(&source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        filesystem::replace_thread_file(&dest, &content)?;
+        threads.push(thread_id.clone());
+    }
+
+    let blueprints_dir = filesystem::get_blueprints_dir()?;
+    std::fs::create_dir_all(&blueprints_dir).map_err(|e| format!("Failed to create blueprints directory: {}", e))?;
+    let mut blueprints = Vec::new();
+    for (blueprint_id, path) in list_files(&dir.join("blueprints"), "json")? {
+        let dest = blueprints_dir.join(path.file_name().unwrap_or_default());
+        let temp = dest.with_extension("json.tmp");
+        // Copied, not renamed: the snapshot's file may be hard-linked into others
+        std::fs::copy(&path, &temp)
+            .and_then(|_| std::fs::rename(&temp, &dest))
+            .map_err(|e| format!("Failed to restore blueprint {}: {}", blueprint_id, e))?;
+        blueprints.push(blueprint_id);
+    }
+
+    log::info!(
+        "Restored {} threads and {} blueprints from snapshot {}",
+        threads.len(),
+        blueprints.len(),
+        snapshot_id
+    );
+    Ok(RestoreReport {
+        snapshot_id: snapshot_id.to_string(),
+        threads,
+        blueprints,
+    })
+}
+
+/// Delete all but the newest `keep` snapshots taken for `reason`, returning the
+/// ids removed (blocking)
+pub fn prune(reason: &str, keep: usize) -> Result<Vec<String>, String> {
+    let dir = snapshots_dir()?;
+    let mut removed = Vec::new();
+    for snapshot in list()?.into_iter().filter(|s| s.reason == reason).skip(keep) {
+        // Unlinking leaves hard-linked copies in newer snapshots intact
+        std::fs::remove_dir_all(dir.join(&snapshot.id))
+            .map_err(|e| format!("Failed to remove snapshot {}: {}", snapshot.id, e))?;
+        removed.push(snapshot.id);
+    }
+    if !removed.is_empty() {
+        log::info!("Pruned {} {} snapshots", removed.len(), reason);
+    }
+    Ok(removed)
+}
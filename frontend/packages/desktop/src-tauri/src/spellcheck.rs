@@ -0,0 +1,462 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::filesystem;
+
+#[cfg(not(any(target_os = "macos", windows)))]
+use hunspell as backend;
+#[cfg(target_os = "macos")]
+use macos as backend;
+#[cfg(windows)]
+use windows_spell as backend;
+
+/// Suggestions returned per misspelled word
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A misspelled word in checked text. Offsets are in UTF-16 code units, to match
+/// JavaScript string indices.
+#[derive(Debug, Clone, Serialize)]
+pub struct Misspelling {
+    pub word: String,
+    pub start: usize,
+    pub end: usize,
+    pub suggestions: Vec<String>,
+}
+
+/// A word in checked text, with its UTF-16 range
+struct Token<'a> {
+    word: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Split text into words worth checking. Runs of letters (with inner apostrophes)
+/// are words; anything touching digits or underscores, and camelCase or all-caps
+/// identifiers, is left alone since prompts are full of code.
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut utf16 = 0;
+
+    while let Some((begin, c)) = chars.next() {
+        let start = utf16;
+        utf16 += c.len_utf16();
+        if !c.is_alphanumeric() && c != '_' {
+            continue;
+        }
+
+        let mut finish = begin + c.len_utf8();
+        while let Some(&(index, next)) = chars.peek() {
+            let inner_apostrophe = (next == '\'' || next == '\u{2019}')
+                && text[index + next.len_utf8()..].chars().next().is_some_and(char::is_alphabetic);
+            if !(next.is_alphanumeric() || next == '_' || inner_apostrophe) {
+                break;
+            }
+            chars.next();
+            utf16 += next.len_utf16();
+            finish = index + next.len_utf8();
+        }
+
+        let word = &text[begin..finish];
+        let code_like = word.chars().any(|c| c.is_numeric() || c == '_')
+            || word.chars().skip(1).any(char::is_uppercase)
+            || word.chars().count() < 2;
+        if !code_like {
+            tokens.push(Token { word, start, end: utf16 });
+        }
+    }
+    tokens
+}
+
+/// Get the user dictionary file path
+fn get_user_dictionary_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("user_dictionary.json"))
+}
+
+/// Spellchecking against the OS dictionaries (NSSpellChecker on macOS, the Spell
+/// Checking API on Windows, hunspell elsewhere), plus a user dictionary kept in
+/// the data dir. Checks block, so call them off the async runtime.
+pub struct Spellchecker {
+    user_words: Mutex<BTreeSet<String>>,
+}
+
+impl Spellchecker {
+    pub fn load() -> Self {
+        let user_words = get_user_dictionary_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(BTreeSet::new());
+                }
+                let content =
+                    std::fs::read_to_string(&path).map_err(|e| format!("Failed to read user dictionary: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse user dictionary: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                BTreeSet::new()
+            });
+
+        Self {
+            user_words: Mutex::new(user_words),
+        }
+    }
+
+    fn save(&self, words: &BTreeSet<String>) -> Result<(), String> {
+        let content =
+            serde_json::to_string_pretty(words).map_err(|e| format!("Failed to serialize user dictionary: {}", e))?;
+        std::fs::write(get_user_dictionary_path()?, content).map_err(|e| format!("Failed to write user dictionary: {}", e))
+    }
+
+    /// User dictionary words, lowercased for case-insensitive matching
+    fn known_words(&self) -> HashSet<String> {
+        self.user_words.lock().unwrap().iter().map(|w| w.to_lowercase()).collect()
+    }
+
+    /// Find misspelled words in `text`, with suggestions for each
+    pub fn check(&self, text: &str, language: Option<&str>) -> Result<Vec<Misspelling>, String> {
+        let known = self.known_words();
+        let tokens: Vec<Token> = tokenize(text)
+            .into_iter()
+            .filter(|token| !known.contains(&token.word.to_lowercase()))
+            .collect();
+
+        // Each distinct word goes to the OS checker once
+        let words: Vec<String> = tokens
+            .iter()
+            .map(|token| token.word.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let results: HashMap<&str, Vec<String>> = words
+            .iter()
+            .zip(backend::check(&words, language)?)
+            .filter_map(|(word, suggestions)| Some((word.as_str(), suggestions?)))
+            .collect();
+
+        Ok(tokens
+            .into_iter()
+            .filter_map(|token| {
+                let suggestions = results.get(token.word)?;
+                Some(Misspelling {
+                    word: token.word.to_string(),
+                    start: token.start,
+                    end: token.end,
+                    suggestions: suggestions.iter().take(MAX_SUGGESTIONS).cloned().collect(),
+                })
+            })
+            .collect())
+    }
+
+    /// Suggested corrections for a word; empty if it's spelled correctly
+    pub fn suggest(&self, word: &str, language: Option<&str>) -> Result<Vec<String>, String> {
+        let word = word.trim();
+        if word.is_empty() || self.known_words().contains(&word.to_lowercase()) {
+            return Ok(Vec::new());
+        }
+        let suggestions = backend::check(&[word.to_string()], language)?
+            .into_iter()
+            .next()
+            .flatten()
+            .unwrap_or_default();
+        Ok(suggestions.into_iter().take(MAX_SUGGESTIONS).collect())
+    }
+
+    /// Language tags the OS can check
+    pub fn languages(&self) -> Result<Vec<String>, String> {
+        backend::languages()
+    }
+
+    pub fn user_words(&self) -> Vec<String> {
+        self.user_words.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Add a word to the user dictionary so it's never flagged
+    pub fn add_word(&self, word: &str) -> Result<Vec<String>, String> {
+        let word = word.trim();
+        if word.is_empty() || word.chars().any(char::is_whitespace) {
+            return Err(format!("Not a single word: {:?}", word));
+        }
+
+        let mut words = self.user_words.lock().unwrap();
+        if words.insert(word.to_string()) {
+            self.save(&words)?;
+            log::info!("Added {:?} to the user dictionary", word);
+        }
+        Ok(words.iter().cloned().collect())
+    }
+
+    /// Remove a word from the user dictionary, in any letter case
+    pub fn remove_word(&self, word: &str) -> Result<Vec<String>, String> {
+        let target = word.trim().to_lowercase();
+        let mut words = self.user_words.lock().unwrap();
+        let before = words.len();
+        words.retain(|w| w.to_lowercase() != target);
+        if words.len() != before {
+            self.save(&words)?;
+            log::info!("Removed {:?} from the user dictionary", word.trim());
+        }
+        Ok(words.iter().cloned().collect())
+    }
+}
+
+/// NSSpellChecker, using the languages chosen in System Settings by default
+#[cfg(target_os = "macos")]
+mod macos {
+    use objc2_app_kit::NSSpellChecker;
+    use objc2_foundation::{NSRange, NSString};
+    use std::sync::Mutex;
+
+    /// The shared checker isn't safe to use from several threads at once
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    /// `None` for each correctly spelled word, suggestions for each misspelled one
+    pub fn check(words: &[String], language: Option<&str>) -> Result<Vec<Option<Vec<String>>>, String> {
+        let _guard = LOCK.lock().unwrap();
+        let checker = NSSpellChecker::sharedSpellChecker();
+        let language = language.map(NSString::from_str);
+        let language = language.as_deref();
+
+        Ok(words
+            .iter()
+            .map(|word| {
+                let word = NSString::from_str(word);
+                let range = unsafe {
+                    checker.checkSpellingOfString_startingAt_language_wrap_inSpellDocumentWithTag_wordCount(
+                        &word,
+                        0,
+                        language,
+                        false,
+                        0,
+                        std::ptr::null_mut(),
+                    )
+                };
+                if range.length == 0 {
+                    return None;
+                }
+                let guesses = checker.guessesForWordRange_inString_language_inSpellDocumentWithTag(
+                    NSRange::new(0, word.length()),
+                    &word,
+                    language,
+                    0,
+                );
+                Some(
+                    guesses
+                        .map(|guesses| guesses.to_vec().iter().map(|guess| guess.to_string()).collect())
+                        .unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    pub fn languages() -> Result<Vec<String>, String> {
+        let _guard = LOCK.lock().unwrap();
+        let available = NSSpellChecker::sharedSpellChecker().availableLanguages();
+        Ok(available.to_vec().iter().map(|language| language.to_string()).collect())
+    }
+}
+
+/// The Windows Spell Checking API, using the user's locale by default
+#[cfg(windows)]
+mod windows_spell {
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::Globalization::{
+        GetUserDefaultLocaleName, ISpellChecker, ISpellCheckerFactory, SpellCheckerFactory,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoTaskMemFree, IEnumString, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::Foundation::S_OK;
+
+    fn factory() -> Result<ISpellCheckerFactory, String> {
+        unsafe {
+            // Already initialized on this thread is fine
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            CoCreateInstance(&SpellCheckerFactory, None, CLSCTX_INPROC_SERVER)
+                .map_err(|e| format!("Failed to start the spell checker: {}", e))
+        }
+    }
+
+    fn default_language() -> String {
+        let mut buffer = [0u16; 85];
+        let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+        if len > 1 {
+            String::from_utf16_lossy(&buffer[..len as usize - 1])
+        } else {
+            "en-US".to_string()
+        }
+    }
+
+    fn checker(language: Option<&str>) -> Result<ISpellChecker, String> {
+        let factory = factory()?;
+        let language = HSTRING::from(language.map(str::to_string).unwrap_or_else(default_language));
+        unsafe {
+            if !factory
+                .IsSupported(&language)
+                .map_err(|e| format!("Failed to query spellcheck languages: {}", e))?
+                .as_bool()
+            {
+                return Err(format!("No spellcheck dictionary for {}", language));
+            }
+            factory
+                .CreateSpellChecker(&language)
+                .map_err(|e| format!("Failed to open the {} dictionary: {}", language, e))
+        }
+    }
+
+    /// Drain a COM string enumerator, freeing each string
+    fn collect_strings(strings: &IEnumString) -> Vec<String> {
+        let mut collected = Vec::new();
+        loop {
+            let mut item = [PWSTR::null()];
+            let mut fetched = 0;
+            if unsafe { strings.Next(&mut item, Some(&mut fetched)) } != S_OK || fetched == 0 {
+                break;
+            }
+            if let Ok(value) = unsafe { item[0].to_string() } {
+                collected.push(value);
+            }
+            unsafe { CoTaskMemFree(Some(item[0].0 as *const _)) };
+        }
+        collected
+    }
+
+    /// `None` for each correctly spelled word, suggestions for each misspelled one
+    pub fn check(words: &[String], language: Option<&str>) -> Result<Vec<Option<Vec<String>>>, String> {
+        let checker = checker(language)?;
+        words
+            .iter()
+            .map(|word| unsafe {
+                let word = HSTRING::from(word.as_str());
+                let errors = checker
+                    .Check(&word)
+                    .map_err(|e| format!("Failed to check spelling: {}", e))?;
+                let mut error = None;
+                if errors.Next(&mut error) != S_OK || error.is_none() {
+                    return Ok(None);
+                }
+                let suggestions = checker
+                    .Suggest(&word)
+                    .map(|suggestions| collect_strings(&suggestions))
+                    .unwrap_or_default();
+                Ok(Some(suggestions))
+            })
+            .collect()
+    }
+
+    pub fn languages() -> Result<Vec<String>, String> {
+        let languages = unsafe { factory()?.SupportedLanguages() }
+            .map_err(|e| format!("Failed to list spellcheck languages: {}", e))?;
+        Ok(collect_strings(&languages))
+    }
+}
+
+/// hunspell's ispell-compatible pipe mode, with the system's installed dictionaries
+#[cfg(not(any(target_os = "macos", windows)))]
+mod hunspell {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// Where distributions install hunspell dictionaries
+    const DICTIONARY_DIRS: [&str; 3] = ["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"];
+
+    /// Dictionary name for the user's locale, e.g. `en_US` from `en_US.UTF-8`
+    fn default_language() -> String {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .and_then(|value| value.split(['.', '@']).next().map(str::to_string))
+            .unwrap_or_else(|| "en_US".to_string())
+    }
+
+    /// `None` for each correctly spelled word, suggestions for each misspelled one
+    pub fn check(words: &[String], language: Option<&str>) -> Result<Vec<Option<Vec<String>>>, String> {
+        let language = language.map(|l| l.replace('-', "_")).unwrap_or_else(default_language);
+        let mut child = Command::new("hunspell")
+            .args(["-a", "-i", "utf-8", "-d", &language])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => "Spellcheck needs hunspell and a dictionary installed".to_string(),
+                _ => format!("Failed to start hunspell: {}", e),
+            })?;
+
+        // One word per line; `^` stops a word from being read as a pipe-mode command
+        let mut input = String::new();
+        for word in words {
+            input.push('^');
+            input.push_str(word);
+            input.push('\n');
+        }
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("Failed to write to hunspell: {}", e))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run hunspell: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "hunspell failed for {}: {}",
+                language,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // After the version banner, each input line gets its result lines and a
+        // blank line: `*`, `+` or `-` if correct, `& word n offset: a, b` with
+        // suggestions or `# word offset` without
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut results: Vec<Option<Vec<String>>> = Vec::with_capacity(words.len());
+        let mut current = None;
+        for line in stdout.lines().skip(1) {
+            if line.is_empty() {
+                results.push(current.take());
+                continue;
+            }
+            if line.starts_with('&') {
+                let suggestions = line
+                    .split_once(": ")
+                    .map(|(_, list)| list.split(", ").map(str::to_string).collect())
+                    .unwrap_or_default();
+                current = Some(suggestions);
+            } else if line.starts_with('#') {
+                current = Some(Vec::new());
+            }
+        }
+
+        if results.len() != words.len() {
+            return Err(format!(
+                "Unexpected hunspell output: {} results for {} words",
+                results.len(),
+                words.len()
+            ));
+        }
+        Ok(results)
+    }
+
+    pub fn languages() -> Result<Vec<String>, String> {
+        let mut languages: Vec<String> = DICTIONARY_DIRS
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(".dic").map(str::to_string)
+            })
+            .collect();
+        languages.sort();
+        languages.dedup();
+        Ok(languages)
+    }
+}
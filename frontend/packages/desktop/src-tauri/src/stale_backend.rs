@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+
+use crate::filesystem;
+use crate::python_backend::DEFAULT_WORKSPACE;
+
+/// How long a stale process gets to exit after SIGTERM before it's killed
+const TERM_GRACE: Duration = Duration::from_millis(500);
+
+/// Command-line fragments that mark a process as a Chimera backend: the
+/// development `uv run uvicorn chimera_api.main:app` and the bundled executable
+const BACKEND_MARKERS: [&str; 2] = ["chimera_api", "chimera-backend"];
+
+/// The backend process the app last started, so one left behind by a crash can
+/// be found on the next launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PidRecord {
+    pid: u32,
+    /// Bound port, or 0 while the backend is still starting
+    port: u16,
+    started_at: String,
+}
+
+/// A leftover process found by `cleanup`
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleProcess {
+    pub pid: u32,
+    pub name: String,
+    pub command: String,
+    /// `pidfile`, or `port <n>` when found listening on a backend port
+    pub found_by: String,
+}
+
+/// Result of `cleanup`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StaleCleanup {
+    /// Chimera backends that were stopped, with their child processes
+    pub killed: Vec<StaleProcess>,
+    /// Other programs holding a backend port; left alone
+    pub port_conflicts: Vec<StaleProcess>,
+}
+
+/// `backend.pid` for the default workspace, `backend-<workspace>.pid` for others
+fn pid_file_path(workspace_id: &str) -> Result<PathBuf, String> {
+    let name = if workspace_id == DEFAULT_WORKSPACE {
+        "backend.pid".to_string()
+    } else {
+        format!("backend-{}.pid", workspace_id)
+    };
+    Ok(filesystem::get_data_dir()?.join(name))
+}
+
+/// Where earlier versions kept a bare PID
+fn legacy_pid_file_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("chimera-desktop")
+        .join("python-backend.pid")
+}
+
+/// Record a workspace's backend process, and its port once known
+pub fn write_pid_file(workspace_id: &str, pid: u32, port: u16) -> Result<(), String> {
+    let path = pid_file_path(workspace_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create PID file directory: {}", e))?;
+    }
+    let record = PidRecord {
+        pid,
+        port,
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let json = serde_json::to_string_pretty(&record).map_err(|e| format!("Failed to serialize PID file: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write PID file: {}", e))?;
+
+    log::info!("Wrote PID {} (port {}) to {:?}", pid, port, path);
+    Ok(())
+}
+
+/// Forget a workspace's backend process after a clean shutdown
+pub fn remove_pid_file(workspace_id: &str) {
+    let Ok(path) = pid_file_path(workspace_id) else { return };
+    remove_file(&path);
+}
+
+fn remove_file(path: &Path) {
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove PID file: {}", e);
+        } else {
+            log::info!("Removed PID file {:?}", path);
+        }
+    }
+}
+
+/// Recorded backends of every workspace, with the file each came from. The
+/// legacy file is removed as it's read.
+fn read_pid_records() -> Vec<(Option<PathBuf>, PidRecord)> {
+    let mut records = Vec::new();
+    let pid_files = filesystem::get_data_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("backend") && name.ends_with(".pid"))
+        });
+    for path in pid_files {
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str(&contents) {
+                Ok(record) => records.push((Some(path), record)),
+                Err(e) => {
+                    log::warn!("Removing unreadable PID file {:?}: {}", path, e);
+                    remove_file(&path);
+                }
+            }
+        }
+    }
+
+    let legacy = legacy_pid_file_path();
+    if let Ok(contents) = std::fs::read_to_string(&legacy) {
+        if let Ok(pid) = contents.trim().parse() {
+            records.push((
+                None,
+                PidRecord {
+                    pid,
+                    port: 0,
+                    started_at: String::new(),
+                },
+            ));
+        }
+        let _ = std::fs::remove_file(&legacy);
+    }
+    records
+}
+
+fn refresh(system: &mut System) {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::Always).with_exe(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+fn describe(system: &System, pid: u32, found_by: String) -> Option<StaleProcess> {
+    let process = system.process(Pid::from_u32(pid))?;
+    Some(StaleProcess {
+        pid,
+        name: process.name().to_string_lossy().into_owned(),
+        command: process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" "),
+        found_by,
+    })
+}
+
+fn is_backend(process: &StaleProcess) -> bool {
+    BACKEND_MARKERS
+        .iter()
+        .any(|marker| process.command.contains(marker) || process.name.contains(marker))
+}
+
+/// `roots` and every process descended from them
+pub(crate) fn with_descendants(system: &System, roots: &[u32]) -> HashSet<u32> {
+    let mut tree: HashSet<u32> = roots.iter().copied().collect();
+    loop {
+        let before = tree.len();
+        for (pid, process) in system.processes() {
+            if process.parent().is_some_and(|parent| tree.contains(&parent.as_u32())) {
+                tree.insert(pid.as_u32());
+            }
+        }
+        if tree.len() == before {
+            return tree;
+        }
+    }
+}
+
+/// Stop a process and its descendants, asking politely first where the OS allows.
+/// Returns the processes in the tree.
+fn kill_tree(system: &mut System, pid: u32) -> HashSet<u32> {
+    let tree = with_descendants(system, &[pid]);
+    for pid in &tree {
+        if let Some(process) = system.process(Pid::from_u32(*pid)) {
+            // Not supported on Windows, where it's killed below
+            let _ = process.kill_with(Signal::Term);
+        }
+    }
+    std::thread::sleep(TERM_GRACE);
+
+    refresh(system);
+    for pid in &tree {
+        if let Some(process) = system.process(Pid::from_u32(*pid)) {
+            log::warn!("Process {} didn't exit on SIGTERM, killing it", pid);
+            process.kill();
+        }
+    }
+    tree
+}
+
+/// Stop Chimera backends left running by an earlier session: those in the PID
+/// files, and any listening on a recorded or the configured backend port. Each is
+/// checked to really be a Chimera backend first, since PIDs get reused and other
+/// programs may hold the port. Processes in `keep`, and their descendants, are
+/// live backends and are left alone (blocking).
+pub fn cleanup(keep: &[u32], configured_port: u16) -> StaleCleanup {
+    let mut system = System::new();
+    refresh(&mut system);
+    let keep = with_descendants(&system, keep);
+    let mut report = StaleCleanup::default();
+
+    let records = read_pid_records();
+    let mut candidates: Vec<StaleProcess> = records
+        .iter()
+        .filter_map(|(_, record)| describe(&system, record.pid, "pidfile".to_string()))
+        .collect();
+
+    let ports: HashSet<u16> = records
+        .iter()
+        .map(|(_, record)| record.port)
+        .chain([configured_port])
+        .filter(|port| *port != 0)
+        .collect();
+    for port in ports {
+        match listeners::get_processes_by_port(port) {
+            Ok(owners) => candidates.extend(
+                owners
+                    .into_iter()
+                    .filter_map(|owner| describe(&system, owner.pid, format!("port {}", port))),
+            ),
+            Err(e) => log::warn!("Failed to find the process on port {}: {}", port, e),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for candidate in candidates {
+        if keep.contains(&candidate.pid) || !seen.insert(candidate.pid) {
+            continue;
+        }
+        if !is_backend(&candidate) {
+            if candidate.found_by != "pidfile" {
+                log::warn!("{} (PID {}) holds backend {}", candidate.name, candidate.pid, candidate.found_by);
+                report.port_conflicts.push(candidate);
+            }
+            continue;
+        }
+
+        log::warn!(
+            "Stopping stale backend {} (PID {}, found by {})",
+            candidate.name,
+            candidate.pid,
+            candidate.found_by
+        );
+        seen.extend(kill_tree(&mut system, candidate.pid));
+        report.killed.push(candidate);
+    }
+
+    // Live backends' records stay
+    for (path, record) in &records {
+        if let Some(path) = path {
+            if !keep.contains(&record.pid) {
+                remove_file(path);
+            }
+        }
+    }
+    if report.killed.is_empty() {
+        log::info!("No stale backend processes found");
+    }
+    report
+}
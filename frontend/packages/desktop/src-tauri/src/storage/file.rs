@@ -0,0 +1,115 @@
+use std::io::{BufRead, Write};
+
+use super::{event_text, SearchHit, Storage, StorageKind};
+use crate::filesystem;
+use crate::thread_compression;
+
+/// Threads as JSONL files in the threads directory, one event per line
+pub struct FileStorage;
+
+fn open_lines(thread_id: &str) -> Result<impl Iterator<Item = Result<Vec<u8>, String>>, String> {
+    let path = filesystem::get_thread_path(thread_id)?;
+    // A compressed thread is read in memory and left compressed
+    let reader: Box<dyn BufRead> = match std::fs::File::open(&path) {
+        Ok(file) => Box::new(std::io::BufReader::new(file)),
+        Err(_) => Box::new(std::io::Cursor::new(thread_compression::read_thread(thread_id)?)),
+    };
+    Ok(reader
+        .split(b'\n')
+        .map(|line| line.map_err(|e| format!("Failed to read thread file: {}", e)))
+        // Only lines load_thread gives events for count, so offsets match its indexes
+        .filter(|line| line.as_ref().map_or(true, |line| filesystem::is_event_line(line))))
+}
+
+impl Storage for FileStorage {
+    fn kind(&self) -> StorageKind {
+        StorageKind::Files
+    }
+
+    fn thread_ids(&self) -> Result<Vec<String>, String> {
+        Ok(filesystem::list_listed_thread_files()?
+            .iter()
+            .filter_map(|path| thread_compression::thread_file_id(path).map(str::to_string))
+            .collect())
+    }
+
+    fn event_count(&self, thread_id: &str) -> Result<usize, String> {
+        let mut count = 0;
+        for line in open_lines(thread_id)? {
+            line?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn load_range(&self, thread_id: &str, offset: usize, limit: usize) -> Result<Vec<serde_json::Value>, String> {
+        let mut events = Vec::new();
+        for line in open_lines(thread_id)?.skip(offset).take(limit) {
+            if let Some(event) = filesystem::parse_line(&line?) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn append(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String> {
+        let mut lines = Vec::new();
+        for event in events {
+            filesystem::serialize_event_line(event, &mut lines)?;
+        }
+        thread_compression::decompress(thread_id)?;
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(filesystem::get_thread_path(thread_id)?)
+            .and_then(|mut file| file.write_all(&lines))
+            .map_err(|e| format!("Failed to append to thread {}: {}", thread_id, e))
+    }
+
+    fn replace(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String> {
+        let mut lines = Vec::new();
+        for event in events {
+            filesystem::serialize_event_line(event, &mut lines)?;
+        }
+        Ok(filesystem::replace_thread_file(&filesystem::get_thread_path(thread_id)?, &lines)?)
+    }
+
+    fn delete(&self, thread_id: &str) -> Result<(), String> {
+        for path in [filesystem::get_thread_path(thread_id)?, thread_compression::compressed_path(thread_id)?] {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to delete thread {}: {}", thread_id, e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// A scan of every thread, newest match last within each thread
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits = Vec::new();
+        for thread_id in self.thread_ids()? {
+            for (event_index, event) in self.load(&thread_id)?.iter().enumerate() {
+                let Some(text) = event_text(event) else { continue };
+                let lower = text.to_lowercase();
+                if !words.iter().all(|word| lower.contains(word.as_str())) {
+                    continue;
+                }
+                hits.push(SearchHit {
+                    thread_id: thread_id.clone(),
+                    event_index,
+                    event_type: event.get("type").and_then(|t| t.as_str()).map(str::to_string),
+                    snippet: text.chars().take(200).collect(),
+                });
+                if hits.len() >= limit {
+                    return Ok(hits);
+                }
+            }
+        }
+        Ok(hits)
+    }
+}
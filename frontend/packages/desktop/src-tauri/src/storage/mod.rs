@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::filesystem;
+
+mod file;
+mod sqlite;
+mod sync;
+
+pub use file::FileStorage;
+pub use sqlite::SqliteStorage;
+pub use sync::follow;
+
+/// Which storage serves thread reads, from `storage` in settings. Applies on restart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// One JSONL file per thread
+    #[default]
+    Files,
+    /// `chimera.db` in the data directory, with full-text search
+    Sqlite,
+}
+
+/// An event matching a search
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub thread_id: String,
+    /// Position in `load_thread`'s result (0 is the blueprint line)
+    pub event_index: usize,
+    pub event_type: Option<String>,
+    /// Matching text, with the match in `[brackets]` where the storage can mark it
+    pub snippet: String,
+}
+
+/// Result of `migrate_to_sqlite`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub threads: usize,
+    pub events: usize,
+    /// Threads already in the database with the same events
+    pub unchanged: usize,
+}
+
+/// Where threads' events are kept. Every method blocks.
+///
+/// The JSONL files stay the journal everything writes to first: backups,
+/// compaction, exports and the append buffer all work on them. Another storage
+/// is kept current from the files by `follow` and serves reads it does better.
+pub trait Storage: Send + Sync {
+    fn kind(&self) -> StorageKind;
+
+    fn thread_ids(&self) -> Result<Vec<String>, String>;
+
+    /// Events in a thread, counting the blueprint line
+    fn event_count(&self, thread_id: &str) -> Result<usize, String>;
+
+    /// `limit` events starting at `offset` (0 is the blueprint line)
+    fn load_range(&self, thread_id: &str, offset: usize, limit: usize) -> Result<Vec<serde_json::Value>, String>;
+
+    fn load(&self, thread_id: &str) -> Result<Vec<serde_json::Value>, String> {
+        self.load_range(thread_id, 0, usize::MAX)
+    }
+
+    fn append(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String>;
+
+    /// Replace a thread's events, creating it if needed
+    fn replace(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String>;
+
+    fn delete(&self, thread_id: &str) -> Result<(), String>;
+
+    /// Events whose text matches every word in `query`, best first
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String>;
+}
+
+/// Open the storage of `kind`
+pub fn open(kind: StorageKind) -> Result<Arc<dyn Storage>, String> {
+    Ok(match kind {
+        StorageKind::Files => Arc::new(FileStorage),
+        StorageKind::Sqlite => Arc::new(SqliteStorage::open(&filesystem::get_data_dir()?.join("chimera.db"))?),
+    })
+}
+
+/// The text of an event worth searching: messages, reasoning, errors, titles and
+/// tool calls
+pub(crate) fn event_text(event: &serde_json::Value) -> Option<String> {
+    let mut parts: Vec<String> = ["content", "text", "delta", "errorText"]
+        .iter()
+        .filter_map(|key| event.get(*key)?.as_str().map(str::to_string))
+        .collect();
+    if let Some(title) = event.pointer("/data/title").and_then(|t| t.as_str()) {
+        parts.push(title.to_string());
+    }
+    for key in ["input", "output"] {
+        if let Some(value) = event.get(key).filter(|v| !v.is_null()) {
+            parts.push(value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("\n"))
+}
+
+/// Copy every JSONL thread into the SQLite database. Threads already there with
+/// the same events are left alone, so this can be re-run safely. Callers should
+/// flush buffered appends first (blocking).
+pub fn migrate_to_sqlite() -> Result<MigrationReport, String> {
+    let files = FileStorage;
+    let database = SqliteStorage::open(&filesystem::get_data_dir()?.join("chimera.db"))?;
+
+    let mut report = MigrationReport::default();
+    for thread_id in files.thread_ids()? {
+        let events = files.load(&thread_id)?;
+        if database.event_count(&thread_id)? == events.len() && database.load(&thread_id)? == events {
+            report.unchanged += 1;
+            continue;
+        }
+        database.replace(&thread_id, &events)?;
+        report.threads += 1;
+        report.events += events.len();
+    }
+
+    log::info!(
+        "Migrated {} threads ({} events) to SQLite, {} already current",
+        report.threads,
+        report.events,
+        report.unchanged
+    );
+    Ok(report)
+}
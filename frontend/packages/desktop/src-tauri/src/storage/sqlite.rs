@@ -0,0 +1,190 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::{event_text, SearchHit, Storage, StorageKind};
+
+/// Bumped when the schema changes; older databases are rebuilt from the JSONL files
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        thread_id TEXT NOT NULL,
+        idx INTEGER NOT NULL,
+        type TEXT,
+        json TEXT NOT NULL,
+        PRIMARY KEY (thread_id, idx)
+    ) WITHOUT ROWID;
+    CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+        text,
+        thread_id UNINDEXED,
+        idx UNINDEXED,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+";
+
+/// Threads in a SQLite database: an `events` table keyed by thread and position,
+/// and an FTS5 index over their text
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+fn db_err(e: rusqlite::Error) -> String {
+    format!("Database error: {}", e)
+}
+
+/// Each word as a quoted FTS5 string, so punctuation in the query can't be
+/// mistaken for query syntax. Every word must match.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_err)?;
+        conn.pragma_update(None, "synchronous", "NORMAL").map_err(db_err)?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(db_err)?;
+        if version != SCHEMA_VERSION {
+            if version != 0 {
+                log::warn!("Rebuilding thread database from schema version {}", version);
+            }
+            conn.execute_batch("DROP TABLE IF EXISTS events; DROP TABLE IF EXISTS events_fts;")
+                .map_err(db_err)?;
+        }
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(db_err)?;
+
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn insert(tx: &rusqlite::Transaction, thread_id: &str, start: usize, events: &[serde_json::Value]) -> Result<(), String> {
+        let mut insert_event = tx
+            .prepare_cached("INSERT OR REPLACE INTO events (thread_id, idx, type, json) VALUES (?1, ?2, ?3, ?4)")
+            .map_err(db_err)?;
+        let mut insert_text = tx
+            .prepare_cached("INSERT INTO events_fts (text, thread_id, idx) VALUES (?1, ?2, ?3)")
+            .map_err(db_err)?;
+        for (offset, event) in events.iter().enumerate() {
+            let idx = (start + offset) as i64;
+            let json = serde_json::to_string(event).map_err(|e| format!("Failed to serialize event: {}", e))?;
+            let event_type = event.get("type").and_then(|t| t.as_str());
+            insert_event.execute(params![thread_id, idx, event_type, json]).map_err(db_err)?;
+            if let Some(text) = event_text(event) {
+                insert_text.execute(params![text, thread_id, idx]).map_err(db_err)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(tx: &rusqlite::Transaction, thread_id: &str) -> Result<(), String> {
+        tx.execute("DELETE FROM events WHERE thread_id = ?1", params![thread_id]).map_err(db_err)?;
+        tx.execute("DELETE FROM events_fts WHERE thread_id = ?1", params![thread_id]).map_err(db_err)?;
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn kind(&self) -> StorageKind {
+        StorageKind::Sqlite
+    }
+
+    fn thread_ids(&self) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached("SELECT DISTINCT thread_id FROM events").map_err(db_err)?;
+        let ids = statement
+            .query_map([], |row| row.get(0))
+            .map_err(db_err)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(db_err)?;
+        Ok(ids)
+    }
+
+    fn event_count(&self, thread_id: &str) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        let next: Option<i64> = conn
+            .query_row("SELECT MAX(idx) + 1 FROM events WHERE thread_id = ?1", params![thread_id], |row| row.get(0))
+            .optional()
+            .map_err(db_err)?
+            .flatten();
+        Ok(next.unwrap_or(0) as usize)
+    }
+
+    fn load_range(&self, thread_id: &str, offset: usize, limit: usize) -> Result<Vec<serde_json::Value>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare_cached("SELECT json FROM events WHERE thread_id = ?1 AND idx >= ?2 ORDER BY idx LIMIT ?3")
+            .map_err(db_err)?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = statement
+            .query_map(params![thread_id, offset as i64, limit], |row| row.get::<_, String>(0))
+            .map_err(db_err)?;
+
+        let mut events = Vec::new();
+        for json in rows {
+            let json = json.map_err(db_err)?;
+            events.push(serde_json::from_str(&json).map_err(|e| format!("Failed to parse stored event: {}", e))?);
+        }
+        Ok(events)
+    }
+
+    fn append(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String> {
+        let start = self.event_count(thread_id)?;
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_err)?;
+        Self::insert(&tx, thread_id, start, events)?;
+        tx.commit().map_err(db_err)
+    }
+
+    fn replace(&self, thread_id: &str, events: &[serde_json::Value]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_err)?;
+        Self::remove(&tx, thread_id)?;
+        Self::insert(&tx, thread_id, 0, events)?;
+        tx.commit().map_err(db_err)
+    }
+
+    fn delete(&self, thread_id: &str) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(db_err)?;
+        Self::remove(&tx, thread_id)?;
+        tx.commit().map_err(db_err)
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let query = fts_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare_cached(
+                "SELECT f.thread_id, f.idx, e.type, snippet(events_fts, 0, '[', ']', '…', 16)
+                 FROM events_fts f
+                 LEFT JOIN events e ON e.thread_id = f.thread_id AND e.idx = f.idx
+                 WHERE events_fts MATCH ?1
+                 ORDER BY rank
+                 LIMIT ?2",
+            )
+            .map_err(db_err)?;
+        let hits = statement
+            .query_map(params![query, limit as i64], |row| {
+                Ok(SearchHit {
+                    thread_id: row.get(0)?,
+                    event_index: row.get::<_, i64>(1)? as usize,
+                    event_type: row.get(2)?,
+                    snippet: row.get(3)?,
+                })
+            })
+            .map_err(db_err)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_err)?;
+        Ok(hits)
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{FileStorage, Storage, StorageKind};
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::thread_compression;
+
+/// How long changes are gathered before the database catches up
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Threads changed since the last sync
+#[derive(Default)]
+struct Dirty {
+    all: bool,
+    threads: HashSet<String>,
+}
+
+/// Bring one thread in `storage` up to date with its JSONL file. When the stored
+/// events are a prefix of the file's only the new ones are added; anything else,
+/// such as an amendment or compaction, replaces the thread.
+fn sync_thread(storage: &dyn Storage, thread_id: &str) -> Result<(), String> {
+    let files = FileStorage;
+    if !thread_compression::thread_exists(thread_id)? {
+        return storage.delete(thread_id);
+    }
+
+    let stored = storage.event_count(thread_id)?;
+    let total = files.event_count(thread_id)?;
+    if stored > 0 && stored <= total {
+        let mut tail = files.load_range(thread_id, stored - 1, usize::MAX)?;
+        if tail.first() == storage.load_range(thread_id, stored - 1, 1)?.first() {
+            tail.remove(0);
+            if !tail.is_empty() {
+                storage.append(thread_id, &tail)?;
+            }
+            return Ok(());
+        }
+    }
+    storage.replace(thread_id, &files.load(thread_id)?)
+}
+
+/// Every thread, dropping ones whose files are gone
+fn sync_all(storage: &dyn Storage) -> Result<(), String> {
+    let ids: HashSet<String> = FileStorage.thread_ids()?.into_iter().collect();
+    for thread_id in storage.thread_ids()? {
+        if !ids.contains(&thread_id) {
+            storage.delete(&thread_id)?;
+        }
+    }
+    for thread_id in &ids {
+        if let Err(e) = sync_thread(storage, thread_id) {
+            log::warn!("Failed to sync thread {} to {:?} storage: {}", thread_id, storage.kind(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Keep `storage` following the JSONL files, from `thread-changed` events. The
+/// first pass catches up on everything changed while the app was closed. Files
+/// storage needs nothing.
+pub fn follow(storage: Arc<dyn Storage>, bus: &EventBus, appends: Arc<AppendBuffer>) {
+    if storage.kind() == StorageKind::Files {
+        return;
+    }
+    let dirty = Arc::new(Mutex::new(Dirty {
+        all: true,
+        threads: HashSet::new(),
+    }));
+
+    let mut receiver = bus.listen();
+    let marker = dirty.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.topic == "thread-changed" => {
+                    let mut dirty = marker.lock().unwrap();
+                    match event.payload.get("thread_id").and_then(|t| t.as_str()) {
+                        Some(thread_id) => {
+                            dirty.threads.insert(thread_id.to_string());
+                        }
+                        None => dirty.all = true,
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(_)) => marker.lock().unwrap().all = true,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // The database belongs to the workspace it was opened in; after switching to
+    // another it's left alone until the restart picks that one's up
+    let data_dir = filesystem::get_data_dir().ok();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SYNC_INTERVAL).await;
+            let Dirty { all, threads } = std::mem::take(&mut *dirty.lock().unwrap());
+            if !all && threads.is_empty() {
+                continue;
+            }
+            if filesystem::get_data_dir().ok() != data_dir {
+                continue;
+            }
+
+            // Buffered appends aren't in the files yet
+            if all {
+                appends.flush_all().await;
+            } else {
+                for thread_id in &threads {
+                    let _ = appends.flush(thread_id).await;
+                }
+            }
+
+            let storage = storage.clone();
+            let result = filesystem::blocking(move || {
+                if all {
+                    return sync_all(storage.as_ref());
+                }
+                for thread_id in &threads {
+                    if let Err(e) = sync_thread(storage.as_ref(), thread_id) {
+                        log::warn!("Failed to sync thread {} to {:?} storage: {}", thread_id, storage.kind(), e);
+                    }
+                }
+                Ok(())
+            })
+            .await;
+            if let Err(e) = result {
+                log::error!("Storage sync failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,403 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::cleanup::disk_usage;
+use crate::{attachments, blob_store, filesystem, thread_compression, thread_index};
+
+/// Files changed more recently than this are never orphans: an attachment is
+/// stored just before the event referencing it is appended
+const ORPHAN_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// What's wrong with a file in the data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Attachment no event of its thread references, or of a thread that's gone
+    OrphanedAttachment,
+    /// Blob no thread references
+    OrphanedBlob,
+    /// Metadata record or draft of a thread that's gone
+    OrphanedMetadata,
+    /// Left behind by an interrupted write
+    TempFile,
+    /// Referenced by a thread event but not on disk
+    MissingAttachment,
+    MissingBlob,
+    /// Content doesn't hash to the name it's stored under
+    ChecksumMismatch,
+    /// Saved thread index entry for a file that no longer exists
+    StaleIndexEntry,
+}
+
+impl IssueKind {
+    /// Whether `gc_storage` removes files with this issue
+    fn collectable(self) -> bool {
+        matches!(
+            self,
+            Self::OrphanedAttachment | Self::OrphanedBlob | Self::OrphanedMetadata | Self::TempFile
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Size on disk, for files that exist
+    pub bytes: u64,
+}
+
+/// Result of `verify_storage_integrity`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Live and trashed threads whose events were read
+    pub threads_checked: usize,
+    pub attachments_checked: usize,
+    pub blobs_checked: usize,
+    /// Threads that couldn't be read; their attachments aren't reported as orphans
+    pub unreadable_threads: Vec<String>,
+    pub issues: Vec<IntegrityIssue>,
+    /// Space `gc_storage` would free
+    pub reclaimable_bytes: u64,
+    pub checked_at: String,
+}
+
+/// Result of `gc_storage`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub dry_run: bool,
+    /// Paths removed, or that would be with `dry_run`
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+    /// Paths that couldn't be removed, with why
+    pub failed: Vec<String>,
+    /// Index entries dropped by re-listing the threads
+    pub stale_index_entries: usize,
+}
+
+/// Attachment and blob hashes a thread's events reference
+#[derive(Default)]
+struct References {
+    attachments: HashSet<String>,
+    blobs: HashSet<String>,
+}
+
+/// Read the references out of a thread file (blocking)
+fn references(path: &Path) -> Result<References, String> {
+    let content = thread_compression::read_thread_file(path)?;
+    let mut refs = References::default();
+    for line in content.as_slice().lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) else { continue };
+        if event.get("type").and_then(|t| t.as_str()) == Some(attachments::ATTACHMENT_EVENT) {
+            if let Some(hash) = event.pointer("/data/hash").and_then(|h| h.as_str()) {
+                refs.attachments.insert(hash.to_ascii_lowercase());
+            }
+        }
+        let Some(fields) = event.as_object() else { continue };
+        for value in fields.values() {
+            let hash = blob_store::blob_ref(value)
+                .or_else(|| value.get(blob_store::ATTACHMENT_KEY).and_then(|h| h.as_str()));
+            if let Some(hash) = hash {
+                refs.blobs.insert(hash.to_ascii_lowercase());
+            }
+        }
+    }
+    Ok(refs)
+}
+
+/// Live thread files and trashed ones, by thread id (blocking). Trashed threads
+/// can still be restored, so what they reference is kept.
+fn thread_files() -> Result<(HashMap<String, PathBuf>, HashMap<String, PathBuf>), String> {
+    let live = filesystem::list_listed_thread_files()?
+        .into_iter()
+        .filter_map(|path| {
+            let id = thread_compression::compressed_thread_id(&path)
+                .or_else(|| path.file_stem().and_then(|s| s.to_str()))?
+                .to_string();
+            Some((id, path))
+        })
+        .collect();
+
+    let trash_dir = filesystem::get_threads_dir()?.join(".trash");
+    let trashed = std::fs::read_dir(&trash_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let id = path.file_name()?.to_str()?.strip_suffix(".jsonl")?.to_string();
+            Some((id, path))
+        })
+        .collect();
+    Ok((live, trashed))
+}
+
+/// SHA-256 of a file's content, streamed
+fn file_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn is_hash(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Whether a file is old enough to be called an orphan
+fn settled(metadata: &std::fs::Metadata) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= ORPHAN_GRACE)
+}
+
+struct Checker {
+    report: IntegrityReport,
+}
+
+impl Checker {
+    fn issue(&mut self, kind: IssueKind, path: &Path, thread_id: Option<&str>, bytes: u64) {
+        if kind.collectable() {
+            self.report.reclaimable_bytes += bytes;
+        }
+        self.report.issues.push(IntegrityIssue {
+            kind,
+            path: path.to_string_lossy().into_owned(),
+            thread_id: thread_id.map(str::to_string),
+            bytes,
+        });
+    }
+
+    /// `attachments/<thread_id>/<sha256>` against the events of each thread
+    fn check_attachments(&mut self, dir: &Path, known: &HashSet<&str>, refs: &HashMap<String, Option<References>>) {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let Some(thread_id) = path.file_name().and_then(|s| s.to_str()).map(str::to_string) else {
+                continue;
+            };
+            if !path.is_dir() {
+                continue;
+            }
+            if !known.contains(thread_id.as_str()) {
+                if entry.metadata().is_ok_and(|m| settled(&m)) {
+                    self.issue(IssueKind::OrphanedAttachment, &path, Some(&thread_id), disk_usage(&path));
+                }
+                continue;
+            }
+
+            let thread_refs = refs.get(&thread_id).and_then(Option::as_ref);
+            let mut present = HashSet::new();
+            for file in std::fs::read_dir(&path).into_iter().flatten().flatten() {
+                let file_path = file.path();
+                let Ok(metadata) = file.metadata() else { continue };
+                let name = file.file_name().to_string_lossy().into_owned();
+                if name.starts_with('.') && name.ends_with(".tmp") {
+                    if settled(&metadata) {
+                        self.issue(IssueKind::TempFile, &file_path, Some(&thread_id), metadata.len());
+                    }
+                    continue;
+                }
+                if !metadata.is_file() || !is_hash(&name) {
+                    continue;
+                }
+                self.report.attachments_checked += 1;
+                let name = name.to_ascii_lowercase();
+                if file_hash(&file_path).is_ok_and(|hash| hash != name) {
+                    self.issue(IssueKind::ChecksumMismatch, &file_path, Some(&thread_id), metadata.len());
+                }
+                // Unknown when the thread couldn't be read
+                if thread_refs.is_some_and(|refs| !refs.attachments.contains(&name)) && settled(&metadata) {
+                    self.issue(IssueKind::OrphanedAttachment, &file_path, Some(&thread_id), metadata.len());
+                }
+                present.insert(name);
+            }
+
+            for hash in thread_refs.map(|refs| &refs.attachments).into_iter().flatten() {
+                if !present.contains(hash) {
+                    self.issue(IssueKind::MissingAttachment, &path.join(hash), Some(&thread_id), 0);
+                }
+            }
+        }
+
+        // Threads with attachment events but no attachments directory at all
+        for (thread_id, thread_refs) in refs {
+            let Some(thread_refs) = thread_refs else { continue };
+            if thread_refs.attachments.is_empty() || dir.join(thread_id).is_dir() {
+                continue;
+            }
+            for hash in &thread_refs.attachments {
+                self.issue(IssueKind::MissingAttachment, &dir.join(thread_id).join(hash), Some(thread_id), 0);
+            }
+        }
+    }
+
+    /// `blobs/<sha256>.json` against every thread's references. Orphans are only
+    /// reported when every thread could be read, since blobs are shared.
+    fn check_blobs(&mut self, dir: &Path, referenced: Option<&HashMap<String, String>>) {
+        let mut present = HashSet::new();
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else { continue };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".tmp") {
+                if settled(&metadata) {
+                    self.issue(IssueKind::TempFile, &path, None, metadata.len());
+                }
+                continue;
+            }
+            let Some(hash) = name.strip_suffix(".json").filter(|hash| is_hash(hash)) else { continue };
+            let hash = hash.to_ascii_lowercase();
+            self.report.blobs_checked += 1;
+            if file_hash(&path).is_ok_and(|actual| actual != hash) {
+                self.issue(IssueKind::ChecksumMismatch, &path, None, metadata.len());
+            }
+            if referenced.is_some_and(|referenced| !referenced.contains_key(&hash)) && settled(&metadata) {
+                self.issue(IssueKind::OrphanedBlob, &path, None, metadata.len());
+            }
+            present.insert(hash);
+        }
+
+        let mut missing: Vec<(&String, &String)> = referenced
+            .into_iter()
+            .flatten()
+            .filter(|(hash, _)| !present.contains(*hash))
+            .collect();
+        missing.sort();
+        for (hash, thread_id) in missing {
+            self.issue(IssueKind::MissingBlob, &dir.join(format!("{}.json", hash)), Some(thread_id), 0);
+        }
+    }
+
+    /// Per-thread files named `<thread_id>.<extension>` in `dir`, for threads in `known`
+    fn check_sidecars(&mut self, dir: &Path, extension: &str, known: &HashSet<&str>) {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some(extension) {
+                continue;
+            }
+            let Some(thread_id) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            if known.contains(thread_id) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            if settled(&metadata) {
+                self.issue(IssueKind::OrphanedMetadata, &path, Some(thread_id), metadata.len());
+            }
+        }
+    }
+}
+
+/// Cross-reference thread events, attachments, blobs and the thread metadata
+/// (blocking). Reads and hashes every stored file, so it can take a while.
+pub fn verify() -> Result<IntegrityReport, String> {
+    let data_dir = filesystem::get_data_dir()?;
+    let (live, trashed) = thread_files()?;
+
+    let mut checker = Checker {
+        report: IntegrityReport::default(),
+    };
+    let mut refs: HashMap<String, Option<References>> = HashMap::new();
+    for (thread_id, path) in live.iter().chain(trashed.iter()) {
+        match references(path) {
+            Ok(thread_refs) => {
+                refs.insert(thread_id.clone(), Some(thread_refs));
+                checker.report.threads_checked += 1;
+            }
+            Err(e) => {
+                log::warn!("Integrity check skipping thread {}: {}", thread_id, e);
+                checker.report.unreadable_threads.push(thread_id.clone());
+                refs.insert(thread_id.clone(), None);
+            }
+        }
+    }
+
+    let known: HashSet<&str> = live.keys().chain(trashed.keys()).map(String::as_str).collect();
+    checker.check_attachments(&data_dir.join("attachments"), &known, &refs);
+
+    // Blob hash to a thread referencing it
+    let blobs: Option<HashMap<String, String>> = refs
+        .iter()
+        .map(|(thread_id, thread_refs)| {
+            thread_refs
+                .as_ref()
+                .map(|thread_refs| thread_refs.blobs.iter().map(|hash| (hash.clone(), thread_id.clone())))
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|pairs| pairs.into_iter().flatten().collect());
+    checker.check_blobs(&blob_store::get_blobs_dir()?, blobs.as_ref());
+
+    checker.check_sidecars(&data_dir.join("thread-meta"), "json", &known);
+    // Deleting a thread clears its draft, so only live threads keep one
+    let live_ids: HashSet<&str> = live.keys().map(String::as_str).collect();
+    checker.check_sidecars(&data_dir.join("drafts"), "txt", &live_ids);
+
+    for path in thread_index::stored_paths().unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        Vec::new()
+    }) {
+        if !path.exists() {
+            checker.issue(IssueKind::StaleIndexEntry, &path, None, 0);
+        }
+    }
+
+    let mut report = checker.report;
+    report.unreadable_threads.sort();
+    report.checked_at = chrono::Utc::now().to_rfc3339();
+    log::info!(
+        "Storage integrity check: {} threads, {} attachments, {} blobs, {} issues",
+        report.threads_checked,
+        report.attachments_checked,
+        report.blobs_checked,
+        report.issues.len()
+    );
+    Ok(report)
+}
+
+/// Remove the orphans and leftover temp files `verify` finds, or with `dry_run`
+/// only list them (blocking). Missing files and checksum mismatches are left for
+/// the user, since removing them loses whatever is left.
+pub fn collect(dry_run: bool) -> Result<GcReport, String> {
+    let report = verify()?;
+    let mut gc = GcReport {
+        dry_run,
+        ..GcReport::default()
+    };
+
+    for issue in report.issues {
+        if issue.kind == IssueKind::StaleIndexEntry {
+            gc.stale_index_entries += 1;
+            continue;
+        }
+        if !issue.kind.collectable() {
+            continue;
+        }
+        let path = Path::new(&issue.path);
+        if !dry_run {
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+            if let Err(e) = removed {
+                gc.failed.push(format!("{}: {}", issue.path, e));
+                continue;
+            }
+        }
+        gc.freed_bytes += issue.bytes;
+        gc.removed.push(issue.path);
+    }
+
+    if !dry_run && !gc.removed.is_empty() {
+        log::info!("Removed {} orphaned files, freeing {} bytes", gc.removed.len(), gc.freed_bytes);
+    }
+    Ok(gc)
+}
@@ -0,0 +1,179 @@
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::cleanup::disk_usage;
+use crate::event_bus::EventBus;
+use crate::{blob_store, filesystem, thread_compression};
+
+/// Stats older than this are recomputed even if nothing announced a change,
+/// since files can change outside the app
+const MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Bus events after which the cached stats are out of date
+const INVALIDATING_TOPICS: &[&str] = &["thread-changed", "backup-created", "threads-quarantined"];
+
+/// Disk used by one thread's file
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadUsage {
+    pub thread_id: String,
+    pub bytes: u64,
+}
+
+/// Disk used by the data directory, broken down for the settings screen
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StorageStats {
+    /// Everything in the data directory, including what isn't broken down below
+    pub total_bytes: u64,
+    pub threads_bytes: u64,
+    /// Every thread, largest first
+    pub threads: Vec<ThreadUsage>,
+    /// Deleted threads waiting to be purged
+    pub trash_bytes: u64,
+    pub blueprint_count: usize,
+    pub blueprints_bytes: u64,
+    pub attachments_bytes: u64,
+    pub backup_count: usize,
+    pub backups_bytes: u64,
+    /// Thread listing cache, search index and database; all can be rebuilt
+    pub index_bytes: u64,
+    pub computed_at: String,
+}
+
+/// Files directly in `dir` with `extension`, with their sizes
+fn files_with_sizes(dir: &Path, extension: &str) -> Vec<(String, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.extension().filter(|e| *e == extension)?;
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((path.file_stem()?.to_str()?.to_string(), metadata.len()))
+        })
+        .collect()
+}
+
+/// Walk the data directory (blocking)
+fn compute() -> Result<StorageStats, String> {
+    let data_dir = filesystem::get_data_dir()?;
+    let threads_dir = filesystem::get_threads_dir()?;
+
+    // Compressed threads count at their size on disk
+    let mut threads: Vec<ThreadUsage> = filesystem::list_listed_thread_files()?
+        .iter()
+        .filter_map(|path| {
+            let thread_id = thread_compression::thread_file_id(path)?;
+            let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+            (!thread_id.starts_with('.')).then(|| ThreadUsage {
+                thread_id: thread_id.to_string(),
+                bytes: metadata.len(),
+            })
+        })
+        .collect();
+    threads.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.thread_id.cmp(&b.thread_id)));
+
+    let blueprints = files_with_sizes(&filesystem::get_blueprints_dir()?, "json");
+
+    let backups_dir = data_dir.join("backups");
+    let backup_count = std::fs::read_dir(&backups_dir)
+        .map(|entries| entries.flatten().filter(|entry| entry.path().is_dir()).count())
+        .unwrap_or(0);
+
+    let index_bytes = [
+        threads_dir.join("index.json"),
+        data_dir.join("index"),
+        data_dir.join("chimera.db"),
+        data_dir.join("chimera.db-wal"),
+        data_dir.join("chimera.db-shm"),
+    ]
+    .iter()
+    .map(|path| disk_usage(path))
+    .sum();
+
+    Ok(StorageStats {
+        total_bytes: disk_usage(&data_dir),
+        threads_bytes: threads.iter().map(|thread| thread.bytes).sum(),
+        threads,
+        trash_bytes: disk_usage(&threads_dir.join(".trash")),
+        blueprint_count: blueprints.len(),
+        blueprints_bytes: blueprints.iter().map(|(_, bytes)| bytes).sum(),
+        attachments_bytes: disk_usage(&blob_store::get_blobs_dir()?),
+        backup_count,
+        backups_bytes: disk_usage(&backups_dir),
+        index_bytes,
+        computed_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+struct Cached {
+    stats: StorageStats,
+    at: Instant,
+}
+
+/// Storage stats, computed off the UI path and kept until the data changes
+#[derive(Default)]
+pub struct StorageStatsCache {
+    cached: Mutex<Option<Cached>>,
+    /// Held while computing, so concurrent callers share one walk
+    computing: tokio::sync::Mutex<()>,
+}
+
+impl StorageStatsCache {
+    fn fresh(&self) -> Option<StorageStats> {
+        let cached = self.cached.lock().unwrap();
+        cached
+            .as_ref()
+            .filter(|cached| cached.at.elapsed() < MAX_AGE)
+            .map(|cached| cached.stats.clone())
+    }
+
+    /// Forget the cached stats, so the next `get` recomputes them
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+
+    /// Cached stats, recomputed first when stale or when `refresh` is set
+    pub async fn get(&self, refresh: bool) -> Result<StorageStats, String> {
+        if refresh {
+            self.invalidate();
+        }
+        if let Some(stats) = self.fresh() {
+            return Ok(stats);
+        }
+
+        let _computing = self.computing.lock().await;
+        // Another caller may have finished while this one waited
+        if let Some(stats) = self.fresh() {
+            return Ok(stats);
+        }
+        let stats = filesystem::blocking(compute).await?;
+        *self.cached.lock().unwrap() = Some(Cached {
+            stats: stats.clone(),
+            at: Instant::now(),
+        });
+        Ok(stats)
+    }
+
+    /// Compute the stats in the background now, and drop them whenever threads or
+    /// backups change
+    pub fn watch(self: &Arc<Self>, bus: &EventBus) {
+        let mut receiver = bus.listen();
+        let cache = self.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = cache.get(false).await {
+                log::warn!("Failed to compute storage stats: {}", e);
+            }
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if INVALIDATING_TOPICS.contains(&event.topic.as_str()) => cache.invalidate(),
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => cache.invalidate(),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,102 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::{JoinError, JoinSet};
+use tokio_util::sync::CancellationToken;
+
+/// How long `shutdown` waits for tasks to finish before aborting them
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Background tasks belonging to one subsystem, cancelled and awaited together on
+/// shutdown. Panics are logged and counted instead of vanishing with a dropped handle.
+pub struct TaskGroup {
+    name: &'static str,
+    token: CancellationToken,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl TaskGroup {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            token: CancellationToken::new(),
+            tasks: Mutex::new(JoinSet::new()),
+        }
+    }
+
+    /// Cancelled when the group shuts down. Long-running tasks should select on
+    /// `cancelled()` so shutdown doesn't have to abort them.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Run `future` in the group. The receiver resolves to its output, or an error
+    /// if the task panicked or was aborted.
+    pub fn spawn<F>(&self, future: F) -> oneshot::Receiver<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let mut tasks = self.tasks.lock().unwrap();
+
+        // Collect finished tasks so the set doesn't grow for the life of the app
+        while let Some(result) = tasks.try_join_next() {
+            self.report(result);
+        }
+
+        if self.token.is_cancelled() {
+            log::warn!("Not starting a {} task after shutdown", self.name);
+            return receiver;
+        }
+
+        tasks.spawn_on(
+            async move {
+                let _ = sender.send(future.await);
+            },
+            tauri::async_runtime::handle().inner(),
+        );
+        receiver
+    }
+
+    fn report(&self, result: Result<(), JoinError>) {
+        let Err(e) = result else { return };
+        if e.is_panic() {
+            let panic = e.into_panic();
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            log::error!("A {} task panicked: {}", self.name, message);
+            crate::metrics::record_task_panic();
+        }
+    }
+
+    /// Cancel the group's token and wait up to `timeout` for its tasks, aborting
+    /// any still running after that
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.token.cancel();
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        if tasks.is_empty() {
+            return;
+        }
+
+        let drained = tokio::time::timeout(timeout, async {
+            while let Some(result) = tasks.join_next().await {
+                self.report(result);
+            }
+        })
+        .await;
+
+        if drained.is_err() {
+            log::warn!("Aborting {} {} task(s) still running after {:?}", tasks.len(), self.name, timeout);
+            tasks.abort_all();
+            while let Some(result) = tasks.join_next().await {
+                self.report(result);
+            }
+        }
+        log::info!("All {} tasks stopped", self.name);
+    }
+}
@@ -0,0 +1,1244 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::settings::SettingsStore;
+
+/// Data directory folders that are synced, and the suffix of their files.
+/// Compressed threads are synced as their JSONL.
+const SYNCED_DIRS: [(&str, &str); 4] = [
+    ("threads", ".jsonl"),
+    ("thread-meta", ".json"),
+    ("blueprints", ".json"),
+    ("blobs", ".json"),
+];
+
+/// Remote file listing every synced file's current and previous versions
+const MANIFEST_KEY: &str = "manifest.json";
+
+/// Previous versions of a file kept on the remote
+const MAX_HISTORY: usize = 5;
+
+/// Syncs retried when another device writes the manifest first
+const MAX_ATTEMPTS: usize = 3;
+
+/// Longest the automatic sync interval can be
+const MAX_INTERVAL_MINUTES: u32 = 7 * 24 * 60;
+
+/// How often the automatic sync checks whether one is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timeout for each request to the remote
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Where synced data is kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncRemote {
+    /// An S3 bucket, or an S3-compatible store such as R2 or MinIO through `endpoint`
+    S3 {
+        /// Defaults to AWS: `https://s3.<region>.amazonaws.com`
+        #[serde(default)]
+        endpoint: Option<String>,
+        region: String,
+        bucket: String,
+        /// Folder in the bucket, e.g. `chimera/`
+        #[serde(default)]
+        prefix: String,
+        access_key_id: String,
+        /// Name of the secret holding the secret access key
+        secret: String,
+    },
+    /// A WebDAV folder, e.g. on Nextcloud
+    Webdav {
+        url: String,
+        #[serde(default)]
+        username: Option<String>,
+        /// Name of the secret holding the password
+        #[serde(default)]
+        secret: Option<String>,
+    },
+}
+
+impl SyncRemote {
+    fn validate(&self) -> Result<(), String> {
+        let http = |url: &str| url.starts_with("https://") || url.starts_with("http://");
+        match self {
+            SyncRemote::S3 {
+                endpoint,
+                region,
+                bucket,
+                prefix,
+                access_key_id,
+                secret,
+            } => {
+                if endpoint.as_deref().is_some_and(|url| !http(url)) {
+                    return Err("sync.remote.endpoint must be an http(s) URL".to_string());
+                }
+                if [region, bucket, access_key_id, secret]
+                    .iter()
+                    .any(|field| field.trim().is_empty())
+                {
+                    return Err("sync.remote needs a region, bucket, access_key_id and secret".to_string());
+                }
+                if prefix.split('/').any(|segment| segment == "..") {
+                    return Err(format!("Invalid sync prefix: {}", prefix));
+                }
+            }
+            SyncRemote::Webdav { url, .. } => {
+                if !http(url) {
+                    return Err("sync.remote.url must be an http(s) URL".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Where the remote is, for status; never includes credentials
+    fn describe(&self) -> String {
+        match self {
+            SyncRemote::S3 { bucket, prefix, .. } => format!("s3://{}/{}", bucket, prefix.trim_matches('/')),
+            SyncRemote::Webdav { url, .. } => url.clone(),
+        }
+    }
+}
+
+/// The `sync` setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSettings {
+    pub enabled: bool,
+    pub remote: Option<SyncRemote>,
+    /// Minutes between automatic syncs; 0 syncs only on `sync_now`
+    pub interval_minutes: u32,
+    /// Name recorded with pushed versions and in conflicted copies' names;
+    /// defaults to the host name
+    pub device_name: Option<String>,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            remote: None,
+            interval_minutes: 15,
+            device_name: None,
+        }
+    }
+}
+
+impl SyncSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_minutes > MAX_INTERVAL_MINUTES {
+            return Err(format!("Sync interval {} minutes is too long", self.interval_minutes));
+        }
+        match &self.remote {
+            Some(remote) => remote.validate(),
+            None if self.enabled => Err("Sync needs a remote to be turned on".to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// One version of a file on the remote
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RemoteVersion {
+    /// SHA-256 of the content, its object's name; None for a deletion
+    hash: Option<String>,
+    size: u64,
+    version: u64,
+    modified_at: String,
+    device: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RemoteEntry {
+    #[serde(flatten)]
+    current: RemoteVersion,
+    /// Earlier versions, newest first
+    #[serde(default)]
+    history: Vec<RemoteVersion>,
+}
+
+/// `manifest.json`: synced files by key, e.g. `threads/<id>.jsonl`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    files: BTreeMap<String, RemoteEntry>,
+}
+
+impl Manifest {
+    /// Objects the manifest refers to, old versions included
+    fn objects(&self) -> HashSet<String> {
+        self.files
+            .values()
+            .flat_map(|entry| std::iter::once(&entry.current).chain(&entry.history))
+            .filter_map(|version| version.hash.clone())
+            .collect()
+    }
+
+    /// Make `hash` the current version of `key`, keeping the one it replaces
+    fn record(&mut self, key: &str, hash: Option<String>, size: u64, modified_at: String, device: &str) -> u64 {
+        let entry = self.files.entry(key.to_string()).or_default();
+        let version = entry.current.version + 1;
+        let previous = std::mem::replace(
+            &mut entry.current,
+            RemoteVersion {
+                hash,
+                size,
+                version,
+                modified_at,
+                device: device.to_string(),
+            },
+        );
+        if previous.hash.is_some() {
+            entry.history.insert(0, previous);
+            entry.history.truncate(MAX_HISTORY);
+        }
+        version
+    }
+}
+
+/// A file as it was when last synced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncedFile {
+    hash: String,
+    /// Remote version it matched
+    version: u64,
+    /// Size and modification time it had, so unchanged files aren't hashed again
+    len: u64,
+    modified_ms: i64,
+}
+
+/// `sync/state.json` in the data directory: what each file was at the last sync,
+/// which is what tells a local change from a remote one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Journal {
+    #[serde(default)]
+    last_sync_at: Option<String>,
+    #[serde(default)]
+    files: BTreeMap<String, SyncedFile>,
+}
+
+impl Journal {
+    fn path() -> Result<PathBuf, String> {
+        Ok(filesystem::get_data_dir()?.join("sync").join("state.json"))
+    }
+
+    /// The journal, or a fresh one for a device that hasn't synced (blocking)
+    fn load() -> Result<Self, String> {
+        let path = Self::path()?;
+        match fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content).map_err(|e| format!("Failed to parse sync state: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self {
+                last_sync_at: None,
+                files: BTreeMap::new(),
+            }),
+            Err(e) => Err(format!("Failed to read sync state: {}", e)),
+        }
+    }
+
+    /// Write the journal (blocking)
+    fn save(&self) -> Result<(), String> {
+        let path = Self::path()?;
+        let content = serde_json::to_vec(self).map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+        write_atomic(&path, &content)
+    }
+}
+
+/// A synced file in the data directory
+struct LocalFile {
+    path: PathBuf,
+    hash: String,
+    len: u64,
+    modified_ms: i64,
+}
+
+impl LocalFile {
+    fn synced(&self, version: u64) -> SyncedFile {
+        SyncedFile {
+            hash: self.hash.clone(),
+            version,
+            len: self.len,
+            modified_ms: self.modified_ms,
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// Size and modification time in milliseconds
+fn stat(path: &Path) -> Option<(u64, i64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = chrono::DateTime::<chrono::Utc>::from(metadata.modified().ok()?);
+    Some((metadata.len(), modified.timestamp_millis()))
+}
+
+/// Write a file through a sibling temp file so readers never see half of it (blocking)
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let temp = path.with_extension("sync-tmp");
+    fs::write(&temp, data).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Thread id of a `threads/<id>.jsonl` key
+fn thread_id(key: &str) -> Option<&str> {
+    key.strip_prefix("threads/")?.strip_suffix(".jsonl")
+}
+
+/// Where a key lives in the data directory. Keys come from the remote, so only
+/// plain file names in the synced folders are accepted.
+fn local_path(key: &str) -> Result<PathBuf, String> {
+    if let Some(id) = thread_id(key) {
+        return Ok(filesystem::get_thread_path(id)?);
+    }
+    let invalid = || format!("Invalid sync key: {}", key);
+    let (dir, name) = key.split_once('/').ok_or_else(invalid)?;
+    let (_, suffix) = SYNCED_DIRS
+        .iter()
+        .find(|(synced, _)| *synced == dir)
+        .ok_or_else(invalid)?;
+    if name.starts_with('.') || name.contains(['/', '\\']) || name.contains("..") || !name.ends_with(suffix) {
+        return Err(invalid());
+    }
+    Ok(filesystem::get_data_dir()?.join(dir).join(name))
+}
+
+/// Hash every synced file, reusing the journal's hash for files whose size and
+/// modification time haven't changed (blocking)
+fn scan_local(journal: &Journal) -> Result<BTreeMap<String, LocalFile>, String> {
+    let data_dir = filesystem::get_data_dir()?;
+    let mut paths = Vec::new();
+
+    let mut threads = filesystem::list_thread_files()?;
+    threads.extend(crate::thread_compression::list_compressed()?);
+    for path in threads {
+        let id = crate::thread_compression::thread_file_id(&path).map(str::to_string);
+        if let Some(id) = id.filter(|id| !id.starts_with('.')) {
+            paths.push((format!("threads/{}.jsonl", id), path));
+        }
+    }
+    for (dir, suffix) in &SYNCED_DIRS[1..] {
+        let entries = match fs::read_dir(data_dir.join(dir)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read {}: {}", dir, e)),
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !name.starts_with('.') && name.ends_with(suffix) && path.is_file() {
+                paths.push((format!("{}/{}", dir, name), path));
+            }
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    for (key, path) in paths {
+        let Some((len, modified_ms)) = stat(&path) else {
+            continue;
+        };
+        let hash = match journal.files.get(&key) {
+            Some(synced) if synced.len == len && synced.modified_ms == modified_ms => synced.hash.clone(),
+            _ => sha256(&crate::thread_compression::read_thread_file(&path)?),
+        };
+        files.insert(
+            key,
+            LocalFile {
+                path,
+                hash,
+                len,
+                modified_ms,
+            },
+        );
+    }
+    Ok(files)
+}
+
+/// Name for the losing side of a conflict, beside the file it conflicted with
+fn conflict_key(key: &str, device: &str) -> Option<String> {
+    if key.starts_with("thread-meta/") || key.starts_with("blobs/") {
+        // Metadata follows its thread, and blobs are named by their content
+        return None;
+    }
+    let (stem, suffix) = if let Some(id) = thread_id(key) {
+        (format!("threads/{}", id), ".jsonl")
+    } else {
+        (key.strip_suffix(".json")?.to_string(), ".json")
+    };
+    let device: String = device
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    Some(format!(
+        "{}-conflict-{}-{}{}",
+        stem,
+        device.trim_matches('-'),
+        stamp,
+        suffix
+    ))
+}
+
+/// A condition on a write, so two devices can't both replace the same manifest
+enum Precondition<'a> {
+    None,
+    /// Only replace this version
+    IfMatch(&'a str),
+    /// Only create
+    IfNoneMatch,
+}
+
+/// An S3 bucket, with requests signed with AWS Signature Version 4
+struct S3 {
+    client: reqwest::Client,
+    /// Bucket URL, path-style, with the prefix: `<endpoint>/<bucket>/<prefix>/`
+    base: String,
+    region: String,
+    access_key_id: String,
+    secret_key: String,
+}
+
+/// Percent-encode a URL path segment the way SigV4 expects
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl S3 {
+    fn new(
+        client: reqwest::Client,
+        endpoint: Option<&str>,
+        region: &str,
+        bucket: &str,
+        prefix: &str,
+        access_key_id: &str,
+        secret_key: String,
+    ) -> Self {
+        let endpoint = endpoint.map_or_else(
+            || format!("https://s3.{}.amazonaws.com", region),
+            |e| e.trim_end_matches('/').to_string(),
+        );
+        let mut base = format!("{}/{}/", endpoint, encode_segment(bucket));
+        for segment in prefix.split('/').filter(|s| !s.is_empty()) {
+            base.push_str(&encode_segment(segment));
+            base.push('/');
+        }
+        Self {
+            client,
+            base,
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_key,
+        }
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        headers: &[(&str, &str)],
+    ) -> Result<reqwest::Response, String> {
+        let url =
+            reqwest::Url::parse(&format!("{}{}", self.base, key)).map_err(|e| format!("Invalid S3 URL: {}", e))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("S3 URL has no host: {}", url)),
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256(&body);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256(canonical_request.as_bytes())
+        );
+        let signing_key = [date.as_str(), self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.secret_key).into_bytes(), |key, part| {
+                hmac_sha256(&key, part)
+            });
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            hex(&hmac_sha256(&signing_key, &string_to_sign))
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.send().await.map_err(|e| format!("S3 request failed: {}", e))
+    }
+}
+
+/// A WebDAV folder
+struct Webdav {
+    client: reqwest::Client,
+    base: reqwest::Url,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl Webdav {
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        headers: &[(&str, &str)],
+    ) -> Result<reqwest::Response, String> {
+        let url = self.base.join(key).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        let mut request = self.client.request(method, url).body(body);
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_deref());
+        }
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))
+    }
+}
+
+enum Remote {
+    S3(S3),
+    Webdav(Webdav),
+}
+
+impl Remote {
+    /// Client for `remote`, reading its credentials from the secrets store
+    async fn connect(remote: &SyncRemote) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let secret = |name: String| async move {
+            filesystem::blocking(move || {
+                crate::secrets::get(&name)?.ok_or_else(|| format!("Secret {} is not set", name))
+            })
+            .await
+        };
+        Ok(match remote {
+            SyncRemote::S3 {
+                endpoint,
+                region,
+                bucket,
+                prefix,
+                access_key_id,
+                secret: name,
+            } => Remote::S3(S3::new(
+                client,
+                endpoint.as_deref(),
+                region,
+                bucket,
+                prefix,
+                access_key_id,
+                secret(name.clone()).await?,
+            )),
+            SyncRemote::Webdav {
+                url,
+                username,
+                secret: name,
+            } => {
+                let url = if url.ends_with('/') {
+                    url.clone()
+                } else {
+                    format!("{}/", url)
+                };
+                Remote::Webdav(Webdav {
+                    client,
+                    base: reqwest::Url::parse(&url).map_err(|e| format!("Invalid WebDAV URL: {}", e))?,
+                    username: username.clone(),
+                    password: match name {
+                        Some(name) => Some(secret(name.clone()).await?),
+                        None => None,
+                    },
+                })
+            }
+        })
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        headers: &[(&str, &str)],
+    ) -> Result<reqwest::Response, String> {
+        match self {
+            Remote::S3(s3) => s3.send(method, key, body, headers).await,
+            Remote::Webdav(webdav) => webdav.send(method, key, body, headers).await,
+        }
+    }
+
+    /// Create the objects folder; WebDAV won't create it on the first upload
+    async fn prepare(&self) -> Result<(), String> {
+        if let Remote::Webdav(_) = self {
+            let mkcol = reqwest::Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method");
+            let response = self.send(mkcol, "objects/", Vec::new(), &[]).await?;
+            // 405: it already exists
+            if !response.status().is_success() && response.status().as_u16() != 405 {
+                return Err(format!(
+                    "Failed to create the WebDAV objects folder: HTTP {}",
+                    response.status()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// A file's content and ETag, or None if it doesn't exist
+    async fn get(&self, key: &str) -> Result<Option<(Vec<u8>, Option<String>)>, String> {
+        let response = self.send(reqwest::Method::GET, key, Vec::new(), &[]).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Failed to download {}: HTTP {}", key, response.status()));
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", key, e))?;
+        Ok(Some((body.to_vec(), etag)))
+    }
+
+    /// Upload a file; false if `precondition` failed because someone else wrote it
+    async fn put(&self, key: &str, body: Vec<u8>, precondition: Precondition<'_>) -> Result<bool, String> {
+        let headers: &[(&str, &str)] = match &precondition {
+            Precondition::None => &[],
+            Precondition::IfMatch(etag) => &[("if-match", etag)],
+            Precondition::IfNoneMatch => &[("if-none-match", "*")],
+        };
+        let response = self.send(reqwest::Method::PUT, key, body, headers).await?;
+        match response.status().as_u16() {
+            200..=299 => Ok(true),
+            // S3 answers a concurrent conditional write with 409
+            409 | 412 => Ok(false),
+            status => Err(format!("Failed to upload {}: HTTP {}", key, status)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self.send(reqwest::Method::DELETE, key, Vec::new(), &[]).await?;
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("Failed to delete {}: HTTP {}", key, response.status()));
+        }
+        Ok(())
+    }
+}
+
+fn object_key(hash: &str) -> String {
+    format!("objects/{}", hash)
+}
+
+/// A file where both sides changed since the last sync
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    pub key: String,
+    /// `local` or `remote`: the side that was newer and kept its name
+    pub kept: &'static str,
+    /// Where the other side was saved, if it was
+    pub copy: Option<String>,
+}
+
+/// What a sync did, by file key, e.g. `threads/<id>.jsonl`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub pushed: Vec<String>,
+    pub pulled: Vec<String>,
+    pub deleted_local: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+    pub started_at: String,
+    pub finished_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    Disabled,
+    Idle,
+    Syncing,
+    Error,
+}
+
+/// Result of `get_sync_status`, and the payload of `sync-status` events
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    /// Bucket or URL synced to
+    pub remote: Option<String>,
+    pub state: SyncPhase,
+    pub last_sync_at: Option<String>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+}
+
+/// A push waiting on the manifest write: the key and its journal record, None
+/// for a deletion
+struct PendingPush {
+    key: String,
+    synced: Option<SyncedFile>,
+}
+
+/// Pushes and pulls the data directory to the remote in settings
+pub struct SyncEngine {
+    app_handle: AppHandle,
+    bus: Arc<EventBus>,
+    status: Mutex<SyncStatus>,
+    /// Held for the length of a sync
+    running: tokio::sync::Mutex<()>,
+}
+
+impl SyncEngine {
+    pub fn new(app_handle: AppHandle, bus: Arc<EventBus>) -> Self {
+        let last_sync_at = Journal::path()
+            .ok()
+            .filter(|path| path.exists())
+            .and_then(|_| Journal::load().ok())
+            .and_then(|journal| journal.last_sync_at);
+        Self {
+            app_handle,
+            bus,
+            status: Mutex::new(SyncStatus {
+                enabled: false,
+                remote: None,
+                state: SyncPhase::Disabled,
+                last_sync_at,
+                last_error: None,
+                last_report: None,
+            }),
+            running: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn settings(&self) -> SyncSettings {
+        self.app_handle
+            .try_state::<Arc<SettingsStore>>()
+            .map(|settings| settings.get().sync)
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        let settings = self.settings();
+        let mut status = self.status.lock().unwrap().clone();
+        status.enabled = settings.enabled && settings.remote.is_some();
+        status.remote = settings.remote.as_ref().map(SyncRemote::describe);
+        if !status.enabled && status.state != SyncPhase::Syncing {
+            status.state = SyncPhase::Disabled;
+        } else if status.enabled && status.state == SyncPhase::Disabled {
+            status.state = SyncPhase::Idle;
+        }
+        status
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut SyncStatus)) {
+        update(&mut self.status.lock().unwrap());
+        self.bus
+            .publish("sync-status", serde_json::to_value(self.status()).unwrap_or_default());
+    }
+
+    /// Sync now, unless a sync is already running
+    pub async fn sync_now(&self) -> Result<SyncReport, String> {
+        let _running = self
+            .running
+            .try_lock()
+            .map_err(|_| "A sync is already running".to_string())?;
+        let settings = self.settings();
+        let Some(remote) = settings.remote.clone().filter(|_| settings.enabled) else {
+            return Err("Sync is turned off".to_string());
+        };
+
+        self.update_status(|status| status.state = SyncPhase::Syncing);
+        let result = self.run(&settings, &remote).await;
+        match &result {
+            Ok(report) => {
+                log::info!(
+                    "Synced with {}: {} pushed, {} pulled, {} conflicts",
+                    remote.describe(),
+                    report.pushed.len() + report.deleted_remote.len(),
+                    report.pulled.len() + report.deleted_local.len(),
+                    report.conflicts.len()
+                );
+                self.update_status(|status| {
+                    status.state = SyncPhase::Idle;
+                    status.last_sync_at = Some(report.finished_at.clone());
+                    status.last_error = None;
+                    status.last_report = Some(report.clone());
+                });
+            }
+            Err(e) => {
+                log::error!("Sync with {} failed: {}", remote.describe(), e);
+                self.update_status(|status| {
+                    status.state = SyncPhase::Error;
+                    status.last_error = Some(e.clone());
+                });
+            }
+        }
+        result
+    }
+
+    async fn run(&self, settings: &SyncSettings, remote: &SyncRemote) -> Result<SyncReport, String> {
+        if let Some(appends) = self.app_handle.try_state::<Arc<AppendBuffer>>() {
+            appends.flush_all().await;
+        }
+        let remote = Remote::connect(remote).await?;
+        remote.prepare().await?;
+        let device = settings
+            .device_name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(sysinfo::System::host_name)
+            .unwrap_or_else(|| "device".to_string());
+
+        let mut journal = filesystem::blocking(Journal::load).await?;
+        let mut report = SyncReport {
+            started_at: chrono::Utc::now().to_rfc3339(),
+            ..Default::default()
+        };
+        // Objects known to be on the remote, so each is uploaded once
+        let mut uploaded = HashSet::new();
+        for attempt in 1..=MAX_ATTEMPTS {
+            let outcome = self
+                .attempt(&remote, &mut journal, &device, &mut uploaded, &mut report)
+                .await;
+            if outcome.as_ref().is_ok_and(|committed| *committed) {
+                report.finished_at = chrono::Utc::now().to_rfc3339();
+                journal.last_sync_at = Some(report.finished_at.clone());
+            }
+            // Pulls are kept even when the manifest write fails
+            let snapshot = journal.clone();
+            filesystem::blocking(move || snapshot.save()).await?;
+            if outcome? {
+                return Ok(report);
+            }
+            log::info!(
+                "Sync manifest changed remotely; retrying ({}/{})",
+                attempt,
+                MAX_ATTEMPTS
+            );
+        }
+        Err("Another device kept changing the remote; try again".to_string())
+    }
+
+    /// One pass over every file: pull remote changes, push local ones and resolve
+    /// conflicts, then write the manifest. False if another device wrote the
+    /// manifest meanwhile, leaving this pass's pushes uncommitted.
+    async fn attempt(
+        &self,
+        remote: &Remote,
+        journal: &mut Journal,
+        device: &str,
+        uploaded: &mut HashSet<String>,
+        report: &mut SyncReport,
+    ) -> Result<bool, String> {
+        let (mut manifest, etag, exists) = match remote.get(MANIFEST_KEY).await? {
+            Some((body, etag)) => (
+                serde_json::from_slice::<Manifest>(&body).map_err(|e| format!("Invalid sync manifest: {}", e))?,
+                etag,
+                true,
+            ),
+            None => (Manifest::default(), None, false),
+        };
+        let referenced = manifest.objects();
+        uploaded.extend(referenced.iter().cloned());
+        let snapshot = journal.clone();
+        let local = filesystem::blocking(move || scan_local(&snapshot)).await?;
+        let keys: BTreeSet<String> = local
+            .keys()
+            .chain(manifest.files.keys())
+            .chain(journal.files.keys())
+            .cloned()
+            .collect();
+
+        let mut pushes = Vec::new();
+        for key in keys {
+            let file = local.get(&key);
+            let entry = manifest.files.get(&key).map(|entry| entry.current.clone());
+            let base = journal.files.get(&key);
+            let local_hash = file.map(|f| f.hash.as_str());
+            let remote_hash = entry.as_ref().and_then(|e| e.hash.clone());
+
+            if local_hash == remote_hash.as_deref() {
+                match file {
+                    Some(file) => journal.files.insert(key, file.synced(entry.map_or(0, |e| e.version))),
+                    None => journal.files.remove(&key),
+                };
+                continue;
+            }
+
+            let local_changed = base.map(|b| b.hash.as_str()) != local_hash;
+            // A file missing from the manifest, rather than deleted in it, is pushed again
+            let remote_changed = match (&entry, base) {
+                (Some(entry), Some(base)) => entry.version != base.version,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            let entry = match entry {
+                Some(entry) if remote_changed => entry,
+                _ => {
+                    pushes.push(self.push(remote, &mut manifest, uploaded, &key, file, device).await?);
+                    continue;
+                }
+            };
+            let Some(file) = file.filter(|_| local_changed) else {
+                self.pull(remote, journal, &key, &entry, report).await?;
+                continue;
+            };
+            if remote_hash.is_none() {
+                // Edited here after being deleted there: the edit wins
+                pushes.push(
+                    self.push(remote, &mut manifest, uploaded, &key, Some(file), device)
+                        .await?,
+                );
+                continue;
+            }
+
+            let remote_newer = chrono::DateTime::parse_from_rfc3339(&entry.modified_at)
+                .is_ok_and(|modified| modified.timestamp_millis() > file.modified_ms);
+            let copy = conflict_key(&key, device);
+            if let Some(copy) = &copy {
+                // The losing side is saved under a new name and pushed with the rest
+                let data = if remote_newer {
+                    let path = file.path.clone();
+                    filesystem::blocking_fs(move || crate::thread_compression::read_thread_file(&path)).await?
+                } else {
+                    self.download(remote, remote_hash.as_deref().unwrap_or_default())
+                        .await?
+                };
+                let hash = sha256(&data);
+                self.write_local(copy, data).await?;
+                let path = local_path(copy)?;
+                let (len, modified_ms) = filesystem::blocking({
+                    let path = path.clone();
+                    move || stat(&path).ok_or_else(|| format!("Failed to read {}", path.display()))
+                })
+                .await?;
+                let copied = LocalFile {
+                    path,
+                    hash,
+                    len,
+                    modified_ms,
+                };
+                pushes.push(
+                    self.push(remote, &mut manifest, uploaded, copy, Some(&copied), device)
+                        .await?,
+                );
+            }
+            report.conflicts.push(SyncConflict {
+                key: key.clone(),
+                kept: if remote_newer { "remote" } else { "local" },
+                copy,
+            });
+            if remote_newer {
+                self.pull(remote, journal, &key, &entry, report).await?;
+            } else {
+                pushes.push(
+                    self.push(remote, &mut manifest, uploaded, &key, Some(file), device)
+                        .await?,
+                );
+            }
+        }
+
+        if !pushes.is_empty() {
+            let body =
+                serde_json::to_vec(&manifest).map_err(|e| format!("Failed to serialize sync manifest: {}", e))?;
+            let precondition = match (&etag, exists) {
+                (Some(etag), _) => Precondition::IfMatch(etag),
+                (None, false) => Precondition::IfNoneMatch,
+                // A server that doesn't give ETags can't be written conditionally
+                (None, true) => Precondition::None,
+            };
+            if !remote.put(MANIFEST_KEY, body, precondition).await? {
+                return Ok(false);
+            }
+        }
+
+        for PendingPush { key, synced } in pushes {
+            match synced {
+                Some(synced) => {
+                    journal.files.insert(key.clone(), synced);
+                    report.pushed.push(key);
+                }
+                None => {
+                    journal.files.remove(&key);
+                    report.deleted_remote.push(key);
+                }
+            }
+        }
+
+        // Versions that fell out of every file's history
+        let current = manifest.objects();
+        for hash in referenced.difference(&current) {
+            if let Err(e) = remote.delete(&object_key(hash)).await {
+                log::warn!("Failed to delete unused sync object {}: {}", hash, e);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Upload a local file, or record its deletion, in the manifest
+    async fn push(
+        &self,
+        remote: &Remote,
+        manifest: &mut Manifest,
+        uploaded: &mut HashSet<String>,
+        key: &str,
+        file: Option<&LocalFile>,
+        device: &str,
+    ) -> Result<PendingPush, String> {
+        let Some(file) = file else {
+            manifest.record(key, None, 0, chrono::Utc::now().to_rfc3339(), device);
+            return Ok(PendingPush {
+                key: key.to_string(),
+                synced: None,
+            });
+        };
+
+        let path = file.path.clone();
+        let data = filesystem::blocking_fs(move || crate::thread_compression::read_thread_file(&path)).await?;
+        // The file may have changed since it was hashed; what's uploaded is what's recorded
+        let hash = sha256(&data);
+        let size = data.len() as u64;
+        if !uploaded.contains(&hash) {
+            remote.put(&object_key(&hash), data, Precondition::None).await?;
+            uploaded.insert(hash.clone());
+        }
+        let modified_at = chrono::DateTime::from_timestamp_millis(file.modified_ms)
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339();
+        let version = manifest.record(key, Some(hash.clone()), size, modified_at, device);
+        Ok(PendingPush {
+            key: key.to_string(),
+            synced: Some(SyncedFile {
+                hash,
+                version,
+                len: file.len,
+                modified_ms: file.modified_ms,
+            }),
+        })
+    }
+
+    /// An object's content, checked against its hash
+    async fn download(&self, remote: &Remote, hash: &str) -> Result<Vec<u8>, String> {
+        let (data, _) = remote
+            .get(&object_key(hash))
+            .await?
+            .ok_or_else(|| format!("Sync object {} is missing from the remote", hash))?;
+        if sha256(&data) != hash {
+            return Err(format!("Sync object {} is corrupt", hash));
+        }
+        Ok(data)
+    }
+
+    /// Make the local file match the remote's current version, deleting it if that's
+    /// a deletion
+    async fn pull(
+        &self,
+        remote: &Remote,
+        journal: &mut Journal,
+        key: &str,
+        entry: &RemoteVersion,
+        report: &mut SyncReport,
+    ) -> Result<(), String> {
+        let Some(hash) = &entry.hash else {
+            self.delete_local(key).await?;
+            journal.files.remove(key);
+            report.deleted_local.push(key.to_string());
+            return Ok(());
+        };
+
+        let data = self.download(remote, hash).await?;
+        self.write_local(key, data).await?;
+        let path = local_path(key)?;
+        let (len, modified_ms) = filesystem::blocking(move || {
+            stat(&path).ok_or_else(|| format!("Failed to read {} after syncing it", path.display()))
+        })
+        .await?;
+        journal.files.insert(
+            key.to_string(),
+            SyncedFile {
+                hash: hash.clone(),
+                version: entry.version,
+                len,
+                modified_ms,
+            },
+        );
+        report.pulled.push(key.to_string());
+        Ok(())
+    }
+
+    /// Replace a local file. Threads are replaced with appends held back and any
+    /// compressed copy removed.
+    async fn write_local(&self, key: &str, data: Vec<u8>) -> Result<(), String> {
+        let path = local_path(key)?;
+        let Some(thread_id) = thread_id(key).map(str::to_string) else {
+            filesystem::blocking(move || write_atomic(&path, &data)).await?;
+            if let Some(blueprint_id) = key
+                .strip_prefix("blueprints/")
+                .and_then(|name| name.strip_suffix(".json"))
+            {
+                self.bus.publish(
+                    "blueprint-changed",
+                    serde_json::json!({ "blueprint_id": blueprint_id, "change": "synced" }),
+                );
+            }
+            return Ok(());
+        };
+
+        let id = thread_id.clone();
+        let replace = filesystem::blocking(move || {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create threads directory: {}", e))?;
+            }
+            let _lock = filesystem::lock_thread_blocking(&id)?;
+            filesystem::replace_thread_file(&path, &data)?;
+            let compressed = crate::thread_compression::compressed_path(&id)?;
+            if compressed.exists() {
+                fs::remove_file(&compressed)
+                    .map_err(|e| format!("Failed to remove {}: {}", compressed.display(), e))?;
+            }
+            filesystem::clear_event_ids(&id);
+            Ok(())
+        });
+        match self.app_handle.try_state::<Arc<AppendBuffer>>() {
+            Some(appends) => appends.exclusive(&thread_id, replace).await?,
+            None => replace.await?,
+        }
+        self.bus.publish(
+            "thread-changed",
+            serde_json::json!({ "thread_id": thread_id, "change": "synced" }),
+        );
+        Ok(())
+    }
+
+    /// Delete a local file deleted on another device; threads go to the trash
+    async fn delete_local(&self, key: &str) -> Result<(), String> {
+        let path = local_path(key)?;
+        if let Some(thread_id) = thread_id(key).map(str::to_string) {
+            if let Some(appends) = self.app_handle.try_state::<Arc<AppendBuffer>>() {
+                appends.close(&thread_id).await?;
+            }
+            match filesystem::delete_thread(thread_id.clone(), "sync").await {
+                Ok(_) => {}
+                Err(e) if e.kind == filesystem::FsErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            self.bus.publish(
+                "thread-changed",
+                serde_json::json!({ "thread_id": thread_id, "change": "deleted" }),
+            );
+            return Ok(());
+        }
+        let key = key.to_string();
+        filesystem::blocking(move || match fs::remove_file(&path) {
+            Ok(()) => {
+                crate::audit::record(crate::audit::AuditEntry::new("sync", "delete", "sync", key, true).paths([&path]));
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to delete {}: {}", path.display(), e)),
+        })
+        .await
+    }
+
+    /// Sync every `interval_minutes` while sync is on
+    pub fn start(self: &Arc<Self>) {
+        let engine = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            // The first check waits, leaving startup to finish
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + CHECK_INTERVAL, CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let Some(engine) = engine.upgrade() else { break };
+                let settings = engine.settings();
+                if !settings.enabled || settings.remote.is_none() || settings.interval_minutes == 0 {
+                    continue;
+                }
+                let due = engine
+                    .status()
+                    .last_sync_at
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+                    .is_none_or(|at| {
+                        chrono::Utc::now().signed_duration_since(at)
+                            >= chrono::Duration::minutes(i64::from(settings.interval_minutes))
+                    });
+                if due {
+                    // Failures are logged and shown in the status
+                    let _ = engine.sync_now().await;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,240 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::ChimeraError;
+use crate::event_bus::EventBus;
+
+/// Least time between two `task-progress` events for a task; starting and
+/// finishing are always sent
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Finished tasks kept for `list_tasks`, newest first
+const KEEP_FINISHED: usize = 20;
+
+/// Message blocking work returns when it notices it was cancelled
+const CANCELLED: &str = "Cancelled";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A long operation as `list_tasks` and `task-progress` events describe it
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub id: String,
+    /// What is running, e.g. `workspace_export`, `backup` or `index_rebuild`
+    pub kind: &'static str,
+    pub label: String,
+    pub state: TaskState,
+    /// 0 to 100; 0 until the operation knows how much there is to do
+    pub percent: f64,
+    pub done: usize,
+    pub total: usize,
+    /// Item being worked on, e.g. a thread id
+    pub current: Option<String>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct Task {
+    info: Mutex<TaskInfo>,
+    token: CancellationToken,
+    last_event: Mutex<Instant>,
+}
+
+/// Handed to a running operation to report progress and notice cancellation
+#[derive(Clone)]
+pub struct TaskProgress {
+    task: Arc<Task>,
+    bus: Arc<EventBus>,
+}
+
+impl TaskProgress {
+    /// Record `done` of `total` items, `current` being the one in hand
+    pub fn report(&self, done: usize, total: usize, current: impl Into<String>) {
+        let info = {
+            let mut info = self.task.info.lock().unwrap();
+            info.done = done;
+            info.total = total;
+            info.percent = if total == 0 {
+                0.0
+            } else {
+                (done as f64 * 100.0 / total as f64).min(100.0)
+            };
+            info.current = Some(current.into());
+
+            let mut last_event = self.task.last_event.lock().unwrap();
+            if last_event.elapsed() < PROGRESS_INTERVAL && done < total {
+                return;
+            }
+            *last_event = Instant::now();
+            info.clone()
+        };
+        self.bus.publish("task-progress", info);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.task.token.is_cancelled()
+    }
+
+    /// An error once the task is cancelled, for blocking loops to return with `?`
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Runs long filesystem operations (exports, backups, index rebuilds) as tokio
+/// tasks that report `task-progress` events and can be cancelled. Async work is
+/// dropped at its next await; blocking work stops when it next calls
+/// `TaskProgress::check`.
+pub struct TaskManager {
+    bus: Arc<EventBus>,
+    tasks: Mutex<HashMap<String, Arc<Task>>>,
+}
+
+impl TaskManager {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            bus,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `operation` as a task and wait for it. Errors with `Cancelled` if
+    /// `cancel` was called before it finished.
+    pub async fn run<T, F, Fut>(
+        &self,
+        kind: &'static str,
+        label: impl Into<String>,
+        operation: F,
+    ) -> Result<T, ChimeraError>
+    where
+        F: FnOnce(TaskProgress) -> Fut,
+        Fut: Future<Output = Result<T, ChimeraError>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+        let task = Arc::new(Task {
+            info: Mutex::new(TaskInfo {
+                id: id.clone(),
+                kind,
+                label: label.into(),
+                state: TaskState::Running,
+                percent: 0.0,
+                done: 0,
+                total: 0,
+                current: None,
+                error: None,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                finished_at: None,
+            }),
+            token: CancellationToken::new(),
+            last_event: Mutex::new(Instant::now()),
+        });
+        self.insert(id.clone(), task.clone());
+        self.bus.publish("task-progress", task.info.lock().unwrap().clone());
+        log::info!("Started {} task {}", kind, id);
+
+        let token = task.token.clone();
+        let future = operation(TaskProgress {
+            task: task.clone(),
+            bus: self.bus.clone(),
+        });
+        // Spawned so a caller that goes away (e.g. a reloaded window) doesn't drop it half done
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::select! {
+                result = future => Some(result),
+                _ = token.cancelled() => None,
+            }
+        });
+        let result = match handle.await {
+            Ok(Some(result)) => result,
+            Ok(None) => Err(ChimeraError::Cancelled(CANCELLED.to_string())),
+            Err(e) => Err(ChimeraError::Internal(format!("{} task failed: {}", kind, e))),
+        };
+        let result = match result {
+            Err(e) if task.token.is_cancelled() && !matches!(e, ChimeraError::Cancelled(_)) => {
+                Err(ChimeraError::Cancelled(CANCELLED.to_string()))
+            }
+            result => result,
+        };
+
+        let info = {
+            let mut info = task.info.lock().unwrap();
+            info.state = match &result {
+                Ok(_) => TaskState::Completed,
+                Err(ChimeraError::Cancelled(_)) => TaskState::Cancelled,
+                Err(_) => TaskState::Failed,
+            };
+            if result.is_ok() {
+                info.percent = 100.0;
+            }
+            info.error = result.as_ref().err().map(|e| e.to_string());
+            info.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            info.clone()
+        };
+        log::info!("{} task {} {:?}", kind, id, info.state);
+        self.bus.publish("task-progress", info);
+        result
+    }
+
+    fn insert(&self, id: String, task: Arc<Task>) {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.insert(id, task);
+
+        let mut finished: Vec<(String, String)> = tasks
+            .iter()
+            .filter_map(|(id, task)| task.info.lock().unwrap().finished_at.clone().map(|at| (at, id.clone())))
+            .collect();
+        if finished.len() > KEEP_FINISHED {
+            finished.sort();
+            for (_, id) in &finished[..finished.len() - KEEP_FINISHED] {
+                tasks.remove(id);
+            }
+        }
+    }
+
+    /// Running tasks and recently finished ones, newest first
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|task| task.info.lock().unwrap().clone())
+            .collect();
+        tasks.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        tasks
+    }
+
+    /// Ask a task to stop. Returns it as it stands; a finished task is left as it was.
+    pub fn cancel(&self, task_id: &str) -> Result<TaskInfo, ChimeraError> {
+        let task = self
+            .tasks
+            .lock()
+            .unwrap()
+            .get(task_id)
+            .cloned()
+            .ok_or_else(|| ChimeraError::NotFound(format!("Task not found: {}", task_id)))?;
+        let info = task.info.lock().unwrap().clone();
+        if info.state == TaskState::Running {
+            log::info!("Cancelling {} task {}", info.kind, task_id);
+            task.token.cancel();
+        }
+        Ok(info)
+    }
+}
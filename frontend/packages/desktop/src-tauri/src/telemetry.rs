@@ -0,0 +1,103 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::metrics;
+
+/// Default service name reported to the collector
+const DEFAULT_SERVICE_NAME: &str = "chimera-desktop";
+
+/// The installed provider, kept so pending spans can be flushed on exit
+static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
+/// Resolve the OTLP/HTTP traces endpoint, appending `/v1/traces` to a bare collector URL
+fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    }
+}
+
+/// Build the OTLP tracer provider when `CHIMERA_OTLP_ENDPOINT` is set
+fn otlp_provider() -> Option<TracerProvider> {
+    let endpoint = std::env::var("CHIMERA_OTLP_ENDPOINT").ok().filter(|e| !e.is_empty())?;
+    let service_name =
+        std::env::var("CHIMERA_OTLP_SERVICE_NAME").unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(&endpoint))
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            log::error!("Failed to create OTLP exporter: {}", e);
+            return None;
+        }
+    };
+
+    log::info!("Exporting traces to {}", traces_endpoint(&endpoint));
+
+    Some(
+        TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(Resource::new([
+                KeyValue::new("service.name", service_name),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]))
+            .build(),
+    )
+}
+
+/// Install the tracing subscriber: the OTLP exporter when `CHIMERA_OTLP_ENDPOINT`
+/// is set and command timing when metrics are enabled. Must be called from within
+/// the async runtime. Without either, spans are no-ops.
+pub fn init() {
+    let provider = otlp_provider();
+    let metrics_layer = metrics::enabled().then_some(metrics::CommandTimingLayer);
+
+    if provider.is_none() && metrics_layer.is_none() {
+        return;
+    }
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    if let Err(e) = tracing_subscriber::registry().with(otel_layer).with(metrics_layer).try_init() {
+        log::error!("Failed to install tracing subscriber: {}", e);
+        return;
+    }
+
+    if let Some(provider) = provider {
+        let _ = PROVIDER.set(provider);
+    }
+}
+
+/// Flush and stop the exporter, if one was installed
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            log::warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// W3C trace context headers for the current span, for outgoing backend requests
+pub fn trace_headers() -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
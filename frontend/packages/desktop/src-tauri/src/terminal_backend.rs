@@ -1,10 +1,20 @@
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
-use std::collections::HashMap;
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tokio::sync::{oneshot, Mutex, Notify};
+
+use crate::error::ChimeraError;
+use crate::event_bus::EventBus;
+use crate::job_object::{self, JobObject};
+use crate::supervisor::{TaskGroup, SHUTDOWN_TIMEOUT};
+use crate::terminal_commands::CommandSpec;
+use crate::terminal_profiles::{ProfileKind, TerminalProfile};
+use crate::terminal_keys::{self, KeyModes};
+use crate::terminal_links::{LinkScanner, TerminalLink};
 
 /// Deployment mode for the terminal backend
 #[derive(Debug, Clone, Copy)]
@@ -15,27 +25,539 @@ enum DeploymentMode {
     Production,
 }
 
+/// PTY read size; one buffer is reused for the life of the terminal
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Where a terminal's output goes
+enum OutputSink {
+    /// `terminal_output` events on the event bus (text)
+    Bus,
+    /// Raw bytes over each channel attached by a frontend view, keyed by subscription id
+    Channels(HashMap<u64, Channel<InvokeResponseBody>>),
+    /// Dropped after counting (throughput measurement)
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Discard,
+    /// Copied to an in-process receiver (latency measurement)
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Probe(tokio::sync::mpsc::UnboundedSender<Vec<u8>>),
+}
+
+/// Default recent output kept per terminal so a reloaded frontend can redraw it
+const DEFAULT_SCROLLBACK_BYTES: usize = 1024 * 1024;
+
+/// Scrollback kept per terminal (`CHIMERA_TERMINAL_SCROLLBACK_BYTES`, default 1 MB)
+fn scrollback_limit() -> usize {
+    static LIMIT: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+    *LIMIT.get_or_init(|| {
+        std::env::var("CHIMERA_TERMINAL_SCROLLBACK_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_SCROLLBACK_BYTES)
+    })
+}
+
+/// Default window output is gathered over before it goes to the frontend, one frame at 60 Hz
+const DEFAULT_BATCH_MS: u64 = 16;
+
+/// Default cap on output sent to the frontend per terminal
+const DEFAULT_MAX_BYTES_PER_SEC: u64 = 8 * 1024 * 1024;
+
+/// How long output is gathered before it's sent to the frontend
+/// (`CHIMERA_TERMINAL_BATCH_MS`, default 16; 0 sends every read as it arrives)
+fn batch_window() -> Duration {
+    static WINDOW: std::sync::OnceLock<Duration> = std::sync::OnceLock::new();
+    *WINDOW.get_or_init(|| {
+        Duration::from_millis(
+            std::env::var("CHIMERA_TERMINAL_BATCH_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_BATCH_MS),
+        )
+    })
+}
+
+/// Most output per second sent to the frontend for one terminal
+/// (`CHIMERA_TERMINAL_MAX_BYTES_PER_SEC`, default 8 MB; 0 for no cap)
+fn rate_limit() -> Option<u64> {
+    static LIMIT: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    let limit = *LIMIT.get_or_init(|| {
+        std::env::var("CHIMERA_TERMINAL_MAX_BYTES_PER_SEC")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BYTES_PER_SEC)
+    });
+    (limit > 0).then_some(limit)
+}
+
+/// Counts of a terminal's output delivery to the frontend
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct OutputStats {
+    /// Bytes read from the PTY
+    pub read_bytes: u64,
+    /// Bytes sent as events or over channels
+    pub sent_bytes: u64,
+    /// Batches sent
+    pub batches: u64,
+    /// Bytes skipped to stay under the rate cap; they are still in the scrollback
+    pub dropped_bytes: u64,
+    /// Times the rate cap stopped output until the view reloaded from the scrollback
+    pub resyncs: u64,
+}
+
+/// Output dropped event payload. Skipped output can cut an escape sequence or a
+/// screen or mode switch, so no more output is sent for the terminal until the view
+/// resets and redraws from `get_terminal_scrollback`.
+#[derive(Clone, serde::Serialize)]
+struct TerminalOutputDroppedEvent<'a> {
+    terminal_id: &'a str,
+    bytes: usize,
+    total_dropped: u64,
+    /// Always `"scrollback"`: where the view reloads from to resume output
+    reload: &'static str,
+}
+
+/// Output routing plus recent history for one terminal
+struct TerminalOutput {
+    sink: OutputSink,
+    /// Output read but not yet sent to a `Bus` or `Channels` sink
+    pending: Vec<u8>,
+    last_flush: Instant,
+    /// Bytes the rate cap allows right now, refilled over time up to one second's worth
+    allowance: f64,
+    allowance_at: Instant,
+    stats: OutputStats,
+    /// Set when the rate cap skipped output; output is skipped until the scrollback
+    /// is next read
+    resync: bool,
+    /// Set once the PTY has closed and the last output is flushed
+    closed: bool,
+    scrollback: VecDeque<u8>,
+    /// Input modes the program in the terminal has set
+    modes: KeyModes,
+    /// Asciicast file the output is also being written to
+    recording: Option<Recording>,
+    /// Finds the links sent with `terminal_output` events
+    links: LinkScanner,
+    /// When output was last read from the PTY
+    last_read_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TerminalOutput {
+    fn new(sink: OutputSink, cwd: Option<std::path::PathBuf>) -> Self {
+        let now = Instant::now();
+        Self {
+            sink,
+            pending: Vec::new(),
+            last_flush: now,
+            allowance: rate_limit().unwrap_or(0) as f64,
+            allowance_at: now,
+            stats: OutputStats::default(),
+            resync: false,
+            closed: false,
+            scrollback: VecDeque::new(),
+            modes: KeyModes::default(),
+            recording: None,
+            links: LinkScanner::new(cwd),
+            last_read_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Skip pending output once it's more than the rate cap allows. Part of a stream
+    /// can't be skipped without risking a cut escape sequence, so everything is
+    /// skipped until the view redraws from the scrollback, which has all of it.
+    fn throttle(&mut self, terminal_id: &str, event_bus: &EventBus) {
+        if self.resync {
+            self.stats.dropped_bytes += self.pending.len() as u64;
+            self.pending.clear();
+            return;
+        }
+        let Some(limit) = rate_limit() else { return };
+        let now = Instant::now();
+        let refill = now.duration_since(self.allowance_at).as_secs_f64() * limit as f64;
+        self.allowance = (self.allowance + refill).min(limit as f64);
+        self.allowance_at = now;
+
+        if self.pending.len() > self.allowance as usize {
+            let dropped = std::mem::take(&mut self.pending).len();
+            self.resync = true;
+            self.stats.dropped_bytes += dropped as u64;
+            self.stats.resyncs += 1;
+            event_bus.publish(
+                "terminal_output_dropped",
+                TerminalOutputDroppedEvent {
+                    terminal_id,
+                    bytes: dropped,
+                    total_dropped: self.stats.dropped_bytes,
+                    reload: "scrollback",
+                },
+            );
+            return;
+        }
+        self.allowance -= self.pending.len() as f64;
+    }
+
+    /// Send pending output to the sink as one batch. With `last`, a UTF-8 sequence
+    /// cut off at the end is sent too rather than held for the next read.
+    fn flush(&mut self, terminal_id: &str, event_bus: &EventBus, last: bool) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.last_flush = Instant::now();
+        self.throttle(terminal_id, event_bus);
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let sent = match &mut self.sink {
+            OutputSink::Channels(channels) => {
+                let bytes = std::mem::take(&mut self.pending);
+                channels.retain(|subscription, channel| {
+                    match channel.send(InvokeResponseBody::Raw(bytes.clone())) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            log::warn!("Terminal {} output channel {} dropped: {}", terminal_id, subscription, e);
+                            false
+                        }
+                    }
+                });
+                if channels.is_empty() {
+                    self.sink = OutputSink::Bus;
+                }
+                bytes.len()
+            }
+            OutputSink::Bus => {
+                let complete = if last { self.pending.len() } else { utf8_complete_len(&self.pending) };
+                if complete == 0 {
+                    return;
+                }
+                let data = String::from_utf8_lossy(&self.pending[..complete]);
+                let links = self.links.scan(&data);
+                event_bus.publish(
+                    "terminal_output",
+                    TerminalOutputEvent {
+                        terminal_id,
+                        data: &data,
+                        links: &links,
+                    },
+                );
+                self.pending.drain(..complete);
+                complete
+            }
+            // Measurement sinks take output as it's read
+            OutputSink::Probe(_) | OutputSink::Discard => {
+                let bytes = self.pending.len();
+                self.pending.clear();
+                bytes
+            }
+        };
+        self.stats.sent_bytes += sent as u64;
+        self.stats.batches += 1;
+    }
+
+    /// The scrollback as one contiguous buffer
+    fn scrollback_bytes(&self) -> Vec<u8> {
+        let (front, back) = self.scrollback.as_slices();
+        let mut bytes = Vec::with_capacity(front.len() + back.len());
+        bytes.extend_from_slice(front);
+        bytes.extend_from_slice(back);
+        bytes
+    }
+}
+
+/// Output being recorded to an asciicast v2 file: a JSON header line, then one
+/// `[seconds, "o", text]` line per chunk of output and `[seconds, "r", "COLSxROWS"]`
+/// per resize
+struct Recording {
+    path: std::path::PathBuf,
+    writer: std::io::BufWriter<std::fs::File>,
+    started: Instant,
+    /// Bytes of a UTF-8 sequence split across reads, carried into the next event
+    pending: Vec<u8>,
+    bytes: u64,
+}
+
+impl Recording {
+    fn start(path: std::path::PathBuf, file: std::fs::File, cols: u16, rows: u16) -> std::io::Result<Self> {
+        let mut writer = std::io::BufWriter::new(file);
+        let header = serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "env": { "TERM": "xterm-256color" },
+        });
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(Self {
+            path,
+            writer,
+            started: Instant::now(),
+            pending: Vec::new(),
+            bytes: 0,
+        })
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> std::io::Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        serde_json::to_writer(&mut self.writer, &(elapsed, code, data))?;
+        self.writer.write_all(b"\n")?;
+        // Flushed per event so the file is usable even if the app dies mid-session
+        self.writer.flush()
+    }
+
+    fn output(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.pending.extend_from_slice(bytes);
+        let complete = utf8_complete_len(&self.pending);
+        if complete > 0 {
+            let data = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+            self.event("o", &data)?;
+            self.pending.drain(..complete);
+        }
+        self.bytes += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    fn finish(mut self) -> RecordingSummary {
+        if !self.pending.is_empty() {
+            let data = String::from_utf8_lossy(&self.pending).into_owned();
+            if let Err(e) = self.event("o", &data) {
+                log::warn!("Failed to finish terminal recording {}: {}", self.path.display(), e);
+            }
+        }
+        RecordingSummary {
+            path: self.path.to_string_lossy().into_owned(),
+            bytes: self.bytes,
+            duration_secs: self.started.elapsed().as_secs_f64(),
+        }
+    }
+}
+
+/// A finished terminal recording
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RecordingSummary {
+    pub path: String,
+    /// Output bytes recorded
+    pub bytes: u64,
+    pub duration_secs: f64,
+}
+
+/// How `export_buffer` writes scrollback
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollbackFormat {
+    /// Escape sequences stripped and carriage-return overwrites applied
+    #[default]
+    Text,
+    /// Exactly the bytes the terminal printed
+    Ansi,
+}
+
+/// Setup applied to a new terminal of any type
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct SpawnOptions {
+    /// Variables set in the terminal's environment, over the inherited ones
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Typed into the terminal once it starts, followed by Enter, e.g. to activate
+    /// a virtualenv
+    #[serde(default)]
+    pub init_command: Option<String>,
+    /// Label of the window the terminal's events go to; all windows when unset
+    #[serde(skip)]
+    pub window: Option<String>,
+    /// Terminal group to open it in; the group's directory is the default working
+    /// directory
+    #[serde(default)]
+    pub group_id: Option<String>,
+}
+
+/// A signal for `signal_terminal`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum TerminalSignal {
+    #[serde(rename = "SIGINT", alias = "INT")]
+    Interrupt,
+    #[serde(rename = "SIGTERM", alias = "TERM")]
+    Terminate,
+    #[serde(rename = "SIGKILL", alias = "KILL")]
+    Kill,
+}
+
+/// Current state of a terminal, for rebuilding the frontend after a reload
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TerminalSnapshot {
+    pub terminal_id: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub scrollback: String,
+}
+
 /// Represents a single terminal instance
 struct TerminalInstance {
     id: String,
     pty_master: Box<dyn MasterPty + Send>,
     cols: u16,
     rows: u16,
+    output: Arc<StdMutex<TerminalOutput>>,
+    pid: Option<u32>,
+    cwd: Option<String>,
+    group_id: Option<String>,
+    started_at: chrono::DateTime<chrono::Utc>,
+    /// When input was last written to the PTY
+    last_write_at: chrono::DateTime<chrono::Utc>,
+    /// Last activity `terminal_idle` was published for, so it's published once
+    /// per idle stretch
+    idle_reported_for: Option<chrono::DateTime<chrono::Utc>>,
+    /// Handle on the child for killing it; the I/O task owns the `Child` to wait on it
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    /// Kills the shell and anything it started when the terminal goes away
+    _job: Option<JobObject>,
+}
+
+impl TerminalInstance {
+    fn info(&self) -> TerminalInfo {
+        TerminalInfo {
+            terminal_id: self.id.clone(),
+            pid: self.pid,
+            status: "running".to_string(),
+            exit_code: None,
+            signal: None,
+            cwd: self.cwd.clone(),
+            group_id: self.group_id.clone(),
+            started_at: self.started_at.to_rfc3339(),
+            exited_at: None,
+            uptime_secs: (chrono::Utc::now() - self.started_at).num_milliseconds() as f64 / 1000.0,
+            output: self.output.lock().unwrap().stats,
+        }
+    }
+
+    /// Latest input or output
+    fn last_active_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.output.lock().unwrap().last_read_at.max(self.last_write_at)
+    }
+
+    fn activity(&self, idle_after: Option<Duration>) -> TerminalActivity {
+        let last_read_at = self.output.lock().unwrap().last_read_at;
+        let idle = (chrono::Utc::now() - last_read_at.max(self.last_write_at)).to_std().unwrap_or_default();
+        TerminalActivity {
+            terminal_id: self.id.clone(),
+            last_read_at: last_read_at.to_rfc3339(),
+            last_write_at: self.last_write_at.to_rfc3339(),
+            idle_secs: idle.as_secs(),
+            idle: idle_after.is_some_and(|after| idle >= after),
+        }
+    }
+}
+
+/// When a terminal last had input or output, for idle badges
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TerminalActivity {
+    pub terminal_id: String,
+    pub last_read_at: String,
+    pub last_write_at: String,
+    /// Seconds since the latest input or output
+    pub idle_secs: u64,
+    /// Whether it's past the idle policy's threshold; always false with no policy
+    pub idle: bool,
+}
+
+/// Terminal idle event payload
+#[derive(Clone, serde::Serialize)]
+struct TerminalIdleEvent<'a> {
+    terminal_id: &'a str,
+    idle_secs: u64,
+    /// Whether the policy is closing it
+    closing: bool,
+}
+
+/// Exited terminals remembered for `get_terminal_info`
+const EXITED_HISTORY: usize = 32;
+
+/// Process details and lifecycle of a terminal
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TerminalInfo {
+    pub terminal_id: String,
+    pub pid: Option<u32>,
+    /// `running` or `exited`
+    pub status: String,
+    pub exit_code: Option<u32>,
+    /// Name of the signal that ended the process, if one did
+    pub signal: Option<String>,
+    pub cwd: Option<String>,
+    pub group_id: Option<String>,
+    pub started_at: String,
+    pub exited_at: Option<String>,
+    /// Seconds the process ran, or has been running
+    pub uptime_secs: f64,
+    pub output: OutputStats,
+}
+
+/// Terminals opened together, e.g. the shells of one project, and closed together
+struct TerminalGroup {
+    name: String,
+    cwd: Option<String>,
+    created_at: String,
+}
+
+/// A terminal group with its open terminals, as listed and in `terminal_group_status` events
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TerminalGroupInfo {
+    pub group_id: String,
+    pub name: String,
+    pub cwd: Option<String>,
+    pub created_at: String,
+    pub terminal_ids: Vec<String>,
+    /// `created`, `running` (has open terminals), `empty` or `closed`
+    pub status: String,
+}
+
+/// Result of a terminal output throughput measurement
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ThroughputReport {
+    pub bytes: u64,
+    pub seconds: f64,
+    pub megabytes_per_second: f64,
+    /// Process CPU time spent during the run (unix only)
+    pub cpu_seconds: Option<f64>,
+    /// CPU time over wall time; 1.0 means one core fully busy
+    pub cpu_utilization: Option<f64>,
 }
 
 /// Manages multiple terminal instances
 pub struct TerminalBackend {
     terminals: Arc<Mutex<HashMap<String, TerminalInstance>>>,
+    exited: Arc<StdMutex<VecDeque<TerminalInfo>>>,
+    groups: Arc<StdMutex<HashMap<String, TerminalGroup>>>,
     next_id: AtomicUsize,
+    next_group_id: AtomicUsize,
+    next_subscription: AtomicU64,
     mode: DeploymentMode,
-    app_handle: AppHandle,
+    event_bus: Arc<EventBus>,
+    /// Per-terminal I/O tasks, awaited on shutdown
+    tasks: TaskGroup,
 }
 
 /// Terminal output event payload
 #[derive(Clone, serde::Serialize)]
-struct TerminalOutputEvent {
-    terminal_id: String,
-    data: String,
+struct TerminalOutputEvent<'a> {
+    terminal_id: &'a str,
+    data: &'a str,
+    /// URLs and file paths in `data`, left out when there are none
+    #[serde(skip_serializing_if = "<[TerminalLink]>::is_empty")]
+    links: &'a [TerminalLink],
+}
+
+/// Terminal exit event payload
+#[derive(Clone, serde::Serialize)]
+struct TerminalExitedEvent<'a> {
+    terminal_id: &'a str,
+    exit_code: Option<u32>,
+    signal: Option<&'a str>,
+    success: bool,
 }
 
 /// Terminal status event payload
@@ -47,7 +569,7 @@ struct TerminalStatusEvent {
 
 impl TerminalBackend {
     /// Create a new terminal backend
-    pub fn new(app_handle: AppHandle) -> Self {
+    pub fn new(event_bus: Arc<EventBus>) -> Self {
         let mode = if std::env::var("CHIMERA_DESKTOP_PRODUCTION").is_ok() {
             log::info!("Terminal backend: Production mode");
             DeploymentMode::Production
@@ -58,29 +580,111 @@ impl TerminalBackend {
 
         Self {
             terminals: Arc::new(Mutex::new(HashMap::new())),
+            exited: Arc::new(StdMutex::new(VecDeque::new())),
+            groups: Arc::new(StdMutex::new(HashMap::new())),
             next_id: AtomicUsize::new(1),
+            next_group_id: AtomicUsize::new(1),
+            next_subscription: AtomicU64::new(1),
             mode,
-            app_handle,
+            event_bus,
+            tasks: TaskGroup::new("terminal"),
         }
     }
 
     /// Spawn a new terminal instance
+    #[tracing::instrument(skip(self), fields(terminal_id))]
     pub async fn spawn_terminal(
         &self,
         terminal_type: String,
         cwd: Option<String>,
-    ) -> Result<String, String> {
+        command: Option<CommandSpec>,
+        options: SpawnOptions,
+    ) -> Result<String, ChimeraError> {
         let terminal_id = format!("terminal_{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        tracing::Span::current().record("terminal_id", terminal_id.as_str());
         log::info!("Spawning terminal {}: type={}", terminal_id, terminal_type);
 
         // Determine working directory
-        let working_dir = if let Some(cwd) = cwd {
+        let working_dir = if let Some(cwd) = cwd.or(self.group_cwd(options.group_id.as_deref())?) {
             std::path::PathBuf::from(cwd)
         } else {
             std::env::current_dir()
-                .map_err(|e| format!("Failed to get current directory: {}", e))?
+                .map_err(|e| ChimeraError::Terminal(format!("Failed to get current directory: {}", e)))?
+        };
+
+        // Types other than `command` are the built-in profiles
+        let cmd = match terminal_type.as_str() {
+            "command" => {
+                let spec = command.as_ref().ok_or("Terminal type \"command\" needs a command")?;
+                let mut cmd = CommandBuilder::new(&spec.command);
+                cmd.args(&spec.args);
+                apply_env(&mut cmd, &spec.env);
+                apply_env(&mut cmd, &options.env);
+                cmd.cwd(&working_dir);
+                cmd
+            }
+            _ => {
+                let profile = TerminalProfile::for_type(&terminal_type)
+                    .ok_or_else(|| format!("Unknown terminal type: {}", terminal_type))?;
+                self.build_profile_command(&profile, &working_dir, &options.env)?
+            }
         };
 
+        let detail = match &command {
+            Some(spec) if terminal_type == "command" => format!("{} {}", spec.command, spec.args.join(" ")),
+            _ => terminal_type,
+        };
+        self.start_terminal(terminal_id, cmd, options, &working_dir, detail).await
+    }
+
+    /// Start `cmd` as terminal `terminal_id`, run the init command and record it in the audit log
+    async fn start_terminal(
+        &self,
+        terminal_id: String,
+        cmd: CommandBuilder,
+        options: SpawnOptions,
+        working_dir: &std::path::Path,
+        detail: String,
+    ) -> Result<String, ChimeraError> {
+        if let Some(window) = &options.window {
+            self.event_bus.assign_terminal(&terminal_id, window);
+        }
+        if let Err(e) = self
+            .spawn_instance(terminal_id.clone(), cmd, OutputSink::Bus, options.group_id.clone())
+            .await
+        {
+            self.event_bus.release_terminal(&terminal_id);
+            return Err(e);
+        }
+        if let Some(group_id) = &options.group_id {
+            publish_group_status(&self.event_bus, &self.groups, &*self.terminals.lock().await, group_id, None);
+        }
+
+        // The PTY holds the input until the shell reads it
+        if let Some(init_command) = options.init_command.as_deref().filter(|c| !c.trim().is_empty()) {
+            log::info!("Running init command in terminal {}", terminal_id);
+            self.write_to_terminal(&terminal_id, &format!("{}\r", init_command.trim_end_matches(['\r', '\n'])))
+                .await?;
+        }
+
+        crate::audit::record_async(
+            crate::audit::AuditEntry::new("terminal", "spawn", "ui", working_dir.display().to_string(), true)
+                .detail(format!("{} as {}", detail, terminal_id)),
+        )
+        .await;
+
+        Ok(terminal_id)
+    }
+
+    /// Open a PTY, run `cmd` in it and start streaming its output to `sink`.
+    /// The returned task resolves to the number of bytes read once the PTY closes.
+    async fn spawn_instance(
+        &self,
+        terminal_id: String,
+        mut cmd: CommandBuilder,
+        sink: OutputSink,
+        group_id: Option<String>,
+    ) -> Result<oneshot::Receiver<u64>, ChimeraError> {
         // Default terminal size
         let cols = 80;
         let rows = 24;
@@ -94,30 +698,35 @@ impl TerminalBackend {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .map_err(|e| format!("Failed to create PTY: {}", e))?;
-
-        // Build command based on terminal type and deployment mode
-        let mut cmd = match terminal_type.as_str() {
-            "ink-cli" => self.build_ink_cli_command(&working_dir)?,
-            "bash" => {
-                let mut cmd = CommandBuilder::new("bash");
-                cmd.cwd(&working_dir);
-                cmd
-            }
-            _ => return Err(format!("Unknown terminal type: {}", terminal_type)),
-        };
+            .map_err(|e| {
+                crate::metrics::record_terminal_spawn_failure();
+                format!("Failed to create PTY: {}", e)
+            })?;
 
         // Set up environment variables for proper terminal emulation
         cmd.env("TERM", "xterm-256color");
         cmd.env("COLORTERM", "truecolor");
+        crate::terminal_env::fixups().apply(&mut cmd);
+
+        let cwd = cmd.get_cwd().map(|cwd| cwd.to_string_lossy().into_owned());
 
         // Spawn the child process in the PTY
         let child = pty_pair
             .slave
             .spawn_command(cmd)
-            .map_err(|e| format!("Failed to spawn command: {}", e))?;
+            .map_err(|e| {
+                crate::metrics::record_terminal_spawn_failure();
+                format!("Failed to spawn command: {}", e)
+            })?;
 
         log::info!("Terminal {} spawned successfully (PID: {:?})", terminal_id, child.process_id());
+        let job = job_object::attach(child.process_id(), "Terminal");
+
+        let reader = pty_pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to clone PTY reader: {}", e)))?;
+        let output = Arc::new(StdMutex::new(TerminalOutput::new(sink, cwd.as_ref().map(std::path::PathBuf::from))));
 
         // Store the terminal instance
         let instance = TerminalInstance {
@@ -125,6 +734,15 @@ impl TerminalBackend {
             pty_master: pty_pair.master,
             cols,
             rows,
+            output: output.clone(),
+            pid: child.process_id(),
+            cwd,
+            group_id,
+            started_at: chrono::Utc::now(),
+            last_write_at: chrono::Utc::now(),
+            idle_reported_for: None,
+            killer: child.clone_killer(),
+            _job: job,
         };
 
         {
@@ -132,8 +750,8 @@ impl TerminalBackend {
             terminals.insert(terminal_id.clone(), instance);
         }
 
-        // Emit ready status
-        let _ = self.app_handle.emit(
+        // Publish ready status
+        self.event_bus.publish(
             "terminal_status",
             TerminalStatusEvent {
                 terminal_id: terminal_id.clone(),
@@ -142,18 +760,79 @@ impl TerminalBackend {
         );
 
         // Start I/O monitoring task
-        self.start_io_task(terminal_id.clone()).await;
+        Ok(self.start_io_task(terminal_id, reader, output, child))
+    }
 
-        Ok(terminal_id)
+    /// The command a profile runs, in `working_dir`, with the profile's environment
+    /// and then `env` over it
+    fn build_profile_command(
+        &self,
+        profile: &TerminalProfile,
+        working_dir: &std::path::Path,
+        env: &HashMap<String, String>,
+    ) -> Result<CommandBuilder, ChimeraError> {
+        let mut env_with_profile = profile.env.clone();
+        env_with_profile.extend(env.iter().map(|(key, value)| (key.clone(), value.clone())));
+
+        let mut cmd = match (profile.kind, &profile.command) {
+            (ProfileKind::InkCli, _) => return self.build_ink_cli_command(working_dir, &env_with_profile),
+            (ProfileKind::Shell, None) => {
+                let (shell, args) = crate::terminal_commands::default_shell();
+                log::info!("Using shell {} for profile {}", shell, profile.id);
+                let mut cmd = CommandBuilder::new(shell);
+                cmd.args(args);
+                cmd
+            }
+            (ProfileKind::Shell | ProfileKind::Command, Some(command)) => {
+                let mut cmd = CommandBuilder::new(command);
+                cmd.args(&profile.args);
+                cmd
+            }
+            (ProfileKind::Command, None) => return Err(ChimeraError::InvalidInput(format!("Terminal profile {} needs a command", profile.id))),
+        };
+        cmd.cwd(working_dir);
+        apply_env(&mut cmd, &env_with_profile);
+        Ok(cmd)
+    }
+
+    /// Spawn a terminal as a profile describes it. `cwd` and `options` override the
+    /// profile's working directory and are added to its environment.
+    pub async fn spawn_from_profile(
+        &self,
+        profile: &TerminalProfile,
+        cwd: Option<String>,
+        mut options: SpawnOptions,
+    ) -> Result<String, ChimeraError> {
+        let terminal_id = format!("terminal_{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        log::info!("Spawning terminal {} from profile {}", terminal_id, profile.id);
+
+        let cwd = cwd.or(self.group_cwd(options.group_id.as_deref())?);
+        let working_dir = match cwd.or_else(|| profile.cwd.clone()) {
+            Some(cwd) => std::path::PathBuf::from(cwd),
+            None => std::env::current_dir().map_err(|e| ChimeraError::Terminal(format!("Failed to get current directory: {}", e)))?,
+        };
+        let cmd = self.build_profile_command(profile, &working_dir, &options.env)?;
+        if options.init_command.is_none() {
+            options.init_command = profile.init_command.clone();
+        }
+        self.start_terminal(terminal_id, cmd, options, &working_dir, format!("profile {}", profile.id))
+            .await
     }
 
-    /// Build command for ink CLI
-    fn build_ink_cli_command(&self, working_dir: &std::path::Path) -> Result<CommandBuilder, String> {
-        match self.mode {
+    /// Build command for ink CLI, with `env` set for it
+    fn build_ink_cli_command(
+        &self,
+        working_dir: &std::path::Path,
+        env: &HashMap<String, String>,
+    ) -> Result<CommandBuilder, ChimeraError> {
+        let mut cmd = match self.mode {
             DeploymentMode::Development => {
                 // Development: use local npm installation or custom path
-                let ink_cli_path = std::env::var("CHIMERA_INK_CLI_PATH")
-                    .unwrap_or_else(|_| {
+                let ink_cli_path = env
+                    .get("CHIMERA_INK_CLI_PATH")
+                    .cloned()
+                    .or_else(|| std::env::var("CHIMERA_INK_CLI_PATH").ok())
+                    .unwrap_or_else(|| {
                         // Default to node_modules/.bin/ink-cli
                         working_dir
                             .join("node_modules")
@@ -168,7 +847,7 @@ impl TerminalBackend {
                 let mut cmd = CommandBuilder::new("node");
                 cmd.arg(&ink_cli_path);
                 cmd.cwd(working_dir);
-                Ok(cmd)
+                cmd
             }
             DeploymentMode::Production => {
                 // Production: use bundled executable
@@ -177,123 +856,337 @@ impl TerminalBackend {
                     .join("ink-cli");
 
                 if !bundled_exe.exists() {
-                    return Err(format!("Bundled ink CLI not found: {:?}", bundled_exe));
+                    return Err(ChimeraError::Terminal(format!("Bundled ink CLI not found: {:?}", bundled_exe)));
                 }
 
                 log::info!("Using bundled ink CLI: {:?}", bundled_exe);
                 let mut cmd = CommandBuilder::new(bundled_exe);
                 cmd.cwd(working_dir);
-                Ok(cmd)
+                cmd
             }
-        }
+        };
+        apply_env(&mut cmd, env);
+        Ok(cmd)
     }
 
-    /// Start I/O monitoring task for a terminal
-    async fn start_io_task(&self, terminal_id: String) {
+    /// Start I/O monitoring task for a terminal. PTY reads block, so they run on the
+    /// blocking pool; the task resolves to the number of bytes read.
+    fn start_io_task(
+        &self,
+        terminal_id: String,
+        reader: Box<dyn Read + Send>,
+        output: Arc<StdMutex<TerminalOutput>>,
+        mut child: Box<dyn Child + Send + Sync>,
+    ) -> oneshot::Receiver<u64> {
         let terminals = self.terminals.clone();
-        let app_handle = self.app_handle.clone();
-        let id = terminal_id.clone();
-
-        tokio::spawn(async move {
-            // Get the PTY reader
-            let mut reader = {
-                let mut terms = terminals.lock().await;
-                let instance = match terms.get_mut(&id) {
-                    Some(inst) => inst,
-                    None => {
-                        log::error!("Terminal {} not found for I/O task", id);
-                        return;
-                    }
-                };
+        let exited = self.exited.clone();
+        let groups = self.groups.clone();
+        let event_bus = self.event_bus.clone();
 
-                instance.pty_master.try_clone_reader()
-                    .expect("Failed to clone PTY reader")
+        let wake = Arc::new(Notify::new());
+        if !batch_window().is_zero() {
+            flush_batches(terminal_id.clone(), output.clone(), event_bus.clone(), wake.clone());
+        }
+
+        self.tasks.spawn(async move {
+            let id = terminal_id.clone();
+            let bus = event_bus.clone();
+            let (total, status) = tokio::task::spawn_blocking(move || {
+                let total = read_output(&id, reader, &output, &bus, &wake);
+                // Output ends when the PTY closes; reap the process for its exit status
+                (total, child.wait())
+            })
+            .await
+            .unwrap_or_else(|e| {
+                log::error!("Terminal {} I/O task failed: {}", terminal_id, e);
+                (0, Err(std::io::Error::other(e.to_string())))
+            });
+
+            // Clean up terminal instance
+            let instance = {
+                let mut terminals = terminals.lock().await;
+                let instance = terminals.remove(&terminal_id);
+                if let Some(group_id) = instance.as_ref().and_then(|instance| instance.group_id.as_deref()) {
+                    publish_group_status(&event_bus, &groups, &terminals, group_id, None);
+                }
+                instance
             };
+            log::info!("Terminal {} cleaned up", terminal_id);
 
-            // Read from PTY and emit events
-            let mut buffer = [0u8; 8192];
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(0) => {
-                        // EOF - terminal closed
-                        log::info!("Terminal {} closed (EOF)", id);
-                        let _ = app_handle.emit(
-                            "terminal_status",
-                            TerminalStatusEvent {
-                                terminal_id: id.clone(),
-                                status: "closed".to_string(),
-                            },
-                        );
-                        break;
-                    }
-                    Ok(n) => {
-                        // Convert to string (lossy for safety)
-                        let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-
-                        // Emit output event
-                        if let Err(e) = app_handle.emit(
-                            "terminal_output",
-                            TerminalOutputEvent {
-                                terminal_id: id.clone(),
-                                data,
-                            },
-                        ) {
-                            log::error!("Failed to emit terminal output: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Error reading from terminal {}: {}", id, e);
-                        let _ = app_handle.emit(
-                            "terminal_status",
-                            TerminalStatusEvent {
-                                terminal_id: id.clone(),
-                                status: "error".to_string(),
-                            },
-                        );
-                        break;
+            let (exit_code, signal, success) = match &status {
+                Ok(status) => (Some(status.exit_code()), status.signal().map(str::to_string), status.success()),
+                Err(e) => {
+                    log::warn!("Failed to get exit status of terminal {}: {}", terminal_id, e);
+                    (None, None, false)
+                }
+            };
+            log::info!("Terminal {} exited (code: {:?}, signal: {:?})", terminal_id, exit_code, signal);
+            event_bus.publish(
+                "terminal_exited",
+                TerminalExitedEvent {
+                    terminal_id: &terminal_id,
+                    exit_code,
+                    signal: signal.as_deref(),
+                    success,
+                },
+            );
+            event_bus.release_terminal(&terminal_id);
+
+            if let Some(instance) = instance {
+                let mut info = instance.info();
+                info.status = "exited".to_string();
+                info.exit_code = exit_code;
+                info.signal = signal;
+                info.exited_at = Some(chrono::Utc::now().to_rfc3339());
+
+                let mut exited = exited.lock().unwrap();
+                exited.push_back(info);
+                if exited.len() > EXITED_HISTORY {
+                    exited.pop_front();
+                }
+            }
+
+            total
+        })
+    }
+
+    /// Send a terminal's output as raw bytes over `channel`. While any channel is
+    /// attached, output goes only to attached channels instead of bus events.
+    /// Returns a subscription id for `detach_output`.
+    pub async fn attach_output(&self, terminal_id: &str, channel: Channel<InvokeResponseBody>) -> Result<u64, ChimeraError> {
+        let terminals = self.terminals.lock().await;
+        let instance = terminals
+            .get(terminal_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+
+        let subscription = self.next_subscription.fetch_add(1, Ordering::Relaxed);
+        let mut output = instance.output.lock().unwrap();
+        match &mut output.sink {
+            OutputSink::Channels(channels) => {
+                channels.insert(subscription, channel);
+            }
+            sink => *sink = OutputSink::Channels(HashMap::from([(subscription, channel)])),
+        }
+
+        log::info!("Terminal {} output attached to channel {}", terminal_id, subscription);
+        Ok(subscription)
+    }
+
+    /// Detach an output channel; with none left, output goes back to bus events
+    pub async fn detach_output(&self, terminal_id: &str, subscription: u64) -> Result<(), ChimeraError> {
+        let terminals = self.terminals.lock().await;
+        let instance = terminals
+            .get(terminal_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+
+        let mut output = instance.output.lock().unwrap();
+        if let OutputSink::Channels(channels) = &mut output.sink {
+            channels.remove(&subscription);
+            if channels.is_empty() {
+                output.sink = OutputSink::Bus;
+            }
+        }
+        Ok(())
+    }
+
+    /// Measure how fast output moves through the PTY read path by running a shell
+    /// that writes `bytes` of text and timing it to EOF
+    #[cfg(unix)]
+    pub async fn measure_output_throughput(&self, bytes: u64) -> Result<ThroughputReport, ChimeraError> {
+        let terminal_id = format!("terminal_{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.arg("-c");
+        cmd.arg(format!("yes 'chimera terminal throughput check' | head -c {}", bytes));
+
+        let cpu_before = process_cpu_seconds();
+        let started = Instant::now();
+
+        let task = self.spawn_instance(terminal_id.clone(), cmd, OutputSink::Discard, None).await?;
+        let read = task.await.map_err(|e| ChimeraError::Terminal(format!("Throughput task failed: {}", e)))?;
+
+        let seconds = started.elapsed().as_secs_f64();
+        let cpu_seconds = cpu_before.and_then(|before| process_cpu_seconds().map(|after| after - before));
+
+        Ok(ThroughputReport {
+            bytes: read,
+            seconds,
+            megabytes_per_second: read as f64 / (1024.0 * 1024.0) / seconds.max(f64::EPSILON),
+            cpu_seconds,
+            cpu_utilization: cpu_seconds.map(|cpu| cpu / seconds.max(f64::EPSILON)),
+        })
+    }
+
+    /// Time keystroke echo: write one byte to a `cat` terminal and wait for the PTY
+    /// to echo it back, `samples` times. Returns round trips in milliseconds.
+    #[cfg(unix)]
+    pub async fn measure_echo_round_trip(&self, samples: usize) -> Result<Vec<f64>, ChimeraError> {
+        let terminal_id = format!("terminal_{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        self.spawn_instance(terminal_id.clone(), CommandBuilder::new("cat"), OutputSink::Probe(sender), None)
+            .await?;
+
+        let mut round_trips = Vec::with_capacity(samples);
+        let mut result = Ok(());
+
+        for i in 0..samples {
+            let marker = b'a' + (i % 26) as u8;
+            let started = Instant::now();
+
+            if let Err(e) = self.write_to_terminal(&terminal_id, &(marker as char).to_string()).await {
+                result = Err(e);
+                break;
+            }
+
+            let echoed = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+                while let Some(chunk) = receiver.recv().await {
+                    if chunk.contains(&marker) {
+                        return true;
                     }
                 }
+                false
+            })
+            .await;
+
+            match echoed {
+                Ok(true) => round_trips.push(started.elapsed().as_secs_f64() * 1000.0),
+                Ok(false) => {
+                    result = Err(ChimeraError::Terminal("Terminal closed before echoing input".to_string()));
+                    break;
+                }
+                Err(_) => {
+                    result = Err(ChimeraError::Terminal("Timed out waiting for terminal echo".to_string()));
+                    break;
+                }
             }
+        }
 
-            // Clean up terminal instance
-            let mut terms = terminals.lock().await;
-            terms.remove(&id);
-            log::info!("Terminal {} cleaned up", id);
-        });
+        let _ = self.close_terminal(&terminal_id).await;
+        result.map(|_| round_trips)
+    }
+
+    #[cfg(not(unix))]
+    pub async fn measure_echo_round_trip(&self, _samples: usize) -> Result<Vec<f64>, ChimeraError> {
+        Err(ChimeraError::Terminal("Terminal echo measurement is only supported on unix".to_string()))
+    }
+
+    #[cfg(not(unix))]
+    pub async fn measure_output_throughput(&self, _bytes: u64) -> Result<ThroughputReport, ChimeraError> {
+        Err(ChimeraError::Terminal("Terminal throughput measurement is only supported on unix".to_string()))
     }
 
     /// Write data to a terminal
-    pub async fn write_to_terminal(&self, terminal_id: &str, data: &str) -> Result<(), String> {
+    pub async fn write_to_terminal(&self, terminal_id: &str, data: &str) -> Result<(), ChimeraError> {
+        self.write_bytes(terminal_id, data.as_bytes()).await
+    }
+
+    /// Send a named key such as `Up`, `F5` or `Ctrl+C`, encoded for the terminal's current modes
+    pub async fn send_key(&self, terminal_id: &str, key: &str) -> Result<(), ChimeraError> {
+        let modes = {
+            let terminals = self.terminals.lock().await;
+            let instance = terminals
+                .get(terminal_id)
+                .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+            let modes = instance.output.lock().unwrap().modes;
+            modes
+        };
+        let bytes = terminal_keys::encode(key, modes)?;
+        self.write_bytes(terminal_id, &bytes).await
+    }
+
+    async fn write_bytes(&self, terminal_id: &str, data: &[u8]) -> Result<(), ChimeraError> {
         let mut terminals = self.terminals.lock().await;
         let instance = terminals
             .get_mut(terminal_id)
-            .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
 
         let mut writer = instance.pty_master.take_writer()
-            .map_err(|e| format!("Failed to get PTY writer: {}", e))?;
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to get PTY writer: {}", e)))?;
 
         writer
-            .write_all(data.as_bytes())
-            .map_err(|e| format!("Failed to write to terminal: {}", e))?;
+            .write_all(data)
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to write to terminal: {}", e)))?;
 
         writer
             .flush()
-            .map_err(|e| format!("Failed to flush terminal: {}", e))?;
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to flush terminal: {}", e)))?;
+        instance.last_write_at = chrono::Utc::now();
 
         Ok(())
     }
 
+    /// Send a signal to whatever runs in a terminal. On unix it goes to the
+    /// terminal's foreground process group, so SIGINT stops a command the shell is
+    /// running rather than the shell. Windows has no signals: SIGKILL terminates the
+    /// process, and SIGINT or SIGTERM send CTRL_BREAK, falling back to typing Ctrl+C.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn signal(&self, terminal_id: &str, signal: TerminalSignal) -> Result<(), ChimeraError> {
+        let mut terminals = self.terminals.lock().await;
+        let instance = terminals
+            .get_mut(terminal_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+
+        if signal == TerminalSignal::Kill && cfg!(windows) {
+            return instance.killer.kill().map_err(|e| ChimeraError::Terminal(format!("Failed to kill terminal process: {}", e)));
+        }
+
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{kill, killpg, Signal};
+            use nix::unistd::Pid;
+
+            let sig = match signal {
+                TerminalSignal::Interrupt => Signal::SIGINT,
+                TerminalSignal::Terminate => Signal::SIGTERM,
+                TerminalSignal::Kill => Signal::SIGKILL,
+            };
+            let group = instance.pty_master.process_group_leader().filter(|pgid| *pgid > 0);
+            if let Some(pgid) = group {
+                match killpg(Pid::from_raw(pgid), sig) {
+                    Ok(()) => {
+                        log::info!("Sent {} to process group {} of terminal {}", sig, pgid, terminal_id);
+                        return Ok(());
+                    }
+                    Err(e) => log::warn!("Failed to signal process group {} of terminal {}: {}", pgid, terminal_id, e),
+                }
+            }
+            let pid = instance.pid.ok_or_else(|| format!("Terminal {} has no process id", terminal_id))?;
+            kill(Pid::from_raw(pid as i32), sig).map_err(|e| ChimeraError::Terminal(format!("Failed to send {} to terminal process: {}", sig, e)))?;
+            log::info!("Sent {} to process {} of terminal {}", sig, pid, terminal_id);
+            Ok(())
+        }
+
+        #[cfg(windows)]
+        {
+            use windows::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+            // Only reaches processes sharing the app's console, which pseudoconsole
+            // children usually don't; Ctrl+C typed into the PTY always arrives
+            let sent = instance
+                .pid
+                .is_some_and(|pid| unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) }.is_ok());
+            if sent {
+                log::info!("Sent CTRL_BREAK to terminal {}", terminal_id);
+                return Ok(());
+            }
+            drop(terminals);
+            log::info!("Sending Ctrl+C to terminal {} for {:?}", terminal_id, signal);
+            self.write_bytes(terminal_id, b"\x03").await
+        }
+    }
+
     /// Resize a terminal
     pub async fn resize_terminal(
         &self,
         terminal_id: &str,
         cols: u16,
         rows: u16,
-    ) -> Result<(), String> {
+    ) -> Result<(), ChimeraError> {
         let mut terminals = self.terminals.lock().await;
         let instance = terminals
             .get_mut(terminal_id)
-            .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
 
         instance
             .pty_master
@@ -303,17 +1196,27 @@ impl TerminalBackend {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .map_err(|e| format!("Failed to resize terminal: {}", e))?;
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to resize terminal: {}", e)))?;
 
         instance.cols = cols;
         instance.rows = rows;
 
+        let mut output = instance.output.lock().unwrap();
+        if let Some(recording) = &mut output.recording {
+            if let Err(e) = recording.resize(cols, rows) {
+                log::warn!("Stopping recording of terminal {}: {}", terminal_id, e);
+                output.recording = None;
+            }
+        }
+        drop(output);
+
         log::info!("Terminal {} resized to {}x{}", terminal_id, cols, rows);
         Ok(())
     }
 
     /// Close a terminal
-    pub async fn close_terminal(&self, terminal_id: &str) -> Result<(), String> {
+    #[tracing::instrument(skip(self))]
+    pub async fn close_terminal(&self, terminal_id: &str) -> Result<(), ChimeraError> {
         let mut terminals = self.terminals.lock().await;
 
         if let Some(instance) = terminals.remove(terminal_id) {
@@ -324,23 +1227,571 @@ impl TerminalBackend {
 
             Ok(())
         } else {
-            Err(format!("Terminal not found: {}", terminal_id))
+            Err(ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))
+        }
+    }
+
+    /// Every open terminal with its size and recent output
+    pub async fn snapshot(&self) -> Vec<TerminalSnapshot> {
+        let terminals = self.terminals.lock().await;
+        terminals
+            .values()
+            .map(|instance| {
+                let bytes = instance.output.lock().unwrap().scrollback_bytes();
+                TerminalSnapshot {
+                    terminal_id: instance.id.clone(),
+                    cols: instance.cols,
+                    rows: instance.rows,
+                    scrollback: String::from_utf8_lossy(&bytes).into_owned(),
+                }
+            })
+            .collect()
+    }
+
+    /// When each open terminal last had input or output. With `idle_after`,
+    /// terminals quiet for that long are marked idle.
+    pub async fn activity(&self, idle_after: Option<Duration>) -> Vec<TerminalActivity> {
+        let terminals = self.terminals.lock().await;
+        let mut activity: Vec<TerminalActivity> =
+            terminals.values().map(|instance| instance.activity(idle_after)).collect();
+        activity.sort_by(|a, b| a.terminal_id.cmp(&b.terminal_id));
+        activity
+    }
+
+    /// Publish `terminal_idle` for terminals quiet for `idle_after`, once per idle
+    /// stretch, and with `close` close them
+    pub async fn check_idle(&self, idle_after: Duration, close: bool) {
+        let mut terminals = self.terminals.lock().await;
+        let now = chrono::Utc::now();
+        let mut idle = Vec::new();
+        for instance in terminals.values_mut() {
+            let last_active_at = instance.last_active_at();
+            let quiet = (now - last_active_at).to_std().unwrap_or_default();
+            if quiet < idle_after || instance.idle_reported_for == Some(last_active_at) {
+                continue;
+            }
+            instance.idle_reported_for = Some(last_active_at);
+            self.event_bus.publish(
+                "terminal_idle",
+                TerminalIdleEvent {
+                    terminal_id: &instance.id,
+                    idle_secs: quiet.as_secs(),
+                    closing: close,
+                },
+            );
+            idle.push(instance.id.clone());
+        }
+
+        if !close {
+            return;
+        }
+        for terminal_id in idle {
+            // Dropping the PTY ends the shell; the I/O task reports the exit
+            if terminals.remove(&terminal_id).is_some() {
+                log::info!("Closing terminal {}: idle for over {:?}", terminal_id, idle_after);
+            }
+        }
+    }
+
+    /// Terminal id and child process id of each open terminal
+    pub async fn pids(&self) -> Vec<(String, u32)> {
+        let terminals = self.terminals.lock().await;
+        terminals
+            .values()
+            .filter_map(|instance| Some((instance.id.clone(), instance.pid?)))
+            .collect()
+    }
+
+    /// Process details of a running terminal, or of one that exited recently
+    pub async fn info(&self, terminal_id: &str) -> Result<TerminalInfo, ChimeraError> {
+        if let Some(instance) = self.terminals.lock().await.get(terminal_id) {
+            return Ok(instance.info());
         }
+        self.exited
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|info| info.terminal_id == terminal_id)
+            .cloned()
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))
+    }
+
+    /// A terminal's recent output, for redrawing it after the view reconnects. Output
+    /// stopped by the rate cap resumes from here: pending output is already in the
+    /// scrollback, so it's cleared rather than sent again.
+    pub async fn scrollback(&self, terminal_id: &str) -> Result<String, ChimeraError> {
+        let terminals = self.terminals.lock().await;
+        let instance = terminals
+            .get(terminal_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+        let mut output = instance.output.lock().unwrap();
+        if output.resync {
+            output.resync = false;
+            output.pending.clear();
+            output.allowance = rate_limit().unwrap_or(0) as f64;
+            output.allowance_at = Instant::now();
+        }
+        let bytes = output.scrollback_bytes();
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Write a terminal's scrollback to `dest`. Returns the bytes written.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn export_buffer(
+        &self,
+        terminal_id: &str,
+        dest: &std::path::Path,
+        format: ScrollbackFormat,
+    ) -> Result<u64, ChimeraError> {
+        let bytes = {
+            let terminals = self.terminals.lock().await;
+            let instance = terminals
+                .get(terminal_id)
+                .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+            let bytes = instance.output.lock().unwrap().scrollback_bytes();
+            bytes
+        };
+
+        let content = match format {
+            ScrollbackFormat::Ansi => bytes,
+            ScrollbackFormat::Text => strip_ansi(&String::from_utf8_lossy(&bytes)).into_bytes(),
+        };
+
+        if crate::audit::outside_data_dir(dest) {
+            crate::audit::record_async(crate::audit::AuditEntry::new(
+                "fs",
+                "export_terminal_buffer",
+                "ui",
+                dest.to_string_lossy().as_ref(),
+                true,
+            ))
+            .await;
+        }
+
+        tokio::fs::write(dest, &content)
+            .await
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to write terminal export: {}", e)))?;
+
+        log::info!("Exported {} bytes of terminal {} to {}", content.len(), terminal_id, dest.display());
+        Ok(content.len() as u64)
+    }
+
+    /// Start recording a terminal's output from now on to an asciicast v2 file at `dest`
+    #[tracing::instrument(skip(self), err)]
+    pub async fn start_recording(&self, terminal_id: &str, dest: &std::path::Path) -> Result<(), ChimeraError> {
+        let (output, cols, rows) = {
+            let terminals = self.terminals.lock().await;
+            let instance = terminals
+                .get(terminal_id)
+                .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+            (instance.output.clone(), instance.cols, instance.rows)
+        };
+        let recording = output.lock().unwrap().recording.is_some();
+        if recording {
+            return Err(ChimeraError::InvalidInput(format!("Terminal {} is already being recorded", terminal_id)));
+        }
+
+        if crate::audit::outside_data_dir(dest) {
+            crate::audit::record_async(crate::audit::AuditEntry::new(
+                "fs",
+                "record_terminal",
+                "ui",
+                dest.to_string_lossy().as_ref(),
+                true,
+            ))
+            .await;
+        }
+
+        let file = tokio::fs::File::create(dest)
+            .await
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to create recording file: {}", e)))?
+            .into_std()
+            .await;
+        let recording = Recording::start(dest.to_path_buf(), file, cols, rows)
+            .map_err(|e| ChimeraError::Terminal(format!("Failed to write recording header: {}", e)))?;
+
+        let mut output = output.lock().unwrap();
+        if output.recording.is_some() {
+            return Err(ChimeraError::InvalidInput(format!("Terminal {} is already being recorded", terminal_id)));
+        }
+        output.recording = Some(recording);
+
+        log::info!("Recording terminal {} to {}", terminal_id, dest.display());
+        Ok(())
+    }
+
+    /// Stop recording a terminal, closing its asciicast file
+    #[tracing::instrument(skip(self), err)]
+    pub async fn stop_recording(&self, terminal_id: &str) -> Result<RecordingSummary, ChimeraError> {
+        let terminals = self.terminals.lock().await;
+        let instance = terminals
+            .get(terminal_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal not found: {}", terminal_id)))?;
+        let recording = instance.output.lock().unwrap().recording.take();
+        let summary = recording
+            .ok_or_else(|| format!("Terminal {} is not being recorded", terminal_id))?
+            .finish();
+
+        log::info!("Recorded {} bytes of terminal {} to {}", summary.bytes, terminal_id, summary.path);
+        Ok(summary)
+    }
+
+    /// The working directory of `group_id`, checking the group exists
+    fn group_cwd(&self, group_id: Option<&str>) -> Result<Option<String>, ChimeraError> {
+        let Some(group_id) = group_id else { return Ok(None) };
+        self.groups
+            .lock()
+            .unwrap()
+            .get(group_id)
+            .map(|group| group.cwd.clone())
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal group not found: {}", group_id)))
+    }
+
+    /// Add a terminal group named `name`. Terminals spawned into it without a
+    /// working directory start in `cwd`.
+    pub async fn create_group(&self, name: &str, cwd: Option<String>) -> Result<TerminalGroupInfo, ChimeraError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ChimeraError::InvalidInput("Terminal group name is empty".to_string()));
+        }
+        if let Some(cwd) = &cwd {
+            if !std::path::Path::new(cwd).is_dir() {
+                return Err(ChimeraError::InvalidInput(format!("Not a directory: {}", cwd)));
+            }
+        }
+
+        let group_id = format!("group_{}", self.next_group_id.fetch_add(1, Ordering::SeqCst));
+        self.groups.lock().unwrap().insert(
+            group_id.clone(),
+            TerminalGroup {
+                name: name.to_string(),
+                cwd,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        log::info!("Created terminal group {} ({})", group_id, name);
+        let terminals = self.terminals.lock().await;
+        publish_group_status(&self.event_bus, &self.groups, &terminals, &group_id, Some("created"))
+            .ok_or_else(|| ChimeraError::Internal(format!("Terminal group {} vanished", group_id)))
+    }
+
+    /// Every terminal group with its open terminals, oldest first
+    pub async fn list_groups(&self) -> Vec<TerminalGroupInfo> {
+        let terminals = self.terminals.lock().await;
+        let groups = self.groups.lock().unwrap();
+        let mut list: Vec<TerminalGroupInfo> = groups
+            .iter()
+            .map(|(group_id, group)| group_info(group_id, group, &terminals, None))
+            .collect();
+        list.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.group_id.cmp(&b.group_id)));
+        list
+    }
+
+    /// Remove a terminal group and close every terminal in it. Returns the ids of
+    /// the terminals closed.
+    #[tracing::instrument(skip(self))]
+    pub async fn close_group(&self, group_id: &str) -> Result<Vec<String>, ChimeraError> {
+        let mut terminals = self.terminals.lock().await;
+        let group = self
+            .groups
+            .lock()
+            .unwrap()
+            .remove(group_id)
+            .ok_or_else(|| ChimeraError::NotFound(format!("Terminal group not found: {}", group_id)))?;
+
+        let mut info = group_info(group_id, &group, &terminals, Some("closed"));
+        for terminal_id in &info.terminal_ids {
+            // Dropping the PTY ends the shell; its I/O task reaps it and reports the exit
+            if let Some(instance) = terminals.remove(terminal_id) {
+                log::info!("Closing terminal {} with group {}", instance.id, group_id);
+            }
+        }
+        log::info!("Closed terminal group {} ({} terminals)", group_id, info.terminal_ids.len());
+        let closed = std::mem::take(&mut info.terminal_ids);
+        self.event_bus.publish("terminal_group_status", &info);
+        Ok(closed)
     }
 
     /// Shutdown all terminals
+    #[tracing::instrument(skip(self))]
     pub async fn shutdown_all(&self) {
         log::info!("Shutting down all terminals...");
 
-        let mut terminals = self.terminals.lock().await;
-        let terminal_ids: Vec<String> = terminals.keys().cloned().collect();
+        {
+            let mut terminals = self.terminals.lock().await;
+            let terminal_ids: Vec<String> = terminals.keys().cloned().collect();
 
-        for id in terminal_ids {
-            if let Some(instance) = terminals.remove(&id) {
-                log::info!("Closing terminal {}", instance.id);
+            for id in terminal_ids {
+                if let Some(instance) = terminals.remove(&id) {
+                    log::info!("Closing terminal {}", instance.id);
+                }
             }
         }
 
+        // Dropping the PTYs ends each I/O task's reads; wait for them to reap
+        // their processes
+        self.tasks.shutdown(SHUTDOWN_TIMEOUT).await;
+
         log::info!("All terminals shutdown complete");
     }
 }
+
+/// How often terminals are checked against the idle policy
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Apply the `terminal_idle` settings policy for as long as the app runs
+pub fn start_idle_checks(app_handle: tauri::AppHandle, backend: Arc<TerminalBackend>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let Some(policy) = idle_policy(&app_handle) else { continue };
+            backend.check_idle(policy.threshold(), policy.auto_close).await;
+        }
+    });
+}
+
+/// The idle policy from settings, if one is on
+pub fn idle_policy(app_handle: &tauri::AppHandle) -> Option<crate::settings::TerminalIdlePolicy> {
+    use tauri::Manager;
+
+    app_handle
+        .try_state::<Arc<crate::settings::SettingsStore>>()
+        .map(|settings| settings.get().terminal_idle)
+        .filter(|policy| policy.idle_minutes > 0)
+}
+
+/// `group` as listed, with the open terminals in it. `status` overrides the one
+/// worked out from its terminals.
+fn group_info(
+    group_id: &str,
+    group: &TerminalGroup,
+    terminals: &HashMap<String, TerminalInstance>,
+    status: Option<&str>,
+) -> TerminalGroupInfo {
+    let mut terminal_ids: Vec<String> = terminals
+        .values()
+        .filter(|instance| instance.group_id.as_deref() == Some(group_id))
+        .map(|instance| instance.id.clone())
+        .collect();
+    terminal_ids.sort();
+    let status = status.unwrap_or(if terminal_ids.is_empty() { "empty" } else { "running" });
+    TerminalGroupInfo {
+        group_id: group_id.to_string(),
+        name: group.name.clone(),
+        cwd: group.cwd.clone(),
+        created_at: group.created_at.clone(),
+        terminal_ids,
+        status: status.to_string(),
+    }
+}
+
+/// Publish `terminal_group_status` for `group_id`, if the group still exists
+fn publish_group_status(
+    event_bus: &EventBus,
+    groups: &StdMutex<HashMap<String, TerminalGroup>>,
+    terminals: &HashMap<String, TerminalInstance>,
+    group_id: &str,
+    status: Option<&str>,
+) -> Option<TerminalGroupInfo> {
+    let info = group_info(group_id, groups.lock().unwrap().get(group_id)?, terminals, status);
+    event_bus.publish("terminal_group_status", &info);
+    Some(info)
+}
+
+/// Set each of `env` on a command
+fn apply_env(cmd: &mut CommandBuilder, env: &HashMap<String, String>) {
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+}
+
+/// Plain text from terminal output: escape sequences are removed, and carriage
+/// returns and backspaces overwrite what came before them on the line, the way
+/// progress bars and prompts appear on screen
+pub(crate) fn strip_ansi(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut line: Vec<char> = Vec::new();
+    let mut column: usize = 0;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS, SOS, PM, APC: up to BEL or ST
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\n' => {
+                out.extend(line.drain(..));
+                out.push('\n');
+                column = 0;
+            }
+            '\r' => column = 0,
+            '\x08' => column = column.saturating_sub(1),
+            c if c.is_control() && c != '\t' => {}
+            c => {
+                if column < line.len() {
+                    line[column] = c;
+                } else {
+                    line.push(c);
+                }
+                column += 1;
+            }
+        }
+    }
+    out.extend(line);
+    out
+}
+
+/// Send each terminal's output at most once per batch window. Reads that arrive
+/// within a window of the last batch are left pending and `wake` this task, which
+/// sends them when the window ends. Stops once the output is closed.
+fn flush_batches(terminal_id: String, output: Arc<StdMutex<TerminalOutput>>, event_bus: Arc<EventBus>, wake: Arc<Notify>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            wake.notified().await;
+            let due = {
+                let output = output.lock().unwrap();
+                if output.closed {
+                    break;
+                }
+                output.last_flush + batch_window()
+            };
+            tokio::time::sleep_until(due.into()).await;
+
+            let mut output = output.lock().unwrap();
+            output.flush(&terminal_id, &event_bus, false);
+            if output.closed {
+                break;
+            }
+        }
+    });
+}
+
+/// Read a PTY until EOF, routing output to the terminal's current sink. Output for
+/// the frontend is batched: a read that comes within a batch window of the last
+/// batch waits for the window to end. Returns the total bytes read.
+fn read_output(
+    terminal_id: &str,
+    mut reader: Box<dyn Read + Send>,
+    output: &StdMutex<TerminalOutput>,
+    event_bus: &EventBus,
+    wake: &Notify,
+) -> u64 {
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut total = 0u64;
+    let window = batch_window();
+
+    let status = loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => {
+                // EOF - terminal closed
+                log::info!("Terminal {} closed (EOF)", terminal_id);
+                break "closed";
+            }
+            Ok(n) => {
+                total += n as u64;
+                crate::metrics::record_terminal_output(n);
+
+                let mut output = output.lock().unwrap();
+                output.stats.read_bytes += n as u64;
+                output.last_read_at = chrono::Utc::now();
+                output.scrollback.extend(&buffer[..n]);
+                let excess = output.scrollback.len().saturating_sub(scrollback_limit());
+                output.scrollback.drain(..excess);
+                output.modes.observe(&buffer[..n]);
+                if let Some(recording) = &mut output.recording {
+                    if let Err(e) = recording.output(&buffer[..n]) {
+                        log::warn!("Stopping recording of terminal {}: {}", terminal_id, e);
+                        output.recording = None;
+                    }
+                }
+
+                match &output.sink {
+                    OutputSink::Bus | OutputSink::Channels(_) => {
+                        output.pending.extend_from_slice(&buffer[..n]);
+                        if output.last_flush.elapsed() >= window {
+                            output.flush(terminal_id, event_bus, false);
+                        } else {
+                            wake.notify_one();
+                        }
+                    }
+                    OutputSink::Probe(sender) => {
+                        if sender.send(buffer[..n].to_vec()).is_err() {
+                            output.sink = OutputSink::Discard;
+                        }
+                    }
+                    OutputSink::Discard => {}
+                }
+            }
+            Err(e) => {
+                log::error!("Error reading from terminal {}: {}", terminal_id, e);
+                break "error";
+            }
+        }
+    };
+
+    {
+        let mut output = output.lock().unwrap();
+        output.flush(terminal_id, event_bus, true);
+        output.closed = true;
+        if let Some(recording) = output.recording.take() {
+            let summary = recording.finish();
+            log::info!("Recorded {} bytes of terminal {} to {}", summary.bytes, terminal_id, summary.path);
+        }
+    }
+    wake.notify_one();
+
+    event_bus.publish(
+        "terminal_status",
+        TerminalStatusEvent {
+            terminal_id: terminal_id.to_string(),
+            status: status.to_string(),
+        },
+    );
+    total
+}
+
+/// Length of the prefix of `bytes` that doesn't end in a truncated UTF-8 sequence
+fn utf8_complete_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        // error_len() is None only when the input ends mid-sequence
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+/// User plus system CPU time consumed by this process
+#[cfg(unix)]
+fn process_cpu_seconds() -> Option<f64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage fills the struct on success
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let usage = unsafe { usage.assume_init() };
+    let seconds = |t: libc::timeval| t.tv_sec as f64 + t.tv_usec as f64 / 1_000_000.0;
+    Some(seconds(usage.ru_utime) + seconds(usage.ru_stime))
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+
+/// Held spawns not approved within this long are dropped
+const PENDING_TTL: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+
+/// A program to run in a terminal of type `command`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandSpec {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+/// Programs that start without confirmation, persisted in `terminal_commands.json`.
+/// Bare names match commands found on PATH; entries with a path separator match
+/// that exact path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandAllowlist {
+    pub allowed: Vec<String>,
+}
+
+/// A spawn held until the user approves it, as sent in
+/// `terminal-command-approval-request` events
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingSpawn {
+    pub id: String,
+    /// Must be passed back to `approve_terminal_command`
+    pub token: String,
+    pub spec: CommandSpec,
+    pub cwd: Option<String>,
+    /// Typed into the terminal once the command starts
+    pub init_command: Option<String>,
+    pub created_at: String,
+}
+
+fn get_allowlist_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("terminal_commands.json"))
+}
+
+/// Program name without a Windows executable extension
+fn program_name(command: &str) -> &str {
+    let lower = command.to_ascii_lowercase();
+    [".exe", ".cmd", ".bat"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(command, |ext| &command[..command.len() - ext.len()])
+}
+
+fn is_path(command: &str) -> bool {
+    command.contains('/') || command.contains('\\')
+}
+
+/// Search PATH for an executable
+#[cfg(windows)]
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The user's interactive shell: `$SHELL` on Unix; PowerShell, else `%ComSpec%`, on Windows
+pub fn default_shell() -> (String, Vec<String>) {
+    #[cfg(windows)]
+    {
+        for shell in ["pwsh.exe", "powershell.exe"] {
+            if let Some(path) = find_in_path(shell) {
+                return (path.to_string_lossy().into_owned(), vec!["-NoLogo".to_string()]);
+            }
+        }
+        let comspec = std::env::var("ComSpec").unwrap_or_else(|_| "cmd.exe".to_string());
+        (comspec, Vec::new())
+    }
+
+    #[cfg(not(windows))]
+    {
+        let fallback = if cfg!(target_os = "macos") { "/bin/zsh" } else { "/bin/bash" };
+        let shell = std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty() && Path::new(shell).exists())
+            .unwrap_or_else(|| fallback.to_string());
+        // Terminal windows on macOS conventionally start login shells
+        let args = if cfg!(target_os = "macos") { vec!["-l".to_string()] } else { Vec::new() };
+        (shell, args)
+    }
+}
+
+/// Decides which `command` terminals may start, holding the rest for approval
+pub struct TerminalCommands {
+    allowlist: Mutex<CommandAllowlist>,
+    pending: Mutex<HashMap<String, PendingSpawn>>,
+    bus: Arc<EventBus>,
+}
+
+impl TerminalCommands {
+    /// Load the saved allowlist
+    pub fn load(bus: Arc<EventBus>) -> Self {
+        let allowlist = get_allowlist_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(CommandAllowlist::default());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read terminal command allowlist: {}", e))?;
+                serde_json::from_str(&content)
+                    .map_err(|e| format!("Failed to parse terminal command allowlist: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                CommandAllowlist::default()
+            });
+
+        Self {
+            allowlist: Mutex::new(allowlist),
+            pending: Mutex::new(HashMap::new()),
+            bus,
+        }
+    }
+
+    pub fn allowlist(&self) -> CommandAllowlist {
+        self.allowlist.lock().unwrap().clone()
+    }
+
+    pub fn set_allowlist(&self, allowlist: CommandAllowlist) -> Result<CommandAllowlist, String> {
+        let content = serde_json::to_string_pretty(&allowlist)
+            .map_err(|e| format!("Failed to serialize terminal command allowlist: {}", e))?;
+        std::fs::write(get_allowlist_path()?, content)
+            .map_err(|e| format!("Failed to write terminal command allowlist: {}", e))?;
+        *self.allowlist.lock().unwrap() = allowlist.clone();
+        Ok(allowlist)
+    }
+
+    fn is_allowed(&self, command: &str) -> bool {
+        self.allowlist.lock().unwrap().allowed.iter().any(|entry| {
+            if is_path(entry) {
+                Path::new(entry) == Path::new(command)
+            } else {
+                !is_path(command) && program_name(entry).eq_ignore_ascii_case(program_name(command))
+            }
+        })
+    }
+
+    /// Check a spawn before it happens. Allowlisted commands pass; anything else is
+    /// held and an approval request published. Returns the held spawn.
+    pub fn inspect(&self, spec: &CommandSpec, cwd: Option<&str>, init_command: Option<&str>) -> Option<PendingSpawn> {
+        if self.is_allowed(&spec.command) {
+            return None;
+        }
+
+        let spawn = PendingSpawn {
+            id: uuid::Uuid::new_v4().to_string(),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            spec: spec.clone(),
+            cwd: cwd.map(str::to_string),
+            init_command: init_command.map(str::to_string),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        log::warn!("Holding terminal command {} {:?} for approval", spec.command, spec.args);
+
+        let mut pending = self.pending.lock().unwrap();
+        let cutoff = (chrono::Utc::now() - PENDING_TTL).to_rfc3339();
+        pending.retain(|_, held| held.created_at >= cutoff);
+        pending.insert(spawn.id.clone(), spawn.clone());
+        drop(pending);
+
+        self.bus.publish("terminal-command-approval-request", &spawn);
+        Some(spawn)
+    }
+
+    /// Take a held spawn the user approved, checking its token. With `remember`,
+    /// the command is added to the allowlist.
+    pub fn approve(&self, id: &str, token: &str, remember: bool) -> Result<PendingSpawn, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let spawn = pending.get(id).ok_or_else(|| format!("No pending terminal command {}", id))?;
+        if spawn.token != token {
+            return Err("Invalid approval token".to_string());
+        }
+        let spawn = pending.remove(id).expect("checked above");
+        drop(pending);
+
+        if spawn.created_at < (chrono::Utc::now() - PENDING_TTL).to_rfc3339() {
+            return Err(format!("Pending terminal command {} expired", id));
+        }
+
+        if remember && !self.is_allowed(&spawn.spec.command) {
+            let mut allowlist = self.allowlist();
+            allowlist.allowed.push(spawn.spec.command.clone());
+            self.set_allowlist(allowlist)?;
+        }
+
+        self.bus
+            .publish("terminal-command-approval-resolved", serde_json::json!({ "id": id, "approved": true }));
+        Ok(spawn)
+    }
+
+    /// Drop a held spawn
+    pub fn deny(&self, id: &str) -> Result<(), String> {
+        self.pending
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| format!("No pending terminal command {}", id))?;
+        self.bus
+            .publish("terminal-command-approval-resolved", serde_json::json!({ "id": id, "approved": false }));
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use portable_pty::CommandBuilder;
+use std::sync::OnceLock;
+
+/// Locale used when the app was launched without one
+const FALLBACK_LANG: &str = "en_US.UTF-8";
+
+/// Environment changes applied to every terminal
+#[derive(Debug, Clone, Default)]
+pub struct EnvFixups {
+    pub set: Vec<(String, String)>,
+    pub remove: Vec<String>,
+}
+
+impl EnvFixups {
+    /// Apply the fixups to a command about to be spawned in a PTY
+    pub fn apply(&self, cmd: &mut CommandBuilder) {
+        for key in &self.remove {
+            cmd.env_remove(key);
+        }
+        for (key, value) in &self.set {
+            cmd.env(key, value);
+        }
+    }
+}
+
+static FIXUPS: OnceLock<EnvFixups> = OnceLock::new();
+
+/// Resolve the fixups in the background so the first terminal doesn't wait on the
+/// login shell
+pub fn prime() {
+    std::thread::spawn(|| {
+        fixups();
+    });
+}
+
+/// The environment fixups, resolved on first use
+pub fn fixups() -> &'static EnvFixups {
+    FIXUPS.get_or_init(|| {
+        let fixups = resolve();
+        for (key, value) in &fixups.set {
+            log::info!("Terminal environment: {}={}", key, value);
+        }
+        fixups
+    })
+}
+
+fn non_empty(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+fn resolve() -> EnvFixups {
+    let mut fixups = EnvFixups::default();
+    fixups.set.push(("TERM_PROGRAM".to_string(), "Chimera".to_string()));
+
+    // An empty LC_ALL overrides LANG with nothing, which most tools read as "C"
+    if std::env::var_os("LC_ALL").is_some_and(|v| v.is_empty()) {
+        fixups.remove.push("LC_ALL".to_string());
+    }
+    if non_empty("LANG").is_none() && non_empty("LC_ALL").is_none() {
+        fixups.set.push(("LANG".to_string(), system_locale()));
+    }
+
+    let login = login_shell_env();
+    if let Some(path) = login.as_ref().and_then(|env| env.get("PATH")) {
+        let merged = merge_paths(path, &std::env::var("PATH").unwrap_or_default());
+        if Some(&merged) != std::env::var("PATH").ok().as_ref() {
+            fixups.set.push(("PATH".to_string(), merged));
+        }
+    }
+
+    if non_empty("SSH_AUTH_SOCK").is_none() {
+        let sock = login
+            .as_ref()
+            .and_then(|env| env.get("SSH_AUTH_SOCK").cloned())
+            .filter(|v| !v.is_empty())
+            .or_else(agent_socket);
+        if let Some(sock) = sock {
+            fixups.set.push(("SSH_AUTH_SOCK".to_string(), sock));
+        }
+    }
+
+    fixups
+}
+
+/// Entries of `preferred` first, then any of `current` it lacks
+fn merge_paths(preferred: &str, current: &str) -> String {
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    let mut entries: Vec<&str> = Vec::new();
+    for entry in preferred.split(separator).chain(current.split(separator)) {
+        if !entry.is_empty() && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries.join(&separator.to_string())
+}
+
+/// The user's locale as a UTF-8 LANG value
+#[cfg(target_os = "macos")]
+fn system_locale() -> String {
+    // AppleLocale looks like en_US or en_US@rg=gbzzzz
+    std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleLocale"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            let locale = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let locale = locale.split('@').next()?.to_string();
+            locale.contains('_').then(|| format!("{}.UTF-8", locale))
+        })
+        .unwrap_or_else(|| FALLBACK_LANG.to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_locale() -> String {
+    FALLBACK_LANG.to_string()
+}
+
+/// Environment of a fresh login shell. Apps started from Finder or the Dock only get
+/// launchd's minimal PATH, so this is where Homebrew, pyenv and friends come from.
+#[cfg(target_os = "macos")]
+fn login_shell_env() -> Option<std::collections::HashMap<String, String>> {
+    use std::io::Read;
+    use std::time::{Duration, Instant};
+
+    const MARKER: &str = "__CHIMERA_ENV__";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let shell = non_empty("SHELL").unwrap_or_else(|| "/bin/zsh".to_string());
+    let mut child = std::process::Command::new(&shell)
+        .args(["-l", "-c", &format!("printf '{}'; env; printf '{}'", MARKER, MARKER)])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| log::warn!("Failed to run login shell {}: {}", shell, e))
+        .ok()?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < TIMEOUT => std::thread::sleep(Duration::from_millis(50)),
+            _ => {
+                log::warn!("Login shell {} did not finish in {:?}; using the app environment", shell, TIMEOUT);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    let env = output.split(MARKER).nth(1)?;
+    Some(
+        env.lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn login_shell_env() -> Option<std::collections::HashMap<String, String>> {
+    None
+}
+
+/// A running SSH agent's socket, for sessions that didn't export one
+#[cfg(target_os = "macos")]
+fn agent_socket() -> Option<String> {
+    std::process::Command::new("launchctl")
+        .args(["getenv", "SSH_AUTH_SOCK"])
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|sock| !sock.is_empty())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn agent_socket() -> Option<String> {
+    let runtime = std::path::PathBuf::from(non_empty("XDG_RUNTIME_DIR")?);
+    ["gcr/ssh", "keyring/ssh", "ssh-agent.socket", "openssh_agent"]
+        .iter()
+        .map(|name| runtime.join(name))
+        .find(|path| path.exists())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Windows' OpenSSH agent uses a fixed named pipe that ssh finds without a variable
+#[cfg(windows)]
+fn agent_socket() -> Option<String> {
+    None
+}
@@ -0,0 +1,225 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+
+/// Held writes not confirmed within this long are dropped
+const PENDING_TTL: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+
+/// Commands held for confirmation unless the config lists its own
+const DEFAULT_PATTERNS: &[&str] = &[
+    r"\brm\s+(-[a-zA-Z]*[rR][a-zA-Z]*\s+)*-[a-zA-Z]*[fF]|\brm\s+(-[a-zA-Z]*[fF][a-zA-Z]*\s+)*-[a-zA-Z]*[rR]",
+    r"\brm\s+.*--recursive",
+    r"\bdd\s+.*\bof=",
+    r"\bmkfs(\.\w+)?\b",
+    r"\bgit\s+push\s+.*(--force\b|-f\b|--force-with-lease\b)",
+    r"\bgit\s+reset\s+--hard\b",
+    r"\bgit\s+clean\s+-[a-zA-Z]*[fdx]",
+    r"\bchmod\s+(-R\s+)?0?777\b",
+    r"\bchown\s+-R\b",
+    r">\s*/dev/(sd[a-z]|nvme\d|disk\d)",
+    r":\(\)\s*\{\s*:\|:&\s*\};:",
+    r"\b(shutdown|reboot|halt|poweroff)\b",
+    r"\bDROP\s+(TABLE|DATABASE)\b",
+];
+
+/// Inspection settings, persisted in `terminal_guard.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardConfig {
+    /// Hold matching agent writes for confirmation
+    pub enabled: bool,
+    /// Regular expressions matched against agent input
+    pub patterns: Vec<String>,
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            patterns: DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+/// A write held until the user confirms it, as sent in `terminal-approval-request` events
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingWrite {
+    pub id: String,
+    /// Must be passed back to `confirm_terminal_write`
+    pub token: String,
+    pub terminal_id: String,
+    pub data: String,
+    /// The pattern that matched
+    pub pattern: String,
+    pub source: String,
+    pub created_at: String,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("terminal_guard.json"))
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).map_err(|e| format!("Invalid pattern {}: {}", p, e)))
+        .collect()
+}
+
+/// Holds destructive-looking terminal input from agents until the user approves it
+pub struct TerminalGuard {
+    config: Mutex<(GuardConfig, Vec<Regex>)>,
+    pending: Mutex<HashMap<String, PendingWrite>>,
+    bus: Arc<EventBus>,
+}
+
+impl TerminalGuard {
+    /// Load saved settings
+    pub fn load(bus: Arc<EventBus>) -> Self {
+        let config = get_config_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(GuardConfig::default());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read terminal guard config: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse terminal guard config: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                GuardConfig::default()
+            });
+
+        let patterns = compile(&config.patterns).unwrap_or_else(|e| {
+            log::warn!("{}; using default terminal guard patterns", e);
+            compile(&GuardConfig::default().patterns).unwrap_or_default()
+        });
+
+        Self {
+            config: Mutex::new((config, patterns)),
+            pending: Mutex::new(HashMap::new()),
+            bus,
+        }
+    }
+
+    pub fn config(&self) -> GuardConfig {
+        self.config.lock().unwrap().0.clone()
+    }
+
+    /// Replace the settings. Patterns are compiled first, so a bad one changes nothing.
+    pub fn set_config(&self, config: GuardConfig) -> Result<GuardConfig, String> {
+        let patterns = compile(&config.patterns)?;
+        let content = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to serialize terminal guard config: {}", e))?;
+        std::fs::write(get_config_path()?, content)
+            .map_err(|e| format!("Failed to write terminal guard config: {}", e))?;
+
+        log::info!("Terminal guard {}", if config.enabled { "enabled" } else { "disabled" });
+        *self.config.lock().unwrap() = (config.clone(), patterns);
+        Ok(config)
+    }
+
+    /// Check a write before it reaches the terminal. Writes from the user, and all
+    /// writes while the guard is off, pass. A matching agent write is held and an
+    /// approval request published; its id is returned.
+    pub fn inspect(&self, terminal_id: &str, data: &str, source: &str) -> Option<String> {
+        if source == "user" {
+            return None;
+        }
+
+        let pattern = {
+            let config = self.config.lock().unwrap();
+            if !config.0.enabled {
+                return None;
+            }
+            config
+                .1
+                .iter()
+                .find(|pattern| pattern.is_match(data))?
+                .as_str()
+                .to_string()
+        };
+
+        let write = PendingWrite {
+            id: uuid::Uuid::new_v4().to_string(),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            terminal_id: terminal_id.to_string(),
+            data: data.to_string(),
+            pattern,
+            source: source.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        log::warn!("Holding {} write to {} for confirmation: {:?}", source, terminal_id, data);
+        crate::audit::record(
+            crate::audit::AuditEntry::new("terminal", "hold_write", source, terminal_id, false).detail(data),
+        );
+
+        let mut pending = self.pending.lock().unwrap();
+        let cutoff = (chrono::Utc::now() - PENDING_TTL).to_rfc3339();
+        pending.retain(|_, held| held.created_at >= cutoff);
+        pending.insert(write.id.clone(), write.clone());
+        drop(pending);
+
+        self.bus.publish("terminal-approval-request", &write);
+        Some(write.id)
+    }
+
+    /// Take a held write the user approved, checking its token
+    pub fn confirm(&self, id: &str, token: &str) -> Result<PendingWrite, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let write = pending.get(id).ok_or_else(|| format!("No pending terminal write {}", id))?;
+        if write.token != token {
+            return Err("Invalid confirmation token".to_string());
+        }
+        let write = pending.remove(id).expect("checked above");
+        drop(pending);
+
+        if write.created_at < (chrono::Utc::now() - PENDING_TTL).to_rfc3339() {
+            return Err(format!("Pending terminal write {} expired", id));
+        }
+
+        crate::audit::record(
+            crate::audit::AuditEntry::new("terminal", "confirm_write", "ui", write.terminal_id.as_str(), true)
+                .detail(write.data.as_str()),
+        );
+        self.bus.publish("terminal-approval-resolved", serde_json::json!({ "id": id, "approved": true }));
+        Ok(write)
+    }
+
+    /// Drop a held write
+    pub fn deny(&self, id: &str) -> Result<(), String> {
+        let write = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| format!("No pending terminal write {}", id))?;
+
+        crate::audit::record(
+            crate::audit::AuditEntry::new("terminal", "deny_write", "ui", write.terminal_id.as_str(), false)
+                .detail(write.data.as_str()),
+        );
+        self.bus.publish("terminal-approval-resolved", serde_json::json!({ "id": id, "approved": false }));
+        Ok(())
+    }
+
+    /// Writes waiting for confirmation
+    pub fn pending(&self) -> Vec<PendingWrite> {
+        let cutoff = (chrono::Utc::now() - PENDING_TTL).to_rfc3339();
+        let mut pending: Vec<PendingWrite> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|write| write.created_at >= cutoff)
+            .cloned()
+            .collect();
+        pending.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        pending
+    }
+}
@@ -0,0 +1,154 @@
+/// Terminal input modes that change what keys send
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyModes {
+    /// DECCKM (`ESC [ ? 1 h`): unmodified cursor keys send `ESC O x` instead of `ESC [ x`
+    pub application_cursor: bool,
+}
+
+impl KeyModes {
+    /// Update modes from a chunk of terminal output. Sequences split across chunks
+    /// are missed, which in practice only happens under heavy output.
+    pub fn observe(&mut self, output: &[u8]) {
+        for window in output.windows(5) {
+            match window {
+                b"\x1b[?1h" => self.application_cursor = true,
+                b"\x1b[?1l" => self.application_cursor = false,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Modifiers {
+    shift: bool,
+    alt: bool,
+    ctrl: bool,
+}
+
+impl Modifiers {
+    fn any(&self) -> bool {
+        self.shift || self.alt || self.ctrl
+    }
+
+    /// xterm modifier parameter: 1 + shift + 2*alt + 4*ctrl
+    fn param(&self) -> u8 {
+        1 + self.shift as u8 + 2 * self.alt as u8 + 4 * self.ctrl as u8
+    }
+}
+
+/// Sequence for a key written `ESC [ x` / `ESC O x`, with modifiers as `ESC [ 1 ; m x`
+fn cursor_style(final_byte: char, modifiers: &Modifiers, application: bool) -> Vec<u8> {
+    if modifiers.any() {
+        format!("\x1b[1;{}{}", modifiers.param(), final_byte).into_bytes()
+    } else if application {
+        format!("\x1bO{}", final_byte).into_bytes()
+    } else {
+        format!("\x1b[{}", final_byte).into_bytes()
+    }
+}
+
+/// Sequence for a key written `ESC [ n ~`, with modifiers as `ESC [ n ; m ~`
+fn tilde_style(code: u8, modifiers: &Modifiers) -> Vec<u8> {
+    if modifiers.any() {
+        format!("\x1b[{};{}~", code, modifiers.param()).into_bytes()
+    } else {
+        format!("\x1b[{}~", code).into_bytes()
+    }
+}
+
+/// Prefix with ESC when Alt is held
+fn with_alt(mut bytes: Vec<u8>, modifiers: &Modifiers) -> Vec<u8> {
+    if modifiers.alt {
+        bytes.insert(0, 0x1b);
+    }
+    bytes
+}
+
+/// Bytes for a printable character with modifiers applied
+fn character(c: char, modifiers: &Modifiers) -> Result<Vec<u8>, String> {
+    let c = if modifiers.shift { c.to_ascii_uppercase() } else { c };
+
+    let bytes = if modifiers.ctrl {
+        let control = match c.to_ascii_lowercase() {
+            c @ 'a'..='z' => c as u8 & 0x1f,
+            '@' | ' ' | '2' => 0x00,
+            '[' | '3' => 0x1b,
+            '\\' | '4' => 0x1c,
+            ']' | '5' => 0x1d,
+            '^' | '6' => 0x1e,
+            '_' | '-' | '7' => 0x1f,
+            '?' | '8' => 0x7f,
+            _ => return Err(format!("No control code for Ctrl+{}", c)),
+        };
+        vec![control]
+    } else {
+        c.to_string().into_bytes()
+    };
+
+    Ok(with_alt(bytes, modifiers))
+}
+
+/// Translate a key name such as `Up`, `F5`, `Ctrl+C` or `Alt+Enter` into the bytes
+/// an xterm-compatible terminal expects. Names and modifiers are case-insensitive.
+pub fn encode(key: &str, modes: KeyModes) -> Result<Vec<u8>, String> {
+    let parts: Vec<&str> = key.split('+').map(str::trim).collect();
+    // A trailing "+" means the plus key itself, e.g. "Ctrl++"
+    let (name, modifier_names) = match parts.as_slice() {
+        [rest @ .., "", ""] => ("+", rest),
+        [rest @ .., name] => (*name, rest),
+        [] => return Err("Empty key".to_string()),
+    };
+
+    let mut modifiers = Modifiers::default();
+    for modifier in modifier_names {
+        match modifier.to_ascii_lowercase().as_str() {
+            "shift" => modifiers.shift = true,
+            "alt" | "meta" | "option" => modifiers.alt = true,
+            "ctrl" | "control" => modifiers.ctrl = true,
+            _ => return Err(format!("Unknown modifier '{}' in key {}", modifier, key)),
+        }
+    }
+
+    let mut single = name.chars();
+    if let (Some(c), None) = (single.next(), single.next()) {
+        return character(c, &modifiers);
+    }
+
+    let app = modes.application_cursor;
+    let bytes = match name.to_ascii_lowercase().as_str() {
+        "up" => cursor_style('A', &modifiers, app),
+        "down" => cursor_style('B', &modifiers, app),
+        "right" => cursor_style('C', &modifiers, app),
+        "left" => cursor_style('D', &modifiers, app),
+        "home" => cursor_style('H', &modifiers, app),
+        "end" => cursor_style('F', &modifiers, app),
+        "insert" => tilde_style(2, &modifiers),
+        "delete" | "del" => tilde_style(3, &modifiers),
+        "pageup" | "pgup" => tilde_style(5, &modifiers),
+        "pagedown" | "pgdn" => tilde_style(6, &modifiers),
+        "f1" => cursor_style('P', &modifiers, true),
+        "f2" => cursor_style('Q', &modifiers, true),
+        "f3" => cursor_style('R', &modifiers, true),
+        "f4" => cursor_style('S', &modifiers, true),
+        "f5" => tilde_style(15, &modifiers),
+        "f6" => tilde_style(17, &modifiers),
+        "f7" => tilde_style(18, &modifiers),
+        "f8" => tilde_style(19, &modifiers),
+        "f9" => tilde_style(20, &modifiers),
+        "f10" => tilde_style(21, &modifiers),
+        "f11" => tilde_style(23, &modifiers),
+        "f12" => tilde_style(24, &modifiers),
+        "enter" | "return" => with_alt(b"\r".to_vec(), &modifiers),
+        "tab" if modifiers.shift => b"\x1b[Z".to_vec(),
+        "tab" => with_alt(b"\t".to_vec(), &modifiers),
+        "backspace" if modifiers.ctrl => with_alt(vec![0x08], &modifiers),
+        "backspace" => with_alt(vec![0x7f], &modifiers),
+        "escape" | "esc" => with_alt(vec![0x1b], &modifiers),
+        "space" => return character(' ', &modifiers),
+        "plus" => return character('+', &modifiers),
+        _ => return Err(format!("Unknown key: {}", key)),
+    };
+
+    Ok(bytes)
+}
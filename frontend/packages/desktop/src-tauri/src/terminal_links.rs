@@ -0,0 +1,356 @@
+use regex::Regex;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Larger batches aren't scanned; they're floods of output nobody clicks on
+const MAX_SCAN_BYTES: usize = 256 * 1024;
+
+/// Most links reported for one batch
+const MAX_LINKS: usize = 200;
+
+const URL_REGEX: &str = r#"\b(?:https?|ftp|file)://[^\s<>"'`]+"#;
+
+/// A path with at least one separator, optionally followed by `:line` or `:line:column`
+const PATH_REGEX: &str =
+    r"(?:[A-Za-z]:[\\/]|~[\\/]|\.\.?[\\/]|[\\/])?(?:[\w.@+\-]+[\\/])+[\w.@+\-]+(?::(\d+)(?::(\d+))?)?";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Url,
+    Path,
+}
+
+/// A URL or file path found in terminal output
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalLink {
+    pub kind: LinkKind,
+    /// Range of the link in the event's `data`, in UTF-16 code units as JS
+    /// indexes strings; escape sequences inside it are included
+    pub start: usize,
+    pub end: usize,
+    /// The link as shown
+    pub text: String,
+    /// URL to open, or the path made absolute against the terminal's starting
+    /// directory where it was relative
+    pub target: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+fn url_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(URL_REGEX).expect("valid URL regex"))
+}
+
+fn path_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(PATH_REGEX).expect("valid path regex"))
+}
+
+/// A run of output between escape sequences: where it starts in the visible text
+/// (bytes) and in the original data (UTF-16 units)
+struct Segment {
+    visible: usize,
+    data: usize,
+}
+
+/// Output with escape sequences removed, mapped back to the original
+struct Visible {
+    text: String,
+    segments: Vec<Segment>,
+    /// OSC 8 hyperlinks: visible range and URI
+    hyperlinks: Vec<(usize, usize, String)>,
+}
+
+impl Visible {
+    /// UTF-16 offset in the data of visible byte `pos`, as the start of a range
+    fn start(&self, pos: usize) -> usize {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.visible <= pos)
+            .saturating_sub(1);
+        self.offset(index, pos)
+    }
+
+    /// UTF-16 offset in the data just after visible byte `pos - 1`, as the end of a range
+    fn end(&self, pos: usize) -> usize {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.visible < pos)
+            .saturating_sub(1);
+        self.offset(index, pos)
+    }
+
+    fn offset(&self, index: usize, pos: usize) -> usize {
+        match self.segments.get(index) {
+            Some(segment) => segment.data + self.text[segment.visible..pos].encode_utf16().count(),
+            None => 0,
+        }
+    }
+}
+
+/// Finds links in a terminal's output batch by batch, carrying an OSC 8
+/// hyperlink that is still open at the end of one batch into the next
+pub struct LinkScanner {
+    cwd: Option<PathBuf>,
+    open: Option<String>,
+}
+
+impl LinkScanner {
+    pub fn new(cwd: Option<PathBuf>) -> Self {
+        Self { cwd, open: None }
+    }
+
+    /// Links in `data`, one batch of output as sent to the frontend
+    pub fn scan(&mut self, data: &str) -> Vec<TerminalLink> {
+        if data.len() > MAX_SCAN_BYTES {
+            self.open = None;
+            return Vec::new();
+        }
+        if self.open.is_none() && !data.contains(['/', '\\']) && !data.contains("\x1b]8;") {
+            return Vec::new();
+        }
+
+        let visible = self.strip(data);
+        let mut links: Vec<TerminalLink> = Vec::new();
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        let overlaps =
+            |taken: &[(usize, usize)], start: usize, end: usize| taken.iter().any(|&(s, e)| start < e && s < end);
+
+        for (start, end, uri) in &visible.hyperlinks {
+            if start == end {
+                continue;
+            }
+            taken.push((*start, *end));
+            links.push(self.link(&visible, *start, *end, uri));
+        }
+
+        for found in url_regex().find_iter(&visible.text) {
+            let end = found.start() + trim_url(found.as_str()).len();
+            if overlaps(&taken, found.start(), end) {
+                continue;
+            }
+            taken.push((found.start(), end));
+            let url = visible.text[found.start()..end].to_string();
+            links.push(self.link(&visible, found.start(), end, &url));
+        }
+
+        for captures in path_regex().captures_iter(&visible.text) {
+            let found = captures.get(0).expect("whole match");
+            let (start, mut end) = (found.start(), found.end());
+            let line = captures.get(1).and_then(|m| m.as_str().parse().ok());
+            let column = captures.get(2).and_then(|m| m.as_str().parse().ok());
+            if line.is_none() {
+                end = start + found.as_str().trim_end_matches(['.', '-']).len();
+            }
+            let path_end = captures.get(1).map_or(end, |m| m.start() - 1);
+            let path = &visible.text[start..path_end];
+
+            // Part of a longer word, or not much like a path
+            let before = visible.text[..start].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || "/\\:.-_@~".contains(c))
+                || !is_path_like(path, line.is_some())
+                || overlaps(&taken, start, end)
+            {
+                continue;
+            }
+            taken.push((start, end));
+            links.push(TerminalLink {
+                kind: LinkKind::Path,
+                start: visible.start(start),
+                end: visible.end(end),
+                text: visible.text[start..end].to_string(),
+                target: self.resolve(path),
+                line,
+                column,
+            });
+        }
+
+        links.sort_by_key(|link| link.start);
+        links.truncate(MAX_LINKS);
+        links
+    }
+
+    /// Remove escape sequences from `data`, noting OSC 8 hyperlinks as they open and close
+    fn strip(&mut self, data: &str) -> Visible {
+        let mut visible = Visible {
+            text: String::with_capacity(data.len()),
+            segments: Vec::new(),
+            hyperlinks: Vec::new(),
+        };
+        let mut open = self.open.take().map(|uri| (0, uri));
+        let mut offset = 0;
+        let mut new_segment = true;
+        let mut chars = data.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            offset += c.len_utf16();
+            if c != '\x1b' {
+                if new_segment {
+                    visible.segments.push(Segment {
+                        visible: visible.text.len(),
+                        data: offset - c.len_utf16(),
+                    });
+                    new_segment = false;
+                }
+                visible.text.push(c);
+                continue;
+            }
+
+            new_segment = true;
+            match chars.next() {
+                // CSI: parameters up to a final byte in @..~
+                Some('[') => {
+                    offset += 1;
+                    for c in chars.by_ref() {
+                        offset += c.len_utf16();
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or ESC \
+                Some(']') => {
+                    offset += 1;
+                    let mut body = String::new();
+                    while let Some(c) = chars.next() {
+                        offset += c.len_utf16();
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            offset += 1;
+                            break;
+                        }
+                        body.push(c);
+                    }
+                    // OSC 8: `8;params;uri` opens a hyperlink, an empty uri closes it
+                    if let Some(uri) = body
+                        .strip_prefix("8;")
+                        .and_then(|rest| rest.split_once(';'))
+                        .map(|(_, uri)| uri)
+                    {
+                        if let Some((start, uri)) = open.take() {
+                            visible.hyperlinks.push((start, visible.text.len(), uri));
+                        }
+                        if !uri.is_empty() {
+                            open = Some((visible.text.len(), uri.to_string()));
+                        }
+                    }
+                }
+                Some(c) => offset += c.len_utf16(),
+                None => {}
+            }
+        }
+
+        if let Some((start, uri)) = open {
+            visible.hyperlinks.push((start, visible.text.len(), uri.clone()));
+            self.open = Some(uri);
+        }
+        visible
+    }
+
+    /// A link for an OSC 8 hyperlink or a URL: `file://` URIs become paths
+    fn link(&self, visible: &Visible, start: usize, end: usize, uri: &str) -> TerminalLink {
+        let (kind, target) = match file_uri_path(uri) {
+            Some(path) => (LinkKind::Path, path),
+            None => (LinkKind::Url, uri.to_string()),
+        };
+        TerminalLink {
+            kind,
+            start: visible.start(start),
+            end: visible.end(end),
+            text: visible.text[start..end].to_string(),
+            target,
+            line: None,
+            column: None,
+        }
+    }
+
+    /// `path` made absolute: `~` is the home directory, and relative paths are
+    /// taken from the terminal's starting directory, which a `cd` may have changed
+    fn resolve(&self, path: &str) -> String {
+        if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+            if let Some(home) = dirs::home_dir() {
+                return home.join(rest).to_string_lossy().into_owned();
+            }
+        }
+        let path_buf = PathBuf::from(path);
+        match &self.cwd {
+            Some(cwd) if path_buf.is_relative() => {
+                let path = path
+                    .strip_prefix("./")
+                    .or_else(|| path.strip_prefix(".\\"))
+                    .unwrap_or(path);
+                cwd.join(path).to_string_lossy().into_owned()
+            }
+            _ => path.to_string(),
+        }
+    }
+}
+
+/// Whether `path` is worth linking: anything rooted, or a relative path with a
+/// line number or a file extension, so fractions and `and/or` aren't linked
+fn is_path_like(path: &str, has_line: bool) -> bool {
+    if !path.chars().any(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    let rooted = path.starts_with(['/', '\\', '~', '.']) || path.get(1..3).is_some_and(|s| s == ":\\" || s == ":/");
+    if rooted || has_line {
+        return true;
+    }
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    name.rsplit_once('.').is_some_and(|(stem, ext)| {
+        !stem.is_empty() && !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// A URL without the punctuation that usually ends the sentence around it, and
+/// without a closing bracket it doesn't open
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+        let trimmed = match trimmed.chars().next_back() {
+            Some(')') if trimmed.matches('(').count() < trimmed.matches(')').count() => &trimmed[..trimmed.len() - 1],
+            Some(']') if trimmed.matches('[').count() < trimmed.matches(']').count() => &trimmed[..trimmed.len() - 1],
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// The local path of a `file://` URI, percent-decoded
+fn file_uri_path(uri: &str) -> Option<String> {
+    let rest = uri.strip_prefix("file://")?;
+    // The host, usually empty or this machine's name, comes before the path
+    let path = &rest[rest.find('/')?..];
+    // Windows paths come as /C:/...
+    let path = match path.get(1..3) {
+        Some(drive) if drive.ends_with(':') && drive.starts_with(|c: char| c.is_ascii_alphabetic()) => &path[1..],
+        _ => path,
+    };
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| path.get(i + 1..i + 3)).flatten();
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&decoded).into_owned())
+}
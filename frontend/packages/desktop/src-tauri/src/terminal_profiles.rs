@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a profile's terminal is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfileKind {
+    /// The user's shell, or `command` when set
+    Shell,
+    /// The Chimera Ink CLI, from the repo in development or bundled in production
+    InkCli,
+    /// `command` with `args`
+    Command,
+}
+
+/// Colors the frontend applies to a profile's terminals, as CSS colors
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalColors {
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    pub cursor: Option<String>,
+}
+
+/// A named way to open a terminal, from `terminal_profiles` in settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminalProfile {
+    pub id: String,
+    pub name: String,
+    pub kind: ProfileKind,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory; the app's own when unset
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Typed into the terminal once it starts, followed by Enter
+    #[serde(default)]
+    pub init_command: Option<String>,
+    #[serde(default)]
+    pub colors: TerminalColors,
+}
+
+impl TerminalProfile {
+    fn builtin(id: &str, name: &str, kind: ProfileKind, command: Option<&str>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind,
+            command: command.map(str::to_string),
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            init_command: None,
+            colors: TerminalColors::default(),
+        }
+    }
+
+    /// Profiles for the terminal types `spawn_terminal` has always accepted
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::builtin("shell", "Shell", ProfileKind::Shell, None),
+            Self::builtin("bash", "Bash", ProfileKind::Shell, Some("bash")),
+            Self::builtin("ink-cli", "Ink CLI", ProfileKind::InkCli, None),
+        ]
+    }
+
+    /// The built-in profile for a terminal type, e.g. `shell`
+    pub fn for_type(terminal_type: &str) -> Option<Self> {
+        Self::defaults().into_iter().find(|profile| profile.id == terminal_type)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Terminal profile id is empty".to_string());
+        }
+        if self.kind == ProfileKind::Command && self.command.as_deref().is_none_or(|c| c.trim().is_empty()) {
+            return Err(format!("Terminal profile {} needs a command", self.id));
+        }
+        if let Some(cwd) = &self.cwd {
+            if !std::path::Path::new(cwd).is_absolute() {
+                return Err(format!("Terminal profile {} cwd must be an absolute path: {}", self.id, cwd));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check every profile and that ids are unique
+pub fn validate(profiles: &[TerminalProfile]) -> Result<(), String> {
+    for (index, profile) in profiles.iter().enumerate() {
+        profile.validate()?;
+        if profiles[..index].iter().any(|other| other.id == profile.id) {
+            return Err(format!("Duplicate terminal profile id: {}", profile.id));
+        }
+    }
+    Ok(())
+}
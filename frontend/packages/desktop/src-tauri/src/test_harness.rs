@@ -0,0 +1,434 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Notify};
+
+use crate::event_bus::{self, BusEvent, EventBus};
+use crate::filesystem;
+use crate::mock_backend::{self, MockServer};
+use crate::terminal_backend::TerminalBackend;
+
+/// Exit codes for harness runs
+const EXIT_OK: i32 = 0;
+const EXIT_USAGE: i32 = 2;
+const EXIT_FAILED: i32 = 4;
+
+const USAGE: &str = "Usage: chimera-desktop --test-harness [--control-port <port>] [--keep-data]
+
+Starts the Rust layer without a window, using a mock backend and a throwaway
+data directory (in /dev/shm when available). The mock backend replays fixtures
+from the data directory's mock-backend/ folder. Prints one JSON line with the
+control address, then accepts JSON-lines requests on it:
+
+  {\"id\": 1, \"command\": \"create_thread\", \"args\": {\"blueprint_json\": \"...\"}}
+
+and answers each with {\"id\", \"ok\", \"result\" | \"error\"}. Every event
+published on the event bus is recorded and can be read with `events`.";
+
+/// Parsed harness arguments
+struct HarnessArgs {
+    control_port: u16,
+    keep_data: bool,
+}
+
+/// A control request
+#[derive(Deserialize)]
+struct ControlRequest {
+    #[serde(default)]
+    id: serde_json::Value,
+    command: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+/// Everything the control commands operate on
+struct Harness {
+    bus: Arc<EventBus>,
+    terminals: Arc<TerminalBackend>,
+    backend: tokio::sync::Mutex<Option<MockServer>>,
+    recorded: Arc<Mutex<Vec<BusEvent>>>,
+    data_dir: PathBuf,
+    shutdown: Notify,
+}
+
+/// Returns true when the process was launched with `--test-harness`
+pub fn is_requested(args: &[String]) -> bool {
+    args.iter().any(|a| a == "--test-harness")
+}
+
+/// Parse harness arguments (everything after the binary name)
+fn parse_args(args: &[String]) -> Result<HarnessArgs, String> {
+    let mut control_port = 0;
+    let mut keep_data = false;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--test-harness" => {}
+            "--keep-data" => keep_data = true,
+            "--control-port" => {
+                control_port = iter
+                    .next()
+                    .and_then(|p| p.parse().ok())
+                    .ok_or_else(|| format!("--control-port needs a port number\n\n{}", USAGE))?;
+            }
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("Unknown argument: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    Ok(HarnessArgs {
+        control_port,
+        keep_data,
+    })
+}
+
+/// Pick a throwaway data directory, preferring a RAM-backed filesystem
+fn scratch_data_dir() -> PathBuf {
+    let shm = PathBuf::from("/dev/shm");
+    let base = if shm.is_dir() { shm } else { std::env::temp_dir() };
+    base.join(format!("chimera-harness-{}", uuid::Uuid::new_v4()))
+}
+
+/// Start the mock backend, replaying fixtures from the harness's data directory
+async fn start_mock_backend(bus: Arc<EventBus>, data_dir: &std::path::Path) -> Result<MockServer, String> {
+    let backend = mock_backend::serve(data_dir.join("mock-backend"), Some(bus.clone())).await?;
+    bus.publish("backend-lifecycle", serde_json::json!({ "state": "started", "url": backend.url }));
+    Ok(backend)
+}
+
+fn arg_str(args: &serde_json::Value, name: &str) -> Result<String, String> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| format!("Missing string argument: {}", name))
+}
+
+fn arg_u16(args: &serde_json::Value, name: &str) -> Result<u16, String> {
+    args.get(name)
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .ok_or_else(|| format!("Missing numeric argument: {}", name))
+}
+
+/// Default output size for `terminal_throughput`
+const TERMINAL_THROUGHPUT_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Default thread count for `stress_scan`
+const STRESS_SCAN_FILES: usize = 5000;
+
+/// Write `files` synthetic threads, then list them while a probe task measures how
+/// long the runtime takes to service a 1ms timer. A large `max_stall_ms` means the
+/// scan is blocking runtime workers.
+async fn stress_scan(data_dir: PathBuf, files: usize) -> Result<serde_json::Value, String> {
+    let threads_dir = data_dir.join("threads");
+    filesystem::blocking(move || {
+        std::fs::create_dir_all(&threads_dir).map_err(|e| format!("Failed to create threads directory: {}", e))?;
+        for i in 0..files {
+            let path = threads_dir.join(format!("stress-{:05}.jsonl", i));
+            let content = format!(
+                "{{\"thread_id\":\"stress-{:05}\"}}\n{{\"type\":\"user-message\",\"content\":\"Stress thread {}\"}}\n",
+                i, i
+            );
+            std::fs::write(&path, content).map_err(|e| format!("Failed to write thread: {}", e))?;
+        }
+        Ok(())
+    })
+    .await?;
+
+    let (done_tx, mut done_rx) = watch::channel(false);
+    let probe = tokio::spawn(async move {
+        let mut max_stall = std::time::Duration::ZERO;
+        let mut samples = 0u64;
+        while !*done_rx.borrow_and_update() {
+            let started = std::time::Instant::now();
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_millis(1)) => {}
+                _ = done_rx.changed() => break,
+            }
+            max_stall = max_stall.max(started.elapsed().saturating_sub(std::time::Duration::from_millis(1)));
+            samples += 1;
+        }
+        (max_stall, samples)
+    });
+
+    let started = std::time::Instant::now();
+    let listed = filesystem::list_threads().await;
+    let scan = started.elapsed();
+    let _ = done_tx.send(true);
+    let (max_stall, samples) = probe.await.map_err(|e| format!("Probe task failed: {}", e))?;
+
+    Ok(serde_json::json!({
+        "files": files,
+        "threads": listed?.len(),
+        "scan_ms": scan.as_secs_f64() * 1000.0,
+        "max_stall_ms": max_stall.as_secs_f64() * 1000.0,
+        "probe_samples": samples,
+    }))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<serde_json::Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+impl Harness {
+    async fn backend_url(&self) -> Result<String, String> {
+        self.backend
+            .lock()
+            .await
+            .as_ref()
+            .map(|b| b.url.clone())
+            .ok_or_else(|| "Mock backend is stopped".to_string())
+    }
+
+    async fn stop_backend(&self) {
+        if let Some(backend) = self.backend.lock().await.take() {
+            backend.stop();
+            self.bus
+                .publish("backend-lifecycle", serde_json::json!({ "state": "stopped", "url": backend.url }));
+        }
+    }
+
+    /// Run a single control command
+    async fn dispatch(&self, command: &str, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+        match command {
+            "ping" => Ok(serde_json::json!("pong")),
+            "info" => Ok(serde_json::json!({
+                "data_dir": self.data_dir,
+                "backend_url": self.backend_url().await.ok(),
+            })),
+
+            // Filesystem
+            "init_filesystem" => to_value(filesystem::init_filesystem().await?),
+            "add_blueprint" => {
+                let id = arg_str(args, "id")?;
+                if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+                    return Err(format!("Invalid blueprint id: {}", id));
+                }
+                let blueprint = args.get("blueprint").ok_or("Missing argument: blueprint")?;
+                let dir = self.data_dir.join("blueprints");
+                std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create blueprints directory: {}", e))?;
+                let path = dir.join(format!("{}.json", id));
+                std::fs::write(&path, blueprint.to_string()).map_err(|e| format!("Failed to write blueprint: {}", e))?;
+                to_value(path.to_string_lossy())
+            }
+            "list_blueprints" => to_value(filesystem::list_blueprints().await?),
+            "read_blueprint" => to_value(filesystem::read_blueprint(arg_str(args, "file_path")?).await?),
+            "create_thread" => to_value(filesystem::create_thread(arg_str(args, "blueprint_json")?).await?),
+            "load_thread" => to_value(filesystem::load_thread(arg_str(args, "thread_id")?).await?),
+            "append_thread_events" => {
+                let events = args
+                    .get("events")
+                    .and_then(|e| e.as_array())
+                    .cloned()
+                    .ok_or("Missing array argument: events")?;
+                to_value(filesystem::append_thread_events(arg_str(args, "thread_id")?, events).await?)
+            }
+            "list_threads" => to_value(filesystem::list_threads().await?),
+            "stress_scan" => {
+                let files = args.get("files").and_then(|f| f.as_u64()).map(|f| f as usize);
+                stress_scan(self.data_dir.clone(), files.unwrap_or(STRESS_SCAN_FILES)).await
+            }
+            "update_thread_title" => to_value(
+                filesystem::update_thread_title(arg_str(args, "thread_id")?, arg_str(args, "title")?).await?,
+            ),
+
+            // Terminals
+            "spawn_terminal" => {
+                let terminal_type = arg_str(args, "terminal_type").unwrap_or_else(|_| "shell".to_string());
+                let cwd = arg_str(args, "cwd").ok();
+                to_value(self.terminals.spawn_terminal(terminal_type, cwd, None, Default::default()).await?)
+            }
+            "write_to_terminal" => to_value(
+                self.terminals
+                    .write_to_terminal(&arg_str(args, "terminal_id")?, &arg_str(args, "data")?)
+                    .await?,
+            ),
+            "resize_terminal" => to_value(
+                self.terminals
+                    .resize_terminal(&arg_str(args, "terminal_id")?, arg_u16(args, "cols")?, arg_u16(args, "rows")?)
+                    .await?,
+            ),
+            "close_terminal" => to_value(self.terminals.close_terminal(&arg_str(args, "terminal_id")?).await?),
+            "terminal_throughput" => {
+                let bytes = args.get("bytes").and_then(|b| b.as_u64()).unwrap_or(TERMINAL_THROUGHPUT_BYTES);
+                to_value(self.terminals.measure_output_throughput(bytes).await?)
+            }
+
+            // Backend lifecycle
+            "start_backend" => {
+                let mut backend = self.backend.lock().await;
+                if backend.is_none() {
+                    *backend = Some(start_mock_backend(self.bus.clone(), &self.data_dir).await?);
+                }
+                to_value(backend.as_ref().map(|b| b.url.clone()))
+            }
+            "stop_backend" => {
+                self.stop_backend().await;
+                Ok(serde_json::Value::Null)
+            }
+            "backend_health" => {
+                let url = self.backend_url().await?;
+                let healthy = reqwest::get(&url).await.map(|r| r.status().is_success()).unwrap_or(false);
+                Ok(serde_json::json!({ "healthy": healthy }))
+            }
+            "stream_backend_request" => {
+                let path = arg_str(args, "path")?;
+                let body = args.get("body").cloned().unwrap_or(serde_json::Value::Null);
+                let url = format!("{}/{}", self.backend_url().await?, path.trim_start_matches('/'));
+                let stream_id = uuid::Uuid::new_v4().to_string();
+                // Awaited so the caller sees every chunk recorded once this returns
+                event_bus::forward_backend_stream(self.bus.clone(), stream_id.clone(), url, body, None).await;
+                to_value(stream_id)
+            }
+
+            // Recorded events
+            "events" => {
+                let since = args.get("since").and_then(|s| s.as_u64()).unwrap_or(0);
+                let topic = args.get("topic").and_then(|t| t.as_str());
+                let recorded = self.recorded.lock().unwrap();
+                to_value(
+                    recorded
+                        .iter()
+                        .filter(|e| e.seq > since && topic.is_none_or(|t| e.topic == t))
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                )
+            }
+            "clear_events" => {
+                self.recorded.lock().unwrap().clear();
+                Ok(serde_json::Value::Null)
+            }
+
+            "shutdown" => {
+                self.shutdown.notify_one();
+                Ok(serde_json::Value::Null)
+            }
+
+            other => Err(format!("Unknown command: {}", other)),
+        }
+    }
+}
+
+/// Serve JSON-lines control requests on one connection
+async fn handle_connection(harness: Arc<Harness>, stream: TcpStream) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => match harness.dispatch(&request.command, &request.args).await {
+                Ok(result) => serde_json::json!({ "id": request.id, "ok": true, "result": result }),
+                Err(e) => serde_json::json!({ "id": request.id, "ok": false, "error": e }),
+            },
+            Err(e) => serde_json::json!({ "id": null, "ok": false, "error": format!("Invalid request: {}", e) }),
+        };
+
+        if writer.write_all(format!("{}\n", response).as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Run the test harness and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    };
+
+    // Must happen before any threads read the environment
+    let data_dir = scratch_data_dir();
+    std::env::set_var("CHIMERA_DATA_DIR", &data_dir);
+
+    let code = tauri::async_runtime::block_on(async {
+        if let Err(e) = filesystem::init_filesystem().await {
+            eprintln!("{}", e);
+            return EXIT_FAILED;
+        }
+
+        let bus = Arc::new(EventBus::detached());
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+
+        let mut receiver = bus.listen();
+        let sink = recorded.clone();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => sink.lock().unwrap().push(event),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Test harness dropped {} events", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let backend = match start_mock_backend(bus.clone(), &data_dir).await {
+            Ok(backend) => backend,
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_FAILED;
+            }
+        };
+
+        let listener = match TcpListener::bind(("127.0.0.1", args.control_port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to bind control socket: {}", e);
+                return EXIT_FAILED;
+            }
+        };
+        let control = listener.local_addr().map(|a| a.to_string()).unwrap_or_default();
+
+        let harness = Arc::new(Harness {
+            terminals: Arc::new(TerminalBackend::new(bus.clone())),
+            bus,
+            backend: tokio::sync::Mutex::new(Some(backend)),
+            recorded,
+            data_dir: data_dir.clone(),
+            shutdown: Notify::new(),
+        });
+
+        println!(
+            "{}",
+            serde_json::json!({
+                "ready": true,
+                "control": control,
+                "backend_url": harness.backend_url().await.ok(),
+                "data_dir": data_dir,
+            })
+        );
+
+        loop {
+            tokio::select! {
+                _ = harness.shutdown.notified() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(harness.clone(), stream));
+                    }
+                    Err(e) => log::warn!("Control socket accept failed: {}", e),
+                },
+            }
+        }
+
+        harness.terminals.shutdown_all().await;
+        harness.stop_backend().await;
+        EXIT_OK
+    });
+
+    if !args.keep_data {
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    code
+}
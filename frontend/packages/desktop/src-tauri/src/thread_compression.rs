@@ -0,0 +1,238 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tauri::Manager;
+
+use crate::append_buffer::AppendBuffer;
+use crate::event_bus::EventBus;
+use crate::filesystem::{self, FsError};
+use crate::settings::SettingsStore;
+
+/// Suffix of compressed thread files, `<thread_id>.jsonl.zst`
+const COMPRESSED_SUFFIX: &str = ".jsonl.zst";
+
+/// zstd level; threads are compressed once and read rarely, so favour size
+const LEVEL: i32 = 9;
+
+/// How often the schedule is checked, so settings changes apply without a restart
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Result of `compress_idle`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompressionReport {
+    /// Ids of the threads compressed
+    pub threads: Vec<String>,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Compressed file for a thread, beside where its JSONL would be
+pub fn compressed_path(thread_id: &str) -> Result<PathBuf, FsError> {
+    let path = filesystem::get_thread_path(thread_id)?;
+    Ok(path.with_file_name(format!("{}{}", thread_id, COMPRESSED_SUFFIX)))
+}
+
+/// Thread id of a compressed thread file
+pub fn compressed_thread_id(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()?.strip_suffix(COMPRESSED_SUFFIX)
+}
+
+/// Thread id of a thread file, compressed or not
+pub fn thread_file_id(path: &Path) -> Option<&str> {
+    compressed_thread_id(path).or_else(|| path.file_stem()?.to_str())
+}
+
+pub fn is_compressed(path: &Path) -> bool {
+    compressed_thread_id(path).is_some()
+}
+
+/// Compressed thread files with no JSONL beside them. When both exist the JSONL
+/// is current and the compressed copy is left over (blocking).
+pub fn list_compressed() -> Result<Vec<PathBuf>, FsError> {
+    let dir = filesystem::get_threads_dir()?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(FsError::io("read threads directory", e)),
+    };
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            compressed_thread_id(path)
+                .is_some_and(|id| !id.starts_with('.') && !dir.join(format!("{}.jsonl", id)).exists())
+        })
+        .collect())
+}
+
+/// A thread file's JSONL, decompressing `.jsonl.zst` files (blocking)
+pub fn read_thread_file(path: &Path) -> Result<Vec<u8>, FsError> {
+    let content = std::fs::read(path).map_err(|e| FsError::io(&format!("read {}", path.display()), e))?;
+    if !is_compressed(path) {
+        return Ok(content);
+    }
+    zstd::decode_all(content.as_slice()).map_err(|e| FsError::io(&format!("decompress {}", path.display()), e))
+}
+
+/// Whether a thread exists, as JSONL or compressed (blocking)
+pub fn thread_exists(thread_id: &str) -> Result<bool, FsError> {
+    Ok(filesystem::get_thread_path(thread_id)?.exists() || compressed_path(thread_id)?.exists())
+}
+
+/// A thread's JSONL without touching its files: a compressed thread is
+/// decompressed in memory, so this works on a read-only data directory (blocking)
+pub fn read_thread(thread_id: &str) -> Result<Vec<u8>, FsError> {
+    match std::fs::read(filesystem::get_thread_path(thread_id)?) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let compressed = compressed_path(thread_id)?;
+            if !compressed.exists() {
+                return Err(FsError::not_found(format!("Thread {} not found", thread_id)));
+            }
+            read_thread_file(&compressed)
+        }
+        Err(e) => Err(FsError::io(&format!("read thread {}", thread_id), e)),
+    }
+}
+
+/// Write `data` to `dest` through a synced temp file, keeping `modified` as its
+/// mtime so listings still sort by when the thread was last used
+fn write_replacing(dest: &Path, data: &[u8], modified: Option<SystemTime>) -> Result<(), FsError> {
+    use std::io::Write;
+
+    let temp = dest.with_extension("tmp");
+    let mut file = std::fs::File::create(&temp).map_err(|e| FsError::io(&format!("create {}", temp.display()), e))?;
+    let written = file
+        .write_all(data)
+        .and_then(|_| file.sync_all())
+        .and_then(|_| modified.map_or(Ok(()), |modified| file.set_modified(modified)));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(FsError::io(&format!("write {}", temp.display()), e));
+    }
+    std::fs::rename(&temp, dest).map_err(|e| FsError::io(&format!("replace {}", dest.display()), e))
+}
+
+/// Replace a thread's JSONL with a compressed copy. Returns the sizes before and
+/// after, or None if the thread has no JSONL (blocking).
+pub fn compress(thread_id: &str) -> Result<Option<(u64, u64)>, FsError> {
+    let _lock = filesystem::lock_thread_blocking(thread_id)?;
+    let path = filesystem::get_thread_path(thread_id)?;
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return Ok(None);
+    };
+    let content = std::fs::read(&path).map_err(|e| FsError::io(&format!("read thread {}", thread_id), e))?;
+    let compressed =
+        zstd::encode_all(content.as_slice(), LEVEL).map_err(|e| FsError::io(&format!("compress thread {}", thread_id), e))?;
+
+    write_replacing(&compressed_path(thread_id)?, &compressed, metadata.modified().ok())?;
+    std::fs::remove_file(&path)
+        .map_err(|e| FsError::io(&format!("remove thread {} after compressing", thread_id), e))?;
+
+    log::info!("Compressed thread {}: {} -> {} bytes", thread_id, content.len(), compressed.len());
+    Ok(Some((content.len() as u64, compressed.len() as u64)))
+}
+
+/// Put a compressed thread back as JSONL so it can be appended to and read in
+/// place. Returns whether there was anything to do (blocking).
+pub fn decompress(thread_id: &str) -> Result<bool, FsError> {
+    let source = compressed_path(thread_id)?;
+    if !source.exists() {
+        return Ok(false);
+    }
+    let _lock = filesystem::lock_thread_blocking(thread_id)?;
+    let dest = filesystem::get_thread_path(thread_id)?;
+    if dest.exists() {
+        // Left over from an interrupted decompress, or a thread restored from a backup
+        let _ = std::fs::remove_file(&source);
+        return Ok(false);
+    }
+
+    let modified = std::fs::metadata(&source).and_then(|m| m.modified()).ok();
+    let content = read_thread_file(&source)?;
+    write_replacing(&dest, &content, modified)?;
+    std::fs::remove_file(&source).map_err(|e| FsError::io(&format!("remove compressed thread {}", thread_id), e))?;
+
+    log::info!("Decompressed thread {}", thread_id);
+    Ok(true)
+}
+
+/// Decompress a thread if it's compressed, before something reads or appends to it
+pub async fn ensure_decompressed(thread_id: &str) -> Result<(), FsError> {
+    if !tokio::fs::try_exists(compressed_path(thread_id)?).await.unwrap_or(false) {
+        return Ok(());
+    }
+    let id = thread_id.to_string();
+    filesystem::blocking_fs(move || decompress(&id)).await.map(|_| ())
+}
+
+/// Compress every thread not modified in `older_than_days` days (blocking)
+pub fn compress_idle(older_than_days: u32) -> Result<CompressionReport, String> {
+    let cutoff = SystemTime::now() - Duration::from_secs(u64::from(older_than_days) * 24 * 60 * 60);
+    let mut report = CompressionReport::default();
+
+    for path in filesystem::list_thread_files()? {
+        let Some(thread_id) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+        let idle = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        if !idle {
+            continue;
+        }
+        match compress(thread_id) {
+            Ok(Some((before, after))) => {
+                report.threads.push(thread_id.to_string());
+                report.bytes_before += before;
+                report.bytes_after += after;
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
+    if !report.threads.is_empty() {
+        log::info!(
+            "Compressed {} idle threads: {} -> {} bytes",
+            report.threads.len(),
+            report.bytes_before,
+            report.bytes_after
+        );
+    }
+    Ok(report)
+}
+
+/// Compress idle threads, flushing buffered appends first, and tell listeners
+pub async fn run(app_handle: &tauri::AppHandle, older_than_days: u32) -> Result<CompressionReport, String> {
+    if let Some(appends) = app_handle.try_state::<Arc<AppendBuffer>>() {
+        appends.flush_all().await;
+    }
+    let report = filesystem::blocking(move || compress_idle(older_than_days)).await?;
+    if !report.threads.is_empty() {
+        if let Some(bus) = app_handle.try_state::<Arc<EventBus>>() {
+            bus.publish("thread-changed", serde_json::json!({ "change": "compressed" }));
+        }
+    }
+    Ok(report)
+}
+
+/// Compress threads idle longer than `compress_threads_after_days` in settings,
+/// checking hourly for as long as the app runs
+pub fn start(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // The first check waits, leaving startup to finish
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + CHECK_INTERVAL, CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let Some(settings) = app_handle.try_state::<Arc<SettingsStore>>() else { continue };
+            let days = settings.get().compress_threads_after_days;
+            if days == 0 {
+                continue;
+            }
+            if let Err(e) = run(&app_handle, days).await {
+                log::error!("Thread compression failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,585 @@
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+
+use crate::event_bus::EventBus;
+use crate::filesystem::{self, ThreadMetadata};
+use crate::tasks::TaskProgress;
+
+/// How often threads changed since the last listing are re-read and the index saved
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Events between recorded byte offsets in a thread file
+const CHECKPOINT_INTERVAL: usize = 256;
+
+/// Most events returned by one `load_page`
+const MAX_PAGE_SIZE: usize = 2000;
+
+/// Get the persisted index path
+fn get_index_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_threads_dir()?.join("index.json"))
+}
+
+/// Thread files the saved index has entries for, whether or not they still
+/// exist (blocking)
+pub fn stored_paths() -> Result<Vec<PathBuf>, String> {
+    let path = get_index_path()?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read thread index: {}", e)),
+    };
+    let stored: Vec<StoredThread> =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse thread index: {}", e))?;
+    Ok(stored.into_iter().map(|thread| thread.path).collect())
+}
+
+/// Order of `list_threads` results
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadSort {
+    #[default]
+    UpdatedDesc,
+    UpdatedAsc,
+    CreatedDesc,
+    CreatedAsc,
+    Title,
+}
+
+/// Which threads `list_threads` returns; unset fields match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThreadFilter {
+    /// Threads carrying all of these tags
+    pub tags: Vec<String>,
+    pub archived: Option<bool>,
+    pub blueprint_id: Option<String>,
+    /// RFC 3339 lower bound on `updated_at`, inclusive
+    pub since: Option<String>,
+    /// RFC 3339 upper bound on `updated_at`, exclusive
+    pub until: Option<String>,
+    pub sort: ThreadSort,
+}
+
+impl ThreadFilter {
+    fn matches(&self, thread: &ThreadMetadata) -> bool {
+        self.tags.iter().all(|tag| thread.tags.contains(tag))
+            && self.archived.is_none_or(|archived| thread.archived == archived)
+            && self
+                .blueprint_id
+                .as_ref()
+                .is_none_or(|id| thread.blueprint_id.as_ref() == Some(id))
+            && self.since.as_ref().is_none_or(|since| thread.updated_at >= *since)
+            && self.until.as_ref().is_none_or(|until| thread.updated_at < *until)
+    }
+
+    /// Keep the matching threads, in the requested order
+    pub fn apply(&self, mut threads: Vec<ThreadMetadata>) -> Vec<ThreadMetadata> {
+        threads.retain(|thread| self.matches(thread));
+        match self.sort {
+            ThreadSort::UpdatedDesc => threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+            ThreadSort::UpdatedAsc => threads.sort_by(|a, b| a.updated_at.cmp(&b.updated_at)),
+            ThreadSort::CreatedDesc => threads.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+            ThreadSort::CreatedAsc => threads.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+            ThreadSort::Title => {
+                threads.sort_by_cached_key(|thread| thread.title.as_deref().unwrap_or_default().to_lowercase())
+            }
+        }
+        threads
+    }
+}
+
+/// Where events start in a thread file: the byte offset of every
+/// `CHECKPOINT_INTERVAL`th non-blank line, so a page can be read without parsing
+/// everything before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LineOffsets {
+    checkpoints: Vec<u64>,
+    /// Non-blank lines in the file
+    count: usize,
+}
+
+impl LineOffsets {
+    /// Scan a thread file for line starts (blocking)
+    fn scan(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open thread file: {}", e))?;
+        let mut reader = std::io::BufReader::with_capacity(1024 * 1024, file);
+        let mut offsets = Self {
+            checkpoints: Vec::new(),
+            count: 0,
+        };
+        let mut line = Vec::new();
+        let mut position = 0u64;
+
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| format!("Failed to read thread file: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            if !line.trim_ascii().is_empty() {
+                if offsets.count.is_multiple_of(CHECKPOINT_INTERVAL) {
+                    offsets.checkpoints.push(position);
+                }
+                offsets.count += 1;
+            }
+            position += read as u64;
+        }
+        Ok(offsets)
+    }
+}
+
+/// A slice of a thread's events, for loading long threads a page at a time
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadPage {
+    pub thread_id: String,
+    /// Index of the first event in the page; the header line is event 0
+    pub offset: usize,
+    pub events: Vec<serde_json::Value>,
+    /// Events in the whole thread
+    pub total: usize,
+}
+
+/// A cached entry as stored in `threads/index.json`
+#[derive(Serialize, Deserialize)]
+struct StoredThread {
+    path: PathBuf,
+    /// File mtime in nanoseconds since the epoch
+    modified_ns: Option<u64>,
+    size: u64,
+    metadata: ThreadMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offsets: Option<LineOffsets>,
+}
+
+/// Listing metadata plus the file stamp it was read from
+struct CachedThread {
+    modified: Option<SystemTime>,
+    size: u64,
+    metadata: ThreadMetadata,
+    /// Filled in the first time the thread is paged
+    offsets: Option<LineOffsets>,
+}
+
+impl CachedThread {
+    fn new(stat: &std::fs::Metadata, metadata: ThreadMetadata) -> Self {
+        Self {
+            modified: stat.modified().ok(),
+            size: stat.len(),
+            metadata,
+            offsets: None,
+        }
+    }
+
+    fn is_current(&self, stat: &std::fs::Metadata) -> bool {
+        let modified = stat.modified().ok();
+        self.modified.is_some() && self.modified == modified && self.size == stat.len()
+    }
+
+    fn store(&self, path: &Path) -> StoredThread {
+        StoredThread {
+            path: path.to_path_buf(),
+            modified_ns: self
+                .modified
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos() as u64),
+            size: self.size,
+            metadata: self.metadata.clone(),
+            offsets: self.offsets.clone(),
+        }
+    }
+
+    fn restore(stored: StoredThread) -> (PathBuf, Self) {
+        let cached = Self {
+            modified: stored.modified_ns.map(|ns| SystemTime::UNIX_EPOCH + Duration::from_nanos(ns)),
+            size: stored.size,
+            metadata: stored.metadata,
+            offsets: stored.offsets,
+        };
+        (stored.path, cached)
+    }
+}
+
+/// Snapshot of the threads directory as of the last listing
+#[derive(Default)]
+struct Snapshot {
+    /// Threads directory mtime; changes when files are added or removed
+    dir_modified: Option<SystemTime>,
+    entries: HashMap<PathBuf, CachedThread>,
+    primed: bool,
+    /// Entries changed since the index was last saved
+    unsaved: bool,
+}
+
+/// Pending invalidations reported since the last listing
+#[derive(Default)]
+struct Dirty {
+    threads: HashSet<String>,
+    /// Set when invalidations may have been missed; forces a full re-stat
+    all: bool,
+}
+
+/// Incremental thread listing, persisted in `threads/index.json`. The first call
+/// stats every file and parses the ones the saved index doesn't match; later calls
+/// only re-stat threads reported changed on the event bus, plus a full re-stat when
+/// the directory itself changed.
+pub struct ThreadIndex {
+    snapshot: Mutex<Snapshot>,
+    dirty: StdMutex<Dirty>,
+}
+
+impl ThreadIndex {
+    /// Start from the saved index, if there is a readable one
+    pub fn load() -> Self {
+        let entries = get_index_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(Vec::new());
+                }
+                let content =
+                    std::fs::read_to_string(&path).map_err(|e| format!("Failed to read thread index: {}", e))?;
+                serde_json::from_str::<Vec<StoredThread>>(&content)
+                    .map_err(|e| format!("Failed to parse thread index: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}; rebuilding the thread index", e);
+                Vec::new()
+            });
+
+        Self {
+            snapshot: Mutex::new(Snapshot {
+                entries: entries.into_iter().map(CachedThread::restore).collect(),
+                ..Snapshot::default()
+            }),
+            dirty: StdMutex::new(Dirty::default()),
+        }
+    }
+
+    /// Start over from the saved index of the current data directory, e.g. after
+    /// switching workspaces
+    pub async fn reload(&self) {
+        let fresh = Self::load();
+        *self.snapshot.lock().await = fresh.snapshot.into_inner();
+        *self.dirty.lock().unwrap() = Dirty::default();
+    }
+
+    /// Mark a thread as changed so the next listing re-reads it
+    pub fn invalidate(&self, thread_id: &str) {
+        self.dirty.lock().unwrap().threads.insert(thread_id.to_string());
+    }
+
+    /// Force the next listing to re-stat every thread file
+    pub fn invalidate_all(&self) {
+        self.dirty.lock().unwrap().all = true;
+    }
+
+    /// Invalidate entries from `thread-changed` events on the bus, re-reading and
+    /// saving changed threads every few seconds
+    pub fn watch(self: &Arc<Self>, bus: &EventBus) {
+        let mut receiver = bus.listen();
+        let index = Arc::downgrade(self);
+
+        let refresher = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(REFRESH_INTERVAL).await;
+                let Some(index) = refresher.upgrade() else { break };
+                let pending = {
+                    let dirty = index.dirty.lock().unwrap();
+                    dirty.all || !dirty.threads.is_empty()
+                };
+                if pending {
+                    if let Err(e) = index.list().await {
+                        log::warn!("Failed to refresh thread index: {}", e);
+                    }
+                }
+            }
+        });
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let event = receiver.recv().await;
+                let Some(index) = index.upgrade() else { break };
+
+                match event {
+                    Ok(event) if event.topic == "thread-changed" => {
+                        match event.payload.get("thread_id").and_then(|t| t.as_str()) {
+                            Some(thread_id) => index.invalidate(thread_id),
+                            None => index.invalidate_all(),
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => index.invalidate_all(),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// List all threads, most recently updated first
+    pub async fn list(&self) -> Result<Vec<ThreadMetadata>, String> {
+        let mut snapshot = self.snapshot.lock().await;
+
+        let dir_modified = tokio::fs::metadata(filesystem::get_threads_dir()?)
+            .await
+            .and_then(|m| m.modified())
+            .ok();
+
+        let dirty = std::mem::take(&mut *self.dirty.lock().unwrap());
+
+        if !snapshot.primed || dirty.all || dir_modified.is_none() || dir_modified != snapshot.dir_modified {
+            Self::rescan(&mut snapshot, None).await?;
+            snapshot.dir_modified = dir_modified;
+            snapshot.primed = true;
+        } else {
+            for thread_id in &dirty.threads {
+                // Either file may have come or gone if the thread was (de)compressed
+                let paths = [
+                    filesystem::get_thread_path(thread_id),
+                    crate::thread_compression::compressed_path(thread_id),
+                ];
+                for path in paths.into_iter().flatten() {
+                    Self::refresh(&mut snapshot, path).await;
+                }
+            }
+        }
+
+        if snapshot.unsaved {
+            Self::save(&mut snapshot).await;
+        }
+
+        let mut threads: Vec<ThreadMetadata> =
+            snapshot.entries.values().map(|cached| cached.metadata.clone()).collect();
+
+        // Sort by updated_at (most recent first)
+        threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+        Ok(threads)
+    }
+
+    /// Drop the saved index and re-read every thread file, reporting each file read
+    /// to `progress`. Returns the thread count.
+    pub async fn rebuild(&self, progress: &TaskProgress) -> Result<usize, String> {
+        {
+            let mut snapshot = self.snapshot.lock().await;
+            *snapshot = Snapshot::default();
+            let path = get_index_path()?;
+            if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                tokio::fs::remove_file(&path)
+                    .await
+                    .map_err(|e| format!("Failed to remove thread index: {}", e))?;
+            }
+            Self::rescan(&mut snapshot, Some(progress)).await?;
+            snapshot.dir_modified = tokio::fs::metadata(filesystem::get_threads_dir()?)
+                .await
+                .and_then(|m| m.modified())
+                .ok();
+            snapshot.primed = true;
+        }
+        let count = self.list().await?.len();
+        log::info!("Rebuilt thread index ({} threads)", count);
+        Ok(count)
+    }
+
+    /// Line offsets for a thread, from the index when its file hasn't changed
+    async fn line_offsets(&self, thread_id: &str) -> Result<(PathBuf, LineOffsets), String> {
+        let path = filesystem::get_thread_path(thread_id)?;
+        crate::thread_compression::ensure_decompressed(thread_id).await?;
+        let stat = tokio::fs::metadata(&path)
+            .await
+            .map_err(|_| format!("Thread {} not found", thread_id))?;
+
+        {
+            let snapshot = self.snapshot.lock().await;
+            if let Some(offsets) = snapshot
+                .entries
+                .get(&path)
+                .filter(|cached| cached.is_current(&stat))
+                .and_then(|cached| cached.offsets.clone())
+            {
+                return Ok((path, offsets));
+            }
+        }
+
+        let scan_path = path.clone();
+        let offsets = filesystem::blocking(move || LineOffsets::scan(&scan_path)).await?;
+
+        // Kept only if the entry describes the file as scanned; otherwise the next
+        // listing re-reads the thread and a later page scans again
+        let mut snapshot = self.snapshot.lock().await;
+        if let Some(cached) = snapshot.entries.get_mut(&path).filter(|cached| cached.is_current(&stat)) {
+            cached.offsets = Some(offsets.clone());
+            snapshot.unsaved = true;
+        }
+        Ok((path, offsets))
+    }
+
+    /// Number of events in a thread, without parsing them
+    pub async fn event_count(&self, thread_id: &str) -> Result<usize, String> {
+        Ok(self.line_offsets(thread_id).await?.1.count)
+    }
+
+    /// Read `limit` events starting at event `offset`, seeking to the nearest
+    /// recorded offset instead of parsing the thread from the start. Malformed
+    /// lines count toward offsets but are left out of the page.
+    pub async fn load_page(&self, thread_id: &str, offset: usize, limit: usize) -> Result<ThreadPage, String> {
+        let (path, offsets) = self.line_offsets(thread_id).await?;
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let total = offsets.count;
+
+        let events = if offset >= total || limit == 0 {
+            Vec::new()
+        } else {
+            let checkpoint = offset / CHECKPOINT_INTERVAL;
+            let start = offsets.checkpoints[checkpoint];
+            let skip = offset - checkpoint * CHECKPOINT_INTERVAL;
+
+            filesystem::blocking(move || {
+                let mut file = std::fs::File::open(&path).map_err(|e| format!("Failed to open thread file: {}", e))?;
+                file.seek(std::io::SeekFrom::Start(start))
+                    .map_err(|e| format!("Failed to seek thread file: {}", e))?;
+                let reader = std::io::BufReader::new(file);
+
+                let mut events = Vec::with_capacity(limit);
+                let lines = reader
+                    .split(b'\n')
+                    .map(|line| line.map_err(|e| format!("Failed to read thread file: {}", e)))
+                    .filter(|line| line.as_ref().map_or(true, |line| !line.trim_ascii().is_empty()))
+                    .skip(skip)
+                    .take(limit);
+                for line in lines {
+                    if let Some(event) = filesystem::parse_line(&line?) {
+                        events.push(event);
+                    }
+                }
+                Ok(events)
+            })
+            .await?
+        };
+
+        Ok(ThreadPage {
+            thread_id: thread_id.to_string(),
+            offset,
+            events: crate::blob_store::rehydrate(events).await?,
+            total,
+        })
+    }
+
+    /// Fill in blueprint ids and write the index. The write touches the threads
+    /// directory, so its new mtime is recorded to avoid a pointless rescan.
+    async fn save(snapshot: &mut Snapshot) {
+        let ids = filesystem::blocking(|| Ok(filesystem::blueprint_ids_by_hash()))
+            .await
+            .unwrap_or_default();
+        for cached in snapshot.entries.values_mut() {
+            filesystem::resolve_blueprint_ids(std::slice::from_mut(&mut cached.metadata), &ids);
+        }
+        // Viewed workspaces are left exactly as they were
+        if crate::viewer::is_active() {
+            snapshot.unsaved = false;
+            return;
+        }
+
+        let stored: Vec<StoredThread> = snapshot.entries.iter().map(|(path, cached)| cached.store(path)).collect();
+        let result = async {
+            let path = get_index_path()?;
+            let content =
+                serde_json::to_vec(&stored).map_err(|e| format!("Failed to serialize thread index: {}", e))?;
+            let temp = path.with_extension("json.tmp");
+            tokio::fs::write(&temp, content)
+                .await
+                .map_err(|e| format!("Failed to write thread index: {}", e))?;
+            tokio::fs::rename(&temp, &path)
+                .await
+                .map_err(|e| format!("Failed to replace thread index: {}", e))?;
+            filesystem::get_threads_dir()
+        }
+        .await;
+
+        match result {
+            Ok(threads_dir) => {
+                snapshot.unsaved = false;
+                snapshot.dir_modified = tokio::fs::metadata(threads_dir).await.and_then(|m| m.modified()).ok();
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
+    /// Re-stat every thread file, re-reading only the ones that changed
+    async fn rescan(snapshot: &mut Snapshot, progress: Option<&TaskProgress>) -> Result<(), String> {
+        let files = filesystem::blocking(|| {
+            Ok(filesystem::list_listed_thread_files()?
+                .into_iter()
+                .map(|path| {
+                    let stat = std::fs::metadata(&path).ok();
+                    (path, stat)
+                })
+                .collect::<Vec<_>>())
+        })
+        .await?;
+
+        let live: HashSet<&PathBuf> = files.iter().map(|(path, _)| path).collect();
+        let before = snapshot.entries.len();
+        snapshot.entries.retain(|path, _| live.contains(path));
+        if snapshot.entries.len() != before {
+            snapshot.unsaved = true;
+        }
+
+        // Re-read changed threads concurrently
+        let stale: Vec<(PathBuf, std::fs::Metadata)> = files
+            .into_iter()
+            .filter_map(|(path, stat)| stat.map(|stat| (path, stat)))
+            .filter(|(path, stat)| !snapshot.entries.get(path).is_some_and(|cached| cached.is_current(stat)))
+            .collect();
+
+        let total = stale.len();
+        let fresh: Vec<(PathBuf, CachedThread)> = stream::iter(stale)
+            .map(|(path, stat)| async move {
+                let metadata = filesystem::read_thread_metadata(&path, &stat).await;
+                (path, CachedThread::new(&stat, metadata))
+            })
+            .buffer_unordered(filesystem::SCAN_CONCURRENCY)
+            .enumerate()
+            .map(|(done, (path, cached))| {
+                if let Some(progress) = progress {
+                    progress.report(done + 1, total, cached.metadata.thread_id.clone());
+                }
+                (path, cached)
+            })
+            .collect()
+            .await;
+
+        if !fresh.is_empty() {
+            snapshot.unsaved = true;
+        }
+        snapshot.entries.extend(fresh);
+
+        Ok(())
+    }
+
+    /// Re-read one thread if its mtime or size moved, dropping it if it is gone
+    async fn refresh(snapshot: &mut Snapshot, path: PathBuf) {
+        let Ok(stat) = tokio::fs::metadata(&path).await else {
+            if snapshot.entries.remove(&path).is_some() {
+                snapshot.unsaved = true;
+            }
+            return;
+        };
+
+        if snapshot.entries.get(&path).is_some_and(|cached| cached.is_current(&stat)) {
+            return;
+        }
+
+        let metadata = filesystem::read_thread_metadata(&path, &stat).await;
+        snapshot.entries.insert(path, CachedThread::new(&stat, metadata));
+        snapshot.unsaved = true;
+    }
+}
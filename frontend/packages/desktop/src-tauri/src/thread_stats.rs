@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::filesystem;
+use crate::usage::UsageTotals;
+
+/// Events read from the thread at a time
+const CHUNK_EVENTS: usize = 1000;
+
+/// Characters per token in the rough estimate
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Message and tool activity, for a whole thread or one agent
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActivityStats {
+    /// Messages by role: `user`, `assistant` and `reasoning`
+    pub messages: BTreeMap<&'static str, usize>,
+    pub tool_calls: usize,
+    pub tool_calls_by_name: BTreeMap<String, usize>,
+    /// Tool calls whose input or output was an error, or that the user denied
+    pub tool_errors: usize,
+    /// Characters of message text
+    pub characters: usize,
+    /// `characters` over four, a rough token count that doesn't need the backend
+    pub estimated_tokens: usize,
+    /// What the backend reported for its model calls
+    pub usage: UsageTotals,
+}
+
+impl ActivityStats {
+    fn message(&mut self, role: &'static str, content: &str) {
+        *self.messages.entry(role).or_default() += 1;
+        self.characters += content.chars().count();
+        self.estimated_tokens = self.characters.div_ceil(CHARS_PER_TOKEN);
+    }
+
+    fn tool_call(&mut self, name: &str) {
+        self.tool_calls += 1;
+        *self.tool_calls_by_name.entry(name.to_string()).or_default() += 1;
+    }
+}
+
+/// One agent's share of a thread: what happened between its `data-agent-start`
+/// and `data-agent-finish` events
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStats {
+    pub agent_id: String,
+    pub agent_name: Option<String>,
+    pub turns: usize,
+    #[serde(flatten)]
+    pub activity: ActivityStats,
+}
+
+/// Result of `get_thread_stats`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThreadStats {
+    pub thread_id: String,
+    /// Events after the blueprint header
+    pub event_count: usize,
+    /// Agent turns, counted by `data-agent-start`
+    pub turns: usize,
+    #[serde(flatten)]
+    pub activity: ActivityStats,
+    pub first_event_at: Option<String>,
+    pub last_event_at: Option<String>,
+    /// Seconds from the first timestamped event to the last
+    pub duration_seconds: Option<i64>,
+    /// In the order agents first spoke
+    pub agents: Vec<AgentStats>,
+}
+
+/// Folds events into `ThreadStats` one at a time
+#[derive(Default)]
+struct Collector {
+    stats: ThreadStats,
+    /// Index in `stats.agents` of the agent whose turn it is
+    current: Option<usize>,
+}
+
+impl Collector {
+    fn agent(&mut self, data: Option<&serde_json::Value>) -> usize {
+        let field = |key: &str| data.and_then(|d| d.get(key)).and_then(|v| v.as_str());
+        let agent_id = field("agentId").unwrap_or("unknown");
+        if let Some(index) = self.stats.agents.iter().position(|a| a.agent_id == agent_id) {
+            return index;
+        }
+        self.stats.agents.push(AgentStats {
+            agent_id: agent_id.to_string(),
+            agent_name: field("agentName").map(str::to_string),
+            turns: 0,
+            activity: ActivityStats::default(),
+        });
+        self.stats.agents.len() - 1
+    }
+
+    /// Apply `update` to the thread's totals and to the current agent's
+    fn both(&mut self, update: impl Fn(&mut ActivityStats)) {
+        update(&mut self.stats.activity);
+        if let Some(index) = self.current {
+            update(&mut self.stats.agents[index].activity);
+        }
+    }
+
+    fn add(&mut self, event: &serde_json::Value) {
+        self.stats.event_count += 1;
+        if let Some(stamp) = event.get("timestamp").and_then(|t| t.as_str()) {
+            if self.stats.first_event_at.is_none() {
+                self.stats.first_event_at = Some(stamp.to_string());
+            }
+            self.stats.last_event_at = Some(stamp.to_string());
+        }
+
+        let content = event
+            .get("content")
+            .or_else(|| event.get("text"))
+            .and_then(|c| c.as_str())
+            .unwrap_or_default();
+
+        match event.get("type").and_then(|t| t.as_str()).unwrap_or_default() {
+            "data-agent-start" => {
+                let index = self.agent(event.get("data"));
+                self.stats.turns += 1;
+                self.stats.agents[index].turns += 1;
+                self.current = Some(index);
+            }
+            "data-agent-finish" => self.current = None,
+            "user-message" => self.stats.activity.message("user", content),
+            "text-complete" => self.both(|stats| stats.message("assistant", content)),
+            "reasoning-complete" => self.both(|stats| stats.message("reasoning", content)),
+            "tool-input-available" => {
+                let name = event.get("toolName").and_then(|n| n.as_str()).unwrap_or("unknown");
+                self.both(|stats| stats.tool_call(name));
+            }
+            "tool-input-error" | "tool-output-error" | "tool-output-denied" => {
+                self.both(|stats| stats.tool_errors += 1);
+            }
+            _ => {
+                if let Some(usage) = UsageTotals::from_event(event) {
+                    self.both(|stats| stats.usage.add(&usage));
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> ThreadStats {
+        if let (Some(first), Some(last)) = (&self.stats.first_event_at, &self.stats.last_event_at) {
+            let parse = |stamp: &str| chrono::DateTime::parse_from_rfc3339(stamp).ok();
+            if let (Some(first), Some(last)) = (parse(first), parse(last)) {
+                self.stats.duration_seconds = Some((last - first).num_seconds().max(0));
+            }
+        }
+        self.stats
+    }
+}
+
+/// Walk a thread's events and total its messages, tool calls and tokens, overall
+/// and per agent, a chunk at a time
+pub async fn collect(thread_id: String) -> Result<ThreadStats, String> {
+    let mut collector = Collector::default();
+    collector.stats.thread_id = thread_id.clone();
+    filesystem::stream_thread(thread_id, CHUNK_EVENTS, |chunk| {
+        // The first event is the blueprint header
+        let skip = usize::from(chunk.offset == 0);
+        for event in chunk.events.iter().skip(skip) {
+            collector.add(event);
+        }
+        Ok(())
+    })
+    .await?;
+    Ok(collector.finish())
+}
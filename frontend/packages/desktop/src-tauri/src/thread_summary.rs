@@ -0,0 +1,211 @@
+use memchr::memmem;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use crate::filesystem::{self, ARCHIVED_EVENT, TAGS_EVENT};
+
+/// Bytes read from the start of a thread for its header and first message; the
+/// header line is read whole even when longer
+const HEAD_BYTES: usize = 64 * 1024;
+
+/// Characters of the latest message kept as a thread's preview
+const PREVIEW_CHARS: usize = 120;
+
+/// Characters of the first user message kept as the fallback title
+const TITLE_CHARS: usize = 50;
+
+const TITLE_EVENT: &str = "data-thread-title";
+
+/// What a thread listing needs from a thread file
+#[derive(Debug, Clone, Default)]
+pub struct ThreadSummary {
+    /// Latest data-thread-title
+    pub explicit_title: Option<String>,
+    /// Start of the first user message, the fallback title
+    pub first_message_title: Option<String>,
+    /// Lines in the file, the header included
+    pub event_count: usize,
+    /// Start of the latest user or assistant message
+    pub preview: Option<String>,
+    /// Timestamp of the latest event that has one
+    pub last_event_at: Option<String>,
+    pub blueprint_hash: Option<String>,
+    pub archived: bool,
+    pub tags: Vec<String>,
+    pub parent_thread_id: Option<String>,
+}
+
+/// A summary and the file state it was made from
+struct Cached {
+    modified: SystemTime,
+    len: u64,
+    summary: ThreadSummary,
+}
+
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, Cached>>> = LazyLock::new(Default::default);
+
+/// Summarize a thread file, compressed or not, reusing the last summary while its
+/// modification time and size are unchanged (blocking)
+pub fn summarize(path: &Path) -> Option<ThreadSummary> {
+    let stat = fs::metadata(path).ok()?;
+    let modified = stat.modified().ok()?;
+    if let Some(cached) = CACHE.lock().unwrap().get(path) {
+        if cached.modified == modified && cached.len == stat.len() {
+            return Some(cached.summary.clone());
+        }
+    }
+
+    let summary = if crate::thread_compression::is_compressed(path) {
+        summarize_bytes(&crate::thread_compression::read_thread_file(path).ok()?)
+    } else if stat.len() == 0 {
+        ThreadSummary::default()
+    } else {
+        let file = fs::File::open(path).ok()?;
+        // SAFETY: thread files are only ever appended to, which doesn't invalidate
+        // the mapped prefix, and rewrites replace the file rather than truncate it
+        let map = unsafe { memmap2::Mmap::map(&file) }.ok()?;
+        summarize_bytes(&map)
+    };
+
+    CACHE.lock().unwrap().insert(
+        path.to_path_buf(),
+        Cached {
+            modified,
+            len: stat.len(),
+            summary: summary.clone(),
+        },
+    );
+    Some(summary)
+}
+
+/// Drop cached summaries of files not in `paths`, e.g. deleted or compressed threads
+pub fn retain(paths: &[PathBuf]) {
+    let mut cache = CACHE.lock().unwrap();
+    if cache.len() > paths.len() {
+        let keep: std::collections::HashSet<&PathBuf> = paths.iter().collect();
+        cache.retain(|path, _| keep.contains(path));
+    }
+}
+
+/// Summarize JSONL without parsing all of it: the header and first user message
+/// come from the first `HEAD_BYTES`, and the latest title, tags, archived flag,
+/// message and timestamp from parsing lines backwards from the end, skipping those
+/// that can't be any of them
+fn summarize_bytes(data: &[u8]) -> ThreadSummary {
+    let mut summary = ThreadSummary {
+        event_count: memchr::memchr_iter(b'\n', data).count() + usize::from(!data.ends_with(b"\n") && !data.is_empty()),
+        ..Default::default()
+    };
+
+    let header_end = memchr::memchr(b'\n', data).map_or(data.len(), |newline| newline + 1);
+    let head_end = match data.get(..HEAD_BYTES.max(header_end)) {
+        Some(head) => memchr::memrchr(b'\n', head).map_or(head.len(), |newline| newline + 1),
+        None => data.len(),
+    };
+    let mut head = data[..head_end].split(|&b| b == b'\n');
+
+    if let Some(header) = head.next().and_then(filesystem::parse_line) {
+        summary.blueprint_hash = header.get("blueprint").map(filesystem::blueprint_hash);
+        summary.parent_thread_id = header
+            .get("forked_from")
+            .and_then(|f| f.get("thread_id"))
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
+    }
+
+    let user_message = quoted("user-message");
+    let first_message = head
+        .filter(|line| memmem::find(line, &user_message).is_some())
+        .chain(
+            // A first message past the head, after a long run of other events
+            data[head_end..]
+                .split(|&b| b == b'\n')
+                .filter(|line| memmem::find(line, &user_message).is_some()),
+        )
+        .filter_map(filesystem::parse_line)
+        .find(|event| event_type(event) == Some("user-message"));
+    summary.first_message_title = first_message
+        .as_ref()
+        .and_then(|event| event.get("content"))
+        .and_then(|c| c.as_str())
+        .map(|content| {
+            if content.chars().count() > TITLE_CHARS {
+                format!("{}...", content.chars().take(TITLE_CHARS).collect::<String>())
+            } else {
+                content.to_string()
+            }
+        });
+
+    scan_tail(data, header_end, &mut summary);
+    summary
+}
+
+/// Fill in the latest title, archived flag, tags, message preview and event
+/// timestamp, walking lines back from the end until all are found
+fn scan_tail(data: &[u8], header_end: usize, summary: &mut ThreadSummary) {
+    let markers = [
+        quoted(TITLE_EVENT),
+        quoted(ARCHIVED_EVENT),
+        quoted(TAGS_EVENT),
+        quoted("user-message"),
+        quoted("text-complete"),
+    ];
+    let timestamp = quoted("timestamp");
+    let (mut title, mut archived, mut tags, mut preview) = (false, false, false, false);
+
+    let mut end = data.len();
+    while end > header_end && !(title && archived && tags && preview && summary.last_event_at.is_some()) {
+        let start = memchr::memrchr(b'\n', &data[header_end..end.saturating_sub(1)])
+            .map_or(header_end, |newline| header_end + newline + 1);
+        let line = &data[start..end];
+        end = start;
+
+        let wanted_timestamp = summary.last_event_at.is_none() && memmem::find(line, &timestamp).is_some();
+        if !wanted_timestamp && !markers.iter().any(|marker| memmem::find(line, marker).is_some()) {
+            continue;
+        }
+        let Some(event) = filesystem::parse_line(line) else {
+            continue;
+        };
+
+        if summary.last_event_at.is_none() {
+            summary.last_event_at = event.get("timestamp").and_then(|t| t.as_str()).map(str::to_string);
+        }
+        let data_field = |key: &str| event.get("data").and_then(|d| d.get(key));
+        match event_type(&event) {
+            Some(TITLE_EVENT) if !title => {
+                title = true;
+                summary.explicit_title = data_field("title").and_then(|t| t.as_str()).map(str::to_string);
+            }
+            Some(ARCHIVED_EVENT) if !archived => {
+                archived = true;
+                summary.archived = data_field("archived").and_then(|a| a.as_bool()).unwrap_or(false);
+            }
+            Some(TAGS_EVENT) if !tags => {
+                tags = true;
+                summary.tags = data_field("tags")
+                    .and_then(|t| serde_json::from_value(t.clone()).ok())
+                    .unwrap_or_default();
+            }
+            Some("user-message" | "text-complete") if !preview => {
+                if let Some(content) = event.get("content").and_then(|c| c.as_str()) {
+                    preview = true;
+                    summary.preview = Some(content.trim().chars().take(PREVIEW_CHARS).collect());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn event_type(event: &serde_json::Value) -> Option<&str> {
+    event.get("type").and_then(|t| t.as_str())
+}
+
+/// `"name"`, as a string appears in serialized JSON
+fn quoted(name: &str) -> Vec<u8> {
+    format!("\"{}\"", name).into_bytes()
+}
@@ -0,0 +1,214 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::ipc::Channel;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+use crate::thread_compression;
+
+/// How often a tailed file is checked when nothing on the bus says it changed;
+/// catches writers outside this process
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Thread changes that replace the file rather than append to it
+const REWRITE_CHANGES: [&str; 3] = ["compacted", "amended", "synced"];
+
+/// Events that landed in a tailed thread file
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadTailEvent {
+    pub thread_id: String,
+    pub events: Vec<serde_json::Value>,
+    /// The file was rewritten; reload the thread rather than applying `events`
+    pub reset: bool,
+}
+
+/// Read position in one thread file
+struct Cursor {
+    path: PathBuf,
+    offset: u64,
+    /// Bytes after the last newline, waiting for the rest of their line
+    partial: Vec<u8>,
+}
+
+impl Cursor {
+    /// Read complete lines appended since the last call (blocking). Returns `None`
+    /// when the file shrank, i.e. was rewritten, after moving to its new end.
+    fn read_new(&mut self) -> Result<Option<Vec<serde_json::Value>>, String> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            // Compressed while idle; the same JSONL comes back when it's next appended to
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Some(Vec::new())),
+            Err(e) => return Err(format!("Failed to open thread file: {}", e)),
+        };
+        let size = file
+            .metadata()
+            .map_err(|e| format!("Failed to read thread metadata: {}", e))?
+            .len();
+
+        if size < self.offset {
+            self.offset = size;
+            self.partial.clear();
+            return Ok(None);
+        }
+        if size == self.offset {
+            return Ok(Some(Vec::new()));
+        }
+
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| format!("Failed to seek thread file: {}", e))?;
+        let mut data = std::mem::take(&mut self.partial);
+        let read = file
+            .take(size - self.offset)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read thread file: {}", e))?;
+        self.offset += read as u64;
+
+        let complete = data.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.partial = data.split_off(complete);
+
+        Ok(Some(
+            data.split(|&b| b == b'\n')
+                .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+                .filter_map(|line| match serde_json::from_slice(line) {
+                    Ok(event) => Some(event),
+                    Err(e) => {
+                        log::warn!("Failed to parse tailed event line: {}", e);
+                        None
+                    }
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Tails thread files for subscribers, so live updates come from what is on disk
+/// regardless of which process wrote it
+pub struct ThreadTails {
+    bus: Arc<EventBus>,
+    subscriptions: Mutex<HashMap<u64, tauri::async_runtime::JoinHandle<()>>>,
+    next_id: AtomicU64,
+}
+
+impl ThreadTails {
+    pub fn new(bus: Arc<EventBus>) -> Self {
+        Self {
+            bus,
+            subscriptions: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Send events appended to `thread_id` from now on to `channel`
+    pub async fn subscribe(&self, thread_id: String, channel: Channel<ThreadTailEvent>) -> Result<u64, String> {
+        let path = filesystem::get_thread_path(&thread_id)?;
+        let offset = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata.len(),
+            // A compressed thread is tailed from where its JSONL ends once decompressed
+            Err(_) => {
+                let id = thread_id.clone();
+                filesystem::blocking_fs(move || thread_compression::read_thread(&id)).await?.len() as u64
+            }
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let cursor = Cursor {
+            path,
+            offset,
+            partial: Vec::new(),
+        };
+        let task = tauri::async_runtime::spawn(tail(self.bus.clone(), thread_id.clone(), cursor, channel));
+        self.subscriptions.lock().unwrap().insert(id, task);
+
+        log::info!("Tailing thread {} (subscription {})", thread_id, id);
+        Ok(id)
+    }
+
+    pub fn unsubscribe(&self, subscription_id: u64) {
+        if let Some(task) = self.subscriptions.lock().unwrap().remove(&subscription_id) {
+            task.abort();
+        }
+    }
+}
+
+async fn tail(bus: Arc<EventBus>, thread_id: String, mut cursor: Cursor, channel: Channel<ThreadTailEvent>) {
+    let mut receiver = bus.listen();
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        // Wake on this thread's bus changes, or on the poll timer
+        let mut rewritten = false;
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(event) if event.topic == "thread-changed"
+                    && event.payload.get("thread_id").and_then(|t| t.as_str()) == Some(thread_id.as_str()) =>
+                {
+                    let change = event.payload.get("change").and_then(|c| c.as_str()).unwrap_or_default();
+                    rewritten = REWRITE_CHANGES.contains(&change);
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {}
+        }
+
+        let (read, returned) = match tauri::async_runtime::spawn_blocking(move || {
+            let read = cursor.read_new();
+            (read, cursor)
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => break,
+        };
+        cursor = returned;
+
+        let message = match read {
+            Ok(Some(events)) if !rewritten => {
+                if events.is_empty() {
+                    continue;
+                }
+                let events = match crate::blob_store::rehydrate(events).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        continue;
+                    }
+                };
+                ThreadTailEvent {
+                    thread_id: thread_id.clone(),
+                    events,
+                    reset: false,
+                }
+            }
+            Ok(_) => {
+                // A rewrite may not have shrunk the file; either way restart from its end
+                if rewritten {
+                    if let Ok(metadata) = tokio::fs::metadata(&cursor.path).await {
+                        cursor.offset = metadata.len();
+                        cursor.partial.clear();
+                    }
+                }
+                ThreadTailEvent {
+                    thread_id: thread_id.clone(),
+                    events: Vec::new(),
+                    reset: true,
+                }
+            }
+            Err(e) => {
+                log::warn!("Stopped tailing thread {}: {}", thread_id, e);
+                break;
+            }
+        };
+
+        if channel.send(message).is_err() {
+            break;
+        }
+    }
+}
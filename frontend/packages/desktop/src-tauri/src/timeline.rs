@@ -0,0 +1,142 @@
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::filesystem::{self, ThreadMetadata};
+use crate::thread_index::ThreadIndex;
+
+/// Entries returned when the range gives no limit
+const DEFAULT_LIMIT: usize = 500;
+
+/// Characters of message text kept in a summary
+const SUMMARY_CHARS: usize = 160;
+
+/// Time window for `get_activity_timeline`; unset bounds are open
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimelineRange {
+    /// RFC 3339 lower bound, inclusive
+    pub since: Option<String>,
+    /// RFC 3339 upper bound, exclusive
+    pub until: Option<String>,
+    /// Return at most this many of the newest entries
+    pub limit: Option<usize>,
+}
+
+/// One thing an agent or user did, in some thread
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub timestamp: String,
+    /// The event carried no timestamp; this one is the last known time before it
+    pub approximate: bool,
+    pub thread_id: String,
+    pub thread_title: Option<String>,
+    pub event_type: String,
+    pub summary: String,
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    let mut summary: String = text.chars().take(SUMMARY_CHARS).collect();
+    if summary.len() < text.len() {
+        summary.push('…');
+    }
+    summary
+}
+
+/// A one-line description of an event worth showing, or `None` for streaming noise
+fn summarize(event: &serde_json::Value) -> Option<(String, String)> {
+    let event_type = event.get("type")?.as_str()?;
+    let content = event
+        .get("content")
+        .and_then(|c| c.as_str())
+        .or_else(|| event.get("data").and_then(|d| d.get("content")).and_then(|c| c.as_str()));
+    let tool = || event.get("toolName").and_then(|n| n.as_str()).unwrap_or("tool");
+
+    let summary = match event_type {
+        "user-message" | "data-user-message" => truncate(content?),
+        "text-complete" => truncate(content?),
+        "tool-input-available" => format!("Called {}", tool()),
+        "tool-output-available" => format!("{} returned", tool()),
+        "data-thread-title" => format!(
+            "Titled \"{}\"",
+            event.get("data").and_then(|d| d.get("title")).and_then(|t| t.as_str())?
+        ),
+        "error" => truncate(
+            event
+                .get("errorText")
+                .and_then(|e| e.as_str())
+                .or(content)
+                .unwrap_or("Unknown error"),
+        ),
+        _ => return None,
+    };
+
+    Some((event_type.to_string(), summary))
+}
+
+/// Timeline entries for one thread. Events without a timestamp take the last one
+/// seen before them, starting from the thread's creation time.
+async fn thread_entries(thread: ThreadMetadata, range: &TimelineRange) -> Vec<TimelineEntry> {
+    let events = match filesystem::load_thread(thread.thread_id.clone()).await {
+        Ok(events) => events,
+        Err(e) => {
+            log::warn!("Skipping thread {} in timeline: {}", thread.thread_id, e);
+            return Vec::new();
+        }
+    };
+
+    let mut last_seen = thread.created_at.clone();
+    let mut entries = Vec::new();
+
+    for event in events.iter().skip(1) {
+        let stamp = event.get("timestamp").and_then(|t| t.as_str());
+        if let Some(stamp) = stamp {
+            last_seen = stamp.to_string();
+        }
+
+        let Some((event_type, summary)) = summarize(event) else { continue };
+        let in_range = range.since.as_ref().is_none_or(|since| last_seen >= *since)
+            && range.until.as_ref().is_none_or(|until| last_seen < *until);
+        if !in_range {
+            continue;
+        }
+
+        entries.push(TimelineEntry {
+            timestamp: last_seen.clone(),
+            approximate: stamp.is_none(),
+            thread_id: thread.thread_id.clone(),
+            thread_title: thread.title.clone(),
+            event_type,
+            summary,
+        });
+    }
+
+    entries
+}
+
+/// Activity across all threads in `range`, oldest first. The listing index rules
+/// out threads not touched during the range before any are opened.
+pub async fn collect(index: &ThreadIndex, range: TimelineRange) -> Result<Vec<TimelineEntry>, String> {
+    let threads: Vec<ThreadMetadata> = index
+        .list()
+        .await?
+        .into_iter()
+        .filter(|t| range.since.as_ref().is_none_or(|since| t.updated_at >= *since))
+        .filter(|t| range.until.as_ref().is_none_or(|until| t.created_at < *until))
+        .collect();
+
+    let mut entries: Vec<TimelineEntry> = stream::iter(threads)
+        .map(|thread| thread_entries(thread, &range))
+        .buffer_unordered(filesystem::SCAN_CONCURRENCY)
+        .flat_map(stream::iter)
+        .collect()
+        .await;
+
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let limit = range.limit.unwrap_or(DEFAULT_LIMIT);
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::event_bus::EventBus;
+use crate::settings::SettingsStore;
+
+/// Release channel updates come from, from `update_channel` in settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases, which go out here before they're promoted to stable
+    Beta,
+}
+
+impl UpdateChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// How often to look for updates in the background
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Delay before the first background check, leaving startup to finish
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// Update manifest URL, with `{channel}` replaced by the channel name. Set by
+/// `CHIMERA_UPDATE_ENDPOINT` at runtime or at build time; builds without one
+/// don't update.
+fn endpoint(channel: UpdateChannel) -> Result<reqwest::Url, String> {
+    let template = std::env::var("CHIMERA_UPDATE_ENDPOINT")
+        .ok()
+        .or_else(|| option_env!("CHIMERA_UPDATE_ENDPOINT").map(str::to_string))
+        .ok_or("Updates aren't configured for this build")?;
+    reqwest::Url::parse(&template.replace("{channel}", channel.as_str()))
+        .map_err(|e| format!("Invalid update endpoint {}: {}", template, e))
+}
+
+/// Public key update signatures are checked against, set at build time
+pub fn pubkey() -> Option<&'static str> {
+    option_env!("CHIMERA_UPDATER_PUBKEY")
+}
+
+/// An available update
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub date: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// An update found by `check`, then its downloaded package
+struct Staged {
+    update: Update,
+    channel: UpdateChannel,
+    bytes: Option<Vec<u8>>,
+}
+
+/// Checks for, downloads and installs app updates, publishing `update-available`,
+/// `update-download-progress` and `update-downloaded`
+pub struct Updater {
+    app_handle: tauri::AppHandle,
+    staged: tokio::sync::Mutex<Option<Staged>>,
+    /// Version last announced, so background checks announce each one once
+    announced: Mutex<Option<String>>,
+}
+
+impl Updater {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self {
+            app_handle,
+            staged: tokio::sync::Mutex::new(None),
+            announced: Mutex::new(None),
+        }
+    }
+
+    fn publish(&self, topic: &str, payload: serde_json::Value) {
+        if let Some(bus) = self.app_handle.try_state::<Arc<EventBus>>() {
+            bus.publish(topic, payload);
+        }
+    }
+
+    /// Look for an update on the channel in settings, publishing `update-available`
+    /// the first time each version is found
+    #[tracing::instrument(skip(self), err)]
+    pub async fn check(&self) -> Result<Option<UpdateInfo>, String> {
+        if pubkey().is_none() {
+            return Err("Updates aren't configured for this build".to_string());
+        }
+        let channel = self
+            .app_handle
+            .try_state::<Arc<SettingsStore>>()
+            .map(|settings| settings.get().update_channel)
+            .unwrap_or_default();
+
+        let updater = self
+            .app_handle
+            .updater_builder()
+            .endpoints(vec![endpoint(channel)?])
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("Failed to set up updater: {}", e))?;
+        let update = updater.check().await.map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+        let mut staged = self.staged.lock().await;
+        let Some(update) = update else {
+            *staged = None;
+            log::info!("No update available on the {} channel", channel.as_str());
+            return Ok(None);
+        };
+
+        let info = UpdateInfo {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            channel,
+            date: update.date.map(|date| date.to_string()),
+            notes: update.body.clone(),
+        };
+        // Keep a download of the same version
+        if staged.as_ref().is_none_or(|s| s.update.version != update.version || s.channel != channel) {
+            *staged = Some(Staged {
+                update,
+                channel,
+                bytes: None,
+            });
+        }
+        drop(staged);
+
+        let first = self.announced.lock().unwrap().replace(info.version.clone()).as_ref() != Some(&info.version);
+        if first {
+            log::info!("Update {} available on the {} channel", info.version, channel.as_str());
+            self.publish("update-available", serde_json::json!(info));
+        }
+        Ok(Some(info))
+    }
+
+    /// Download the update found by the last check, publishing progress
+    #[tracing::instrument(skip(self), err)]
+    pub async fn download(&self) -> Result<UpdateInfo, String> {
+        let mut staged = self.staged.lock().await;
+        let staged = staged.as_mut().ok_or("No update to download; check for updates first")?;
+        let info = UpdateInfo {
+            version: staged.update.version.clone(),
+            current_version: staged.update.current_version.clone(),
+            channel: staged.channel,
+            date: staged.update.date.map(|date| date.to_string()),
+            notes: staged.update.body.clone(),
+        };
+        if staged.bytes.is_some() {
+            return Ok(info);
+        }
+
+        let mut downloaded = 0u64;
+        let mut last_percent = None;
+        let bytes = staged
+            .update
+            .download(
+                |chunk, total| {
+                    downloaded += chunk as u64;
+                    // One event per percent, or per chunk when the size is unknown
+                    let percent = total.map(|total| downloaded * 100 / total.max(1));
+                    if percent.is_none() || percent != last_percent {
+                        last_percent = percent;
+                        self.publish(
+                            "update-download-progress",
+                            serde_json::json!({ "version": info.version, "downloaded": downloaded, "total": total }),
+                        );
+                    }
+                },
+                || {},
+            )
+            .await
+            .map_err(|e| format!("Failed to download update: {}", e))?;
+
+        log::info!("Downloaded update {} ({} bytes)", info.version, bytes.len());
+        staged.bytes = Some(bytes);
+        self.publish("update-downloaded", serde_json::json!(info));
+        Ok(info)
+    }
+
+    /// Install the downloaded update and relaunch. Terminals and Python backends are
+    /// shut down first, as on any exit, since the installer may end this process.
+    #[tracing::instrument(skip(self), err)]
+    pub async fn install_and_restart(&self) -> Result<(), String> {
+        let mut staged = self.staged.lock().await;
+        let ready = staged.as_ref().is_some_and(|staged| staged.bytes.is_some());
+        if !ready {
+            return Err("No downloaded update to install".to_string());
+        }
+        let Staged { update, bytes, .. } = staged.take().expect("checked above");
+
+        log::info!("Installing update {}", update.version);
+        crate::shutdown_services(&self.app_handle).await;
+        update
+            .install(bytes.expect("checked above"))
+            .map_err(|e| format!("Failed to install update: {}", e))?;
+
+        self.app_handle.restart()
+    }
+
+    /// Check for updates now and then every few hours for as long as the app runs
+    pub fn start(self: &Arc<Self>) {
+        if pubkey().is_none() {
+            log::info!("Updater disabled: no update signing key in this build");
+            return;
+        }
+        let updater = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + FIRST_CHECK_DELAY, CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = updater.check().await {
+                    log::warn!("Background update check failed: {}", e);
+                }
+            }
+        });
+    }
+}
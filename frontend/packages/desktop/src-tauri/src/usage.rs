@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::event_bus::EventBus;
+use crate::filesystem;
+
+/// Event type the backend emits after each model call
+const USAGE_EVENT: &str = "chimera-app-usage";
+
+/// Token and cost totals
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    /// Only counted when the backend reports a cost
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub(crate) fn add(&mut self, other: &UsageTotals) {
+        self.calls += other.calls;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// Totals for one usage event, if it is one
+    pub(crate) fn from_event(event: &serde_json::Value) -> Option<Self> {
+        if event.get("type").and_then(|t| t.as_str()) != Some(USAGE_EVENT) {
+            return None;
+        }
+        let tokens = |key: &str| event.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(Self {
+            calls: 1,
+            input_tokens: tokens("inputTokens"),
+            output_tokens: tokens("outputTokens"),
+            cache_write_tokens: tokens("cacheWriteTokens"),
+            cache_read_tokens: tokens("cacheReadTokens"),
+            total_tokens: tokens("totalTokens"),
+            cost_usd: event
+                .get("costUsd")
+                .or_else(|| event.get("cost"))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0),
+        })
+    }
+}
+
+/// Persistent per-day, per-thread totals
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    /// `YYYY-MM-DD` -> thread id -> totals
+    days: BTreeMap<String, BTreeMap<String, UsageTotals>>,
+    /// Last `YYYY-MM` a budget warning was sent for
+    warned_month: Option<String>,
+}
+
+/// Which days `get_usage_report` covers; unset bounds are open
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UsageRange {
+    /// `YYYY-MM-DD`, inclusive
+    pub since: Option<String>,
+    /// `YYYY-MM-DD`, inclusive
+    pub until: Option<String>,
+}
+
+/// How report rows are grouped
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGrouping {
+    #[default]
+    Day,
+    Month,
+    Thread,
+    Total,
+}
+
+/// One row of a usage report
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageRow {
+    /// Day, month or thread id, depending on the grouping; `total` for the total
+    pub key: String,
+    pub totals: UsageTotals,
+}
+
+/// Monthly limits that trigger a `usage-budget-exceeded` event
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct UsageBudget {
+    pub cost_usd: Option<f64>,
+    pub tokens: Option<u64>,
+}
+
+impl UsageBudget {
+    /// Read `CHIMERA_MONTHLY_BUDGET_USD` and `CHIMERA_MONTHLY_TOKEN_BUDGET`
+    fn from_env() -> Self {
+        Self {
+            cost_usd: std::env::var("CHIMERA_MONTHLY_BUDGET_USD").ok().and_then(|v| v.parse().ok()),
+            tokens: std::env::var("CHIMERA_MONTHLY_TOKEN_BUDGET").ok().and_then(|v| v.parse().ok()),
+        }
+    }
+
+    fn exceeded_by(&self, totals: &UsageTotals) -> bool {
+        self.cost_usd.is_some_and(|budget| totals.cost_usd > budget)
+            || self.tokens.is_some_and(|budget| totals.total_tokens > budget)
+    }
+}
+
+fn get_ledger_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("usage.json"))
+}
+
+/// Accumulates usage from appended events into `usage.json`
+pub struct UsageLedger {
+    ledger: Mutex<Ledger>,
+    budget: UsageBudget,
+    bus: Arc<EventBus>,
+}
+
+impl UsageLedger {
+    /// Load the saved ledger
+    pub fn load(bus: Arc<EventBus>) -> Self {
+        let ledger = get_ledger_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(Ledger::default());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read usage ledger: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse usage ledger: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                Ledger::default()
+            });
+
+        Self {
+            ledger: Mutex::new(ledger),
+            budget: UsageBudget::from_env(),
+            bus,
+        }
+    }
+
+    fn save(&self, ledger: &Ledger) -> Result<(), String> {
+        let path = get_ledger_path()?;
+        let content = serde_json::to_string(ledger).map_err(|e| format!("Failed to serialize usage ledger: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write usage ledger: {}", e))
+    }
+
+    /// Add any usage events among `events` to today's totals for the thread
+    pub fn record(&self, thread_id: &str, events: &[serde_json::Value]) {
+        let mut added = UsageTotals::default();
+        for totals in events.iter().filter_map(UsageTotals::from_event) {
+            added.add(&totals);
+        }
+        if added.calls == 0 {
+            return;
+        }
+
+        let now = chrono::Utc::now();
+        let day = now.format("%Y-%m-%d").to_string();
+        let month = now.format("%Y-%m").to_string();
+
+        let mut ledger = self.ledger.lock().unwrap();
+        ledger
+            .days
+            .entry(day)
+            .or_default()
+            .entry(thread_id.to_string())
+            .or_default()
+            .add(&added);
+
+        let month_totals = month_totals(&ledger, &month);
+        if self.budget.exceeded_by(&month_totals) && ledger.warned_month.as_deref() != Some(month.as_str()) {
+            log::warn!("Monthly usage budget exceeded: {:?}", month_totals);
+            ledger.warned_month = Some(month.clone());
+            self.bus.publish(
+                "usage-budget-exceeded",
+                serde_json::json!({ "month": month, "totals": month_totals, "budget": self.budget }),
+            );
+        }
+
+        if let Err(e) = self.save(&ledger) {
+            log::error!("{}", e);
+        }
+    }
+
+    /// Totals for `range`, grouped by `group_by`, in key order
+    pub fn report(&self, range: &UsageRange, group_by: UsageGrouping) -> Vec<UsageRow> {
+        let ledger = self.ledger.lock().unwrap();
+        let mut rows: BTreeMap<String, UsageTotals> = BTreeMap::new();
+
+        let days = ledger.days.iter().filter(|(day, _)| {
+            range.since.as_ref().is_none_or(|since| *day >= since) && range.until.as_ref().is_none_or(|until| *day <= until)
+        });
+        for (day, threads) in days {
+            for (thread_id, totals) in threads {
+                let key = match group_by {
+                    UsageGrouping::Day => day.clone(),
+                    UsageGrouping::Month => day[..7.min(day.len())].to_string(),
+                    UsageGrouping::Thread => thread_id.clone(),
+                    UsageGrouping::Total => "total".to_string(),
+                };
+                rows.entry(key).or_default().add(totals);
+            }
+        }
+
+        rows.into_iter().map(|(key, totals)| UsageRow { key, totals }).collect()
+    }
+}
+
+fn month_totals(ledger: &Ledger, month: &str) -> UsageTotals {
+    let mut totals = UsageTotals::default();
+    for (_, threads) in ledger.days.iter().filter(|(day, _)| day.starts_with(month)) {
+        for thread in threads.values() {
+            totals.add(thread);
+        }
+    }
+    totals
+}
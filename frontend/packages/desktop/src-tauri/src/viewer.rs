@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::ChimeraError;
+
+/// Set once at startup, before any state that reads the data directory
+static VIEWER: OnceLock<Option<ViewerMode>> = OnceLock::new();
+
+/// A read-only session: mutating commands fail and no backend is started
+#[derive(Debug, Clone, Serialize)]
+pub struct ViewerMode {
+    /// The workspace being viewed
+    pub source: Option<String>,
+    pub data_dir: String,
+}
+
+/// Whether viewer mode was requested, with `--viewer [path]` or `CHIMERA_VIEWER`
+/// (`1`, or a path)
+fn requested_source(args: &[String]) -> Option<Option<String>> {
+    if let Some(index) = args.iter().position(|a| a == "--viewer") {
+        return Some(args.get(index + 1).filter(|a| !a.starts_with("--")).cloned());
+    }
+    match std::env::var("CHIMERA_VIEWER").as_deref() {
+        Ok("") | Ok("0") | Err(_) => None,
+        Ok("1") | Ok("true") => Some(None),
+        Ok(path) => Some(Some(path.to_string())),
+    }
+}
+
+/// Unpack a workspace archive into a temporary directory. An archive holding a
+/// single top-level folder is viewed from inside that folder.
+fn extract_archive(archive: &Path) -> Result<PathBuf, String> {
+    let file = std::fs::File::open(archive).map_err(|e| format!("Failed to open workspace archive: {}", e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read workspace archive: {}", e))?;
+
+    let dest = std::env::temp_dir().join(format!("chimera-viewer-{}", uuid::Uuid::new_v4()));
+    zip.extract(&dest)
+        .map_err(|e| format!("Failed to extract workspace archive: {}", e))?;
+
+    let entries: Vec<PathBuf> = std::fs::read_dir(&dest)
+        .map_err(|e| format!("Failed to read extracted workspace: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    match entries.as_slice() {
+        [only] if only.is_dir() && !only.ends_with("threads") => Ok(only.clone()),
+        _ => Ok(dest),
+    }
+}
+
+/// Enter viewer mode if requested. A directory or `.zip` workspace archive given
+/// as the source becomes the data directory. Must run before anything reads it.
+pub fn init(args: &[String]) -> Result<(), String> {
+    let Some(source) = requested_source(args) else {
+        let _ = VIEWER.set(None);
+        return Ok(());
+    };
+
+    if let Some(source) = &source {
+        let path = Path::new(source);
+        let data_dir = if path.is_dir() {
+            path.to_path_buf()
+        } else if path.is_file() {
+            extract_archive(path)?
+        } else {
+            return Err(format!("Workspace not found: {}", source));
+        };
+        std::env::set_var("CHIMERA_DATA_DIR", &data_dir);
+    }
+
+    let data_dir = crate::filesystem::get_data_dir()?;
+    let _ = VIEWER.set(Some(ViewerMode {
+        source,
+        data_dir: data_dir.to_string_lossy().to_string(),
+    }));
+    Ok(())
+}
+
+/// The viewer session, if the app is running as one
+pub fn mode() -> Option<&'static ViewerMode> {
+    VIEWER.get().and_then(|mode| mode.as_ref())
+}
+
+pub fn is_active() -> bool {
+    mode().is_some()
+}
+
+/// Fail `action` in viewer mode
+pub fn ensure_writable(action: &str) -> Result<(), ChimeraError> {
+    if is_active() {
+        return Err(ChimeraError::ReadOnly(format!("Cannot {} in viewer mode", action)));
+    }
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::filesystem;
+
+/// Events a webhook can subscribe to
+pub const EVENT_THREAD_CREATED: &str = "thread.created";
+pub const EVENT_AGENT_FINISHED: &str = "agent.finished";
+pub const EVENT_AGENT_ERROR: &str = "agent.error";
+/// The backend exited or stopped answering health checks
+pub const EVENT_BACKEND_CRASHED: &str = "backend.crashed";
+
+pub(crate) const KNOWN_EVENTS: [&str; 4] = [
+    EVENT_THREAD_CREATED,
+    EVENT_AGENT_FINISHED,
+    EVENT_AGENT_ERROR,
+    EVENT_BACKEND_CRASHED,
+];
+
+/// Maximum delivery attempts per event (first try plus retries)
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Number of delivery records kept in memory
+const DELIVERY_LOG_CAPACITY: usize = 500;
+
+/// A configured webhook endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub id: String,
+    pub url: String,
+    /// Events to deliver; empty means all events
+    pub events: Vec<String>,
+    /// Shared secret used to sign payloads (HMAC-SHA256)
+    pub secret: Option<String>,
+    pub enabled: bool,
+}
+
+/// A webhook as shown to the frontend. The secret is write-only: it's given when
+/// the webhook is added, and only whether one is set is reported back.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookInfo {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub has_secret: bool,
+    pub enabled: bool,
+}
+
+impl From<&WebhookConfig> for WebhookInfo {
+    fn from(hook: &WebhookConfig) -> Self {
+        Self {
+            id: hook.id.clone(),
+            url: hook.url.clone(),
+            events: hook.events.clone(),
+            has_secret: hook.secret.is_some(),
+            enabled: hook.enabled,
+        }
+    }
+}
+
+/// Outcome of a single delivery attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryRecord {
+    pub webhook_id: String,
+    pub event: String,
+    pub url: String,
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub success: bool,
+    pub timestamp: String,
+}
+
+/// Delivers signed JSON payloads to user-configured URLs
+pub struct WebhookManager {
+    hooks: Mutex<Vec<WebhookConfig>>,
+    deliveries: Arc<Mutex<VecDeque<DeliveryRecord>>>,
+    client: reqwest::Client,
+}
+
+/// Get the webhook configuration file path
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(filesystem::get_data_dir()?.join("webhooks.json"))
+}
+
+/// Sign a payload with HMAC-SHA256, returning a lowercase hex digest
+fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl WebhookManager {
+    /// Create a manager, loading saved webhooks from the data directory
+    pub fn load() -> Self {
+        let hooks = get_config_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(Vec::new());
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read webhooks config: {}", e))?;
+                serde_json::from_str(&content).map_err(|e| format!("Failed to parse webhooks config: {}", e))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                Vec::new()
+            });
+
+        log::info!("Loaded {} webhook(s)", hooks.len());
+
+        Self {
+            hooks: Mutex::new(hooks),
+            deliveries: Arc::new(Mutex::new(VecDeque::new())),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Persist the current webhook list
+    fn save(&self, hooks: &[WebhookConfig]) -> Result<(), String> {
+        let path = get_config_path()?;
+        let content = serde_json::to_string_pretty(hooks)
+            .map_err(|e| format!("Failed to serialize webhooks: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write webhooks config: {}", e))
+    }
+
+    /// List configured webhooks
+    pub fn list(&self) -> Vec<WebhookInfo> {
+        self.hooks.lock().unwrap().iter().map(WebhookInfo::from).collect()
+    }
+
+    /// Register a new webhook
+    pub fn add(&self, url: String, events: Vec<String>, secret: Option<String>) -> Result<WebhookInfo, String> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("Webhook URL must be http(s): {}", url));
+        }
+
+        if let Some(unknown) = events.iter().find(|e| !KNOWN_EVENTS.contains(&e.as_str())) {
+            return Err(format!("Unknown webhook event: {}", unknown));
+        }
+
+        let hook = WebhookConfig {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            events,
+            secret,
+            enabled: true,
+        };
+
+        let mut hooks = self.hooks.lock().unwrap();
+        hooks.push(hook.clone());
+        self.save(&hooks)?;
+
+        log::info!("Added webhook {} -> {}", hook.id, hook.url);
+        Ok(WebhookInfo::from(&hook))
+    }
+
+    /// Remove a webhook by id
+    pub fn remove(&self, webhook_id: &str) -> Result<(), String> {
+        let mut hooks = self.hooks.lock().unwrap();
+        let before = hooks.len();
+        hooks.retain(|h| h.id != webhook_id);
+
+        if hooks.len() == before {
+            return Err(format!("Webhook not found: {}", webhook_id));
+        }
+
+        self.save(&hooks)?;
+        log::info!("Removed webhook {}", webhook_id);
+        Ok(())
+    }
+
+    /// Most recent delivery attempts, newest first
+    pub fn deliveries(&self, limit: usize) -> Vec<DeliveryRecord> {
+        self.deliveries.lock().unwrap().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Deliver an event to every matching webhook in the background
+    pub fn dispatch(&self, event: &str, data: serde_json::Value) {
+        let targets: Vec<WebhookConfig> = self
+            .hooks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|h| h.enabled && (h.events.is_empty() || h.events.iter().any(|e| e == event)))
+            .cloned()
+            .collect();
+
+        if targets.is_empty() {
+            return;
+        }
+
+        let payload = serde_json::json!({
+            "event": event,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "data": data,
+        });
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+
+        for hook in targets {
+            let client = self.client.clone();
+            let deliveries = self.deliveries.clone();
+            let event = event.to_string();
+            let body = body.clone();
+
+            tauri::async_runtime::spawn(async move {
+                deliver(client, deliveries, hook, event, body).await;
+            });
+        }
+    }
+}
+
+/// POST a payload with exponential backoff, recording every attempt
+async fn deliver(
+    client: reqwest::Client,
+    deliveries: Arc<Mutex<VecDeque<DeliveryRecord>>>,
+    hook: WebhookConfig,
+    event: String,
+    body: Vec<u8>,
+) {
+    let mut backoff = Duration::from_secs(1);
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(&hook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Chimera-Event", &event)
+            .header("X-Chimera-Delivery-Attempt", attempt.to_string())
+            .body(body.clone());
+
+        if let Some(secret) = &hook.secret {
+            request = request.header("X-Chimera-Signature", format!("sha256={}", sign_payload(secret, &body)));
+        }
+
+        let (status, error) = match request.send().await {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+            Ok(response) => (Some(response.status().as_u16()), Some(format!("HTTP {}", response.status()))),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let success = error.is_none();
+
+        {
+            let mut log = deliveries.lock().unwrap();
+            log.push_back(DeliveryRecord {
+                webhook_id: hook.id.clone(),
+                event: event.clone(),
+                url: hook.url.clone(),
+                attempt,
+                status,
+                error: error.clone(),
+                success,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+            while log.len() > DELIVERY_LOG_CAPACITY {
+                log.pop_front();
+            }
+        }
+
+        if success {
+            log::info!("Delivered {} to webhook {}", event, hook.id);
+            return;
+        }
+
+        log::warn!(
+            "Webhook {} delivery attempt {}/{} failed: {}",
+            hook.id,
+            attempt,
+            MAX_ATTEMPTS,
+            error.unwrap_or_default()
+        );
+
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    log::error!("Giving up on {} delivery to webhook {}", event, hook.id);
+}
@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize};
+
+/// Windows whose size, position and zoom are remembered
+const TRACKED_WINDOWS: [&str; 1] = ["main"];
+
+/// Moves and resizes are written at most this often
+const SAVE_DELAY: Duration = Duration::from_millis(750);
+
+/// How much of a restored window must be on a connected display, so its title
+/// bar can still be grabbed
+const MIN_VISIBLE_WIDTH: i64 = 120;
+const MIN_VISIBLE_HEIGHT: i64 = 48;
+
+/// Monitor layouts remembered per window; the least recently used are dropped
+const MAX_LAYOUTS: usize = 8;
+
+/// Zoom range `set_window_zoom` accepts
+const MIN_ZOOM: f64 = 0.25;
+const MAX_ZOOM: f64 = 5.0;
+
+/// Where a window was, in physical pixels: its outer position and inner size,
+/// which is what `set_position` and `set_size` take
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    #[serde(default = "default_zoom")]
+    zoom: f64,
+    saved_at: String,
+}
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+/// Geometry by window label, then by the fingerprint of the monitors connected
+type SavedStates = BTreeMap<String, BTreeMap<String, WindowGeometry>>;
+
+/// `window-state.json`, beside the settings file: it's about this machine's
+/// displays, not any one workspace
+fn state_path() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home.join("chimera-desktop").join("window-state.json"))
+}
+
+/// Identifies a set of connected monitors by name, resolution and arrangement,
+/// so a laptop remembers one layout docked and another on its own
+fn fingerprint(monitors: &[Monitor]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}:{}x{}@{},{}",
+                monitor.name().map(String::as_str).unwrap_or_default(),
+                monitor.size().width,
+                monitor.size().height,
+                monitor.position().x,
+                monitor.position().y
+            )
+        })
+        .collect();
+    parts.sort();
+    let hash = format!("{:x}", Sha256::digest(parts.join("|").as_bytes()));
+    hash[..16].to_string()
+}
+
+/// Area of `geometry` on `monitor`'s work area
+fn visible_area(geometry: &WindowGeometry, monitor: &Monitor) -> (i64, i64) {
+    let area = monitor.work_area();
+    let (left, top) = (i64::from(area.position.x), i64::from(area.position.y));
+    let (right, bottom) = (left + i64::from(area.size.width), top + i64::from(area.size.height));
+    let width = (i64::from(geometry.x) + i64::from(geometry.width)).min(right) - i64::from(geometry.x).max(left);
+    let height = (i64::from(geometry.y) + i64::from(geometry.height)).min(bottom) - i64::from(geometry.y).max(top);
+    (width.max(0), height.max(0))
+}
+
+/// Fit `geometry` on the displays connected now: on the monitor showing most of
+/// it if enough is visible, else centred on `fallback`, shrunk to fit either way
+fn place(mut geometry: WindowGeometry, monitors: &[Monitor], fallback: Option<&Monitor>) -> Option<WindowGeometry> {
+    let best = monitors
+        .iter()
+        .map(|monitor| (monitor, visible_area(&geometry, monitor)))
+        .max_by_key(|(_, (width, height))| width * height);
+    let (monitor, centre) = match best {
+        Some((monitor, (width, height))) if width >= MIN_VISIBLE_WIDTH && height >= MIN_VISIBLE_HEIGHT => {
+            (monitor, false)
+        }
+        _ => (fallback.or(monitors.first())?, true),
+    };
+
+    let area = monitor.work_area();
+    geometry.width = geometry.width.min(area.size.width);
+    geometry.height = geometry.height.min(area.size.height);
+    if centre {
+        geometry.x = area.position.x + ((area.size.width - geometry.width) / 2) as i32;
+        geometry.y = area.position.y + ((area.size.height - geometry.height) / 2) as i32;
+    }
+    Some(geometry)
+}
+
+/// Saves each window's geometry and zoom per monitor layout as it changes, and
+/// puts windows back where they were at startup
+pub struct WindowStateStore {
+    saved: Mutex<SavedStates>,
+    /// Zoom applied to each window, which the webview can't be asked for
+    zoom: Mutex<BTreeMap<String, f64>>,
+    changed: tokio::sync::Notify,
+}
+
+impl WindowStateStore {
+    pub fn load() -> Self {
+        let saved = state_path()
+            .and_then(|path| match std::fs::read(&path) {
+                Ok(content) => {
+                    serde_json::from_slice(&content).map_err(|e| format!("Failed to parse window state: {}", e))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SavedStates::new()),
+                Err(e) => Err(format!("Failed to read window state: {}", e)),
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                SavedStates::new()
+            });
+        Self {
+            saved: Mutex::new(saved),
+            zoom: Mutex::new(BTreeMap::new()),
+            changed: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// Put tracked windows back where they were with the monitors connected now,
+    /// or where they last were with other monitors, moved onto a display if that
+    /// one's gone
+    pub fn restore(&self, app_handle: &AppHandle) {
+        for label in TRACKED_WINDOWS {
+            let Some(window) = app_handle.get_webview_window(label) else {
+                continue;
+            };
+            let monitors = window.available_monitors().unwrap_or_default();
+            let geometry = {
+                let saved = self.saved.lock().unwrap();
+                let Some(layouts) = saved.get(label) else { continue };
+                layouts
+                    .get(&fingerprint(&monitors))
+                    .or_else(|| layouts.values().max_by(|a, b| a.saved_at.cmp(&b.saved_at)))
+                    .cloned()
+            };
+            let primary = window.primary_monitor().ok().flatten();
+            let Some(geometry) = geometry.and_then(|g| place(g, &monitors, primary.as_ref())) else {
+                continue;
+            };
+
+            let restored = window
+                .set_size(PhysicalSize::new(geometry.width, geometry.height))
+                .and_then(|_| window.set_position(PhysicalPosition::new(geometry.x, geometry.y)))
+                .and_then(|_| if geometry.maximized { window.maximize() } else { Ok(()) });
+            if let Err(e) = restored {
+                log::warn!("Failed to restore the {} window's position: {}", label, e);
+                continue;
+            }
+            if geometry.zoom != 1.0 {
+                match window.set_zoom(geometry.zoom) {
+                    Ok(()) => {
+                        self.zoom.lock().unwrap().insert(label.to_string(), geometry.zoom);
+                    }
+                    Err(e) => log::warn!("Failed to restore the {} window's zoom: {}", label, e),
+                }
+            }
+            log::debug!(
+                "Restored the {} window to {}x{} at {},{}",
+                label,
+                geometry.width,
+                geometry.height,
+                geometry.x,
+                geometry.y
+            );
+        }
+    }
+
+    /// Note a tracked window's geometry after it moved or resized. A maximized
+    /// window keeps the size it had before, to go back to when it's restored.
+    pub fn record(&self, window: &tauri::Window) {
+        if !TRACKED_WINDOWS.contains(&window.label()) {
+            return;
+        }
+        // Minimized windows report a position far off screen on Windows
+        if window.is_minimized().unwrap_or(false) || window.is_fullscreen().unwrap_or(false) {
+            return;
+        }
+        let maximized = window.is_maximized().unwrap_or(false);
+        let bounds = window
+            .outer_position()
+            .and_then(|p| window.inner_size().map(|s| (p, s)));
+        let Ok((position, size)) = bounds else { return };
+        let zoom = self.zoom.lock().unwrap().get(window.label()).copied().unwrap_or(1.0);
+        let layout = fingerprint(&window.available_monitors().unwrap_or_default());
+
+        let mut saved = self.saved.lock().unwrap();
+        let layouts = saved.entry(window.label().to_string()).or_default();
+        let previous = layouts.get(&layout);
+        let geometry = match (maximized, previous) {
+            (true, Some(previous)) => WindowGeometry {
+                maximized,
+                zoom,
+                saved_at: chrono::Utc::now().to_rfc3339(),
+                ..previous.clone()
+            },
+            _ => WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+                zoom,
+                saved_at: chrono::Utc::now().to_rfc3339(),
+            },
+        };
+        layouts.insert(layout, geometry);
+        if layouts.len() > MAX_LAYOUTS {
+            let oldest = layouts
+                .iter()
+                .min_by(|a, b| a.1.saved_at.cmp(&b.1.saved_at))
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                layouts.remove(&oldest);
+            }
+        }
+        drop(saved);
+        self.changed.notify_one();
+    }
+
+    /// Zoom a window's webview and remember it with the window's geometry
+    pub fn set_zoom(&self, window: &tauri::WebviewWindow, zoom: f64) -> Result<(), String> {
+        if !(MIN_ZOOM..=MAX_ZOOM).contains(&zoom) {
+            return Err(format!("Zoom must be between {} and {}", MIN_ZOOM, MAX_ZOOM));
+        }
+        window
+            .set_zoom(zoom)
+            .map_err(|e| format!("Failed to zoom window: {}", e))?;
+        self.zoom.lock().unwrap().insert(window.label().to_string(), zoom);
+        self.record(&window.as_ref().window());
+        Ok(())
+    }
+
+    /// Write the saved states now (blocking)
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_vec_pretty(&*self.saved.lock().unwrap())
+            .map_err(|e| format!("Failed to serialize window state: {}", e))?;
+        let path = state_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, content).map_err(|e| format!("Failed to write window state: {}", e))?;
+        std::fs::rename(&temp, &path).map_err(|e| format!("Failed to replace window state: {}", e))
+    }
+
+    /// Write changes in the background, a moment after they happen rather than
+    /// on every move event
+    pub fn start(self: &Arc<Self>) {
+        let store = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                store.changed.notified().await;
+                tokio::time::sleep(SAVE_DELAY).await;
+                let writer = store.clone();
+                if let Err(e) = crate::filesystem::blocking(move || writer.save()).await {
+                    log::warn!("{}", e);
+                }
+            }
+        });
+    }
+}
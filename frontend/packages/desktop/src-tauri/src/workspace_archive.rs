@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::tasks::TaskProgress;
+use crate::{blob_store, filesystem, thread_compression};
+
+/// `format` of a workspace archive's manifest
+const FORMAT: &str = "chimera-workspace";
+const VERSION: u32 = 1;
+
+/// Settings that describe this machine rather than the user's preferences
+const MACHINE_SETTINGS: [&str; 2] = ["data_dir", "backend_port"];
+
+/// `manifest.json` at the root of a workspace archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    created_at: String,
+    app_version: String,
+    threads: Vec<String>,
+    blueprints: Vec<String>,
+    blobs: usize,
+    settings: bool,
+}
+
+/// What to do with a thread or blueprint whose id already exists here
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Import it under a new id, keeping both
+    #[default]
+    KeepBoth,
+    /// Keep the local copy
+    Skip,
+    /// Replace the local copy. Settings are only imported with this strategy.
+    Overwrite,
+}
+
+/// Result of `export`
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceExport {
+    pub path: String,
+    pub bytes: u64,
+    pub threads: usize,
+    pub blueprints: usize,
+    pub blobs: usize,
+}
+
+/// Result of `import`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkspaceImport {
+    /// Thread ids as imported
+    pub threads: Vec<String>,
+    pub blueprints: Vec<String>,
+    /// Archive ids that were taken here and got new ones, old to new
+    pub renamed: BTreeMap<String, String>,
+    pub skipped: Vec<String>,
+    pub blobs: usize,
+    /// Settings to apply, without machine-specific fields. Only set for `overwrite`.
+    #[serde(skip)]
+    pub settings: Option<serde_json::Value>,
+}
+
+fn zip_err(e: zip::result::ZipError) -> String {
+    format!("Failed to write workspace archive: {}", e)
+}
+
+fn io_err(e: std::io::Error) -> String {
+    format!("Failed to write workspace archive: {}", e)
+}
+
+/// Files in `dir` with `extension`, by id (the file stem)
+fn list_files(dir: &Path, extension: &str) -> Result<Vec<(String, std::path::PathBuf)>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            (!id.starts_with('.')).then_some((id, path))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Thread ids and files, compressed or not, sorted by id (blocking)
+fn thread_files() -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files: Vec<_> = filesystem::list_listed_thread_files()?
+        .into_iter()
+        .filter_map(|path| {
+            let id = thread_compression::thread_file_id(&path)?.to_string();
+            (!id.starts_with('.')).then_some((id, path))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Package every thread, blueprint and blob, and `settings`, into a zip at
+/// `dest_zip`. Callers should flush buffered appends first. A cancelled task
+/// stops the export and leaves nothing at `dest_zip` (blocking).
+pub fn export(
+    dest_zip: &Path,
+    settings: Option<serde_json::Value>,
+    progress: &TaskProgress,
+) -> Result<WorkspaceExport, String> {
+    let threads = thread_files()?;
+    let blueprints = list_files(&filesystem::get_blueprints_dir()?, "json")?;
+    let blobs = list_files(&blob_store::get_blobs_dir()?, "json")?;
+
+    // Written beside the destination and renamed, so a failed export leaves nothing
+    let temp = dest_zip.with_extension("zip.tmp");
+    let file = std::fs::File::create(&temp).map_err(|e| format!("Failed to create workspace archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let settings = settings.map(|mut settings| {
+        if let Some(obj) = settings.as_object_mut() {
+            for key in MACHINE_SETTINGS {
+                obj.remove(key);
+            }
+        }
+        settings
+    });
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        threads: threads.iter().map(|(id, _)| id.clone()).collect(),
+        blueprints: blueprints.iter().map(|(id, _)| id.clone()).collect(),
+        blobs: blobs.len(),
+        settings: settings.is_some(),
+    };
+    zip.start_file("manifest.json", options).map_err(zip_err)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)
+        .map_err(io_err)?;
+
+    let groups = [("threads", &threads, "jsonl"), ("blueprints", &blueprints, "json"), ("blobs", &blobs, "json")];
+    let total = threads.len() + blueprints.len() + blobs.len();
+    let mut done = 0;
+    for (dir, files, extension) in groups {
+        for (id, path) in files {
+            if let Err(e) = progress.check() {
+                drop(zip);
+                let _ = std::fs::remove_file(&temp);
+                return Err(e);
+            }
+            progress.report(done, total, format!("{}/{}", dir, id));
+            done += 1;
+            // Compressed threads go in as the JSONL they hold
+            let content = thread_compression::read_thread_file(path)?;
+            zip.start_file(format!("{}/{}.{}", dir, id, extension), options).map_err(zip_err)?;
+            zip.write_all(&content).map_err(io_err)?;
+        }
+    }
+    if let Some(settings) = &settings {
+        zip.start_file("settings.json", options).map_err(zip_err)?;
+        zip.write_all(&serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?)
+            .map_err(io_err)?;
+    }
+    zip.finish().map_err(zip_err)?;
+    std::fs::rename(&temp, dest_zip).map_err(|e| format!("Failed to write workspace archive: {}", e))?;
+
+    let bytes = std::fs::metadata(dest_zip).map(|m| m.len()).unwrap_or(0);
+    log::info!(
+        "Exported workspace to {} ({} threads, {} blueprints, {} blobs)",
+        dest_zip.display(),
+        threads.len(),
+        blueprints.len(),
+        blobs.len()
+    );
+    Ok(WorkspaceExport {
+        path: dest_zip.to_string_lossy().into_owned(),
+        bytes,
+        threads: threads.len(),
+        blueprints: blueprints.len(),
+        blobs: blobs.len(),
+    })
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let mut content = Vec::new();
+    archive
+        .by_name(name)
+        .map_err(|e| format!("Workspace archive is missing {}: {}", name, e))?
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read {} from workspace archive: {}", name, e))?;
+    Ok(content)
+}
+
+/// Ids name files, so anything that could leave the target directory is rejected
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && !id.starts_with('.')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Workspace archive has an invalid id: {}", id))
+    }
+}
+
+/// Check every line of a thread parses and the header is an object
+fn parse_thread(id: &str, content: &[u8]) -> Result<Vec<serde_json::Value>, String> {
+    let events: Vec<serde_json::Value> = content
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(serde_json::from_slice)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Thread {} in the archive is damaged: {}", id, e))?;
+    if !events.first().is_some_and(|header| header.is_object()) {
+        return Err(format!("Thread {} in the archive has no blueprint line", id));
+    }
+    Ok(events)
+}
+
+/// The first free `<id>-imported`, `<id>-imported-2`, ... blueprint id
+fn free_blueprint_id(id: &str) -> Result<String, String> {
+    let dir = filesystem::get_blueprints_dir()?;
+    (1..)
+        .map(|n| if n == 1 { format!("{}-imported", id) } else { format!("{}-imported-{}", id, n) })
+        .find(|candidate| !dir.join(format!("{}.json", candidate)).exists())
+        .ok_or_else(|| "No free blueprint id".to_string())
+}
+
+/// Add a workspace archive's contents to this data directory. Everything is read
+/// and validated before anything is written. Callers must keep appends out of
+/// threads that may be overwritten (blocking).
+pub fn import(src_zip: &Path, strategy: MergeStrategy) -> Result<WorkspaceImport, String> {
+    let file = std::fs::File::open(src_zip).map_err(|e| format!("Failed to open workspace archive: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read workspace archive: {}", e))?;
+
+    let manifest: Manifest = serde_json::from_slice(&read_entry(&mut archive, "manifest.json")?)
+        .map_err(|_| "Not a Chimera workspace archive".to_string())?;
+    if manifest.format != FORMAT {
+        return Err("Not a Chimera workspace archive".to_string());
+    }
+    if manifest.version > VERSION {
+        return Err(format!("Workspace archive version {} needs a newer Chimera", manifest.version));
+    }
+
+    let mut threads = Vec::new();
+    for id in &manifest.threads {
+        validate_id(id)?;
+        let content = read_entry(&mut archive, &format!("threads/{}.jsonl", id))?;
+        threads.push((id.clone(), parse_thread(id, &content)?, content));
+    }
+    let mut blueprints = Vec::new();
+    for id in &manifest.blueprints {
+        validate_id(id)?;
+        let content = read_entry(&mut archive, &format!("blueprints/{}.json", id))?;
+        let blueprint: serde_json::Value = serde_json::from_slice(&content)
+            .map_err(|e| format!("Blueprint {} in the archive is damaged: {}", id, e))?;
+        crate::blueprint_schema::validate(&blueprint).map_err(|e| format!("Blueprint {} in the archive: {}", id, e))?;
+        blueprints.push((id.clone(), content));
+    }
+    let mut blobs = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| format!("Failed to read workspace archive: {}", e))?;
+        if !entry.name().starts_with("blobs/") || entry.is_dir() {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Failed to read blob from workspace archive: {}", e))?;
+        blobs.push(content);
+    }
+    let settings = if manifest.settings {
+        let settings: serde_json::Value = serde_json::from_slice(&read_entry(&mut archive, "settings.json")?)
+            .map_err(|e| format!("Settings in the archive are damaged: {}", e))?;
+        Some(settings)
+    } else {
+        None
+    };
+
+    let mut report = WorkspaceImport::default();
+
+    // Blobs first, so threads never refer to one that isn't there
+    for blob in &blobs {
+        if blob_store::put_bytes(blob)?.1 {
+            report.blobs += 1;
+        }
+    }
+
+    for (id, mut events, content) in threads {
+        let (thread_id, content) = match (thread_compression::thread_exists(&id)?, strategy) {
+            (false, _) | (true, MergeStrategy::Overwrite) => (id, content),
+            (true, MergeStrategy::Skip) => {
+                report.skipped.push(id);
+                continue;
+            }
+            (true, MergeStrategy::KeepBoth) => {
+                let new_id = uuid::Uuid::new_v4().to_string();
+                if let Some(header) = events[0].as_object_mut() {
+                    header.insert("thread_id".to_string(), serde_json::Value::String(new_id.clone()));
+                }
+                let mut lines = Vec::with_capacity(content.len());
+                for event in &events {
+                    filesystem::serialize_event_line(event, &mut lines)?;
+                }
+                report.renamed.insert(id, new_id.clone());
+                (new_id, lines)
+            }
+        };
+        filesystem::replace_thread_file(&filesystem::get_thread_path(&thread_id)?, &content)?;
+        report.threads.push(thread_id);
+    }
+
+    let blueprints_dir = filesystem::get_blueprints_dir()?;
+    std::fs::create_dir_all(&blueprints_dir).map_err(|e| format!("Failed to create blueprints directory: {}", e))?;
+    for (id, content) in blueprints {
+        let exists = blueprints_dir.join(format!("{}.json", id)).exists();
+        let blueprint_id = match (exists, strategy) {
+            (false, _) | (true, MergeStrategy::Overwrite) => id,
+            (true, MergeStrategy::Skip) => {
+                report.skipped.push(id);
+                continue;
+            }
+            (true, MergeStrategy::KeepBoth) => {
+                let new_id = free_blueprint_id(&id)?;
+                report.renamed.insert(id, new_id.clone());
+                new_id
+            }
+        };
+        let path = blueprints_dir.join(format!("{}.json", blueprint_id));
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, &content)
+            .and_then(|_| std::fs::rename(&temp, &path))
+            .map_err(|e| format!("Failed to write blueprint {}: {}", blueprint_id, e))?;
+        report.blueprints.push(blueprint_id);
+    }
+
+    if strategy == MergeStrategy::Overwrite {
+        report.settings = settings.map(|mut settings| {
+            if let Some(obj) = settings.as_object_mut() {
+                for key in MACHINE_SETTINGS {
+                    obj.remove(key);
+                }
+                obj.remove("version");
+            }
+            settings
+        });
+    }
+
+    log::info!(
+        "Imported workspace from {} ({} threads, {} blueprints, {} new blobs, {} renamed, {} skipped)",
+        src_zip.display(),
+        report.threads.len(),
+        report.blueprints.len(),
+        report.blobs,
+        report.renamed.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
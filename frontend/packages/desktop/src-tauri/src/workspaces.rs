@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use crate::error::ChimeraError;
+
+/// Id of the workspace every install starts with
+pub const DEFAULT_ID: &str = "default";
+
+/// Data directory of the active workspace; None until `init`, and for the default workspace
+static ACTIVE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Where the default workspace lives, from the `data_dir` setting
+static DEFAULT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Held while the registry file is read, changed and written back
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+/// An independent data root with its own threads, blueprints and attachments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    /// None in the registry for the default workspace, which follows the
+    /// `data_dir` setting; always filled in when listed
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    pub created_at: String,
+}
+
+/// The registry saved as workspaces.json, next to settings.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Registry {
+    active: String,
+    workspaces: Vec<Workspace>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_ID.to_string(),
+            workspaces: vec![Workspace {
+                id: DEFAULT_ID.to_string(),
+                name: "Default".to_string(),
+                data_dir: None,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+impl Registry {
+    fn get(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|workspace| workspace.id == id)
+    }
+}
+
+/// Workspaces for the switcher
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceList {
+    pub active: String,
+    /// The directory `CHIMERA_DATA_DIR` fixes the app to, in which case
+    /// switching workspaces has no effect
+    pub pinned: Option<String>,
+    pub workspaces: Vec<Workspace>,
+}
+
+/// Payload of `workspace-changed`, also returned by `switch_workspace`
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceChanged {
+    pub workspace: Workspace,
+    /// Data directory of the workspace switched away from
+    pub previous_data_dir: String,
+    /// Whether anything still reads the previous workspace until the app is
+    /// restarted, e.g. search with SQLite storage
+    pub restart_required: bool,
+}
+
+fn get_registry_path() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home.join("chimera-desktop").join("workspaces.json"))
+}
+
+fn read_registry() -> Registry {
+    let registry = get_registry_path()
+        .and_then(|path| {
+            if !path.exists() {
+                return Ok(Registry::default());
+            }
+            let content =
+                std::fs::read_to_string(&path).map_err(|e| format!("Failed to read workspaces: {}", e))?;
+            serde_json::from_str::<Registry>(&content).map_err(|e| format!("Failed to parse workspaces: {}", e))
+        })
+        .unwrap_or_else(|e| {
+            log::warn!("{}; using the default workspace", e);
+            Registry::default()
+        });
+    repair(registry)
+}
+
+/// Put back the default workspace and point `active` at one that exists
+fn repair(mut registry: Registry) -> Registry {
+    if registry.get(DEFAULT_ID).is_none() {
+        registry.workspaces.insert(0, Registry::default().workspaces.remove(0));
+    }
+    if registry.get(&registry.active).is_none() {
+        log::warn!("Active workspace {} is missing; using the default workspace", registry.active);
+        registry.active = DEFAULT_ID.to_string();
+    }
+    registry
+}
+
+fn write_registry(registry: &Registry) -> Result<(), String> {
+    let path = get_registry_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create workspaces directory: {}", e))?;
+    }
+    let content =
+        serde_json::to_string_pretty(registry).map_err(|e| format!("Failed to serialize workspaces: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write workspaces: {}", e))
+}
+
+/// The default workspace's data directory: the `data_dir` setting, or ~/chimera-desktop
+fn default_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = DEFAULT_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home.join("chimera-desktop"))
+}
+
+fn resolve(workspace: &Workspace) -> Result<PathBuf, String> {
+    match &workspace.data_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => default_dir(),
+    }
+}
+
+/// Pick up the active workspace at launch. `default_dir` is the `data_dir`
+/// setting, which moves the default workspace.
+pub fn init(default_dir: Option<&str>) {
+    if let Some(dir) = default_dir {
+        let _ = DEFAULT_DIR.set(PathBuf::from(dir));
+    }
+    if pinned().is_some() {
+        return;
+    }
+    let registry = read_registry();
+    if let Some(workspace) = registry.get(&registry.active).filter(|w| w.id != DEFAULT_ID) {
+        match resolve(workspace) {
+            Ok(dir) => {
+                log::info!("Using workspace {} at {}", workspace.name, dir.display());
+                *ACTIVE_DIR.write().unwrap() = Some(dir);
+            }
+            Err(e) => log::warn!("Failed to resolve workspace {}: {}", workspace.id, e),
+        }
+    }
+}
+
+/// The data directory `CHIMERA_DATA_DIR` fixes the app to, e.g. in viewer mode
+pub fn pinned() -> Option<PathBuf> {
+    std::env::var_os("CHIMERA_DATA_DIR").map(PathBuf::from)
+}
+
+/// The active workspace's data directory
+pub fn active_dir() -> Result<PathBuf, String> {
+    match ACTIVE_DIR.read().unwrap().as_ref() {
+        Some(dir) => Ok(dir.clone()),
+        None => default_dir(),
+    }
+}
+
+/// All workspaces, with their data directories filled in
+pub fn list() -> Result<WorkspaceList, ChimeraError> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let registry = read_registry();
+    let workspaces = registry
+        .workspaces
+        .iter()
+        .map(|workspace| {
+            Ok(Workspace {
+                data_dir: Some(resolve(workspace)?.to_string_lossy().to_string()),
+                ..workspace.clone()
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(WorkspaceList {
+        active: registry.active,
+        pinned: pinned().map(|dir| dir.to_string_lossy().to_string()),
+        workspaces,
+    })
+}
+
+/// Lowercase letters, digits and dashes from `name`, for the workspace id
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').chars().take(40).collect()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Add a workspace named `name`, in `data_dir` if given (it may already hold
+/// threads and blueprints) or a new directory under ~/chimera-desktop/workspaces
+pub fn create(name: &str, data_dir: Option<&str>) -> Result<Workspace, ChimeraError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ChimeraError::InvalidInput("Workspace name is empty".to_string()));
+    }
+
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = read_registry();
+    if registry.workspaces.iter().any(|w| w.name.eq_ignore_ascii_case(name)) {
+        return Err(ChimeraError::InvalidInput(format!("A workspace named {} already exists", name)));
+    }
+
+    let base = match slug(name) {
+        slug if slug.is_empty() => uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+        slug => slug,
+    };
+    let id = (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) })
+        .find(|id| registry.get(id).is_none())
+        .expect("some suffix is free");
+
+    let dir = match data_dir {
+        Some(dir) if !Path::new(dir).is_absolute() => {
+            return Err(ChimeraError::InvalidInput(format!("Workspace directory must be absolute: {}", dir)));
+        }
+        Some(dir) => PathBuf::from(dir),
+        None => default_dir()?.join("workspaces").join(&id),
+    };
+    for existing in &registry.workspaces {
+        if same_dir(&resolve(existing)?, &dir) {
+            return Err(ChimeraError::InvalidInput(format!(
+                "{} is already the data directory of workspace {}",
+                dir.display(),
+                existing.name
+            )));
+        }
+    }
+    for sub in ["threads", "blueprints"] {
+        std::fs::create_dir_all(dir.join(sub)).map_err(|e| crate::filesystem::FsError::io("create workspace", e))?;
+    }
+
+    let workspace = Workspace {
+        id,
+        name: name.to_string(),
+        data_dir: Some(dir.to_string_lossy().to_string()),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    registry.workspaces.push(workspace.clone());
+    write_registry(&registry)?;
+    log::info!("Created workspace {} at {}", workspace.name, dir.display());
+    Ok(workspace)
+}
+
+/// Make workspace `id` the active one, saved for the next launch. Returns it
+/// with its data directory filled in.
+pub fn switch(id: &str) -> Result<Workspace, ChimeraError> {
+    if let Some(dir) = pinned() {
+        return Err(ChimeraError::InvalidInput(format!(
+            "The data directory is fixed to {} by CHIMERA_DATA_DIR",
+            dir.display()
+        )));
+    }
+
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = read_registry();
+    let workspace = registry
+        .get(id)
+        .cloned()
+        .ok_or_else(|| ChimeraError::NotFound(format!("Workspace not found: {}", id)))?;
+    let dir = resolve(&workspace)?;
+
+    registry.active = workspace.id.clone();
+    write_registry(&registry)?;
+    *ACTIVE_DIR.write().unwrap() = (workspace.id != DEFAULT_ID).then(|| dir.clone());
+    log::info!("Switched to workspace {} at {}", workspace.name, dir.display());
+
+    Ok(Workspace {
+        data_dir: Some(dir.to_string_lossy().to_string()),
+        ..workspace
+    })
+}
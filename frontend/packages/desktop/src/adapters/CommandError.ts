@@ -0,0 +1,44 @@
+/**
+ * Error shape every Tauri command rejects with (`ChimeraError` in Rust).
+ * Match on `code`; `message` is for display.
+ *
+ * - `not_found`: a thread, blueprint, terminal or other item doesn't exist
+ * - `invalid_input`: bad arguments, or content that doesn't parse
+ * - `permission_denied`: needs approval, or the OS refused access
+ * - `read_only`: viewer mode, or a data directory on read-only media
+ * - `disk_full`: no space left for the data directory
+ * - `unreachable`: the data directory's drive isn't responding
+ * - `io`: any other filesystem failure
+ * - `backend_unavailable`: the Python backend isn't running or didn't answer
+ * - `terminal`: a terminal couldn't be started or written to
+ * - `cancelled`: the operation was cancelled, e.g. with `cancel_task`
+ * - `internal`: anything else
+ */
+export type CommandErrorCode =
+  | "not_found"
+  | "invalid_input"
+  | "permission_denied"
+  | "read_only"
+  | "disk_full"
+  | "unreachable"
+  | "io"
+  | "backend_unavailable"
+  | "terminal"
+  | "cancelled"
+  | "internal";
+
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+  /** Filesystem errors carry `{ kind, hint }`; null otherwise */
+  detail: { kind?: string; hint?: string | null } | null;
+}
+
+export function isCommandError(error: unknown): error is CommandError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as CommandError).code === "string" &&
+    typeof (error as CommandError).message === "string"
+  );
+}
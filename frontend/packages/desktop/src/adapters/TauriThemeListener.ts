@@ -3,7 +3,8 @@ import type { ThemeEventListener } from "@chimera/platform";
 
 /**
  * Tauri implementation of ThemeEventListener
- * Listens to OS theme changes via Tauri events
+ * Listens to theme changes via Tauri events: the OS theme, or the one chosen
+ * with the set_theme command, which wins over it
  */
 export class TauriThemeListener implements ThemeEventListener {
   listen(callback: (theme: "light" | "dark") => void): () => void {
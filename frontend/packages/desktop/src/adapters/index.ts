@@ -1,3 +1,5 @@
 export { TauriStorageAdapter } from "./TauriStorageAdapter";
 export { TauriConfigProvider } from "./TauriConfigProvider";
 export { TauriThemeListener } from "./TauriThemeListener";
+export { isCommandError } from "./CommandError";
+export type { CommandError, CommandErrorCode } from "./CommandError";
@@ -32,6 +32,8 @@ export interface BlueprintMetadata {
 export interface ThreadProtocolEvent {
   type: string;
   timestamp?: string;
+  /** Idempotency key: an event whose id was already appended is not written again */
+  event_id?: string;
   // eslint-disable-next-line @typescript-eslint/no-explicit-any -- VSP events are extensible
   [key: string]: any;
 }
@@ -4,9 +4,11 @@ Provides streaming chat endpoint using Vercel AI SDK Stream Protocol (VSP).
 """
 
 import asyncio
+import importlib.metadata
 import json
 import logging
 import os
+import signal
 import sys
 import threading
 from contextlib import asynccontextmanager
@@ -15,7 +17,7 @@ from pathlib import Path
 from typing import Set
 
 from dotenv import load_dotenv
-from fastapi import FastAPI, HTTPException, Request
+from fastapi import FastAPI, HTTPException, Request, WebSocket, WebSocketDisconnect
 from fastapi.exceptions import RequestValidationError
 from fastapi.middleware.cors import CORSMiddleware
 from fastapi.responses import JSONResponse, StreamingResponse
@@ -42,6 +44,9 @@ logger = logging.getLogger(__name__)
 # Registry for background tasks to prevent GC and enable clean shutdown
 _background_tasks: Set[asyncio.Task] = set()
 
+# Set by /shutdown; new runs are refused while active ones finish
+_shutting_down = False
+
 
 def _create_background_task(coro, name: str = None) -> asyncio.Task:
     """Create a background task with proper lifecycle management.
@@ -221,12 +226,26 @@ class StreamRequest(BaseModel):
         arbitrary_types_allowed = True
 
 
+def _package_version() -> str | None:
+    """Installed chimera-api version, reported so threads can record what produced them."""
+    try:
+        return importlib.metadata.version("chimera-api")
+    except importlib.metadata.PackageNotFoundError:
+        return None
+
+
 @app.get("/")
 async def root():
     """Health check endpoint."""
     return {"status": "ok", "message": "Chimera v4 backend is running"}
 
 
+@app.get("/health")
+async def health():
+    """Liveness probe polled by the desktop app's backend supervisor."""
+    return {"status": "ok", "version": _package_version()}
+
+
 @app.post("/debug")
 async def debug_endpoint(request: Request):
     """Raw debug endpoint to see what's actually being sent."""
@@ -295,6 +314,84 @@ async def halt_execution(request: HaltRequest):
         }
 
 
+class ShutdownRequest(BaseModel):
+    """Request model for /shutdown endpoint."""
+
+    timeout: float = Field(30.0, ge=0, description="Seconds to wait for active runs before exiting")
+
+
+async def _drain_and_exit(timeout: float):
+    """Wait for active runs to finish (up to timeout), then stop the server."""
+    from .stream_handler import task_registry
+
+    deadline = asyncio.get_running_loop().time() + timeout
+    while await task_registry.active_count() > 0:
+        if asyncio.get_running_loop().time() >= deadline:
+            logger.warning("[SHUTDOWN] Timed out waiting for active runs")
+            break
+        await asyncio.sleep(0.2)
+
+    logger.info("[SHUTDOWN] Exiting")
+    # Uvicorn handles SIGTERM as a graceful stop, running the lifespan shutdown
+    os.kill(os.getpid(), signal.SIGTERM)
+
+
+@app.post("/shutdown")
+async def shutdown(request: ShutdownRequest | None = None):
+    """
+    Stop the server once active runs finish.
+
+    The desktop app calls this before signaling the process, so in-flight model
+    calls complete and threads aren't left half-written. New runs are refused
+    from now on. Returns immediately with the number of runs still active.
+    """
+    global _shutting_down
+    from .stream_handler import task_registry
+
+    timeout = request.timeout if request else 30.0
+    active = await task_registry.active_count()
+    if not _shutting_down:
+        _shutting_down = True
+        logger.info(f"[SHUTDOWN] Requested; waiting for {active} active runs")
+        _create_background_task(_drain_and_exit(timeout), name="shutdown")
+
+    return {"status": "shutting_down", "active_runs": active}
+
+
+@app.websocket("/events")
+async def events(websocket: WebSocket):
+    """
+    Push every streamed event to the client as `{"threadId", "event"}` JSON.
+
+    The desktop app keeps this open so the UI gets run output without polling.
+    Nothing is replayed on connect; clients reconnect after a restart and see
+    events from then on.
+    """
+    from .stream_handler import event_hub
+
+    await websocket.accept()
+    queue = event_hub.subscribe()
+    # Notice disconnects even while no events are flowing
+    receiver = asyncio.create_task(websocket.receive())
+    try:
+        while True:
+            getter = asyncio.create_task(queue.get())
+            done, _ = await asyncio.wait({getter, receiver}, return_when=asyncio.FIRST_COMPLETED)
+            if getter in done:
+                await websocket.send_json(getter.result())
+            else:
+                getter.cancel()
+            if receiver in done:
+                if receiver.result()["type"] == "websocket.disconnect":
+                    break
+                receiver = asyncio.create_task(websocket.receive())
+    except WebSocketDisconnect:
+        pass
+    finally:
+        receiver.cancel()
+        event_hub.unsubscribe(queue)
+
+
 @app.post("/stream")
 async def stream_chat(stream_request: StreamRequest, raw_request: Request):
     """
@@ -303,6 +400,9 @@ async def stream_chat(stream_request: StreamRequest, raw_request: Request):
 
     Expects ThreadProtocol JSONL from client (stateless paradigm).
     """
+    if _shutting_down:
+        raise HTTPException(status_code=503, detail="Backend is shutting down")
+
     # TEMPORARY LOGGING - Dump entire request to temp_requests.log
     temp_log_path = Path(__file__).parent / "temp_requests.log"
 
@@ -740,6 +840,9 @@ if __name__ == "__main__":
     parser.add_argument(
         "--port", type=int, default=33003, help="Port to run the server on (default: 33003)"
     )
+    parser.add_argument(
+        "--host", default="0.0.0.0", help="Interface to bind (default: 0.0.0.0)"
+    )
     parser.add_argument(
         "--embedded",
         action="store_true",
@@ -752,4 +855,4 @@ if __name__ == "__main__":
     if args.embedded:
         logger.info("Running in embedded desktop mode")
 
-    uvicorn.run(app, host="0.0.0.0", port=args.port)
+    uvicorn.run(app, host=args.host, port=args.port)
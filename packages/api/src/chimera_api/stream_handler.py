@@ -15,7 +15,7 @@ import asyncio
 import json
 import logging
 import uuid
-from typing import AsyncIterator, Dict, Optional
+from typing import AsyncIterator, Dict, Optional, Set
 
 from chimera_core.thread import ThreadDeps, run_thread
 from chimera_core.threadprotocol.writer import NoOpThreadProtocolWriter
@@ -71,6 +71,11 @@ class ActiveTaskRegistry:
         async with self._lock:
             self._tasks.pop(task_id, None)
 
+    async def active_count(self) -> int:
+        """Number of tasks still running."""
+        async with self._lock:
+            return sum(1 for task in self._tasks.values() if not task.done())
+
     async def cleanup_done_tasks(self):
         """Remove completed tasks from registry."""
         async with self._lock:
@@ -83,6 +88,38 @@ class ActiveTaskRegistry:
 task_registry = ActiveTaskRegistry()
 
 
+class EventHub:
+    """Fan-out of streamed events to /events WebSocket subscribers.
+
+    Each subscriber gets its own bounded queue; a subscriber that falls behind
+    loses events rather than slowing down the run.
+    """
+
+    def __init__(self, max_queue: int = 1000):
+        self._subscribers: Set[asyncio.Queue] = set()
+        self._max_queue = max_queue
+
+    def subscribe(self) -> asyncio.Queue:
+        queue: asyncio.Queue = asyncio.Queue(maxsize=self._max_queue)
+        self._subscribers.add(queue)
+        return queue
+
+    def unsubscribe(self, queue: asyncio.Queue):
+        self._subscribers.discard(queue)
+
+    def publish(self, thread_id: str, event: dict):
+        """Send an event to every subscriber, without waiting."""
+        for queue in self._subscribers:
+            try:
+                queue.put_nowait({"threadId": thread_id, "event": event})
+            except asyncio.QueueFull:
+                logger.warning("Event subscriber is falling behind; dropping event")
+
+
+# Global hub for pushing events to desktop clients
+event_hub = EventHub()
+
+
 # NOTE: Event emission factories (create_emit_vsp_event, create_emit_threadprotocol_event)
 # have been moved to core/ui/streaming_infrastructure.py per Issue #38.
 # The StreamingInfrastructure class now handles:
@@ -312,7 +349,8 @@ async def generate_vsp_events(
             if event is None:
                 break
 
-            # Yield raw event dict
+            # Push to socket subscribers, then yield raw event dict
+            event_hub.publish(thread_id, event)
             yield event
 
     finally: